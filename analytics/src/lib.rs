@@ -0,0 +1,193 @@
+// Symmetric‑Solana ─ Analytics
+// ================================================================
+// Pool statistics for a stats API, from decoded pool events and
+// historical pool snapshots:
+//   • `Analytics` keeps each pool's swaps, joins, exits and snapshots
+//     by block time;
+//   • `Prices` values raw token amounts in a quote currency (USD);
+//   • `stats` computes 24h and 7d volume, fees earned (LP and
+//     protocol), liquidity utilization and LP APR per pool, as
+//     serializable structs.
+// A swap's fees are the event's swap fee amount and protocol fee, in
+// the token in. Amounts of tokens without a price are counted but not
+// valued.
+// ================================================================
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+use events::{Event, EventRecord, SwapEvent};
+use quoter::PoolState;
+
+pub mod stats;
+
+pub use stats::{PoolStats, WindowStats, DAY, WEEK};
+
+/// A pool's balances and parameters at a block time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolSnapshot {
+    /// Unix seconds
+    pub time: i64,
+    /// Token mints, in pool order
+    pub tokens: Vec<Pubkey>,
+    /// Balance of each token, asset‑managed part included
+    pub balances: Vec<u64>,
+    /// 18‑dec fraction of each swap's amount in
+    pub swap_fee: u64,
+    pub total_bpt: u64,
+}
+
+impl PoolSnapshot {
+    /// The snapshot of a quoter pool state read at `time`.
+    pub fn from_state(time: i64, state: &PoolState) -> Self {
+        let (cash, managed, swap_fee, total_bpt) = match state {
+            PoolState::Weighted(s) => (&s.cash, &s.managed, s.pool.swap_fee, s.pool.total_bpt),
+            PoolState::Stable(s) => (&s.cash, &s.managed, s.pool.swap_fee, s.pool.total_bpt),
+        };
+        PoolSnapshot {
+            time,
+            tokens: state.tokens().to_vec(),
+            balances: cash.iter().zip(managed).map(|(c, m)| c.saturating_add(*m)).collect(),
+            swap_fee,
+            total_bpt,
+        }
+    }
+}
+
+/// A token's price in the quote currency.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenPrice {
+    pub decimals: u8,
+    /// Per whole token
+    pub price: f64,
+}
+
+/// Token prices for valuing raw amounts.
+#[derive(Clone, Debug, Default)]
+pub struct Prices(pub HashMap<Pubkey, TokenPrice>);
+
+impl Prices {
+    pub fn set(&mut self, mint: Pubkey, decimals: u8, price: f64) {
+        self.0.insert(mint, TokenPrice { decimals, price });
+    }
+
+    /// Value of `amount` raw units of `mint`; `None` without a price.
+    pub fn value(&self, mint: &Pubkey, amount: u64) -> Option<f64> {
+        self.0.get(mint).map(|p| amount as f64 / 10f64.powi(p.decimals as i32) * p.price)
+    }
+
+    /// Value of a snapshot's balances, skipping unpriced tokens.
+    pub fn tvl(&self, snapshot: &PoolSnapshot) -> f64 {
+        snapshot.tokens.iter().zip(&snapshot.balances).filter_map(|(mint, b)| self.value(mint, *b)).sum()
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PoolHistory {
+    /// (block time, swap)
+    pub swaps: Vec<(i64, SwapEvent)>,
+    pub joins: Vec<i64>,
+    pub exits: Vec<i64>,
+    /// In time order
+    pub snapshots: Vec<PoolSnapshot>,
+}
+
+impl PoolHistory {
+    /// The snapshot in effect at `time`: the latest at or before it, or
+    /// the earliest when all are later.
+    pub fn snapshot_at(&self, time: i64) -> Option<&PoolSnapshot> {
+        match self.snapshots.partition_point(|s| s.time <= time) {
+            0 => self.snapshots.first(),
+            n => Some(&self.snapshots[n - 1]),
+        }
+    }
+}
+
+/// Events and snapshots of every pool seen, by block time.
+#[derive(Clone, Debug, Default)]
+pub struct Analytics {
+    pub(crate) pools: HashMap<Pubkey, PoolHistory>,
+}
+
+impl Analytics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a decoded event of a block at `block_time`; events other than
+    /// pool swaps, joins and exits are ignored.
+    pub fn record(&mut self, block_time: i64, record: &EventRecord) {
+        if !matches!(record.event, Event::Swap(_) | Event::Join(_) | Event::Exit(_)) {
+            return;
+        }
+        let Some(pool) = record.event.pool() else { return };
+        let history = self.pools.entry(pool).or_default();
+        match &record.event {
+            Event::Swap(swap) => history.swaps.push((block_time, swap.clone())),
+            Event::Join(_) => history.joins.push(block_time),
+            Event::Exit(_) => history.exits.push(block_time),
+            _ => {}
+        }
+    }
+
+    /// Add a snapshot of `pool`, in any order.
+    pub fn snapshot(&mut self, pool: Pubkey, snapshot: PoolSnapshot) {
+        let snapshots = &mut self.pools.entry(pool).or_default().snapshots;
+        let at = snapshots.partition_point(|s| s.time <= snapshot.time);
+        snapshots.insert(at, snapshot);
+    }
+
+    /// Drop what no window ending at or after `now` uses: events older
+    /// than a week, and snapshots superseded before then.
+    pub fn prune(&mut self, now: i64) {
+        let horizon = now - WEEK;
+        for history in self.pools.values_mut() {
+            history.swaps.retain(|(t, _)| *t > horizon);
+            history.joins.retain(|t| *t > horizon);
+            history.exits.retain(|t| *t > horizon);
+            let keep_from = history.snapshots.partition_point(|s| s.time <= horizon).saturating_sub(1);
+            history.snapshots.drain(..keep_from);
+        }
+    }
+
+    pub fn pools(&self) -> impl Iterator<Item = &Pubkey> {
+        self.pools.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(time: i64, swap_fee: u64) -> PoolSnapshot {
+        PoolSnapshot { time, tokens: vec![], balances: vec![], swap_fee, total_bpt: 0 }
+    }
+
+    #[test]
+    fn keeps_snapshots_in_time_order_and_prunes_old_ones() {
+        let pool = Pubkey::new_unique();
+        let mut analytics = Analytics::new();
+        for time in [300, 100, 200] {
+            analytics.snapshot(pool, snapshot(time, time as u64));
+        }
+        let history = &analytics.pools[&pool];
+        assert_eq!(history.snapshots.iter().map(|s| s.time).collect::<Vec<_>>(), [100, 200, 300]);
+        assert_eq!(history.snapshot_at(50).unwrap().time, 100);
+        assert_eq!(history.snapshot_at(250).unwrap().time, 200);
+
+        // the snapshot in effect at the horizon stays
+        analytics.prune(250 + WEEK);
+        assert_eq!(analytics.pools[&pool].snapshots.iter().map(|s| s.time).collect::<Vec<_>>(), [200, 300]);
+    }
+
+    #[test]
+    fn values_raw_amounts() {
+        let (usdc, eth) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut prices = Prices::default();
+        prices.set(usdc, 6, 1.0);
+        prices.set(eth, 8, 3_000.0);
+        assert_eq!(prices.value(&usdc, 2_500_000), Some(2.5));
+        assert_eq!(prices.value(&Pubkey::new_unique(), 1), None);
+        let s = PoolSnapshot { time: 0, tokens: vec![usdc, eth, Pubkey::new_unique()], balances: vec![1_000_000, 50_000_000, 7], swap_fee: 0, total_bpt: 0 };
+        assert_eq!(prices.tvl(&s), 1_501.0);
+    }
+}
@@ -0,0 +1,202 @@
+// Symmetric‑Solana ─ Pool statistics
+// ================================================================
+// Per‑pool figures over a trailing window ending at `now`:
+//   • volume: each swap's amount in, valued at the token in's price
+//     (or its amount out at the token out's, when only that is priced);
+//   • fees: the event's swap fee amount, valued as the volume; the
+//     protocol's part is the event's protocol fee, the rest is earned
+//     by LPs;
+//   • average TVL: snapshot values weighted by how long each held
+//     within the window;
+//   • utilization: volume over average TVL;
+//   • LP APR: LP fees over average TVL, annualized from the window.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use serde::Serialize;
+
+use crate::{Analytics, PoolHistory, Prices};
+
+pub const DAY: i64 = 86_400;
+pub const WEEK: i64 = 7 * DAY;
+const YEAR: f64 = 365.0 * DAY as f64;
+
+/// A pool's figures over one window, in the prices' quote currency.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct WindowStats {
+    pub window_secs: i64,
+    pub swaps: u64,
+    pub joins: u64,
+    pub exits: u64,
+    pub volume: f64,
+    pub fees: f64,
+    pub lp_fees: f64,
+    pub protocol_fees: f64,
+    pub average_tvl: f64,
+    pub utilization: f64,
+    pub lp_apr: f64,
+    /// Swaps neither of whose tokens has a price, left out of volume
+    pub unpriced_swaps: u64,
+}
+
+/// A pool's current TVL with its 24h and 7d figures.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PoolStats {
+    pub pool: String,
+    /// Unix seconds the windows end at
+    pub time: i64,
+    pub tvl: f64,
+    pub day: WindowStats,
+    pub week: WindowStats,
+}
+
+impl PoolHistory {
+    /// Time‑weighted TVL over `start..now`; the value in effect at `now`
+    /// when no snapshot covers any of it.
+    fn average_tvl(&self, start: i64, now: i64, prices: &Prices) -> f64 {
+        let (mut weighted, mut covered) = (0.0, 0i64);
+        for (i, snapshot) in self.snapshots.iter().enumerate() {
+            let from = snapshot.time.max(start);
+            let to = self.snapshots.get(i + 1).map_or(now, |next| next.time).min(now);
+            if to > from {
+                weighted += prices.tvl(snapshot) * (to - from) as f64;
+                covered += to - from;
+            }
+        }
+        if covered > 0 {
+            weighted / covered as f64
+        } else {
+            self.snapshot_at(now).map_or(0.0, |s| prices.tvl(s))
+        }
+    }
+
+    fn window(&self, now: i64, window_secs: i64, prices: &Prices) -> WindowStats {
+        let start = now - window_secs;
+        let within = |t: &i64| *t > start && *t <= now;
+        let mut stats = WindowStats {
+            window_secs,
+            joins: self.joins.iter().filter(|t| within(t)).count() as u64,
+            exits: self.exits.iter().filter(|t| within(t)).count() as u64,
+            average_tvl: self.average_tvl(start, now, prices),
+            ..WindowStats::default()
+        };
+        for (_, swap) in self.swaps.iter().filter(|(t, _)| within(t)) {
+            stats.swaps += 1;
+            let volume = prices.value(&swap.token_in, swap.amount_in).or_else(|| prices.value(&swap.token_out, swap.amount_out));
+            let Some(volume) = volume else {
+                stats.unpriced_swaps += 1;
+                continue;
+            };
+            let share = |amount: u64| if swap.amount_in == 0 { 0.0 } else { volume * amount as f64 / swap.amount_in as f64 };
+            let (fees, protocol_fees) = (share(swap.swap_fee_amount), share(swap.protocol_fee));
+            stats.volume += volume;
+            stats.fees += fees;
+            stats.protocol_fees += protocol_fees;
+            stats.lp_fees += (fees - protocol_fees).max(0.0);
+        }
+        if stats.average_tvl > 0.0 {
+            stats.utilization = stats.volume / stats.average_tvl;
+            stats.lp_apr = stats.lp_fees / stats.average_tvl * YEAR / window_secs as f64;
+        }
+        stats
+    }
+}
+
+impl Analytics {
+    /// `pool`'s figures over the `window_secs` ending at `now`.
+    pub fn window(&self, pool: &Pubkey, now: i64, window_secs: i64, prices: &Prices) -> Option<WindowStats> {
+        self.pools.get(pool).map(|history| history.window(now, window_secs, prices))
+    }
+
+    /// `pool`'s TVL at `now` and its 24h and 7d figures.
+    pub fn pool_stats(&self, pool: &Pubkey, now: i64, prices: &Prices) -> Option<PoolStats> {
+        let history = self.pools.get(pool)?;
+        Some(PoolStats {
+            pool: pool.to_string(),
+            time: now,
+            tvl: history.snapshot_at(now).map_or(0.0, |s| prices.tvl(s)),
+            day: history.window(now, DAY, prices),
+            week: history.window(now, WEEK, prices),
+        })
+    }
+
+    /// Every pool's statistics, largest TVL first.
+    pub fn stats(&self, now: i64, prices: &Prices) -> Vec<PoolStats> {
+        let mut all: Vec<PoolStats> = self.pools.keys().filter_map(|pool| self.pool_stats(pool, now, prices)).collect();
+        all.sort_by(|a, b| b.tvl.total_cmp(&a.tvl).then_with(|| a.pool.cmp(&b.pool)));
+        all
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PoolSnapshot;
+    use events::{Event, EventRecord, JoinEvent, SwapEvent};
+    use solana_signature::Signature;
+
+    fn record(event: Event) -> EventRecord {
+        EventRecord { slot: 0, signature: Signature::default(), program_id: weighted_pool::ID, index: 0, event }
+    }
+
+    fn swap(pool: Pubkey, token_in: Pubkey, token_out: Pubkey, amount_in: u64, protocol_fee: u64) -> EventRecord {
+        record(Event::Swap(SwapEvent {
+            pool,
+            pool_id: [0; 32],
+            user: Pubkey::default(),
+            token_in,
+            token_out,
+            amount_in,
+            amount_out: amount_in,
+            protocol_fee,
+            spot_price_before: 0,
+            spot_price_after: 0,
+            swap_fee_amount: amount_in * 3 / 1_000,
+            balance_in: 0,
+            balance_out: 0,
+        }))
+    }
+
+    #[test]
+    fn computes_volume_fees_and_apr_per_window() {
+        let (pool, usdc, usdt) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mut prices = Prices::default();
+        prices.set(usdc, 6, 1.0);
+        let now = 10 * DAY;
+        let snapshot = |time, balance| PoolSnapshot {
+            time,
+            tokens: vec![usdc, usdt],
+            balances: vec![balance, balance],
+            swap_fee: 3_000_000_000_000_000,
+            total_bpt: 0,
+        };
+
+        let mut analytics = Analytics::new();
+        // 1 000 USDC of TVL (USDT unpriced) for a week, 3 000 for the last day
+        analytics.snapshot(pool, snapshot(now - WEEK, 1_000_000_000));
+        analytics.snapshot(pool, snapshot(now - DAY, 3_000_000_000));
+        analytics.record(now - 2 * DAY, &swap(pool, usdc, usdt, 1_000_000_000, 1_500_000));
+        analytics.record(now - 3600, &swap(pool, usdt, usdc, 500_000_000, 750_000));
+        let unknown = Pubkey::new_unique();
+        analytics.record(now - 60, &swap(pool, unknown, unknown, 1, 0));
+        let join = JoinEvent { pool, pool_id: [0; 32], user: Pubkey::default(), amounts_in: vec![], bpt_out: 0, protocol_bpt: 0 };
+        analytics.record(now - 8 * DAY, &record(Event::Join(join)));
+
+        let stats = analytics.pool_stats(&pool, now, &prices).unwrap();
+        assert_eq!(stats.tvl, 3_000.0);
+        // valued at USDC in, or USDC out when USDT went in
+        assert_eq!((stats.day.swaps, stats.day.unpriced_swaps, stats.day.volume), (2, 1, 500.0));
+        assert_eq!(stats.week.volume, 1_500.0);
+        assert!((stats.week.fees - 4.5).abs() < 1e-9);
+        assert!((stats.week.protocol_fees - 2.25).abs() < 1e-9);
+        assert!((stats.week.lp_fees - 2.25).abs() < 1e-9);
+        assert_eq!(stats.week.joins, 0);
+        assert_eq!(stats.day.average_tvl, 3_000.0);
+        assert!((stats.week.average_tvl - (6.0 * 1_000.0 + 3_000.0) / 7.0).abs() < 1e-9);
+        assert!((stats.day.utilization - 500.0 / 3_000.0).abs() < 1e-12);
+        assert!((stats.day.lp_apr - 0.75 / 3_000.0 * 365.0).abs() < 1e-12);
+
+        let json = serde_json::to_value(analytics.stats(now, &prices)).unwrap();
+        assert_eq!(json[0]["pool"], pool.to_string());
+        assert_eq!(json[0]["day"]["window_secs"], DAY);
+    }
+}
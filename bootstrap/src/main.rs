@@ -0,0 +1,147 @@
+// Symmetric‑Solana ─ bootstrap
+// ================================================================
+// A one‑command local environment for frontend and bot developers:
+//   • starts `solana-test-validator` with the Vault and weighted pool
+//     programs of `anchor build` (validator), or uses a running one
+//     given `--url` that already has them;
+//   • creates test mints, a Vault, sample 80/20 and 50/50 pools with
+//     liquidity, and funded test wallets (setup);
+//   • writes every address to `<out>/bootstrap.json` and the wallet
+//     keypairs to `<out>/wallets/`.
+// A validator it started runs until interrupted.
+// ================================================================
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+use clap::{value_parser, Arg, ArgMatches, Command};
+use solana_commitment_config::CommitmentConfig;
+use solana_keypair::{read_keypair_file, write_keypair_file, Keypair};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
+
+mod setup;
+mod validator;
+
+use setup::Local;
+use validator::{Validator, PROGRAMS};
+
+fn cli() -> Command {
+    Command::new("bootstrap")
+        .about("Local validator with Symmetric programs, test mints, a Vault, sample pools and funded wallets")
+        .arg(Arg::new("url").long("url").short('u').help("Use this running validator instead of starting one"))
+        .arg(
+            Arg::new("programs")
+                .long("programs")
+                .value_parser(value_parser!(PathBuf))
+                .default_value("target/deploy")
+                .help("Directory of vault.so and weighted_pool.so"),
+        )
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_parser(value_parser!(PathBuf))
+                .default_value(".localnet")
+                .help("Directory of the ledger, wallets and bootstrap.json"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .short('k')
+                .value_parser(value_parser!(PathBuf))
+                .help("Payer, mint authority and Vault owner [default: <out>/payer.json, created if missing]"),
+        )
+        .arg(Arg::new("rpc-port").long("rpc-port").value_parser(value_parser!(u16)).default_value("8899"))
+        .arg(
+            Arg::new("wallets")
+                .long("wallets")
+                .value_parser(value_parser!(usize))
+                .default_value("3")
+                .help("Test wallets to create and fund"),
+        )
+        .arg(
+            Arg::new("wallet-sol")
+                .long("wallet-sol")
+                .value_parser(value_parser!(u64))
+                .default_value("100")
+                .help("SOL airdropped to each test wallet"),
+        )
+}
+
+fn value<T: Clone + Send + Sync + 'static>(m: &ArgMatches, name: &str) -> T {
+    m.get_one::<T>(name).cloned().expect("defaulted")
+}
+
+/// The payer at `path`, created there first when missing.
+fn payer(path: &PathBuf) -> Result<Keypair, Box<dyn Error>> {
+    if !path.exists() {
+        write_keypair_file(&Keypair::new(), path).map_err(|err| format!("{}: {err}", path.display()))?;
+    }
+    read_keypair_file(path).map_err(|err| format!("keypair {}: {err}", path.display()).into())
+}
+
+fn run(matches: ArgMatches) -> Result<(), Box<dyn Error>> {
+    let out: PathBuf = value(&matches, "out");
+    std::fs::create_dir_all(&out)?;
+
+    let (url, validator) = match matches.get_one::<String>("url") {
+        Some(url) => (url.clone(), None),
+        None => {
+            let port: u16 = value(&matches, "rpc-port");
+            let mut validator = Validator::start(&value::<PathBuf>(&matches, "programs"), &out, port)?;
+            let url = format!("http://127.0.0.1:{port}");
+            validator.wait_ready(&RpcClient::new(url.clone()), Duration::from_secs(60))?;
+            (url, Some(validator))
+        }
+    };
+    let rpc = RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed());
+    for (file, id) in PROGRAMS {
+        match rpc.get_account(&id) {
+            Ok(account) if account.executable => {}
+            _ => return Err(format!("{id} ({file}) is not deployed at {url}").into()),
+        }
+    }
+
+    let keypair = matches.get_one::<PathBuf>("keypair").cloned().unwrap_or_else(|| out.join("payer.json"));
+    let local = Local { rpc, payer: payer(&keypair)? };
+    local.airdrop(&local.payer(), 1_000)?;
+    println!("payer {} {}", local.payer.pubkey(), keypair.display());
+
+    let summary = setup::run(&local, value(&matches, "wallets"), value(&matches, "wallet-sol"), &out)?;
+    let path = out.join("bootstrap.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&summary.to_json())?)?;
+    println!("wrote {}", path.display());
+
+    match validator {
+        Some(validator) => {
+            println!("validator running at {url} (log {}); Ctrl-C to stop", validator.log.display());
+            validator.wait()
+        }
+        None => Ok(()),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_defaults() {
+        cli().debug_assert();
+        let m = cli().try_get_matches_from(["bootstrap", "--wallets", "5"]).unwrap();
+        assert_eq!(value::<usize>(&m, "wallets"), 5);
+        assert_eq!(value::<PathBuf>(&m, "programs"), PathBuf::from("target/deploy"));
+        assert!(m.get_one::<String>("url").is_none());
+    }
+}
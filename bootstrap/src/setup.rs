@@ -0,0 +1,291 @@
+// Symmetric‑Solana ─ Environment set‑up
+// ================================================================
+// Everything the environment holds once the programs run:
+//   • test mints (`TOKENS`), their mint authority the payer;
+//   • the payer's Vault, Authorizer and fees collector, the payer
+//     holding `RegisterPool` and `SetProtocolFees`;
+//   • the sample weighted pools (`POOLS`), seeded with liquidity from
+//     the payer so they quote and swap right away;
+//   • test wallets with SOL and every test token, their keypairs
+//     written next to the summary.
+// The summary (`Summary::to_json`) is what frontends and bots load.
+// ================================================================
+use std::collections::HashSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use client::{PoolClient, VaultClient};
+use serde_json::json;
+use solana_keypair::{write_keypair_file, Keypair};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use vault::Action;
+
+type SetupResult<T = ()> = Result<T, Box<dyn Error>>;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+pub struct TestToken {
+    pub symbol: &'static str,
+    pub decimals: u8,
+    /// Whole tokens each test wallet receives
+    pub per_wallet: u64,
+}
+
+pub const TOKENS: [TestToken; 3] = [
+    TestToken { symbol: "SYMM", decimals: 9, per_wallet: 100_000 },
+    TestToken { symbol: "USDC", decimals: 6, per_wallet: 100_000 },
+    TestToken { symbol: "WETH", decimals: 8, per_wallet: 50 },
+];
+
+pub struct SamplePool {
+    pub name: &'static str,
+    /// (index into `TOKENS`, weight as an 18‑dec fraction, whole tokens
+    /// seeded)
+    pub tokens: [(usize, u128, u64); 2],
+    pub swap_fee: u64,
+}
+
+/// An 80/20 SYMM/USDC pool and a 50/50 WETH/USDC pool, both seeded at
+/// market‑like prices: 1 SYMM = 1 USDC, 1 WETH = 3 000 USDC.
+pub const POOLS: [SamplePool; 2] = [
+    SamplePool {
+        name: "SYMM/USDC 80/20",
+        tokens: [(0, ONE * 8 / 10, 800_000), (1, ONE * 2 / 10, 200_000)],
+        swap_fee: 3_000_000_000_000_000,
+    },
+    SamplePool { name: "WETH/USDC 50/50", tokens: [(2, ONE / 2, 100), (1, ONE / 2, 300_000)], swap_fee: 1_000_000_000_000_000 },
+];
+
+/// Raw units of `whole` tokens of `token`.
+pub fn raw(token: &TestToken, whole: u64) -> u64 {
+    whole * 10u64.pow(token.decimals as u32)
+}
+
+/// What the environment holds, as written to `bootstrap.json`.
+pub struct Summary {
+    pub rpc_url: String,
+    pub payer: Pubkey,
+    pub vault: Pubkey,
+    pub mints: Vec<Pubkey>,
+    /// (name, pool state, LP mint)
+    pub pools: Vec<(&'static str, Pubkey, Pubkey)>,
+    /// (address, keypair file)
+    pub wallets: Vec<(Pubkey, PathBuf)>,
+}
+
+impl Summary {
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "rpc": self.rpc_url,
+            "programs": { "vault": vault::ID.to_string(), "weighted_pool": weighted_pool::ID.to_string() },
+            "payer": self.payer.to_string(),
+            "vault": self.vault.to_string(),
+            "mints": TOKENS.iter().zip(&self.mints).map(|(token, mint)| {
+                (token.symbol.to_string(), json!({ "address": mint.to_string(), "decimals": token.decimals }))
+            }).collect::<serde_json::Map<_, _>>(),
+            "pools": self.pools.iter().map(|(name, pool, lp_mint)| {
+                json!({ "name": name, "address": pool.to_string(), "lp_mint": lp_mint.to_string() })
+            }).collect::<Vec<_>>(),
+            "wallets": self.wallets.iter().map(|(address, keypair)| {
+                json!({ "address": address.to_string(), "keypair": keypair.display().to_string() })
+            }).collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// The payer on a local cluster: sends and airdrops.
+pub struct Local {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Local {
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    fn send(&self, ixs: &[Instruction], signers: &[&Keypair]) -> SetupResult {
+        let mut all: Vec<&dyn Signer> = vec![&self.payer];
+        all.extend(signers.iter().map(|k| *k as &dyn Signer));
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.payer()), &all, blockhash);
+        self.rpc.send_and_confirm_transaction(&tx)?;
+        Ok(())
+    }
+
+    /// Airdrop `sol` to `to` from the validator's faucet.
+    pub fn airdrop(&self, to: &Pubkey, sol: u64) -> SetupResult {
+        let signature = self.rpc.request_airdrop(to, sol * LAMPORTS_PER_SOL)?;
+        self.rpc.poll_for_signature(&signature)?;
+        Ok(())
+    }
+
+    /// Instructions creating an SPL mint at `mint`.
+    fn mint_instructions(&self, mint: &Pubkey, authority: &Pubkey, decimals: u8) -> SetupResult<Vec<Instruction>> {
+        let space = spl_token::state::Mint::LEN;
+        let lamports = self.rpc.get_minimum_balance_for_rent_exemption(space)?;
+        Ok(vec![
+            system_instruction::create_account(&self.payer(), mint, lamports, space as u64, &spl_token::ID),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, mint, authority, None, decimals)?,
+        ])
+    }
+
+    fn create_mint(&self, authority: &Pubkey, decimals: u8) -> SetupResult<Pubkey> {
+        let mint = Keypair::new();
+        self.send(&self.mint_instructions(&mint.pubkey(), authority, decimals)?, &[&mint])?;
+        Ok(mint.pubkey())
+    }
+
+    /// Mint `amount` of each payer‑authority mint to `owner`'s associated
+    /// token accounts, created as needed; returns the accounts.
+    fn fund(&self, owner: &Pubkey, mints: &[(Pubkey, u64)]) -> SetupResult<Vec<Pubkey>> {
+        let payer = self.payer();
+        let mut ixs = Vec::new();
+        let mut accounts = Vec::new();
+        for (mint, amount) in mints {
+            let account = get_associated_token_address(owner, mint);
+            ixs.push(create_associated_token_account_idempotent(&payer, owner, mint, &spl_token::ID));
+            ixs.push(spl_token::instruction::mint_to(&spl_token::ID, mint, &account, &payer, &[], *amount)?);
+            accounts.push(account);
+        }
+        self.send(&ixs, &[])?;
+        Ok(accounts)
+    }
+
+    /// The payer's Vault with its Authorizer and fees collector, the
+    /// payer holding the roles to register pools and set fees.
+    pub fn init_vault(&self) -> SetupResult<VaultClient> {
+        let payer = self.payer();
+        let vault = VaultClient::for_creator(&payer);
+        self.send(
+            &[
+                VaultClient::initialize(&payer, payer),
+                vault.initialize_authorizer(&payer),
+                vault.grant_role(&payer, Action::RegisterPool, payer),
+                vault.grant_role(&payer, Action::SetProtocolFees, payer),
+                vault.initialize_fees_collector(&payer),
+            ],
+            &[],
+        )?;
+        Ok(vault)
+    }
+
+    /// Create `sample` as a canonical weighted pool with its fee and
+    /// custody accounts (fee accounts in `fee_mints` already exist), then
+    /// seed it from the payer.
+    pub fn create_pool(&self, vault: &VaultClient, mints: &[Pubkey], sample: &SamplePool, fee_mints: &mut HashSet<Pubkey>) -> SetupResult<PoolClient> {
+        let payer = self.payer();
+        let mut tokens: Vec<(Pubkey, u128, u64)> =
+            sample.tokens.iter().map(|(i, weight, whole)| (mints[*i], *weight, raw(&TOKENS[*i], *whole))).collect();
+        tokens.sort_by_key(|(mint, _, _)| *mint);
+        let state = vault.fetch_state(&self.rpc)?;
+        let lp_mint = Keypair::new();
+        let (pool, ix) = PoolClient::create_weighted(
+            vault.vault_state,
+            &state,
+            &payer,
+            &payer,
+            lp_mint.pubkey(),
+            tokens.iter().map(|t| t.0).collect(),
+            tokens.iter().map(|t| t.1).collect(),
+            sample.swap_fee,
+        )?;
+        let mut ixs = self.mint_instructions(&lp_mint.pubkey(), &pool.authority(), 9)?;
+        ixs.push(ix);
+        self.send(&ixs, &[&lp_mint])?;
+
+        let mut accounts = pool.join_tokens();
+        accounts.push(pool.lp_mint);
+        let fee_accounts: Vec<Instruction> =
+            accounts.into_iter().filter(|mint| fee_mints.insert(*mint)).map(|mint| vault.create_fee_account(&payer, mint, &pool.token_program)).collect();
+        self.send(&fee_accounts, &[])?;
+        let join_tokens = pool.join_tokens();
+        self.send(&[vault.register_tokens(&payer, &pool.pool_id, &join_tokens, &pool.token_program, vec![Pubkey::default(); join_tokens.len()])?], &[])?;
+
+        let amounts: Vec<u64> = tokens.iter().map(|t| t.2).collect();
+        let user_tokens = self.fund(&payer, &tokens.iter().map(|t| (t.0, t.2)).collect::<Vec<_>>())?;
+        let user_lp = get_associated_token_address(&payer, &pool.lp_mint);
+        self.send(
+            &[
+                create_associated_token_account_idempotent(&payer, &payer, &pool.lp_mint, &spl_token::ID),
+                pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, amounts, 0)?,
+            ],
+            &[],
+        )?;
+        Ok(pool)
+    }
+}
+
+/// Build the environment on `local`: mints, Vault, sample pools and
+/// `wallets` funded test wallets, their keypairs written to `out`.
+pub fn run(local: &Local, wallets: usize, wallet_sol: u64, out: &Path) -> SetupResult<Summary> {
+    let payer = local.payer();
+    let mut mints = Vec::new();
+    for token in &TOKENS {
+        mints.push(local.create_mint(&payer, token.decimals)?);
+        println!("mint {} {}", token.symbol, mints.last().expect("pushed"));
+    }
+    let vault = local.init_vault()?;
+    println!("vault {}", vault.vault_state);
+
+    let mut fee_mints = HashSet::new();
+    let mut pools = Vec::new();
+    for sample in &POOLS {
+        let pool = local.create_pool(&vault, &mints, sample, &mut fee_mints)?;
+        println!("pool {} {}", sample.name, pool.pool);
+        pools.push((sample.name, pool.pool, pool.lp_mint));
+    }
+
+    let dir = out.join("wallets");
+    std::fs::create_dir_all(&dir)?;
+    let mut funded = Vec::new();
+    for n in 0..wallets {
+        let wallet = Keypair::new();
+        let path = dir.join(format!("wallet-{n}.json"));
+        write_keypair_file(&wallet, &path).map_err(|err| format!("{}: {err}", path.display()))?;
+        local.airdrop(&wallet.pubkey(), wallet_sol)?;
+        let tokens: Vec<(Pubkey, u64)> = TOKENS.iter().zip(&mints).map(|(t, m)| (*m, raw(t, t.per_wallet))).collect();
+        local.fund(&wallet.pubkey(), &tokens)?;
+        println!("wallet {} {}", wallet.pubkey(), path.display());
+        funded.push((wallet.pubkey(), path));
+    }
+
+    Ok(Summary { rpc_url: local.rpc.url(), payer, vault: vault.vault_state, mints, pools, wallets: funded })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_pools_are_well_formed() {
+        for pool in &POOLS {
+            assert_eq!(pool.tokens.iter().map(|t| t.1).sum::<u128>(), ONE);
+            assert_ne!(pool.tokens[0].0, pool.tokens[1].0);
+        }
+        assert_eq!(raw(&TOKENS[1], 3), 3_000_000);
+
+        let summary = Summary {
+            rpc_url: "http://localhost:8899".into(),
+            payer: Pubkey::new_unique(),
+            vault: Pubkey::new_unique(),
+            mints: vec![Pubkey::new_unique(); 3],
+            pools: vec![("SYMM/USDC 80/20", Pubkey::new_unique(), Pubkey::new_unique())],
+            wallets: vec![(Pubkey::new_unique(), PathBuf::from(".localnet/wallets/wallet-0.json"))],
+        };
+        let doc = summary.to_json();
+        assert_eq!(doc["mints"]["USDC"]["decimals"], 6);
+        assert_eq!(doc["programs"]["weighted_pool"], weighted_pool::ID.to_string());
+        assert_eq!(doc["wallets"][0]["keypair"], ".localnet/wallets/wallet-0.json");
+    }
+}
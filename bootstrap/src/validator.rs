@@ -0,0 +1,107 @@
+// Symmetric‑Solana ─ Local validator
+// ================================================================
+// A `solana-test-validator` with the Vault and weighted pool programs
+// loaded at their declared IDs from genesis (`--bpf-program`): their
+// IDs are vanity addresses whose keypairs are not in the repo, so they
+// cannot be deployed over RPC.
+//   • ledger and log under the output directory, reset on every run;
+//   • ready once its RPC answers `getHealth`;
+//   • killed when dropped, unless waited on.
+// ================================================================
+use std::error::Error;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anchor_lang::prelude::Pubkey;
+use solana_rpc_client::rpc_client::RpcClient;
+
+/// The programs the environment deploys, and their `.so` file names.
+pub const PROGRAMS: [(&str, Pubkey); 2] = [("vault.so", vault::ID), ("weighted_pool.so", weighted_pool::ID)];
+
+/// `solana-test-validator` arguments loading every program from
+/// `programs_dir`, with its ledger in `ledger` and RPC on `rpc_port`.
+pub fn args(programs_dir: &Path, ledger: &Path, rpc_port: u16) -> Vec<String> {
+    let mut args = vec![
+        "--reset".to_string(),
+        "--quiet".to_string(),
+        "--ledger".to_string(),
+        ledger.display().to_string(),
+        "--rpc-port".to_string(),
+        rpc_port.to_string(),
+    ];
+    for (file, id) in PROGRAMS {
+        args.extend(["--bpf-program".to_string(), id.to_string(), programs_dir.join(file).display().to_string()]);
+    }
+    args
+}
+
+pub struct Validator {
+    child: Child,
+    pub log: PathBuf,
+}
+
+impl Validator {
+    /// Start a validator with the programs of `programs_dir`, which must
+    /// hold a build of each (`anchor build`).
+    pub fn start(programs_dir: &Path, out: &Path, rpc_port: u16) -> Result<Self, Box<dyn Error>> {
+        for (file, _) in PROGRAMS {
+            let so = programs_dir.join(file);
+            if !so.is_file() {
+                return Err(format!("{} not found; run `anchor build` first", so.display()).into());
+            }
+        }
+        let log = out.join("validator.log");
+        let child = Command::new("solana-test-validator")
+            .args(args(programs_dir, &out.join("ledger"), rpc_port))
+            .stdout(File::create(&log)?)
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|err| format!("solana-test-validator: {err}"))?;
+        Ok(Validator { child, log })
+    }
+
+    /// Block until the validator's RPC is healthy, up to `timeout`.
+    pub fn wait_ready(&mut self, rpc: &RpcClient, timeout: Duration) -> Result<(), Box<dyn Error>> {
+        let start = Instant::now();
+        while rpc.get_health().is_err() {
+            if let Some(status) = self.child.try_wait()? {
+                return Err(format!("validator exited with {status}; see {}", self.log.display()).into());
+            }
+            if start.elapsed() > timeout {
+                return Err(format!("validator not healthy after {timeout:?}; see {}", self.log.display()).into());
+            }
+            sleep(Duration::from_millis(500));
+        }
+        Ok(())
+    }
+
+    /// Run until the validator exits (or the user interrupts both).
+    pub fn wait(mut self) -> Result<(), Box<dyn Error>> {
+        let status = self.child.wait()?;
+        Err(format!("validator exited with {status}").into())
+    }
+}
+
+impl Drop for Validator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_every_program_at_its_id() {
+        let args = args(Path::new("target/deploy"), Path::new(".localnet/ledger"), 8899);
+        assert_eq!(&args[..6], ["--reset", "--quiet", "--ledger", ".localnet/ledger", "--rpc-port", "8899"]);
+        assert_eq!(args[6..9], ["--bpf-program".to_string(), vault::ID.to_string(), "target/deploy/vault.so".to_string()]);
+        assert_eq!(args[10], weighted_pool::ID.to_string());
+        assert_eq!(args.len(), 12);
+    }
+}
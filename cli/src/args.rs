@@ -0,0 +1,81 @@
+// Symmetric‑Solana ─ CLI argument parsing
+// ================================================================
+// Value parsers for the subcommands' arguments:
+//   • fractions (fees, weights) as a percentage ("0.3%"), a decimal
+//     ("0.003") or the programs' raw 18‑dec integer;
+//   • `MINT:WEIGHT` pairs for weighted pools;
+//   • roles of the Vault's Authorizer.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use vault::Action;
+
+/// Decimals of the programs' fixed‑point fractions
+const FRACTION_DECIMALS: u32 = 18;
+
+pub fn parse_pubkey(s: &str) -> Result<Pubkey, String> {
+    s.parse().map_err(|_| format!("invalid address: {s}"))
+}
+
+/// An 18‑dec fraction: "0.3%" and "0.003" are both 3·10¹⁵; a plain
+/// integer is taken as already scaled.
+pub fn parse_fraction(s: &str) -> Result<u128, String> {
+    let invalid = || format!("invalid fraction: {s}");
+    let (number, decimals) = match s.strip_suffix('%') {
+        Some(percent) => (percent, FRACTION_DECIMALS - 2),
+        None if s.contains('.') => (s, FRACTION_DECIMALS),
+        None => return s.parse().map_err(|_| invalid()),
+    };
+    let (whole, frac) = number.split_once('.').unwrap_or((number, ""));
+    if number.is_empty() || frac.len() > decimals as usize || !whole.chars().chain(frac.chars()).all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let digits = format!("{whole}{frac:0<width$}", width = decimals as usize);
+    digits.parse().map_err(|_| invalid())
+}
+
+/// A fee as the programs' u64 18‑dec fraction.
+pub fn parse_fee(s: &str) -> Result<u64, String> {
+    u64::try_from(parse_fraction(s)?).map_err(|_| format!("fee too large: {s}"))
+}
+
+/// `MINT:WEIGHT`, the weight a fraction (see [`parse_fraction`]).
+pub fn parse_token_weight(s: &str) -> Result<(Pubkey, u128), String> {
+    let (mint, weight) = s.split_once(':').ok_or_else(|| format!("expected MINT:WEIGHT, got {s}"))?;
+    Ok((parse_pubkey(mint)?, parse_fraction(weight)?))
+}
+
+/// An Authorizer role by its kebab‑case name.
+pub fn parse_role(s: &str) -> Result<Action, String> {
+    Ok(match s {
+        "register-pool" => Action::RegisterPool,
+        "set-swap-fee" => Action::SetSwapFee,
+        "pause" => Action::Pause,
+        "collect-fees" => Action::CollectFees,
+        "set-protocol-fees" => Action::SetProtocolFees,
+        "manage-rewards" => Action::ManageRewards,
+        "manage-token-policy" => Action::ManageTokenPolicy,
+        "transfer-ownership" => Action::TransferOwnership,
+        _ => return Err(format!("unknown role: {s}")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fractions_in_every_form() {
+        assert_eq!(parse_fraction("0.3%"), Ok(3_000_000_000_000_000));
+        assert_eq!(parse_fraction("0.003"), Ok(3_000_000_000_000_000));
+        assert_eq!(parse_fraction("3000000000000000"), Ok(3_000_000_000_000_000));
+        assert_eq!(parse_fraction("60%"), Ok(600_000_000_000_000_000));
+        assert_eq!(parse_fraction("0%"), Ok(0));
+        assert!(parse_fraction("0.0000000000000000001").is_err());
+        assert!(parse_fraction("1.5x").is_err());
+
+        let mint = Pubkey::new_unique();
+        assert_eq!(parse_token_weight(&format!("{mint}:40%")), Ok((mint, 400_000_000_000_000_000)));
+        assert!(parse_token_weight("40%").is_err());
+        assert!(parse_role("pause").is_ok_and(|a| a == Action::Pause));
+    }
+}
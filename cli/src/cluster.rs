@@ -0,0 +1,75 @@
+// Symmetric‑Solana ─ CLI cluster connection
+// ================================================================
+// The RPC endpoint, commitment and fee payer every subcommand runs
+// with, and sending their instructions as one signed transaction.
+//   • `--url` takes a full URL or a moniker of the public clusters.
+//   • `--keypair` defaults to the Solana CLI's default keypair.
+// ================================================================
+use std::error::Error;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_commitment_config::CommitmentConfig;
+use solana_keypair::{read_keypair_file, Keypair};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+
+/// The RPC URL of a cluster moniker; anything else is taken as a URL.
+pub fn cluster_url(url_or_moniker: &str) -> String {
+    match url_or_moniker {
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "l" | "localhost" => "http://localhost:8899",
+        url => url,
+    }
+    .to_string()
+}
+
+/// The Solana CLI's default keypair path.
+pub fn default_keypair_path() -> String {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    format!("{home}/.config/solana/id.json")
+}
+
+/// An RPC connection and the keypair paying for and signing transactions.
+pub struct Cluster {
+    pub rpc: RpcClient,
+    pub payer: Keypair,
+}
+
+impl Cluster {
+    pub fn connect(url: &str, keypair_path: &str, commitment: CommitmentConfig) -> Result<Self, Box<dyn Error>> {
+        let payer = read_keypair_file(keypair_path).map_err(|err| format!("keypair {keypair_path}: {err}"))?;
+        Ok(Cluster { rpc: RpcClient::new_with_commitment(cluster_url(url), commitment), payer })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    /// Send `ixs` as one transaction signed by the payer and `signers`,
+    /// and wait for its confirmation; prints and returns the signature.
+    pub fn send(&self, ixs: &[Instruction], signers: &[&Keypair]) -> Result<String, Box<dyn Error>> {
+        let mut all: Vec<&dyn Signer> = vec![&self.payer];
+        all.extend(signers.iter().map(|k| *k as &dyn Signer));
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(ixs, Some(&self.payer()), &all, blockhash);
+        let signature = self.rpc.send_and_confirm_transaction(&tx)?.to_string();
+        println!("{signature}");
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_monikers_and_passes_urls_through() {
+        assert_eq!(cluster_url("devnet"), "https://api.devnet.solana.com");
+        assert_eq!(cluster_url("l"), "http://localhost:8899");
+        assert_eq!(cluster_url("https://rpc.example.com"), "https://rpc.example.com");
+    }
+}
@@ -0,0 +1,285 @@
+// Symmetric‑Solana ─ CLI subcommands
+// ================================================================
+// One function per subcommand, building its instructions with the
+// client SDK and sending them from the configured payer:
+//   • vault: init-vault, grant-role, set-protocol-fees, pause, unpause;
+//   • pools: create-weighted-pool, create-stable-pool, set-swap-fee,
+//     set-lp-metadata;
+//   • trading: join, exit, swap through the payer's associated token
+//     accounts (created on demand);
+//   • dump: pool state as JSON.
+// Amounts are raw token units; fees and weights 18‑dec fractions.
+// ================================================================
+use std::error::Error;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use client::{fetch_registered_pools, pda, PoolClient, VaultClient};
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use vault::Action;
+
+use crate::cluster::Cluster;
+use crate::dump::{fetch_pool, pool_json};
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+/// Create the Vault of the payer, with its Authorizer and protocol fees
+/// collector, all owned by the payer; the payer takes the
+/// `SetProtocolFees` role to create the collector.
+pub fn init_vault(c: &Cluster) -> CliResult {
+    let payer = c.payer();
+    let vault = VaultClient::for_creator(&payer);
+    println!("{}", vault.vault_state);
+    c.send(
+        &[
+            VaultClient::initialize(&payer, payer),
+            vault.initialize_authorizer(&payer),
+            vault.grant_role(&payer, Action::SetProtocolFees, payer),
+            vault.initialize_fees_collector(&payer),
+        ],
+        &[],
+    )?;
+    Ok(())
+}
+
+/// Grant (or revoke) an Authorizer role; the payer must be its admin.
+pub fn set_role(c: &Cluster, vault: Pubkey, action: Action, account: Pubkey, grant: bool) -> CliResult {
+    let vault = VaultClient::new(vault);
+    let ix = if grant {
+        vault.grant_role(&c.payer(), action, account)
+    } else {
+        vault.revoke_role(&c.payer(), action, account)
+    };
+    c.send(&[ix], &[])?;
+    Ok(())
+}
+
+pub fn set_protocol_fees(c: &Cluster, vault: Pubkey, swap_fee_percentage: u64, flash_loan_fee_percentage: u64) -> CliResult {
+    let ix = VaultClient::new(vault).set_protocol_fee_percentages(&c.payer(), swap_fee_percentage, flash_loan_fee_percentage);
+    c.send(&[ix], &[])?;
+    Ok(())
+}
+
+/// Pause (or resume) the whole Vault, or one pool when given.
+pub fn set_paused(c: &Cluster, vault: Pubkey, pool: Option<Pubkey>, paused: bool) -> CliResult {
+    let vault = VaultClient::new(vault);
+    let ix = match pool {
+        Some(pool) => vault.set_pool_paused(&c.payer(), &PoolClient::fetch(&c.rpc, &pool)?.pool_id, paused),
+        None if paused => vault.emergency_pause(&c.payer()),
+        None => vault.unpause(&c.payer()),
+    };
+    c.send(&[ix], &[])?;
+    Ok(())
+}
+
+/// Create a canonical weighted pool with a new LP mint, then its fee
+/// and custody accounts; the payer needs the `RegisterPool` role. The
+/// LP mint is of the tokens' program, SPL Token or Token‑2022. Given
+/// each token's symbol, the LP mint also gets token metadata named
+/// after them.
+#[allow(clippy::too_many_arguments)]
+pub fn create_weighted_pool(
+    c: &Cluster,
+    vault: Pubkey,
+    tokens: Vec<(Pubkey, u128)>,
+    swap_fee: u64,
+    lp_decimals: u8,
+    symbols: Vec<String>,
+    metadata_uri: String,
+) -> CliResult {
+    if !symbols.is_empty() && symbols.len() != tokens.len() {
+        return Err("one --symbol per --token".into());
+    }
+    let mut tokens: Vec<_> = tokens.into_iter().zip(symbols.iter().map(Some).chain(std::iter::repeat(None))).collect();
+    tokens.sort_by_key(|((mint, _), _)| *mint);
+    let (tokens, symbols): (Vec<(Pubkey, u128)>, Vec<Option<&String>>) = tokens.into_iter().unzip();
+    let (mints, weights): (Vec<Pubkey>, Vec<u128>) = tokens.into_iter().unzip();
+    let symbols: Option<Vec<&str>> = symbols.into_iter().map(|s| s.map(String::as_str)).collect();
+    let (name, symbol) = symbols.map(|s| client::lp_token_name(&s, &weights)).unzip();
+    let token_program = c.rpc.get_account(mints.first().ok_or("no tokens")?)?.owner;
+    let state = VaultClient::new(vault).fetch_state(&c.rpc)?;
+    let lp_mint = Keypair::new();
+    let payer = c.payer();
+    let (mut pool, ix) = PoolClient::create_weighted(vault, &state, &payer, &payer, lp_mint.pubkey(), mints, weights, swap_fee)?;
+    pool.token_program = token_program;
+    println!("{}", pool.pool);
+
+    let mut ixs = create_mint(c, &token_program, &lp_mint.pubkey(), &pool.authority(), lp_decimals)?;
+    ixs.push(ix);
+    c.send(&ixs, &[&lp_mint])?;
+    register_pool_accounts(c, &pool)?;
+    if let (Some(name), Some(symbol)) = (name, symbol) {
+        c.send(&[pool.create_lp_metadata(&payer, &payer, name, symbol, metadata_uri)?], &[])?;
+    }
+    Ok(())
+}
+
+/// Replace a weighted pool's LP token name, symbol and URI; the payer
+/// needs the `RegisterPool` role.
+pub fn set_lp_metadata(c: &Cluster, pool: Pubkey, name: String, symbol: String, uri: String) -> CliResult {
+    let pool = PoolClient::fetch(&c.rpc, &pool)?;
+    c.send(&[pool.set_lp_metadata(&c.payer(), name, symbol, uri)?], &[])?;
+    Ok(())
+}
+
+/// Create the Vault's stable pool with a new LP mint, then its fee and
+/// custody accounts; the payer needs the `RegisterPool` role.
+#[allow(clippy::too_many_arguments)]
+pub fn create_stable_pool(
+    c: &Cluster,
+    vault: Pubkey,
+    tokens: Vec<Pubkey>,
+    amplification: u64,
+    swap_fee: u64,
+    composable: bool,
+    rate_providers: Vec<Pubkey>,
+    lp_decimals: u8,
+) -> CliResult {
+    let state = VaultClient::new(vault).fetch_state(&c.rpc)?;
+    let lp_mint = Keypair::new();
+    let (pool, ix) = PoolClient::create_stable(
+        vault,
+        &state,
+        &c.payer(),
+        lp_mint.pubkey(),
+        tokens,
+        amplification,
+        swap_fee,
+        composable,
+        rate_providers,
+    );
+    println!("{}", pool.pool);
+
+    let mut ixs = create_mint(c, &spl_token::ID, &lp_mint.pubkey(), &pool.authority(), lp_decimals)?;
+    ixs.push(ix);
+    c.send(&ixs, &[&lp_mint])?;
+    register_pool_accounts(c, &pool)
+}
+
+/// A new mint of `token_program` at `mint` with `authority` as its mint authority.
+fn create_mint(
+    c: &Cluster,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    decimals: u8,
+) -> Result<Vec<Instruction>, Box<dyn Error>> {
+    let space = spl_token::state::Mint::LEN;
+    let lamports = c.rpc.get_minimum_balance_for_rent_exemption(space)?;
+    Ok(vec![
+        system_instruction::create_account(&c.payer(), mint, lamports, space as u64, token_program),
+        spl_token_2022::instruction::initialize_mint2(token_program, mint, authority, None, decimals)?,
+    ])
+}
+
+/// The collector's fee accounts of the pool's tokens and BPT that do
+/// not exist yet, and the custody account of each joined token.
+fn register_pool_accounts(c: &Cluster, pool: &PoolClient) -> CliResult {
+    let vault = VaultClient::new(pool.vault_state);
+    let mut mints = pool.join_tokens();
+    mints.push(pool.lp_mint);
+    let fee_accounts: Vec<Pubkey> = mints.iter().map(|m| pda::protocol_fee_account(&pool.vault_state, m)).collect();
+    let missing: Vec<Instruction> = c
+        .rpc
+        .get_multiple_accounts(&fee_accounts)?
+        .iter()
+        .zip(&mints)
+        .filter(|(account, _)| account.is_none())
+        .map(|(_, mint)| vault.create_fee_account(&c.payer(), *mint, &pool.token_program))
+        .collect();
+    if !missing.is_empty() {
+        c.send(&missing, &[])?;
+    }
+    let tokens = pool.join_tokens();
+    let register = vault.register_tokens(&c.payer(), &pool.pool_id, &tokens, &pool.token_program, vec![Pubkey::default(); tokens.len()])?;
+    c.send(&[register], &[])?;
+    Ok(())
+}
+
+pub fn set_swap_fee(c: &Cluster, pool: Pubkey, swap_fee: u64) -> CliResult {
+    let ix = PoolClient::fetch(&c.rpc, &pool)?.set_swap_fee(&c.payer(), swap_fee);
+    c.send(&[ix], &[])?;
+    Ok(())
+}
+
+/// The payer's associated token account of each mint of the pool's
+/// token program, and idempotent instructions creating them.
+fn user_accounts(c: &Cluster, pool: &PoolClient, mints: &[Pubkey]) -> (Vec<Pubkey>, Vec<Instruction>) {
+    let payer = c.payer();
+    let accounts =
+        mints.iter().map(|mint| get_associated_token_address_with_program_id(&payer, mint, &pool.token_program)).collect();
+    let create = mints
+        .iter()
+        .map(|mint| create_associated_token_account_idempotent(&payer, &payer, mint, &pool.token_program))
+        .collect();
+    (accounts, create)
+}
+
+/// Join with exactly `amounts_in` of every joined token.
+pub fn join(c: &Cluster, pool: Pubkey, amounts_in: Vec<u64>, minimum_bpt_out: u64) -> CliResult {
+    let pool = PoolClient::fetch(&c.rpc, &pool)?;
+    let (user_tokens, _) = user_accounts(c, &pool, &pool.join_tokens());
+    let (user_lp, mut ixs) = user_accounts(c, &pool, &[pool.lp_mint]);
+    ixs.push(pool.join_exact_tokens_in_for_bpt_out(&c.payer(), user_lp[0], &user_tokens, amounts_in, minimum_bpt_out)?);
+    c.send(&ixs, &[])?;
+    Ok(())
+}
+
+/// Exit burning exactly `bpt_in` for at least `minimum_amounts_out`;
+/// none given takes any amounts.
+pub fn exit(c: &Cluster, pool: Pubkey, bpt_in: u64, mut minimum_amounts_out: Vec<u64>) -> CliResult {
+    let pool = PoolClient::fetch(&c.rpc, &pool)?;
+    let (user_tokens, mut ixs) = user_accounts(c, &pool, &pool.join_tokens());
+    if minimum_amounts_out.is_empty() {
+        minimum_amounts_out = vec![0; user_tokens.len()];
+    }
+    let user_lp = get_associated_token_address_with_program_id(&c.payer(), &pool.lp_mint, &pool.token_program);
+    ixs.push(pool.exit_exact_bpt_in_for_tokens_out(&c.payer(), user_lp, &user_tokens, bpt_in, minimum_amounts_out)?);
+    c.send(&ixs, &[])?;
+    Ok(())
+}
+
+/// Exact‑in swap directly against the pool.
+pub fn swap(c: &Cluster, pool: Pubkey, mint_in: Pubkey, mint_out: Pubkey, amount_in: u64, minimum_amount_out: u64) -> CliResult {
+    let pool = PoolClient::fetch(&c.rpc, &pool)?;
+    let (accounts, create) = user_accounts(c, &pool, &[mint_in, mint_out]);
+    let swap = pool.swap_exact_token_in_for_token_out(
+        &c.payer(),
+        accounts[0],
+        accounts[1],
+        &mint_in,
+        &mint_out,
+        amount_in,
+        minimum_amount_out,
+    );
+    c.send(&[create[1].clone(), swap], &[])?;
+    Ok(())
+}
+
+/// Print `pool` as JSON, or every weighted and stable pool registered
+/// with `vault` as a JSON array.
+pub fn dump(c: &Cluster, pool: Option<Pubkey>, vault: Option<Pubkey>) -> CliResult {
+    let to_json = |pool: &Pubkey| -> Result<Option<serde_json::Value>, Box<dyn Error>> {
+        Ok(fetch_pool(&c.rpc, pool)?.map(|(state, registration, balances)| pool_json(pool, &state, &registration, &balances)))
+    };
+    let doc = match (pool, vault) {
+        (Some(pool), _) => to_json(&pool)?.ok_or_else(|| format!("{pool} is not a weighted or stable pool"))?,
+        (None, Some(vault)) => {
+            let mut docs = Vec::new();
+            for entry in fetch_registered_pools(&c.rpc, &vault)? {
+                docs.extend(to_json(&entry.pool)?);
+            }
+            docs.into()
+        }
+        (None, None) => return Err("either --pool or --vault is required".into()),
+    };
+    println!("{}", serde_json::to_string_pretty(&doc)?);
+    Ok(())
+}
@@ -0,0 +1,171 @@
+// Symmetric‑Solana ─ CLI pool dumps
+// ================================================================
+// A pool's state, Vault registration and custody balances as one JSON
+// document, for operators' scripts and dashboards. 18‑dec values that
+// can exceed 2⁵³ (weights, rates) are written as strings.
+// ================================================================
+use std::error::Error;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::token::TokenAccount;
+use client::{deserialize, fetch, fetch_many, PoolKind};
+use serde_json::{json, Value};
+use solana_rpc_client::rpc_client::RpcClient;
+use stable_pool::StablePool;
+use vault::PoolRegistration;
+use weighted_pool::Pool as WeightedPool;
+
+/// A pool account of either pool program.
+pub enum PoolAccount {
+    Weighted(WeightedPool),
+    Stable(StablePool),
+}
+
+impl PoolAccount {
+    fn vault_and_id(&self) -> (Pubkey, [u8; 32]) {
+        match self {
+            PoolAccount::Weighted(p) => (p.vault, p.pool_id),
+            PoolAccount::Stable(p) => (p.vault, p.pool_id),
+        }
+    }
+}
+
+/// A pool with its registration and the amount held by each custody
+/// account (`None` for tokens not yet registered).
+pub type PoolDump = (PoolAccount, PoolRegistration, Vec<Option<u64>>);
+
+/// Fetch `pool` and its Vault accounts; `None` if neither pool program
+/// owns it.
+pub fn fetch_pool(rpc: &RpcClient, pool: &Pubkey) -> Result<Option<PoolDump>, Box<dyn Error>> {
+    let account = rpc.get_account(pool)?;
+    let state = match PoolKind::from_program(&account.owner) {
+        Some(PoolKind::Weighted) => PoolAccount::Weighted(deserialize(pool, &account.data)?),
+        Some(PoolKind::Stable) => PoolAccount::Stable(deserialize(pool, &account.data)?),
+        None => return Ok(None),
+    };
+    let (vault_state, pool_id) = state.vault_and_id();
+    let registration: PoolRegistration = fetch(rpc, &client::pda::pool_registration(&vault_state, &pool_id))?;
+    let registered: Vec<Pubkey> =
+        registration.token_accounts.iter().filter(|a| **a != Pubkey::default()).copied().collect();
+    let mut custody = fetch_many::<TokenAccount>(rpc, &registered)?.into_iter();
+    let balances = registration
+        .token_accounts
+        .iter()
+        .map(|a| if *a == Pubkey::default() { None } else { custody.next().flatten().map(|t| t.amount) })
+        .collect();
+    Ok(Some((state, registration, balances)))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The JSON document of a pool at `address`.
+pub fn pool_json(address: &Pubkey, state: &PoolAccount, registration: &PoolRegistration, balances: &[Option<u64>]) -> Value {
+    let tokens: Vec<Value> = registration
+        .tokens
+        .iter()
+        .enumerate()
+        .map(|(i, mint)| {
+            let mut token = json!({
+                "mint": mint.to_string(),
+                "custody": registration.token_accounts.get(i).map(|a| a.to_string()),
+                "balance": balances.get(i).copied().flatten(),
+                "managed": registration.managed.get(i),
+                "asset_manager": registration.asset_managers.get(i).map(|a| a.to_string()),
+                "token_program": registration.token_programs.get(i).map(|p| p.to_string()),
+                "freeze_authority": registration.freeze_authorities.get(i).map(|a| a.to_string()),
+            });
+            match state {
+                PoolAccount::Weighted(p) => token["weight"] = json!(p.weights.get(i).map(|w| w.to_string())),
+                PoolAccount::Stable(p) => {
+                    token["decimals"] = json!(p.decimals.get(i));
+                    token["rate"] = json!(p.rates.get(i).map(|r| r.to_string()));
+                    token["rate_provider"] = json!(p.rate_providers.get(i).map(|r| r.to_string()));
+                }
+            }
+            token
+        })
+        .collect();
+    let mut doc = json!({
+        "address": address.to_string(),
+        "registration": {
+            "pool_program": registration.pool_program.to_string(),
+            "specialization": format!("{:?}", registration.specialization),
+            "paused": registration.paused,
+        },
+        "tokens": tokens,
+    });
+    let fields = match state {
+        PoolAccount::Weighted(p) => json!({
+            "kind": "weighted",
+            "vault": p.vault.to_string(),
+            "pool_id": hex(&p.pool_id),
+            "lp_mint": p.lp_mint.to_string(),
+            "swap_fee": p.swap_fee,
+            "total_bpt": p.total_bpt,
+        }),
+        PoolAccount::Stable(p) => json!({
+            "kind": "stable",
+            "vault": p.vault.to_string(),
+            "pool_id": hex(&p.pool_id),
+            "lp_mint": p.lp_mint.to_string(),
+            "lp_decimals": p.lp_decimals,
+            "amp": p.amp,
+            "swap_fee": p.swap_fee,
+            "total_bpt": p.total_bpt,
+            "bpt_index": p.bpt_index,
+            "rates_updated_slot": p.rates_updated_slot,
+        }),
+    };
+    if let (Value::Object(doc), Value::Object(fields)) = (&mut doc, fields) {
+        doc.extend(fields);
+    }
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::Specialization;
+    use weighted_pool::OracleAccumulators;
+
+    #[test]
+    fn writes_weights_as_strings_and_missing_custody_as_null() {
+        let tokens = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let pool = WeightedPool {
+            vault: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            weights: vec![800_000_000_000_000_000, 200_000_000_000_000_000],
+            swap_fee: 3_000_000_000_000_000,
+            total_bpt: 1_000,
+            pool_id: [0xab; 32],
+            oracle: OracleAccumulators::default(),
+            entered: false,
+            version: weighted_pool::POOL_VERSION,
+            reserved: [0; WeightedPool::RESERVED],
+        };
+        let registration = PoolRegistration {
+            vault: pool.vault,
+            pool_id: pool.pool_id,
+            pool_program: weighted_pool::ID,
+            pool: Pubkey::new_unique(),
+            specialization: Specialization::TwoToken,
+            tokens: tokens.clone(),
+            token_accounts: vec![Pubkey::new_unique(), Pubkey::default()],
+            asset_managers: vec![Pubkey::default(); 2],
+            managed: vec![0, 0],
+            paused: false,
+            token_programs: vec![anchor_spl::token::ID; 2],
+            freeze_authorities: vec![Pubkey::default(); 2],
+            lp_mint: Pubkey::new_unique(),
+        };
+        let doc = pool_json(&registration.pool, &PoolAccount::Weighted(pool), &registration, &[Some(500), None]);
+        assert_eq!(doc["kind"], "weighted");
+        assert_eq!(doc["pool_id"].as_str().unwrap().len(), 64);
+        assert_eq!(doc["registration"]["specialization"], "TwoToken");
+        assert_eq!(doc["tokens"][0]["weight"], "800000000000000000");
+        assert_eq!(doc["tokens"][0]["balance"], 500);
+        assert!(doc["tokens"][1]["balance"].is_null());
+    }
+}
@@ -0,0 +1,316 @@
+// Symmetric‑Solana ─ symmetric-cli
+// ================================================================
+// Pool lifecycle operations from the command line, against any
+// cluster, for operators:
+//   • vault set‑up, roles, protocol fees and pausing;
+//   • weighted and stable pool creation, swap fees and LP token
+//     metadata;
+//   • joins, exits and swaps from the payer's token accounts;
+//   • pool state dumps as JSON (dump).
+// Global options pick the cluster (`--url`), fee payer and signer
+// (`--keypair`) and commitment. Parsing lives in `args`, sending in
+// `cluster`, and each subcommand in `commands`.
+// ================================================================
+use std::error::Error;
+use std::process::ExitCode;
+
+use anchor_lang::prelude::Pubkey;
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use solana_commitment_config::CommitmentConfig;
+
+mod args;
+mod cluster;
+mod commands;
+mod dump;
+
+use args::{parse_fee, parse_pubkey, parse_role, parse_token_weight};
+use cluster::{default_keypair_path, Cluster};
+
+fn pubkey_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).value_name("ADDRESS").value_parser(parse_pubkey).required(true).help(help)
+}
+
+fn fee_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).value_name("FRACTION").value_parser(parse_fee).required(true).help(help)
+}
+
+fn amount_arg(name: &'static str, help: &'static str) -> Arg {
+    Arg::new(name).long(name).value_name("AMOUNT").value_parser(value_parser!(u64)).required(true).help(help)
+}
+
+fn lp_decimals_arg() -> Arg {
+    Arg::new("lp-decimals")
+        .long("lp-decimals")
+        .value_parser(value_parser!(u8))
+        .default_value("9")
+        .help("Decimals of the new LP mint")
+}
+
+fn cli() -> Command {
+    Command::new("symmetric-cli")
+        .about("Symmetric vault and pool operations")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .global(true)
+                .default_value("localhost")
+                .help("RPC URL or moniker: mainnet-beta, devnet, testnet, localhost"),
+        )
+        .arg(
+            Arg::new("keypair")
+                .long("keypair")
+                .short('k')
+                .global(true)
+                .help("Fee payer and signer [default: ~/.config/solana/id.json]"),
+        )
+        .arg(
+            Arg::new("commitment")
+                .long("commitment")
+                .global(true)
+                .value_parser(["processed", "confirmed", "finalized"])
+                .default_value("confirmed"),
+        )
+        .subcommand(Command::new("init-vault").about("Create the payer's Vault with its Authorizer and fees collector"))
+        .subcommand(
+            Command::new("grant-role")
+                .about("Grant an Authorizer role (the payer must be the admin)")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(Arg::new("role").long("role").value_parser(parse_role).required(true).help(
+                    "register-pool, set-swap-fee, pause, collect-fees, set-protocol-fees, manage-rewards, \
+                     manage-token-policy or transfer-ownership",
+                ))
+                .arg(pubkey_arg("account", "Account receiving the role"))
+                .arg(Arg::new("revoke").long("revoke").action(ArgAction::SetTrue).help("Revoke instead")),
+        )
+        .subcommand(
+            Command::new("set-protocol-fees")
+                .about("Set the protocol's share of swap fees and the flash loan fee")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(fee_arg("swap", "Protocol share of swap fees, e.g. 50%"))
+                .arg(fee_arg("flash-loan", "Flash loan fee, e.g. 0.05%")),
+        )
+        .subcommand(
+            Command::new("pause")
+                .about("Pause the Vault, or a single pool")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(pubkey_arg("pool", "Pause only this pool").required(false)),
+        )
+        .subcommand(
+            Command::new("unpause")
+                .about("Resume the Vault, or a single pool")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(pubkey_arg("pool", "Resume only this pool").required(false)),
+        )
+        .subcommand(
+            Command::new("create-weighted-pool")
+                .about("Create a canonical weighted pool and its custody accounts")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(
+                    Arg::new("token")
+                        .long("token")
+                        .value_name("MINT:WEIGHT")
+                        .value_parser(parse_token_weight)
+                        .action(ArgAction::Append)
+                        .required(true)
+                        .help("A token and its weight, e.g. <MINT>:80%; repeat per token"),
+                )
+                .arg(fee_arg("swap-fee", "Swap fee, e.g. 0.3%"))
+                .arg(lp_decimals_arg())
+                .arg(
+                    Arg::new("symbol")
+                        .long("symbol")
+                        .action(ArgAction::Append)
+                        .help("A token's symbol, in --token order, to name the LP token; repeat per token"),
+                )
+                .arg(Arg::new("metadata-uri").long("metadata-uri").default_value("").help("LP token metadata URI")),
+        )
+        .subcommand(
+            Command::new("create-stable-pool")
+                .about("Create the Vault's stable pool and its custody accounts")
+                .arg(pubkey_arg("vault", "Vault state"))
+                .arg(pubkey_arg("token", "A token mint; repeat per token").action(ArgAction::Append))
+                .arg(amount_arg("amp", "Amplification parameter"))
+                .arg(fee_arg("swap-fee", "Swap fee, e.g. 0.04%"))
+                .arg(Arg::new("composable").long("composable").action(ArgAction::SetTrue).help("Pre-mint the BPT as a pool token"))
+                .arg(
+                    pubkey_arg("rate-provider", "Rate account per token (11111111111111111111111111111111 for none)")
+                        .action(ArgAction::Append)
+                        .required(false),
+                )
+                .arg(lp_decimals_arg()),
+        )
+        .subcommand(
+            Command::new("set-swap-fee")
+                .about("Set a pool's swap fee (needs the set-swap-fee role)")
+                .arg(pubkey_arg("pool", "Pool state"))
+                .arg(fee_arg("fee", "Swap fee, e.g. 0.3%")),
+        )
+        .subcommand(
+            Command::new("set-lp-metadata")
+                .about("Set a weighted pool's LP token name, symbol and URI (needs the register-pool role)")
+                .arg(pubkey_arg("pool", "Pool state"))
+                .arg(Arg::new("name").long("name").required(true).help("Token name, at most 32 bytes"))
+                .arg(Arg::new("symbol").long("symbol").required(true).help("Token symbol, at most 10 bytes"))
+                .arg(Arg::new("uri").long("uri").default_value("").help("Metadata JSON URI")),
+        )
+        .subcommand(
+            Command::new("join")
+                .about("Join a pool with exact amounts of every token")
+                .arg(pubkey_arg("pool", "Pool state"))
+                .arg(
+                    Arg::new("amounts")
+                        .long("amounts")
+                        .value_name("AMOUNT,…")
+                        .value_delimiter(',')
+                        .value_parser(value_parser!(u64))
+                        .required(true)
+                        .help("Raw amount of each token, in pool order"),
+                )
+                .arg(amount_arg("min-bpt", "Least BPT out (stable pools)").required(false).default_value("0")),
+        )
+        .subcommand(
+            Command::new("exit")
+                .about("Exit a pool burning an exact BPT amount")
+                .arg(pubkey_arg("pool", "Pool state"))
+                .arg(amount_arg("bpt", "BPT to burn"))
+                .arg(
+                    Arg::new("min-amounts")
+                        .long("min-amounts")
+                        .value_name("AMOUNT,…")
+                        .value_delimiter(',')
+                        .value_parser(value_parser!(u64))
+                        .help("Least amount of each token out"),
+                ),
+        )
+        .subcommand(
+            Command::new("swap")
+                .about("Swap an exact amount in directly against a pool")
+                .arg(pubkey_arg("pool", "Pool state"))
+                .arg(pubkey_arg("mint-in", "Token sold"))
+                .arg(pubkey_arg("mint-out", "Token bought"))
+                .arg(amount_arg("amount", "Raw amount sold"))
+                .arg(amount_arg("min-out", "Least amount bought").required(false).default_value("0")),
+        )
+        .subcommand(
+            Command::new("dump")
+                .about("Print a pool, or all of a Vault's pools, as JSON")
+                .arg(pubkey_arg("pool", "Pool state").required(false))
+                .arg(pubkey_arg("vault", "Every weighted and stable pool of this Vault").required(false))
+                .group(clap::ArgGroup::new("target").args(["pool", "vault"]).required(true)),
+        )
+}
+
+fn pubkey(m: &ArgMatches, name: &str) -> Pubkey {
+    *m.get_one::<Pubkey>(name).expect("required")
+}
+
+fn pubkeys(m: &ArgMatches, name: &str) -> Vec<Pubkey> {
+    m.get_many::<Pubkey>(name).map(|v| v.copied().collect()).unwrap_or_default()
+}
+
+fn value<T: Clone + Send + Sync + 'static>(m: &ArgMatches, name: &str) -> T {
+    m.get_one::<T>(name).cloned().expect("required")
+}
+
+fn amounts(m: &ArgMatches, name: &str) -> Vec<u64> {
+    m.get_many::<u64>(name).map(|v| v.copied().collect()).unwrap_or_default()
+}
+
+fn run(matches: ArgMatches) -> Result<(), Box<dyn Error>> {
+    let commitment = match matches.get_one::<String>("commitment").map(String::as_str) {
+        Some("processed") => CommitmentConfig::processed(),
+        Some("finalized") => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    };
+    let keypair = matches.get_one::<String>("keypair").cloned().unwrap_or_else(default_keypair_path);
+    let c = Cluster::connect(&value::<String>(&matches, "url"), &keypair, commitment)?;
+
+    let (name, m) = matches.subcommand().expect("subcommand required");
+    match name {
+        "init-vault" => commands::init_vault(&c),
+        "grant-role" => commands::set_role(&c, pubkey(m, "vault"), value(m, "role"), pubkey(m, "account"), !m.get_flag("revoke")),
+        "set-protocol-fees" => commands::set_protocol_fees(&c, pubkey(m, "vault"), value(m, "swap"), value(m, "flash-loan")),
+        "pause" | "unpause" => commands::set_paused(&c, pubkey(m, "vault"), m.get_one("pool").copied(), name == "pause"),
+        "create-weighted-pool" => commands::create_weighted_pool(
+            &c,
+            pubkey(m, "vault"),
+            m.get_many::<(Pubkey, u128)>("token").expect("required").copied().collect(),
+            value(m, "swap-fee"),
+            value(m, "lp-decimals"),
+            m.get_many::<String>("symbol").map(|v| v.cloned().collect()).unwrap_or_default(),
+            value(m, "metadata-uri"),
+        ),
+        "set-lp-metadata" => commands::set_lp_metadata(&c, pubkey(m, "pool"), value(m, "name"), value(m, "symbol"), value(m, "uri")),
+        "create-stable-pool" => commands::create_stable_pool(
+            &c,
+            pubkey(m, "vault"),
+            pubkeys(m, "token"),
+            value(m, "amp"),
+            value(m, "swap-fee"),
+            m.get_flag("composable"),
+            pubkeys(m, "rate-provider"),
+            value(m, "lp-decimals"),
+        ),
+        "set-swap-fee" => commands::set_swap_fee(&c, pubkey(m, "pool"), value(m, "fee")),
+        "join" => commands::join(&c, pubkey(m, "pool"), amounts(m, "amounts"), value(m, "min-bpt")),
+        "exit" => commands::exit(&c, pubkey(m, "pool"), value(m, "bpt"), amounts(m, "min-amounts")),
+        "swap" => commands::swap(
+            &c,
+            pubkey(m, "pool"),
+            pubkey(m, "mint-in"),
+            pubkey(m, "mint-out"),
+            value(m, "amount"),
+            value(m, "min-out"),
+        ),
+        "dump" => commands::dump(&c, m.get_one("pool").copied(), m.get_one("vault").copied()),
+        _ => unreachable!("clap rejects unknown subcommands"),
+    }
+}
+
+fn main() -> ExitCode {
+    match run(cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_subcommand() {
+        cli().debug_assert();
+        let mint = Pubkey::new_unique();
+        let matches = cli()
+            .try_get_matches_from([
+                "symmetric-cli",
+                "create-weighted-pool",
+                "--vault",
+                &mint.to_string(),
+                "--token",
+                &format!("{mint}:80%"),
+                "--token",
+                &format!("{mint}:20%"),
+                "--swap-fee",
+                "0.3%",
+                "-u",
+                "devnet",
+            ])
+            .unwrap();
+        assert_eq!(value::<String>(&matches, "url"), "devnet");
+        let (_, m) = matches.subcommand().unwrap();
+        assert_eq!(m.get_many::<(Pubkey, u128)>("token").unwrap().count(), 2);
+        assert_eq!(value::<u64>(m, "swap-fee"), 3_000_000_000_000_000);
+        assert_eq!(value::<u8>(m, "lp-decimals"), 9);
+
+        let join = cli().try_get_matches_from(["symmetric-cli", "join", "--pool", &mint.to_string(), "--amounts", "1,2,3"]).unwrap();
+        assert_eq!(amounts(join.subcommand().unwrap().1, "amounts"), vec![1, 2, 3]);
+        assert!(cli().try_get_matches_from(["symmetric-cli", "dump"]).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+// Symmetric‑Solana ─ Client account fetching
+// ================================================================
+// Reads program accounts over RPC and deserializes them with the
+// programs' own Anchor types (discriminator checked), and loads the
+// clients' context – a pool's kind, registration, tokens and their
+// transfer hooks – from chain state.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_hook, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Mint;
+use solana_rpc_client::rpc_client::RpcClient;
+use stable_pool::StablePool;
+use vault::{PoolRegistration, PoolRegistryPage, ProtocolFeesCollector, RegistryEntry, VaultState};
+use weighted_pool::Pool as WeightedPool;
+
+use crate::pool::{PoolClient, PoolKind};
+use crate::vault::VaultClient;
+use crate::{pda, ClientError};
+
+/// Deserialize the account at `address` from its raw data.
+pub fn deserialize<T: AccountDeserialize>(address: &Pubkey, data: &[u8]) -> Result<T, ClientError> {
+    T::try_deserialize(&mut &data[..]).map_err(|_| ClientError::Deserialize(*address))
+}
+
+/// Fetch and deserialize one account; missing accounts are an error.
+pub fn fetch<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T, ClientError> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    deserialize(address, &account.data)
+}
+
+/// Fetch and deserialize several accounts in one request; `None` where
+/// an account does not exist.
+pub fn fetch_many<T: AccountDeserialize>(rpc: &RpcClient, addresses: &[Pubkey]) -> Result<Vec<Option<T>>, ClientError> {
+    rpc.get_multiple_accounts(addresses)?
+        .into_iter()
+        .zip(addresses)
+        .map(|(account, address)| account.map(|a| deserialize(address, &a.data)).transpose())
+        .collect()
+}
+
+/// Every pool registered with `vault_state`, walking its registry pages.
+pub fn fetch_registered_pools(rpc: &RpcClient, vault_state: &Pubkey) -> Result<Vec<RegistryEntry>, ClientError> {
+    let mut entries = Vec::new();
+    let mut index = 0;
+    loop {
+        let address = pda::pool_registry_page(vault_state, index);
+        let Some(page) = fetch_many::<PoolRegistryPage>(rpc, &[address])?.pop().flatten() else {
+            break;
+        };
+        entries.extend(page.entries);
+        if page.next == Pubkey::default() {
+            break;
+        }
+        index += 1;
+    }
+    Ok(entries)
+}
+
+impl VaultClient {
+    pub fn fetch_state(&self, rpc: &RpcClient) -> Result<VaultState, ClientError> {
+        fetch(rpc, &self.vault_state)
+    }
+
+    pub fn fetch_fees_collector(&self, rpc: &RpcClient) -> Result<ProtocolFeesCollector, ClientError> {
+        fetch(rpc, &self.fees_collector())
+    }
+}
+
+impl PoolClient {
+    /// Load the client of the pool at `pool`, whichever pool program owns it.
+    pub fn fetch(rpc: &RpcClient, pool: &Pubkey) -> Result<Self, ClientError> {
+        let account = rpc
+            .get_account_with_commitment(pool, rpc.commitment())?
+            .value
+            .ok_or(ClientError::AccountNotFound(*pool))?;
+        let kind = PoolKind::from_program(&account.owner).ok_or(ClientError::UnknownPool(*pool))?;
+        let (vault_state, pool_id, lp_mint) = match kind {
+            PoolKind::Weighted => {
+                let state: WeightedPool = deserialize(pool, &account.data)?;
+                (state.vault, state.pool_id, state.lp_mint)
+            }
+            PoolKind::Stable => {
+                let state: StablePool = deserialize(pool, &account.data)?;
+                (state.vault, state.pool_id, state.lp_mint)
+            }
+        };
+        let registration: PoolRegistration = fetch(rpc, &pda::pool_registration(&vault_state, &pool_id))?;
+        let collector = VaultClient::new(vault_state).fetch_fees_collector(rpc)?;
+        let mint = rpc
+            .get_account_with_commitment(&lp_mint, rpc.commitment())?
+            .value
+            .ok_or(ClientError::AccountNotFound(lp_mint))?;
+        let transfer_hook_accounts = transfer_hook_accounts(rpc, &registration.tokens)?;
+        let token_programs = if registration.token_programs.iter().all(|p| *p == mint.owner) {
+            vec![]
+        } else {
+            registration.token_programs
+        };
+        Ok(PoolClient {
+            kind,
+            pool: *pool,
+            vault_state,
+            pool_id,
+            lp_mint,
+            tokens: registration.tokens,
+            specialization: registration.specialization,
+            protocol_swap_fee: collector.swap_fee_percentage > 0,
+            token_program: mint.owner,
+            token_programs,
+            transfer_hook_accounts,
+        })
+    }
+}
+
+/// The hook programs of the hooked mints among `mints`, each followed by
+/// its extra‑account‑meta list when it has one. Accounts the lists
+/// resolve to are not included.
+pub fn transfer_hook_accounts(rpc: &RpcClient, mints: &[Pubkey]) -> Result<Vec<Pubkey>, ClientError> {
+    let mut hooks: Vec<(Pubkey, Pubkey)> = Vec::new();
+    for (mint, account) in mints.iter().zip(rpc.get_multiple_accounts(mints)?) {
+        let state = account.as_ref().and_then(|a| StateWithExtensions::<Mint>::unpack(&a.data).ok());
+        if let Some(program) = state.and_then(|s| transfer_hook::get_program_id(&s)) {
+            hooks.push((program, pda::extra_account_metas(mint, &program)));
+        }
+    }
+    let lists: Vec<Pubkey> = hooks.iter().map(|(_, list)| *list).collect();
+    let mut accounts = Vec::new();
+    for ((program, list), exists) in hooks.into_iter().zip(rpc.get_multiple_accounts(&lists)?) {
+        for address in [Some(program), exists.map(|_| list)].into_iter().flatten() {
+            if !accounts.contains(&address) {
+                accounts.push(address);
+            }
+        }
+    }
+    Ok(accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+
+    #[test]
+    fn checks_the_discriminator() {
+        let address = Pubkey::new_unique();
+        let collector = ProtocolFeesCollector { vault: address, swap_fee_percentage: 5, flash_loan_fee_percentage: 0 };
+        let mut data = Vec::new();
+        collector.try_serialize(&mut data).unwrap();
+
+        let read: ProtocolFeesCollector = deserialize(&address, &data).unwrap();
+        assert_eq!(read.swap_fee_percentage, 5);
+        assert!(matches!(deserialize::<VaultState>(&address, &data), Err(ClientError::Deserialize(a)) if a == address));
+    }
+}
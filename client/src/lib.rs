@@ -0,0 +1,84 @@
+// Symmetric‑Solana ─ Client SDK
+// ================================================================
+// Typed Rust access to the programs for integrators:
+//   • PDA derivation for every Vault account and pool authority (pda).
+//   • Vault instruction builders, batch swaps included (vault).
+//   • Weighted and stable pool joins, exits, swaps and admin (pool).
+//   • Account fetching and deserialization over RPC (fetch).
+//   • Address lookup tables of a pool's accounts and v0 messages
+//     using them (lookup).
+// Builders derive every PDA and lay out `remaining_accounts` as each
+// instruction documents; anything else goes through [`build`] with the
+// program's generated `accounts` and `instruction` types.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_message::CompileError;
+use solana_rpc_client_api::client_error::Error as RpcError;
+
+pub mod fetch;
+pub mod lookup;
+pub mod pda;
+pub mod pool;
+pub mod vault;
+
+pub use fetch::{deserialize, fetch, fetch_many, fetch_registered_pools, transfer_hook_accounts};
+pub use lookup::{compile_v0, create_lookup_table, extend_lookup_table, fetch_lookup_table};
+pub use pool::{lp_token_name, PoolClient, PoolKind};
+pub use vault::{AssetAccounts, VaultClient};
+
+/// An instruction of `program_id` from its generated accounts and
+/// instruction data, followed by `remaining` accounts.
+pub fn build(
+    program_id: Pubkey,
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining: Vec<AccountMeta>,
+) -> Instruction {
+    let mut metas = accounts.to_account_metas(None);
+    metas.extend(remaining);
+    Instruction { program_id, accounts: metas, data: data.data() }
+}
+
+/// Why an instruction could not be built or an account read.
+#[derive(Debug)]
+pub enum ClientError {
+    /// Account lists of different lengths, or an index past their end
+    LengthMismatch,
+    /// A batch swap step whose PoolId is not its pool's
+    PoolMismatch,
+    /// The pool program has no such instruction or argument
+    Unsupported,
+    AccountNotFound(Pubkey),
+    /// Not an account of the expected type
+    Deserialize(Pubkey),
+    /// Not owned by a pool program the client knows
+    UnknownPool(Pubkey),
+    /// Instructions that do not compile into a v0 message
+    Compile(CompileError),
+    Rpc(Box<RpcError>),
+}
+
+impl From<RpcError> for ClientError {
+    fn from(err: RpcError) -> Self {
+        ClientError::Rpc(Box::new(err))
+    }
+}
+
+impl core::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ClientError::LengthMismatch => f.write_str("account lists do not match"),
+            ClientError::PoolMismatch => f.write_str("batch swap step does not match its pool"),
+            ClientError::Unsupported => f.write_str("not supported by this pool"),
+            ClientError::AccountNotFound(a) => write!(f, "account {a} not found"),
+            ClientError::Deserialize(a) => write!(f, "account {a} has unexpected data"),
+            ClientError::UnknownPool(a) => write!(f, "account {a} is not a known pool"),
+            ClientError::Compile(err) => write!(f, "message: {err}"),
+            ClientError::Rpc(err) => write!(f, "rpc: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
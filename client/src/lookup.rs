@@ -0,0 +1,176 @@
+// Symmetric‑Solana ─ Client address lookup tables
+// ================================================================
+// Joins and exits of 6–8 token pools list more accounts than a legacy
+// transaction fits. A lookup table (ALT) of the pool's shared accounts
+// – programs, Vault PDAs, mints, custody and fee accounts – lets a v0
+// transaction refer to each by a one‑byte index:
+//   • `PoolClient::lookup_table_addresses` lists them;
+//   • `create_lookup_table` / `extend_lookup_table` build the table,
+//     `fetch_lookup_table` reads it back;
+//   • `compile_v0` compiles instructions against tables into a
+//     versioned message ready to sign.
+// A table's new addresses are usable from the slot after the extend.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::Hash;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::system_program;
+use solana_address_lookup_table_interface::instruction as alt;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_message::{v0, AddressLookupTableAccount, VersionedMessage};
+use solana_rpc_client::rpc_client::RpcClient;
+
+use crate::pool::{PoolClient, PoolKind};
+use crate::{pda, ClientError};
+
+/// Addresses per extend instruction, so each fits one transaction.
+pub const EXTEND_BATCH: usize = 20;
+
+impl PoolClient {
+    /// Every account of the pool's joins, exits and swaps that is not
+    /// the user's, deduplicated.
+    pub fn lookup_table_addresses(&self) -> Vec<Pubkey> {
+        let mut addresses = vec![
+            self.program_id(),
+            vault::ID,
+            self.token_program,
+            system_program::ID,
+            self.pool,
+            self.vault_state,
+            self.lp_mint,
+            self.authority(),
+            self.registration(),
+            pda::fees_collector(&self.vault_state),
+            pda::protocol_fee_account(&self.vault_state, &self.lp_mint),
+        ];
+        if self.kind == PoolKind::Weighted {
+            addresses.push(self.locked_bpt_account());
+        }
+        for mint in &self.tokens {
+            for address in [*mint, self.custody(mint), pda::protocol_fee_account(&self.vault_state, mint)] {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+        for address in self.transfer_hook_accounts.iter().chain(&self.other_token_programs()) {
+            if !addresses.contains(address) {
+                addresses.push(*address);
+            }
+        }
+        addresses
+    }
+}
+
+/// A new lookup table of `authority`, funded by `payer`, and its address;
+/// `recent_slot` must be a recent slot (e.g. `getSlot`).
+pub fn create_lookup_table(authority: &Pubkey, payer: &Pubkey, recent_slot: u64) -> (Instruction, Pubkey) {
+    alt::create_lookup_table(*authority, *payer, recent_slot)
+}
+
+/// Instructions adding the `addresses` not already in `existing` to the
+/// table, one per transaction, in batches of [`EXTEND_BATCH`].
+pub fn extend_lookup_table(
+    table: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    existing: &[Pubkey],
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut new: Vec<Pubkey> = Vec::new();
+    for address in addresses {
+        if !existing.contains(address) && !new.contains(address) {
+            new.push(*address);
+        }
+    }
+    new.chunks(EXTEND_BATCH)
+        .map(|batch| alt::extend_lookup_table(*table, *authority, Some(*payer), batch.to_vec()))
+        .collect()
+}
+
+/// The table at `address` with its addresses, as `compile_v0` takes it.
+pub fn fetch_lookup_table(rpc: &RpcClient, address: &Pubkey) -> Result<AddressLookupTableAccount, ClientError> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    let table = AddressLookupTable::deserialize(&account.data).map_err(|_| ClientError::Deserialize(*address))?;
+    Ok(AddressLookupTableAccount { key: *address, addresses: table.addresses.to_vec() })
+}
+
+/// A v0 message of `ixs` paid by `payer`, loading every non‑signer
+/// account it can from `tables`.
+pub fn compile_v0(
+    payer: &Pubkey,
+    ixs: &[Instruction],
+    tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> Result<VersionedMessage, ClientError> {
+    let message = v0::Message::try_compile(payer, ixs, tables, recent_blockhash).map_err(ClientError::Compile)?;
+    Ok(VersionedMessage::V0(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::{token, token_2022};
+    use common::{PoolId, Specialization};
+    use solana_message::Message;
+
+    /// Largest serialized transaction: an IPv6 packet less headers.
+    const PACKET_DATA_SIZE: usize = 1232;
+
+    /// Signatures count, each signature, then the message.
+    fn transaction_size(message: Vec<u8>, signers: usize) -> usize {
+        1 + 64 * signers + message.len()
+    }
+
+    fn pool(tokens: usize) -> PoolClient {
+        let mut tokens: Vec<Pubkey> = (0..tokens).map(|_| Pubkey::new_unique()).collect();
+        tokens.sort();
+        PoolClient {
+            kind: PoolKind::Weighted,
+            pool: Pubkey::new_unique(),
+            vault_state: Pubkey::new_unique(),
+            pool_id: PoolId::default(),
+            lp_mint: Pubkey::new_unique(),
+            tokens,
+            specialization: Specialization::MinimalSwapInfo,
+            protocol_swap_fee: true,
+            token_program: token::ID,
+            token_programs: vec![],
+            transfer_hook_accounts: vec![],
+        }
+    }
+
+    #[test]
+    fn eight_token_exit_fits_only_with_a_lookup_table() {
+        // a Token‑2022 exit also passes every mint for checked transfers
+        let mut pool = pool(8);
+        pool.token_program = token_2022::ID;
+        let user = Pubkey::new_unique();
+        let user_tokens: Vec<Pubkey> = pool.tokens.iter().map(|_| Pubkey::new_unique()).collect();
+        let exit = pool.exit_exact_bpt_in_for_tokens_out(&user, Pubkey::new_unique(), &user_tokens, 1, vec![0; user_tokens.len()]).unwrap();
+
+        let legacy = Message::new_with_blockhash(std::slice::from_ref(&exit), Some(&user), &Hash::default());
+        assert!(transaction_size(legacy.serialize(), 1) > PACKET_DATA_SIZE);
+
+        let addresses = pool.lookup_table_addresses();
+        assert_eq!(addresses.len(), 12 + 3 * 8);
+        let table = AddressLookupTableAccount { key: Pubkey::new_unique(), addresses };
+        let v0 = compile_v0(&user, &[exit], &[table], Hash::default()).unwrap();
+        // only the user's accounts and the invoked program stay static
+        assert_eq!(v0.static_account_keys().len(), 2 + 8 + 1);
+        assert!(transaction_size(v0.serialize(), 1) <= PACKET_DATA_SIZE);
+    }
+
+    #[test]
+    fn extends_with_new_addresses_in_batches() {
+        let (table, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let addresses = pool(8).lookup_table_addresses();
+        let ixs = extend_lookup_table(&table, &authority, &authority, &addresses[..5], &addresses);
+        assert_eq!(ixs.len(), 2);
+        assert!(ixs.iter().all(|ix| ix.program_id == solana_address_lookup_table_interface::program::ID));
+        assert!(extend_lookup_table(&table, &authority, &authority, &addresses, &addresses).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+// Symmetric‑Solana ─ Client PDAs
+// ================================================================
+// Addresses of every Vault PDA (see `common::seeds`) and of the pool
+// authorities, derived the way the programs check them.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use common::{seeds, PoolId};
+
+fn vault_pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &vault::ID).0
+}
+
+/// ["vault-state", creator]
+pub fn vault_state(creator: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::VAULT_STATE, creator.as_ref()])
+}
+
+/// ["vault-authority", vault_state]: owns every custody account
+pub fn vault_authority(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::VAULT_AUTHORITY, vault_state.as_ref()])
+}
+
+/// ["asset-manager-authority", vault_state]: signs the pools' managed
+/// transfers for `withdraw_managed`
+pub fn asset_manager_authority(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::ASSET_MANAGER_AUTHORITY, vault_state.as_ref()])
+}
+
+/// ["authorizer", vault_state]
+pub fn authorizer(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::AUTHORIZER, vault_state.as_ref()])
+}
+
+/// ["fees-collector", vault_state]
+pub fn fees_collector(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::FEES_COLLECTOR, vault_state.as_ref()])
+}
+
+/// ["protocol-fees", fees_collector, mint]: the collector's `mint` account
+pub fn protocol_fee_account(vault_state: &Pubkey, mint: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::PROTOCOL_FEES, fees_collector(vault_state).as_ref(), mint.as_ref()])
+}
+
+/// ["pool-registration", vault_state, pool_id]
+pub fn pool_registration(vault_state: &Pubkey, pool_id: &PoolId) -> Pubkey {
+    vault_pda(&[seeds::POOL_REGISTRATION, vault_state.as_ref(), pool_id.as_ref()])
+}
+
+/// ["pool-token", pool_registration, mint]: a pool's custody account
+pub fn pool_token(pool_registration: &Pubkey, mint: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::POOL_TOKEN, pool_registration.as_ref(), mint.as_ref()])
+}
+
+/// ["pool-registry", vault_state, index (u32 LE)]
+pub fn pool_registry_page(vault_state: &Pubkey, index: u32) -> Pubkey {
+    vault_pda(&[seeds::POOL_REGISTRY, vault_state.as_ref(), &index.to_le_bytes()])
+}
+
+/// ["relayer-approval", vault_state, user, relayer]
+pub fn relayer_approval(vault_state: &Pubkey, user: &Pubkey, relayer: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::RELAYER_APPROVAL, vault_state.as_ref(), user.as_ref(), relayer.as_ref()])
+}
+
+/// ["permit-nonce", vault_state, user]
+pub fn permit_nonce(vault_state: &Pubkey, user: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::PERMIT_NONCE, vault_state.as_ref(), user.as_ref()])
+}
+
+/// ["transfer-hook-allowlist", vault_state]
+pub fn transfer_hook_allowlist(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::TRANSFER_HOOK_ALLOWLIST, vault_state.as_ref()])
+}
+
+/// ["mint-extension-overrides", vault_state]
+pub fn mint_extension_overrides(vault_state: &Pubkey) -> Pubkey {
+    vault_pda(&[seeds::MINT_EXTENSION_OVERRIDES, vault_state.as_ref()])
+}
+
+/// ["extra-account-metas", mint] under a transfer‑hook program: the
+/// extra accounts its hook needs, per the transfer‑hook interface
+pub fn extra_account_metas(mint: &Pubkey, hook_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], hook_program).0
+}
+
+/// ["lp-mint-authority", pool] under the pool program: holds the pool's
+/// tokens and mints its BPT
+pub fn pool_authority(pool: &Pubkey, pool_program: &Pubkey) -> Pubkey {
+    common::pool_authority(pool, pool_program)
+}
+
+/// The associated LP token account of a pool authority: where a weighted
+/// pool's first join locks MINIMUM_BPT
+pub fn locked_bpt_account(pool_authority: &Pubkey, lp_mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    anchor_spl::associated_token::get_associated_token_address_with_program_id(pool_authority, lp_mint, token_program)
+}
+
+/// ["metadata", metadata_program, mint] under the token metadata
+/// program: a mint's name, symbol and URI
+pub fn token_metadata(mint: &Pubkey) -> Pubkey {
+    let program = anchor_spl::metadata::ID;
+    Pubkey::find_program_address(&[b"metadata", program.as_ref(), mint.as_ref()], &program).0
+}
+
+/// ["pool-state", vault_state, config_hash] under the weighted pool
+/// program: the canonical pool of one configuration
+pub fn canonical_weighted_pool(vault_state: &Pubkey, config_hash: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool-state", vault_state.as_ref(), config_hash], &weighted_pool::ID).0
+}
+
+/// ["pool-state", vault_state] under the stable pool program
+pub fn stable_pool(vault_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"pool-state", vault_state.as_ref()], &stable_pool::ID).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_vaults_own_derivations() {
+        let vault = Pubkey::new_unique();
+        assert_eq!(pool_registry_page(&vault, 3), vault::PoolRegistryPage::address(&vault, 3, &vault::ID));
+        assert_ne!(protocol_fee_account(&vault, &Pubkey::new_unique()), fees_collector(&vault));
+        assert_ne!(asset_manager_authority(&vault), vault_authority(&vault));
+    }
+}
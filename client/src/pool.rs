@@ -0,0 +1,799 @@
+// Symmetric‑Solana ─ Pool client
+// ================================================================
+// Instruction builders for the weighted and stable pool programs.
+// A `PoolClient` holds what the instructions need to know about one
+// pool – its program, Vault, PoolId, LP mint and tokens – and lays out
+// the per‑token `remaining_accounts` each instruction documents:
+//   • joins and proportional exits: [user_tok_i, vault_tok_i] pairs;
+//   • stable swaps: every custody account, as General pools price
+//     against all balances;
+//   • weighted pools on Token‑2022: the mints and transfer‑hook
+//     accounts last, so transfers are checked as transfer‑fee and hook
+//     mints require;
+//   • LP token metadata, named after the tokens by `lp_token_name`.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::{token, token_2022};
+use common::{canonical_pool_hash, derive_pool_id, PoolId, Specialization};
+use vault::VaultState;
+
+use crate::{build, pda, ClientError};
+
+/// Pool programs the client builds instructions for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    Weighted,
+    Stable,
+}
+
+impl PoolKind {
+    pub fn program_id(&self) -> Pubkey {
+        match self {
+            PoolKind::Weighted => weighted_pool::ID,
+            PoolKind::Stable => stable_pool::ID,
+        }
+    }
+
+    /// The kind of pool a program owns.
+    pub fn from_program(program_id: &Pubkey) -> Option<Self> {
+        [PoolKind::Weighted, PoolKind::Stable].into_iter().find(|k| k.program_id() == *program_id)
+    }
+}
+
+/// Builds instructions against one pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolClient {
+    pub kind: PoolKind,
+    pub pool: Pubkey,
+    pub vault_state: Pubkey,
+    pub pool_id: PoolId,
+    pub lp_mint: Pubkey,
+    /// Registered tokens, in pool order (a composable pool's BPT included)
+    pub tokens: Vec<Pubkey>,
+    pub specialization: Specialization,
+    /// The Vault takes a protocol share of swap fees; weighted exits then
+    /// pay it in tokens and need the collector's accounts
+    pub protocol_swap_fee: bool,
+    /// SPL Token or Token‑2022: owns the LP mint and every pool token,
+    /// unless `token_programs` says otherwise
+    pub token_program: Pubkey,
+    /// Token program of each registered token, in pool order, when the
+    /// pool mixes SPL Token and Token‑2022 mints; empty otherwise
+    pub token_programs: Vec<Pubkey>,
+    /// Hook programs of the pool's hooked Token‑2022 tokens and any extra
+    /// accounts their hooks need, passed along with every checked transfer
+    pub transfer_hook_accounts: Vec<Pubkey>,
+}
+
+impl PoolClient {
+    pub fn program_id(&self) -> Pubkey {
+        self.kind.program_id()
+    }
+
+    pub fn registration(&self) -> Pubkey {
+        pda::pool_registration(&self.vault_state, &self.pool_id)
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        pda::pool_authority(&self.pool, &self.program_id())
+    }
+
+    /// The authority's own LP token account, where a weighted pool's first
+    /// join locks MINIMUM_BPT.
+    pub fn locked_bpt_account(&self) -> Pubkey {
+        pda::locked_bpt_account(&self.authority(), &self.lp_mint, &self.token_program)
+    }
+
+    /// The Vault custody account of `mint` for this pool.
+    pub fn custody(&self, mint: &Pubkey) -> Pubkey {
+        pda::pool_token(&self.registration(), mint)
+    }
+
+    /// Tokens deposited by joins and paid out by exits: all but a
+    /// composable pool's own BPT.
+    pub fn join_tokens(&self) -> Vec<Pubkey> {
+        self.tokens.iter().filter(|t| **t != self.lp_mint).copied().collect()
+    }
+
+    /* ---------------------------------------------------------------
+       Creation
+    ---------------------------------------------------------------- */
+    /// The canonical weighted pool of `tokens` (ascending) at `weights`
+    /// and `swap_fee` in the Vault at `vault_state`, and the instruction
+    /// creating and registering it. `authority` holds the `RegisterPool`
+    /// role; `lp_mint` must be a fresh mint of the pool's authority.
+    /// Custody accounts are created by a later `register_tokens`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_weighted(
+        vault_state: Pubkey,
+        vault: &VaultState,
+        payer: &Pubkey,
+        authority: &Pubkey,
+        lp_mint: Pubkey,
+        tokens: Vec<Pubkey>,
+        weights: Vec<u128>,
+        swap_fee: u64,
+    ) -> Result<(Self, Instruction), ClientError> {
+        let config_hash = canonical_pool_hash(&tokens, &weights, swap_fee).ok_or(ClientError::LengthMismatch)?;
+        let pool = pda::canonical_weighted_pool(&vault_state, &config_hash);
+        let specialization = if tokens.len() == 2 { Specialization::TwoToken } else { Specialization::MinimalSwapInfo };
+        let client = Self::registered(PoolKind::Weighted, pool, vault_state, vault, lp_mint, tokens, specialization);
+        let remaining = client.tokens.iter().map(|mint| AccountMeta::new_readonly(*mint, false)).collect();
+        let ix = build(
+            weighted_pool::ID,
+            weighted_pool::accounts::InitializeCanonicalPool {
+                payer: *payer,
+                authority: *authority,
+                vault_state,
+                authorizer: pda::authorizer(&vault_state),
+                pool_registration: client.registration(),
+                registry_page: pda::pool_registry_page(&vault_state, vault.registry_tail),
+                vault_program: vault::ID,
+                lp_mint,
+                lp_mint_authority: client.authority(),
+                pool,
+                system_program: system_program::ID,
+            },
+            weighted_pool::instruction::InitializeCanonicalPool { config_hash, weights, swap_fee },
+            remaining,
+        );
+        Ok((client, ix))
+    }
+
+    /// The Vault's stable pool of `tokens` and the instruction creating
+    /// and registering it; `payer` holds the `RegisterPool` role. A
+    /// composable pool lists its BPT last and gets its BPT custody here;
+    /// other custody accounts are created by a later `register_tokens`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_stable(
+        vault_state: Pubkey,
+        vault: &VaultState,
+        payer: &Pubkey,
+        lp_mint: Pubkey,
+        mut tokens: Vec<Pubkey>,
+        amplification: u64,
+        swap_fee: u64,
+        composable: bool,
+        rate_providers: Vec<Pubkey>,
+    ) -> (Self, Instruction) {
+        let pool = pda::stable_pool(&vault_state);
+        let remaining = tokens.iter().map(|mint| AccountMeta::new_readonly(*mint, false)).collect();
+        if composable {
+            tokens.push(lp_mint);
+        }
+        let client = Self::registered(PoolKind::Stable, pool, vault_state, vault, lp_mint, tokens, Specialization::General);
+        let ix = build(
+            stable_pool::ID,
+            stable_pool::accounts::InitializePool {
+                payer: *payer,
+                vault_state,
+                authorizer: pda::authorizer(&vault_state),
+                pool_registration: client.registration(),
+                registry_page: pda::pool_registry_page(&vault_state, vault.registry_tail),
+                vault_program: vault::ID,
+                lp_mint,
+                lp_mint_authority: client.authority(),
+                pool,
+                vault_authority: composable.then(|| pda::vault_authority(&vault_state)),
+                pool_bpt: composable.then(|| client.custody(&lp_mint)),
+                token_program: token::ID,
+                system_program: system_program::ID,
+            },
+            stable_pool::instruction::InitializePool { amplification, swap_fee, composable, rate_providers },
+            remaining,
+        );
+        (client, ix)
+    }
+
+    /// Create [`PoolClient::locked_bpt_account`], paid for by `payer`,
+    /// unless it exists; a weighted pool's first join needs it.
+    pub fn create_locked_bpt_account(&self, payer: &Pubkey) -> Instruction {
+        create_associated_token_account_idempotent(payer, &self.authority(), &self.lp_mint, &self.token_program)
+    }
+
+    /// The client of `pool` as the Vault will register it next: its
+    /// PoolId is derived from the Vault's current pool count.
+    fn registered(
+        kind: PoolKind,
+        pool: Pubkey,
+        vault_state: Pubkey,
+        vault: &VaultState,
+        lp_mint: Pubkey,
+        tokens: Vec<Pubkey>,
+        specialization: Specialization,
+    ) -> Self {
+        PoolClient {
+            kind,
+            pool,
+            vault_state,
+            pool_id: derive_pool_id(&vault_state, &pool, specialization, vault.pool_count),
+            lp_mint,
+            tokens,
+            specialization,
+            protocol_swap_fee: false,
+            token_program: token::ID,
+            token_programs: vec![],
+            transfer_hook_accounts: vec![],
+        }
+    }
+
+    /// Token programs of the pool's tokens other than `token_program`:
+    /// transfers of a mixed pool need them passed along with the mints.
+    pub fn other_token_programs(&self) -> Vec<Pubkey> {
+        let mut programs: Vec<Pubkey> = Vec::new();
+        for program in &self.token_programs {
+            if *program != self.token_program && !programs.contains(program) {
+                programs.push(*program);
+            }
+        }
+        programs
+    }
+
+    /* ---------------------------------------------------------------
+       Joins & exits
+    ---------------------------------------------------------------- */
+    /// Deposit `amounts_in` of [`PoolClient::join_tokens`] from
+    /// `user_tokens`. Weighted pools take no BPT minimum: pass 0.
+    pub fn join_exact_tokens_in_for_bpt_out(
+        &self,
+        user: &Pubkey,
+        user_lp: Pubkey,
+        user_tokens: &[Pubkey],
+        amounts_in: Vec<u64>,
+        minimum_bpt_out: u64,
+    ) -> Result<Instruction, ClientError> {
+        let remaining = self.token_pairs(user_tokens)?;
+        if amounts_in.len() != user_tokens.len() {
+            return Err(ClientError::LengthMismatch);
+        }
+        Ok(match self.kind {
+            PoolKind::Weighted if minimum_bpt_out > 0 => return Err(ClientError::Unsupported),
+            PoolKind::Weighted => build(
+                weighted_pool::ID,
+                self.weighted_pool_context(user, user_lp),
+                weighted_pool::instruction::JoinExactTokensInForBptOut { amounts_in },
+                [remaining, self.checked_mints(&self.join_tokens())].concat(),
+            ),
+            PoolKind::Stable => build(
+                stable_pool::ID,
+                self.stable_pool_context(user, user_lp),
+                stable_pool::instruction::JoinExactTokensInForBptOut { amounts_in, minimum_bpt_out },
+                remaining,
+            ),
+        })
+    }
+
+    /// Burn `bpt_in` for a proportional share, paid to `user_tokens`, of
+    /// at least `minimum_amounts_out` of each token.
+    pub fn exit_exact_bpt_in_for_tokens_out(
+        &self,
+        user: &Pubkey,
+        user_lp: Pubkey,
+        user_tokens: &[Pubkey],
+        bpt_in: u64,
+        minimum_amounts_out: Vec<u64>,
+    ) -> Result<Instruction, ClientError> {
+        let mut remaining = self.token_pairs(user_tokens)?;
+        if minimum_amounts_out.len() != user_tokens.len() {
+            return Err(ClientError::LengthMismatch);
+        }
+        Ok(match self.kind {
+            PoolKind::Weighted => {
+                remaining.extend(self.checked_mints(&self.join_tokens()));
+                build(
+                    weighted_pool::ID,
+                    self.weighted_pool_context(user, user_lp),
+                    weighted_pool::instruction::ExitExactBptInForTokensOut { bpt_in, minimum_amounts_out },
+                    remaining,
+                )
+            }
+            PoolKind::Stable => {
+                build(
+                    stable_pool::ID,
+                    self.stable_pool_context(user, user_lp),
+                    stable_pool::instruction::ExitExactBptInForTokensOut { bpt_in, minimum_amounts_out },
+                    remaining,
+                )
+            }
+        })
+    }
+
+    /// Burn `bpt_in` for a proportional share, fee free, of every token
+    /// whose custody and user account are not frozen (weighted pools only).
+    /// Fails unless the pool or Vault is paused or some token is skipped.
+    pub fn recovery_exit(&self, user: &Pubkey, user_lp: Pubkey, user_tokens: &[Pubkey], bpt_in: u64) -> Result<Instruction, ClientError> {
+        let remaining = self.token_pairs(user_tokens)?;
+        match self.kind {
+            PoolKind::Weighted => Ok(build(
+                weighted_pool::ID,
+                self.weighted_pool_context(user, user_lp),
+                weighted_pool::instruction::RecoveryExit { bpt_in },
+                [remaining, self.checked_mints(&self.join_tokens())].concat(),
+            )),
+            PoolKind::Stable => Err(ClientError::Unsupported),
+        }
+    }
+
+    /// remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+    fn token_pairs(&self, user_tokens: &[Pubkey]) -> Result<Vec<AccountMeta>, ClientError> {
+        let mints = self.join_tokens();
+        if user_tokens.len() != mints.len() {
+            return Err(ClientError::LengthMismatch);
+        }
+        Ok(user_tokens
+            .iter()
+            .zip(&mints)
+            .flat_map(|(user, mint)| [AccountMeta::new(*user, false), AccountMeta::new(self.custody(mint), false)])
+            .collect())
+    }
+
+    /// remaining_accounts: [mint0, mint1, …, hook accounts…, other token
+    /// program] on Token‑2022 or a mixed pool, none on SPL Token
+    fn checked_mints(&self, mints: &[Pubkey]) -> Vec<AccountMeta> {
+        let others = self.other_token_programs();
+        if self.token_program != token_2022::ID && others.is_empty() {
+            return vec![];
+        }
+        mints
+            .iter()
+            .chain(&self.transfer_hook_accounts)
+            .chain(&others)
+            .map(|address| AccountMeta::new_readonly(*address, false))
+            .collect()
+    }
+
+    fn weighted_pool_context(&self, user: &Pubkey, user_lp: Pubkey) -> weighted_pool::accounts::PoolContext {
+        weighted_pool::accounts::PoolContext {
+            pool: self.pool,
+            vault_state: self.vault_state,
+            lp_mint: self.lp_mint,
+            lp_mint_authority: self.authority(),
+            user: *user,
+            user_lp_account: user_lp,
+            fees_collector: pda::fees_collector(&self.vault_state),
+            protocol_fee_lp_account: pda::protocol_fee_account(&self.vault_state, &self.lp_mint),
+            locked_bpt_account: Some(self.locked_bpt_account()),
+            pool_registration: self.registration(),
+            token_program: self.token_program,
+        }
+    }
+
+    fn stable_pool_context(&self, user: &Pubkey, user_lp: Pubkey) -> stable_pool::accounts::PoolContext {
+        stable_pool::accounts::PoolContext {
+            pool: self.pool,
+            vault_state: self.vault_state,
+            lp_mint: self.lp_mint,
+            lp_mint_authority: self.authority(),
+            user: *user,
+            user_lp_account: user_lp,
+            fees_collector: pda::fees_collector(&self.vault_state),
+            protocol_fee_lp_account: pda::protocol_fee_account(&self.vault_state, &self.lp_mint),
+            pool_registration: self.registration(),
+            pool_bpt: self.tokens.contains(&self.lp_mint).then(|| self.custody(&self.lp_mint)),
+            token_program: self.token_program,
+        }
+    }
+
+    /* ---------------------------------------------------------------
+       Swaps
+    ---------------------------------------------------------------- */
+    /// Swap exactly `amount_in` of `mint_in` for at least
+    /// `minimum_amount_out` of `mint_out`, directly against the pool.
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_token_in_for_token_out(
+        &self,
+        user: &Pubkey,
+        user_token_in: Pubkey,
+        user_token_out: Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Instruction {
+        match self.kind {
+            PoolKind::Weighted => build(
+                weighted_pool::ID,
+                self.weighted_swap_context(user, user_token_in, user_token_out, mint_in, mint_out),
+                weighted_pool::instruction::SwapExactTokenInForTokenOut { amount_in, minimum_amount_out },
+                self.checked_mints(&[*mint_in, *mint_out]),
+            ),
+            PoolKind::Stable => build(
+                stable_pool::ID,
+                self.stable_swap_context(user, user_token_in, user_token_out, mint_in, mint_out),
+                stable_pool::instruction::SwapExactTokenInForTokenOut { amount_in, minimum_amount_out },
+                self.all_custody(),
+            ),
+        }
+    }
+
+    /// `swap_exact_token_in_for_token_out` that also fails when the
+    /// post‑trade spot price of `mint_out` in `mint_in` (18‑dec, fee
+    /// included) exceeds `max_price`. Weighted pools only.
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_exact_token_in_for_token_out_with_max_price(
+        &self,
+        user: &Pubkey,
+        user_token_in: Pubkey,
+        user_token_out: Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        max_price: Option<u128>,
+    ) -> Result<Instruction, ClientError> {
+        match self.kind {
+            PoolKind::Weighted => Ok(build(
+                weighted_pool::ID,
+                self.weighted_swap_context(user, user_token_in, user_token_out, mint_in, mint_out),
+                weighted_pool::instruction::SwapExactTokenInForTokenOutWithMaxPrice { amount_in, minimum_amount_out, max_price },
+                self.checked_mints(&[*mint_in, *mint_out]),
+            )),
+            PoolKind::Stable => Err(ClientError::Unsupported),
+        }
+    }
+
+    /// Swap at most `maximum_amount_in` of `mint_in` for exactly
+    /// `amount_out` of `mint_out` (stable pools only).
+    #[allow(clippy::too_many_arguments)]
+    pub fn swap_token_in_for_exact_token_out(
+        &self,
+        user: &Pubkey,
+        user_token_in: Pubkey,
+        user_token_out: Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<Instruction, ClientError> {
+        match self.kind {
+            PoolKind::Weighted => Err(ClientError::Unsupported),
+            PoolKind::Stable => Ok(build(
+                stable_pool::ID,
+                self.stable_swap_context(user, user_token_in, user_token_out, mint_in, mint_out),
+                stable_pool::instruction::SwapTokenInForExactTokenOut { amount_out, maximum_amount_in },
+                self.all_custody(),
+            )),
+        }
+    }
+
+    /// remaining_accounts: [vault_tok0, vault_tok1, …]
+    fn all_custody(&self) -> Vec<AccountMeta> {
+        self.tokens.iter().map(|mint| AccountMeta::new(self.custody(mint), false)).collect()
+    }
+
+    fn weighted_swap_context(
+        &self,
+        user: &Pubkey,
+        user_token_in: Pubkey,
+        user_token_out: Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> weighted_pool::accounts::SwapContext {
+        weighted_pool::accounts::SwapContext {
+            pool: self.pool,
+            vault_state: self.vault_state,
+            vault_in: self.custody(mint_in),
+            vault_out: self.custody(mint_out),
+            user_authority: *user,
+            user_token_account_in: user_token_in,
+            user_token_account_out: user_token_out,
+            lp_mint_authority: self.authority(),
+            token_program: self.token_program,
+            fees_collector: pda::fees_collector(&self.vault_state),
+            protocol_fee_account: pda::protocol_fee_account(&self.vault_state, mint_in),
+            pool_registration: self.registration(),
+        }
+    }
+
+    fn stable_swap_context(
+        &self,
+        user: &Pubkey,
+        user_token_in: Pubkey,
+        user_token_out: Pubkey,
+        mint_in: &Pubkey,
+        mint_out: &Pubkey,
+    ) -> stable_pool::accounts::SwapContext {
+        stable_pool::accounts::SwapContext {
+            pool: self.pool,
+            vault_state: self.vault_state,
+            vault_in: self.custody(mint_in),
+            vault_out: self.custody(mint_out),
+            user_authority: *user,
+            user_token_account_in: user_token_in,
+            user_token_account_out: user_token_out,
+            lp_mint_authority: self.authority(),
+            token_program: self.token_program,
+            fees_collector: pda::fees_collector(&self.vault_state),
+            protocol_fee_account: pda::protocol_fee_account(&self.vault_state, mint_in),
+            pool_registration: self.registration(),
+        }
+    }
+
+    /// The step group of a Vault `batch_swap` through this pool:
+    /// registration, program, pool, custody in and out, authority, the
+    /// collector's account of `mint_in`, then every custody account for
+    /// General pools.
+    pub fn batch_swap_step_accounts(&self, mint_in: &Pubkey, mint_out: &Pubkey) -> Vec<AccountMeta> {
+        let mut metas = vec![
+            AccountMeta::new_readonly(self.registration(), false),
+            AccountMeta::new_readonly(self.program_id(), false),
+            AccountMeta::new(self.pool, false),
+            AccountMeta::new(self.custody(mint_in), false),
+            AccountMeta::new(self.custody(mint_out), false),
+            AccountMeta::new_readonly(self.authority(), false),
+            AccountMeta::new(pda::protocol_fee_account(&self.vault_state, mint_in), false),
+        ];
+        if self.specialization == Specialization::General {
+            metas.extend(self.tokens.iter().map(|mint| AccountMeta::new_readonly(self.custody(mint), false)));
+        }
+        metas
+    }
+
+    /* ---------------------------------------------------------------
+       Monitoring
+    ---------------------------------------------------------------- */
+    /// Permissionless health probe emitting a `PoolHealth` event.
+    /// Weighted pools only.
+    pub fn verify_pool(&self) -> Result<Instruction, ClientError> {
+        if self.kind != PoolKind::Weighted {
+            return Err(ClientError::Unsupported);
+        }
+        Ok(build(
+            weighted_pool::ID,
+            weighted_pool::accounts::VerifyPool {
+                pool: self.pool,
+                pool_registration: self.registration(),
+                lp_mint: self.lp_mint,
+                lp_mint_authority: self.authority(),
+            },
+            weighted_pool::instruction::VerifyPool {},
+            self.tokens.iter().map(|mint| AccountMeta::new_readonly(self.custody(mint), false)).collect(),
+        ))
+    }
+
+    /// Read‑only preview of the protocol fee a join of `amounts_in` would
+    /// take, returned as `DueProtocolFees`. Weighted pools only.
+    pub fn query_due_protocol_fees(&self, amounts_in: Vec<u64>) -> Result<Instruction, ClientError> {
+        if self.kind != PoolKind::Weighted {
+            return Err(ClientError::Unsupported);
+        }
+        Ok(build(
+            weighted_pool::ID,
+            weighted_pool::accounts::QueryDueProtocolFees {
+                pool: self.pool,
+                fees_collector: pda::fees_collector(&self.vault_state),
+                pool_registration: self.registration(),
+                lp_mint: self.lp_mint,
+                lp_mint_authority: self.authority(),
+            },
+            weighted_pool::instruction::QueryDueProtocolFees { amounts_in },
+            self.tokens.iter().map(|mint| AccountMeta::new_readonly(self.custody(mint), false)).collect(),
+        ))
+    }
+
+    /* ---------------------------------------------------------------
+       Admin
+    ---------------------------------------------------------------- */
+    /// Requires the `SetSwapFee` role.
+    pub fn set_swap_fee(&self, authority: &Pubkey, swap_fee: u64) -> Instruction {
+        let authorizer = pda::authorizer(&self.vault_state);
+        match self.kind {
+            PoolKind::Weighted => build(
+                weighted_pool::ID,
+                weighted_pool::accounts::SetSwapFee { pool: self.pool, authorizer, authority: *authority },
+                weighted_pool::instruction::SetSwapFee { swap_fee },
+                vec![],
+            ),
+            PoolKind::Stable => build(
+                stable_pool::ID,
+                stable_pool::accounts::SetSwapFee { pool: self.pool, authorizer, authority: *authority },
+                stable_pool::instruction::SetSwapFee { swap_fee },
+                vec![],
+            ),
+        }
+    }
+
+    /// Create the LP mint's token metadata; requires the `RegisterPool`
+    /// role. Weighted pools only.
+    pub fn create_lp_metadata(
+        &self,
+        authority: &Pubkey,
+        payer: &Pubkey,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<Instruction, ClientError> {
+        if self.kind != PoolKind::Weighted {
+            return Err(ClientError::Unsupported);
+        }
+        Ok(build(
+            weighted_pool::ID,
+            weighted_pool::accounts::CreateLpMetadata {
+                pool: self.pool,
+                authorizer: pda::authorizer(&self.vault_state),
+                authority: *authority,
+                payer: *payer,
+                lp_mint: self.lp_mint,
+                lp_mint_authority: self.authority(),
+                metadata: pda::token_metadata(&self.lp_mint),
+                token_metadata_program: anchor_spl::metadata::ID,
+                system_program: system_program::ID,
+                rent: sysvar::rent::ID,
+            },
+            weighted_pool::instruction::CreateLpMetadata { name, symbol, uri },
+            vec![],
+        ))
+    }
+
+    /// Replace the LP mint's name, symbol and URI; requires the
+    /// `RegisterPool` role. Weighted pools only.
+    pub fn set_lp_metadata(&self, authority: &Pubkey, name: String, symbol: String, uri: String) -> Result<Instruction, ClientError> {
+        if self.kind != PoolKind::Weighted {
+            return Err(ClientError::Unsupported);
+        }
+        Ok(build(
+            weighted_pool::ID,
+            weighted_pool::accounts::SetLpMetadata {
+                pool: self.pool,
+                authorizer: pda::authorizer(&self.vault_state),
+                authority: *authority,
+                lp_mint_authority: self.authority(),
+                metadata: pda::token_metadata(&self.lp_mint),
+                token_metadata_program: anchor_spl::metadata::ID,
+            },
+            weighted_pool::instruction::SetLpMetadata { name, symbol, uri },
+            vec![],
+        ))
+    }
+
+    /// Grow a pool written under an older layout to the current one;
+    /// requires the Authorizer's admin. Weighted pools only.
+    pub fn migrate_pool_state(&self, admin: &Pubkey, payer: &Pubkey) -> Result<Instruction, ClientError> {
+        if self.kind != PoolKind::Weighted {
+            return Err(ClientError::Unsupported);
+        }
+        Ok(build(
+            weighted_pool::ID,
+            weighted_pool::accounts::MigratePoolState {
+                pool: self.pool,
+                authorizer: pda::authorizer(&self.vault_state),
+                admin: *admin,
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            weighted_pool::instruction::MigratePoolState {},
+            vec![],
+        ))
+    }
+}
+
+/// Longest name and symbol the token metadata program stores
+pub const MAX_NAME_LENGTH: usize = 32;
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
+/// A BPT's metadata name and symbol from its tokens' symbols and 18‑dec
+/// weights, in pool order: e.g. "Symmetric 80SOL-20USDC", and "S-" plus
+/// the same pair as the symbol when it fits ("S-50A-50B"), else "SPT".
+pub fn lp_token_name(symbols: &[&str], weights: &[u128]) -> (String, String) {
+    let percent = |w: u128| (w + 5_000_000_000_000_000) / 10_000_000_000_000_000;
+    let pair: Vec<String> = symbols.iter().zip(weights).map(|(s, w)| format!("{}{s}", percent(*w))).collect();
+    let pair = pair.join("-");
+    let name: String = format!("Symmetric {pair}").chars().take(MAX_NAME_LENGTH).collect();
+    let symbol = format!("S-{pair}");
+    let symbol = if symbol.len() <= MAX_SYMBOL_LENGTH { symbol } else { "SPT".to_string() };
+    (name, symbol)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn weighted_client(vault_state: Pubkey) -> PoolClient {
+        PoolClient {
+            kind: PoolKind::Weighted,
+            pool: Pubkey::new_unique(),
+            vault_state,
+            pool_id: [7; 32],
+            lp_mint: Pubkey::new_unique(),
+            tokens: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            specialization: Specialization::TwoToken,
+            protocol_swap_fee: false,
+            token_program: token::ID,
+            token_programs: vec![],
+            transfer_hook_accounts: vec![],
+        }
+    }
+
+    #[test]
+    fn names_bpt_after_its_tokens_and_weights() {
+        let (name, symbol) = lp_token_name(&["SOL", "USDC"], &[800_000_000_000_000_000, 200_000_000_000_000_000]);
+        assert_eq!((name.as_str(), symbol.as_str()), ("Symmetric 80SOL-20USDC", "SPT"));
+        let (name, symbol) = lp_token_name(&["A", "B"], &[500_000_000_000_000_000, 500_000_000_000_000_000]);
+        assert_eq!((name.as_str(), symbol.as_str()), ("Symmetric 50A-50B", "S-50A-50B"));
+        let symbols = ["SOL", "USDC", "USDT", "JITOSOL", "MSOL"];
+        let (name, _) = lp_token_name(&symbols, &[200_000_000_000_000_000; 5]);
+        assert_eq!(name.len(), MAX_NAME_LENGTH);
+    }
+
+    #[test]
+    fn lays_out_per_token_accounts_for_each_pool_kind() {
+        let mut pool = weighted_client(Pubkey::new_unique());
+        let user = Pubkey::new_unique();
+        let user_tokens = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        let join = pool.join_exact_tokens_in_for_bpt_out(&user, Pubkey::new_unique(), &user_tokens, vec![1, 2], 0).unwrap();
+        assert_eq!(join.accounts.len(), 11 + 4);
+        assert_eq!(join.accounts[12].pubkey, pool.custody(&pool.tokens[0]));
+        assert!(matches!(
+            pool.join_exact_tokens_in_for_bpt_out(&user, Pubkey::new_unique(), &user_tokens, vec![1, 2], 5),
+            Err(ClientError::Unsupported)
+        ));
+
+        pool.protocol_swap_fee = true;
+        let exit = pool.exit_exact_bpt_in_for_tokens_out(&user, Pubkey::new_unique(), &user_tokens, 10, vec![0; user_tokens.len()]).unwrap();
+        assert_eq!(exit.accounts.len(), 11 + 4);
+
+        // a composable stable pool: the BPT is a registered token but not joined
+        pool.kind = PoolKind::Stable;
+        pool.specialization = Specialization::General;
+        pool.tokens.push(pool.lp_mint);
+        let swap = pool.swap_exact_token_in_for_token_out(&user, user_tokens[0], user_tokens[1], &pool.tokens[0], &pool.tokens[1], 5, 0);
+        assert_eq!(swap.program_id, stable_pool::ID);
+        assert_eq!(swap.accounts.len(), 12 + 3);
+        let join = pool.join_exact_tokens_in_for_bpt_out(&user, Pubkey::new_unique(), &user_tokens, vec![1, 2], 5).unwrap();
+        assert_eq!(join.accounts.len(), 11 + 4);
+        assert_eq!(pool.batch_swap_step_accounts(&pool.tokens[0], &pool.tokens[2]).len(), 7 + 3);
+    }
+
+    #[test]
+    fn mixed_pools_pass_the_other_token_program_with_the_mints() {
+        let mut pool = weighted_client(Pubkey::new_unique());
+        pool.token_programs = vec![token::ID, token_2022::ID];
+        assert_eq!(pool.other_token_programs(), vec![token_2022::ID]);
+        let user = Pubkey::new_unique();
+        let user_tokens = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let join = pool.join_exact_tokens_in_for_bpt_out(&user, Pubkey::new_unique(), &user_tokens, vec![1, 2], 0).unwrap();
+        assert_eq!(join.accounts.len(), 11 + 4 + 2 + 1);
+        assert_eq!(join.accounts[15].pubkey, pool.tokens[0]);
+        assert_eq!(join.accounts[17].pubkey, token_2022::ID);
+    }
+
+    #[test]
+    fn creates_pools_at_the_addresses_the_programs_derive() {
+        let vault_state = Pubkey::new_unique();
+        let vault = VaultState {
+            owner: Pubkey::new_unique(),
+            pool_count: 4,
+            pause_window_end: 0,
+            paused_until: 0,
+            pending_owner: Pubkey::default(),
+            creator: Pubkey::new_unique(),
+            registry_tail: 1,
+            reject_freezable_mints: false,
+        };
+        let (payer, lp_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut tokens = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        tokens.sort();
+
+        let weights = vec![600_000_000_000_000_000, 400_000_000_000_000_000];
+        let (client, ix) =
+            PoolClient::create_weighted(vault_state, &vault, &payer, &payer, lp_mint, tokens.clone(), weights.clone(), 3).unwrap();
+        assert_eq!(client.pool_id, derive_pool_id(&vault_state, &client.pool, Specialization::TwoToken, 4));
+        assert_eq!(ix.accounts[5].pubkey, pda::pool_registry_page(&vault_state, 1));
+        assert_eq!(ix.accounts.len(), 11 + 2);
+        let unsorted = tokens.iter().rev().copied().collect();
+        assert!(matches!(
+            PoolClient::create_weighted(vault_state, &vault, &payer, &payer, lp_mint, unsorted, weights, 3),
+            Err(ClientError::LengthMismatch)
+        ));
+
+        let (client, ix) = PoolClient::create_stable(vault_state, &vault, &payer, lp_mint, tokens, 100, 3, true, vec![]);
+        assert_eq!(client.tokens.len(), 3);
+        assert_eq!(client.join_tokens().len(), 2);
+        assert_eq!(ix.accounts[10].pubkey, client.custody(&lp_mint));
+    }
+}
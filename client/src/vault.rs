@@ -0,0 +1,562 @@
+// Symmetric‑Solana ─ Vault client
+// ================================================================
+// Instruction builders for the Vault program. Every PDA an
+// instruction checks is derived here, and `remaining_accounts` are laid
+// out as the program documents them, so callers only supply signers,
+// their own token accounts and instruction arguments.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::system_program;
+use anchor_spl::{token, token_2022};
+use common::{PoolId, Specialization};
+use vault::{accounts, instruction, Action, BatchSwapStep};
+
+use crate::pool::PoolClient;
+use crate::{build, pda, ClientError};
+
+/// The user's token account of one batch asset, and the vault‑authority
+/// account it settles against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssetAccounts {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+}
+
+/// Builds instructions against one Vault.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VaultClient {
+    pub vault_state: Pubkey,
+}
+
+impl VaultClient {
+    pub fn new(vault_state: Pubkey) -> Self {
+        Self { vault_state }
+    }
+
+    /// The Vault initialised by `creator`.
+    pub fn for_creator(creator: &Pubkey) -> Self {
+        Self::new(pda::vault_state(creator))
+    }
+
+    pub fn vault_authority(&self) -> Pubkey {
+        pda::vault_authority(&self.vault_state)
+    }
+
+    pub fn authorizer(&self) -> Pubkey {
+        pda::authorizer(&self.vault_state)
+    }
+
+    pub fn fees_collector(&self) -> Pubkey {
+        pda::fees_collector(&self.vault_state)
+    }
+
+    /* ---------------------------------------------------------------
+       Ownership & roles
+    ---------------------------------------------------------------- */
+    /// `payer` creates (and seeds) the Vault, owned by `owner`.
+    pub fn initialize(payer: &Pubkey, owner: Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::Initialize {
+                vault_state: pda::vault_state(payer),
+                payer: *payer,
+                system_program: system_program::ID,
+            },
+            instruction::Initialize { owner },
+            vec![],
+        )
+    }
+
+    /// Propose `new_owner`; `authority` holds the `TransferOwnership` role.
+    pub fn transfer_ownership(&self, authority: &Pubkey, new_owner: Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::TransferOwnership { vault_state: self.vault_state, authorizer: self.authorizer(), authority: *authority },
+            instruction::TransferOwnership { new_owner },
+            vec![],
+        )
+    }
+
+    pub fn accept_ownership(&self, pending_owner: &Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::AcceptOwnership { vault_state: self.vault_state, pending_owner: *pending_owner },
+            instruction::AcceptOwnership {},
+            vec![],
+        )
+    }
+
+    pub fn initialize_authorizer(&self, owner: &Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::InitializeAuthorizer {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                owner: *owner,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeAuthorizer {},
+            vec![],
+        )
+    }
+
+    pub fn grant_role(&self, admin: &Pubkey, action: Action, account: Pubkey) -> Instruction {
+        build(vault::ID, self.manage_roles(admin), instruction::GrantRole { action, account }, vec![])
+    }
+
+    pub fn revoke_role(&self, admin: &Pubkey, action: Action, account: Pubkey) -> Instruction {
+        build(vault::ID, self.manage_roles(admin), instruction::RevokeRole { action, account }, vec![])
+    }
+
+    pub fn set_authorizer_admin(&self, admin: &Pubkey, new_admin: Pubkey) -> Instruction {
+        build(vault::ID, self.manage_roles(admin), instruction::SetAuthorizerAdmin { new_admin }, vec![])
+    }
+
+    fn manage_roles(&self, admin: &Pubkey) -> accounts::ManageRoles {
+        accounts::ManageRoles { authorizer: self.authorizer(), admin: *admin }
+    }
+
+    /* ---------------------------------------------------------------
+       Pausing
+    ---------------------------------------------------------------- */
+    pub fn emergency_pause(&self, guardian: &Pubkey) -> Instruction {
+        build(vault::ID, self.set_paused(guardian), instruction::EmergencyPause {}, vec![])
+    }
+
+    pub fn unpause(&self, guardian: &Pubkey) -> Instruction {
+        build(vault::ID, self.set_paused(guardian), instruction::Unpause {}, vec![])
+    }
+
+    fn set_paused(&self, guardian: &Pubkey) -> accounts::SetPaused {
+        accounts::SetPaused { vault_state: self.vault_state, authorizer: self.authorizer(), guardian: *guardian }
+    }
+
+    pub fn expire_pause(&self, cranker: &Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::ExpirePause { vault_state: self.vault_state, cranker: *cranker },
+            instruction::ExpirePause {},
+            vec![],
+        )
+    }
+
+    pub fn set_pool_paused(&self, guardian: &Pubkey, pool_id: &PoolId, paused: bool) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetPoolPaused {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                pool_registration: pda::pool_registration(&self.vault_state, pool_id),
+                guardian: *guardian,
+            },
+            instruction::SetPoolPaused { paused },
+            vec![],
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Pool registration
+    ---------------------------------------------------------------- */
+    /// `registry_tail` is the Vault state's current `registry_tail`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn register_pool(
+        &self,
+        authority: &Pubkey,
+        pool: Pubkey,
+        lp_mint: Pubkey,
+        pool_id: PoolId,
+        specialization: Specialization,
+        tokens: Vec<Pubkey>,
+        registry_tail: u32,
+    ) -> Instruction {
+        build(
+            vault::ID,
+            accounts::RegisterPool {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                pool_registration: pda::pool_registration(&self.vault_state, &pool_id),
+                registry_page: pda::pool_registry_page(&self.vault_state, registry_tail),
+                pool,
+                lp_mint,
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            instruction::RegisterPool { pool_id, specialization, tokens },
+            vec![],
+        )
+    }
+
+    /// Custody accounts for `mints` of a registered pool, all owned by
+    /// `token_program` (SPL Token or Token‑2022).
+    pub fn register_tokens(
+        &self,
+        authority: &Pubkey,
+        pool_id: &PoolId,
+        mints: &[Pubkey],
+        token_program: &Pubkey,
+        asset_managers: Vec<Pubkey>,
+    ) -> Result<Instruction, ClientError> {
+        if mints.len() != asset_managers.len() {
+            return Err(ClientError::LengthMismatch);
+        }
+        let (accounts, remaining) = self.register_tokens_accounts(authority, pool_id, mints, token_program);
+        Ok(build(vault::ID, accounts, instruction::RegisterTokens { asset_managers }, remaining))
+    }
+
+    /// Close the (empty) custody accounts of `mints`.
+    pub fn deregister_tokens(&self, authority: &Pubkey, pool_id: &PoolId, mints: &[Pubkey], token_program: &Pubkey) -> Instruction {
+        let (accounts, remaining) = self.register_tokens_accounts(authority, pool_id, mints, token_program);
+        build(vault::ID, accounts, instruction::DeregisterTokens {}, remaining)
+    }
+
+    /// remaining_accounts: [mint_0, token_account_0, mint_1, token_account_1, …]
+    fn register_tokens_accounts(
+        &self,
+        authority: &Pubkey,
+        pool_id: &PoolId,
+        mints: &[Pubkey],
+        token_program: &Pubkey,
+    ) -> (accounts::RegisterTokens, Vec<AccountMeta>) {
+        let registration = pda::pool_registration(&self.vault_state, pool_id);
+        let remaining = mints
+            .iter()
+            .flat_map(|mint| {
+                [AccountMeta::new_readonly(*mint, false), AccountMeta::new(pda::pool_token(&registration, mint), false)]
+            })
+            .collect();
+        let accounts = accounts::RegisterTokens {
+            vault_state: self.vault_state,
+            authorizer: self.authorizer(),
+            pool_registration: registration,
+            vault_authority: self.vault_authority(),
+            transfer_hook_allowlist: Some(pda::transfer_hook_allowlist(&self.vault_state)),
+            mint_extension_overrides: Some(pda::mint_extension_overrides(&self.vault_state)),
+            authority: *authority,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        };
+        (accounts, remaining)
+    }
+
+    /* ---------------------------------------------------------------
+       Protocol fees
+    ---------------------------------------------------------------- */
+    /// Create the fees collector; `authority` holds the `SetProtocolFees`
+    /// role.
+    pub fn initialize_fees_collector(&self, authority: &Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::InitializeFeesCollector {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                fees_collector: self.fees_collector(),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            instruction::InitializeFeesCollector {},
+            vec![],
+        )
+    }
+
+    /// Allow (or stop allowing) tokens whose transfer hook runs
+    /// `hook_program` to be registered; requires the `ManageTokenPolicy`
+    /// role.
+    pub fn set_transfer_hook_program(&self, authority: &Pubkey, hook_program: Pubkey, allowed: bool) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetTransferHookProgram {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                transfer_hook_allowlist: pda::transfer_hook_allowlist(&self.vault_state),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            instruction::SetTransferHookProgram { hook_program, allowed },
+            vec![],
+        )
+    }
+
+    /// Allow (or stop allowing) `mint` to be registered despite a permanent
+    /// delegate, frozen default state, confidential transfers or (while
+    /// the Vault rejects freezable mints) a freeze authority; requires the
+    /// `ManageTokenPolicy` role.
+    pub fn set_mint_extension_override(&self, authority: &Pubkey, mint: Pubkey, allowed: bool) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetMintExtensionOverride {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                mint_extension_overrides: pda::mint_extension_overrides(&self.vault_state),
+                authority: *authority,
+                system_program: system_program::ID,
+            },
+            instruction::SetMintExtensionOverride { mint, allowed },
+            vec![],
+        )
+    }
+
+    /// Refuse (or again accept) registering mints with a freeze authority
+    /// no `ManageTokenPolicy` holder has acknowledged; requires that role.
+    pub fn set_freeze_authority_policy(&self, authority: &Pubkey, reject: bool) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetFreezeAuthorityPolicy {
+                vault_state: self.vault_state,
+                authorizer: self.authorizer(),
+                authority: *authority,
+            },
+            instruction::SetFreezeAuthorityPolicy { reject },
+            vec![],
+        )
+    }
+
+    /// The collector's account of `mint`, owned by `token_program`.
+    pub fn create_fee_account(&self, payer: &Pubkey, mint: Pubkey, token_program: &Pubkey) -> Instruction {
+        build(
+            vault::ID,
+            accounts::CreateFeeAccount {
+                fees_collector: self.fees_collector(),
+                mint,
+                fee_account: pda::protocol_fee_account(&self.vault_state, &mint),
+                payer: *payer,
+                token_program: *token_program,
+                system_program: system_program::ID,
+            },
+            instruction::CreateFeeAccount {},
+            vec![],
+        )
+    }
+
+    pub fn set_protocol_fee_percentages(
+        &self,
+        authority: &Pubkey,
+        swap_fee_percentage: u64,
+        flash_loan_fee_percentage: u64,
+    ) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetProtocolFeePercentages {
+                fees_collector: self.fees_collector(),
+                authorizer: self.authorizer(),
+                authority: *authority,
+            },
+            instruction::SetProtocolFeePercentages { swap_fee_percentage, flash_loan_fee_percentage },
+            vec![],
+        )
+    }
+
+    pub fn withdraw_collected_fees(
+        &self,
+        authority: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        build(
+            vault::ID,
+            accounts::WithdrawCollectedFees {
+                vault_state: self.vault_state,
+                fees_collector: self.fees_collector(),
+                authorizer: self.authorizer(),
+                fee_account: pda::protocol_fee_account(&self.vault_state, mint),
+                mint: *mint,
+                recipient,
+                authority: *authority,
+                token_program: *token_program,
+            },
+            instruction::WithdrawCollectedFees { amount },
+            vec![],
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Asset management
+    ---------------------------------------------------------------- */
+    /// Move `amount` of `pool`'s `mint` out of custody into `manager`'s
+    /// `counterparty` account; the pool keeps pricing it as managed.
+    pub fn withdraw_managed(&self, manager: &Pubkey, pool: &PoolClient, mint: &Pubkey, counterparty: Pubkey, amount: u64) -> Instruction {
+        let accounts = self.manage_pool_balance_accounts(manager, pool, mint, counterparty);
+        build(vault::ID, accounts, instruction::WithdrawManaged { amount }, vec![])
+    }
+
+    /// Return `amount` of `pool`'s managed `mint` to custody from
+    /// `manager`'s `counterparty` account.
+    pub fn deposit_managed(&self, manager: &Pubkey, pool: &PoolClient, mint: &Pubkey, counterparty: Pubkey, amount: u64) -> Instruction {
+        let accounts = self.manage_pool_balance_accounts(manager, pool, mint, counterparty);
+        build(vault::ID, accounts, instruction::DepositManaged { amount }, vec![])
+    }
+
+    /// Report `managed_balance` as what `manager` now holds of `pool`'s
+    /// `mint`; `counterparty` is any of the manager's accounts of it.
+    pub fn update_managed_balance(
+        &self,
+        manager: &Pubkey,
+        pool: &PoolClient,
+        mint: &Pubkey,
+        counterparty: Pubkey,
+        managed_balance: u64,
+    ) -> Instruction {
+        let accounts = self.manage_pool_balance_accounts(manager, pool, mint, counterparty);
+        build(vault::ID, accounts, instruction::UpdateManagedBalance { managed_balance }, vec![])
+    }
+
+    fn manage_pool_balance_accounts(
+        &self,
+        manager: &Pubkey,
+        pool: &PoolClient,
+        mint: &Pubkey,
+        counterparty: Pubkey,
+    ) -> accounts::ManagePoolBalance {
+        let token_program = pool
+            .join_tokens()
+            .iter()
+            .position(|t| t == mint)
+            .and_then(|i| pool.token_programs.get(i))
+            .copied()
+            .unwrap_or(pool.token_program);
+        accounts::ManagePoolBalance {
+            vault_state: self.vault_state,
+            asset_manager_authority: pda::asset_manager_authority(&self.vault_state),
+            pool_registration: pool.registration(),
+            pool_program: pool.program_id(),
+            pool: pool.pool,
+            pool_authority: pool.authority(),
+            custody: pool.custody(mint),
+            counterparty,
+            manager: *manager,
+            token_program,
+        }
+    }
+
+    /* ---------------------------------------------------------------
+       Trading
+    ---------------------------------------------------------------- */
+    pub fn set_relayer_approval(&self, user: &Pubkey, relayer: Pubkey, approved: bool) -> Instruction {
+        build(
+            vault::ID,
+            accounts::SetRelayerApproval {
+                vault_state: self.vault_state,
+                relayer_approval: pda::relayer_approval(&self.vault_state, user, &relayer),
+                user: *user,
+                system_program: system_program::ID,
+            },
+            instruction::SetRelayerApproval { relayer, approved },
+            vec![],
+        )
+    }
+
+    /// `batch_swap` of `steps` over `assets` (mints, with the matching
+    /// accounts in `asset_accounts`). `pools[i]` is the pool of `steps[i]`.
+    /// A `sender` other than `user` trades through its relayer approval.
+    /// The first pool's token program is the context's; on Token‑2022, or
+    /// when any pool holds tokens of the other program, the asset mints,
+    /// the pools' transfer‑hook accounts and that other program follow.
+    #[allow(clippy::too_many_arguments)]
+    pub fn batch_swap(
+        &self,
+        user: &Pubkey,
+        sender: &Pubkey,
+        assets: &[Pubkey],
+        asset_accounts: &[AssetAccounts],
+        steps: Vec<BatchSwapStep>,
+        pools: &[&PoolClient],
+        limits: Vec<i64>,
+        deadline: i64,
+    ) -> Result<Instruction, ClientError> {
+        if assets.len() != asset_accounts.len() || assets.len() != limits.len() || steps.len() != pools.len() {
+            return Err(ClientError::LengthMismatch);
+        }
+        let relayer_approval = (user != sender).then(|| pda::relayer_approval(&self.vault_state, user, sender));
+        let fixed = accounts::BatchSwap {
+            vault_state: self.vault_state,
+            vault_authority: self.vault_authority(),
+            user: *user,
+            sender: *sender,
+            relayer_approval,
+            permit_nonce: None,
+            instructions: None,
+            system_program: None,
+            fees_collector: self.fees_collector(),
+            token_program: pools.first().map_or(token::ID, |pool| pool.token_program),
+        };
+
+        let mut remaining: Vec<AccountMeta> = asset_accounts
+            .iter()
+            .flat_map(|a| [AccountMeta::new(a.user, false), AccountMeta::new(a.vault, false)])
+            .collect();
+        for (step, pool) in steps.iter().zip(pools) {
+            let asset = |i: u8| assets.get(i as usize).ok_or(ClientError::LengthMismatch);
+            let (mint_in, mint_out) = (asset(step.asset_in_index)?, asset(step.asset_out_index)?);
+            if step.pool_id != pool.pool_id {
+                return Err(ClientError::PoolMismatch);
+            }
+            remaining.extend(pool.batch_swap_step_accounts(mint_in, mint_out));
+        }
+        let other_programs: Vec<Pubkey> = pools
+            .iter()
+            .flat_map(|pool| pool.token_programs.iter().chain([&pool.token_program]))
+            .filter(|program| **program != fixed.token_program)
+            .copied()
+            .collect();
+        if fixed.token_program == token_2022::ID || !other_programs.is_empty() {
+            remaining.extend(assets.iter().map(|mint| AccountMeta::new_readonly(*mint, false)));
+            let mut hook_accounts: Vec<Pubkey> = Vec::new();
+            for address in pools.iter().flat_map(|pool| &pool.transfer_hook_accounts).chain(&other_programs) {
+                if !hook_accounts.contains(address) {
+                    hook_accounts.push(*address);
+                }
+            }
+            remaining.extend(hook_accounts.into_iter().map(|address| AccountMeta::new_readonly(address, false)));
+        }
+        Ok(build(vault::ID, fixed, instruction::BatchSwap { steps, limits, deadline }, remaining))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::tests::weighted_client;
+
+    #[test]
+    fn batch_swap_lays_out_assets_then_step_groups() {
+        let client = VaultClient::for_creator(&Pubkey::new_unique());
+        let pool = weighted_client(client.vault_state);
+        let user = Pubkey::new_unique();
+        let step = BatchSwapStep { pool_id: pool.pool_id, asset_in_index: 0, asset_out_index: 1, amount: 100 };
+        let asset_accounts = [
+            AssetAccounts { user: Pubkey::new_unique(), vault: Pubkey::new_unique() },
+            AssetAccounts { user: Pubkey::new_unique(), vault: Pubkey::new_unique() },
+        ];
+        let ix = client
+            .batch_swap(&user, &user, &pool.tokens, &asset_accounts, vec![step.clone()], &[&pool], vec![100, -90], 0)
+            .unwrap();
+        // 10 fixed accounts, a pair per asset, one step group
+        assert_eq!(ix.accounts.len(), 10 + 2 * 2 + BatchSwapStep::ACCOUNTS);
+        assert_eq!(ix.accounts[10].pubkey, asset_accounts[0].user);
+        assert_eq!(ix.accounts[14].pubkey, pool.registration());
+        assert_eq!(ix.accounts[17].pubkey, pool.custody(&pool.tokens[0]));
+        assert_eq!(ix.accounts[20].pubkey, pda::protocol_fee_account(&client.vault_state, &pool.tokens[0]));
+
+        let other = BatchSwapStep { pool_id: [9; 32], ..step };
+        assert!(matches!(
+            client.batch_swap(&user, &user, &pool.tokens, &asset_accounts, vec![other], &[&pool], vec![100, -90], 0),
+            Err(ClientError::PoolMismatch)
+        ));
+    }
+
+    #[test]
+    fn managed_transfers_go_through_the_asset_manager_authority() {
+        let client = VaultClient::for_creator(&Pubkey::new_unique());
+        let mut pool = weighted_client(client.vault_state);
+        pool.token_programs = vec![token::ID, token_2022::ID];
+        let (manager, counterparty) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let ix = client.withdraw_managed(&manager, &pool, &pool.tokens[1], counterparty, 100);
+        assert_eq!(ix.accounts[1].pubkey, pda::asset_manager_authority(&client.vault_state));
+        assert_eq!(ix.accounts[6].pubkey, pool.custody(&pool.tokens[1]));
+        assert_eq!(ix.accounts[7].pubkey, counterparty);
+        assert!(ix.accounts[8].pubkey == manager && ix.accounts[8].is_signer);
+        assert_eq!(ix.accounts[9].pubkey, token_2022::ID);
+    }
+}
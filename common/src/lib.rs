@@ -1,16 +1,516 @@
+// Symmetric‑Solana ─ Common layer
+// ================================================================
+// Definitions shared between the Vault and the pool programs.
+// The Vault cannot depend on the pool crates (they depend on it for
+// CPI), so anything it needs to call *into* a pool lives here.
+// ================================================================
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
 
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+// ------------------------------------------------------------
+// PDA seeds
+// ------------------------------------------------------------
+pub mod seeds {
+    /// Vault state PDA: ["vault-state", owner]
+    pub const VAULT_STATE: &[u8] = b"vault-state";
+    /// Vault token authority PDA: ["vault-authority", vault_state]
+    pub const VAULT_AUTHORITY: &[u8] = b"vault-authority";
+    /// Asset manager authority PDA: ["asset-manager-authority", vault_state];
+    /// signs only the pools' managed transfers
+    pub const ASSET_MANAGER_AUTHORITY: &[u8] = b"asset-manager-authority";
+    /// Pool state PDA (weighted pool): ["pool-state", vault_state], or
+    /// ["pool-state", vault_state, config_hash] for canonical pools
+    pub const POOL_STATE: &[u8] = b"pool-state";
+    /// Pool token / LP mint authority PDA: ["lp-mint-authority", pool]
+    pub const LP_MINT_AUTHORITY: &[u8] = b"lp-mint-authority";
+    /// Authorizer PDA: ["authorizer", vault_state]
+    pub const AUTHORIZER: &[u8] = b"authorizer";
+    /// Pool registration PDA: ["pool-registration", vault_state, pool_id]
+    pub const POOL_REGISTRATION: &[u8] = b"pool-registration";
+    /// Relayer approval PDA: ["relayer-approval", vault_state, user, relayer]
+    pub const RELAYER_APPROVAL: &[u8] = b"relayer-approval";
+    /// Protocol fees collector PDA: ["fees-collector", vault_state]
+    pub const FEES_COLLECTOR: &[u8] = b"fees-collector";
+    /// Protocol fee token account PDA: ["protocol-fees", fees_collector, mint]
+    pub const PROTOCOL_FEES: &[u8] = b"protocol-fees";
+    /// Pool custody token account PDA (vault program): ["pool-token", pool_registration, mint]
+    pub const POOL_TOKEN: &[u8] = b"pool-token";
+    /// Pool registry page PDA: ["pool-registry", vault_state, page_index (u32 LE)]
+    pub const POOL_REGISTRY: &[u8] = b"pool-registry";
+    /// Relayer permit nonce PDA: ["permit-nonce", vault_state, user]
+    pub const PERMIT_NONCE: &[u8] = b"permit-nonce";
+    /// Transfer-hook program allowlist PDA: ["transfer-hook-allowlist", vault_state]
+    pub const TRANSFER_HOOK_ALLOWLIST: &[u8] = b"transfer-hook-allowlist";
+    /// Mint extension override PDA: ["mint-extension-overrides", vault_state]
+    pub const MINT_EXTENSION_OVERRIDES: &[u8] = b"mint-extension-overrides";
+}
+
+/// The PDA that holds a pool's tokens and signs its outbound transfers:
+/// ["lp-mint-authority", pool] under the pool program.
+pub fn pool_authority(pool: &Pubkey, pool_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[seeds::LP_MINT_AUTHORITY, pool.as_ref()], pool_program).0
+}
+
+// ------------------------------------------------------------
+// Pool identity
+// ------------------------------------------------------------
+/// Maximum number of tokens in any pool
+pub const MAX_POOL_TOKENS: usize = 8;
+
+/// Vault‑wide pool identifier (see [`derive_pool_id`]).
+pub type PoolId = [u8; 32];
+
+/// How much balance information a pool needs to price a swap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Specialization {
+    /// Needs every token balance
+    General,
+    /// Needs only the balances of the two tokens being swapped
+    MinimalSwapInfo,
+    /// Exactly two tokens
+    TwoToken,
+}
+
+/// PoolId = sha256("pool-id" ‖ vault ‖ pool ‖ specialization ‖ nonce), where
+/// `nonce` is the vault's `pool_count` at registration time.
+pub fn derive_pool_id(vault: &Pubkey, pool: &Pubkey, specialization: Specialization, nonce: u64) -> PoolId {
+    anchor_lang::solana_program::hash::hashv(&[
+        b"pool-id",
+        vault.as_ref(),
+        pool.as_ref(),
+        &[specialization as u8],
+        &nonce.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+/// Config hash of a canonical weighted pool (see [`seeds::POOL_STATE`]):
+/// sha256("canonical-pool" ‖ token_0 ‖ … ‖ weight_0 ‖ … ‖ swap_fee).
+/// Tokens must be in ascending order so each configuration has exactly
+/// one address; returns `None` when they are not.
+pub fn canonical_pool_hash(tokens: &[Pubkey], weights: &[u128], swap_fee: u64) -> Option<[u8; 32]> {
+    if tokens.len() != weights.len() || tokens.windows(2).any(|w| w[0] >= w[1]) {
+        return None;
+    }
+    let weight_bytes: Vec<[u8; 16]> = weights.iter().map(|w| w.to_le_bytes()).collect();
+    let fee_bytes = swap_fee.to_le_bytes();
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(2 + tokens.len() * 2);
+    parts.push(b"canonical-pool");
+    parts.extend(tokens.iter().map(|t| t.as_ref()));
+    parts.extend(weight_bytes.iter().map(|w| w.as_slice()));
+    parts.push(&fee_bytes);
+    Some(anchor_lang::solana_program::hash::hashv(&parts).to_bytes())
+}
+
+// ------------------------------------------------------------
+// Pool program interface (raw CPI builders)
+// ------------------------------------------------------------
+pub mod pool_interface {
+    use super::*;
+
+    /// Anchor discriminator of `swap_exact_token_in_for_token_out`
+    /// (first 8 bytes of sha256("global:swap_exact_token_in_for_token_out")).
+    pub const SWAP_EXACT_IN_DISCRIMINATOR: [u8; 8] = [148, 138, 114, 105, 25, 252, 117, 230];
+
+    /// Anchor discriminator of `managed_transfer`
+    /// (first 8 bytes of sha256("global:managed_transfer")).
+    pub const MANAGED_TRANSFER_DISCRIMINATOR: [u8; 8] = [15, 220, 101, 8, 248, 72, 74, 181];
+
+    /// Anchor discriminator of `query_swap`
+    /// (first 8 bytes of sha256("global:query_swap")).
+    pub const QUERY_SWAP_DISCRIMINATOR: [u8; 8] = [160, 52, 250, 177, 38, 177, 233, 108];
+
+    /// Result of a pool's `query_swap`, returned through return data.
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SwapQuote {
+        /// Amount the pool would send out
+        pub amount_out: u64,
+        /// Part of the amount in that would go to the protocol instead of the pool
+        pub protocol_fee: u64,
+    }
+
+    /// Accounts of a pool's `query_swap`, in instruction order.
+    /// `pool_token_in`/`pool_token_out` only identify the tokens swapped;
+    /// their balances are passed as arguments. The Vault appends a General
+    /// pool's custody accounts, or any other pool's registration.
+    pub struct QuerySwapAccounts {
+        pub pool: Pubkey,
+        pub fees_collector: Pubkey,
+        pub pool_token_in: Pubkey,
+        pub pool_token_out: Pubkey,
+    }
+
+    /// Build the read‑only `query_swap` instruction: prices an exact‑in swap
+    /// against the given balances without touching any token account.
+    pub fn query_swap(
+        program_id: Pubkey,
+        accounts: &QuerySwapAccounts,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Instruction {
+        let mut data = Vec::with_capacity(32);
+        data.extend_from_slice(&QUERY_SWAP_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&balance_in.to_le_bytes());
+        data.extend_from_slice(&balance_out.to_le_bytes());
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(accounts.pool, false),
+                AccountMeta::new_readonly(accounts.fees_collector, false),
+                AccountMeta::new_readonly(accounts.pool_token_in, false),
+                AccountMeta::new_readonly(accounts.pool_token_out, false),
+            ],
+            data,
+        }
+    }
+
+    /// Accounts of a pool's `managed_transfer`, in instruction order.
+    pub struct ManagedTransferAccounts {
+        pub pool: Pubkey,
+        pub asset_manager_authority: Pubkey,
+        pub pool_registration: Pubkey,
+        pub from: Pubkey,
+        pub to: Pubkey,
+        pub pool_authority: Pubkey,
+        pub token_program: Pubkey,
+    }
+
+    /// Build the `managed_transfer` instruction: the pool sends `amount` out
+    /// of its custody account on behalf of an asset manager. Must be signed
+    /// by the asset manager authority PDA.
+    pub fn managed_transfer(program_id: Pubkey, accounts: &ManagedTransferAccounts, amount: u64) -> Instruction {
+        let mut data = Vec::with_capacity(16);
+        data.extend_from_slice(&MANAGED_TRANSFER_DISCRIMINATOR);
+        data.extend_from_slice(&amount.to_le_bytes());
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(accounts.pool, false),
+                AccountMeta::new_readonly(accounts.asset_manager_authority, true),
+                AccountMeta::new_readonly(accounts.pool_registration, false),
+                AccountMeta::new(accounts.from, false),
+                AccountMeta::new(accounts.to, false),
+                AccountMeta::new_readonly(accounts.pool_authority, false),
+                AccountMeta::new_readonly(accounts.token_program, false),
+            ],
+            data,
+        }
+    }
+
+    /// Accounts of a pool's exact‑in swap, in instruction order.
+    pub struct SwapExactInAccounts {
+        pub pool: Pubkey,
+        pub vault_state: Pubkey,
+        pub pool_token_in: Pubkey,
+        pub pool_token_out: Pubkey,
+        pub authority: Pubkey,
+        pub user_token_in: Pubkey,
+        pub user_token_out: Pubkey,
+        pub pool_authority: Pubkey,
+        pub token_program: Pubkey,
+        pub fees_collector: Pubkey,
+        pub protocol_fee_account: Pubkey,
+        pub pool_registration: Pubkey,
+    }
+
+    /// Build the `swap_exact_token_in_for_token_out` instruction for `program_id`.
+    /// The pool returns the amount sent out through return data (little‑endian u64).
+    pub fn swap_exact_in(
+        program_id: Pubkey,
+        accounts: &SwapExactInAccounts,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Instruction {
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&SWAP_EXACT_IN_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(accounts.pool, false),
+                AccountMeta::new_readonly(accounts.vault_state, false),
+                AccountMeta::new(accounts.pool_token_in, false),
+                AccountMeta::new(accounts.pool_token_out, false),
+                AccountMeta::new(accounts.authority, true),
+                AccountMeta::new(accounts.user_token_in, false),
+                AccountMeta::new(accounts.user_token_out, false),
+                AccountMeta::new_readonly(accounts.pool_authority, false),
+                AccountMeta::new_readonly(accounts.token_program, false),
+                AccountMeta::new_readonly(accounts.fees_collector, false),
+                AccountMeta::new(accounts.protocol_fee_account, false),
+                AccountMeta::new_readonly(accounts.pool_registration, false),
+            ],
+            data,
+        }
+    }
+}
+
+// ------------------------------------------------------------
+// Rate provider interface
+// ------------------------------------------------------------
+/// Exchange rates of yield‑bearing tokens (an LST against SOL, a lending
+/// receipt against its underlying), as Balancer's `IRateProvider`. A rate
+/// provider is any program with a `get_rate` instruction that takes the
+/// rate account it owns and returns the token's rate (little‑endian u128,
+/// 18‑dec) through return data. Pools that use them ("boosted" pools)
+/// multiply balances by the rate before any swap maths. The
+/// `rate-provider` program serves this interface for interest‑bearing
+/// Token‑2022 mints, SPL stake pool LSTs and keeper‑pushed rates, and
+/// fails `get_rate` once its cached rate is stale.
+pub mod rate_provider {
+    use super::*;
+
+    /// Anchor discriminator of `get_rate`
+    /// (first 8 bytes of sha256("global:get_rate")).
+    pub const GET_RATE_DISCRIMINATOR: [u8; 8] = [177, 72, 238, 202, 231, 17, 128, 243];
+
+    /// Build the read‑only `get_rate` instruction for `rate_account`, sent
+    /// to the program that owns it.
+    pub fn get_rate(program_id: Pubkey, rate_account: Pubkey) -> Instruction {
+        Instruction {
+            program_id,
+            accounts: vec![AccountMeta::new_readonly(rate_account, false)],
+            data: GET_RATE_DISCRIMINATOR.to_vec(),
+        }
+    }
+
+    /// The rate in a provider's return data, if it is one.
+    pub fn parse_rate(data: &[u8]) -> Option<u128> {
+        let rate = u128::from_le_bytes(data.try_into().ok()?);
+        (rate > 0).then_some(rate)
+    }
+}
+
+// ------------------------------------------------------------
+// Crank bounties
+// ------------------------------------------------------------
+/// Permissionless cranks (finalising weight schedules, ending sales,
+/// expiring pauses) pay whoever sends them a small bounty out of the
+/// lamports the cranked account holds above its rent‑exempt minimum.
+/// Anyone funds the bounty with a plain SOL transfer to that account.
+pub mod crank {
+    use super::*;
+
+    /// Most a single crank pays, in lamports
+    pub const CRANK_BOUNTY: u64 = 50_000;
+
+    /// Pay `cranker` up to [`CRANK_BOUNTY`] from `source`'s spare
+    /// lamports; `source` must be owned by the calling program. Returns
+    /// the amount paid, 0 when the bounty is unfunded.
+    pub fn pay_bounty(source: &AccountInfo, cranker: &AccountInfo) -> Result<u64> {
+        let reserve = Rent::get()?.minimum_balance(source.data_len());
+        let bounty = bounty(source.lamports(), reserve);
+        if bounty > 0 {
+            **source.try_borrow_mut_lamports()? -= bounty;
+            **cranker.try_borrow_mut_lamports()? += bounty;
+        }
+        Ok(bounty)
+    }
+
+    /// Bounty payable from an account holding `lamports` of which
+    /// `reserve` must stay.
+    pub fn bounty(lamports: u64, reserve: u64) -> u64 {
+        lamports.saturating_sub(reserve).min(CRANK_BOUNTY)
+    }
+}
+
+// ------------------------------------------------------------
+// Pool token helpers
+// ------------------------------------------------------------
+/// Fixed‑point scaling and SPL Token account checks shared by the
+/// stable, LBP, managed and linear pools. The maths fails with a
+/// `MathError` and the checks with the error code the caller passes;
+/// each pool maps both to its own `ErrorCode`.
+pub mod pool_tokens {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use math::{fees, fixed, MathError, U256};
+    use spl_token_2022::extension::{BaseState, StateWithExtensions};
+    use spl_token_2022::state::Mint;
+
+    /// A token account's base state, the same under either token program.
+    pub use spl_token_2022::state::Account as TokenAccount;
+
+    /// Protocol share (`pct`, 18‑dec) of a fee amount, rounded up.
+    pub fn protocol_share(fee_amount_fp: U256, pct: u64) -> std::result::Result<U256, MathError> {
+        fees::try_protocol_share(fee_amount_fp, U256::from(pct))
+    }
+
+    /// Raw token amount as an 18‑dec value.
+    pub fn scale_up(amount: u64, decimals: u8) -> std::result::Result<U256, MathError> {
+        fixed::try_scale_up(amount, decimals)
+    }
+
+    /// 18‑dec value as a raw token amount, rounded down (amounts paid out).
+    pub fn scale_down(value_fp: U256, decimals: u8) -> std::result::Result<u64, MathError> {
+        fixed::try_scale_down_rounding_down(value_fp, decimals)
+    }
+
+    /// 18‑dec value as a raw token amount, rounded up (amounts paid in).
+    pub fn scale_down_up(value_fp: U256, decimals: u8) -> std::result::Result<u64, MathError> {
+        fixed::try_scale_down_rounding_up(value_fp, decimals)
+    }
+
+    /// Decimals of a mint of either token program, checked to be
+    /// scalable to 18‑dec; an account that is not an initialized mint of
+    /// SPL Token or Token‑2022 fails with `not_a_mint`.
+    pub fn mint_decimals<E>(ai: &AccountInfo, not_a_mint: E) -> Result<u8>
+    where
+        E: From<MathError>,
+        Error: From<E>,
+    {
+        let decimals = match unpack_state::<Mint>(ai) {
+            Some(mint) => mint.decimals,
+            None => return Err(Error::from(not_a_mint).with_account_name(ai.key.to_string())),
+        };
+        fixed::try_scaling_factor(decimals).map_err(E::from)?;
+        Ok(decimals)
+    }
+
+    /// A token account of either token program, its extensions skipped;
+    /// an account that is not an initialized token account of SPL Token
+    /// or Token‑2022 fails with `invalid`.
+    pub fn unpack_token_account<E>(ai: &AccountInfo, invalid: E) -> Result<TokenAccount>
+    where
+        Error: From<E>,
+    {
+        unpack_state::<TokenAccount>(ai).ok_or_else(|| Error::from(invalid).with_account_name(ai.key.to_string()))
+    }
+
+    /// The base state of an account one of the token programs owns, if
+    /// it unpacks as an initialized `S`.
+    fn unpack_state<S: BaseState + Pack>(ai: &AccountInfo) -> Option<S> {
+        if *ai.owner != spl_token::ID && *ai.owner != spl_token_2022::ID {
+            return None;
+        }
+        let data = ai.try_borrow_data().ok()?;
+        Some(StateWithExtensions::<S>::unpack(&data).ok()?.base)
+    }
+
+    /// A protocol fee account must be a token account of `mint` held by
+    /// the collector; any other fails with `invalid`.
+    pub fn check_fee_account<E>(ai: &AccountInfo, collector: &Pubkey, mint: &Pubkey, invalid: E) -> Result<()>
+    where
+        Error: From<E>,
+    {
+        match unpack_state::<TokenAccount>(ai) {
+            Some(acct) if acct.owner == *collector && acct.mint == *mint => Ok(()),
+            _ => Err(invalid.into()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[error_code]
+    pub enum TestError {
+        NotAToken,
+    }
+
+    impl From<math::MathError> for TestError {
+        fn from(_: math::MathError) -> Self {
+            TestError::NotAToken
+        }
+    }
+
+    /// `state` packed as `owner`'s account at a fresh key.
+    fn run_with_account<S, R>(owner: &Pubkey, state: S, f: impl FnOnce(&AccountInfo) -> R) -> R
+    where
+        S: anchor_lang::solana_program::program_pack::Pack,
+    {
+        let (key, mut lamports) = (Pubkey::new_unique(), 0);
+        let mut data = vec![0; S::LEN];
+        S::pack(state, &mut data).unwrap();
+        f(&AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0))
+    }
+
+    #[test]
+    fn token_state_reads_under_either_token_program_only() {
+        use spl_token_2022::state::{Account, AccountState, Mint};
+        let mint = Mint { decimals: 9, is_initialized: true, ..Mint::default() };
+        let account = Account { amount: 7, state: AccountState::Initialized, ..Account::default() };
+        for program in [spl_token::ID, spl_token_2022::ID] {
+            let decimals = run_with_account(&program, mint, |ai| pool_tokens::mint_decimals(ai, TestError::NotAToken));
+            assert_eq!(decimals.unwrap(), 9);
+            let acct = run_with_account(&program, account, |ai| pool_tokens::unpack_token_account(ai, TestError::NotAToken));
+            assert_eq!(acct.unwrap().amount, 7);
+        }
+
+        // a foreign owner, an uninitialized account, or the wrong layout fail with the caller's error
+        let foreign = Pubkey::new_unique();
+        let not_a_token = Error::from(TestError::NotAToken);
+        let err = run_with_account(&foreign, mint, |ai| pool_tokens::mint_decimals(ai, TestError::NotAToken));
+        assert_eq!(err.unwrap_err(), not_a_token);
+        let err = run_with_account(&foreign, account, |ai| pool_tokens::unpack_token_account(ai, TestError::NotAToken));
+        assert_eq!(err.unwrap_err(), not_a_token);
+        let uninitialized = Account::default();
+        let err = run_with_account(&spl_token::ID, uninitialized, |ai| pool_tokens::unpack_token_account(ai, TestError::NotAToken));
+        assert_eq!(err.unwrap_err(), not_a_token);
+        let err = run_with_account(&spl_token::ID, mint, |ai| pool_tokens::unpack_token_account(ai, TestError::NotAToken));
+        assert_eq!(err.unwrap_err(), not_a_token);
+    }
+
+    #[test]
+    fn pool_id_depends_on_every_input() {
+        let (vault, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let id = derive_pool_id(&vault, &pool, Specialization::TwoToken, 0);
+        assert_eq!(id, derive_pool_id(&vault, &pool, Specialization::TwoToken, 0));
+        assert_ne!(id, derive_pool_id(&vault, &pool, Specialization::TwoToken, 1));
+        assert_ne!(id, derive_pool_id(&vault, &pool, Specialization::General, 0));
+        assert_ne!(id, derive_pool_id(&pool, &vault, Specialization::TwoToken, 0));
+    }
+
+    #[test]
+    fn canonical_hash_needs_sorted_tokens() {
+        let (mut a, mut b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        if a > b {
+            std::mem::swap(&mut a, &mut b);
+        }
+        let hash = canonical_pool_hash(&[a, b], &[8, 2], 30).unwrap();
+        assert_ne!(Some(hash), canonical_pool_hash(&[a, b], &[2, 8], 30));
+        assert_ne!(Some(hash), canonical_pool_hash(&[a, b], &[8, 2], 31));
+        assert_eq!(canonical_pool_hash(&[b, a], &[2, 8], 30), None);
+        assert_eq!(canonical_pool_hash(&[a, a], &[5, 5], 30), None);
+        assert_eq!(canonical_pool_hash(&[a, b], &[10], 30), None);
+    }
+
+    #[test]
+    fn swap_discriminator_matches_anchor() {
+        let hash = anchor_lang::solana_program::hash::hash(b"global:swap_exact_token_in_for_token_out");
+        assert_eq!(hash.to_bytes()[..8], pool_interface::SWAP_EXACT_IN_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn query_discriminator_matches_anchor() {
+        let hash = anchor_lang::solana_program::hash::hash(b"global:query_swap");
+        assert_eq!(hash.to_bytes()[..8], pool_interface::QUERY_SWAP_DISCRIMINATOR);
+    }
+
+    #[test]
+    fn get_rate_discriminator_matches_anchor() {
+        let hash = anchor_lang::solana_program::hash::hash(b"global:get_rate");
+        assert_eq!(hash.to_bytes()[..8], rate_provider::GET_RATE_DISCRIMINATOR);
+        assert_eq!(rate_provider::parse_rate(&1_050_000_000_000_000_000u128.to_le_bytes()), Some(1_050_000_000_000_000_000));
+        assert_eq!(rate_provider::parse_rate(&0u128.to_le_bytes()), None);
+        assert_eq!(rate_provider::parse_rate(&[1; 8]), None);
+    }
+
+    #[test]
+    fn managed_transfer_discriminator_matches_anchor() {
+        let hash = anchor_lang::solana_program::hash::hash(b"global:managed_transfer");
+        assert_eq!(hash.to_bytes()[..8], pool_interface::MANAGED_TRANSFER_DISCRIMINATOR);
+    }
+
     #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    fn crank_bounty_never_touches_the_rent_reserve() {
+        assert_eq!(crank::bounty(1_000, 1_000), 0);
+        assert_eq!(crank::bounty(1_000, 2_000), 0);
+        assert_eq!(crank::bounty(1_000 + 7, 1_000), 7);
+        assert_eq!(crank::bounty(u64::MAX, 1_000), crank::CRANK_BOUNTY);
     }
 }
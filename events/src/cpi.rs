@@ -0,0 +1,37 @@
+// Symmetric‑Solana ─ Events from self‑CPI instruction data
+// ================================================================
+// `emit_cpi!` carries an event as the data of an instruction the
+// program invokes on itself: Anchor's event tag, then the event's
+// discriminator and body. Unlike logs, those inner instructions are
+// never truncated. Callers pass the inner instructions' program ids
+// and data from the transaction's metadata.
+// ================================================================
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+
+use crate::{decode, DecodeError, Event};
+
+/// The event carried by an inner instruction of `program_id`; `None` if
+/// the data is not an `emit_cpi!` event or not a known one.
+pub fn decode_cpi(program_id: &Pubkey, ix_data: &[u8]) -> Result<Option<Event>, DecodeError> {
+    match ix_data.strip_prefix(EVENT_IX_TAG_LE) {
+        Some(data) => decode(program_id, data),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event as _;
+
+    #[test]
+    fn decodes_only_tagged_instruction_data() {
+        let event = limit_orders::OrderCancelled { order: Pubkey::new_unique(), maker: Pubkey::new_unique(), refund: 7 };
+        let mut ix_data = EVENT_IX_TAG_LE.to_vec();
+        ix_data.extend(event.data());
+
+        assert_eq!(decode_cpi(&limit_orders::ID, &ix_data), Ok(Some(Event::OrderCancelled(event.clone()))));
+        assert_eq!(decode_cpi(&limit_orders::ID, &event.data()), Ok(None));
+    }
+}
@@ -0,0 +1,295 @@
+// Symmetric‑Solana ─ Events
+// ================================================================
+// The programs' Anchor events as typed Rust values, for indexers,
+// bots and analytics:
+//   • `decode` maps an emitting program and its event bytes
+//     (discriminator + Borsh body) to an `Event`.
+//   • Weighted and stable pools emit the same swap, join and exit
+//     layouts, decoded into one `SwapEvent` / `JoinEvent` /
+//     `ExitEvent`; the record's program id tells the pools apart.
+//   • `logs` reads "Program data:" lines of a transaction's logs,
+//     attributing each to the program on top of the invoke stack.
+//   • `cpi` reads events self‑CPI'd with `emit_cpi!`.
+// Events of unknown programs or discriminators are skipped; a known
+// discriminator with a body that does not parse is an error.
+// ================================================================
+use anchor_lang::prelude::{borsh, AnchorDeserialize, Pubkey};
+use anchor_lang::Discriminator;
+use solana_signature::Signature;
+
+pub mod cpi;
+pub mod logs;
+
+pub use cpi::decode_cpi;
+pub use logs::parse_logs;
+
+/// Tokens were swapped through a weighted or stable pool.
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub token_in: Pubkey,
+    pub token_out: Pubkey,
+    /// Taken from the user, protocol fee included
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Part of `amount_in` paid to the protocol fee collector
+    pub protocol_fee: u64,
+    /// Marginal price of `token_out` in `token_in` (18‑dec, fee included)
+    /// before and after the swap; zero where the pool does not price it
+    pub spot_price_before: u128,
+    pub spot_price_after: u128,
+    /// Swap fee charged on `amount_in`, `protocol_fee` included
+    pub swap_fee_amount: u64,
+    /// Balances of `token_in` and `token_out` the swap was priced on
+    /// (18‑dec; a stable pool's at the tokens' decimals and rates)
+    pub balance_in: u128,
+    pub balance_out: u128,
+}
+
+/// Tokens were deposited into a weighted or stable pool for BPT.
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct JoinEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    /// In pool order
+    pub amounts_in: Vec<u64>,
+    pub bpt_out: u64,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+}
+
+/// BPT of a weighted or stable pool was burned for tokens.
+#[derive(AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ExitEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub bpt_in: u64,
+    /// In pool order
+    pub amounts_out: Vec<u64>,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+    /// Tokens paid to the protocol fee collector, in pool order
+    pub protocol_fees: Vec<u64>,
+}
+
+/// An event of any of the decoded programs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    // weighted and stable pools
+    Swap(SwapEvent),
+    Join(JoinEvent),
+    Exit(ExitEvent),
+    // weighted pool
+    PoolHealth(weighted_pool::PoolHealth),
+    // vault
+    PoolRegistered(vault::PoolRegistered),
+    TokensRegistered(vault::TokensRegistered),
+    PoolPaused(vault::PoolPaused),
+    PauseExpired(vault::PauseExpired),
+    PoolBalanceManaged(vault::PoolBalanceManaged),
+    FeesCollected(vault::FeesCollected),
+    // pool factory
+    PoolCreated(pool_factory::PoolCreated),
+    // managed pool
+    GradualWeightUpdateScheduled(managed_pool::GradualWeightUpdateScheduled),
+    GradualSwapFeeUpdateScheduled(managed_pool::GradualSwapFeeUpdateScheduled),
+    SchedulesFinalized(managed_pool::SchedulesFinalized),
+    ManagementAumFeeCollected(managed_pool::ManagementAumFeeCollected),
+    TokenAdded(managed_pool::TokenAdded),
+    TokenRemoved(managed_pool::TokenRemoved),
+    // liquidity bootstrapping pool
+    SwapEnabledSet(lbp::SwapEnabledSet),
+    SaleFinalized(lbp::SaleFinalized),
+    // limit orders
+    OrderPlaced(limit_orders::OrderPlaced),
+    OrderFilled(limit_orders::OrderFilled),
+    OrderCancelled(limit_orders::OrderCancelled),
+    // TWAMM
+    TwammInitialized(twamm::TwammInitialized),
+    LongTermOrderPlaced(twamm::LongTermOrderPlaced),
+    TwammExecuted(twamm::TwammExecuted),
+    ProceedsWithdrawn(twamm::ProceedsWithdrawn),
+    LongTermOrderCancelled(twamm::LongTermOrderCancelled),
+}
+
+impl Event {
+    /// The pool a pool event is about.
+    pub fn pool(&self) -> Option<Pubkey> {
+        match self {
+            Event::Swap(e) => Some(e.pool),
+            Event::Join(e) => Some(e.pool),
+            Event::Exit(e) => Some(e.pool),
+            Event::PoolHealth(e) => Some(e.pool),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded event with where it was emitted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRecord {
+    pub slot: u64,
+    pub signature: Signature,
+    /// Program that emitted the event
+    pub program_id: Pubkey,
+    /// Position among the transaction's decoded events
+    pub index: usize,
+    pub event: Event,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A "Program data:" payload that is not base64
+    Base64(String),
+    /// A known event whose body does not parse
+    Body { program_id: Pubkey, reason: String },
+}
+
+impl core::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DecodeError::Base64(data) => write!(f, "invalid base64 event data: {data}"),
+            DecodeError::Body { program_id, reason } => write!(f, "malformed event of {program_id}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// The first listed event whose discriminator prefixes `$data`, its body
+/// deserialized into the variant's type; `None` if none matches.
+macro_rules! decode_as {
+    ($program_id:expr, $data:expr; $($event:ty => $variant:path),+ $(,)?) => {{
+        let data: &[u8] = $data;
+        $(
+            if let Some(body) = data.strip_prefix(<$event as Discriminator>::DISCRIMINATOR) {
+                return AnchorDeserialize::try_from_slice(body)
+                    .map(|e| Some($variant(e)))
+                    .map_err(|err| DecodeError::Body { program_id: *$program_id, reason: err.to_string() });
+            }
+        )+
+        Ok(None)
+    }};
+}
+
+/// Decode an event emitted by `program_id` (discriminator + body).
+pub fn decode(program_id: &Pubkey, data: &[u8]) -> Result<Option<Event>, DecodeError> {
+    match *program_id {
+        id if id == weighted_pool::ID => decode_as!(program_id, data;
+            weighted_pool::SwapEvent => Event::Swap,
+            weighted_pool::JoinEvent => Event::Join,
+            weighted_pool::ExitEvent => Event::Exit,
+            weighted_pool::PoolHealth => Event::PoolHealth,
+        ),
+        id if id == stable_pool::ID => decode_as!(program_id, data;
+            weighted_pool::SwapEvent => Event::Swap,
+            weighted_pool::JoinEvent => Event::Join,
+            weighted_pool::ExitEvent => Event::Exit,
+        ),
+        id if id == vault::ID => decode_as!(program_id, data;
+            vault::PoolRegistered => Event::PoolRegistered,
+            vault::TokensRegistered => Event::TokensRegistered,
+            vault::PoolPaused => Event::PoolPaused,
+            vault::PauseExpired => Event::PauseExpired,
+            vault::PoolBalanceManaged => Event::PoolBalanceManaged,
+            vault::FeesCollected => Event::FeesCollected,
+        ),
+        id if id == pool_factory::ID => decode_as!(program_id, data;
+            pool_factory::PoolCreated => Event::PoolCreated,
+        ),
+        id if id == managed_pool::ID => decode_as!(program_id, data;
+            managed_pool::GradualWeightUpdateScheduled => Event::GradualWeightUpdateScheduled,
+            managed_pool::GradualSwapFeeUpdateScheduled => Event::GradualSwapFeeUpdateScheduled,
+            managed_pool::SchedulesFinalized => Event::SchedulesFinalized,
+            managed_pool::ManagementAumFeeCollected => Event::ManagementAumFeeCollected,
+            managed_pool::TokenAdded => Event::TokenAdded,
+            managed_pool::TokenRemoved => Event::TokenRemoved,
+        ),
+        id if id == lbp::ID => decode_as!(program_id, data;
+            lbp::SwapEnabledSet => Event::SwapEnabledSet,
+            lbp::SaleFinalized => Event::SaleFinalized,
+        ),
+        id if id == limit_orders::ID => decode_as!(program_id, data;
+            limit_orders::OrderPlaced => Event::OrderPlaced,
+            limit_orders::OrderFilled => Event::OrderFilled,
+            limit_orders::OrderCancelled => Event::OrderCancelled,
+        ),
+        id if id == twamm::ID => decode_as!(program_id, data;
+            twamm::TwammInitialized => Event::TwammInitialized,
+            twamm::LongTermOrderPlaced => Event::LongTermOrderPlaced,
+            twamm::TwammExecuted => Event::TwammExecuted,
+            twamm::ProceedsWithdrawn => Event::ProceedsWithdrawn,
+            twamm::LongTermOrderCancelled => Event::LongTermOrderCancelled,
+        ),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event as _;
+
+    #[test]
+    fn decodes_both_pools_into_one_swap_event() {
+        let pool = Pubkey::new_unique();
+        let weighted = weighted_pool::SwapEvent {
+            pool,
+            pool_id: [7; 32],
+            user: Pubkey::new_unique(),
+            token_in: Pubkey::new_unique(),
+            token_out: Pubkey::new_unique(),
+            amount_in: 1_000,
+            amount_out: 990,
+            protocol_fee: 1,
+            spot_price_before: 1_003_009_027_081_243_732,
+            spot_price_after: 1_005_018_072_289_156_627,
+            swap_fee_amount: 3,
+            balance_in: 1_000_000_000_000_000_000_000,
+            balance_out: 1_000_000_000_000_000_000_000,
+        };
+        let stable = stable_pool::SwapEvent {
+            pool,
+            pool_id: weighted.pool_id,
+            user: weighted.user,
+            token_in: weighted.token_in,
+            token_out: weighted.token_out,
+            amount_in: 1_000,
+            amount_out: 990,
+            protocol_fee: 1,
+            spot_price_before: weighted.spot_price_before,
+            spot_price_after: weighted.spot_price_after,
+            swap_fee_amount: 3,
+            balance_in: weighted.balance_in,
+            balance_out: weighted.balance_out,
+        };
+        let from_weighted = decode(&weighted_pool::ID, &weighted.data()).unwrap().unwrap();
+        assert_eq!(decode(&stable_pool::ID, &stable.data()).unwrap(), Some(from_weighted.clone()));
+        assert_eq!(from_weighted.pool(), Some(pool));
+
+        // unknown program, unknown discriminator, truncated body
+        assert_eq!(decode(&Pubkey::new_unique(), &weighted.data()), Ok(None));
+        assert_eq!(decode(&vault::ID, &weighted.data()), Ok(None));
+        let data = weighted.data();
+        assert!(decode(&weighted_pool::ID, &data[..data.len() - 1]).is_err());
+
+        // the health probe is the weighted pool's own
+        let health = weighted_pool::PoolHealth {
+            pool,
+            pool_id: [7; 32],
+            balances: vec![1_000, 4_000],
+            invariant: 2_000,
+            bpt_supply: 4_000,
+            total_bpt: 4_000,
+            weight_sum: 1_000_000_000_000_000_000,
+            healthy: true,
+        };
+        let decoded = decode(&weighted_pool::ID, &health.data()).unwrap().unwrap();
+        assert_eq!(decoded.pool(), Some(pool));
+        assert_eq!(decode(&stable_pool::ID, &health.data()), Ok(None));
+    }
+}
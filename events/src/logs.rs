@@ -0,0 +1,98 @@
+// Symmetric‑Solana ─ Events from transaction logs
+// ================================================================
+// `emit!` writes "Program data: <base64>" to the log of the program
+// running it. The runtime brackets each program's logs with
+//   Program <id> invoke [<depth>]
+//   Program <id> success | Program <id> failed: …
+// so the emitting program is the top of a stack of those lines, even
+// for events emitted inside CPIs. Truncated logs lose the events past
+// the cut.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_signature::Signature;
+
+use crate::{decode, DecodeError, EventRecord};
+
+const PROGRAM_DATA: &str = "Program data: ";
+
+/// The decoded events of a transaction's log messages, in order.
+pub fn parse_logs(slot: u64, signature: Signature, logs: &[String]) -> Result<Vec<EventRecord>, DecodeError> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut records = Vec::new();
+    for line in logs {
+        if let Some(payload) = line.strip_prefix(PROGRAM_DATA) {
+            let Some(program_id) = stack.last() else { continue };
+            // `sol_log_data` logs each slice separately, space‑separated
+            let mut data = Vec::new();
+            for chunk in payload.split_whitespace() {
+                data.extend(STANDARD.decode(chunk).map_err(|_| DecodeError::Base64(chunk.to_string()))?);
+            }
+            if let Some(event) = decode(program_id, &data)? {
+                records.push(EventRecord { slot, signature, program_id: *program_id, index: records.len(), event });
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let (Some(id), Some(status)) = (words.next(), words.next()) else { continue };
+            match (id.parse::<Pubkey>(), status) {
+                (Ok(id), "invoke") => stack.push(id),
+                (Ok(_), "success" | "failed:") => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Event;
+    use anchor_lang::Event as _;
+
+    #[test]
+    fn attributes_events_to_the_invoked_program() {
+        let event = weighted_pool::ExitEvent {
+            pool: Pubkey::new_unique(),
+            pool_id: [1; 32],
+            user: Pubkey::new_unique(),
+            bpt_in: 500,
+            amounts_out: vec![10, 20],
+            protocol_bpt: 0,
+            protocol_fees: vec![0, 0],
+        };
+        let data = format!("{PROGRAM_DATA}{}", STANDARD.encode(event.data()));
+        let logs: Vec<String> = [
+            format!("Program {} invoke [1]", vault::ID),
+            format!("Program {} invoke [2]", weighted_pool::ID),
+            "Program log: Instruction: ExitExactBptInForTokensOut".to_string(),
+            data.clone(),
+            format!("Program {} consumed 4000 of 200000 compute units", weighted_pool::ID),
+            format!("Program {} success", weighted_pool::ID),
+            // same bytes from the vault: not a vault event
+            data.clone(),
+            format!("Program {} success", vault::ID),
+            format!("Program {} invoke [1]", weighted_pool::ID),
+            data,
+            format!("Program {} success", weighted_pool::ID),
+        ]
+        .into_iter()
+        .collect();
+
+        let signature = Signature::default();
+        let records = parse_logs(42, signature, &logs).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().all(|r| r.program_id == weighted_pool::ID && r.slot == 42));
+        assert_eq!(records[1].index, 1);
+        match &records[0].event {
+            Event::Exit(exit) => assert_eq!(exit.amounts_out, vec![10, 20]),
+            other => panic!("unexpected {other:?}"),
+        }
+
+        let bad = vec![format!("Program {} invoke [1]", weighted_pool::ID), format!("{PROGRAM_DATA}!!")];
+        assert!(matches!(parse_logs(0, signature, &bad), Err(DecodeError::Base64(_))));
+    }
+}
@@ -0,0 +1,188 @@
+// Symmetric‑Solana ─ Indexer local API
+// ================================================================
+// The pool view over plain HTTP/1.1 GETs, JSON responses:
+//   • /health                       indexed slot and pool count;
+//   • /pools                        every pool;
+//   • /pools/<address>              one pool;
+//   • /quote?in=&out=&amount=[&pool=][&exact=out]
+//                                   a swap quote against one pool, or
+//                                   the best indexed pool.
+// `serve` answers with any such router, so services built on the
+// indexer (quote-server) add their own routes. Meant behind a proxy or
+// on a local network: no TLS, no keep‑alive, one request per
+// connection; any origin may read the responses (CORS).
+// ================================================================
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, RwLock};
+
+use anchor_lang::prelude::Pubkey;
+use quoter::{PoolState, SwapQuote};
+use serde_json::{json, Value};
+
+use crate::view::{IndexedPool, PoolView};
+
+/// Status and JSON body of a GET of a target, from the view.
+pub type Router = fn(&PoolView, &str) -> (u16, Value);
+
+/// Answer requests on `listener` with `route` over the shared view,
+/// forever.
+pub fn serve(listener: TcpListener, view: Arc<RwLock<PoolView>>, route: Router) {
+    for stream in listener.incoming().flatten() {
+        let view = view.clone();
+        std::thread::spawn(move || {
+            let _ = respond(stream, &view, route);
+        });
+    }
+}
+
+fn respond(mut stream: TcpStream, view: &RwLock<PoolView>, route: Router) -> std::io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let (status, body) = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", target, ..] => route(&view.read().expect("view lock poisoned"), target),
+        _ => (405, json!({ "error": "only GET is supported" })),
+    };
+    let body = body.to_string();
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+/// Status and JSON body of a GET of `target`.
+pub fn route(view: &PoolView, target: &str) -> (u16, Value) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let result = match path.trim_end_matches('/') {
+        "/health" => Ok(json!({ "slot": view.slot(), "pools": view.pools().len() })),
+        "/pools" => Ok(view.pools().iter().map(|(address, pool)| pool_json(address, pool)).collect()),
+        "/quote" => swap_quote(view, query),
+        path => match path.strip_prefix("/pools/").map(str::parse::<Pubkey>) {
+            Some(Ok(address)) => view.pool(&address).map(|p| pool_json(&address, p)).ok_or((404, "pool not indexed".to_string())),
+            Some(Err(_)) => Err((400, "invalid pool address".to_string())),
+            None => Err((404, "not found".to_string())),
+        },
+    };
+    match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, json!({ "error": error })),
+    }
+}
+
+/// The JSON document of an indexed pool; 18‑dec values as strings.
+pub fn pool_json(address: &Pubkey, pool: &IndexedPool) -> Value {
+    let mut doc = json!({
+        "address": address.to_string(),
+        "vault": pool.vault.to_string(),
+        "slot": pool.slot,
+        "error": pool.error,
+    });
+    let fields = match &pool.state {
+        Some(PoolState::Weighted(s)) => json!({
+            "kind": "weighted",
+            "swap_fee": s.pool.swap_fee.to_string(),
+            "total_bpt": s.pool.total_bpt,
+            "tokens": (0..s.tokens.len()).map(|i| json!({
+                "mint": s.tokens[i].to_string(),
+                "balance": s.cash[i],
+                "managed": s.managed[i],
+                "weight": s.pool.weights.get(i).map(|w| w.to_string()),
+            })).collect::<Vec<_>>(),
+        }),
+        Some(PoolState::Stable(s)) => json!({
+            "kind": "stable",
+            "swap_fee": s.pool.swap_fee.to_string(),
+            "total_bpt": s.pool.total_bpt,
+            "amp": s.pool.amp,
+            "tokens": (0..s.pool.tokens.len()).map(|i| json!({
+                "mint": s.pool.tokens[i].to_string(),
+                "balance": s.cash[i],
+                "managed": s.managed[i],
+                "rate": s.pool.rates.get(i).map(|r| r.to_string()),
+            })).collect::<Vec<_>>(),
+        }),
+        None => json!({}),
+    };
+    if let (Value::Object(doc), Value::Object(fields)) = (&mut doc, fields) {
+        doc.extend(fields);
+    }
+    doc
+}
+
+/// The value of `name` in a query string.
+pub fn param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&').filter_map(|kv| kv.split_once('=')).find(|(k, _)| *k == name).map(|(_, v)| v)
+}
+
+/// The value of `name` parsed, or a 400 naming it.
+pub fn required<T: std::str::FromStr>(query: &str, name: &str) -> Result<T, (u16, String)> {
+    param(query, name).and_then(|v| v.parse().ok()).ok_or((400, format!("missing or invalid `{name}`")))
+}
+
+/// A swap quote (`in`, `out`, `amount`, [`exact=out`]) against `pool`,
+/// or the best priced pool holding both tokens: most out for exact in,
+/// least in for exact out.
+pub fn swap_quote(view: &PoolView, query: &str) -> Result<Value, (u16, String)> {
+    let mint_in: Pubkey = required(query, "in")?;
+    let mint_out: Pubkey = required(query, "out")?;
+    let amount: u64 = required(query, "amount")?;
+    let exact_out = param(query, "exact") == Some("out");
+    let pools: Vec<(Pubkey, &IndexedPool)> = match param(query, "pool") {
+        Some(_) => {
+            let address: Pubkey = required(query, "pool")?;
+            vec![(address, view.pool(&address).ok_or((404, "pool not indexed".to_string()))?)]
+        }
+        None => view.pools().iter().map(|(a, p)| (*a, p)).collect(),
+    };
+    let price = |state: &PoolState| -> Option<SwapQuote> {
+        if exact_out {
+            state.swap_exact_out(&mint_in, &mint_out, amount).ok()
+        } else {
+            state.swap_exact_in(&mint_in, &mint_out, amount).ok()
+        }
+    };
+    let quotes = pools.iter().filter_map(|(address, pool)| Some((address, pool.slot, price(pool.state.as_ref()?)?)));
+    let best = if exact_out {
+        quotes.min_by_key(|(_, _, q)| q.amount_in)
+    } else {
+        quotes.max_by_key(|(_, _, q)| q.amount_out)
+    };
+    let (address, slot, q) = best.ok_or((404, "no indexed pool quotes this swap".to_string()))?;
+    Ok(json!({
+        "pool": address.to_string(),
+        "slot": slot,
+        "amount_in": q.amount_in,
+        "amount_out": q.amount_out,
+        "protocol_fee": q.protocol_fee,
+        "price_impact": q.price_impact.to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_and_rejects_bad_requests() {
+        let view = PoolView::new([Pubkey::new_unique()]);
+        assert_eq!(route(&view, "/health"), (200, json!({ "slot": 0, "pools": 0 })));
+        assert_eq!(route(&view, "/pools/").1, json!([]));
+        assert_eq!(route(&view, "/pools/nope").0, 400);
+        assert_eq!(route(&view, &format!("/pools/{}", Pubkey::new_unique())).0, 404);
+        assert_eq!(route(&view, "/elsewhere").0, 404);
+
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(route(&view, &format!("/quote?in={a}&out={b}")).0, 400);
+        let (status, body) = route(&view, &format!("/quote?in={a}&out={b}&amount=10"));
+        assert_eq!((status, body["error"].as_str()), (404, Some("no indexed pool quotes this swap")));
+        assert_eq!(param("a=1&b=2", "b"), Some("2"));
+    }
+}
@@ -0,0 +1,138 @@
+// Symmetric‑Solana ─ Indexer account feeds
+// ================================================================
+// Account updates for the pool view, as (address, RawAccount):
+//   • `fetch` reads accounts over RPC, at the response's slot.
+//   • `Feed` holds websocket program subscriptions, each forwarded by
+//     its own thread into one channel:
+//       – the Vault, weighted and stable pool programs, whole;
+//       – both token programs, filtered to the accounts owned by one
+//         pool's authority (its custody accounts).
+// Dropping the feed ends every subscription.
+// ================================================================
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use solana_account_decoder::UiAccountEncoding;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubsub_client::pubsub_client::{PubsubClient, PubsubProgramClientSubscription};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client_api::config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use solana_rpc_client_api::response::{Response, RpcKeyedAccount};
+
+use crate::view::RawAccount;
+
+/// Accounts per `getMultipleAccounts` request
+const FETCH_CHUNK: usize = 100;
+
+/// Offset of the owner in a token account of either program
+const TOKEN_OWNER_OFFSET: usize = 32;
+
+pub type Update = (Pubkey, RawAccount);
+
+/// Fetch `addresses`; accounts that do not exist are left out.
+pub fn fetch(rpc: &RpcClient, addresses: &[Pubkey]) -> Result<Vec<Update>, Box<dyn Error>> {
+    let mut updates = Vec::new();
+    for chunk in addresses.chunks(FETCH_CHUNK) {
+        let response = rpc.get_multiple_accounts_with_commitment(chunk, rpc.commitment())?;
+        let slot = response.context.slot;
+        updates.extend(
+            chunk
+                .iter()
+                .zip(response.value)
+                .filter_map(|(address, account)| account.map(|a| (*address, RawAccount { owner: a.owner, data: a.data, slot }))),
+        );
+    }
+    Ok(updates)
+}
+
+/// The update carried by a program subscription notification.
+pub fn keyed_update(response: Response<RpcKeyedAccount>) -> Option<Update> {
+    let address = response.value.pubkey.parse().ok()?;
+    let account = response.value.account;
+    Some((address, RawAccount { owner: account.owner.parse().ok()?, data: account.data.decode()?, slot: response.context.slot }))
+}
+
+/// Program subscriptions feeding one channel.
+pub struct Feed {
+    ws_url: String,
+    commitment: CommitmentConfig,
+    sender: Sender<Update>,
+    authorities: HashSet<Pubkey>,
+    /// Kept alive: dropping a subscription unsubscribes it
+    subscriptions: Vec<PubsubProgramClientSubscription>,
+}
+
+impl Feed {
+    pub fn new(ws_url: String, commitment: CommitmentConfig, sender: Sender<Update>) -> Self {
+        Feed { ws_url, commitment, sender, authorities: HashSet::new(), subscriptions: Vec::new() }
+    }
+
+    /// Subscribe to the accounts of `program` matching `filters`.
+    pub fn subscribe(&mut self, program: &Pubkey, filters: Option<Vec<RpcFilterType>>) -> Result<(), Box<dyn Error>> {
+        let config = RpcProgramAccountsConfig {
+            filters,
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(self.commitment),
+                ..Default::default()
+            },
+            with_context: Some(true),
+            ..Default::default()
+        };
+        let (subscription, receiver) = PubsubClient::program_subscribe(&self.ws_url, program, Some(config))?;
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            for update in receiver.iter().filter_map(keyed_update) {
+                if sender.send(update).is_err() {
+                    break;
+                }
+            }
+        });
+        self.subscriptions.push(subscription);
+        Ok(())
+    }
+
+    /// Subscribe to the token accounts owned by a pool authority, under
+    /// either token program, once.
+    pub fn watch_custody(&mut self, authority: Pubkey) -> Result<(), Box<dyn Error>> {
+        if self.authorities.contains(&authority) {
+            return Ok(());
+        }
+        let owner = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(TOKEN_OWNER_OFFSET, authority.as_ref()));
+        let filters = vec![RpcFilterType::DataSize(spl_token::state::Account::LEN as u64), owner.clone()];
+        self.subscribe(&spl_token::ID, Some(filters))?;
+        // Token‑2022 accounts grow with their extensions: no size filter
+        self.subscribe(&spl_token_2022::ID, Some(vec![owner]))?;
+        self.authorities.insert(authority);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_account_decoder::{UiAccount, UiAccountData};
+    use solana_rpc_client_api::response::RpcResponseContext;
+
+    #[test]
+    fn reads_notifications_at_their_slot() {
+        let (address, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let response = |data| Response {
+            context: RpcResponseContext { slot: 77, api_version: None },
+            value: RpcKeyedAccount {
+                pubkey: address.to_string(),
+                account: UiAccount { lamports: 1, data, owner: owner.to_string(), executable: false, rent_epoch: 0, space: None },
+            },
+        };
+        let (key, raw) = keyed_update(response(UiAccountData::Binary("AQID".into(), UiAccountEncoding::Base64))).unwrap();
+        assert_eq!((key, raw.owner, raw.data, raw.slot), (address, owner, vec![1, 2, 3], 77));
+        assert!(keyed_update(response(UiAccountData::LegacyBinary("0OIl".into()))).is_none());
+    }
+}
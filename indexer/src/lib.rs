@@ -0,0 +1,100 @@
+// Symmetric‑Solana ─ Indexer
+// ================================================================
+// Keeps every weighted and stable pool of the given Vaults in memory,
+// so routers, bots and quote services price without an RPC round trip
+// per quote:
+//   • websocket subscriptions to the Vault and pool programs, and to
+//     each pool's custody accounts (feed);
+//   • the registry of each Vault and any account the view still
+//     misses are fetched over RPC (feed::fetch);
+//   • the view reprices a pool on every update (view);
+//   • HTTP/JSON serving of the view (api).
+// `index` runs the whole loop into a shared view. A Geyser plugin can
+// feed the same view: it only takes (address, account, slot) updates.
+// ================================================================
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+
+use anchor_lang::prelude::Pubkey;
+use client::{fetch_registered_pools, pda};
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::rpc_client::RpcClient;
+
+pub mod api;
+pub mod feed;
+pub mod view;
+
+use feed::{fetch, Feed};
+use view::PoolView;
+
+/// The websocket URL of an RPC URL, as the Solana CLI derives it: same
+/// host, ws(s) scheme, the next port when one is given.
+pub fn ws_url(rpc_url: &str) -> String {
+    let (scheme, rest) = match rpc_url.split_once("://") {
+        Some(("https", rest)) => ("wss", rest),
+        Some((_, rest)) => ("ws", rest),
+        None => ("ws", rpc_url),
+    };
+    let (host, path) = rest.split_once('/').map_or((rest, ""), |(h, p)| (h, p));
+    let host = match host.rsplit_once(':').map(|(h, p)| (h, p.parse::<u16>())) {
+        Some((h, Ok(port))) => format!("{h}:{}", port + 1),
+        _ => host.to_string(),
+    };
+    let path = if path.is_empty() { String::new() } else { format!("/{path}") };
+    format!("{scheme}://{host}{path}")
+}
+
+/// Index the pools of `view`'s Vaults from `rpc_url` and `ws_url` into
+/// `view`, until a feed or the RPC fails.
+pub fn index(rpc_url: &str, ws_url: String, commitment: CommitmentConfig, view: Arc<RwLock<PoolView>>) -> Result<(), Box<dyn Error>> {
+    let rpc = RpcClient::new_with_commitment(rpc_url.to_string(), commitment);
+    let vaults = view.read().expect("view lock poisoned").vaults();
+
+    // Subscribe before the first fetch so no update falls in between
+    let (sender, updates) = mpsc::channel();
+    let mut feed = Feed::new(ws_url, commitment, sender);
+    for program in [vault::ID, weighted_pool::ID, stable_pool::ID] {
+        feed.subscribe(&program, None)?;
+    }
+
+    let mut pending: Vec<Pubkey> = Vec::new();
+    for vault_state in &vaults {
+        pending.extend(fetch_registered_pools(&rpc, vault_state)?.iter().map(|e| pda::pool_registration(vault_state, &e.pool_id)));
+    }
+    // Accounts fetched once; later changes arrive from the feed
+    let mut requested: HashSet<Pubkey> = HashSet::new();
+    loop {
+        requested.extend(pending.iter().copied());
+        let mut fetched = fetch(&rpc, &pending)?;
+        if pending.is_empty() {
+            fetched.push(updates.recv()?);
+        }
+        fetched.extend(updates.try_iter());
+
+        let authorities = {
+            let mut view = view.write().expect("view lock poisoned");
+            for (address, account) in fetched {
+                view.update(address, account);
+            }
+            pending = view.missing().into_iter().filter(|a| !requested.contains(a)).collect();
+            view.authorities()
+        };
+        for authority in authorities {
+            feed.watch_custody(authority)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_websocket_urls() {
+        assert_eq!(ws_url("http://localhost:8899"), "ws://localhost:8900");
+        assert_eq!(ws_url("https://api.devnet.solana.com"), "wss://api.devnet.solana.com");
+        assert_eq!(ws_url("https://rpc.example.com:443/key"), "wss://rpc.example.com:444/key");
+    }
+}
@@ -0,0 +1,89 @@
+// Symmetric‑Solana ─ symmetric-indexer
+// ================================================================
+// Runs the indexer (`indexer::index`) over the given Vaults and serves
+// its view locally, so routers and bots quote without an RPC round
+// trip per quote:
+//   • /health, /pools, /pools/<address> and /quote (indexer::api);
+//   • `--url` / `--ws` pick the cluster, `--vault` each Vault indexed.
+// ================================================================
+use std::error::Error;
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::sync::{Arc, RwLock};
+
+use anchor_lang::prelude::Pubkey;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use indexer::view::PoolView;
+use indexer::{api, index, ws_url};
+use solana_commitment_config::CommitmentConfig;
+
+fn cli() -> Command {
+    Command::new("symmetric-indexer")
+        .about("In-memory view of Symmetric pools, served over a local HTTP API")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .default_value("http://localhost:8899")
+                .help("RPC URL"),
+        )
+        .arg(Arg::new("ws").long("ws").help("Websocket URL [default: the RPC URL on ws(s), port + 1]"))
+        .arg(
+            Arg::new("vault")
+                .long("vault")
+                .value_name("ADDRESS")
+                .value_parser(|s: &str| s.parse::<Pubkey>().map_err(|_| format!("invalid address: {s}")))
+                .action(ArgAction::Append)
+                .required(true)
+                .help("Vault state to index; repeat per Vault"),
+        )
+        .arg(Arg::new("listen").long("listen").default_value("127.0.0.1:8900").help("Address of the HTTP API"))
+        .arg(
+            Arg::new("commitment")
+                .long("commitment")
+                .value_parser(["processed", "confirmed", "finalized"])
+                .default_value("confirmed"),
+        )
+}
+
+fn run(matches: ArgMatches) -> Result<(), Box<dyn Error>> {
+    let commitment = match matches.get_one::<String>("commitment").map(String::as_str) {
+        Some("processed") => CommitmentConfig::processed(),
+        Some("finalized") => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    };
+    let url = matches.get_one::<String>("url").expect("defaulted");
+    let ws = matches.get_one::<String>("ws").cloned().unwrap_or_else(|| ws_url(url));
+    let vaults: Vec<Pubkey> = matches.get_many::<Pubkey>("vault").expect("required").copied().collect();
+    let view = Arc::new(RwLock::new(PoolView::new(vaults)));
+
+    let listener = TcpListener::bind(matches.get_one::<String>("listen").expect("defaulted"))?;
+    println!("serving on {}", listener.local_addr()?);
+    let api_view = view.clone();
+    std::thread::spawn(move || api::serve(listener, api_view, api::route));
+    index(url, ws, commitment, view)
+}
+
+fn main() -> ExitCode {
+    match run(cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_vaults() {
+        cli().debug_assert();
+        let vault = Pubkey::new_unique();
+        let m = cli().try_get_matches_from(["symmetric-indexer", "--vault", &vault.to_string()]).unwrap();
+        assert_eq!(m.get_many::<Pubkey>("vault").unwrap().copied().collect::<Vec<_>>(), [vault]);
+        assert!(cli().try_get_matches_from(["symmetric-indexer"]).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+// Symmetric‑Solana ─ Indexer pool view
+// ================================================================
+// The in‑memory state of every pool of the indexed Vaults, rebuilt
+// from raw account updates whatever their source (RPC fetch,
+// websocket or a Geyser feed):
+//   • A `PoolRegistration` of an indexed Vault starts tracking its
+//     pool; the pool, custody and fees‑collector accounts it names are
+//     reported missing until an update for them arrives.
+//   • Each update of a tracked account reprices the pools reading it
+//     with the quoter's `PoolState`.
+//   • An update older than the stored one for the same account is
+//     dropped, so feeds may race.
+// ================================================================
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use quoter::{AccountData, PoolState};
+use vault::PoolRegistration;
+
+/// An account's data as of a slot.
+#[derive(Clone, Debug)]
+pub struct RawAccount {
+    pub owner: Pubkey,
+    pub data: Vec<u8>,
+    pub slot: u64,
+}
+
+/// A tracked pool and its accounts.
+#[derive(Clone)]
+pub struct IndexedPool {
+    pub registration: Pubkey,
+    pub vault: Pubkey,
+    /// Custody account of each token, in registration order
+    pub custody: Vec<Pubkey>,
+    pub fees_collector: Pubkey,
+    /// Priced state, once every account is known and consistent
+    pub state: Option<PoolState>,
+    /// Latest slot of the accounts `state` was built from
+    pub slot: u64,
+    /// Why `state` is missing
+    pub error: Option<String>,
+}
+
+impl IndexedPool {
+    fn accounts(&self, pool: &Pubkey) -> impl Iterator<Item = Pubkey> + '_ {
+        [*pool, self.registration, self.fees_collector].into_iter().chain(self.custody.iter().copied())
+    }
+}
+
+/// Pools of the indexed Vaults, by pool address.
+#[derive(Default)]
+pub struct PoolView {
+    vaults: HashSet<Pubkey>,
+    accounts: HashMap<Pubkey, RawAccount>,
+    pools: BTreeMap<Pubkey, IndexedPool>,
+    /// Tracked account → pools reading it
+    readers: HashMap<Pubkey, Vec<Pubkey>>,
+}
+
+impl PoolView {
+    pub fn new(vaults: impl IntoIterator<Item = Pubkey>) -> Self {
+        PoolView { vaults: vaults.into_iter().collect(), ..Default::default() }
+    }
+
+    /// The indexed Vaults.
+    pub fn vaults(&self) -> Vec<Pubkey> {
+        self.vaults.iter().copied().collect()
+    }
+
+    pub fn pools(&self) -> &BTreeMap<Pubkey, IndexedPool> {
+        &self.pools
+    }
+
+    pub fn pool(&self, address: &Pubkey) -> Option<&IndexedPool> {
+        self.pools.get(address)
+    }
+
+    /// Latest slot of any stored account.
+    pub fn slot(&self) -> u64 {
+        self.accounts.values().map(|a| a.slot).max().unwrap_or(0)
+    }
+
+    /// Apply one account update; returns the tracked accounts still
+    /// missing, to fetch.
+    pub fn update(&mut self, address: Pubkey, account: RawAccount) -> Vec<Pubkey> {
+        if self.accounts.get(&address).is_some_and(|a| a.slot > account.slot) {
+            return Vec::new();
+        }
+        if account.owner == vault::ID && !self.readers.contains_key(&address) {
+            self.discover(address, &account);
+        }
+        if !self.readers.contains_key(&address) {
+            return Vec::new();
+        }
+        self.accounts.insert(address, account);
+        for pool in self.readers[&address].clone() {
+            self.reprice(&pool);
+        }
+        self.missing()
+    }
+
+    /// Start tracking the pool of a registration of an indexed Vault.
+    fn discover(&mut self, address: Pubkey, account: &RawAccount) {
+        let Ok(registration) = PoolRegistration::try_deserialize(&mut &account.data[..]) else { return };
+        if !self.vaults.contains(&registration.vault) || self.pools.contains_key(&registration.pool) {
+            return;
+        }
+        let pool = IndexedPool {
+            registration: address,
+            vault: registration.vault,
+            custody: Vec::new(),
+            fees_collector: client::pda::fees_collector(&registration.vault),
+            state: None,
+            slot: 0,
+            error: None,
+        };
+        self.track(registration.pool, pool);
+    }
+
+    fn track(&mut self, address: Pubkey, pool: IndexedPool) {
+        for account in pool.accounts(&address) {
+            let readers = self.readers.entry(account).or_default();
+            if !readers.contains(&address) {
+                readers.push(address);
+            }
+        }
+        self.pools.insert(address, pool);
+    }
+
+    /// Token authority of each pool whose program is known: the owner of
+    /// its custody accounts.
+    pub fn authorities(&self) -> Vec<Pubkey> {
+        self.pools
+            .keys()
+            .filter_map(|pool| self.accounts.get(pool).map(|raw| client::pda::pool_authority(pool, &raw.owner)))
+            .collect()
+    }
+
+    /// Tracked accounts without data yet.
+    pub fn missing(&self) -> Vec<Pubkey> {
+        self.readers.keys().filter(|a| !self.accounts.contains_key(a)).copied().collect()
+    }
+
+    /// Rebuild a pool's state from its stored accounts.
+    fn reprice(&mut self, address: &Pubkey) {
+        let Some(mut pool) = self.pools.get(address).cloned() else { return };
+        // Registering tokens later fills in custody accounts
+        if let Some(raw) = self.accounts.get(&pool.registration) {
+            if let Ok(registration) = PoolRegistration::try_deserialize(&mut &raw.data[..]) {
+                pool.custody = registration.token_accounts.into_iter().filter(|a| *a != Pubkey::default()).collect();
+                if pool.custody.len() < registration.tokens.len() {
+                    pool.custody.clear();
+                }
+            }
+        }
+        let accounts: Option<Vec<AccountData>> = pool
+            .accounts(address)
+            .map(|a| self.accounts.get(&a).map(|raw| AccountData { address: a, owner: raw.owner, data: &raw.data }))
+            .collect();
+        match accounts {
+            Some(accounts) if !pool.custody.is_empty() => {
+                let (fixed, custody) = accounts.split_at(3);
+                match PoolState::from_accounts(&fixed[0], &fixed[1], custody, &fixed[2]) {
+                    Ok(state) => {
+                        pool.slot = pool.accounts(address).filter_map(|a| self.accounts.get(&a)).map(|a| a.slot).max().unwrap_or(0);
+                        pool.state = Some(state);
+                        pool.error = None;
+                    }
+                    Err(err) => {
+                        pool.state = None;
+                        pool.error = Some(err.to_string());
+                    }
+                }
+            }
+            _ => pool.error = Some("waiting for accounts".to_string()),
+        }
+        self.track(*address, pool);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use anchor_spl::token::spl_token;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use common::Specialization;
+    use vault::ProtocolFeesCollector;
+    use weighted_pool::{OracleAccumulators, Pool as WeightedPool};
+
+    fn anchor_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    fn token_data(mint: Pubkey, amount: u64) -> Vec<u8> {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        let account = spl_token::state::Account {
+            mint,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        spl_token::state::Account::pack(account, &mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn discovers_and_prices_pools_from_updates() {
+        let vault_state = Pubkey::new_unique();
+        let (pool, registration) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let tokens = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let custody = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let registered = PoolRegistration {
+            vault: vault_state,
+            pool_id: [3; 32],
+            pool_program: weighted_pool::ID,
+            pool,
+            specialization: Specialization::TwoToken,
+            tokens: tokens.clone(),
+            token_accounts: custody.clone(),
+            asset_managers: vec![Pubkey::default(); 2],
+            managed: vec![0, 0],
+            paused: false,
+            token_programs: vec![anchor_spl::token::ID; 2],
+            freeze_authorities: vec![Pubkey::default(); 2],
+            lp_mint: Pubkey::new_unique(),
+        };
+        let weighted = WeightedPool {
+            vault: vault_state,
+            lp_mint: Pubkey::new_unique(),
+            weights: vec![500_000_000_000_000_000; 2],
+            swap_fee: 3_000_000_000_000_000,
+            total_bpt: 1_000_000,
+            pool_id: [3; 32],
+            oracle: OracleAccumulators::default(),
+            entered: false,
+            version: weighted_pool::POOL_VERSION,
+            reserved: [0; WeightedPool::RESERVED],
+        };
+        let collector = ProtocolFeesCollector { vault: vault_state, swap_fee_percentage: 0, flash_loan_fee_percentage: 0 };
+
+        let mut view = PoolView::new([vault_state]);
+        let raw = |owner, data, slot| RawAccount { owner, data, slot };
+        // registrations of other Vaults are ignored
+        let other = PoolRegistration { vault: Pubkey::new_unique(), ..registered.clone() };
+        assert!(view.update(Pubkey::new_unique(), raw(vault::ID, anchor_data(&other), 1)).is_empty());
+
+        let mut missing = view.update(registration, raw(vault::ID, anchor_data(&registered), 1));
+        missing.sort();
+        let mut expected = vec![pool, custody[0], custody[1], client::pda::fees_collector(&vault_state)];
+        expected.sort();
+        assert_eq!(missing, expected);
+
+        view.update(pool, raw(weighted_pool::ID, anchor_data(&weighted), 2));
+        view.update(client::pda::fees_collector(&vault_state), raw(vault::ID, anchor_data(&collector), 2));
+        view.update(custody[0], raw(spl_token::ID, token_data(tokens[0], 1_000_000), 2));
+        assert!(view.pool(&pool).unwrap().state.is_none());
+        assert!(view.update(custody[1], raw(spl_token::ID, token_data(tokens[1], 1_000_000), 3)).is_empty());
+        let indexed = view.pool(&pool).unwrap();
+        assert_eq!(indexed.slot, 3);
+        let quote = indexed.state.as_ref().unwrap().swap_exact_in(&tokens[0], &tokens[1], 1_000).unwrap();
+        assert!(quote.amount_out > 0 && quote.amount_out < 1_000);
+
+        // a stale update does not roll the balance back
+        view.update(custody[1], raw(spl_token::ID, token_data(tokens[1], 1), 2));
+        assert_eq!(view.pool(&pool).unwrap().state.as_ref().unwrap().swap_exact_in(&tokens[0], &tokens[1], 1_000), Ok(quote));
+    }
+}
@@ -0,0 +1,234 @@
+// Symmetric‑Solana ─ Instruction fuzzing
+// ================================================================
+// Random pool operations, some with one account swapped for another
+// account of the pool, run against the bank with the invariants every
+// outcome must keep:
+//   • a failed transaction moves nothing;
+//   • every token is conserved across the user, custody and protocol
+//     fee accounts, and the pool's BPT count is the mint's supply;
+//   • custody only pays out on an exit or as a swap's token out, and
+//     only takes in on a join or as a swap's token in;
+//   • an unmodified operation moves exactly what it asked for.
+// A seeded generator (splitmix64) makes every failure replayable.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use client::PoolClient;
+
+use crate::Snapshot;
+
+/// splitmix64: small, seedable and good enough to pick operations.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n` (`n` > 0).
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// An amount against `available`: mostly within it, sometimes zero,
+    /// dust, just above it or far beyond.
+    pub fn amount(&mut self, available: u64) -> u64 {
+        match self.below(20) {
+            0 => 0,
+            1 => 1,
+            2 => available.saturating_add(1),
+            3 => u64::MAX,
+            _ => self.below(available.max(1)) + 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Op {
+    Join { amounts_in: Vec<u64> },
+    Swap { token_in: usize, amount_in: u64, minimum_amount_out: u64 },
+    Exit { bpt_in: u64 },
+}
+
+/// Account meta `index` of the instruction replaced by `with`.
+#[derive(Clone, Debug)]
+pub struct Mutation {
+    pub index: usize,
+    pub with: Pubkey,
+}
+
+#[derive(Clone, Debug)]
+pub struct Case {
+    pub op: Op,
+    pub mutation: Option<Mutation>,
+}
+
+/// The user's accounts in a pool.
+pub struct User {
+    pub authority: Pubkey,
+    /// One per pool token, in pool order
+    pub tokens: Vec<Pubkey>,
+    pub lp: Pubkey,
+}
+
+impl Case {
+    /// A random operation on a two‑token pool at `state`; one in four
+    /// swaps an account for one of `accounts`.
+    pub fn random(rng: &mut Rng, state: &Snapshot, accounts: &[Pubkey]) -> Case {
+        let op = match rng.below(3) {
+            0 => Op::Join { amounts_in: state.user.iter().map(|b| rng.amount(*b)).collect() },
+            1 => {
+                let token_in = rng.below(2) as usize;
+                let amount_in = rng.amount(state.user[token_in]);
+                let minimum_amount_out = if rng.below(4) == 0 { rng.amount(state.custody[1 - token_in]) } else { 0 };
+                Op::Swap { token_in, amount_in, minimum_amount_out }
+            }
+            _ => Op::Exit { bpt_in: rng.amount(state.user_bpt) },
+        };
+        let mutation = (rng.below(4) == 0).then(|| Mutation {
+            // resolved against the instruction's non‑signer metas
+            index: rng.below(64) as usize,
+            with: accounts[rng.below(accounts.len() as u64) as usize],
+        });
+        Case { op, mutation }
+    }
+
+    pub fn instruction(&self, pool: &PoolClient, user: &User) -> Instruction {
+        let mints = pool.join_tokens();
+        let mut ix = match &self.op {
+            Op::Join { amounts_in } => {
+                pool.join_exact_tokens_in_for_bpt_out(&user.authority, user.lp, &user.tokens, amounts_in.clone(), 0).unwrap()
+            }
+            Op::Swap { token_in, amount_in, minimum_amount_out } => {
+                let out = 1 - token_in;
+                pool.swap_exact_token_in_for_token_out(
+                    &user.authority,
+                    user.tokens[*token_in],
+                    user.tokens[out],
+                    &mints[*token_in],
+                    &mints[out],
+                    *amount_in,
+                    *minimum_amount_out,
+                )
+            }
+            Op::Exit { bpt_in } => pool.exit_exact_bpt_in_for_tokens_out(&user.authority, user.lp, &user.tokens, *bpt_in, vec![0; user.tokens.len()]).unwrap(),
+        };
+        if let Some(m) = &self.mutation {
+            let candidates: Vec<usize> = (0..ix.accounts.len()).filter(|i| !ix.accounts[*i].is_signer).collect();
+            ix.accounts[candidates[m.index % candidates.len()]].pubkey = m.with;
+        }
+        ix
+    }
+
+    /// Whether the outcome `before` → `after` of this case, which
+    /// succeeded or not, keeps every invariant.
+    pub fn check(&self, succeeded: bool, before: &Snapshot, after: &Snapshot) -> Result<(), String> {
+        if !succeeded {
+            return if before == after { Ok(()) } else { Err("a failed transaction moved balances".into()) };
+        }
+        if after.total_bpt != after.bpt_supply {
+            return Err(format!("pool counts {} BPT, the mint has {}", after.total_bpt, after.bpt_supply));
+        }
+        for i in 0..before.user.len() {
+            let held = |s: &Snapshot| s.user[i] as u128 + s.custody[i] as u128 + s.protocol[i] as u128;
+            if held(before) != held(after) {
+                return Err(format!("token {i} not conserved"));
+            }
+            let (paid_out, took_in) = (after.custody[i] < before.custody[i], after.custody[i] > before.custody[i]);
+            let (may_pay, may_take) = match &self.op {
+                Op::Join { .. } => (false, true),
+                Op::Swap { token_in, .. } => (*token_in != i, *token_in == i),
+                Op::Exit { .. } => (true, false),
+            };
+            if (paid_out && !may_pay) || (took_in && !may_take) {
+                return Err(format!("custody of token {i} moved {} → {}", before.custody[i], after.custody[i]));
+            }
+        }
+        if self.mutation.is_some() {
+            return Ok(());
+        }
+
+        let bpt_minted = after.bpt_supply as i128 - before.bpt_supply as i128;
+        let bpt_held = |s: &Snapshot| s.user_bpt as i128 + s.protocol_bpt as i128;
+        if bpt_held(after) - bpt_held(before) != bpt_minted {
+            return Err("BPT minted or burned outside the user and protocol accounts".into());
+        }
+        match &self.op {
+            Op::Join { amounts_in } => {
+                for (i, amount) in amounts_in.iter().enumerate() {
+                    if before.user[i] - after.user[i] != *amount || after.custody[i] - before.custody[i] != *amount {
+                        return Err(format!("join moved the wrong amount of token {i}"));
+                    }
+                }
+                if bpt_minted <= 0 {
+                    return Err("join minted no BPT".into());
+                }
+            }
+            Op::Swap { token_in, amount_in, minimum_amount_out } => {
+                let out = 1 - token_in;
+                if before.user[*token_in] - after.user[*token_in] != *amount_in {
+                    return Err("swap took the wrong amount in".into());
+                }
+                if after.user[out] - before.user[out] < *minimum_amount_out {
+                    return Err("swap paid less than its minimum out".into());
+                }
+                if bpt_minted != 0 {
+                    return Err("swap changed the BPT supply".into());
+                }
+            }
+            Op::Exit { bpt_in } => {
+                if before.user_bpt - after.user_bpt != *bpt_in || -bpt_minted != *bpt_in as i128 {
+                    return Err("exit burned the wrong BPT amount".into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(user: [u64; 2], custody: [u64; 2], user_bpt: u64) -> Snapshot {
+        Snapshot {
+            user: user.to_vec(),
+            custody: custody.to_vec(),
+            protocol: vec![0, 0],
+            user_bpt,
+            protocol_bpt: 0,
+            bpt_supply: user_bpt,
+            total_bpt: user_bpt,
+        }
+    }
+
+    #[test]
+    fn flags_broken_invariants() {
+        let before = snapshot([100, 100], [1_000, 1_000], 50);
+        let swap = Case { op: Op::Swap { token_in: 0, amount_in: 10, minimum_amount_out: 5 }, mutation: None };
+        assert_eq!(swap.check(false, &before, &before), Ok(()));
+        assert_eq!(swap.check(true, &before, &snapshot([90, 109], [1_010, 991], 50)), Ok(()));
+        // paid below the minimum out, and out of the wrong custody account
+        assert!(swap.check(true, &before, &snapshot([90, 104], [1_010, 996], 50)).is_err());
+        assert!(swap.check(true, &before, &snapshot([109, 90], [991, 1_010], 50)).is_err());
+        // created tokens
+        assert!(swap.check(true, &before, &snapshot([90, 200], [1_010, 991], 50)).is_err());
+        assert!(swap.check(false, &before, &snapshot([90, 109], [1_010, 991], 50)).is_err());
+
+        let exit = Case { op: Op::Exit { bpt_in: 10 }, mutation: None };
+        assert_eq!(exit.check(true, &before, &snapshot([110, 110], [990, 990], 40)), Ok(()));
+        assert!(exit.check(true, &before, &snapshot([110, 110], [990, 990], 45)).is_err());
+
+        let mut rng = Rng::new(7);
+        assert_eq!(Rng::new(7).next_u64(), rng.next_u64());
+        assert!((0..100).all(|_| rng.below(3) < 3));
+    }
+}
@@ -0,0 +1,406 @@
+// Symmetric‑Solana ─ Integration test harness
+// ================================================================
+// A solana-program-test bank running the Vault and weighted pool
+// programs natively, and the steps the end‑to‑end tests share:
+//   • `Env::start` boots the bank with a funded payer;
+//   • mints, associated token accounts and balances, under SPL Token
+//     or Token‑2022 (each mint's owner picks the program), mints with
+//     extensions – transfer fees, a native transfer‑hook program, … –
+//     included;
+//   • Vault set‑up – state, Authorizer with the payer's roles, fees
+//     collector – and weighted pool creation through the client SDK;
+//   • snapshots of every balance a pool operation moves;
+//   • random operation sequences and their invariants (fuzz).
+// Programs run as native processors: no SBF build is needed, and CPIs
+// between them go through program-test's syscall stubs.
+// ================================================================
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::instruction::{Instruction, InstructionError};
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorDeserialize};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::{transfer_fee, transfer_hook, BaseState, ExtensionType, StateWithExtensions};
+use client::{pda, PoolClient, VaultClient};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use vault::{Action, VaultState};
+use weighted_pool::Pool;
+
+pub mod fuzz;
+
+/// Anchor's entrypoints tie the accounts slice and its infos to one
+/// lifetime; program-test hands out shorter‑lived slices, so they are
+/// leaked for the (test‑long) duration of the bank.
+macro_rules! native_entry {
+    ($name:ident, $program:ident) => {
+        fn $name(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+            let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+            $program::entry(program_id, accounts, data)
+        }
+    };
+}
+
+native_entry!(vault_entry, vault);
+native_entry!(weighted_pool_entry, weighted_pool);
+
+/// A transfer‑hook program for tests: a compliance check that refuses
+/// any single transfer above [`HOOK_TRANSFER_CAP`].
+pub const TRANSFER_HOOK_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+pub const HOOK_TRANSFER_CAP: u64 = 5_000_000;
+
+/// Execute: 8‑byte discriminator, then the amount (u64 LE).
+fn transfer_hook_entry(_program_id: &Pubkey, _accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let amount = data.get(8..16).map(|a| u64::from_le_bytes(a.try_into().unwrap()));
+    match amount {
+        Some(amount) if amount <= HOOK_TRANSFER_CAP => Ok(()),
+        _ => Err(ProgramError::Custom(0)),
+    }
+}
+
+pub type TestResult<T = ()> = Result<T, BanksClientError>;
+
+/// The custom error a transaction failed with, if any: compare it with
+/// `u32::from` an `#[error_code]` variant.
+pub fn custom_error(result: TestResult) -> Option<u32> {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) => Some(code),
+        _ => None,
+    }
+}
+
+/// Balances a pool operation moves: the user's, the pool's custody and
+/// the protocol fee account of each token, and BPT.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub user: Vec<u64>,
+    pub custody: Vec<u64>,
+    pub protocol: Vec<u64>,
+    pub user_bpt: u64,
+    pub protocol_bpt: u64,
+    pub bpt_supply: u64,
+    /// The pool's own BPT count
+    pub total_bpt: u64,
+}
+
+/// A running bank and its payer, who owns the Vault.
+pub struct Env {
+    pub ctx: ProgramTestContext,
+}
+
+impl Env {
+    pub async fn start() -> Self {
+        let mut test = ProgramTest::default();
+        test.prefer_bpf(false);
+        test.add_program("vault", vault::ID, processor!(vault_entry));
+        test.add_program("weighted_pool", weighted_pool::ID, processor!(weighted_pool_entry));
+        test.add_program("transfer_hook", TRANSFER_HOOK_ID, processor!(transfer_hook_entry));
+        Env { ctx: test.start_with_context().await }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    /// Run `ixs` as one transaction paid by the payer and signed by it
+    /// and `signers`.
+    pub async fn process(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TestResult {
+        let tx = self.transaction(ixs, signers).await?;
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    /// Run `ixs` as `process` does and deserialize the return data of
+    /// the last instruction that set any. It runs on the bank directly,
+    /// where the last `process`ed transaction may still hold its account
+    /// locks for a moment: a lock conflict is retried.
+    pub async fn process_returning<T: AnchorDeserialize>(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TestResult<T> {
+        let tx = self.transaction(ixs, signers).await?;
+        let processed = loop {
+            let processed = self.ctx.banks_client.process_transaction_with_metadata(tx.clone()).await?;
+            if processed.result != Err(TransactionError::AccountInUse) {
+                break processed;
+            }
+        };
+        processed.result.map_err(BanksClientError::TransactionError)?;
+        let data = processed.metadata.and_then(|m| m.return_data).expect("return data").data;
+        Ok(T::try_from_slice(&data).expect("return type"))
+    }
+
+    /// Wait for a blockhash other than the latest: a transaction
+    /// identical to an earlier one is otherwise rejected as already
+    /// processed.
+    pub async fn next_blockhash(&mut self) {
+        let used = self.ctx.banks_client.get_latest_blockhash().await.unwrap();
+        self.ctx.banks_client.get_new_latest_blockhash(&used).await.unwrap();
+    }
+
+    async fn transaction(&mut self, ixs: &[Instruction], signers: &[&Keypair]) -> TestResult<Transaction> {
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await?;
+        let mut all: Vec<&Keypair> = vec![&self.ctx.payer];
+        all.extend_from_slice(signers);
+        Ok(Transaction::new_signed_with_payer(ixs, Some(&self.payer()), &all, blockhash))
+    }
+
+    /// Deserialize the Anchor account at `address`.
+    pub async fn fetch<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let account = self.ctx.banks_client.get_account(*address).await.unwrap().expect("account exists");
+        T::try_deserialize(&mut &account.data[..]).expect("account type")
+    }
+
+    /// Overwrite the Anchor account at `address` with `state`, as no
+    /// instruction would: for states a test cannot reach otherwise.
+    pub async fn store<T: AccountSerialize>(&mut self, address: &Pubkey, state: &T) {
+        let mut account = self.ctx.banks_client.get_account(*address).await.unwrap().expect("account exists");
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        account.data[..data.len()].copy_from_slice(&data);
+        self.ctx.set_account(address, &account.into());
+    }
+
+    /// Unpack the token account or mint at `address`, of either token
+    /// program.
+    async fn unpack<T: BaseState + Pack>(&mut self, address: &Pubkey) -> T {
+        let account = self.ctx.banks_client.get_account(*address).await.unwrap().expect("account exists");
+        StateWithExtensions::<T>::unpack(&account.data).expect("token program account").base
+    }
+
+    /* ---------------------------------------------------------------
+       SPL tokens
+    ---------------------------------------------------------------- */
+    /// A new mint of `token_program` at `mint` with `authority` as its
+    /// mint authority.
+    pub async fn create_mint_at(&mut self, token_program: &Pubkey, mint: &Keypair, authority: &Pubkey, decimals: u8) -> TestResult {
+        let space = spl_token::state::Mint::LEN;
+        let rent = self.ctx.banks_client.get_rent().await?;
+        let ixs = [
+            system_instruction::create_account(&self.payer(), &mint.pubkey(), rent.minimum_balance(space), space as u64, token_program),
+            spl_token_2022::instruction::initialize_mint2(token_program, &mint.pubkey(), authority, None, decimals).unwrap(),
+        ];
+        self.process(&ixs, &[mint]).await
+    }
+
+    /// A new Token‑2022 mint whose authority is the payer, with
+    /// `extensions` set up by `init` (given the mint) before the mint
+    /// itself is initialised.
+    pub async fn create_mint_with_extensions(
+        &mut self,
+        decimals: u8,
+        extensions: &[ExtensionType],
+        init: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+    ) -> TestResult<Pubkey> {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(extensions).unwrap();
+        let rent = self.ctx.banks_client.get_rent().await?;
+        let mut ixs =
+            vec![system_instruction::create_account(&payer, &mint.pubkey(), rent.minimum_balance(space), space as u64, &spl_token_2022::ID)];
+        ixs.extend(init(&mint.pubkey()));
+        ixs.push(spl_token_2022::instruction::initialize_mint2(&spl_token_2022::ID, &mint.pubkey(), &payer, None, decimals).unwrap());
+        self.process(&ixs, &[&mint]).await?;
+        Ok(mint.pubkey())
+    }
+
+    /// A new Token‑2022 mint whose authority is the payer, withholding
+    /// `basis_points` of every transfer (at most `maximum_fee`).
+    pub async fn create_transfer_fee_mint(&mut self, decimals: u8, basis_points: u16, maximum_fee: u64) -> Pubkey {
+        let init = |mint: &Pubkey| {
+            vec![transfer_fee::instruction::initialize_transfer_fee_config(
+                &spl_token_2022::ID,
+                mint,
+                None,
+                None,
+                basis_points,
+                maximum_fee,
+            )
+            .unwrap()]
+        };
+        self.create_mint_with_extensions(decimals, &[ExtensionType::TransferFeeConfig], init).await.unwrap()
+    }
+
+    /// A new Token‑2022 mint whose authority is the payer and whose
+    /// transfers call `hook_program`.
+    pub async fn create_transfer_hook_mint(&mut self, decimals: u8, hook_program: &Pubkey) -> Pubkey {
+        let init = |mint: &Pubkey| vec![transfer_hook::instruction::initialize(&spl_token_2022::ID, mint, None, Some(*hook_program)).unwrap()];
+        self.create_mint_with_extensions(decimals, &[ExtensionType::TransferHook], init).await.unwrap()
+    }
+
+    /// A new SPL Token mint whose mint and freeze authority is the payer.
+    pub async fn create_freezable_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let space = spl_token::state::Mint::LEN;
+        let rent = self.ctx.banks_client.get_rent().await.unwrap();
+        let ixs = [
+            system_instruction::create_account(&payer, &mint.pubkey(), rent.minimum_balance(space), space as u64, &spl_token::ID),
+            spl_token::instruction::initialize_mint2(&spl_token::ID, &mint.pubkey(), &payer, Some(&payer), decimals).unwrap(),
+        ];
+        self.process(&ixs, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// A new SPL Token mint whose authority is the payer.
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        self.create_mint_of(&spl_token::ID, decimals).await
+    }
+
+    /// A new mint of `token_program` whose authority is the payer.
+    pub async fn create_mint_of(&mut self, token_program: &Pubkey, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        self.create_mint_at(token_program, &mint, &payer, decimals).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// The token program owning `mint`.
+    pub async fn token_program_of(&mut self, mint: &Pubkey) -> Pubkey {
+        self.ctx.banks_client.get_account(*mint).await.unwrap().expect("mint exists").owner
+    }
+
+    /// The payer's associated token account of `mint`, created if needed.
+    pub async fn token_account(&mut self, mint: &Pubkey) -> Pubkey {
+        let payer = self.payer();
+        let token_program = self.token_program_of(mint).await;
+        let ix = create_associated_token_account_idempotent(&payer, &payer, mint, &token_program);
+        self.process(&[ix], &[]).await.unwrap();
+        get_associated_token_address_with_program_id(&payer, mint, &token_program)
+    }
+
+    /// Mint `amount` of a payer‑authority mint to the payer; returns the
+    /// payer's token account.
+    pub async fn mint_to(&mut self, mint: &Pubkey, amount: u64) -> Pubkey {
+        let account = self.token_account(mint).await;
+        let payer = self.payer();
+        let token_program = self.token_program_of(mint).await;
+        let ix = spl_token_2022::instruction::mint_to(&token_program, mint, &account, &payer, &[], amount).unwrap();
+        self.process(&[ix], &[]).await.unwrap();
+        account
+    }
+
+    pub async fn balance(&mut self, token_account: &Pubkey) -> u64 {
+        self.unpack::<spl_token_2022::state::Account>(token_account).await.amount
+    }
+
+    pub async fn supply(&mut self, mint: &Pubkey) -> u64 {
+        self.unpack::<spl_token_2022::state::Mint>(mint).await.supply
+    }
+
+    /* ---------------------------------------------------------------
+       Vault & pools
+    ---------------------------------------------------------------- */
+    /// The payer's Vault, Authorizer and fees collector, the payer
+    /// holding the `RegisterPool`, `SetProtocolFees` and
+    /// `ManageTokenPolicy` roles and `protocol_swap_fee` of swap fees
+    /// going to the protocol.
+    pub async fn init_vault(&mut self, protocol_swap_fee: u64) -> VaultClient {
+        let payer = self.payer();
+        let vault = VaultClient::for_creator(&payer);
+        let ixs = [
+            VaultClient::initialize(&payer, payer),
+            vault.initialize_authorizer(&payer),
+            vault.grant_role(&payer, Action::RegisterPool, payer),
+            vault.grant_role(&payer, Action::SetProtocolFees, payer),
+            vault.grant_role(&payer, Action::ManageTokenPolicy, payer),
+            vault.initialize_fees_collector(&payer),
+        ];
+        self.process(&ixs, &[]).await.unwrap();
+        self.process(&[vault.set_protocol_fee_percentages(&payer, protocol_swap_fee, 0)], &[]).await.unwrap();
+        vault
+    }
+
+    /// A canonical weighted pool of `tokens` (any order) with its LP
+    /// mint, protocol fee accounts and custody accounts: each token's
+    /// under its own token program, the LP mint under the first token's.
+    pub async fn create_weighted_pool(&mut self, vault: &VaultClient, tokens: Vec<(Pubkey, u128)>, swap_fee: u64) -> PoolClient {
+        self.try_create_weighted_pool(vault, tokens, swap_fee).await.unwrap()
+    }
+
+    /// [`Env::create_weighted_pool`], failing if any step does.
+    pub async fn try_create_weighted_pool(
+        &mut self,
+        vault: &VaultClient,
+        mut tokens: Vec<(Pubkey, u128)>,
+        swap_fee: u64,
+    ) -> TestResult<PoolClient> {
+        tokens.sort_by_key(|(mint, _)| *mint);
+        let (mints, weights): (Vec<Pubkey>, _) = tokens.into_iter().unzip();
+        let token_program = self.token_program_of(&mints[0]).await;
+        let state: VaultState = self.fetch(&vault.vault_state).await;
+        let payer = self.payer();
+        let lp_mint = Keypair::new();
+        let (mut pool, ix) =
+            PoolClient::create_weighted(vault.vault_state, &state, &payer, &payer, lp_mint.pubkey(), mints, weights, swap_fee).unwrap();
+        pool.token_program = token_program;
+        pool.transfer_hook_accounts = self.transfer_hook_programs(&pool.tokens).await;
+        self.create_mint_at(&token_program, &lp_mint, &pool.authority(), 6).await?;
+        self.process(&[ix], &[]).await?;
+        self.process(&[pool.create_locked_bpt_account(&payer)], &[]).await?;
+
+        let collector: vault::ProtocolFeesCollector = self.fetch(&vault.fees_collector()).await;
+        pool.protocol_swap_fee = collector.swap_fee_percentage > 0;
+        let tokens = pool.join_tokens();
+        let mut programs = Vec::with_capacity(tokens.len());
+        for mint in &tokens {
+            programs.push(self.token_program_of(mint).await);
+        }
+        if programs.iter().any(|p| *p != token_program) {
+            pool.token_programs = programs.clone();
+        }
+        // a token's fee account outlives a pool that failed to register
+        let mut fee_accounts = Vec::new();
+        for (mint, program) in tokens.iter().zip(&programs).chain([(&pool.lp_mint, &token_program)]) {
+            let address = pda::protocol_fee_account(&vault.vault_state, mint);
+            if self.ctx.banks_client.get_account(address).await.unwrap().is_none() {
+                fee_accounts.push(vault.create_fee_account(&payer, *mint, program));
+            }
+        }
+        self.process(&fee_accounts, &[]).await?;
+        // custody is created through each token's own program
+        for program in [spl_token::ID, spl_token_2022::ID] {
+            let group: Vec<Pubkey> = tokens.iter().zip(&programs).filter(|(_, p)| **p == program).map(|(t, _)| *t).collect();
+            if group.is_empty() {
+                continue;
+            }
+            let register = vault.register_tokens(&payer, &pool.pool_id, &group, &program, vec![Pubkey::default(); group.len()]).unwrap();
+            self.process(&[register], &[]).await?;
+        }
+        Ok(pool)
+    }
+
+    /// The hook programs of the hooked mints among `mints`; the test hook
+    /// needs no extra‑account‑meta list.
+    pub async fn transfer_hook_programs(&mut self, mints: &[Pubkey]) -> Vec<Pubkey> {
+        let mut programs = Vec::new();
+        for mint in mints {
+            let account = self.ctx.banks_client.get_account(*mint).await.unwrap().unwrap();
+            let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&account.data).unwrap();
+            if let Some(program) = transfer_hook::get_program_id(&state) {
+                if !programs.contains(&program) {
+                    programs.push(program);
+                }
+            }
+        }
+        programs
+    }
+
+    /// Every balance `pool` operations move for the user holding
+    /// `user_tokens` (in pool order) and `user_lp`.
+    pub async fn snapshot(&mut self, pool: &PoolClient, user_tokens: &[Pubkey], user_lp: &Pubkey) -> Snapshot {
+        let mut s = Snapshot { user: vec![], custody: vec![], protocol: vec![], user_bpt: 0, protocol_bpt: 0, bpt_supply: 0, total_bpt: 0 };
+        for (mint, user) in pool.join_tokens().iter().zip(user_tokens) {
+            s.user.push(self.balance(user).await);
+            s.custody.push(self.balance(&pool.custody(mint)).await);
+            s.protocol.push(self.balance(&pda::protocol_fee_account(&pool.vault_state, mint)).await);
+        }
+        s.user_bpt = self.balance(user_lp).await;
+        s.protocol_bpt = self.balance(&pda::protocol_fee_account(&pool.vault_state, &pool.lp_mint)).await;
+        s.bpt_supply = self.supply(&pool.lp_mint).await;
+        s.total_bpt = self.fetch::<Pool>(&pool.pool).await.total_bpt;
+        s
+    }
+}
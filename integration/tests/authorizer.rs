@@ -0,0 +1,42 @@
+// The Vault's settings answer to the Authorizer, not to the owner key:
+// the owner without a role is refused like anyone else, and whoever
+// holds the role may act, owner or not.
+use integration::{custom_error, Env, TRANSFER_HOOK_ID};
+use solana_sdk::signature::{Keypair, Signer};
+use vault::{Action, ErrorCode, VaultState};
+
+#[tokio::test]
+async fn owner_settings_need_their_role() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let payer = env.payer();
+    let unauthorized = Some(ErrorCode::Unauthorized.into());
+
+    // the owner proposes a successor only once it holds the role
+    let successor = Keypair::new();
+    let propose = vault.transfer_ownership(&payer, successor.pubkey());
+    assert_eq!(custom_error(env.process(std::slice::from_ref(&propose), &[]).await), unauthorized);
+    env.process(&[vault.grant_role(&payer, Action::TransferOwnership, payer)], &[]).await.unwrap();
+    env.next_blockhash().await;
+    env.process(&[propose], &[]).await.unwrap();
+    let state: VaultState = env.fetch(&vault.vault_state).await;
+    assert_eq!(state.pending_owner, successor.pubkey());
+
+    // a token policy manager need not be the owner
+    let manager = Keypair::new();
+    let policy = vault.set_freeze_authority_policy(&manager.pubkey(), true);
+    assert_eq!(custom_error(env.process(std::slice::from_ref(&policy), &[&manager]).await), unauthorized);
+    env.process(&[vault.grant_role(&payer, Action::ManageTokenPolicy, manager.pubkey())], &[]).await.unwrap();
+    env.next_blockhash().await;
+    env.process(&[policy], &[&manager]).await.unwrap();
+    let state: VaultState = env.fetch(&vault.vault_state).await;
+    assert!(state.reject_freezable_mints);
+
+    // and the owner loses it like anyone else
+    env.process(&[vault.revoke_role(&payer, Action::ManageTokenPolicy, payer)], &[]).await.unwrap();
+    let mint = env.create_mint(6).await;
+    let ix = vault.set_mint_extension_override(&payer, mint, true);
+    assert_eq!(custom_error(env.process(&[ix], &[]).await), unauthorized);
+    let ix = vault.set_transfer_hook_program(&payer, TRANSFER_HOOK_ID, true);
+    assert_eq!(custom_error(env.process(&[ix], &[]).await), unauthorized);
+}
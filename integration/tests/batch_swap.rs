@@ -0,0 +1,220 @@
+// Batch swaps through the Vault: a two‑hop route settles only the net
+// of each asset with the user, the net is held to the caller's limits
+// and deadline, and a relayer trades for the user only on an approval
+// or on a one‑time permit the user signed for it.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{system_program, AnchorSerialize};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use client::{AssetAccounts, PoolClient, VaultClient};
+use integration::{custom_error, Env};
+use solana_ed25519_program::new_ed25519_instruction_with_signature;
+use solana_sdk::clock::Clock;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::sysvar;
+use vault::{BatchSwapStep, ErrorCode, RelayerPermit};
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+const SWAP_FEE: u64 = 3_000_000_000_000_000;
+const AMOUNT_IN: u64 = 100_000;
+
+/// Pools of (a, b) and (b, c), each joined with 5 tokens a side, and the
+/// payer's and the Vault authority's accounts of a, b and c.
+struct Route {
+    vault: VaultClient,
+    pools: [PoolClient; 2],
+    assets: [Pubkey; 3],
+    accounts: Vec<AssetAccounts>,
+}
+
+impl Route {
+    async fn new(env: &mut Env) -> Self {
+        let vault = env.init_vault(0).await;
+        let assets = [env.create_mint(6).await, env.create_mint(6).await, env.create_mint(6).await];
+        let payer = env.payer();
+        let mut accounts = Vec::new();
+        for mint in &assets {
+            let user = env.mint_to(mint, 20_000_000).await;
+            let ix = create_associated_token_account_idempotent(&payer, &vault.vault_authority(), mint, &spl_token::ID);
+            env.process(&[ix], &[]).await.unwrap();
+            accounts.push(AssetAccounts { user, vault: get_associated_token_address(&vault.vault_authority(), mint) });
+        }
+        let mut pools = Vec::new();
+        for pair in [[assets[0], assets[1]], [assets[1], assets[2]]] {
+            let pool = env.create_weighted_pool(&vault, vec![(pair[0], ONE / 2), (pair[1], ONE / 2)], SWAP_FEE).await;
+            let user_tokens: Vec<Pubkey> = pool
+                .join_tokens()
+                .iter()
+                .map(|mint| accounts[assets.iter().position(|a| a == mint).unwrap()].user)
+                .collect();
+            let user_lp = env.token_account(&pool.lp_mint).await;
+            let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+            env.process(&[join], &[]).await.unwrap();
+            pools.push(pool);
+        }
+        let pools = pools.try_into().unwrap();
+        Route { vault, pools, assets, accounts }
+    }
+
+    /// a → b → c, the second hop swapping all the first one received.
+    fn steps(&self) -> Vec<BatchSwapStep> {
+        vec![
+            BatchSwapStep { pool_id: self.pools[0].pool_id, asset_in_index: 0, asset_out_index: 1, amount: AMOUNT_IN },
+            BatchSwapStep { pool_id: self.pools[1].pool_id, asset_in_index: 1, asset_out_index: 2, amount: 0 },
+        ]
+    }
+
+    fn batch_swap(&self, user: &Pubkey, sender: &Pubkey, limits: Vec<i64>, deadline: i64) -> Instruction {
+        let pools = [&self.pools[0], &self.pools[1]];
+        self.vault.batch_swap(user, sender, &self.assets, &self.accounts, self.steps(), &pools, limits, deadline).unwrap()
+    }
+
+    async fn balances(&self, env: &mut Env) -> Vec<u64> {
+        let mut balances = Vec::new();
+        for a in &self.accounts {
+            balances.push(env.balance(&a.user).await);
+            balances.push(env.balance(&a.vault).await);
+        }
+        balances
+    }
+}
+
+/// Any deadline the bank has not reached.
+const OPEN: i64 = i64::MAX;
+
+#[tokio::test]
+async fn multi_hop_settles_the_net_of_each_asset() {
+    let mut env = Env::start().await;
+    let route = Route::new(&mut env).await;
+    let payer = env.payer();
+    let before = route.balances(&mut env).await;
+
+    let ix = route.batch_swap(&payer, &payer, vec![AMOUNT_IN as i64, 0, -1], OPEN);
+    let deltas: Vec<i64> = env.process_returning(&[ix], &[]).await.unwrap();
+    // a is paid in, b passes straight from one pool to the next, c comes out
+    assert_eq!(deltas[0], AMOUNT_IN as i64);
+    assert_eq!(deltas[1], 0);
+    assert!(deltas[2] < 0);
+
+    let after = route.balances(&mut env).await;
+    assert_eq!(after[0], before[0] - AMOUNT_IN);
+    assert_eq!(after[2], before[2]);
+    assert_eq!(after[4], before[4] + deltas[2].unsigned_abs());
+    // the Vault's side of every asset ends where it began
+    for a in 0..3 {
+        assert_eq!(after[a * 2 + 1], before[a * 2 + 1]);
+    }
+}
+
+#[tokio::test]
+async fn a_missed_limit_or_deadline_moves_nothing() {
+    let mut env = Env::start().await;
+    let route = Route::new(&mut env).await;
+    let payer = env.payer();
+    let before = route.balances(&mut env).await;
+
+    // more of c than the route yields, less of a than it costs
+    for limits in [vec![AMOUNT_IN as i64, 0, -(AMOUNT_IN as i64)], vec![AMOUNT_IN as i64 - 1, 0, -1]] {
+        let ix = route.batch_swap(&payer, &payer, limits, OPEN);
+        assert_eq!(custom_error(env.process(&[ix], &[]).await), Some(ErrorCode::SwapLimit.into()));
+    }
+    let now = env.ctx.banks_client.get_sysvar::<Clock>().await.unwrap().unix_timestamp;
+    let ix = route.batch_swap(&payer, &payer, vec![AMOUNT_IN as i64, 0, -1], now - 1);
+    assert_eq!(custom_error(env.process(&[ix], &[]).await), Some(ErrorCode::SwapDeadline.into()));
+    assert_eq!(route.balances(&mut env).await, before);
+}
+
+/// A relayer with lamports for the permit nonce account, and the payer's
+/// asset accounts delegated to the Vault authority for it to pull from.
+async fn relayer(env: &mut Env, route: &Route) -> Keypair {
+    let relayer = Keypair::new();
+    let payer = env.payer();
+    let mut ixs = vec![system_instruction::transfer(&payer, &relayer.pubkey(), 1_000_000_000)];
+    for a in &route.accounts {
+        let approve = spl_token::instruction::approve(&spl_token::ID, &a.user, &route.vault.vault_authority(), &payer, &[], u64::MAX);
+        ixs.push(approve.unwrap());
+    }
+    env.process(&ixs, &[]).await.unwrap();
+    relayer
+}
+
+#[tokio::test]
+async fn a_relayer_needs_the_users_approval() {
+    let mut env = Env::start().await;
+    let route = Route::new(&mut env).await;
+    let relayer = relayer(&mut env, &route).await;
+    let payer = env.payer();
+    let before = route.balances(&mut env).await;
+    let limits = vec![AMOUNT_IN as i64, 0, -1];
+
+    // never approved: the approval account does not exist
+    let ix = route.batch_swap(&payer, &relayer.pubkey(), limits.clone(), OPEN);
+    assert!(env.process(&[ix], &[&relayer]).await.is_err());
+    // approved, then revoked
+    for approved in [true, false] {
+        env.process(&[route.vault.set_relayer_approval(&payer, relayer.pubkey(), approved)], &[]).await.unwrap();
+    }
+    let ix = route.batch_swap(&payer, &relayer.pubkey(), limits.clone(), OPEN);
+    assert_eq!(custom_error(env.process(&[ix], &[&relayer]).await), Some(ErrorCode::RelayerNotApproved.into()));
+    assert_eq!(route.balances(&mut env).await, before);
+
+    env.process(&[route.vault.set_relayer_approval(&payer, relayer.pubkey(), true)], &[]).await.unwrap();
+    let ix = route.batch_swap(&payer, &relayer.pubkey(), limits, OPEN);
+    env.process(&[ix], &[&relayer]).await.unwrap();
+    let after = route.balances(&mut env).await;
+    assert_eq!(after[0], before[0] - AMOUNT_IN);
+    assert!(after[4] > before[4]);
+}
+
+/// `route`'s batch swap sent by `sender` on the permit `permit_for` holds:
+/// the payer's signature over the permit for `permit_for` at `nonce`,
+/// checked by the Ed25519 program right before the swap.
+fn swap_on_permit(env: &Env, route: &Route, sender: &Pubkey, permit_for: &Pubkey, nonce: u64) -> [Instruction; 2] {
+    let (limits, payer) = (vec![AMOUNT_IN as i64, 0, -1], env.payer());
+    let permit = RelayerPermit {
+        vault: route.vault.vault_state,
+        user: payer,
+        relayer: *permit_for,
+        nonce,
+        action: RelayerPermit::batch_swap_action(&route.steps(), &limits, OPEN).unwrap(),
+    };
+    let message = permit.try_to_vec().unwrap();
+    let signature = env.ctx.payer.sign_message(&message);
+    let check = new_ed25519_instruction_with_signature(&message, signature.as_ref().try_into().unwrap(), &payer.to_bytes());
+
+    // the permit's accounts stand in for the approval
+    let mut swap = route.batch_swap(&payer, sender, limits, OPEN);
+    swap.accounts[4] = AccountMeta::new_readonly(vault::ID, false);
+    swap.accounts[5] = AccountMeta::new(client::pda::permit_nonce(&route.vault.vault_state, &payer), false);
+    swap.accounts[6] = AccountMeta::new_readonly(sysvar::instructions::ID, false);
+    swap.accounts[7] = AccountMeta::new_readonly(system_program::ID, false);
+    [check, swap]
+}
+
+#[tokio::test]
+async fn a_permit_serves_its_relayer_once() {
+    let mut env = Env::start().await;
+    let route = Route::new(&mut env).await;
+    let relayer = relayer(&mut env, &route).await;
+    let other = Keypair::new();
+    env.process(&[system_instruction::transfer(&env.payer(), &other.pubkey(), 1_000_000_000)], &[]).await.unwrap();
+    let before = route.balances(&mut env).await;
+
+    // another relayer cannot use it
+    let ixs = swap_on_permit(&env, &route, &other.pubkey(), &relayer.pubkey(), 0);
+    assert_eq!(custom_error(env.process(&ixs, &[&other]).await), Some(ErrorCode::InvalidPermit.into()));
+    assert_eq!(route.balances(&mut env).await, before);
+
+    let ixs = swap_on_permit(&env, &route, &relayer.pubkey(), &relayer.pubkey(), 0);
+    env.process(&ixs, &[&relayer]).await.unwrap();
+    let swapped = route.balances(&mut env).await;
+    assert_eq!(swapped[0], before[0] - AMOUNT_IN);
+
+    // replayed: the nonce it signed is spent
+    env.next_blockhash().await;
+    assert_eq!(custom_error(env.process(&ixs, &[&relayer]).await), Some(ErrorCode::InvalidPermit.into()));
+    assert_eq!(route.balances(&mut env).await, swapped);
+}
@@ -0,0 +1,107 @@
+// Mints with a freeze authority: a Vault that rejects them registers one
+// only once a `ManageTokenPolicy` holder acknowledges it, the
+// registration records the authority, and when the issuer freezes the
+// token's custody a recovery exit still pays out the other token. A closed custody account fails
+// the same operations by name and is skipped the same way.
+use anchor_spl::token::spl_token;
+use anchor_lang::prelude::Pubkey;
+use integration::{custom_error, Env};
+use solana_sdk::account::Account;
+use weighted_pool::ErrorCode;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+const SWAP_FEE: u64 = 3_000_000_000_000_000;
+
+#[tokio::test]
+async fn frozen_custody_leaves_a_recovery_exit() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let payer = env.payer();
+    env.process(&[vault.set_freeze_authority_policy(&payer, true)], &[]).await.unwrap();
+
+    // pools are canonical per token set: the rejected one takes another
+    let freezable = env.create_freezable_mint(6).await;
+    let rejected = env.create_mint(6).await;
+    assert!(env.try_create_weighted_pool(&vault, vec![(freezable, ONE / 2), (rejected, ONE / 2)], SWAP_FEE).await.is_err());
+
+    env.process(&[vault.set_mint_extension_override(&payer, freezable, true)], &[]).await.unwrap();
+    let plain = env.create_mint(6).await;
+    let pool = env.create_weighted_pool(&vault, vec![(freezable, ONE / 2), (plain, ONE / 2)], SWAP_FEE).await;
+    let mints = pool.join_tokens();
+    let f = mints.iter().position(|m| *m == freezable).unwrap();
+    let p = 1 - f;
+    let registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    assert_eq!(registration.freeze_authorities[f], payer);
+    assert_eq!(registration.freeze_authorities[p], Pubkey::default());
+
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![2_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // While every token can move there is nothing to recover
+    let exit = pool.recovery_exit(&payer, user_lp, &user_tokens, 1_000).unwrap();
+    assert_eq!(custom_error(env.process(&[exit], &[]).await), Some(ErrorCode::RecoveryNotNeeded.into()));
+
+    // The issuer freezes the pool's custody: a plain exit cannot pay it out
+    let custody = pool.custody(&freezable);
+    let freeze = spl_token::instruction::freeze_account(&spl_token::ID, &custody, &freezable, &payer, &[]).unwrap();
+    env.process(&[freeze], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, joined.user_bpt / 2, vec![0; user_tokens.len()]).unwrap();
+    assert_eq!(custom_error(env.process(&[exit], &[]).await), Some(ErrorCode::TokenAccountFrozen.into()));
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[p], user_tokens[f], &mints[p], &mints[f], 100_000, 0);
+    assert_eq!(custom_error(env.process(&[swap], &[]).await), Some(ErrorCode::TokenAccountFrozen.into()));
+
+    // A recovery exit pays the other token's proportional share and
+    // leaves the frozen one in custody
+    let bpt_in = joined.user_bpt / 2;
+    let exit = pool.recovery_exit(&payer, user_lp, &user_tokens, bpt_in).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let share = (joined.custody[p] as u128 * bpt_in as u128 / joined.total_bpt as u128) as u64;
+    assert_eq!(exited.user[p], joined.user[p] + share);
+    assert_eq!(exited.custody[p], joined.custody[p] - share);
+    assert_eq!((exited.user[f], exited.custody[f]), (joined.user[f], joined.custody[f]));
+    assert_eq!(exited.total_bpt, joined.total_bpt - bpt_in);
+    assert_eq!(exited.bpt_supply, joined.bpt_supply - bpt_in);
+}
+
+#[tokio::test]
+async fn closed_custody_is_named_and_skipped_by_recovery() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let payer = env.payer();
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], SWAP_FEE).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![2_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    // The first token's custody is gone: closed accounts belong to the
+    // system program and hold no data
+    env.ctx.set_account(&pool.custody(&mints[0]), &Account::default().into());
+    let bpt_in = joined.user_bpt / 2;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; user_tokens.len()]).unwrap();
+    assert_eq!(custom_error(env.process(&[exit], &[]).await), Some(ErrorCode::InvalidVaultOwner.into()));
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![1_000_000; 2], 0).unwrap();
+    assert_eq!(custom_error(env.process(&[join], &[]).await), Some(ErrorCode::InvalidVaultOwner.into()));
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[1], user_tokens[0], &mints[1], &mints[0], 100_000, 0);
+    assert_eq!(custom_error(env.process(&[swap], &[]).await), Some(ErrorCode::InvalidVaultOwner.into()));
+
+    let exit = pool.recovery_exit(&payer, user_lp, &user_tokens, bpt_in).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let share = (joined.custody[1] as u128 * bpt_in as u128 / joined.total_bpt as u128) as u64;
+    assert_eq!(env.balance(&user_tokens[1]).await, joined.user[1] + share);
+    assert_eq!(env.balance(&user_tokens[0]).await, joined.user[0]);
+    assert_eq!(env.supply(&pool.lp_mint).await, joined.bpt_supply - bpt_in);
+}
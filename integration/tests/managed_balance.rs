@@ -0,0 +1,83 @@
+// Asset management round trip: a token's asset manager withdraws cash
+// from custody, reports what it earned and deposits it back, the pool
+// pricing cash plus managed throughout; nobody else may move it.
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use client::PoolClient;
+use integration::{custom_error, Env};
+use solana_sdk::signature::{Keypair, Signer};
+use vault::ErrorCode;
+use weighted_pool::PoolHealth;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+/// What the pool prices its tokens at: cash plus managed. The probe is
+/// the same transaction each time, so each gets a fresh blockhash.
+async fn health(env: &mut Env, pool: &PoolClient) -> PoolHealth {
+    env.next_blockhash().await;
+    env.process_returning(&[pool.verify_pool().unwrap()], &[]).await.unwrap()
+}
+
+#[tokio::test]
+async fn withdraw_report_and_deposit_managed_cash() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![4_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // the first token gets an asset manager, as registering it with one would
+    let manager = Keypair::new();
+    let mut registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    registration.asset_managers[0] = manager.pubkey();
+    env.store(&pool.registration(), &registration).await;
+    let mint = mints[0];
+    let ix = create_associated_token_account_idempotent(&payer, &manager.pubkey(), &mint, &spl_token::ID);
+    env.process(&[ix], &[]).await.unwrap();
+    let counterparty = get_associated_token_address(&manager.pubkey(), &mint);
+
+    let before = health(&mut env, &pool).await;
+    let custody = env.balance(&pool.custody(&mint)).await;
+
+    // only the manager withdraws
+    let stranger = Keypair::new();
+    let withdraw = vault.withdraw_managed(&stranger.pubkey(), &pool, &mint, counterparty, 1_000_000);
+    assert_eq!(custom_error(env.process(&[withdraw], &[&stranger]).await), Some(ErrorCode::Unauthorized.into()));
+
+    // cash moves out, the pool's balance stays
+    let withdraw = vault.withdraw_managed(&manager.pubkey(), &pool, &mint, counterparty, 1_000_000);
+    env.process(&[withdraw], &[&manager]).await.unwrap();
+    assert_eq!(env.balance(&pool.custody(&mint)).await, custody - 1_000_000);
+    assert_eq!(env.balance(&counterparty).await, 1_000_000);
+    let registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    assert_eq!(registration.managed[0], 1_000_000);
+    let managed = health(&mut env, &pool).await;
+    assert_eq!(managed.balances, before.balances);
+    assert_eq!(managed.invariant, before.invariant);
+
+    // a reported gain raises the pool's balance without moving tokens
+    let update = vault.update_managed_balance(&manager.pubkey(), &pool, &mint, counterparty, 1_050_000);
+    env.process(&[update], &[&manager]).await.unwrap();
+    let earned = health(&mut env, &pool).await;
+    assert_eq!(earned.balances[0], before.balances[0] + 50_000);
+
+    // depositing more than is managed fails; the cash returns in full
+    let deposit = vault.deposit_managed(&manager.pubkey(), &pool, &mint, counterparty, 1_050_001);
+    assert_eq!(custom_error(env.process(&[deposit], &[&manager]).await), Some(ErrorCode::ManagedBalanceTooLow.into()));
+    let deposit = vault.deposit_managed(&manager.pubkey(), &pool, &mint, counterparty, 1_000_000);
+    env.process(&[deposit], &[&manager]).await.unwrap();
+    assert_eq!(env.balance(&pool.custody(&mint)).await, custody);
+    assert_eq!(env.balance(&counterparty).await, 0);
+    let registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    assert_eq!(registration.managed[0], 50_000);
+    assert_eq!(health(&mut env, &pool).await.balances, earned.balances);
+}
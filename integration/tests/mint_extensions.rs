@@ -0,0 +1,41 @@
+// Token‑2022 mints whose extensions can strand or confiscate pool
+// liquidity: the Vault refuses to register them unless its owner
+// overrides a specific mint, and never registers non‑transferable ones.
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_2022::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_2022::spl_token_2022::instruction::{initialize_non_transferable_mint, initialize_permanent_delegate};
+use integration::Env;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+const SWAP_FEE: u64 = 3_000_000_000_000_000;
+
+#[tokio::test]
+async fn permanent_delegates_need_an_owner_override() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let payer = env.payer();
+    let init = |mint: &_| vec![initialize_permanent_delegate(&spl_token_2022::ID, mint, &payer).unwrap()];
+    let delegated = env.create_mint_with_extensions(6, &[ExtensionType::PermanentDelegate], init).await.unwrap();
+
+    let rejected = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let tokens = vec![(delegated, ONE / 2), (rejected, ONE / 2)];
+    assert!(env.try_create_weighted_pool(&vault, tokens, SWAP_FEE).await.is_err());
+
+    env.process(&[vault.set_mint_extension_override(&payer, delegated, true)], &[]).await.unwrap();
+    let plain = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let pool = env.create_weighted_pool(&vault, vec![(delegated, ONE / 2), (plain, ONE / 2)], SWAP_FEE).await;
+    assert!(pool.tokens.contains(&delegated));
+}
+
+#[tokio::test]
+async fn non_transferable_mints_are_never_registered() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let payer = env.payer();
+    let init = |mint: &_| vec![initialize_non_transferable_mint(&spl_token_2022::ID, mint).unwrap()];
+    let soulbound = env.create_mint_with_extensions(6, &[ExtensionType::NonTransferable], init).await.unwrap();
+    env.process(&[vault.set_mint_extension_override(&payer, soulbound, true)], &[]).await.unwrap();
+
+    let plain = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    assert!(env.try_create_weighted_pool(&vault, vec![(soulbound, ONE / 2), (plain, ONE / 2)], SWAP_FEE).await.is_err());
+}
@@ -0,0 +1,89 @@
+// A weighted pool holding one SPL Token and one Token‑2022 mint: the
+// Vault records each custody account's token program at registration,
+// and joins, swaps and exits move every token through its own program,
+// the one the context does not name passed along with the mints.
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use integration::{custom_error, Env};
+use solana_sdk::instruction::AccountMeta;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn pools_mix_spl_token_and_token_2022_mints() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(ONE as u64 / 2).await;
+    let classic = env.create_mint_of(&spl_token::ID, 6).await;
+    let extended = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let pool = env.create_weighted_pool(&vault, vec![(classic, ONE / 2), (extended, ONE / 2)], 3_000_000_000_000_000).await;
+
+    let mints = pool.join_tokens();
+    let programs = [env.token_program_of(&mints[0]).await, env.token_program_of(&mints[1]).await];
+    assert_eq!(pool.token_programs, programs);
+    assert_eq!(pool.token_program, programs[0]);
+    let registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    assert_eq!(registration.token_programs, programs);
+
+    let payer = env.payer();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+
+    // Join: both tokens arrive in custody, each through its own program
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![2_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(joined.custody, vec![2_000_000; 2]);
+    assert!(joined.user_bpt > 0);
+
+    // Swap both ways, across the two programs
+    for (i, o) in [(0, 1), (1, 0)] {
+        let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[i], user_tokens[o], &mints[i], &mints[o], 100_000, 1);
+        env.process(&[swap], &[]).await.unwrap();
+    }
+    let swapped = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert!(swapped.custody.iter().zip(&joined.custody).all(|(after, before)| after != before));
+    assert!(swapped.protocol.iter().all(|fee| *fee > 0));
+
+    // Without the other program a transfer of its token cannot be made
+    let mut join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![1_000; 2], 0).unwrap();
+    join.accounts.pop();
+    assert!(env.process(&[join], &[]).await.is_err());
+
+    // Exit pays both tokens out of custody
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, swapped.user_bpt / 2, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    for i in 0..2 {
+        assert!(exited.user[i] > swapped.user[i] && exited.custody[i] < swapped.custody[i]);
+    }
+}
+
+// Deregistering a token closes its custody through the token's own
+// program, passed after the pairs when the context names the other one.
+#[tokio::test]
+async fn deregister_tokens_closes_custody_through_its_program() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let classic = env.create_mint_of(&spl_token::ID, 6).await;
+    let extended = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let pool = env.create_weighted_pool(&vault, vec![(classic, ONE / 2), (extended, ONE / 2)], 3_000_000_000_000_000).await;
+    let other = *pool.token_programs.iter().find(|p| **p != pool.token_program).unwrap();
+    let mint = pool.join_tokens()[pool.token_programs.iter().position(|p| *p == other).unwrap()];
+    let payer = env.payer();
+
+    let mut deregister = vault.deregister_tokens(&payer, &pool.pool_id, &[mint], &pool.token_program);
+    assert_eq!(
+        custom_error(env.process(std::slice::from_ref(&deregister), &[]).await),
+        Some(vault::ErrorCode::MissingTokenProgram.into())
+    );
+
+    deregister.accounts.push(AccountMeta::new_readonly(other, false));
+    env.process(&[deregister], &[]).await.unwrap();
+    let registration: vault::PoolRegistration = env.fetch(&pool.registration()).await;
+    assert!(!registration.tokens.contains(&mint));
+    assert_eq!(registration.token_programs, vec![pool.token_program]);
+    assert!(env.ctx.banks_client.get_account(pool.custody(&mint)).await.unwrap().is_none());
+}
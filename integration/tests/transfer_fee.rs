@@ -0,0 +1,93 @@
+// A Token‑2022 weighted pool with a transfer‑fee token: joins and swaps
+// price what custody received, the fee withheld on the way in; exits
+// and swaps out pay from custody, the fee withheld from the user.
+use anchor_spl::token_2022::spl_token_2022;
+use integration::{custom_error, Env};
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+/// 1% of every transfer of the fee token, capped well above the amounts here
+const FEE_BPS: u64 = 100;
+
+/// The fee withheld from a transfer of `amount`, rounded up as Token‑2022 does.
+fn fee(amount: u64) -> u64 {
+    (amount * FEE_BPS).div_ceil(10_000)
+}
+
+#[tokio::test]
+async fn prices_what_custody_receives() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(ONE as u64 / 2).await;
+    let taxed = env.create_transfer_fee_mint(6, FEE_BPS as u16, u64::MAX).await;
+    let plain = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let pool = env.create_weighted_pool(&vault, vec![(taxed, ONE / 2), (plain, ONE / 2)], 3_000_000_000_000_000).await;
+
+    let mints = pool.join_tokens();
+    let t = mints.iter().position(|m| *m == taxed).unwrap();
+    let p = 1 - t;
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    // minting is not a transfer: the user starts with the full amount
+    let start = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(start.user, vec![10_000_000; 2]);
+
+    // Join: custody holds the amounts in less the fee on the taxed token
+    let amounts_in = vec![1_000_000; 2];
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, amounts_in.clone(), 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(joined.user, vec![9_000_000; 2]);
+    assert_eq!(joined.custody[t], 1_000_000 - fee(1_000_000));
+    assert_eq!(joined.custody[p], 1_000_000);
+    assert!(joined.user_bpt > 0);
+
+    // Swap the taxed token in: the pool prices the 99% that arrived and
+    // pays the protocol fee out of custody
+    let amount_in = 100_000;
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[t], user_tokens[p], &mints[t], &mints[p], amount_in, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    let swapped = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(swapped.user[t], joined.user[t] - amount_in);
+    let received = amount_in - fee(amount_in);
+    let protocol_sent = joined.custody[t] + received - swapped.custody[t];
+    assert!(protocol_sent > 0);
+    assert_eq!(swapped.protocol[t] - joined.protocol[t], protocol_sent - fee(protocol_sent));
+    let amount_out = swapped.user[p] - joined.user[p];
+    assert!(amount_out > 0);
+    assert_eq!(swapped.custody[p], joined.custody[p] - amount_out);
+
+    // Swap the taxed token out: custody pays the quoted amount, the user
+    // receives it less the fee
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[p], user_tokens[t], &mints[p], &mints[t], amount_in, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    let back = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let sent = swapped.custody[t] - back.custody[t];
+    assert!(sent > 0);
+    assert_eq!(back.user[t] - swapped.user[t], sent - fee(sent));
+
+    // Exit: custody pays out in full, the taxed token arriving less its fee
+    let bpt_in = back.user_bpt / 2;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(exited.protocol, back.protocol);
+    let arrived = |i: usize| exited.user[i] - back.user[i];
+    assert_eq!(back.custody[p] - exited.custody[p], arrived(p));
+    let paid = back.custody[t] - exited.custody[t];
+    assert!(arrived(t) < paid && paid <= arrived(t) + fee(paid) + 1);
+
+    // The dust floor applies to what arrived: the minimum sent of the
+    // taxed token lands short of it, in a swap or a join
+    let amount_in = weighted_pool::MIN_TRADE_AMOUNT;
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[t], user_tokens[p], &mints[t], &mints[p], amount_in, 0);
+    let dust = Some(weighted_pool::ErrorCode::DustAmount.into());
+    assert_eq!(custom_error(env.process(&[swap], &[]).await), dust);
+    let mut amounts_in = vec![0; 2];
+    amounts_in[t] = amount_in;
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, amounts_in, 0).unwrap();
+    assert_eq!(custom_error(env.process(&[join], &[]).await), dust);
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, exited);
+}
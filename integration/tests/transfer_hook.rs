@@ -0,0 +1,61 @@
+// A Token‑2022 weighted pool with a transfer‑hook token: the Vault
+// registers it only once its owner allowlists the hook program, and
+// every transfer of it – joins, swaps, exits – runs the hook, which
+// here refuses transfers above a cap.
+use anchor_spl::token_2022::spl_token_2022;
+use integration::{Env, HOOK_TRANSFER_CAP, TRANSFER_HOOK_ID};
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn hooked_mints_need_an_allowlisted_hook() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(ONE as u64 / 2).await;
+    let hooked = env.create_transfer_hook_mint(6, &TRANSFER_HOOK_ID).await;
+    // pools are canonical per token set: the rejected one takes another
+    let rejected = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let tokens = vec![(hooked, ONE / 2), (rejected, ONE / 2)];
+    assert!(env.try_create_weighted_pool(&vault, tokens, 3_000_000_000_000_000).await.is_err());
+
+    let plain = env.create_mint_of(&spl_token_2022::ID, 6).await;
+    let tokens = vec![(hooked, ONE / 2), (plain, ONE / 2)];
+
+    let payer = env.payer();
+    env.process(&[vault.set_transfer_hook_program(&payer, TRANSFER_HOOK_ID, true)], &[]).await.unwrap();
+    let pool = env.create_weighted_pool(&vault, tokens, 3_000_000_000_000_000).await;
+    assert_eq!(pool.transfer_hook_accounts, vec![TRANSFER_HOOK_ID]);
+
+    let mints = pool.join_tokens();
+    let h = mints.iter().position(|m| *m == hooked).unwrap();
+    let p = 1 - h;
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+
+    // Join and swap both ways: each hooked transfer passes the hook
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![2_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(joined.custody, vec![2_000_000; 2]);
+
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[h], user_tokens[p], &mints[h], &mints[p], 100_000, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[p], user_tokens[h], &mints[p], &mints[h], 100_000, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    let swapped = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert!(swapped.user[h] > joined.user[h] - 100_000);
+
+    // The hook refuses a transfer above its cap, failing the whole join
+    let over = vec![HOOK_TRANSFER_CAP + 1; 2];
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, over, 0).unwrap();
+    assert!(env.process(&[join], &[]).await.is_err());
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await.custody, swapped.custody);
+
+    // Exit pays the hooked token out of custody through the hook
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, swapped.user_bpt / 2, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert!(exited.user[h] > swapped.user[h] && exited.custody[h] < swapped.custody[h]);
+}
@@ -0,0 +1,737 @@
+// End to end: a two‑token weighted pool created through the Vault,
+// joined, swapped against and exited, checking every token balance
+// and the BPT supply after each step – under SPL Token and Token‑2022.
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use client::PoolClient;
+use integration::Env;
+use solana_sdk::signature::{Keypair, Signer};
+use weighted_pool::MINIMUM_BPT;
+
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn create_join_swap_exit() {
+    join_swap_exit(spl_token::ID).await;
+}
+
+#[tokio::test]
+async fn create_join_swap_exit_token_2022() {
+    join_swap_exit(spl_token_2022::ID).await;
+}
+
+async fn join_swap_exit(token_program: Pubkey) {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(ONE as u64 / 2).await;
+    let (a, b) = (env.create_mint_of(&token_program, 6).await, env.create_mint_of(&token_program, 6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE * 8 / 10), (b, ONE * 2 / 10)], 3_000_000_000_000_000).await;
+    assert_eq!(env.token_program_of(&pool.lp_mint).await, token_program);
+    assert_eq!(env.token_program_of(&pool.custody(&a)).await, token_program);
+
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+
+    // Initial join: every token moves into custody, BPT is minted to
+    // the user but for MINIMUM_BPT, locked in the pool authority's account
+    let start = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!((start.bpt_supply, start.total_bpt), (0, 0));
+    let amounts_in = vec![4_000_000, 1_000_000];
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, amounts_in.clone(), 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(joined.custody, amounts_in);
+    assert_eq!(joined.user, vec![start.user[0] - amounts_in[0], start.user[1] - amounts_in[1]]);
+    assert!(joined.user_bpt > 0);
+    assert_eq!(env.balance(&pool.locked_bpt_account()).await, MINIMUM_BPT);
+    let supply = joined.user_bpt + MINIMUM_BPT;
+    assert_eq!((joined.protocol_bpt, joined.bpt_supply, joined.total_bpt), (0, supply, supply));
+
+    // A second join is priced against the balances: a proportional 10%
+    // pays no swap fee and mints 10% of the supply, rounded down
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![400_000, 100_000], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let rejoined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let minted = rejoined.user_bpt - joined.user_bpt;
+    assert!((joined.bpt_supply / 10 - 1..=joined.bpt_supply / 10).contains(&minted));
+    assert_eq!(rejoined.custody, vec![4_400_000, 1_100_000]);
+    assert_eq!(rejoined.bpt_supply, rejoined.user_bpt + rejoined.protocol_bpt + MINIMUM_BPT);
+    assert_eq!(rejoined.total_bpt, rejoined.bpt_supply);
+    let joined = rejoined;
+
+    // Swap: the amount in splits between custody and the protocol fee
+    // account; the amount out leaves custody for the user
+    let amount_in = 100_000;
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], amount_in, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    let swapped = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(swapped.user[0], joined.user[0] - amount_in);
+    let protocol_fee = swapped.protocol[0] - joined.protocol[0];
+    assert!(protocol_fee > 0);
+    assert_eq!(swapped.custody[0], joined.custody[0] + amount_in - protocol_fee);
+    let amount_out = swapped.user[1] - joined.user[1];
+    assert!(amount_out > 0);
+    assert_eq!(swapped.custody[1], joined.custody[1] - amount_out);
+    assert_eq!((swapped.bpt_supply, swapped.total_bpt), (joined.bpt_supply, joined.total_bpt));
+
+    // A swap below its minimum out fails and moves nothing
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], amount_in, u64::MAX);
+    assert!(env.process(&[swap], &[]).await.is_err());
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, swapped);
+
+    // An exit below its minimums fails and moves nothing
+    let bpt_in = swapped.user_bpt / 2;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0, u64::MAX]).unwrap();
+    assert!(env.process(&[exit], &[]).await.is_err());
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, swapped);
+
+    // Exit half: BPT is burned, whatever leaves custody goes to the user
+    // and the protocol takes nothing
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; 2]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(exited.user_bpt, swapped.user_bpt - bpt_in);
+    assert_eq!(exited.bpt_supply, swapped.bpt_supply - bpt_in);
+    assert_eq!(exited.total_bpt, exited.bpt_supply);
+    for i in 0..2 {
+        let paid = swapped.custody[i] - exited.custody[i];
+        assert!(paid > 0);
+        assert_eq!(exited.user[i] - swapped.user[i], paid);
+    }
+    assert_eq!(exited.protocol, swapped.protocol);
+    // every token pays out the same share of its balance, at most the
+    // share of BPT burned
+    let share = |i: usize| (swapped.custody[i] - exited.custody[i]) as u128 * ONE / swapped.custody[i] as u128;
+    assert!(share(0).abs_diff(share(1)) < ONE / 100_000);
+    assert!(share(0) <= bpt_in as u128 * ONE / swapped.bpt_supply as u128);
+}
+
+// Joins mint and exits burn only through the signer's own account of
+// the pool's BPT: not another holder's, even one it is a delegate of,
+// and not an account of another mint.
+#[tokio::test]
+async fn lp_account_must_be_the_users_own() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in &pool.join_tokens() {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let other = Keypair::new();
+    let ix = create_associated_token_account_idempotent(&payer, &other.pubkey(), &pool.lp_mint, &spl_token::ID);
+    env.process(&[ix], &[]).await.unwrap();
+    let other_lp = get_associated_token_address(&other.pubkey(), &pool.lp_mint);
+
+    for lp in [other_lp, user_tokens[0]] {
+        let join = pool.join_exact_tokens_in_for_bpt_out(&payer, lp, &user_tokens, vec![1_000_000; 2], 0).unwrap();
+        assert!(env.process(&[join], &[]).await.is_err());
+    }
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![1_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // The other holder delegates its BPT to the user: the token program
+    // would let the user burn it, the pool does not
+    let bpt = env.balance(&user_lp).await / 2;
+    let ixs = [
+        spl_token::instruction::transfer(&spl_token::ID, &user_lp, &other_lp, &payer, &[], bpt).unwrap(),
+        spl_token::instruction::approve(&spl_token::ID, &other_lp, &payer, &other.pubkey(), &[], bpt).unwrap(),
+    ];
+    env.process(&ixs, &[&other]).await.unwrap();
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, other_lp, &user_tokens, bpt, vec![0; user_tokens.len()]).unwrap();
+    assert!(env.process(&[exit], &[]).await.is_err());
+    assert_eq!(env.balance(&other_lp).await, bpt);
+
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    assert_eq!(env.balance(&user_lp).await, 0);
+}
+
+// A pool is created only over a mint its authority PDA alone can mint,
+// and joins mint only the pool's own BPT.
+#[tokio::test]
+async fn lp_mint_must_be_the_pools() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let (mints, weights) = if a < b { (vec![a, b], vec![ONE / 2; 2]) } else { (vec![b, a], vec![ONE / 2; 2]) };
+    let state: vault::VaultState = env.fetch(&vault.vault_state).await;
+    let payer = env.payer();
+    let lp_mint = Keypair::new();
+    let (_, create) =
+        PoolClient::create_weighted(vault.vault_state, &state, &payer, &payer, lp_mint.pubkey(), mints, weights, 3_000_000_000_000_000).unwrap();
+    env.create_mint_at(&spl_token::ID, &lp_mint, &payer, 6).await.unwrap();
+    assert!(env.process(&[create], &[]).await.is_err());
+
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in &pool.join_tokens() {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    // another mint of the pool's authority, and the user's account of it
+    let forged = Keypair::new();
+    env.create_mint_at(&spl_token::ID, &forged, &pool.authority(), 6).await.unwrap();
+    let forged_lp = env.token_account(&forged.pubkey()).await;
+    let mut join = pool.join_exact_tokens_in_for_bpt_out(&payer, forged_lp, &user_tokens, vec![1_000_000; 2], 0).unwrap();
+    for meta in &mut join.accounts {
+        if meta.pubkey == pool.lp_mint {
+            meta.pubkey = forged.pubkey();
+        }
+    }
+    assert!(env.process(&[join], &[]).await.is_err());
+    assert_eq!(env.supply(&forged.pubkey()).await, 0);
+}
+
+// A swap reads and moves only the pool's registered custody: paying in
+// to an account of the swapper's own cannot draw on the pool's.
+#[tokio::test]
+async fn swap_custody_must_be_the_pools() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // a second, funded account of the 'in' mint, owned by the swapper
+    let own = Keypair::new();
+    let ixs = [
+        solana_sdk::system_instruction::create_account(
+            &payer,
+            &own.pubkey(),
+            env.ctx.banks_client.get_rent().await.unwrap().minimum_balance(165),
+            165,
+            &spl_token::ID,
+        ),
+        spl_token::instruction::initialize_account3(&spl_token::ID, &own.pubkey(), &mints[0], &payer).unwrap(),
+        spl_token::instruction::mint_to(&spl_token::ID, &mints[0], &own.pubkey(), &payer, &[], 5_000_000).unwrap(),
+    ];
+    env.process(&ixs, &[&own]).await.unwrap();
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let mut swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], 1_000_000, 1);
+    for meta in &mut swap.accounts {
+        if meta.pubkey == pool.custody(&mints[0]) {
+            meta.pubkey = own.pubkey();
+        }
+    }
+    assert!(env.process(&[swap], &[]).await.is_err());
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+}
+
+// A swap pays in from and out to the swapper's own accounts of the
+// custody mints: not another holder's, even one it is a delegate of,
+// and not an account of another mint.
+#[tokio::test]
+async fn swap_user_accounts_must_be_the_swappers() {
+    use integration::custom_error;
+    use weighted_pool::ErrorCode;
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // another holder of the 'in' token delegates its balance to the swapper
+    let other = Keypair::new();
+    let ix = create_associated_token_account_idempotent(&payer, &other.pubkey(), &mints[0], &spl_token::ID);
+    env.process(&[ix], &[]).await.unwrap();
+    let other_in = get_associated_token_address(&other.pubkey(), &mints[0]);
+    let ixs = [
+        spl_token::instruction::transfer(&spl_token::ID, &user_tokens[0], &other_in, &payer, &[], 1_000_000).unwrap(),
+        spl_token::instruction::approve(&spl_token::ID, &other_in, &payer, &other.pubkey(), &[], 1_000_000).unwrap(),
+    ];
+    env.process(&ixs, &[&other]).await.unwrap();
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, other_in, user_tokens[1], &mints[0], &mints[1], 1_000_000, 1);
+    assert_eq!(custom_error(env.process(&[swap], &[]).await), Some(ErrorCode::InvalidUserTokenAccount.into()));
+    assert_eq!(env.balance(&other_in).await, 1_000_000);
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[0], &mints[0], &mints[1], 1_000_000, 1);
+    assert_eq!(custom_error(env.process(&[swap], &[]).await), Some(ErrorCode::InvalidUserTokenAccount.into()));
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], 1_000_000, 1);
+    env.process(&[swap], &[]).await.unwrap();
+    assert_eq!(env.balance(&user_tokens[0]).await, before.user[0] - 1_000_000);
+}
+
+// The reentrancy mark: cleared once a join, swap or exit completes, and
+// while set – as an invocation nested in one would find it – every one
+// of them fails.
+#[tokio::test]
+async fn entered_pool_rejects_nested_operations() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+    let swap = pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], 100_000, 1);
+    env.process(&[join, swap], &[]).await.unwrap();
+    let state: weighted_pool::Pool = env.fetch(&pool.pool).await;
+    assert!(!state.entered);
+
+    let mut entered = state.clone();
+    entered.entered = true;
+    env.store(&pool.pool, &entered).await;
+
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let ixs = [
+        pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![1_000_000; 2], 0).unwrap(),
+        pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], 100_000, 1),
+        pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, before.user_bpt / 2, vec![0; user_tokens.len()]).unwrap(),
+        pool.recovery_exit(&payer, user_lp, &user_tokens, before.user_bpt / 2).unwrap(),
+    ];
+    for ix in ixs {
+        assert!(env.process(&[ix], &[]).await.is_err());
+    }
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+}
+
+// Joins and exits price against the LP mint's supply, whatever the
+// stored total says, and bring the stored total back in line with it.
+#[tokio::test]
+async fn total_bpt_follows_the_mint_supply() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in &pool.join_tokens() {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![4_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    let mut state: weighted_pool::Pool = env.fetch(&pool.pool).await;
+    state.total_bpt *= 2;
+    env.store(&pool.pool, &state).await;
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let bpt_in = before.bpt_supply / 4;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    for i in 0..2 {
+        let share = (before.custody[i] as u128 * bpt_in as u128 / before.bpt_supply as u128) as u64;
+        assert_eq!(exited.user[i], before.user[i] + share);
+    }
+    assert_eq!(exited.total_bpt, exited.bpt_supply);
+
+    state = env.fetch(&pool.pool).await;
+    state.total_bpt /= 3;
+    env.store(&pool.pool, &state).await;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(exited.total_bpt, exited.bpt_supply);
+}
+
+// The first join locks MINIMUM_BPT: a seeder who exits all but one unit
+// of BPT and donates to custody cannot make a unit of BPT worth so much
+// that a later joiner's mint rounds their deposit away.
+#[tokio::test]
+async fn locked_bpt_defeats_first_depositor_inflation() {
+    use integration::custom_error;
+    use solana_sdk::instruction::AccountMeta;
+    use weighted_pool::ErrorCode;
+
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut attacker_tokens = Vec::new();
+    for mint in &mints {
+        attacker_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let attacker_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+
+    // seeding needs the account the BPT is locked in
+    let mut join = pool.join_exact_tokens_in_for_bpt_out(&payer, attacker_lp, &attacker_tokens, vec![1_000_000; 2], 0).unwrap();
+    let locked = std::mem::replace(&mut join.accounts[8], AccountMeta::new_readonly(weighted_pool::ID, false));
+    assert_eq!(locked.pubkey, pool.locked_bpt_account());
+    assert_eq!(custom_error(env.process(&[join.clone()], &[]).await), Some(ErrorCode::InvalidLockedBptAccount.into()));
+
+    // seed, exit down to one unit, donate
+    join.accounts[8] = locked;
+    env.process(&[join], &[]).await.unwrap();
+    let bpt_in = env.balance(&attacker_lp).await - 1;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, attacker_lp, &attacker_tokens, bpt_in, vec![0; 2]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    assert_eq!(env.supply(&pool.lp_mint).await, MINIMUM_BPT + 1);
+    let mut ixs = Vec::new();
+    for (mint, from) in mints.iter().zip(&attacker_tokens) {
+        ixs.push(spl_token::instruction::transfer(&spl_token::ID, from, &pool.custody(mint), &payer, &[], 10_000_000).unwrap());
+    }
+    env.process(&ixs, &[]).await.unwrap();
+
+    // the victim joins with half the donation
+    let victim = Keypair::new();
+    let mut victim_tokens = Vec::new();
+    for mint in mints.iter().chain([&pool.lp_mint]) {
+        let ix = create_associated_token_account_idempotent(&payer, &victim.pubkey(), mint, &spl_token::ID);
+        env.process(&[ix], &[]).await.unwrap();
+        victim_tokens.push(get_associated_token_address(&victim.pubkey(), mint));
+    }
+    let victim_lp = victim_tokens.pop().unwrap();
+    let mut ixs = Vec::new();
+    for (from, to) in attacker_tokens.iter().zip(&victim_tokens) {
+        ixs.push(spl_token::instruction::transfer(&spl_token::ID, from, to, &payer, &[], 5_000_000).unwrap());
+    }
+    env.process(&ixs, &[]).await.unwrap();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&victim.pubkey(), victim_lp, &victim_tokens, vec![5_000_000; 2], 0).unwrap();
+    env.process(&[join], &[&victim]).await.unwrap();
+
+    // their BPT is worth all but a sliver of what they paid in, and the
+    // attacker's unit a sliver of what it donated
+    let (victim_bpt, supply) = (env.balance(&victim_lp).await, env.supply(&pool.lp_mint).await);
+    for mint in &mints {
+        let custody = env.balance(&pool.custody(mint)).await as u128;
+        assert!(custody * victim_bpt as u128 / supply as u128 >= 4_950_000);
+        assert!(custody / supply as u128 <= 10_000_000 / 100);
+    }
+}
+
+// A recovery exit is closed while every token can move and nothing is
+// paused; once a guardian pauses the pool it pays a proportional share.
+#[tokio::test]
+async fn recovery_exit_needs_a_paused_pool() {
+    use integration::custom_error;
+    use weighted_pool::ErrorCode;
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in &pool.join_tokens() {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![4_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let bpt_in = before.user_bpt / 4;
+    let exit = pool.recovery_exit(&payer, user_lp, &user_tokens, bpt_in).unwrap();
+    assert_eq!(custom_error(env.process(&[exit], &[]).await), Some(ErrorCode::RecoveryNotNeeded.into()));
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+
+    let pause = [vault.grant_role(&payer, vault::Action::Pause, payer), vault.set_pool_paused(&payer, &pool.pool_id, true)];
+    env.process(&pause, &[]).await.unwrap();
+    // the same exit again, so under a fresh blockhash
+    env.next_blockhash().await;
+    let exit = pool.recovery_exit(&payer, user_lp, &user_tokens, bpt_in).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    for i in 0..2 {
+        let share = (before.custody[i] as u128 * bpt_in as u128 / before.bpt_supply as u128) as u64;
+        assert_eq!(exited.user[i], before.user[i] + share);
+    }
+}
+
+// Swap fees stay within [MIN_SWAP_FEE, MAX_SWAP_FEE], at creation and
+// at every update.
+#[tokio::test]
+async fn swap_fee_stays_within_bounds() {
+    use weighted_pool::{MAX_SWAP_FEE, MIN_SWAP_FEE};
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    for swap_fee in [0, MIN_SWAP_FEE - 1, MAX_SWAP_FEE + 1, ONE as u64] {
+        assert!(env.try_create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], swap_fee).await.is_err());
+    }
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], MIN_SWAP_FEE).await;
+
+    let payer = env.payer();
+    env.process(&[vault.grant_role(&payer, vault::Action::SetSwapFee, payer)], &[]).await.unwrap();
+    for swap_fee in [MIN_SWAP_FEE - 1, MAX_SWAP_FEE + 1] {
+        assert!(env.process(&[pool.set_swap_fee(&payer, swap_fee)], &[]).await.is_err());
+    }
+    env.process(&[pool.set_swap_fee(&payer, MAX_SWAP_FEE)], &[]).await.unwrap();
+    let state: weighted_pool::Pool = env.fetch(&pool.pool).await;
+    assert_eq!(state.swap_fee, MAX_SWAP_FEE);
+}
+
+// A join's or exit's token pairs follow the registration: the user's own
+// account of each token, in pool order, then that token's custody.
+#[tokio::test]
+async fn token_pairs_must_follow_the_registration() {
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    // user accounts out of pool order
+    let swapped = vec![user_tokens[1], user_tokens[0]];
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &swapped, vec![1_000_000; 2], 0).unwrap();
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &swapped, before.user_bpt / 4, vec![0; swapped.len()]).unwrap();
+    // one token's custody passed for both
+    let mut doubled = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, before.user_bpt / 4, vec![0; user_tokens.len()]).unwrap();
+    for meta in &mut doubled.accounts {
+        if meta.pubkey == pool.custody(&mints[0]) {
+            meta.pubkey = pool.custody(&mints[1]);
+        }
+    }
+    // someone else's account of the first token
+    let other = Keypair::new();
+    let ix = create_associated_token_account_idempotent(&payer, &other.pubkey(), &mints[0], &spl_token::ID);
+    env.process(&[ix], &[]).await.unwrap();
+    let elsewhere = vec![get_associated_token_address(&other.pubkey(), &mints[0]), user_tokens[1]];
+    let paid_elsewhere = pool.recovery_exit(&payer, user_lp, &elsewhere, before.user_bpt / 4).unwrap();
+    for ix in [join, exit, doubled, paid_elsewhere] {
+        assert!(env.process(&[ix], &[]).await.is_err());
+    }
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+}
+
+// Nothing moves for zero or dust amounts, nor for an operation whose
+// proceeds round to nothing.
+#[tokio::test]
+async fn zero_and_dust_operations_are_rejected() {
+    use weighted_pool::MIN_TRADE_AMOUNT;
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![5_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    let join = |amounts_in: Vec<u64>| pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, amounts_in, 0).unwrap();
+    let swap = |amount_in| pool.swap_exact_token_in_for_token_out(&payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], amount_in, 0);
+    let ixs = [
+        join(vec![0, 0]),
+        join(vec![MIN_TRADE_AMOUNT - 1, 1_000_000]),
+        swap(0),
+        swap(MIN_TRADE_AMOUNT - 1),
+        pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, 0, vec![0; user_tokens.len()]).unwrap(),
+        // one BPT of millions is worth less than a raw unit of either token
+        pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, 1, vec![0; user_tokens.len()]).unwrap(),
+        pool.recovery_exit(&payer, user_lp, &user_tokens, 1).unwrap(),
+    ];
+    for ix in ixs {
+        assert!(env.process(&[ix], &[]).await.is_err());
+    }
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+
+    // a single‑token join and a swap at the minimum go through
+    env.process(&[join(vec![0, MIN_TRADE_AMOUNT]), swap(MIN_TRADE_AMOUNT)], &[]).await.unwrap();
+}
+
+// A pool written before `version` and `reserved` existed – the old,
+// shorter account – grows to the current size under the admin's
+// migration and keeps its state; only the admin may migrate, and only
+// an older version.
+#[tokio::test]
+async fn migrate_pool_state_upgrades_old_layouts() {
+    use anchor_lang::AccountSerialize;
+    use weighted_pool::{Pool, POOL_VERSION};
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut current: Pool = env.fetch(&pool.pool).await;
+    assert_eq!(current.version, POOL_VERSION);
+    let payer = env.payer();
+
+    let old_len = 8 + Pool::INIT_SPACE - 1 - Pool::RESERVED;
+    let rent = env.ctx.banks_client.get_rent().await.unwrap();
+    current.version = 0;
+    let mut account = env.ctx.banks_client.get_account(pool.pool).await.unwrap().unwrap();
+    let mut data = Vec::new();
+    current.try_serialize(&mut data).unwrap();
+    data.resize(old_len, 0);
+    account.data = data;
+    account.lamports = rent.minimum_balance(old_len);
+    env.ctx.set_account(&pool.pool, &account.into());
+
+    let stranger = Keypair::new();
+    let ix = pool.migrate_pool_state(&stranger.pubkey(), &payer).unwrap();
+    assert!(env.process(&[ix], &[&stranger]).await.is_err());
+    env.process(&[pool.migrate_pool_state(&payer, &payer).unwrap()], &[]).await.unwrap();
+
+    let account = env.ctx.banks_client.get_account(pool.pool).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), 8 + Pool::INIT_SPACE);
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let migrated: Pool = env.fetch(&pool.pool).await;
+    assert_eq!(migrated.version, POOL_VERSION);
+    assert_eq!((migrated.weights, migrated.swap_fee, migrated.pool_id), (current.weights, current.swap_fee, current.pool_id));
+
+    // already current (the stranger pays no rent here, and as payer keeps
+    // the bank from replaying the identical migration's result)
+    let ix = pool.migrate_pool_state(&payer, &stranger.pubkey()).unwrap();
+    assert!(env.process(&[ix], &[&stranger]).await.is_err());
+}
+
+// The health probe reports a joined pool consistent, and a pool whose
+// stored BPT count drifted from the mint's supply not.
+#[tokio::test]
+async fn verify_pool_reports_health() {
+    use weighted_pool::{Pool, PoolHealth};
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in pool.join_tokens() {
+        user_tokens.push(env.mint_to(&mint, 10_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![1_000_000, 4_000_000], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    let health: PoolHealth = env.process_returning(&[pool.verify_pool().unwrap()], &[]).await.unwrap();
+    assert!(health.healthy);
+    assert_eq!(health.pool, pool.pool);
+    assert_eq!(health.balances, joined.custody);
+    assert_eq!((health.bpt_supply, health.total_bpt), (joined.bpt_supply, joined.total_bpt));
+    assert_eq!(health.weight_sum, ONE);
+    // √(1 · 4) tokens of 18‑dec invariant, to within rounding
+    assert!(health.invariant.abs_diff(2_000_000 * ONE) < ONE);
+
+    let mut state: Pool = env.fetch(&pool.pool).await;
+    state.total_bpt += 1;
+    env.store(&pool.pool, &state).await;
+    env.next_blockhash().await;
+    let health: PoolHealth = env.process_returning(&[pool.verify_pool().unwrap()], &[]).await.unwrap();
+    assert!(!health.healthy);
+    assert_eq!(health.total_bpt, joined.total_bpt + 1);
+}
+
+// A price‑limited swap fails once its post‑trade spot price – token out
+// in token in, fee included – would exceed the limit, and otherwise
+// settles as the plain swap does.
+#[tokio::test]
+async fn swap_respects_max_price() {
+    use integration::custom_error;
+    use weighted_pool::ErrorCode;
+    let mut env = Env::start().await;
+    let vault = env.init_vault(0).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE / 2), (b, ONE / 2)], 3_000_000_000_000_000).await;
+    let mints = pool.join_tokens();
+    let mut user_tokens = Vec::new();
+    for mint in &mints {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![10_000_000; 2], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+
+    // equal balances price at 1 / (1 − 0.3 %) before the trade, higher after
+    let swap = |max_price| {
+        pool.swap_exact_token_in_for_token_out_with_max_price(
+            &payer, user_tokens[0], user_tokens[1], &mints[0], &mints[1], 100_000, 0, Some(max_price),
+        )
+        .unwrap()
+    };
+    let limited = swap(ONE * 1_003_010 / 1_000_000);
+    assert_eq!(custom_error(env.process(&[limited], &[]).await), Some(ErrorCode::PriceLimitExceeded.into()));
+    assert_eq!(env.snapshot(&pool, &user_tokens, &user_lp).await, before);
+
+    env.process(&[swap(ONE * 103 / 100)], &[]).await.unwrap();
+    let after = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(after.user[0], before.user[0] - 100_000);
+    assert!(after.user[1] > before.user[1]);
+}
+
+// The protocol fee preview matches what the next join mints to the fee
+// collector; a proportional exit, even with a swap fee set, pays the
+// collector nothing and the user their full share.
+#[tokio::test]
+async fn query_due_protocol_fees_matches_the_next_join() {
+    use weighted_pool::DueProtocolFees;
+    let mut env = Env::start().await;
+    let vault = env.init_vault(ONE as u64 / 2).await;
+    let (a, b) = (env.create_mint(6).await, env.create_mint(6).await);
+    let pool = env.create_weighted_pool(&vault, vec![(a, ONE * 8 / 10), (b, ONE * 2 / 10)], 3_000_000_000_000_000).await;
+    let mut user_tokens = Vec::new();
+    for mint in &pool.join_tokens() {
+        user_tokens.push(env.mint_to(mint, 20_000_000).await);
+    }
+    let user_lp = env.token_account(&pool.lp_mint).await;
+    let payer = env.payer();
+
+    // nothing is due on the first join
+    let query = pool.query_due_protocol_fees(vec![4_000_000, 1_000_000]).unwrap();
+    let due: DueProtocolFees = env.process_returning(&[query], &[]).await.unwrap();
+    assert_eq!(due, DueProtocolFees { protocol_bpt: 0 });
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![4_000_000, 1_000_000], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+
+    // an unbalanced join pays the protocol part of its fee
+    let before = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    let query = pool.query_due_protocol_fees(vec![2_000_000, 0]).unwrap();
+    let due: DueProtocolFees = env.process_returning(&[query], &[]).await.unwrap();
+    assert!(due.protocol_bpt > 0);
+    let join = pool.join_exact_tokens_in_for_bpt_out(&payer, user_lp, &user_tokens, vec![2_000_000, 0], 0).unwrap();
+    env.process(&[join], &[]).await.unwrap();
+    let joined = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(joined.protocol_bpt - before.protocol_bpt, due.protocol_bpt);
+
+    let bpt_in = joined.user_bpt / 4;
+    let exit = pool.exit_exact_bpt_in_for_tokens_out(&payer, user_lp, &user_tokens, bpt_in, vec![0; user_tokens.len()]).unwrap();
+    env.process(&[exit], &[]).await.unwrap();
+    let exited = env.snapshot(&pool, &user_tokens, &user_lp).await;
+    assert_eq!(exited.protocol, joined.protocol);
+    for i in 0..2 {
+        let share = joined.custody[i] as u128 * bpt_in as u128 / joined.bpt_supply as u128;
+        assert!((exited.user[i] - joined.user[i]) as u128 >= share - 1);
+    }
+}
@@ -0,0 +1,94 @@
+// Host‑side timings for the hot math paths. On‑chain compute units are
+// measured by the `math-bench` program (see tests/math-bench.ts).
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use math::{fixed, log_exp_math, weighted_math, U256};
+
+fn fp(v: u128) -> U256 {
+    U256::from(v) * fixed::ONE
+}
+
+/// `ppm / 1e6` as an 18‑dec value.
+fn ppm(ppm: u64) -> U256 {
+    U256::from(ppm) * U256::from(1_000_000_000_000u64)
+}
+
+fn fixed_point(c: &mut Criterion) {
+    let (a, b) = (fp(1_234_567), ppm(987_654));
+    c.bench_function("fixed/mul_down", |bench| bench.iter(|| fixed::mul_down(black_box(a), black_box(b))));
+    c.bench_function("fixed/mul_up", |bench| bench.iter(|| fixed::mul_up(black_box(a), black_box(b))));
+    c.bench_function("fixed/div_down", |bench| bench.iter(|| fixed::div_down(black_box(a), black_box(b))));
+    c.bench_function("fixed/div_up", |bench| bench.iter(|| fixed::div_up(black_box(a), black_box(b))));
+}
+
+fn pow(c: &mut Criterion) {
+    let base = ppm(1_250_000);
+    c.bench_function("pow/log_exp", |bench| bench.iter(|| log_exp_math::pow(black_box(base), black_box(ppm(333_333)))));
+    // (0.9, 1.1) takes the 36‑decimal ln
+    c.bench_function("pow/log_exp_near_one", |bench| {
+        bench.iter(|| log_exp_math::pow(black_box(ppm(1_050_000)), black_box(ppm(333_333))))
+    });
+    c.bench_function("pow/pow_down", |bench| bench.iter(|| fixed::pow_down(black_box(base), black_box(ppm(333_333)))));
+    c.bench_function("pow/pow_up", |bench| bench.iter(|| fixed::pow_up(black_box(base), black_box(ppm(333_333)))));
+    c.bench_function("pow/pow_down_integer", |bench| bench.iter(|| fixed::pow_down(black_box(base), black_box(fp(2)))));
+}
+
+fn invariant(c: &mut Criterion) {
+    let balances2 = [fp(1_000_000), fp(2_500_000)];
+    let weights2 = [ppm(800_000), ppm(200_000)];
+    c.bench_function("invariant/2_tokens", |bench| {
+        bench.iter(|| weighted_math::calculate_invariant(black_box(&balances2), black_box(&weights2)))
+    });
+    let balances8: Vec<U256> = (1..=8).map(|i| fp(100_000 * i)).collect();
+    let weights8 = vec![ppm(125_000); 8];
+    c.bench_function("invariant/2_tokens_50_50", |bench| {
+        bench.iter(|| weighted_math::calculate_invariant(black_box(&balances2), black_box(&[ppm(500_000); 2])))
+    });
+    c.bench_function("invariant/8_tokens", |bench| {
+        bench.iter(|| weighted_math::calculate_invariant(black_box(&balances8), black_box(&weights8)))
+    });
+}
+
+fn swap(c: &mut Criterion) {
+    let (b_in, w_in, b_out, w_out) = (fp(1_000_000), ppm(800_000), fp(2_500_000), ppm(200_000));
+    let fee = ppm(3_000);
+    c.bench_function("swap/out_given_in", |bench| {
+        bench.iter(|| weighted_math::calc_out_given_in(b_in, w_in, b_out, w_out, black_box(fp(1_000)), fee))
+    });
+    c.bench_function("swap/out_given_in_50_50", |bench| {
+        bench.iter(|| weighted_math::calc_out_given_in(b_in, ppm(500_000), b_out, ppm(500_000), black_box(fp(1_000)), fee))
+    });
+    c.bench_function("swap/in_given_out", |bench| {
+        bench.iter(|| weighted_math::calc_in_given_out(b_in, w_in, b_out, w_out, black_box(fp(1_000)), fee))
+    });
+}
+
+fn join_exit(c: &mut Criterion) {
+    let balances = [fp(1_000_000), fp(2_500_000)];
+    let weights = [ppm(800_000), ppm(200_000)];
+    let total_bpt = fp(1_000_000);
+    let fee = ppm(3_000);
+    c.bench_function("join/bpt_out_given_exact_tokens_in", |bench| {
+        let amounts = [fp(10_000), fp(1_000)];
+        bench.iter(|| {
+            weighted_math::calc_bpt_out_given_exact_tokens_in(&balances, &weights, black_box(&amounts), total_bpt, fee)
+        })
+    });
+    c.bench_function("join/token_in_given_exact_bpt_out", |bench| {
+        bench.iter(|| {
+            weighted_math::calc_token_in_given_exact_bpt_out(balances[0], weights[0], black_box(fp(1_000)), total_bpt, fee)
+        })
+    });
+    c.bench_function("exit/tokens_out_given_exact_bpt_in", |bench| {
+        bench.iter(|| {
+            weighted_math::calc_tokens_out_given_exact_bpt_in(&balances, black_box(fp(1_000)), total_bpt, U256::zero())
+        })
+    });
+    c.bench_function("exit/token_out_given_exact_bpt_in", |bench| {
+        bench.iter(|| {
+            weighted_math::calc_token_out_given_exact_bpt_in(balances[0], weights[0], black_box(fp(1_000)), total_bpt, fee)
+        })
+    });
+}
+
+criterion_group!(benches, fixed_point, pow, invariant, swap, join_exit);
+criterion_main!(benches);
@@ -0,0 +1,378 @@
+// Symmetric‑Solana ─ E‑CLP math
+// ================================================================
+// Elliptic concentrated liquidity (Gyroscope E‑CLP) for two‑token pools.
+// Liquidity sits on an ellipse: rotated by φ, stretched by λ and
+// restricted to the arc whose prices (x in units of y) lie in [α, β].
+//
+//   ‖A · (t − r·χ)‖ = r,   A = ⎡ c/λ  −s/λ ⎤
+//                              ⎣ s     c   ⎦
+//
+// with t the balances, r the invariant, c = cos φ, s = sin φ, and the
+// virtual offsets r·χ fixed by the price bounds. Everything is 18‑dec
+// fixed point (signed intermediates in `I256`). The curve is evaluated
+// in units of r, so intermediates stay near one whatever the balances,
+// and every swap pays a relative error margin on r to the pool.
+// ================================================================
+use super::{fees, fixed, MathError, I256, U256};
+
+const ONE: I256 = I256::ONE;
+
+/// Allowed deviation of c² + s² from one (1e‑8)
+const ROTATION_TOLERANCE: U256 = U256([10_000_000_000, 0, 0, 0]);
+/// Largest accepted stretch λ (1e8)
+pub const MAX_LAMBDA: U256 = U256([0x52b7d2dcc80cd2e4000000u128 as u64, (0x52b7d2dcc80cd2e4000000u128 >> 64) as u64, 0, 0]);
+/// Relative error of evaluating the curve (1e‑12 of the invariant),
+/// taken from every amount out and added to every amount in.
+pub const MAX_RELATIVE_ERROR: U256 = U256([1_000_000, 0, 0, 0]);
+
+/// Curve parameters, all 18‑dec fixed point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// Lowest price of x in units of y
+    pub alpha: I256,
+    /// Highest price of x in units of y
+    pub beta: I256,
+    /// cos φ
+    pub c: I256,
+    /// sin φ
+    pub s: I256,
+    /// Stretch λ ≥ 1; larger concentrates liquidity around the price tan φ
+    pub lambda: I256,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Vector2 {
+    x: I256,
+    y: I256,
+}
+
+/// Quantities derived from the parameters, in units of the invariant.
+struct Derived {
+    /// Virtual offsets / r
+    chi: Vector2,
+    /// Largest x / r (reached at y = 0)
+    x_max: I256,
+    /// Largest y / r (reached at x = 0)
+    y_max: I256,
+}
+
+impl Params {
+    /// Checks 0 < α < β, c, s ≥ 0 with c² + s² = 1, and 1 ≤ λ ≤ `MAX_LAMBDA`.
+    pub fn validate(&self) -> Result<(), MathError> {
+        let max_lambda = I256::from_unsigned(MAX_LAMBDA).ok_or(MathError::InvalidParams)?;
+        if self.alpha <= I256::ZERO
+            || self.beta <= self.alpha
+            || self.c.is_negative()
+            || self.s.is_negative()
+            || self.lambda < ONE
+            || self.lambda > max_lambda
+        {
+            return Err(MathError::InvalidParams);
+        }
+        let norm = mul(self.c, self.c)? + mul(self.s, self.s)?;
+        if (norm - ONE).unsigned_abs() > ROTATION_TOLERANCE {
+            return Err(MathError::InvalidParams);
+        }
+        Ok(())
+    }
+
+    /// A · v
+    fn mul_a(&self, v: Vector2) -> Result<Vector2, MathError> {
+        Ok(Vector2 {
+            x: div(mul(self.c, v.x)? - mul(self.s, v.y)?, self.lambda)?,
+            y: mul(self.s, v.x)? + mul(self.c, v.y)?,
+        })
+    }
+
+    /// A⁻¹ · v
+    fn mul_a_inv(&self, v: Vector2) -> Result<Vector2, MathError> {
+        Ok(Vector2 {
+            x: mul(mul(self.c, self.lambda)?, v.x)? + mul(self.s, v.y)?,
+            y: mul(-mul(self.s, self.lambda)?, v.x)? + mul(self.c, v.y)?,
+        })
+    }
+
+    /// Unit‑circle point whose tangent corresponds to price `px`.
+    fn tau(&self, px: I256) -> Result<Vector2, MathError> {
+        let nd = self.mul_a(Vector2 { x: -ONE, y: px })?;
+        let pxc = -div(nd.y, nd.x)?;
+        let z = sqrt(ONE + mul(pxc, pxc)?)?;
+        Ok(Vector2 { x: div(pxc, z)?, y: div(ONE, z)? })
+    }
+
+    fn derived(&self) -> Result<Derived, MathError> {
+        self.validate()?;
+        let at_alpha = self.mul_a_inv(self.tau(self.alpha)?)?;
+        let at_beta = self.mul_a_inv(self.tau(self.beta)?)?;
+        Ok(Derived {
+            chi: Vector2 { x: at_beta.x, y: at_alpha.y },
+            x_max: at_beta.x - at_alpha.x,
+            y_max: at_alpha.y - at_beta.y,
+        })
+    }
+}
+
+/// Invariant r of `balances` = [x, y], rounded down.
+pub fn calculate_invariant(params: &Params, balances: [U256; 2]) -> U256 {
+    expect(try_calculate_invariant(params, balances))
+}
+
+pub fn try_calculate_invariant(params: &Params, balances: [U256; 2]) -> Result<U256, MathError> {
+    let d = params.derived()?;
+    // r is homogeneous in the balances: solve for balances scaled to ≤ 1
+    let scale = balances[0].max(balances[1]);
+    if scale.is_zero() {
+        return Ok(U256::zero());
+    }
+    let t = Vector2 { x: unsigned_ratio(balances[0], scale)?, y: unsigned_ratio(balances[1], scale)? };
+
+    // ‖A·t − r·A·χ‖² = r²  ⇔  r²(‖Aχ‖² − 1) − 2r(At·Aχ) + ‖At‖² = 0
+    let at = params.mul_a(t)?;
+    let a_chi = params.mul_a(d.chi)?;
+    let qa = dot(a_chi, a_chi)? - ONE;
+    if qa <= I256::ZERO {
+        return Err(MathError::InvalidParams);
+    }
+    let qb = dot(at, a_chi)?;
+    let qc = dot(at, at)?;
+    let discriminant = mul(qb, qb)? - mul(qa, qc)?;
+    let r = div(qb + sqrt(discriminant)?, qa)?;
+    let r = r.to_unsigned().ok_or(MathError::Underflow)?;
+    fixed::try_mul_down(r, scale)
+}
+
+/// Exact‑in swap: amount of the other token received for `amount_in` of
+/// `token_index_in` (0 = x, 1 = y). The swap fee is taken from the amount in.
+pub fn calc_out_given_in(
+    params: &Params,
+    balances: [U256; 2],
+    token_index_in: usize,
+    amount_in: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_out_given_in(params, balances, token_index_in, amount_in, invariant, swap_fee))
+}
+
+pub fn try_calc_out_given_in(
+    params: &Params,
+    balances: [U256; 2],
+    token_index_in: usize,
+    amount_in: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    let (i, o) = indices(token_index_in)?;
+    let amount_in_after_fee = fees::try_subtract_fee(amount_in, swap_fee)?;
+    let new_balance_in = fixed::try_add(balances[i], amount_in_after_fee)?;
+    let new_balance_out = balance_on_curve(params, new_balance_in, i, invariant)?;
+    let new_balance_out = fixed::try_add(new_balance_out, error_margin(invariant)?)?;
+    Ok(balances[o].saturating_sub(new_balance_out))
+}
+
+/// Exact‑out swap: amount of `token_index_in` (0 = x, 1 = y) needed to
+/// receive `amount_out` of the other token, swap fee included.
+pub fn calc_in_given_out(
+    params: &Params,
+    balances: [U256; 2],
+    token_index_in: usize,
+    amount_out: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_in_given_out(params, balances, token_index_in, amount_out, invariant, swap_fee))
+}
+
+pub fn try_calc_in_given_out(
+    params: &Params,
+    balances: [U256; 2],
+    token_index_in: usize,
+    amount_out: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    let (i, o) = indices(token_index_in)?;
+    let new_balance_out = fixed::try_sub(balances[o], amount_out)?;
+    let new_balance_in = balance_on_curve(params, new_balance_out, o, invariant)?;
+    let new_balance_in = fixed::try_add(new_balance_in, error_margin(invariant)?)?;
+    let amount_in = new_balance_in.saturating_sub(balances[i]);
+    fees::try_add_fee(amount_in, swap_fee)
+}
+
+/// Balance of the other token on the curve `invariant` when token `index`
+/// holds `balance`. Rounded down; the swap functions add the error margin.
+pub fn try_balance_on_curve(params: &Params, balance: U256, index: usize, invariant: U256) -> Result<U256, MathError> {
+    indices(index)?;
+    balance_on_curve(params, balance, index, invariant)
+}
+
+fn balance_on_curve(params: &Params, balance: U256, index: usize, invariant: U256) -> Result<U256, MathError> {
+    let d = params.derived()?;
+    if invariant.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    // solving for y given x and x given y only differ by exchanging the
+    // roles of c and s (and of the coordinates)
+    let (given_chi, other_chi, given_max, c, s) = if index == 0 {
+        (d.chi.x, d.chi.y, d.x_max, params.c, params.s)
+    } else {
+        (d.chi.y, d.chi.x, d.y_max, params.s, params.c)
+    };
+    let given = unsigned_ratio(balance, invariant)?;
+    if given > given_max {
+        return Err(MathError::AssetBoundsExceeded);
+    }
+
+    // (c·u − s·v)²/λ² + (s·u + c·v)² = 1 in units of r, with u, v the
+    // offset coordinates; quadratic in v, and the curve's arc is the lower root
+    let u = given - given_chi;
+    let lambda_sq_inv = div(ONE, mul(params.lambda, params.lambda)?)?;
+    let qa = mul(c, c)? + mul(mul(s, s)?, lambda_sq_inv)?;
+    let qb = mul(mul(mul(c, s)?, u)?, ONE - lambda_sq_inv)? * I256::from(2i64);
+    let qc = mul(mul(u, u)?, mul(s, s)? + mul(mul(c, c)?, lambda_sq_inv)?)? - ONE;
+    let discriminant = mul(qb, qb)? - mul(qa, qc)? * I256::from(4i64);
+    if discriminant.is_negative() {
+        return Err(MathError::AssetBoundsExceeded);
+    }
+    let v = div(-qb - sqrt(discriminant)?, qa * I256::from(2i64))?;
+    let other = (v + other_chi).to_unsigned().ok_or(MathError::AssetBoundsExceeded)?;
+    fixed::try_mul_down(other, invariant)
+}
+
+fn indices(token_index_in: usize) -> Result<(usize, usize), MathError> {
+    match token_index_in {
+        0 => Ok((0, 1)),
+        1 => Ok((1, 0)),
+        _ => Err(MathError::TokenIndexOutOfBounds),
+    }
+}
+
+fn error_margin(invariant: U256) -> Result<U256, MathError> {
+    fixed::try_add(fixed::try_mul_up(invariant, MAX_RELATIVE_ERROR)?, U256::one())
+}
+
+fn expect<T>(result: Result<T, MathError>) -> T {
+    result.unwrap_or_else(|e| panic!("eclp: {}", e))
+}
+
+// ---------------------------------------------------------------------
+// Signed fixed‑point helpers
+// ---------------------------------------------------------------------
+fn mul(a: I256, b: I256) -> Result<I256, MathError> {
+    Ok(a.checked_mul(b).ok_or(MathError::Overflow)? / ONE)
+}
+
+fn div(a: I256, b: I256) -> Result<I256, MathError> {
+    if b.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    Ok(a.checked_mul(ONE).ok_or(MathError::Overflow)? / b)
+}
+
+fn dot(a: Vector2, b: Vector2) -> Result<I256, MathError> {
+    Ok(mul(a.x, b.x)? + mul(a.y, b.y)?)
+}
+
+/// a / b of two unsigned values as a signed fixed‑point number.
+fn unsigned_ratio(a: U256, b: U256) -> Result<I256, MathError> {
+    I256::from_unsigned(fixed::try_div_down(a, b)?).ok_or(MathError::Overflow)
+}
+
+/// √x of a non‑negative fixed‑point value, rounded down.
+fn sqrt(x: I256) -> Result<I256, MathError> {
+    let x = x.to_unsigned().ok_or(MathError::Underflow)?;
+    I256::from_unsigned(fixed::try_sqrt(x)?).ok_or(MathError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(v: f64) -> I256 {
+        I256::from_unsigned(fixed::from_f64(v)).unwrap()
+    }
+
+    fn ufp(v: u128) -> U256 {
+        U256::from(v) * fixed::ONE
+    }
+
+    /// Stablecoin‑style curve around a price of 1: φ = 45°, λ = 100.
+    fn params() -> Params {
+        let half_sqrt2 = I256::from(707_106_781_186_547_524i128);
+        Params { alpha: fp(0.97), beta: fp(1.03), c: half_sqrt2, s: half_sqrt2, lambda: fp(100.0) }
+    }
+
+    fn rel_diff(a: U256, b: U256) -> U256 {
+        let diff = if a > b { a - b } else { b - a };
+        fixed::div_up(diff, a.max(b))
+    }
+
+    #[test]
+    fn rejects_invalid_params() {
+        let p = params();
+        assert!(p.validate().is_ok());
+        for bad in [
+            Params { beta: p.alpha, ..p },
+            Params { alpha: I256::ZERO, ..p },
+            Params { lambda: fp(0.5), ..p },
+            Params { c: fp(0.5), ..p },
+            Params { s: -p.s, ..p },
+        ] {
+            assert_eq!(bad.validate(), Err(MathError::InvalidParams));
+        }
+    }
+
+    #[test]
+    fn balances_lie_on_their_invariant() {
+        let p = params();
+        for balances in [[ufp(1_000), ufp(1_000)], [ufp(1_500), ufp(600)], [ufp(3), ufp(7)]] {
+            let r = calculate_invariant(&p, balances);
+            let y = try_balance_on_curve(&p, balances[0], 0, r).unwrap();
+            let x = try_balance_on_curve(&p, balances[1], 1, r).unwrap();
+            assert!(rel_diff(y, balances[1]) < U256::from(1_000_000u64), "y {} vs {}", y, balances[1]);
+            assert!(rel_diff(x, balances[0]) < U256::from(1_000_000u64), "x {} vs {}", x, balances[0]);
+        }
+        // homogeneous: twice the balances, twice the invariant
+        let r = calculate_invariant(&p, [ufp(1_000), ufp(1_000)]);
+        let r2 = calculate_invariant(&p, [ufp(2_000), ufp(2_000)]);
+        assert!(rel_diff(r * 2, r2) < U256::from(1_000u64));
+    }
+
+    #[test]
+    fn swaps_trade_near_the_concentrated_price_and_favor_the_pool() {
+        let p = params();
+        let balances = [ufp(1_000), ufp(1_000)];
+        let r = calculate_invariant(&p, balances);
+
+        let out = calc_out_given_in(&p, balances, 0, ufp(100), r, U256::zero());
+        // price stays within [α, β]: 100 x buys between 97 and 103 y
+        assert!(out > ufp(97) && out < ufp(103), "out {}", out);
+        let back_in = calc_in_given_out(&p, balances, 0, out, r, U256::zero());
+        // both directions pay the margin, so the round trip lands back on the input
+        let diff = if back_in > ufp(100) { back_in - ufp(100) } else { ufp(100) - back_in };
+        assert!(diff <= error_margin(r).unwrap(), "out {} back_in {}", out, back_in);
+        let cost = calc_in_given_out(&p, balances, 1, ufp(50), r, U256::zero());
+        let after_out = calculate_invariant(&p, [balances[0] - ufp(50), balances[1] + cost]);
+        assert!(after_out >= r);
+
+        // the invariant never falls across a swap
+        let after = calculate_invariant(&p, [balances[0] + ufp(100), balances[1] - out]);
+        assert!(after >= r);
+
+        // fees reduce the amount out
+        let with_fee = calc_out_given_in(&p, balances, 0, ufp(100), r, fixed::from_f64(0.003));
+        assert!(with_fee < out);
+    }
+
+    #[test]
+    fn trades_beyond_the_price_range_fail() {
+        let p = params();
+        let balances = [ufp(1_000), ufp(1_000)];
+        let r = calculate_invariant(&p, balances);
+        assert_eq!(
+            try_calc_out_given_in(&p, balances, 0, ufp(1_000_000), r, U256::zero()),
+            Err(MathError::AssetBoundsExceeded)
+        );
+        assert_eq!(try_calc_out_given_in(&p, balances, 2, ufp(1), r, U256::zero()), Err(MathError::TokenIndexOutOfBounds));
+    }
+}
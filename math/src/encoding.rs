@@ -0,0 +1,115 @@
+// Symmetric‑Solana ─ U256 encodings
+// ================================================================
+// Lossless wire formats for `U256`, each behind its own feature:
+//   • serde: a decimal string ("1000000000000000000"), since JSON
+//     numbers cannot carry 256 bits; "0x…" hex and plain integers are
+//     accepted when reading.
+//   • borsh: the four little‑endian u64 limbs, i.e. 32 bytes LE.
+// ================================================================
+use super::U256;
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::U256;
+    use core::fmt;
+    use serde::de::{self, Visitor};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for U256 {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_str(self)
+        }
+    }
+
+    struct U256Visitor;
+
+    impl Visitor<'_> for U256Visitor {
+        type Value = U256;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a 256-bit unsigned integer as a decimal or 0x-prefixed hex string")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<U256, E> {
+            let parsed = match v.strip_prefix("0x") {
+                Some(hex) if !hex.is_empty() && hex.len() <= 64 => U256::from_str_radix(hex, 16).ok(),
+                Some(_) => None,
+                None => U256::from_dec_str(v).ok(),
+            };
+            parsed.ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<U256, E> {
+            Ok(U256::from(v))
+        }
+
+        fn visit_u128<E: de::Error>(self, v: u128) -> Result<U256, E> {
+            Ok(U256::from(v))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for U256 {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+            deserializer.deserialize_any(U256Visitor)
+        }
+    }
+}
+
+#[cfg(feature = "borsh")]
+mod borsh_impl {
+    use super::U256;
+    use borsh::io::{Read, Result, Write};
+    use borsh::{BorshDeserialize, BorshSerialize};
+
+    impl BorshSerialize for U256 {
+        fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+            self.0.serialize(writer)
+        }
+    }
+
+    impl BorshDeserialize for U256 {
+        fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+            <[u64; 4]>::deserialize_reader(reader).map(U256)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::U256;
+    use crate::fixed;
+
+    fn samples() -> [U256; 4] {
+        [U256::zero(), fixed::ONE, U256::from(u128::MAX) + 1, U256::MAX]
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trips_through_decimal_strings() {
+        for v in samples() {
+            let json = serde_json::to_string(&v).unwrap();
+            assert_eq!(json, format!("\"{}\"", v));
+            assert_eq!(serde_json::from_str::<U256>(&json).unwrap(), v);
+        }
+        assert_eq!(serde_json::from_str::<U256>("\"0xde0b6b3a7640000\"").unwrap(), fixed::ONE);
+        assert_eq!(serde_json::from_str::<U256>("1000000000000000000").unwrap(), fixed::ONE);
+        // one past U256::MAX, an empty hex string and a float are rejected
+        let too_big = "\"115792089237316195423570985008687907853269984665640564039457584007913129639936\"";
+        for bad in [too_big, "\"0x\"", "\"12ab\"", "1.5"] {
+            assert!(serde_json::from_str::<U256>(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh_round_trips_through_32_le_bytes() {
+        for v in samples() {
+            let bytes = borsh::to_vec(&v).unwrap();
+            let mut le = [0u8; 32];
+            v.to_little_endian(&mut le);
+            assert_eq!(bytes, le);
+            assert_eq!(borsh::from_slice::<U256>(&bytes).unwrap(), v);
+        }
+        assert!(borsh::from_slice::<U256>(&[0u8; 31]).is_err());
+    }
+}
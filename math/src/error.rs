@@ -0,0 +1,68 @@
+// Symmetric‑Solana ─ Math errors
+// ================================================================
+// Failure reasons of the fallible (`try_*`) math API. The panicking
+// functions report the same reasons through their panic message.
+// ================================================================
+use core::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    /// A result does not fit in 256 bits.
+    Overflow,
+    /// A subtraction would go below zero.
+    Underflow,
+    DivisionByZero,
+    /// Parallel input slices have different lengths.
+    LengthMismatch,
+    /// Normalized weights do not sum to exactly one.
+    WeightsNotNormalized,
+    /// A normalized weight outside (0, 1).
+    InvalidWeight,
+    /// A token index past the end of the pool.
+    TokenIndexOutOfBounds,
+    /// `pow`/`ln` base outside the range LogExpMath accepts.
+    BaseOutOfBounds,
+    /// `pow`/`exp` exponent outside the range LogExpMath accepts.
+    ExponentOutOfBounds,
+    /// Curve parameters outside their valid ranges.
+    InvalidParams,
+    /// A balance outside the range the curve can hold.
+    AssetBoundsExceeded,
+    /// A fee or percentage above 100 %.
+    InvalidPercentage,
+    /// A token with more decimals than 18‑dec fixed point can represent.
+    DecimalsOutOfBounds,
+    /// A time window that is empty or runs backwards.
+    InvalidTimeRange,
+    /// An iterative solver hit its iteration cap.
+    ConvergenceFailure,
+    /// A swap puts in more than `MAX_IN_RATIO` of the balance in.
+    MaxInRatio,
+    /// A swap takes out more than `MAX_OUT_RATIO` of the balance out.
+    MaxOutRatio,
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            MathError::Overflow => "math overflow",
+            MathError::Underflow => "math underflow",
+            MathError::DivisionByZero => "division by zero",
+            MathError::LengthMismatch => "input length mismatch",
+            MathError::WeightsNotNormalized => "weights do not sum to one",
+            MathError::InvalidWeight => "weight out of range",
+            MathError::TokenIndexOutOfBounds => "token index out of bounds",
+            MathError::BaseOutOfBounds => "base out of bounds",
+            MathError::ExponentOutOfBounds => "exponent out of bounds",
+            MathError::InvalidParams => "invalid curve parameters",
+            MathError::AssetBoundsExceeded => "asset bounds exceeded",
+            MathError::InvalidPercentage => "percentage above one",
+            MathError::DecimalsOutOfBounds => "token decimals out of bounds",
+            MathError::InvalidTimeRange => "invalid time range",
+            MathError::ConvergenceFailure => "solver did not converge",
+            MathError::MaxInRatio => "amount in exceeds max in ratio",
+            MathError::MaxOutRatio => "amount out exceeds max out ratio",
+        };
+        f.write_str(msg)
+    }
+}
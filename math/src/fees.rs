@@ -0,0 +1,208 @@
+// Symmetric‑Solana ─ Fee math
+// ================================================================
+// Swap fees and their split between liquidity providers and the
+// protocol, fee schedules, and management (AUM) fees. Fees and
+// percentages are 18‑dec fixed point (1e18 ≙ 100 %).
+// Every rounding choice favours the protocol side: fees charged are
+// rounded up, the protocol's cut of a fee is rounded up, and the LP
+// portion is whatever remains.
+// ================================================================
+use super::{fixed, MathError, U256};
+
+/// A gross fee split between liquidity providers and the protocol;
+/// `lp + protocol` always equals the gross fee.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub lp: U256,
+    pub protocol: U256,
+}
+
+/// Fee charged on `amount` at `fee_percentage`, rounded up.
+pub fn fee_amount(amount: U256, fee_percentage: U256) -> U256 {
+    expect(try_fee_amount(amount, fee_percentage))
+}
+
+pub fn try_fee_amount(amount: U256, fee_percentage: U256) -> Result<U256, MathError> {
+    check_percentage(fee_percentage)?;
+    fixed::try_mul_up(amount, fee_percentage)
+}
+
+/// `amount` less its fee (the part of an exact‑in amount that trades).
+pub fn subtract_fee(amount: U256, fee_percentage: U256) -> U256 {
+    expect(try_subtract_fee(amount, fee_percentage))
+}
+
+pub fn try_subtract_fee(amount: U256, fee_percentage: U256) -> Result<U256, MathError> {
+    fixed::try_sub(amount, try_fee_amount(amount, fee_percentage)?)
+}
+
+/// Gross amount whose remainder after the fee is `amount`, rounded up
+/// (the amount an exact‑out trade must pay in).
+pub fn add_fee(amount: U256, fee_percentage: U256) -> U256 {
+    expect(try_add_fee(amount, fee_percentage))
+}
+
+pub fn try_add_fee(amount: U256, fee_percentage: U256) -> Result<U256, MathError> {
+    check_percentage(fee_percentage)?;
+    fixed::try_div_up(amount, fixed::complement(fee_percentage))
+}
+
+/// Protocol's cut (`protocol_percentage` of `gross_fee`), rounded up.
+pub fn protocol_share(gross_fee: U256, protocol_percentage: U256) -> U256 {
+    expect(try_protocol_share(gross_fee, protocol_percentage))
+}
+
+pub fn try_protocol_share(gross_fee: U256, protocol_percentage: U256) -> Result<U256, MathError> {
+    check_percentage(protocol_percentage)?;
+    // mul_up of a percentage ≤ 1 never exceeds the fee itself
+    fixed::try_mul_up(gross_fee, protocol_percentage)
+}
+
+/// Split `gross_fee` into the protocol's share and the LPs' remainder.
+pub fn split_fee(gross_fee: U256, protocol_percentage: U256) -> FeeSplit {
+    expect(try_split_fee(gross_fee, protocol_percentage))
+}
+
+pub fn try_split_fee(gross_fee: U256, protocol_percentage: U256) -> Result<FeeSplit, MathError> {
+    let protocol = try_protocol_share(gross_fee, protocol_percentage)?;
+    Ok(FeeSplit { lp: gross_fee - protocol, protocol })
+}
+
+/// `outer` percent of `inner` percent as a percentage of the whole, e.g.
+/// the protocol's effective rate on volume from its share of the swap
+/// fee. Rounded up.
+pub fn percentage_of_percentage(inner: U256, outer: U256) -> U256 {
+    expect(try_percentage_of_percentage(inner, outer))
+}
+
+pub fn try_percentage_of_percentage(inner: U256, outer: U256) -> Result<U256, MathError> {
+    check_percentage(inner)?;
+    check_percentage(outer)?;
+    fixed::try_mul_up(inner, outer)
+}
+
+/// Seconds in the 365‑day year AUM fees are quoted over.
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Fee `now` on the linear path from `start_fee` (at `start_time`) to
+/// `end_fee` (at `end_time`), with its change rounded down.
+pub fn interpolate_fee(start_fee: U256, end_fee: U256, start_time: i64, end_time: i64, now: i64) -> U256 {
+    expect(try_interpolate_fee(start_fee, end_fee, start_time, end_time, now))
+}
+
+pub fn try_interpolate_fee(
+    start_fee: U256,
+    end_fee: U256,
+    start_time: i64,
+    end_time: i64,
+    now: i64,
+) -> Result<U256, MathError> {
+    check_percentage(start_fee)?;
+    check_percentage(end_fee)?;
+    if now <= start_time {
+        return Ok(start_fee);
+    }
+    if now >= end_time {
+        return Ok(end_fee);
+    }
+    let progress = fixed::try_div_down(
+        U256::from(now.abs_diff(start_time)),
+        U256::from(end_time.abs_diff(start_time)),
+    )?;
+    if end_fee >= start_fee {
+        fixed::try_add(start_fee, fixed::try_mul_down(end_fee - start_fee, progress)?)
+    } else {
+        fixed::try_sub(start_fee, fixed::try_mul_down(start_fee - end_fee, progress)?)
+    }
+}
+
+/// BPT to mint to a manager charging `annual_percentage` of the pool per
+/// year, for `elapsed` seconds: with f = annual_percentage · elapsed / year,
+/// the new BPT is `supply · f / (1 − f)`, so that it is worth f of the
+/// pool afterwards. Rounded down, in the LPs' favour.
+pub fn aum_fee_bpt(total_supply: U256, annual_percentage: U256, elapsed: u64) -> U256 {
+    expect(try_aum_fee_bpt(total_supply, annual_percentage, elapsed))
+}
+
+pub fn try_aum_fee_bpt(total_supply: U256, annual_percentage: U256, elapsed: u64) -> Result<U256, MathError> {
+    check_percentage(annual_percentage)?;
+    let share = annual_percentage
+        .checked_mul(U256::from(elapsed))
+        .ok_or(MathError::Overflow)?
+        / U256::from(SECONDS_PER_YEAR);
+    // a manager can take at most the whole pool
+    if share >= fixed::ONE {
+        return Err(MathError::InvalidPercentage);
+    }
+    fixed::try_div_down(fixed::try_mul_down(total_supply, share)?, fixed::complement(share))
+}
+
+fn check_percentage(percentage: U256) -> Result<(), MathError> {
+    if percentage > fixed::ONE {
+        return Err(MathError::InvalidPercentage);
+    }
+    Ok(())
+}
+
+fn expect<T>(result: Result<T, MathError>) -> T {
+    result.unwrap_or_else(|e| panic!("fees: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fixed::from_f64 as fp;
+
+    #[test]
+    fn fees_round_in_favour_of_the_protocol() {
+        // 0.3 % of 1 wei is still one wei
+        assert_eq!(fee_amount(U256::one(), fp(0.003)), U256::one());
+        assert_eq!(fee_amount(fp(1_000.0), fp(0.003)), fp(3.0));
+        assert_eq!(subtract_fee(fp(1_000.0), fp(0.003)), fp(997.0));
+        assert_eq!(add_fee(fp(997.0), fp(0.003)), fp(1_000.0));
+        assert_eq!(add_fee(U256::one(), fp(0.5)), U256::from(2u64));
+
+        let split = split_fee(U256::from(3u64), fp(0.5));
+        assert_eq!(split, FeeSplit { lp: U256::one(), protocol: U256::from(2u64) });
+        let split = split_fee(fp(3.0), fp(0.5));
+        assert_eq!(split.lp + split.protocol, fp(3.0));
+        assert_eq!(split_fee(fp(3.0), fixed::ONE), FeeSplit { lp: U256::zero(), protocol: fp(3.0) });
+        assert_eq!(split_fee(fp(3.0), U256::zero()), FeeSplit { lp: fp(3.0), protocol: U256::zero() });
+
+        // half of a 0.3 % fee is 0.15 % of volume
+        assert_eq!(percentage_of_percentage(fp(0.003), fp(0.5)), fp(0.0015));
+    }
+
+    #[test]
+    fn percentages_above_one_are_rejected() {
+        let too_much = fixed::ONE + 1;
+        assert_eq!(try_fee_amount(fp(1.0), too_much), Err(MathError::InvalidPercentage));
+        assert_eq!(try_add_fee(fp(1.0), too_much), Err(MathError::InvalidPercentage));
+        assert_eq!(try_split_fee(fp(1.0), too_much), Err(MathError::InvalidPercentage));
+        assert_eq!(try_percentage_of_percentage(too_much, fp(0.1)), Err(MathError::InvalidPercentage));
+        // a 100 % fee leaves nothing to trade and cannot be grossed up
+        assert_eq!(subtract_fee(fp(1.0), fixed::ONE), U256::zero());
+        assert_eq!(try_add_fee(fp(1.0), fixed::ONE), Err(MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn fees_interpolate_linearly() {
+        let (start, end) = (fp(0.01), fp(0.03));
+        assert_eq!(interpolate_fee(start, end, 100, 200, 50), start);
+        assert_eq!(interpolate_fee(start, end, 100, 200, 150), fp(0.02));
+        assert_eq!(interpolate_fee(end, start, 100, 200, 175), fp(0.015));
+        assert_eq!(interpolate_fee(start, end, 100, 200, 500), end);
+        assert_eq!(try_interpolate_fee(start, fixed::ONE + 1, 0, 1, 0), Err(MathError::InvalidPercentage));
+    }
+
+    #[test]
+    fn aum_fee_dilutes_by_the_pro_rata_share() {
+        // 1 % a year for a whole year: the manager ends up with 1 % of the supply
+        let minted = aum_fee_bpt(fp(990.0), fp(0.01), SECONDS_PER_YEAR);
+        assert_eq!(minted, fp(10.0));
+        // half a year of 2 % is the same
+        assert_eq!(aum_fee_bpt(fp(990.0), fp(0.02), SECONDS_PER_YEAR / 2), fp(10.0));
+        assert_eq!(aum_fee_bpt(fp(990.0), fp(0.02), 0), U256::zero());
+        assert_eq!(try_aum_fee_bpt(fp(1.0), fp(0.5), 2 * SECONDS_PER_YEAR), Err(MathError::InvalidPercentage));
+    }
+}
@@ -0,0 +1,298 @@
+// Symmetric‑Solana ─ Signed 256‑bit integers
+// ================================================================
+// Two's‑complement `I256` on top of `U256`, for math that needs signed
+// intermediates (LogExpMath, invariant derivations). Semantics follow
+// Solidity's `int256`: division truncates toward zero, the remainder
+// takes the sign of the dividend, and arithmetic panics on overflow
+// (use the `checked_*` variants to handle it).
+//
+// As a fixed‑point number, 1e18 ≙ 1.0 (see `I256::ONE`).
+// ================================================================
+use super::U256;
+use core::cmp::Ordering;
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub, SubAssign};
+
+/// Signed 256‑bit integer (two's complement).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct I256(U256);
+
+const SIGN_BIT: U256 = U256([0, 0, 0, 1 << 63]);
+
+impl I256 {
+    pub const ZERO: I256 = I256(U256([0, 0, 0, 0]));
+    /// 1.0 in 18‑dec fixed point
+    pub const ONE: I256 = I256(U256([1_000_000_000_000_000_000, 0, 0, 0]));
+    pub const MAX: I256 = I256(U256([u64::MAX, u64::MAX, u64::MAX, u64::MAX >> 1]));
+    pub const MIN: I256 = I256(SIGN_BIT);
+
+    /// Reinterpret two's‑complement bits.
+    pub const fn from_raw(bits: U256) -> Self {
+        I256(bits)
+    }
+
+    /// Two's‑complement bits.
+    pub const fn into_raw(self) -> U256 {
+        self.0
+    }
+
+    /// Non‑negative value from an unsigned one; `None` if it is ≥ 2^255.
+    pub fn from_unsigned(value: U256) -> Option<Self> {
+        if value.bit(255) { None } else { Some(I256(value)) }
+    }
+
+    /// Value with the given sign and magnitude; `None` if out of range.
+    pub fn from_sign_and_abs(negative: bool, abs: U256) -> Option<Self> {
+        if !negative {
+            Self::from_unsigned(abs)
+        } else if abs <= SIGN_BIT {
+            Some(I256(twos_negate(abs)))
+        } else {
+            None
+        }
+    }
+
+    /// The value as unsigned; `None` if negative.
+    pub fn to_unsigned(self) -> Option<U256> {
+        if self.is_negative() { None } else { Some(self.0) }
+    }
+
+    /// The value as an `i128`; `None` if out of its range.
+    pub fn to_i128(self) -> Option<i128> {
+        let abs = self.unsigned_abs();
+        if abs > U256::from(i128::MAX as u128) + U256::from(self.is_negative() as u8) {
+            return None;
+        }
+        let abs = abs.as_u128();
+        Some(if self.is_negative() { (abs as i128).wrapping_neg() } else { abs as i128 })
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0.bit(255)
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// Magnitude (fits even for `MIN`).
+    pub fn unsigned_abs(self) -> U256 {
+        if self.is_negative() { twos_negate(self.0) } else { self.0 }
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        if self == Self::MIN { None } else { Some(I256(twos_negate(self.0))) }
+    }
+
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let (sum, _) = self.0.overflowing_add(rhs.0);
+        let sum = I256(sum);
+        // overflow iff both operands share a sign the result does not
+        if self.is_negative() == rhs.is_negative() && sum.is_negative() != self.is_negative() {
+            None
+        } else {
+            Some(sum)
+        }
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let (diff, _) = self.0.overflowing_sub(rhs.0);
+        let diff = I256(diff);
+        if self.is_negative() != rhs.is_negative() && diff.is_negative() != self.is_negative() {
+            None
+        } else {
+            Some(diff)
+        }
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let abs = self.unsigned_abs().checked_mul(rhs.unsigned_abs())?;
+        Self::from_sign_and_abs(self.is_negative() != rhs.is_negative(), abs)
+    }
+
+    /// Division truncating toward zero; `None` on division by zero or `MIN / -1`.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        let abs = self.unsigned_abs() / rhs.unsigned_abs();
+        Self::from_sign_and_abs(self.is_negative() != rhs.is_negative(), abs)
+    }
+
+    /// Remainder with the sign of the dividend; `None` on division by zero.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+        let abs = self.unsigned_abs() % rhs.unsigned_abs();
+        Self::from_sign_and_abs(self.is_negative(), abs)
+    }
+
+    // ----------------------------------------------------
+    // 18‑decimal fixed‑point helpers
+    // ----------------------------------------------------
+    /// Fixed‑point multiply, truncating toward zero.
+    pub fn mul_down(self, rhs: Self) -> Self {
+        (self * rhs) / Self::ONE
+    }
+
+    /// Fixed‑point divide, truncating toward zero.
+    pub fn div_down(self, rhs: Self) -> Self {
+        (self * Self::ONE) / rhs
+    }
+
+    /// Natural logarithm of a positive fixed‑point value.
+    pub fn ln(self) -> Self {
+        super::log_exp_math::ln(self)
+    }
+
+    /// e^self for a fixed‑point value in [-41, 130].
+    pub fn exp(self) -> Self {
+        super::log_exp_math::exp(self)
+    }
+}
+
+/// Two's‑complement negation of raw bits.
+fn twos_negate(bits: U256) -> U256 {
+    (!bits).overflowing_add(U256::one()).0
+}
+
+impl From<i128> for I256 {
+    fn from(v: i128) -> Self {
+        I256::from_sign_and_abs(v < 0, U256::from(v.unsigned_abs())).expect("i128 fits")
+    }
+}
+
+impl From<i64> for I256 {
+    fn from(v: i64) -> Self {
+        I256::from(i128::from(v))
+    }
+}
+
+impl Ord for I256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.is_negative(), other.is_negative()) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            // same sign: two's complement bits order like the values
+            _ => self.0.cmp(&other.0),
+        }
+    }
+}
+
+impl PartialOrd for I256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for I256 {
+    type Output = I256;
+    fn neg(self) -> I256 {
+        self.checked_neg().expect("I256 negation overflow")
+    }
+}
+
+impl Add for I256 {
+    type Output = I256;
+    fn add(self, rhs: I256) -> I256 {
+        self.checked_add(rhs).expect("I256 addition overflow")
+    }
+}
+
+impl Sub for I256 {
+    type Output = I256;
+    fn sub(self, rhs: I256) -> I256 {
+        self.checked_sub(rhs).expect("I256 subtraction overflow")
+    }
+}
+
+impl Mul for I256 {
+    type Output = I256;
+    fn mul(self, rhs: I256) -> I256 {
+        self.checked_mul(rhs).expect("I256 multiplication overflow")
+    }
+}
+
+impl Div for I256 {
+    type Output = I256;
+    fn div(self, rhs: I256) -> I256 {
+        self.checked_div(rhs).expect("I256 division by zero or overflow")
+    }
+}
+
+impl Rem for I256 {
+    type Output = I256;
+    fn rem(self, rhs: I256) -> I256 {
+        self.checked_rem(rhs).expect("I256 remainder by zero")
+    }
+}
+
+impl AddAssign for I256 {
+    fn add_assign(&mut self, rhs: I256) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for I256 {
+    fn sub_assign(&mut self, rhs: I256) {
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic_matches_i128() {
+        let values: [i128; 7] = [0, 1, -1, 7, -7, 1_000_000_000_000_000_000, -123_456_789_012_345_678_901];
+        for &a in &values {
+            for &b in &values {
+                let (x, y) = (I256::from(a), I256::from(b));
+                assert_eq!(x + y, I256::from(a + b));
+                assert_eq!(x - y, I256::from(a - b));
+                // I256 has room where i128 overflows
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!(x * y, I256::from(product));
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                if b != 0 {
+                    // truncation toward zero, remainder signed like the dividend
+                    assert_eq!(x / y, I256::from(a / b));
+                    assert_eq!(x % y, I256::from(a % b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bounds_and_overflow() {
+        assert!(I256::MAX.checked_add(I256::from(1i64)).is_none());
+        assert!(I256::MIN.checked_sub(I256::from(1i64)).is_none());
+        assert!(I256::MIN.checked_neg().is_none());
+        assert!(I256::MIN.checked_div(I256::from(-1i64)).is_none());
+        assert!(I256::MAX.checked_mul(I256::from(2i64)).is_none());
+        assert_eq!(I256::MIN.checked_mul(I256::from(1i64)), Some(I256::MIN));
+        assert_eq!(I256::MIN.unsigned_abs(), SIGN_BIT);
+        assert!(I256::from_unsigned(SIGN_BIT).is_none());
+        assert_eq!(I256::from(-5i64).to_unsigned(), None);
+        for v in [0, -1, i128::MAX, i128::MIN, -123_456_789_012_345_678_901] {
+            assert_eq!(I256::from(v).to_i128(), Some(v));
+        }
+        assert_eq!((I256::from(i128::MAX) + I256::from(1i64)).to_i128(), None);
+        assert_eq!((I256::from(i128::MIN) - I256::from(1i64)).to_i128(), None);
+    }
+
+    #[test]
+    fn fixed_point_helpers() {
+        let half = I256::ONE / I256::from(2i64);
+        let minus_three = -(I256::ONE * I256::from(3i64));
+        assert_eq!(half.mul_down(minus_three), -(I256::ONE * I256::from(3i64) / I256::from(2i64)));
+        assert_eq!(minus_three.div_down(half), -(I256::ONE * I256::from(6i64)));
+        // e^(ln 2) ≈ 2
+        let two = I256::ONE * I256::from(2i64);
+        let round_trip = two.ln().exp();
+        assert!((round_trip - two).unsigned_abs() < U256::from(100u8));
+        assert!(half.ln().is_negative());
+    }
+}
@@ -3,19 +3,44 @@
 // Expanded Balancer‑style math helpers (Weighted Pool).
 // Includes:
 //   • Fixed‑point helpers (60.18‑dec) – unchanged API.
-//   • Deterministic integer‑based exponentiation rounding **up** (pow_up).
+//   • Deterministic integer exponentiation (LogExpMath) for pow_down/pow_up.
 //   • Join/exit & LP‑token math parity with Balancer V3.
-//   • All functions kept `no_std` compatible.
+//   • StableSwap invariant math (stable_math) on a shared Newton solver (newton).
+//   • Elliptic concentrated‑liquidity (E‑CLP) math (eclp).
+//   • Linear pool math over a main/wrapped token target range (linear_math).
+//   • Fee amounts and LP/protocol fee splits (fees).
+//   • Geometric‑mean TWAP accumulators (oracle).
+//   • Signed 256‑bit fixed point (I256) for signed intermediates.
+//   • Fallible `try_*` variants returning `MathError` instead of panicking.
+//   • Optional serde / borsh encodings of U256 (features `serde`, `borsh`).
+//   • Optional wasm-bindgen quote exports for browsers (feature `wasm`).
+//   • All functions kept `no_std` compatible (std is linked only for `wasm`).
 // ================================================================
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "wasm")), no_std)]
 #![allow(clippy::many_single_char_names)]
+#![allow(clippy::manual_div_ceil, clippy::assign_op_pattern)] // construct_uint! expansion
 
 extern crate alloc;
 
-use alloc::vec::Vec;
-use libm::pow as f64_pow; // f64 pow – used only in pow_down; see pow_up for integer path.
 use uint::construct_uint;
 
+pub mod eclp;
+#[cfg(any(feature = "serde", feature = "borsh"))]
+mod encoding;
+pub mod error;
+pub mod fees;
+pub mod i256;
+pub mod linear_math;
+pub mod log_exp_math;
+pub mod newton;
+pub mod oracle;
+pub mod stable_math;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use error::MathError;
+pub use i256::I256;
+
 construct_uint! {
     /// 256‑bit unsigned integer (little‑endian limbs).
     pub struct U256(4);
@@ -26,64 +51,341 @@ construct_uint! {
 // ------------------------------------------------------------
 #[allow(dead_code)]
 pub mod fixed {
-    use super::U256;
-    use super::f64_pow;
+    use super::{log_exp_math, MathError, U256};
 
     /// 1e18 (fixed‑point representation of 1).
     pub const ONE: U256 = U256([1_000_000_000_000_000_000u64, 0, 0, 0]);
 
+    const ONE_U128: u128 = 1_000_000_000_000_000_000;
+
+    // Amounts scaled to 18 decimals nearly always fit in 128 bits. When the
+    // intermediate product does too, native u128 arithmetic gives the same
+    // result as the 256‑bit long multiplication/division at a fraction of
+    // the compute cost; everything else falls through to `U256`.
+    #[inline] fn as_u128(x: U256) -> Option<u128> {
+        if x.0[2] == 0 && x.0[3] == 0 { Some(x.low_u128()) } else { None }
+    }
+    #[inline] fn small_product(a: U256, b: U256) -> Option<u128> {
+        as_u128(a)?.checked_mul(as_u128(b)?)
+    }
+    /// (a · 1e18, b) when both fit in u128 and b ≠ 0.
+    #[inline] fn small_quotient(a: U256, b: U256) -> Option<(u128, u128)> {
+        let b = as_u128(b).filter(|&b| b != 0)?;
+        Some((as_u128(a)?.checked_mul(ONE_U128)?, b))
+    }
+
     /// Multiply two numbers, round **down**.
-    #[inline] pub fn mul_down(a: U256, b: U256) -> U256 { (a * b) / ONE }
+    #[inline] pub fn mul_down(a: U256, b: U256) -> U256 {
+        if let Some(p) = small_product(a, b) { return U256::from(p / ONE_U128); }
+        (a * b) / ONE
+    }
     /// Multiply, round **up**.
     #[inline] pub fn mul_up(a: U256, b: U256) -> U256 {
         if a.is_zero() || b.is_zero() { return U256::zero(); }
+        if let Some(p) = small_product(a, b) { return U256::from((p - 1) / ONE_U128 + 1); }
         ((a * b) + ONE - U256::one()) / ONE
     }
     /// Divide, round **down**.
-    #[inline] pub fn div_down(a: U256, b: U256) -> U256 { (a * ONE) / b }
+    #[inline] pub fn div_down(a: U256, b: U256) -> U256 {
+        if let Some((n, d)) = small_quotient(a, b) { return U256::from(n / d); }
+        (a * ONE) / b
+    }
     /// Divide, round **up**.
     #[inline] pub fn div_up(a: U256, b: U256) -> U256 {
         if a.is_zero() { return U256::zero(); }
+        if let Some((n, d)) = small_quotient(a, b) { return U256::from((n - 1) / d + 1); }
         ((a * ONE) + b - U256::one()) / b
     }
-    /// 1 − x.
-    #[inline] pub fn complement(x: U256) -> U256 { ONE - x }
+    /// 1 − x, floored at zero (Balancer `complement`).
+    #[inline] pub fn complement(x: U256) -> U256 { ONE.saturating_sub(x) }
 
     // ----------------------------------------------------
     // Exponentiation helpers
     // ----------------------------------------------------
-    /// Deterministic integer exponentiation rounding **down** (wrapper around f64).
-    #[inline]
-    pub fn pow_down(base: U256, exp: U256) -> U256 { // keeps previous behaviour
-        if exp.is_zero() { return ONE; }
-        if base.is_zero() { return U256::zero(); }
-        from_f64(f64_pow(to_f64(base), to_f64(exp)))
-    }
-
-    /// Deterministic integer exponentiation rounding **up** (port of Balancer `powUpFixed`).
-    /// Uses binary fraction exponentiation entirely in integer domain to avoid FP rounding‑errors.
-    pub fn pow_up(mut base: U256, mut exp: U256) -> U256 {
-        // Based on binary decomposition of `exp` in [0,1] range (18‑dec fixed).
-        // 1) Convert exp to 128‑bit fractional (Q128.128) to iterate.
-        // 2) Square‑and‑multiply keeping rounding **up**.
-        if exp.is_zero() { return ONE; }
-        if base.is_zero() { return U256::zero(); }
-        // Scale exp to Q128 (shift left by 128) then iterate 128 bits.
-        let mut result = ONE; // running product
-        let mut bit = U256::from(1u128 << 127); // highest bit in 128 range
-        while bit > U256::zero() {
-            base = mul_up(base, base); // square, rounding up
-            if exp & bit != U256::zero() {
-                result = mul_up(result, base);
-            }
-            bit >>= 1;
+    /// Relative error bound of `log_exp_math::pow` (1e‑14), absorbed by
+    /// rounding the result away from the exact value.
+    pub const MAX_POW_RELATIVE_ERROR: U256 = U256([10_000, 0, 0, 0]);
+
+    /// x^y rounded **down** (Balancer `powDown`): never above the exact
+    /// value. Integer exponents 1, 2 and 4 are computed directly; other
+    /// exponents take `log_exp_math::pow` minus its error bound.
+    pub fn pow_down(base: U256, exp: U256) -> U256 {
+        if exp == ONE {
+            base
+        } else if exp == ONE * 2 {
+            mul_down(base, base)
+        } else if exp == ONE * 4 {
+            let square = mul_down(base, base);
+            mul_down(square, square)
+        } else {
+            let raw = log_exp_math::pow(base, exp);
+            let max_error = mul_up(raw, MAX_POW_RELATIVE_ERROR) + U256::one();
+            raw.saturating_sub(max_error)
+        }
+    }
+
+    /// x^y rounded **up** (Balancer `powUp`): never below the exact value.
+    /// Integer exponents 1, 2 and 4 are computed directly; other exponents
+    /// take `log_exp_math::pow` plus its error bound.
+    pub fn pow_up(base: U256, exp: U256) -> U256 {
+        if exp == ONE {
+            base
+        } else if exp == ONE * 2 {
+            mul_up(base, base)
+        } else if exp == ONE * 4 {
+            let square = mul_up(base, base);
+            mul_up(square, square)
+        } else {
+            let raw = log_exp_math::pow(base, exp);
+            raw + mul_up(raw, MAX_POW_RELATIVE_ERROR) + U256::one()
         }
-        result
     }
 
     /// Default exponentiation – **down** for swap math (matches EVM powDownFixed).
     #[inline] pub fn pow(base: U256, exp: U256) -> U256 { pow_down(base, exp) }
 
+    // ----------------------------------------------------
+    // Fallible variants
+    // ----------------------------------------------------
+    #[inline] pub fn try_add(a: U256, b: U256) -> Result<U256, MathError> {
+        a.checked_add(b).ok_or(MathError::Overflow)
+    }
+    #[inline] pub fn try_sub(a: U256, b: U256) -> Result<U256, MathError> {
+        a.checked_sub(b).ok_or(MathError::Underflow)
+    }
+    #[inline] pub fn try_mul_down(a: U256, b: U256) -> Result<U256, MathError> {
+        if let Some(p) = small_product(a, b) { return Ok(U256::from(p / ONE_U128)); }
+        Ok(a.checked_mul(b).ok_or(MathError::Overflow)? / ONE)
+    }
+    #[inline] pub fn try_mul_up(a: U256, b: U256) -> Result<U256, MathError> {
+        if a.is_zero() || b.is_zero() { return Ok(U256::zero()); }
+        if let Some(p) = small_product(a, b) { return Ok(U256::from((p - 1) / ONE_U128 + 1)); }
+        let product = a.checked_mul(b).ok_or(MathError::Overflow)?;
+        Ok((product - U256::one()) / ONE + U256::one())
+    }
+    #[inline] pub fn try_div_down(a: U256, b: U256) -> Result<U256, MathError> {
+        if b.is_zero() { return Err(MathError::DivisionByZero); }
+        if let Some((n, d)) = small_quotient(a, b) { return Ok(U256::from(n / d)); }
+        Ok(a.checked_mul(ONE).ok_or(MathError::Overflow)? / b)
+    }
+    #[inline] pub fn try_div_up(a: U256, b: U256) -> Result<U256, MathError> {
+        if b.is_zero() { return Err(MathError::DivisionByZero); }
+        if a.is_zero() { return Ok(U256::zero()); }
+        if let Some((n, d)) = small_quotient(a, b) { return Ok(U256::from((n - 1) / d + 1)); }
+        let scaled = a.checked_mul(ONE).ok_or(MathError::Overflow)?;
+        Ok((scaled - U256::one()) / b + U256::one())
+    }
+
+    /// Fallible `pow_down`.
+    pub fn try_pow_down(base: U256, exp: U256) -> Result<U256, MathError> {
+        if exp == ONE {
+            Ok(base)
+        } else if exp == ONE * 2 {
+            try_mul_down(base, base)
+        } else if exp == ONE * 4 {
+            let square = try_mul_down(base, base)?;
+            try_mul_down(square, square)
+        } else {
+            let raw = log_exp_math::try_pow(base, exp)?;
+            let max_error = try_add(try_mul_up(raw, MAX_POW_RELATIVE_ERROR)?, U256::one())?;
+            Ok(raw.saturating_sub(max_error))
+        }
+    }
+
+    /// Fallible `pow_up`.
+    pub fn try_pow_up(base: U256, exp: U256) -> Result<U256, MathError> {
+        if exp == ONE {
+            Ok(base)
+        } else if exp == ONE * 2 {
+            try_mul_up(base, base)
+        } else if exp == ONE * 4 {
+            let square = try_mul_up(base, base)?;
+            try_mul_up(square, square)
+        } else {
+            let raw = log_exp_math::try_pow(base, exp)?;
+            let max_error = try_add(try_mul_up(raw, MAX_POW_RELATIVE_ERROR)?, U256::one())?;
+            try_add(raw, max_error)
+        }
+    }
+
+    /// Fallible `pow` (rounds **down**).
+    #[inline] pub fn try_pow(base: U256, exp: U256) -> Result<U256, MathError> { try_pow_down(base, exp) }
+
+    // ----------------------------------------------------
+    // Roots
+    // ----------------------------------------------------
+    /// Largest root degree `nth_root` accepts.
+    pub const MAX_ROOT_DEGREE: u32 = 16;
+
+    /// √x rounded **down** (Babylonian method on x · 1e18).
+    pub fn sqrt(x: U256) -> U256 {
+        try_sqrt(x).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    /// Fallible `sqrt`; fails once x · 1e18 no longer fits in 256 bits.
+    pub fn try_sqrt(x: U256) -> Result<U256, MathError> {
+        let n = x.checked_mul(ONE).ok_or(MathError::Overflow)?;
+        if n.is_zero() {
+            return Ok(U256::zero());
+        }
+        // 2^⌈bits/2⌉ is above the root; the iteration then decreases
+        // monotonically until it reaches the floor
+        let mut z = U256::one() << n.bits().div_ceil(2);
+        loop {
+            let y = (z + n / z) >> 1;
+            if y >= z {
+                return Ok(z);
+            }
+            z = y;
+        }
+    }
+
+    /// x^(1/n) rounded **down** (never above the exact root), for
+    /// 1 ≤ n ≤ `MAX_ROOT_DEGREE`. Combine with `pow_down`/`pow_up` for
+    /// other rational exponents.
+    pub fn nth_root(x: U256, n: u32) -> U256 {
+        try_nth_root(x, n).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    /// Fallible `nth_root`.
+    pub fn try_nth_root(x: U256, n: u32) -> Result<U256, MathError> {
+        match n {
+            0 => return Err(MathError::ExponentOutOfBounds),
+            1 => return Ok(x),
+            2 => return try_sqrt(x),
+            n if n > MAX_ROOT_DEGREE => return Err(MathError::ExponentOutOfBounds),
+            _ => {}
+        }
+        if x.is_zero() {
+            return Ok(U256::zero());
+        }
+        // Newton from above: y ← ((n − 1)·y + x / y^(n−1)) / n, starting at a
+        // power of two no smaller than the root (1e18 < 2^60)
+        let degree = U256::from(n);
+        let bits = (x.bits() + 60 * (n as usize - 1)).div_ceil(n as usize);
+        if bits >= 255 {
+            return Err(MathError::Overflow);
+        }
+        let mut y = U256::one() << bits;
+        loop {
+            let power = try_int_pow_down(y, n - 1)?;
+            if power.is_zero() {
+                break;
+            }
+            let next = try_add(y.checked_mul(degree - 1).ok_or(MathError::Overflow)?, try_div_down(x, power)?)? / degree;
+            if next >= y {
+                break;
+            }
+            y = next;
+        }
+        // the truncated steps can settle a few wei above the root
+        while !y.is_zero() && try_int_pow_up(y, n)? > x {
+            y -= U256::one();
+        }
+        Ok(y)
+    }
+
+    /// y^n by repeated `mul_down`.
+    fn try_int_pow_down(y: U256, n: u32) -> Result<U256, MathError> {
+        (1..n).try_fold(y, |acc, _| try_mul_down(acc, y))
+    }
+
+    /// y^n by repeated `mul_up`: never below the exact power.
+    fn try_int_pow_up(y: U256, n: u32) -> Result<U256, MathError> {
+        (1..n).try_fold(y, |acc, _| try_mul_up(acc, y))
+    }
+
+    // ----------------------------------------------------
+    // Decimal scaling (raw SPL amounts ⇄ 18‑dec fixed point)
+    // ----------------------------------------------------
+    /// Most decimals a token may have to be scaled to 18‑dec.
+    pub const MAX_DECIMALS: u8 = 18;
+
+    const POWERS_OF_TEN: [u64; MAX_DECIMALS as usize + 1] = {
+        let mut powers = [1u64; MAX_DECIMALS as usize + 1];
+        let mut i = 1;
+        while i < powers.len() {
+            powers[i] = powers[i - 1] * 10;
+            i += 1;
+        }
+        powers
+    };
+
+    /// 10^(18 − decimals): the 18‑dec value of one raw unit of a token.
+    pub fn try_scaling_factor(decimals: u8) -> Result<U256, MathError> {
+        if decimals > MAX_DECIMALS {
+            return Err(MathError::DecimalsOutOfBounds);
+        }
+        Ok(U256::from(POWERS_OF_TEN[(MAX_DECIMALS - decimals) as usize]))
+    }
+
+    /// Raw `amount` of a token with `decimals` as an 18‑dec value (exact).
+    pub fn scale_up(amount: u64, decimals: u8) -> U256 {
+        try_scale_up(amount, decimals).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    pub fn try_scale_up(amount: u64, decimals: u8) -> Result<U256, MathError> {
+        // at most 2^64 · 1e18: never overflows
+        Ok(U256::from(amount) * try_scaling_factor(decimals)?)
+    }
+
+    /// 18‑dec `value` as a raw amount of a token with `decimals`, rounded
+    /// **down** (amounts paid out by the pool).
+    pub fn scale_down_rounding_down(value: U256, decimals: u8) -> u64 {
+        try_scale_down_rounding_down(value, decimals).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    pub fn try_scale_down_rounding_down(value: U256, decimals: u8) -> Result<u64, MathError> {
+        to_u64(value / try_scaling_factor(decimals)?)
+    }
+
+    /// 18‑dec `value` as a raw amount of a token with `decimals`, rounded
+    /// **up** (amounts paid into the pool).
+    pub fn scale_down_rounding_up(value: U256, decimals: u8) -> u64 {
+        try_scale_down_rounding_up(value, decimals).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    pub fn try_scale_down_rounding_up(value: U256, decimals: u8) -> Result<u64, MathError> {
+        let factor = try_scaling_factor(decimals)?;
+        if value.is_zero() {
+            return Ok(0);
+        }
+        to_u64((value - U256::one()) / factor + U256::one())
+    }
+
+    // ----------------------------------------------------
+    // Rounding policy (raw amounts held as 18‑dec values)
+    // ----------------------------------------------------
+    // Every amount leaves the math in the pool's favour: whatever the
+    // pool pays – tokens out, BPT minted, fees taken from custody – rounds
+    // down, whatever it is paid – tokens in, BPT burnt – rounds up. Pool
+    // programs convert through these rather than dividing by `ONE`.
+
+    /// An amount the pool pays, from `raw · ONE`, rounded **down**.
+    pub fn to_amount_out(value: U256) -> u64 {
+        try_to_amount_out(value).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    pub fn try_to_amount_out(value: U256) -> Result<u64, MathError> {
+        try_scale_down_rounding_down(value, 0)
+    }
+
+    /// An amount the pool is paid, from `raw · ONE`, rounded **up**.
+    pub fn to_amount_in(value: U256) -> u64 {
+        try_to_amount_in(value).unwrap_or_else(|e| panic!("fixed: {}", e))
+    }
+
+    pub fn try_to_amount_in(value: U256) -> Result<u64, MathError> {
+        try_scale_down_rounding_up(value, 0)
+    }
+
+    fn to_u64(value: U256) -> Result<u64, MathError> {
+        if value > U256::from(u64::MAX) {
+            return Err(MathError::Overflow);
+        }
+        Ok(value.as_u64())
+    }
+
     // ---------- helpers ----------
     #[inline] pub fn to_f64(x: U256) -> f64 { (x.low_u128() as f64) / 1e18 }
     #[inline] pub fn from_f64(v: f64) -> U256 {
@@ -98,23 +400,99 @@ pub mod fixed {
 // ------------------------------------------------------------
 #[allow(dead_code)]
 pub mod weighted_math {
-    use super::{fixed, U256};
+    use super::{fees, fixed, MathError, U256};
+    use fixed::{complement, try_add as add, try_sub as sub};
+    use fixed::{try_div_down as div_down, try_div_up as div_up, try_mul_down as mul_down, try_mul_up as mul_up};
     use alloc::vec::Vec;
 
+    // The plain functions panic with the `MathError` of their `try_*`
+    // counterpart, which programs should call instead.
+    #[inline]
+    fn expect<T>(result: Result<T, MathError>) -> T {
+        result.unwrap_or_else(|e| panic!("weighted_math: {}", e))
+    }
+
+    #[inline]
+    fn check_len(a: usize, b: usize) -> Result<(), MathError> {
+        if a == b { Ok(()) } else { Err(MathError::LengthMismatch) }
+    }
+
     // ---------------- Invariant
 
+    /// 0.5, the weight of both tokens in a 50/50 pool.
+    const HALF: U256 = U256([500_000_000_000_000_000, 0, 0, 0]);
+
+    /// Invariant factor b^w rounded down. Half weights take the exact
+    /// floor square root instead of the generic pow.
+    #[inline]
+    fn pow_weight_down(balance: U256, weight: U256) -> Result<U256, MathError> {
+        if weight == HALF {
+            if let Ok(root) = fixed::try_sqrt(balance) {
+                return Ok(root);
+            }
+        }
+        fixed::try_pow(balance, weight)
+    }
+
     #[inline]
     pub fn calculate_invariant(balances: &[U256], weights: &[U256]) -> U256 {
-        assert_eq!(balances.len(), weights.len());
+        expect(try_calculate_invariant(balances, weights))
+    }
+
+    pub fn try_calculate_invariant(balances: &[U256], weights: &[U256]) -> Result<U256, MathError> {
+        check_len(balances.len(), weights.len())?;
         let mut inv = fixed::ONE;
         for (b, w) in balances.iter().zip(weights) {
-            inv = fixed::mul_down(inv, fixed::pow(*b, *w));
+            inv = mul_down(inv, pow_weight_down(*b, *w)?)?;
         }
-        inv
+        Ok(inv)
     }
 
     // ---------------- Swap math (already present – kept)
 
+    /// Inputs of a two‑token swap. Named fields keep balances and weights
+    /// from being passed in the wrong order, which the positional
+    /// functions below cannot detect.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SwapParams {
+        pub balance_in: U256,
+        pub weight_in: U256,
+        pub balance_out: U256,
+        pub weight_out: U256,
+        /// Amount in for exact‑in swaps, amount out for exact‑out swaps
+        pub amount: U256,
+        pub fee: U256,
+    }
+
+    impl SwapParams {
+        /// Amount out for an exact‑in swap of `amount` (see `calc_out_given_in`).
+        pub fn out_given_in(&self) -> U256 {
+            expect(self.try_out_given_in())
+        }
+
+        pub fn try_out_given_in(&self) -> Result<U256, MathError> {
+            try_calc_out_given_in(self.balance_in, self.weight_in, self.balance_out, self.weight_out, self.amount, self.fee)
+        }
+
+        /// Amount in for an exact‑out swap of `amount` (see `calc_in_given_out`).
+        pub fn in_given_out(&self) -> U256 {
+            expect(self.try_in_given_out())
+        }
+
+        pub fn try_in_given_out(&self) -> Result<U256, MathError> {
+            try_calc_in_given_out(self.balance_in, self.weight_in, self.balance_out, self.weight_out, self.amount, self.fee)
+        }
+    }
+
+    /// Largest swap amount in, as a fraction of the balance in (30 %).
+    pub const MAX_IN_RATIO: U256 = U256([300_000_000_000_000_000, 0, 0, 0]);
+    /// Largest swap amount out, as a fraction of the balance out (30 %).
+    pub const MAX_OUT_RATIO: U256 = U256([300_000_000_000_000_000, 0, 0, 0]);
+
+    /// Exact‑in swap: B_out · (1 − (B_in / (B_in + A))^(W_in / W_out)).
+    /// The power is taken with `pow_up` (exact value plus
+    /// `fixed::MAX_POW_RELATIVE_ERROR`), so the amount out never exceeds
+    /// the exact curve's.
     pub fn calc_out_given_in(
         balance_in: U256,
         weight_in: U256,
@@ -123,14 +501,34 @@ pub mod weighted_math {
         amount_in: U256,
         swap_fee: U256,
     ) -> U256 {
-        let amount_in_after_fee = fixed::mul_down(amount_in, fixed::complement(swap_fee));
-        let new_balance_in = balance_in + amount_in_after_fee;
-        let base = fixed::div_down(balance_in, new_balance_in);
-        let exponent = fixed::div_down(weight_in, weight_out);
-        let power = fixed::pow(base, exponent);
-        fixed::mul_down(balance_out, fixed::complement(power))
+        expect(try_calc_out_given_in(balance_in, weight_in, balance_out, weight_out, amount_in, swap_fee))
+    }
+
+    pub fn try_calc_out_given_in(
+        balance_in: U256,
+        weight_in: U256,
+        balance_out: U256,
+        weight_out: U256,
+        amount_in: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
+        let amount_in_after_fee = fees::try_subtract_fee(amount_in, swap_fee)?;
+        if amount_in_after_fee > mul_down(balance_in, MAX_IN_RATIO)? {
+            return Err(MathError::MaxInRatio);
+        }
+        let new_balance_in = add(balance_in, amount_in_after_fee)?;
+        let base = div_up(balance_in, new_balance_in)?;
+        // equal weights: the exponent is exactly one
+        let power = if weight_in == weight_out {
+            base
+        } else {
+            fixed::try_pow_up(base, div_down(weight_in, weight_out)?)?
+        };
+        mul_down(balance_out, complement(power))
     }
 
+    /// Exact‑out swap: B_in · ((B_out / (B_out − A))^(W_out / W_in) − 1).
+    /// `pow_up` again keeps the amount in at or above the exact curve's.
     pub fn calc_in_given_out(
         balance_in: U256,
         weight_in: U256,
@@ -139,13 +537,325 @@ pub mod weighted_math {
         amount_out: U256,
         swap_fee: U256,
     ) -> U256 {
-        let denom = balance_out - amount_out;
-        let base = fixed::div_down(balance_out, denom);
-        let exponent = fixed::div_down(weight_out, weight_in);
-        let power = fixed::pow(base, exponent);
-        let ratio = power - fixed::ONE;
-        let without_fee = fixed::mul_down(balance_in, ratio);
-        fixed::div_up(without_fee, fixed::complement(swap_fee))
+        expect(try_calc_in_given_out(balance_in, weight_in, balance_out, weight_out, amount_out, swap_fee))
+    }
+
+    pub fn try_calc_in_given_out(
+        balance_in: U256,
+        weight_in: U256,
+        balance_out: U256,
+        weight_out: U256,
+        amount_out: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
+        if amount_out > mul_down(balance_out, MAX_OUT_RATIO)? {
+            return Err(MathError::MaxOutRatio);
+        }
+        let denom = sub(balance_out, amount_out)?;
+        let base = div_up(balance_out, denom)?;
+        let power = if weight_in == weight_out {
+            base
+        } else {
+            fixed::try_pow_up(base, div_up(weight_out, weight_in)?)?
+        };
+        let ratio = sub(power, fixed::ONE)?;
+        let without_fee = mul_up(balance_in, ratio)?;
+        fees::try_add_fee(without_fee, swap_fee)
+    }
+
+    // ---------------- Gradual weight updates
+
+    /// Weights `now` on the linear path from `start_weights` (at
+    /// `start_time`) to `end_weights` (at `end_time`). Each weight but the
+    /// last is interpolated with its change rounded down; the last takes
+    /// the remainder so the result sums to exactly one.
+    pub fn interpolate_weights(
+        start_weights: &[U256],
+        end_weights: &[U256],
+        start_time: i64,
+        end_time: i64,
+        now: i64,
+    ) -> Vec<U256> {
+        expect(try_interpolate_weights(start_weights, end_weights, start_time, end_time, now))
+    }
+
+    pub fn try_interpolate_weights(
+        start_weights: &[U256],
+        end_weights: &[U256],
+        start_time: i64,
+        end_time: i64,
+        now: i64,
+    ) -> Result<Vec<U256>, MathError> {
+        let n = start_weights.len();
+        check_len(n, end_weights.len())?;
+        check_normalized(start_weights)?;
+        check_normalized(end_weights)?;
+
+        if now <= start_time {
+            return Ok(start_weights.to_vec());
+        }
+        if now >= end_time {
+            return Ok(end_weights.to_vec());
+        }
+        // start_time < now < end_time
+        let elapsed = U256::from(now.abs_diff(start_time));
+        let progress = div_down(elapsed, U256::from(end_time.abs_diff(start_time)))?;
+
+        let mut weights = Vec::with_capacity(n);
+        let mut sum = U256::zero();
+        for i in 0..n - 1 {
+            let (start, end) = (start_weights[i], end_weights[i]);
+            let weight = if end >= start {
+                start + mul_down(end - start, progress)?
+            } else {
+                start - mul_down(start - end, progress)?
+            };
+            sum = add(sum, weight)?;
+            weights.push(weight);
+        }
+        weights.push(sub(fixed::ONE, sum)?);
+        Ok(weights)
+    }
+
+    fn check_normalized(weights: &[U256]) -> Result<(), MathError> {
+        if weights.is_empty() {
+            return Err(MathError::WeightsNotNormalized);
+        }
+        let mut sum = U256::zero();
+        for w in weights {
+            sum = add(sum, *w)?;
+        }
+        if sum == fixed::ONE { Ok(()) } else { Err(MathError::WeightsNotNormalized) }
+    }
+
+    // ---------------- Managed pools: adding and removing tokens
+
+    /// Normalizes denormalized weights to sum to one; the last weight
+    /// takes the rounding remainder.
+    pub fn normalize_weights(denormalized: &[U256]) -> Vec<U256> {
+        expect(try_normalize_weights(denormalized))
+    }
+
+    pub fn try_normalize_weights(denormalized: &[U256]) -> Result<Vec<U256>, MathError> {
+        let Some((_, head)) = denormalized.split_last() else {
+            return Err(MathError::WeightsNotNormalized);
+        };
+        let mut total = U256::zero();
+        for w in denormalized {
+            total = add(total, *w)?;
+        }
+        let mut weights = Vec::with_capacity(denormalized.len());
+        let mut sum = U256::zero();
+        for w in head {
+            let weight = div_down(*w, total)?;
+            sum = add(sum, weight)?;
+            weights.push(weight);
+        }
+        weights.push(sub(fixed::ONE, sum)?);
+        Ok(weights)
+    }
+
+    /// Weights after adding a token at `new_weight`: the existing weights
+    /// shrink by (1 − new_weight) and the new token is appended last,
+    /// taking the rounding remainder.
+    pub fn calc_weights_after_add_token(weights: &[U256], new_weight: U256) -> Vec<U256> {
+        expect(try_calc_weights_after_add_token(weights, new_weight))
+    }
+
+    pub fn try_calc_weights_after_add_token(weights: &[U256], new_weight: U256) -> Result<Vec<U256>, MathError> {
+        check_normalized(weights)?;
+        check_weight(new_weight)?;
+        let scale = complement(new_weight);
+        let mut scaled = Vec::with_capacity(weights.len() + 1);
+        let mut sum = U256::zero();
+        for w in weights {
+            let weight = mul_down(*w, scale)?;
+            sum = add(sum, weight)?;
+            scaled.push(weight);
+        }
+        scaled.push(sub(fixed::ONE, sum)?);
+        Ok(scaled)
+    }
+
+    /// Weights after removing the token at `index`: the others grow by
+    /// 1 / (1 − removed weight); the last remaining token takes the
+    /// rounding remainder.
+    pub fn calc_weights_after_remove_token(weights: &[U256], index: usize) -> Vec<U256> {
+        expect(try_calc_weights_after_remove_token(weights, index))
+    }
+
+    pub fn try_calc_weights_after_remove_token(weights: &[U256], index: usize) -> Result<Vec<U256>, MathError> {
+        check_normalized(weights)?;
+        if index >= weights.len() {
+            return Err(MathError::TokenIndexOutOfBounds);
+        }
+        let scale = complement(weights[index]);
+        if scale.is_zero() {
+            // removing the only token
+            return Err(MathError::InvalidWeight);
+        }
+        let remaining: Vec<U256> = weights
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, w)| *w)
+            .collect();
+        let mut scaled = Vec::with_capacity(remaining.len());
+        let mut sum = U256::zero();
+        for w in &remaining[..remaining.len() - 1] {
+            let weight = div_down(*w, scale)?;
+            sum = add(sum, weight)?;
+            scaled.push(weight);
+        }
+        scaled.push(sub(fixed::ONE, sum)?);
+        Ok(scaled)
+    }
+
+    /// BPT to mint when a token is added at `new_weight` with a matching
+    /// balance, keeping the BPT price unchanged:
+    /// total_bpt · w / (1 − w). Rounds down.
+    pub fn calc_bpt_out_for_add_token(total_bpt: U256, new_weight: U256) -> U256 {
+        expect(try_calc_bpt_out_for_add_token(total_bpt, new_weight))
+    }
+
+    pub fn try_calc_bpt_out_for_add_token(total_bpt: U256, new_weight: U256) -> Result<U256, MathError> {
+        check_weight(new_weight)?;
+        mul_down(total_bpt, div_down(new_weight, complement(new_weight))?)
+    }
+
+    /// BPT to burn when a token of `removed_weight` leaves with its whole
+    /// balance: total_bpt · w. Rounds up.
+    pub fn calc_bpt_in_for_remove_token(total_bpt: U256, removed_weight: U256) -> U256 {
+        expect(try_calc_bpt_in_for_remove_token(total_bpt, removed_weight))
+    }
+
+    pub fn try_calc_bpt_in_for_remove_token(total_bpt: U256, removed_weight: U256) -> Result<U256, MathError> {
+        check_weight(removed_weight)?;
+        mul_up(total_bpt, removed_weight)
+    }
+
+    fn check_weight(weight: U256) -> Result<(), MathError> {
+        if weight.is_zero() || weight >= fixed::ONE { Err(MathError::InvalidWeight) } else { Ok(()) }
+    }
+
+    // ---------------- Spot price
+
+    /// Marginal price of the out token in units of the in token, fee
+    /// included: (B_in / W_in) / (B_out / W_out) / (1 − fee). Rounds up.
+    pub fn calc_spot_price(
+        balance_in: U256,
+        weight_in: U256,
+        balance_out: U256,
+        weight_out: U256,
+        swap_fee: U256,
+    ) -> U256 {
+        expect(try_calc_spot_price(balance_in, weight_in, balance_out, weight_out, swap_fee))
+    }
+
+    pub fn try_calc_spot_price(
+        balance_in: U256,
+        weight_in: U256,
+        balance_out: U256,
+        weight_out: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
+        let price = try_calc_spot_price_no_fee(balance_in, weight_in, balance_out, weight_out)?;
+        div_up(price, complement(swap_fee))
+    }
+
+    /// `calc_spot_price` without the swap fee. Rounds up.
+    pub fn calc_spot_price_no_fee(balance_in: U256, weight_in: U256, balance_out: U256, weight_out: U256) -> U256 {
+        expect(try_calc_spot_price_no_fee(balance_in, weight_in, balance_out, weight_out))
+    }
+
+    pub fn try_calc_spot_price_no_fee(
+        balance_in: U256,
+        weight_in: U256,
+        balance_out: U256,
+        weight_out: U256,
+    ) -> Result<U256, MathError> {
+        let numer = div_up(balance_in, weight_in)?;
+        let denom = div_down(balance_out, weight_out)?;
+        div_up(numer, denom)
+    }
+
+    // ---------------- Price impact
+
+    /// Price impact of a trade: how far its effective price
+    /// (`amount_in / amount_out`) lies above `spot_price`, as an 18‑dec
+    /// fraction. Rounds up; zero when the trade beat the spot price.
+    pub fn calc_price_impact(amount_in: U256, amount_out: U256, spot_price: U256) -> U256 {
+        expect(try_calc_price_impact(amount_in, amount_out, spot_price))
+    }
+
+    pub fn try_calc_price_impact(amount_in: U256, amount_out: U256, spot_price: U256) -> Result<U256, MathError> {
+        let effective_price = div_up(amount_in, amount_out)?;
+        Ok(div_up(effective_price, spot_price)?.saturating_sub(fixed::ONE))
+    }
+
+    /// BPT a join of `amounts` would mint (or an exit would burn) at spot
+    /// prices: Σ total_bpt · w_i · a_i / b_i. Rounds down.
+    pub fn try_calc_bpt_zero_price_impact(
+        balances: &[U256],
+        weights: &[U256],
+        amounts: &[U256],
+        total_bpt: U256,
+    ) -> Result<U256, MathError> {
+        let n = balances.len();
+        check_len(n, weights.len())?;
+        check_len(n, amounts.len())?;
+        let mut bpt = U256::zero();
+        for i in 0..n {
+            let share = div_down(mul_down(amounts[i], weights[i])?, balances[i])?;
+            bpt = add(bpt, mul_down(total_bpt, share)?)?;
+        }
+        Ok(bpt)
+    }
+
+    /// Price impact of a join minting `bpt_out` for `amounts_in`:
+    /// 1 − bpt_out / bpt_at_spot. Rounds up.
+    pub fn calc_join_price_impact(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_in: &[U256],
+        total_bpt: U256,
+        bpt_out: U256,
+    ) -> U256 {
+        expect(try_calc_join_price_impact(balances, weights, amounts_in, total_bpt, bpt_out))
+    }
+
+    pub fn try_calc_join_price_impact(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_in: &[U256],
+        total_bpt: U256,
+        bpt_out: U256,
+    ) -> Result<U256, MathError> {
+        let bpt_at_spot = try_calc_bpt_zero_price_impact(balances, weights, amounts_in, total_bpt)?;
+        Ok(fixed::ONE.saturating_sub(div_down(bpt_out, bpt_at_spot)?))
+    }
+
+    /// Price impact of an exit burning `bpt_in` for `amounts_out`:
+    /// bpt_in / bpt_at_spot − 1. Rounds up.
+    pub fn calc_exit_price_impact(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_out: &[U256],
+        total_bpt: U256,
+        bpt_in: U256,
+    ) -> U256 {
+        expect(try_calc_exit_price_impact(balances, weights, amounts_out, total_bpt, bpt_in))
+    }
+
+    pub fn try_calc_exit_price_impact(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_out: &[U256],
+        total_bpt: U256,
+        bpt_in: U256,
+    ) -> Result<U256, MathError> {
+        let bpt_at_spot = try_calc_bpt_zero_price_impact(balances, weights, amounts_out, total_bpt)?;
+        Ok(div_up(bpt_in, bpt_at_spot)?.saturating_sub(fixed::ONE))
     }
 
     // ---------------- BPT math (joins / exits)
@@ -158,17 +868,27 @@ pub mod weighted_math {
         total_bpt: U256,
         swap_fee: U256,
     ) -> U256 {
+        expect(try_calc_bpt_out_given_exact_tokens_in(balances, weights, amounts_in, total_bpt, swap_fee))
+    }
+
+    pub fn try_calc_bpt_out_given_exact_tokens_in(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_in: &[U256],
+        total_bpt: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
         let n = balances.len();
-        assert_eq!(n, weights.len());
-        assert_eq!(n, amounts_in.len());
+        check_len(n, weights.len())?;
+        check_len(n, amounts_in.len())?;
 
         // --- First pass: calculate the weighted balance ratio with fee.
         let mut invariant_ratio_with_fees = U256::zero();
         let mut balance_ratios_with_fees: Vec<U256> = Vec::with_capacity(n);
         for i in 0..n {
-            let ratio = fixed::div_down(balances[i] + amounts_in[i], balances[i]);
+            let ratio = div_down(add(balances[i], amounts_in[i])?, balances[i])?;
             balance_ratios_with_fees.push(ratio);
-            invariant_ratio_with_fees += fixed::mul_down(ratio, weights[i]);
+            invariant_ratio_with_fees = add(invariant_ratio_with_fees, mul_down(ratio, weights[i])?)?;
         }
         // invariant_ratio_with_fees is a weighted arithmetic mean (already fixed‑point)
 
@@ -178,15 +898,15 @@ pub mod weighted_math {
             let mut amount_in_after_fee = amounts_in[i];
             if balance_ratios_with_fees[i] > invariant_ratio_with_fees {
                 // taxable = amounts_in[i] - balances[i] * (invariant_ratio_with_fees - 1)
-                let non_taxable = fixed::mul_down(balances[i], invariant_ratio_with_fees - fixed::ONE);
-                let taxable = amounts_in[i].saturating_sub(non_taxable);
-                amount_in_after_fee = non_taxable + fixed::mul_down(taxable, fixed::complement(swap_fee));
+                let non_taxable = mul_down(balances[i], sub(invariant_ratio_with_fees, fixed::ONE)?)?;
+                let taxable = sub(amounts_in[i], non_taxable)?;
+                amount_in_after_fee = add(non_taxable, sub(taxable, mul_up(taxable, swap_fee)?)?)?;
             }
-            let balance_ratio = fixed::div_down(balances[i] + amount_in_after_fee, balances[i]);
-            invariant_ratio = fixed::mul_down(invariant_ratio, fixed::pow(balance_ratio, weights[i]));
+            let balance_ratio = div_down(add(balances[i], amount_in_after_fee)?, balances[i])?;
+            invariant_ratio = mul_down(invariant_ratio, fixed::try_pow(balance_ratio, weights[i])?)?;
         }
-        if invariant_ratio <= fixed::ONE { return U256::zero(); }
-        fixed::mul_down(total_bpt, invariant_ratio - fixed::ONE)
+        if invariant_ratio <= fixed::ONE { return Ok(U256::zero()); }
+        mul_down(total_bpt, invariant_ratio - fixed::ONE)
     }
 
     /// Single‑token join: returns token_amount_in needed to mint `bpt_out`.
@@ -197,16 +917,25 @@ pub mod weighted_math {
         total_bpt: U256,
         swap_fee: U256,
     ) -> U256 {
+        expect(try_calc_token_in_given_exact_bpt_out(balance_in, weight_in, bpt_out, total_bpt, swap_fee))
+    }
+
+    pub fn try_calc_token_in_given_exact_bpt_out(
+        balance_in: U256,
+        weight_in: U256,
+        bpt_out: U256,
+        total_bpt: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
         // invariant_ratio = 1 + bpt_out / total_bpt
-        let invariant_ratio = fixed::div_up(total_bpt + bpt_out, total_bpt);
-        // new_balance_in = balance_in * invariant_ratio^{1/weight_in}
-        let pow = fixed::pow_up(invariant_ratio, fixed::div_down(fixed::ONE, weight_in));
-        let new_balance_in = fixed::mul_up(balance_in, pow);
-        let amount_in_without_fee = new_balance_in.saturating_sub(balance_in);
-        // fee on the taxable portion only (amount above proportional share)
-        let non_taxable = fixed::mul_up(balance_in, invariant_ratio - fixed::ONE);
-        let taxable = amount_in_without_fee.saturating_sub(non_taxable);
-        non_taxable + fixed::div_up(taxable, fixed::complement(swap_fee))
+        let invariant_ratio = div_up(add(total_bpt, bpt_out)?, total_bpt)?;
+        // balance_ratio = invariant_ratio^{1/weight_in}
+        let balance_ratio = fixed::try_pow_up(invariant_ratio, div_up(fixed::ONE, weight_in)?)?;
+        let amount_in_without_fee = mul_up(balance_in, sub(balance_ratio, fixed::ONE)?)?;
+        // the other tokens' share (1 − weight_in) of the amount is a swap and pays the fee
+        let taxable = mul_up(amount_in_without_fee, complement(weight_in))?;
+        let non_taxable = sub(amount_in_without_fee, taxable)?;
+        add(non_taxable, div_up(taxable, complement(swap_fee))?)
     }
 
     /// All‑tokens‑out exit: burns BPT and returns per‑token amounts.
@@ -216,9 +945,18 @@ pub mod weighted_math {
         total_bpt: U256,
         exit_fee: U256, // protocol exit fee (can be zero)
     ) -> Vec<U256> {
-        let bpt_to_burn = fixed::mul_up(bpt_in, fixed::complement(exit_fee));
-        let bpt_ratio = fixed::div_down(bpt_to_burn, total_bpt);
-        balances.iter().map(|b| fixed::mul_down(*b, bpt_ratio)).collect()
+        expect(try_calc_tokens_out_given_exact_bpt_in(balances, bpt_in, total_bpt, exit_fee))
+    }
+
+    pub fn try_calc_tokens_out_given_exact_bpt_in(
+        balances: &[U256],
+        bpt_in: U256,
+        total_bpt: U256,
+        exit_fee: U256,
+    ) -> Result<Vec<U256>, MathError> {
+        let bpt_to_burn = mul_up(bpt_in, complement(exit_fee))?;
+        let bpt_ratio = div_down(bpt_to_burn, total_bpt)?;
+        balances.iter().map(|b| mul_down(*b, bpt_ratio)).collect()
     }
 
     /// Single‑token out exit: exact `bpt_in` burned, returns token_amount_out.
@@ -229,15 +967,25 @@ pub mod weighted_math {
         total_bpt: U256,
         swap_fee: U256,
     ) -> U256 {
-        let invariant_ratio = fixed::complement(fixed::div_down(bpt_in, total_bpt));
-        // new_balance_out = balance_out * invariant_ratio^{1/weight_out}
-        let pow = fixed::pow_down(invariant_ratio, fixed::div_down(fixed::ONE, weight_out));
-        let new_balance_out = fixed::mul_down(balance_out, pow);
-        let amount_out_before_fee = balance_out.saturating_sub(new_balance_out);
-        // fee only on proportion that exceeds ideal exit share
-        let non_taxable = fixed::mul_down(balance_out, fixed::complement(invariant_ratio));
-        let taxable = amount_out_before_fee.saturating_sub(non_taxable);
-        non_taxable + fixed::mul_down(taxable, fixed::complement(swap_fee))
+        expect(try_calc_token_out_given_exact_bpt_in(balance_out, weight_out, bpt_in, total_bpt, swap_fee))
+    }
+
+    pub fn try_calc_token_out_given_exact_bpt_in(
+        balance_out: U256,
+        weight_out: U256,
+        bpt_in: U256,
+        total_bpt: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
+        // invariant_ratio = 1 − bpt_in / total_bpt
+        let invariant_ratio = div_up(sub(total_bpt, bpt_in)?, total_bpt)?;
+        // balance_ratio = invariant_ratio^{1/weight_out}
+        let balance_ratio = fixed::try_pow_up(invariant_ratio, div_down(fixed::ONE, weight_out)?)?;
+        let amount_out_without_fee = mul_down(balance_out, complement(balance_ratio))?;
+        // the other tokens' share (1 − weight_out) of the amount is a swap and pays the fee
+        let taxable = mul_up(amount_out_without_fee, complement(weight_out))?;
+        let non_taxable = sub(amount_out_without_fee, taxable)?;
+        add(non_taxable, mul_down(taxable, complement(swap_fee))?)
     }
 
     /// Exact tokens out: returns BPT to burn.
@@ -248,17 +996,27 @@ pub mod weighted_math {
         total_bpt: U256,
         swap_fee: U256,
     ) -> U256 {
+        expect(try_calc_bpt_in_given_exact_tokens_out(balances, weights, amounts_out, total_bpt, swap_fee))
+    }
+
+    pub fn try_calc_bpt_in_given_exact_tokens_out(
+        balances: &[U256],
+        weights: &[U256],
+        amounts_out: &[U256],
+        total_bpt: U256,
+        swap_fee: U256,
+    ) -> Result<U256, MathError> {
         let n = balances.len();
-        assert_eq!(n, weights.len());
-        assert_eq!(n, amounts_out.len());
+        check_len(n, weights.len())?;
+        check_len(n, amounts_out.len())?;
 
         // First pass: compute balance ratios without fee to get arithmetic mean.
         let mut invariant_ratio_without_fees = U256::zero();
         let mut balance_ratios_without_fees: Vec<U256> = Vec::with_capacity(n);
         for i in 0..n {
-            let ratio = fixed::div_down(balances[i] - amounts_out[i], balances[i]);
+            let ratio = div_up(sub(balances[i], amounts_out[i])?, balances[i])?;
             balance_ratios_without_fees.push(ratio);
-            invariant_ratio_without_fees += fixed::mul_down(ratio, weights[i]);
+            invariant_ratio_without_fees = add(invariant_ratio_without_fees, mul_up(ratio, weights[i])?)?;
         }
 
         // Second pass: adjust each amount by fee.
@@ -266,15 +1024,14 @@ pub mod weighted_math {
         for i in 0..n {
             let mut amount_out_with_fee = amounts_out[i];
             if balance_ratios_without_fees[i] < invariant_ratio_without_fees {
-                let non_taxable = fixed::mul_down(balances[i], fixed::complement(invariant_ratio_without_fees));
-                let taxable = amounts_out[i].saturating_sub(non_taxable);
-                amount_out_with_fee = non_taxable + fixed::div_up(taxable, fixed::complement(swap_fee));
+                let non_taxable = mul_down(balances[i], complement(invariant_ratio_without_fees))?;
+                let taxable = sub(amounts_out[i], non_taxable)?;
+                amount_out_with_fee = add(non_taxable, div_up(taxable, complement(swap_fee))?)?;
             }
-            let balance_ratio = fixed::div_down(balances[i] - amount_out_with_fee, balances[i]);
-            invariant_ratio = fixed::mul_down(invariant_ratio, fixed::pow(balance_ratio, weights[i]));
+            let balance_ratio = div_down(sub(balances[i], amount_out_with_fee)?, balances[i])?;
+            invariant_ratio = mul_down(invariant_ratio, fixed::try_pow(balance_ratio, weights[i])?)?;
         }
-        if invariant_ratio >= fixed::ONE { return U256::zero(); }
-        fixed::mul_up(total_bpt, fixed::complement(invariant_ratio))
+        mul_up(total_bpt, complement(invariant_ratio))
     }
 }
 
@@ -290,15 +1047,285 @@ mod tests {
     fn join_exit_round_trip() {
         let balances = [fp(50.0), fp(50.0)];
         let weights  = [fp(0.5), fp(0.5)];
-        let mut amounts_in = [fp(10.0), fp(0.0)];
+        let amounts_in = [fp(10.0), fp(0.0)];
         let total_bpt = fp(100.0);
         let swap_fee = fp(0.001);
 
         let bpt_out = weighted_math::calc_bpt_out_given_exact_tokens_in(&balances, &weights, &amounts_in, total_bpt, swap_fee);
         assert!(bpt_out > U256::zero());
 
-        // burn same BPT via proportional exit => should roughly match input amounts (ignoring fees).
-        let amounts_out = weighted_math::calc_tokens_out_given_exact_bpt_in(&balances, bpt_out, total_bpt + bpt_out, fp(0.0));
-        assert!(amounts_out[0] > fp(8.0));
+        // burn same BPT via proportional exit against the post‑join balances => the value handed back
+        // across both tokens should roughly match the deposit (ignoring fees), and never exceed it.
+        let balances_after = [balances[0] + amounts_in[0], balances[1] + amounts_in[1]];
+        let amounts_out = weighted_math::calc_tokens_out_given_exact_bpt_in(&balances_after, bpt_out, total_bpt + bpt_out, fp(0.0));
+        let value_out = amounts_out[0] + amounts_out[1];
+        assert!(value_out > fp(8.0));
+        assert!(value_out < fp(10.0));
+    }
+
+    #[test]
+    fn spot_price_matches_marginal_swap() {
+        let (b_in, w_in, b_out, w_out) = (fp(200.0), fp(0.8), fp(50.0), fp(0.2));
+        // (200 / 0.8) / (50 / 0.2) = 1
+        assert_eq!(weighted_math::calc_spot_price_no_fee(b_in, w_in, b_out, w_out), fp(1.0));
+        let with_fee = weighted_math::calc_spot_price(b_in, w_in, b_out, w_out, fp(0.01));
+        assert_eq!(with_fee, fixed::div_up(fp(1.0), fp(0.99)));
+
+        // a tiny trade pays about the spot price per unit out
+        let amount_in = fp(0.001);
+        let out = weighted_math::calc_out_given_in(b_in, w_in, b_out, w_out, amount_in, fp(0.01));
+        let paid = fixed::div_down(amount_in, out);
+        assert!(paid >= with_fee && paid - with_fee < fp(0.0001));
+
+        assert_eq!(
+            weighted_math::try_calc_spot_price_no_fee(b_in, w_in, U256::zero(), w_out),
+            Err(MathError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn price_impact_grows_with_trade_size() {
+        let (b, w) = (fp(100.0), fp(0.5));
+        let spot = weighted_math::calc_spot_price(b, w, b, w, fp(0.0));
+        let impact = |amount_in: U256| {
+            let out = weighted_math::calc_out_given_in(b, w, b, w, amount_in, U256::zero());
+            weighted_math::calc_price_impact(amount_in, out, spot)
+        };
+        // 50/50 constant product: impact ≈ amount_in / balance_in
+        assert!(impact(fp(1.0)) > fp(0.0099) && impact(fp(1.0)) < fp(0.0101));
+        assert!(impact(fp(10.0)) > impact(fp(1.0)));
+        assert_eq!(weighted_math::calc_price_impact(fp(1.0), fp(2.0), fp(1.0)), U256::zero());
+
+        let balances = [b, b];
+        let weights = [w, w];
+        let total_bpt = fp(100.0);
+        // proportional join and exit trade at spot
+        let proportional = [fp(10.0), fp(10.0)];
+        assert_eq!(weighted_math::calc_join_price_impact(&balances, &weights, &proportional, total_bpt, fp(10.0)), U256::zero());
+        assert_eq!(weighted_math::calc_exit_price_impact(&balances, &weights, &proportional, total_bpt, fp(10.0)), U256::zero());
+        // single‑sided join gets less BPT than at spot
+        let single = [fp(20.0), U256::zero()];
+        let bpt_out = weighted_math::calc_bpt_out_given_exact_tokens_in(&balances, &weights, &single, total_bpt, U256::zero());
+        let join_impact = weighted_math::calc_join_price_impact(&balances, &weights, &single, total_bpt, bpt_out);
+        assert!(join_impact > fp(0.04) && join_impact < fp(0.05));
+    }
+
+    #[test]
+    fn weights_interpolate_linearly_and_stay_normalized() {
+        let start = [fp(0.9), fp(0.1)];
+        let end = [fp(0.3), fp(0.7)];
+        assert_eq!(weighted_math::interpolate_weights(&start, &end, 100, 200, 50), start.to_vec());
+        assert_eq!(weighted_math::interpolate_weights(&start, &end, 100, 200, 250), end.to_vec());
+        assert_eq!(weighted_math::interpolate_weights(&start, &end, 100, 200, 150), vec![fp(0.6), fp(0.4)]);
+
+        // thirds do not divide evenly: the last token absorbs the remainder
+        let start = [fp(0.5), fp(0.3), fp(0.2)];
+        let end = [fp(0.2), fp(0.3), fp(0.5)];
+        let w = weighted_math::interpolate_weights(&start, &end, 0, 3, 1);
+        assert_eq!(w[0], fp(0.4) + U256::one());
+        assert_eq!(w[1], fp(0.3));
+        assert_eq!(w.iter().fold(U256::zero(), |a, b| a + *b), fixed::ONE);
+
+        assert_eq!(
+            weighted_math::try_interpolate_weights(&[fp(0.5), fp(0.4)], &end[..2], 0, 10, 5),
+            Err(MathError::WeightsNotNormalized)
+        );
+        assert_eq!(
+            weighted_math::try_interpolate_weights(&start, &end[..2], 0, 10, 5),
+            Err(MathError::LengthMismatch)
+        );
+    }
+
+    #[test]
+    fn adding_and_removing_a_token_keeps_weights_and_bpt_price() {
+        let weights = [fp(0.6), fp(0.4)];
+        let added = weighted_math::calc_weights_after_add_token(&weights, fp(0.2));
+        assert_eq!(added, vec![fp(0.48), fp(0.32), fp(0.2)]);
+        let removed = weighted_math::calc_weights_after_remove_token(&added, 2);
+        assert_eq!(removed, weights.to_vec());
+
+        // the new supply is total / (1 − w) after an add and total · (1 − w) after a remove
+        let total = fp(1_000.0);
+        assert_eq!(weighted_math::calc_bpt_out_for_add_token(total, fp(0.2)), fp(250.0));
+        assert_eq!(weighted_math::calc_bpt_in_for_remove_token(total + fp(250.0), fp(0.2)), fp(250.0));
+
+        assert_eq!(weighted_math::normalize_weights(&[fp(30.0), fp(10.0), fp(10.0)]), vec![fp(0.6), fp(0.2), fp(0.2)]);
+        let thirds = weighted_math::normalize_weights(&[fp(1.0), fp(1.0), fp(1.0)]);
+        assert_eq!(thirds.iter().fold(U256::zero(), |a, b| a + *b), fixed::ONE);
+
+        assert_eq!(weighted_math::try_calc_weights_after_add_token(&weights, fixed::ONE), Err(MathError::InvalidWeight));
+        assert_eq!(weighted_math::try_calc_weights_after_remove_token(&weights, 2), Err(MathError::TokenIndexOutOfBounds));
+        assert_eq!(weighted_math::try_calc_weights_after_remove_token(&[fixed::ONE], 0), Err(MathError::InvalidWeight));
+    }
+
+    #[test]
+    fn u128_fast_path_matches_wide_arithmetic() {
+        construct_uint! { struct U512(8); }
+        let wide = |v: U256| { let mut w = [0u64; 8]; w[..4].copy_from_slice(&v.0); U512(w) };
+        let narrow = |v: U512| if v.0[4..].iter().all(|&l| l == 0) { Some(U256([v.0[0], v.0[1], v.0[2], v.0[3]])) } else { None };
+        let (one, edge) = (fixed::ONE, U256::from(u128::MAX));
+        let values = [
+            U256::zero(), U256::one(), U256::from(7u64), one - 1, one, one + 1, fp(0.3), fp(123.456),
+            fp(1e12), U256::from(u64::MAX), edge / one, edge / one + 1, edge / 3, edge - 1, edge, edge + 1,
+            edge * 5,
+        ];
+        for &a in &values {
+            for &b in &values {
+                // reference: exact 512‑bit product, rounded once
+                let product = wide(a) * wide(b);
+                if narrow(product).is_some() {
+                    let down = narrow(product / wide(one)).unwrap();
+                    let up = if product.is_zero() { U256::zero() } else { narrow((product - 1) / wide(one) + 1).unwrap() };
+                    assert_eq!(fixed::mul_down(a, b), down, "mul_down {} {}", a, b);
+                    assert_eq!(fixed::mul_up(a, b), up, "mul_up {} {}", a, b);
+                    assert_eq!(fixed::try_mul_down(a, b), Ok(down));
+                    assert_eq!(fixed::try_mul_up(a, b), Ok(up));
+                }
+                let scaled = wide(a) * wide(one);
+                if narrow(scaled).is_some() && !b.is_zero() {
+                    let down = narrow(scaled / wide(b)).unwrap();
+                    let up = if scaled.is_zero() { U256::zero() } else { narrow((scaled - 1) / wide(b) + 1).unwrap() };
+                    assert_eq!(fixed::div_down(a, b), down, "div_down {} {}", a, b);
+                    assert_eq!(fixed::div_up(a, b), up, "div_up {} {}", a, b);
+                    assert_eq!(fixed::try_div_down(a, b), Ok(down));
+                    assert_eq!(fixed::try_div_up(a, b), Ok(up));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn roots_of_exact_powers() {
+        assert_eq!(fixed::sqrt(fp(4.0)), fp(2.0));
+        assert_eq!(fixed::sqrt(fp(2.0)), U256::from(1_414_213_562_373_095_048u64));
+        assert_eq!(fixed::sqrt(U256::zero()), U256::zero());
+        assert_eq!(fixed::sqrt(U256::one()), U256::from(1_000_000_000u64));
+        assert_eq!(fixed::nth_root(fp(27.0), 3), fp(3.0));
+        assert_eq!(fixed::nth_root(fp(1024.0), 10), fp(2.0));
+        assert_eq!(fixed::nth_root(fp(0.0625), 4), fp(0.5));
+        assert_eq!(fixed::nth_root(fp(7.0), 1), fp(7.0));
+        assert_eq!(fixed::try_nth_root(fp(2.0), 0), Err(MathError::ExponentOutOfBounds));
+        assert_eq!(fixed::try_nth_root(fp(2.0), fixed::MAX_ROOT_DEGREE + 1), Err(MathError::ExponentOutOfBounds));
+        assert_eq!(fixed::try_sqrt(U256::MAX), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn equal_weight_pools_skip_the_generic_pow() {
+        let (b_in, b_out, w) = (fp(1_000.0), fp(4_000.0), fp(0.5));
+        let amount = fp(10.0);
+        // x·y = k: out = B_out · A / (B_in + A), rounded in the pool's favour
+        let out = weighted_math::calc_out_given_in(b_in, w, b_out, w, amount, U256::zero());
+        let closed_form = fixed::mul_down(b_out, fixed::ONE - fixed::div_up(b_in, b_in + amount));
+        assert_eq!(out, closed_form);
+        assert!(out <= b_out * amount / (b_in + amount));
+        let back_in = weighted_math::calc_in_given_out(b_in, w, b_out, w, out, U256::zero());
+        assert!(back_in >= fixed::mul_down(amount, fp(0.999_999_999_999)) && back_in <= amount);
+        // other equal weights take the same path
+        let w3 = fp(0.3);
+        assert_eq!(weighted_math::calc_out_given_in(b_in, w3, b_out, w3, amount, U256::zero()), out);
+
+        // sqrt(1000) · sqrt(4000) = 2000
+        let inv = weighted_math::calculate_invariant(&[b_in, b_out], &[w, w]);
+        assert!(inv <= fp(2_000.0) && fp(2_000.0) - inv < U256::from(1_000u64));
+        let generic = weighted_math::calculate_invariant(&[b_in, b_out], &[w + 1, w - 1]);
+        assert!(inv > generic && inv - generic < fp(0.000_001));
+    }
+
+    #[test]
+    fn decimal_scaling_round_trips_for_every_common_precision() {
+        let amounts = [0u64, 1, 2, 9, 10, 999, 1_000_000, 123_456_789, u64::MAX / 10, u64::MAX - 1, u64::MAX];
+        for decimals in 0..=12u8 {
+            let factor = U256::from(10u64).pow(U256::from(18 - decimals));
+            assert_eq!(fixed::try_scaling_factor(decimals), Ok(factor));
+            for &amount in &amounts {
+                let scaled = fixed::scale_up(amount, decimals);
+                assert_eq!(scaled, U256::from(amount) * factor);
+                assert_eq!(fixed::scale_down_rounding_down(scaled, decimals), amount);
+                assert_eq!(fixed::scale_down_rounding_up(scaled, decimals), amount);
+                if amount < u64::MAX {
+                    // anything strictly between two raw units rounds to the neighbours
+                    for dust in [U256::one(), factor / 2, factor - 1] {
+                        assert_eq!(fixed::scale_down_rounding_down(scaled + dust, decimals), amount);
+                        assert_eq!(fixed::scale_down_rounding_up(scaled + dust, decimals), amount + 1);
+                    }
+                }
+            }
+            let past_max = fixed::scale_up(u64::MAX, decimals) + factor;
+            assert_eq!(fixed::try_scale_down_rounding_down(past_max, decimals), Err(MathError::Overflow));
+            assert_eq!(fixed::try_scale_down_rounding_up(past_max - factor + 1, decimals), Err(MathError::Overflow));
+        }
+        // 18 decimals are already fixed point
+        assert_eq!(fixed::scale_up(5, 18), U256::from(5u64));
+        assert_eq!(fixed::try_scale_up(1, 19), Err(MathError::DecimalsOutOfBounds));
+        assert_eq!(fixed::try_scale_down_rounding_up(U256::one(), 19), Err(MathError::DecimalsOutOfBounds));
+    }
+
+    #[test]
+    fn amounts_out_round_down_and_amounts_in_round_up() {
+        for amount in [0u64, 1, 7, 1_000_000, u64::MAX - 1] {
+            let exact = U256::from(amount) * fixed::ONE;
+            assert_eq!((fixed::to_amount_out(exact), fixed::to_amount_in(exact)), (amount, amount));
+            for dust in [U256::one(), fixed::ONE / 2, fixed::ONE - 1] {
+                assert_eq!(fixed::to_amount_out(exact + dust), amount);
+                assert_eq!(fixed::to_amount_in(exact + dust), amount + 1);
+            }
+        }
+        let past_max = U256::from(u64::MAX) * fixed::ONE + 1;
+        assert_eq!(fixed::try_to_amount_out(past_max), Ok(u64::MAX));
+        assert_eq!(fixed::try_to_amount_in(past_max), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn swap_params_price_like_the_positional_functions() {
+        let params = weighted_math::SwapParams {
+            balance_in: fp(1_000.0),
+            weight_in: fp(0.8),
+            balance_out: fp(2_500.0),
+            weight_out: fp(0.2),
+            amount: fp(10.0),
+            fee: fp(0.003),
+        };
+        let out = params.out_given_in();
+        assert_eq!(out, weighted_math::calc_out_given_in(fp(1_000.0), fp(0.8), fp(2_500.0), fp(0.2), fp(10.0), fp(0.003)));
+        // 2500 · (1 − (1000 / 1009.97)^4) ≈ 97.25
+        assert!(out > fp(97.0) && out < fp(97.5), "{}", out);
+        let exact_out = weighted_math::SwapParams { amount: out, ..params };
+        assert_eq!(
+            exact_out.in_given_out(),
+            weighted_math::calc_in_given_out(fp(1_000.0), fp(0.8), fp(2_500.0), fp(0.2), out, fp(0.003))
+        );
+
+        // the transposition weighted_pool used to make (weights where
+        // balances belong) type‑checks but cannot price the swap
+        let transposed = weighted_math::try_calc_out_given_in(fp(1_000.0), fp(2_500.0), fp(0.8), fp(0.2), fp(10.0), fp(0.003));
+        assert_ne!(transposed, Ok(out));
+    }
+
+    #[test]
+    fn try_variants_report_errors() {
+        let (b, w) = (fp(100.0), fp(0.5));
+        assert_eq!(
+            weighted_math::try_calc_out_given_in(b, w, b, w, fp(10.0), fp(0.01)),
+            Ok(weighted_math::calc_out_given_in(b, w, b, w, fp(10.0), fp(0.01)))
+        );
+        // fee above 100 %
+        assert_eq!(weighted_math::try_calc_out_given_in(b, w, b, w, fp(10.0), fp(2.0)), Err(MathError::InvalidPercentage));
+        // more out than the pool holds
+        assert_eq!(weighted_math::try_calc_in_given_out(b, w, b, w, fp(200.0), U256::zero()), Err(MathError::MaxOutRatio));
+        // swaps are capped at 30 % of the balances
+        assert!(weighted_math::try_calc_out_given_in(b, w, b, w, fp(30.0), U256::zero()).is_ok());
+        assert_eq!(weighted_math::try_calc_out_given_in(b, w, b, w, fp(30.0) + 1, U256::zero()), Err(MathError::MaxInRatio));
+        // the cap applies to the amount after the fee
+        assert!(weighted_math::try_calc_out_given_in(b, w, b, w, fp(30.3), fp(0.01)).is_ok());
+        assert!(weighted_math::try_calc_in_given_out(b, w, b, w, fp(30.0), U256::zero()).is_ok());
+        assert_eq!(weighted_math::try_calc_in_given_out(b, w, b, w, fp(30.0) + 1, U256::zero()), Err(MathError::MaxOutRatio));
+        assert_eq!(weighted_math::try_calc_out_given_in(b, w, b, U256::zero(), fp(10.0), U256::zero()), Err(MathError::DivisionByZero));
+        assert_eq!(weighted_math::try_calculate_invariant(&[b], &[w, w]), Err(MathError::LengthMismatch));
+        assert_eq!(
+            weighted_math::try_calc_bpt_out_given_exact_tokens_in(&[U256::MAX], &[fixed::ONE], &[fp(1.0)], fp(1.0), U256::zero()),
+            Err(MathError::Overflow)
+        );
+        assert_eq!(fixed::try_mul_up(fp(1.5), fp(2.5)), Ok(fixed::mul_up(fp(1.5), fp(2.5))));
+        assert_eq!(fixed::try_div_up(fp(1.0), U256::from(3u8)), Ok(fixed::div_up(fp(1.0), U256::from(3u8))));
     }
 }
@@ -0,0 +1,384 @@
+// Symmetric‑Solana ─ LinearMath
+// ================================================================
+// Linear pools (Balancer V2 `LinearMath`) trade a main token, a wrapped
+// yield‑bearing version of it and the pool's own BPT at a fixed 1:1,
+// the wrapped token at its rate. There is no price curve: fees keep the
+// main balance inside a target range instead. A swap pushing the main
+// balance outside [lower_target, upper_target] pays `fee` on the part
+// outside; a swap bringing it back in earns those fees back.
+//
+// The "nominal" main balance is the real one less the fees so owed, and
+// the invariant is the nominal main balance plus the wrapped balance.
+// Everything is 18‑dec fixed point, wrapped amounts already multiplied
+// by their rate; BPT is priced against the virtual supply.
+// ================================================================
+use super::{fixed, MathError, U256};
+use fixed::{try_add as add, try_sub as sub};
+use fixed::{try_div_down as div_down, try_mul_down as mul_down};
+
+/// Position of the main token in a linear pool
+pub const MAIN_INDEX: usize = 0;
+/// Position of the wrapped token in a linear pool
+pub const WRAPPED_INDEX: usize = 1;
+/// Position of the pool's own BPT in a linear pool
+pub const BPT_INDEX: usize = 2;
+
+/// Fee and target range, all 18‑dec fixed point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Params {
+    /// Fee on the main balance moved outside the target range
+    pub fee: U256,
+    pub lower_target: U256,
+    pub upper_target: U256,
+}
+
+impl Params {
+    pub fn validate(&self) -> Result<(), MathError> {
+        if self.fee >= fixed::ONE {
+            return Err(MathError::InvalidPercentage);
+        }
+        if self.lower_target > self.upper_target {
+            return Err(MathError::InvalidParams);
+        }
+        Ok(())
+    }
+}
+
+/// Balances a linear pool swap is priced against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Balances {
+    pub main: U256,
+    /// At the wrapped token's rate
+    pub wrapped: U256,
+    /// BPT in circulation
+    pub virtual_supply: U256,
+}
+
+// The plain functions panic with the `MathError` of their `try_*`
+// counterpart, which programs should call instead.
+#[inline]
+fn expect<T>(result: Result<T, MathError>) -> T {
+    result.unwrap_or_else(|e| panic!("LinearMath: {}", e))
+}
+
+/// Exact‑in swap between any two of main (0), wrapped (1) and BPT (2).
+pub fn calc_out_given_in(params: &Params, balances: &Balances, index_in: usize, index_out: usize, amount_in: U256) -> U256 {
+    expect(try_calc_out_given_in(params, balances, index_in, index_out, amount_in))
+}
+
+pub fn try_calc_out_given_in(
+    params: &Params,
+    balances: &Balances,
+    index_in: usize,
+    index_out: usize,
+    amount_in: U256,
+) -> Result<U256, MathError> {
+    let Balances { main, wrapped, virtual_supply: supply } = *balances;
+    match (index_in, index_out) {
+        (MAIN_INDEX, WRAPPED_INDEX) => try_calc_wrapped_out_per_main_in(amount_in, main, params),
+        (MAIN_INDEX, BPT_INDEX)     => try_calc_bpt_out_per_main_in(amount_in, main, wrapped, supply, params),
+        (WRAPPED_INDEX, MAIN_INDEX) => try_calc_main_out_per_wrapped_in(amount_in, main, params),
+        (WRAPPED_INDEX, BPT_INDEX)  => try_calc_bpt_out_per_wrapped_in(amount_in, main, wrapped, supply, params),
+        (BPT_INDEX, MAIN_INDEX)     => try_calc_main_out_per_bpt_in(amount_in, main, wrapped, supply, params),
+        (BPT_INDEX, WRAPPED_INDEX)  => try_calc_wrapped_out_per_bpt_in(amount_in, main, wrapped, supply, params),
+        _ => Err(MathError::TokenIndexOutOfBounds),
+    }
+}
+
+/// Exact‑out swap; see `calc_out_given_in`.
+pub fn calc_in_given_out(params: &Params, balances: &Balances, index_in: usize, index_out: usize, amount_out: U256) -> U256 {
+    expect(try_calc_in_given_out(params, balances, index_in, index_out, amount_out))
+}
+
+pub fn try_calc_in_given_out(
+    params: &Params,
+    balances: &Balances,
+    index_in: usize,
+    index_out: usize,
+    amount_out: U256,
+) -> Result<U256, MathError> {
+    let Balances { main, wrapped, virtual_supply: supply } = *balances;
+    match (index_in, index_out) {
+        (MAIN_INDEX, WRAPPED_INDEX) => try_calc_main_in_per_wrapped_out(amount_out, main, params),
+        (MAIN_INDEX, BPT_INDEX)     => try_calc_main_in_per_bpt_out(amount_out, main, wrapped, supply, params),
+        (WRAPPED_INDEX, MAIN_INDEX) => try_calc_wrapped_in_per_main_out(amount_out, main, params),
+        (WRAPPED_INDEX, BPT_INDEX)  => try_calc_wrapped_in_per_bpt_out(amount_out, main, wrapped, supply, params),
+        (BPT_INDEX, MAIN_INDEX)     => try_calc_bpt_in_per_main_out(amount_out, main, wrapped, supply, params),
+        (BPT_INDEX, WRAPPED_INDEX)  => try_calc_bpt_in_per_wrapped_out(amount_out, main, wrapped, supply, params),
+        _ => Err(MathError::TokenIndexOutOfBounds),
+    }
+}
+
+// ------------------------------------------------------------
+// Main ↔ BPT
+// ------------------------------------------------------------
+pub fn try_calc_bpt_out_per_main_in(
+    main_in: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    // the first BPT are worth the nominal main value put in
+    if supply.is_zero() {
+        return try_to_nominal(main_in, params);
+    }
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let after_nominal    = try_to_nominal(add(main_balance, main_in)?, params)?;
+    let delta_nominal    = sub(after_nominal, previous_nominal)?;
+    let invariant        = add(previous_nominal, wrapped_balance)?;
+    int_div_down(int_mul(supply, delta_nominal)?, invariant)
+}
+
+pub fn try_calc_bpt_in_per_main_out(
+    main_out: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let after_nominal    = try_to_nominal(sub(main_balance, main_out)?, params)?;
+    let delta_nominal    = sub(previous_nominal, after_nominal)?;
+    let invariant        = add(previous_nominal, wrapped_balance)?;
+    int_div_up(int_mul(supply, delta_nominal)?, invariant)
+}
+
+pub fn try_calc_main_in_per_bpt_out(
+    bpt_out: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    if supply.is_zero() {
+        return try_from_nominal(bpt_out, params);
+    }
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let invariant        = add(previous_nominal, wrapped_balance)?;
+    let delta_nominal    = int_div_up(int_mul(invariant, bpt_out)?, supply)?;
+    let new_main         = try_from_nominal(add(previous_nominal, delta_nominal)?, params)?;
+    sub(new_main, main_balance)
+}
+
+pub fn try_calc_main_out_per_bpt_in(
+    bpt_in: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let invariant        = add(previous_nominal, wrapped_balance)?;
+    let delta_nominal    = int_div_down(int_mul(invariant, bpt_in)?, supply)?;
+    let new_main         = try_from_nominal(sub(previous_nominal, delta_nominal)?, params)?;
+    sub(main_balance, new_main)
+}
+
+// ------------------------------------------------------------
+// Main ↔ wrapped
+// ------------------------------------------------------------
+pub fn try_calc_wrapped_out_per_main_in(main_in: U256, main_balance: U256, params: &Params) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let after_nominal    = try_to_nominal(add(main_balance, main_in)?, params)?;
+    sub(after_nominal, previous_nominal)
+}
+
+pub fn try_calc_wrapped_in_per_main_out(main_out: U256, main_balance: U256, params: &Params) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let after_nominal    = try_to_nominal(sub(main_balance, main_out)?, params)?;
+    sub(previous_nominal, after_nominal)
+}
+
+pub fn try_calc_main_in_per_wrapped_out(wrapped_out: U256, main_balance: U256, params: &Params) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let new_main         = try_from_nominal(add(previous_nominal, wrapped_out)?, params)?;
+    sub(new_main, main_balance)
+}
+
+pub fn try_calc_main_out_per_wrapped_in(wrapped_in: U256, main_balance: U256, params: &Params) -> Result<U256, MathError> {
+    let previous_nominal = try_to_nominal(main_balance, params)?;
+    let new_main         = try_from_nominal(sub(previous_nominal, wrapped_in)?, params)?;
+    sub(main_balance, new_main)
+}
+
+// ------------------------------------------------------------
+// Wrapped ↔ BPT
+// ------------------------------------------------------------
+pub fn try_calc_bpt_out_per_wrapped_in(
+    wrapped_in: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    if supply.is_zero() {
+        return Ok(wrapped_in);
+    }
+    let nominal_main  = try_to_nominal(main_balance, params)?;
+    let invariant     = add(nominal_main, wrapped_balance)?;
+    let new_invariant = add(invariant, wrapped_in)?;
+    let new_supply    = int_div_down(int_mul(supply, new_invariant)?, invariant)?;
+    sub(new_supply, supply)
+}
+
+pub fn try_calc_bpt_in_per_wrapped_out(
+    wrapped_out: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    let nominal_main  = try_to_nominal(main_balance, params)?;
+    let invariant     = add(nominal_main, wrapped_balance)?;
+    let new_invariant = add(nominal_main, sub(wrapped_balance, wrapped_out)?)?;
+    let new_supply    = int_div_down(int_mul(supply, new_invariant)?, invariant)?;
+    sub(supply, new_supply)
+}
+
+pub fn try_calc_wrapped_in_per_bpt_out(
+    bpt_out: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    if supply.is_zero() {
+        return Ok(bpt_out);
+    }
+    let nominal_main = try_to_nominal(main_balance, params)?;
+    let invariant    = add(nominal_main, wrapped_balance)?;
+    let new_supply   = add(supply, bpt_out)?;
+    let new_wrapped  = sub(int_div_up(int_mul(new_supply, invariant)?, supply)?, nominal_main)?;
+    sub(new_wrapped, wrapped_balance)
+}
+
+pub fn try_calc_wrapped_out_per_bpt_in(
+    bpt_in: U256,
+    main_balance: U256,
+    wrapped_balance: U256,
+    supply: U256,
+    params: &Params,
+) -> Result<U256, MathError> {
+    let nominal_main = try_to_nominal(main_balance, params)?;
+    let invariant    = add(nominal_main, wrapped_balance)?;
+    let new_supply   = sub(supply, bpt_in)?;
+    let new_wrapped  = sub(int_div_up(int_mul(new_supply, invariant)?, supply)?, nominal_main)?;
+    sub(wrapped_balance, new_wrapped)
+}
+
+// ------------------------------------------------------------
+// Nominal balances
+// ------------------------------------------------------------
+/// Real main balance less the fees owed for its distance outside the
+/// target range (rounded down).
+pub fn try_to_nominal(real: U256, params: &Params) -> Result<U256, MathError> {
+    if real < params.lower_target {
+        let fees = mul_down(sub(params.lower_target, real)?, params.fee)?;
+        sub(real, fees)
+    } else if real <= params.upper_target {
+        Ok(real)
+    } else {
+        let fees = mul_down(sub(real, params.upper_target)?, params.fee)?;
+        sub(real, fees)
+    }
+}
+
+/// Real main balance with the nominal value `nominal`; inverse of `try_to_nominal`.
+pub fn try_from_nominal(nominal: U256, params: &Params) -> Result<U256, MathError> {
+    if nominal < params.lower_target {
+        div_down(add(nominal, mul_down(params.fee, params.lower_target)?)?, add(fixed::ONE, params.fee)?)
+    } else if nominal <= params.upper_target {
+        Ok(nominal)
+    } else {
+        div_down(sub(nominal, mul_down(params.fee, params.upper_target)?)?, sub(fixed::ONE, params.fee)?)
+    }
+}
+
+#[inline]
+fn int_mul(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+#[inline]
+fn int_div_down(a: U256, b: U256) -> Result<U256, MathError> {
+    if b.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    Ok(a / b)
+}
+
+#[inline]
+fn int_div_up(a: U256, b: U256) -> Result<U256, MathError> {
+    if b.is_zero() {
+        return Err(MathError::DivisionByZero);
+    }
+    if a.is_zero() {
+        return Ok(U256::zero());
+    }
+    Ok((a - U256::one()) / b + U256::one())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(v: u64) -> U256 {
+        U256::from(v) * fixed::ONE
+    }
+
+    /// 1% fee, targets 1000..2000
+    fn params() -> Params {
+        Params { fee: fixed::ONE / 100, lower_target: fp(1_000), upper_target: fp(2_000) }
+    }
+
+    #[test]
+    fn trades_one_to_one_inside_the_target_range() {
+        let balances = Balances { main: fp(1_500), wrapped: fp(1_000), virtual_supply: fp(2_500) };
+        assert_eq!(try_calc_out_given_in(&params(), &balances, MAIN_INDEX, WRAPPED_INDEX, fp(400)).unwrap(), fp(400));
+        assert_eq!(try_calc_in_given_out(&params(), &balances, WRAPPED_INDEX, MAIN_INDEX, fp(400)).unwrap(), fp(400));
+        // BPT is worth the invariant over the supply, here exactly one
+        assert_eq!(try_calc_out_given_in(&params(), &balances, MAIN_INDEX, BPT_INDEX, fp(100)).unwrap(), fp(100));
+        assert_eq!(try_calc_out_given_in(&params(), &balances, BPT_INDEX, WRAPPED_INDEX, fp(100)).unwrap(), fp(100));
+    }
+
+    #[test]
+    fn charges_the_fee_outside_the_range_and_refunds_it_back_in() {
+        let p = params();
+        // main 1100 → 900: the last 100 below the lower target cost 1% more
+        let wrapped = try_calc_wrapped_in_per_main_out(fp(200), fp(1_100), &p).unwrap();
+        assert_eq!(wrapped, fp(201));
+        let out = try_calc_main_out_per_wrapped_in(fp(199), fp(1_100), &p).unwrap();
+        assert!(out < fp(199) && out > fp(198), "{}", out);
+
+        // topping the main balance back up to 1100 pays that fee back
+        let wrapped_out = try_calc_wrapped_out_per_main_in(fp(200), fp(900), &p).unwrap();
+        assert_eq!(wrapped_out, fp(201));
+
+        // above the upper target adding main earns less
+        let wrapped_out = try_calc_wrapped_out_per_main_in(fp(200), fp(2_000), &p).unwrap();
+        assert_eq!(wrapped_out, fp(198));
+    }
+
+    #[test]
+    fn nominal_round_trip() {
+        let p = params();
+        for real in [fp(500), fp(1_000), fp(1_700), fp(2_000), fp(5_000)] {
+            let back = try_from_nominal(try_to_nominal(real, &p).unwrap(), &p).unwrap();
+            assert!(real - back <= U256::one(), "{} {}", real, back);
+        }
+        assert_eq!(Params { fee: fixed::ONE, ..p }.validate(), Err(MathError::InvalidPercentage));
+        assert_eq!(Params { lower_target: fp(3_000), ..p }.validate(), Err(MathError::InvalidParams));
+    }
+
+    #[test]
+    fn first_bpt_follow_the_nominal_value() {
+        let empty = Balances { main: U256::zero(), wrapped: U256::zero(), virtual_supply: U256::zero() };
+        // below the lower target the first deposit pays the fee too
+        let bpt = try_calc_out_given_in(&params(), &empty, MAIN_INDEX, BPT_INDEX, fp(500)).unwrap();
+        assert_eq!(bpt, fp(495));
+        let bpt = try_calc_out_given_in(&params(), &empty, WRAPPED_INDEX, BPT_INDEX, fp(7)).unwrap();
+        assert_eq!(bpt, fp(7));
+        assert!(try_calc_out_given_in(&params(), &empty, MAIN_INDEX, MAIN_INDEX, fp(1)).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+// Symmetric‑Solana ─ LogExpMath
+// ================================================================
+// Port of Balancer's `LogExpMath`: fixed‑point natural exponentiation
+// and logarithm entirely in integers, so results are bit‑for‑bit
+// deterministic on every validator.
+//   • Inputs/outputs are 18‑dec fixed point; internals run at 20
+//     decimals (36 for `ln` close to 1).
+//   • Signed values use `I256`, whose division truncates toward zero
+//     like Solidity's, so results match Balancer bit for bit.
+// ================================================================
+use super::{MathError, I256, U256};
+
+const fn from_u128(v: u128) -> U256 {
+    U256([v as u64, (v >> 64) as u64, 0, 0])
+}
+
+const ONE_18: U256 = from_u128(1_000_000_000_000_000_000);
+const ONE_20: U256 = from_u128(100_000_000_000_000_000_000);
+const ONE_36: U256 = from_u128(1_000_000_000_000_000_000_000_000_000_000_000_000);
+
+/// Largest natural exponent accepted by `exp` (130.0)
+pub const MAX_NATURAL_EXPONENT: U256 = from_u128(130_000_000_000_000_000_000);
+/// Magnitude of the most negative natural exponent accepted by `exp` (-41.0)
+pub const MIN_NATURAL_EXPONENT_ABS: U256 = from_u128(41_000_000_000_000_000_000);
+
+/// `ln` switches to the 36‑decimal series inside (0.9, 1.1)
+const LN_36_LOWER_BOUND: U256 = from_u128(900_000_000_000_000_000);
+const LN_36_UPPER_BOUND: U256 = from_u128(1_100_000_000_000_000_000);
+
+/// 2^254 / 1e20: keeps `ln(x) * y` within 256 bits
+const MILD_EXPONENT_BOUND: U256 =
+    U256([0x4181_ea80_59f7_6532, 0xa88f_4bb1_ca6b_cf58, 0x0bce_5086_4921_11ae, 0]);
+
+// x_n and a_n = e^(x_n). x0, x1 are 18‑dec; a0, a1 have no decimals.
+const X0: U256 = from_u128(128_000_000_000_000_000_000);
+/// 38877084059945950922200000000000000000000000000000000000
+const A0: U256 = U256([0x0262_8270_0000_0000, 0xf53a_2717_2fa9_ec63, 0x0195_e54c_5dd4_2177, 0]);
+const X1: U256 = from_u128(64_000_000_000_000_000_000);
+const A1: U256 = from_u128(6_235_149_080_811_616_882_910_000_000);
+
+// 20‑dec x_n and a_n from here on
+const X2: U256 = from_u128(3_200_000_000_000_000_000_000);
+const A2: U256 = from_u128(7_896_296_018_268_069_516_100_000_000_000_000);
+const X3: U256 = from_u128(1_600_000_000_000_000_000_000);
+const A3: U256 = from_u128(888_611_052_050_787_263_676_000_000);
+const X4: U256 = from_u128(800_000_000_000_000_000_000);
+const A4: U256 = from_u128(298_095_798_704_172_827_474_000);
+const X5: U256 = from_u128(400_000_000_000_000_000_000);
+const A5: U256 = from_u128(5_459_815_003_314_423_907_810);
+const X6: U256 = from_u128(200_000_000_000_000_000_000);
+const A6: U256 = from_u128(738_905_609_893_065_022_723);
+const X7: U256 = from_u128(100_000_000_000_000_000_000);
+const A7: U256 = from_u128(271_828_182_845_904_523_536);
+const X8: U256 = from_u128(50_000_000_000_000_000_000);
+const A8: U256 = from_u128(164_872_127_070_012_814_685);
+const X9: U256 = from_u128(25_000_000_000_000_000_000);
+const A9: U256 = from_u128(128_402_541_668_774_148_407);
+const X10: U256 = from_u128(12_500_000_000_000_000_000);
+const A10: U256 = from_u128(113_314_845_306_682_631_683);
+const X11: U256 = from_u128(6_250_000_000_000_000_000);
+const A11: U256 = from_u128(106_449_445_891_785_942_956);
+
+/// x^y for 18‑dec `x` and `y`, computed as exp(y · ln x).
+/// Panics when `x ≥ 2^255`, `y` is too large, or the result leaves the
+/// range of `exp`; see `try_pow`.
+pub fn pow(x: U256, y: U256) -> U256 {
+    try_pow(x, y).unwrap_or_else(|e| panic!("LogExpMath: {}", e))
+}
+
+/// e^x for an 18‑dec `x` in [-41, 130]; see `try_exp`.
+pub fn exp(x: I256) -> I256 {
+    try_exp(x).unwrap_or_else(|e| panic!("LogExpMath: {}", e))
+}
+
+/// Natural logarithm of an 18‑dec `a > 0`; see `try_ln`.
+pub fn ln(a: I256) -> I256 {
+    try_ln(a).unwrap_or_else(|e| panic!("LogExpMath: {}", e))
+}
+
+/// Fallible `pow`.
+pub fn try_pow(x: U256, y: U256) -> Result<U256, MathError> {
+    if y.is_zero() {
+        return Ok(ONE_18);
+    }
+    if x.is_zero() {
+        return Ok(U256::zero());
+    }
+    let x = I256::from_unsigned(x).ok_or(MathError::BaseOutOfBounds)?;
+    if y >= MILD_EXPONENT_BOUND {
+        return Err(MathError::ExponentOutOfBounds);
+    }
+    let y = I256::from_raw(y);
+    let one_18 = I256::from_raw(ONE_18);
+
+    // with y below MILD_EXPONENT_BOUND neither product can overflow
+    let logx_times_y = if signed(LN_36_LOWER_BOUND) < x && x < signed(LN_36_UPPER_BOUND) {
+        let ln_36_x = ln_36(x);
+        // ln_36_x has 36 decimals: split it to multiply by y without overflow
+        (ln_36_x / one_18) * y + ((ln_36_x % one_18) * y) / one_18
+    } else {
+        ln_raw(x) * y
+    };
+    Ok(try_exp(logx_times_y / one_18)?
+        .to_unsigned()
+        .expect("e^x is positive"))
+}
+
+/// Fallible `exp`.
+pub fn try_exp(x: I256) -> Result<I256, MathError> {
+    if x < -signed(MIN_NATURAL_EXPONENT_ABS) || x > signed(MAX_NATURAL_EXPONENT) {
+        return Err(MathError::ExponentOutOfBounds);
+    }
+    if x.is_negative() {
+        // e^(-x) = 1 / e^x; the positive side always fits
+        let one_18 = I256::from_raw(ONE_18);
+        return Ok((one_18 * one_18) / signed(exp_unsigned(x.unsigned_abs())));
+    }
+    Ok(signed(exp_unsigned(x.unsigned_abs())))
+}
+
+/// Fallible `ln`.
+pub fn try_ln(a: I256) -> Result<I256, MathError> {
+    if a <= I256::ZERO {
+        return Err(MathError::BaseOutOfBounds);
+    }
+    if signed(LN_36_LOWER_BOUND) < a && a < signed(LN_36_UPPER_BOUND) {
+        Ok(ln_36(a) / I256::from_raw(ONE_18))
+    } else {
+        Ok(ln_raw(a))
+    }
+}
+
+#[inline]
+fn signed(x: U256) -> I256 {
+    I256::from_raw(x)
+}
+
+/// e^x for 0 ≤ x ≤ 130.
+fn exp_unsigned(x: U256) -> U256 {
+    // strip the large powers (x0, x1) first; they are applied last
+    let mut x = x;
+    let first_an = if x >= X0 {
+        x -= X0;
+        A0
+    } else if x >= X1 {
+        x -= X1;
+        A1
+    } else {
+        U256::one()
+    };
+
+    // to 20 decimals for the remaining decomposition and series
+    x *= U256::from(100u8);
+    let mut product = ONE_20;
+    for (xn, an) in [(X2, A2), (X3, A3), (X4, A4), (X5, A5), (X6, A6), (X7, A7), (X8, A8), (X9, A9)] {
+        if x >= xn {
+            x -= xn;
+            product = (product * an) / ONE_20;
+        }
+    }
+
+    // Taylor series for the remainder (x < 0.25)
+    let mut series_sum = ONE_20;
+    let mut term = x;
+    series_sum += term;
+    for n in 2u8..=12 {
+        term = ((term * x) / ONE_20) / U256::from(n);
+        series_sum += term;
+    }
+
+    (((product * series_sum) / ONE_20) * first_an) / U256::from(100u8)
+}
+
+/// ln with 18‑dec output, via repeated division by known powers of e.
+fn ln_raw(a: I256) -> I256 {
+    let a = a.unsigned_abs();
+    if a < ONE_18 {
+        // ln(a) = -ln(1/a)
+        return -ln_raw(signed((ONE_18 * ONE_18) / a));
+    }
+
+    let mut a = a;
+    let mut sum = U256::zero();
+    if a >= A0 * ONE_18 {
+        a /= A0;
+        sum += X0;
+    }
+    if a >= A1 * ONE_18 {
+        a /= A1;
+        sum += X1;
+    }
+
+    // to 20 decimals
+    let hundred = U256::from(100u8);
+    sum *= hundred;
+    a *= hundred;
+    for (xn, an) in [
+        (X2, A2), (X3, A3), (X4, A4), (X5, A5), (X6, A6),
+        (X7, A7), (X8, A8), (X9, A9), (X10, A10), (X11, A11),
+    ] {
+        if a >= an {
+            a = (a * ONE_20) / an;
+            sum += xn;
+        }
+    }
+
+    // ln(a) = 2·atanh(z), z = (a - 1)/(a + 1); here 1 ≤ a < e^(1/16)
+    let z = ((a - ONE_20) * ONE_20) / (a + ONE_20);
+    let z_squared = (z * z) / ONE_20;
+    let mut num = z;
+    let mut series_sum = num;
+    for d in [3u8, 5, 7, 9, 11] {
+        num = (num * z_squared) / ONE_20;
+        series_sum += num / U256::from(d);
+    }
+    series_sum *= U256::from(2u8);
+
+    signed((sum + series_sum) / hundred)
+}
+
+/// High‑precision ln for `x` close to 1, with 36‑dec output.
+fn ln_36(x: I256) -> I256 {
+    let one_36 = signed(ONE_36);
+    let x = x * signed(ONE_18);
+    let z = ((x - one_36) * one_36) / (x + one_36);
+    let z_squared = (z * z) / one_36;
+    let mut num = z;
+    let mut series_sum = num;
+    for d in [3i64, 5, 7, 9, 11, 13, 15] {
+        num = (num * z_squared) / one_36;
+        series_sum += num / I256::from(d);
+    }
+    series_sum * I256::from(2i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(v: u128) -> U256 {
+        U256::from(v) * ONE_18
+    }
+
+    fn close(a: U256, b: U256, tolerance: U256) -> bool {
+        let diff = if a > b { a - b } else { b - a };
+        diff <= tolerance
+    }
+
+    #[test]
+    fn constants_match_their_decimal_values() {
+        assert_eq!(A0, U256::from_dec_str("38877084059945950922200000000000000000000000000000000000").unwrap());
+        assert_eq!(MILD_EXPONENT_BOUND, (U256::one() << 254) / ONE_20);
+    }
+
+    #[test]
+    fn exp_and_ln_of_known_values() {
+        let e = I256::from(2_718_281_828_459_045_235i128);
+        assert_eq!(exp(I256::ZERO), I256::ONE);
+        // e = 2.718281828459045235
+        assert!((exp(I256::ONE) - e).unsigned_abs() <= U256::from(10u8));
+        // e^-1 = 0.367879441171442321
+        assert!((exp(-I256::ONE) - I256::from(367_879_441_171_442_321i128)).unsigned_abs() <= U256::from(10u8));
+        assert_eq!(ln(I256::ONE), I256::ZERO);
+        assert!((ln(e) - I256::ONE).unsigned_abs() <= U256::from(10u8));
+        // ln(0.5) = -0.693147180559945309
+        let ln_half = ln(I256::ONE / I256::from(2i64));
+        assert!((ln_half - I256::from(-693_147_180_559_945_309i128)).unsigned_abs() <= U256::from(10u8));
+        // 0.95 takes the 36‑decimal path: ln(0.95) = -0.051293294387550533
+        let ln_95 = ln(I256::from(950_000_000_000_000_000i128));
+        assert!((ln_95 - I256::from(-51_293_294_387_550_533i128)).unsigned_abs() <= U256::from(10u8));
+    }
+
+    #[test]
+    fn pow_of_known_values() {
+        assert_eq!(pow(fp(2), U256::zero()), ONE_18);
+        assert!(close(pow(fp(2), fp(10)), fp(1024), U256::from(1_000_000u32)));
+        // 4^0.5 = 2, 0.25^0.5 = 0.5
+        assert!(close(pow(fp(4), ONE_18 / 2), fp(2), U256::from(100u8)));
+        assert!(close(pow(ONE_18 / 4, ONE_18 / 2), ONE_18 / 2, U256::from(100u8)));
+        // close to 1 takes the 36‑decimal path: 1.05^2 = 1.1025
+        assert!(close(pow(U256::from(1_050_000_000_000_000_000u128), fp(2)), U256::from(1_102_500_000_000_000_000u128), U256::from(10u8)));
+    }
+
+    #[test]
+    fn exp_rejects_out_of_range() {
+        assert!(std::panic::catch_unwind(|| exp(I256::from_raw(fp(131)))).is_err());
+        assert!(std::panic::catch_unwind(|| exp(-I256::from_raw(fp(42)))).is_err());
+        assert_eq!(try_exp(I256::from_raw(fp(131))), Err(MathError::ExponentOutOfBounds));
+        assert_eq!(try_ln(I256::ZERO), Err(MathError::BaseOutOfBounds));
+        assert_eq!(try_pow(U256::MAX, ONE_18), Err(MathError::BaseOutOfBounds));
+        assert_eq!(try_pow(fp(2), MILD_EXPONENT_BOUND), Err(MathError::ExponentOutOfBounds));
+        // 10^60 leaves the range of exp
+        assert_eq!(try_pow(fp(10), fp(60)), Err(MathError::ExponentOutOfBounds));
+    }
+}
@@ -0,0 +1,101 @@
+// Symmetric‑Solana ─ Newton–Raphson solver
+// ================================================================
+// Bounded iteration shared by the invariant solvers. `try_iterate`
+// runs any fixed‑point update x ← g(x) (the form StableMath's
+// closed‑form Newton steps take); `try_solve` builds that update from
+// f and f′ for solvers that have them separately. Both stop once two
+// successive iterates are within `tolerance` and fail with
+// `ConvergenceFailure` after `max_iterations` steps.
+// ================================================================
+use super::{MathError, I256, U256};
+
+/// Iterate `step` from `initial` until |xₖ₊₁ − xₖ| ≤ `tolerance`;
+/// returns the last iterate.
+pub fn try_iterate<F>(initial: U256, tolerance: U256, max_iterations: usize, mut step: F) -> Result<U256, MathError>
+where
+    F: FnMut(U256) -> Result<U256, MathError>,
+{
+    let mut x = initial;
+    for _ in 0..max_iterations {
+        let next = step(x)?;
+        let delta = if next > x { next - x } else { x - next };
+        x = next;
+        if delta <= tolerance {
+            return Ok(x);
+        }
+    }
+    Err(MathError::ConvergenceFailure)
+}
+
+/// Root of `f` by Newton–Raphson: x ← x − f(x) / f′(x), where `f`
+/// returns raw units of its codomain and `df` the derivative as an
+/// 18‑dec fixed‑point rate (codomain units per unit of x). An iterate
+/// below zero fails with `Underflow`.
+pub fn try_solve<F, D>(
+    initial: U256,
+    tolerance: U256,
+    max_iterations: usize,
+    mut f: F,
+    mut df: D,
+) -> Result<U256, MathError>
+where
+    F: FnMut(U256) -> Result<I256, MathError>,
+    D: FnMut(U256) -> Result<I256, MathError>,
+{
+    try_iterate(initial, tolerance, max_iterations, |x| {
+        let slope = df(x)?;
+        if slope.is_zero() {
+            return Err(MathError::DivisionByZero);
+        }
+        let delta = f(x)?.checked_mul(I256::ONE).ok_or(MathError::Overflow)? / slope;
+        let x = I256::from_unsigned(x).ok_or(MathError::Overflow)?;
+        x.checked_sub(delta).ok_or(MathError::Overflow)?.to_unsigned().ok_or(MathError::Underflow)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed;
+
+    #[test]
+    fn solves_a_square_root() {
+        // x² − 2 = 0 in 18‑dec fixed point
+        let two = fixed::ONE * 2;
+        let signed = |v: U256| I256::from_unsigned(v).unwrap();
+        let root = try_solve(
+            fixed::ONE,
+            U256::one(),
+            50,
+            |x| Ok(signed(fixed::mul_down(x, x)) - signed(two)),
+            |x| Ok(signed(x * 2)),
+        )
+        .unwrap();
+        let diff = root.max(fixed::sqrt(two)) - root.min(fixed::sqrt(two));
+        assert!(diff <= U256::from(2u8), "{}", root);
+    }
+
+    #[test]
+    fn reports_divergence_and_bad_steps() {
+        // x ← x + 2 never settles
+        let stepper = |x: U256| Ok(x + 2);
+        assert_eq!(try_iterate(U256::zero(), U256::one(), 10, stepper), Err(MathError::ConvergenceFailure));
+        // the iteration count is a hard cap
+        let mut calls = 0;
+        let _ = try_iterate(U256::zero(), U256::zero(), 7, |x| {
+            calls += 1;
+            Ok(x + 1)
+        });
+        assert_eq!(calls, 7);
+        // flat function
+        assert_eq!(
+            try_solve(fixed::ONE, U256::one(), 10, |_| Ok(I256::ONE), |_| Ok(I256::ZERO)),
+            Err(MathError::DivisionByZero)
+        );
+        // overshooting below zero
+        assert_eq!(
+            try_solve(fixed::ONE, U256::one(), 10, |_| Ok(I256::ONE * I256::from(10i64)), |_| Ok(I256::ONE)),
+            Err(MathError::Underflow)
+        );
+    }
+}
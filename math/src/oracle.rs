@@ -0,0 +1,126 @@
+// Symmetric‑Solana ─ TWAP oracle math
+// ================================================================
+// Time‑weighted geometric means (Balancer V2 / Uniswap style). An
+// accumulator integrates ln(value) over time; the geometric mean of a
+// value across a window is
+//
+//   exp((acc(t₁) − acc(t₀)) / (t₁ − t₀))
+//
+// so a reader only needs two accumulator snapshots. ln/exp are the
+// 18‑dec LogExpMath functions; times are unix seconds as on Solana.
+// ================================================================
+use super::{log_exp_math, MathError, I256, U256};
+
+/// Running integral of ln(value) over time. The value recorded at
+/// `timestamp` holds until the next update.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Accumulator {
+    /// Time of the last update
+    pub timestamp: i64,
+    /// ln of the value recorded at `timestamp` (18‑dec)
+    pub log_value: I256,
+    /// ∫ ln(value) dt up to `timestamp` (18‑dec · seconds)
+    pub accumulated: I256,
+}
+
+impl Accumulator {
+    /// Start accumulating `value` (18‑dec, > 0) at `now`.
+    pub fn new(now: i64, value: U256) -> Self {
+        expect(Self::try_new(now, value))
+    }
+
+    pub fn try_new(now: i64, value: U256) -> Result<Self, MathError> {
+        Ok(Self { timestamp: now, log_value: try_log_value(value)?, accumulated: I256::ZERO })
+    }
+
+    /// Close the previous value's interval at `now` and record `value`.
+    pub fn update(&self, now: i64, value: U256) -> Self {
+        expect(self.try_update(now, value))
+    }
+
+    pub fn try_update(&self, now: i64, value: U256) -> Result<Self, MathError> {
+        Ok(Self { timestamp: now, log_value: try_log_value(value)?, accumulated: self.try_accumulated_at(now)? })
+    }
+
+    /// Accumulator value at `now` (≥ `timestamp`), extrapolating the last
+    /// recorded value, so readers need not wait for an update.
+    pub fn accumulated_at(&self, now: i64) -> I256 {
+        expect(self.try_accumulated_at(now))
+    }
+
+    pub fn try_accumulated_at(&self, now: i64) -> Result<I256, MathError> {
+        let elapsed = now.checked_sub(self.timestamp).filter(|e| *e >= 0).ok_or(MathError::InvalidTimeRange)?;
+        let area = self.log_value.checked_mul(I256::from(elapsed)).ok_or(MathError::Overflow)?;
+        self.accumulated.checked_add(area).ok_or(MathError::Overflow)
+    }
+}
+
+/// ln of an 18‑dec value, as stored by the accumulator.
+pub fn try_log_value(value: U256) -> Result<I256, MathError> {
+    let value = I256::from_unsigned(value).ok_or(MathError::BaseOutOfBounds)?;
+    log_exp_math::try_ln(value)
+}
+
+/// Geometric mean of the accumulated value between two snapshots
+/// `(timestamp, accumulated)`, with `start` strictly before `end`.
+pub fn geometric_mean(start: (i64, I256), end: (i64, I256)) -> U256 {
+    expect(try_geometric_mean(start, end))
+}
+
+pub fn try_geometric_mean(start: (i64, I256), end: (i64, I256)) -> Result<U256, MathError> {
+    let duration = end.0.checked_sub(start.0).filter(|d| *d > 0).ok_or(MathError::InvalidTimeRange)?;
+    let area = end.1.checked_sub(start.1).ok_or(MathError::Overflow)?;
+    let mean_log = area / I256::from(duration);
+    let mean = log_exp_math::try_exp(mean_log)?;
+    mean.to_unsigned().ok_or(MathError::Underflow)
+}
+
+fn expect<T>(result: Result<T, MathError>) -> T {
+    result.unwrap_or_else(|e| panic!("oracle: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::{self, from_f64 as fp};
+
+    fn close(a: U256, b: U256) -> bool {
+        let diff = if a > b { a - b } else { b - a };
+        diff <= fixed::mul_up(b, U256::from(1_000_000u64)) // 1e-12
+    }
+
+    #[test]
+    fn geometric_mean_weights_values_by_time() {
+        // price 1 for 100 s, then 4 for 100 s: geometric mean 2
+        let acc = Accumulator::new(1_000, fp(1.0));
+        let acc = acc.update(1_100, fp(4.0));
+        let start = (1_000, I256::ZERO);
+        let end = (1_200, acc.accumulated_at(1_200));
+        assert!(close(geometric_mean(start, end), fp(2.0)), "{}", geometric_mean(start, end));
+
+        // a sub‑window only sees the value it spans
+        let mid = (1_150, acc.accumulated_at(1_150));
+        assert!(close(geometric_mean(mid, end), fp(4.0)));
+        // 4 for 300 s against 1 for 100 s: 4^(3/4)
+        let later = (1_400, acc.accumulated_at(1_400));
+        assert!(close(geometric_mean(start, later), fp(2.828_427_124_746_19)));
+    }
+
+    #[test]
+    fn constant_value_averages_to_itself() {
+        let acc = Accumulator::new(0, fp(1_234.5));
+        let updated = acc.update(3_600, fp(1_234.5));
+        assert_eq!(updated.accumulated, acc.accumulated_at(3_600));
+        let mean = geometric_mean((0, I256::ZERO), (7_200, updated.accumulated_at(7_200)));
+        assert!(close(mean, fp(1_234.5)));
+    }
+
+    #[test]
+    fn rejects_bad_times_and_values() {
+        let acc = Accumulator::new(100, fp(2.0));
+        assert_eq!(acc.try_accumulated_at(99), Err(MathError::InvalidTimeRange));
+        assert_eq!(acc.try_update(99, fp(2.0)), Err(MathError::InvalidTimeRange));
+        assert_eq!(try_geometric_mean((5, I256::ZERO), (5, I256::ZERO)), Err(MathError::InvalidTimeRange));
+        assert_eq!(Accumulator::try_new(0, U256::zero()), Err(MathError::BaseOutOfBounds));
+    }
+}
@@ -0,0 +1,737 @@
+// Symmetric‑Solana ─ StableMath
+// ================================================================
+// StableSwap invariant (Curve / Balancer V2 `StableMath`) for pools of
+// like‑priced assets. Balances are 18‑dec fixed point (already scaled
+// to 18 decimals); `amp` is the amplification parameter multiplied by
+// `AMP_PRECISION`. The invariant and token balances are solved with
+// Newton–Raphson, rounding in the pool's favour.
+// ================================================================
+#![allow(clippy::too_many_arguments)]
+
+use super::{fixed, newton, MathError, U256};
+use alloc::vec::Vec;
+use fixed::{complement, try_add as add, try_sub as sub};
+use fixed::{try_div_down as div_down, try_div_up as div_up, try_mul_down as mul_down, try_mul_up as mul_up};
+
+/// Precision `amp` is expressed in (amp 100 ≙ 100_000)
+pub const AMP_PRECISION: u64 = 1_000;
+/// Smallest accepted amplification parameter (without precision)
+pub const MIN_AMP: u64 = 1;
+/// Largest accepted amplification parameter (without precision)
+pub const MAX_AMP: u64 = 5_000;
+
+/// Newton–Raphson iteration cap for the invariant and balance solvers
+const MAX_ITERATIONS: usize = 255;
+
+// The plain functions panic with the `MathError` of their `try_*`
+// counterpart, which programs should call instead.
+#[inline]
+fn expect<T>(result: Result<T, MathError>) -> T {
+    result.unwrap_or_else(|e| panic!("StableMath: {}", e))
+}
+
+/// Calculate the invariant D, rounding down.
+pub fn calculate_invariant(amp: U256, balances: &[U256]) -> U256 {
+    expect(try_calculate_invariant(amp, balances))
+}
+
+pub fn try_calculate_invariant(amp: U256, balances: &[U256]) -> Result<U256, MathError> {
+    let n = U256::from(balances.len());
+    let amp_precision = U256::from(AMP_PRECISION);
+    let sum = try_sum(balances)?;
+    if sum.is_zero() {
+        return Ok(U256::zero());
+    }
+
+    let amp_times_total = mul(amp, n)?;
+    newton::try_iterate(sum, U256::one(), MAX_ITERATIONS, |invariant| {
+        let mut d_p = invariant;
+        for b in balances {
+            d_p = div(mul(d_p, invariant)?, mul(*b, n)?)?;
+        }
+        let numerator = mul(add(div(mul(amp_times_total, sum)?, amp_precision)?, mul(d_p, n)?)?, invariant)?;
+        let denominator = add(
+            div(mul(sub(amp_times_total, amp_precision)?, invariant)?, amp_precision)?,
+            mul(add(n, U256::one())?, d_p)?,
+        )?;
+        div(numerator, denominator)
+    })
+}
+
+/// Exact‑in swap: amount of `token_index_out` received for `amount_in` of
+/// `token_index_in`. The swap fee is taken from the amount in.
+pub fn calc_out_given_in(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_out_given_in(amp, balances, token_index_in, token_index_out, amount_in, invariant, swap_fee))
+}
+
+pub fn try_calc_out_given_in(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index_in)?;
+    check_index(balances, token_index_out)?;
+    let amount_in_after_fee = mul_down(amount_in, complement(swap_fee))?;
+    let mut balances = balances.to_vec();
+    balances[token_index_in] = add(balances[token_index_in], amount_in_after_fee)?;
+    let final_balance_out =
+        try_get_token_balance_given_invariant_and_all_other_balances(amp, &balances, invariant, token_index_out)?;
+    // -1 rounds in the pool's favour
+    sub(sub(balances[token_index_out], final_balance_out)?, U256::one())
+}
+
+/// Exact‑out swap: amount of `token_index_in` required for `amount_out` of
+/// `token_index_out`, fee included.
+pub fn calc_in_given_out(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_out: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_in_given_out(amp, balances, token_index_in, token_index_out, amount_out, invariant, swap_fee))
+}
+
+pub fn try_calc_in_given_out(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_out: U256,
+    invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index_in)?;
+    check_index(balances, token_index_out)?;
+    let mut balances = balances.to_vec();
+    balances[token_index_out] = sub(balances[token_index_out], amount_out)?;
+    let final_balance_in =
+        try_get_token_balance_given_invariant_and_all_other_balances(amp, &balances, invariant, token_index_in)?;
+    // +1 rounds in the pool's favour
+    let without_fee = add(sub(final_balance_in, balances[token_index_in])?, U256::one())?;
+    div_up(without_fee, complement(swap_fee))
+}
+
+/// Marginal price of `token_index_out` in units of `token_index_in`, fee
+/// included: the ratio of the invariant's partial derivatives,
+/// (A·n·x_out + D_P)·x_in / ((A·n·x_in + D_P)·x_out) / (1 − fee), where
+/// D_P = D^(n+1) / (n^n·Π x). Rounds up.
+pub fn calc_spot_price(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_spot_price(amp, balances, token_index_in, token_index_out, invariant, swap_fee))
+}
+
+pub fn try_calc_spot_price(
+    amp: U256,
+    balances: &[U256],
+    token_index_in: usize,
+    token_index_out: usize,
+    invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index_in)?;
+    check_index(balances, token_index_out)?;
+    let n = U256::from(balances.len());
+    let mut d_p = invariant;
+    for b in balances {
+        d_p = div(mul(d_p, invariant)?, mul(*b, n)?)?;
+    }
+    // both terms carry AMP_PRECISION
+    let amp_times_total = mul(amp, n)?;
+    let d_p = mul(d_p, U256::from(AMP_PRECISION))?;
+    let (x_in, x_out) = (balances[token_index_in], balances[token_index_out]);
+    let numer = add(mul(amp_times_total, x_out)?, d_p)?;
+    let denom = add(mul(amp_times_total, x_in)?, d_p)?;
+    let price = mul_up(div_up(numer, denom)?, div_up(x_in, x_out)?)?;
+    div_up(price, complement(swap_fee))
+}
+
+/// Exact tokens in join: BPT minted. Only the part of each amount above the
+/// proportional share is charged the swap fee.
+pub fn calc_bpt_out_given_exact_tokens_in(
+    amp: U256,
+    balances: &[U256],
+    amounts_in: &[U256],
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_bpt_out_given_exact_tokens_in(amp, balances, amounts_in, total_bpt, current_invariant, swap_fee))
+}
+
+pub fn try_calc_bpt_out_given_exact_tokens_in(
+    amp: U256,
+    balances: &[U256],
+    amounts_in: &[U256],
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_len(balances.len(), amounts_in.len())?;
+    let sum = try_sum(balances)?;
+
+    let mut balance_ratios_with_fee: Vec<U256> = Vec::with_capacity(balances.len());
+    let mut invariant_ratio_with_fees = U256::zero();
+    for (b, a) in balances.iter().zip(amounts_in) {
+        let current_weight = div_down(*b, sum)?;
+        let ratio = div_down(add(*b, *a)?, *b)?;
+        invariant_ratio_with_fees = add(invariant_ratio_with_fees, mul_down(ratio, current_weight)?)?;
+        balance_ratios_with_fee.push(ratio);
+    }
+
+    let mut new_balances: Vec<U256> = Vec::with_capacity(balances.len());
+    for i in 0..balances.len() {
+        let amount_in_without_fee = if balance_ratios_with_fee[i] > invariant_ratio_with_fees {
+            let non_taxable = mul_down(balances[i], sub(invariant_ratio_with_fees, fixed::ONE)?)?;
+            let taxable = sub(amounts_in[i], non_taxable)?;
+            add(non_taxable, mul_down(taxable, complement(swap_fee))?)?
+        } else {
+            amounts_in[i]
+        };
+        new_balances.push(add(balances[i], amount_in_without_fee)?);
+    }
+
+    let new_invariant = try_calculate_invariant(amp, &new_balances)?;
+    let invariant_ratio = div_down(new_invariant, current_invariant)?;
+    if invariant_ratio > fixed::ONE {
+        mul_down(total_bpt, invariant_ratio - fixed::ONE)
+    } else {
+        Ok(U256::zero())
+    }
+}
+
+/// Single token join: amount of `token_index` required to mint `bpt_out`.
+pub fn calc_token_in_given_exact_bpt_out(
+    amp: U256,
+    balances: &[U256],
+    token_index: usize,
+    bpt_out: U256,
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_token_in_given_exact_bpt_out(
+        amp,
+        balances,
+        token_index,
+        bpt_out,
+        total_bpt,
+        current_invariant,
+        swap_fee,
+    ))
+}
+
+pub fn try_calc_token_in_given_exact_bpt_out(
+    amp: U256,
+    balances: &[U256],
+    token_index: usize,
+    bpt_out: U256,
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index)?;
+    let new_invariant = mul_up(div_up(add(total_bpt, bpt_out)?, total_bpt)?, current_invariant)?;
+    let new_balance =
+        try_get_token_balance_given_invariant_and_all_other_balances(amp, balances, new_invariant, token_index)?;
+    let amount_in_without_fee = sub(new_balance, balances[token_index])?;
+
+    // the fee applies to the share of the amount not matched by other tokens
+    let sum = try_sum(balances)?;
+    let current_weight = div_down(balances[token_index], sum)?;
+    let taxable = mul_up(amount_in_without_fee, complement(current_weight))?;
+    let non_taxable = sub(amount_in_without_fee, taxable)?;
+    add(non_taxable, div_up(taxable, complement(swap_fee))?)
+}
+
+/// Exact tokens out exit: BPT to burn.
+pub fn calc_bpt_in_given_exact_tokens_out(
+    amp: U256,
+    balances: &[U256],
+    amounts_out: &[U256],
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_bpt_in_given_exact_tokens_out(amp, balances, amounts_out, total_bpt, current_invariant, swap_fee))
+}
+
+pub fn try_calc_bpt_in_given_exact_tokens_out(
+    amp: U256,
+    balances: &[U256],
+    amounts_out: &[U256],
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_len(balances.len(), amounts_out.len())?;
+    let sum = try_sum(balances)?;
+
+    let mut balance_ratios_without_fee: Vec<U256> = Vec::with_capacity(balances.len());
+    let mut invariant_ratio_without_fees = U256::zero();
+    for (b, a) in balances.iter().zip(amounts_out) {
+        let current_weight = div_up(*b, sum)?;
+        let ratio = div_up(sub(*b, *a)?, *b)?;
+        invariant_ratio_without_fees = add(invariant_ratio_without_fees, mul_up(ratio, current_weight)?)?;
+        balance_ratios_without_fee.push(ratio);
+    }
+
+    let mut new_balances: Vec<U256> = Vec::with_capacity(balances.len());
+    for i in 0..balances.len() {
+        let amount_out_with_fee = if invariant_ratio_without_fees > balance_ratios_without_fee[i] {
+            let non_taxable = mul_down(balances[i], complement(invariant_ratio_without_fees))?;
+            let taxable = sub(amounts_out[i], non_taxable)?;
+            add(non_taxable, div_up(taxable, complement(swap_fee))?)?
+        } else {
+            amounts_out[i]
+        };
+        new_balances.push(sub(balances[i], amount_out_with_fee)?);
+    }
+
+    let new_invariant = try_calculate_invariant(amp, &new_balances)?;
+    let invariant_ratio = div_down(new_invariant, current_invariant)?;
+    mul_up(total_bpt, complement(invariant_ratio))
+}
+
+/// Single token exit: amount of `token_index` received for burning `bpt_in`.
+pub fn calc_token_out_given_exact_bpt_in(
+    amp: U256,
+    balances: &[U256],
+    token_index: usize,
+    bpt_in: U256,
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_token_out_given_exact_bpt_in(
+        amp,
+        balances,
+        token_index,
+        bpt_in,
+        total_bpt,
+        current_invariant,
+        swap_fee,
+    ))
+}
+
+pub fn try_calc_token_out_given_exact_bpt_in(
+    amp: U256,
+    balances: &[U256],
+    token_index: usize,
+    bpt_in: U256,
+    total_bpt: U256,
+    current_invariant: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index)?;
+    let new_invariant = mul_up(div_up(sub(total_bpt, bpt_in)?, total_bpt)?, current_invariant)?;
+    let new_balance =
+        try_get_token_balance_given_invariant_and_all_other_balances(amp, balances, new_invariant, token_index)?;
+    let amount_out_without_fee = sub(balances[token_index], new_balance)?;
+
+    let sum = try_sum(balances)?;
+    let current_weight = div_down(balances[token_index], sum)?;
+    let taxable = mul_up(amount_out_without_fee, complement(current_weight))?;
+    let non_taxable = sub(amount_out_without_fee, taxable)?;
+    add(non_taxable, mul_down(taxable, complement(swap_fee))?)
+}
+
+/// Solve for the balance of `token_index` that keeps `invariant` given all
+/// other balances, rounding up.
+pub fn get_token_balance_given_invariant_and_all_other_balances(
+    amp: U256,
+    balances: &[U256],
+    invariant: U256,
+    token_index: usize,
+) -> U256 {
+    expect(try_get_token_balance_given_invariant_and_all_other_balances(amp, balances, invariant, token_index))
+}
+
+pub fn try_get_token_balance_given_invariant_and_all_other_balances(
+    amp: U256,
+    balances: &[U256],
+    invariant: U256,
+    token_index: usize,
+) -> Result<U256, MathError> {
+    check_index(balances, token_index)?;
+    let n = U256::from(balances.len());
+    let amp_precision = U256::from(AMP_PRECISION);
+    let amp_times_total = mul(amp, n)?;
+
+    let mut sum = balances[0];
+    let mut p_d = mul(balances[0], n)?;
+    for b in &balances[1..] {
+        p_d = div(mul(mul(p_d, *b)?, n)?, invariant)?;
+        sum = add(sum, *b)?;
+    }
+    sum = sub(sum, balances[token_index])?;
+
+    let inv2 = mul(invariant, invariant)?;
+    // c = D² / (A·n·P_D) · AMP_PRECISION · x_i
+    let c = mul(mul(div_up_raw(inv2, mul(amp_times_total, p_d)?)?, amp_precision)?, balances[token_index])?;
+    // b = sum + D / (A·n) · AMP_PRECISION
+    let b = add(sum, mul(div(invariant, amp_times_total)?, amp_precision)?)?;
+
+    let initial = div_up_raw(add(inv2, c)?, add(invariant, b)?)?;
+    newton::try_iterate(initial, U256::one(), MAX_ITERATIONS, |token_balance| {
+        let numerator = add(mul(token_balance, token_balance)?, c)?;
+        let denominator = sub(add(mul(token_balance, U256::from(2u8))?, b)?, invariant)?;
+        div_up_raw(numerator, denominator)
+    })
+}
+
+// ---------------- Composable pools
+//
+// A composable pool pre-mints its whole BPT supply into its own custody
+// and lists that BPT among its tokens. BPT still held by the pool is not
+// in circulation: the invariant is over the other tokens only and joins
+// and exits are priced against the virtual supply. Buying BPT is a single
+// token join, selling it a single token exit, both reached as swaps.
+
+/// BPT in circulation: everything minted less what the pool itself holds.
+pub fn virtual_supply(total_supply: U256, pool_bpt_balance: U256) -> U256 {
+    expect(try_virtual_supply(total_supply, pool_bpt_balance))
+}
+
+pub fn try_virtual_supply(total_supply: U256, pool_bpt_balance: U256) -> Result<U256, MathError> {
+    sub(total_supply, pool_bpt_balance)
+}
+
+/// Exact in swap in a composable pool. `balances` include the pool's own
+/// BPT at `bpt_index`; `total_supply` is all BPT minted.
+pub fn calc_composable_out_given_in(
+    amp: U256,
+    balances: &[U256],
+    bpt_index: usize,
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: U256,
+    total_supply: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_composable_out_given_in(
+        amp,
+        balances,
+        bpt_index,
+        token_index_in,
+        token_index_out,
+        amount_in,
+        total_supply,
+        swap_fee,
+    ))
+}
+
+pub fn try_calc_composable_out_given_in(
+    amp: U256,
+    balances: &[U256],
+    bpt_index: usize,
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_in: U256,
+    total_supply: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    let ComposableSwap { tokens, supply, index_in, index_out } =
+        split_composable(balances, bpt_index, token_index_in, token_index_out, total_supply)?;
+    let invariant = try_calculate_invariant(amp, &tokens)?;
+    match (index_in, index_out) {
+        // join swap: token in, BPT out
+        (Some(i), None) => {
+            let amounts_in = one_hot(tokens.len(), i, amount_in);
+            try_calc_bpt_out_given_exact_tokens_in(amp, &tokens, &amounts_in, supply, invariant, swap_fee)
+        }
+        // exit swap: BPT in, token out
+        (None, Some(o)) => try_calc_token_out_given_exact_bpt_in(amp, &tokens, o, amount_in, supply, invariant, swap_fee),
+        (Some(i), Some(o)) => try_calc_out_given_in(amp, &tokens, i, o, amount_in, invariant, swap_fee),
+        (None, None) => Err(MathError::TokenIndexOutOfBounds),
+    }
+}
+
+/// Exact out swap in a composable pool; see `calc_composable_out_given_in`.
+pub fn calc_composable_in_given_out(
+    amp: U256,
+    balances: &[U256],
+    bpt_index: usize,
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_out: U256,
+    total_supply: U256,
+    swap_fee: U256,
+) -> U256 {
+    expect(try_calc_composable_in_given_out(
+        amp,
+        balances,
+        bpt_index,
+        token_index_in,
+        token_index_out,
+        amount_out,
+        total_supply,
+        swap_fee,
+    ))
+}
+
+pub fn try_calc_composable_in_given_out(
+    amp: U256,
+    balances: &[U256],
+    bpt_index: usize,
+    token_index_in: usize,
+    token_index_out: usize,
+    amount_out: U256,
+    total_supply: U256,
+    swap_fee: U256,
+) -> Result<U256, MathError> {
+    let ComposableSwap { tokens, supply, index_in, index_out } =
+        split_composable(balances, bpt_index, token_index_in, token_index_out, total_supply)?;
+    let invariant = try_calculate_invariant(amp, &tokens)?;
+    match (index_in, index_out) {
+        // join swap: token in, exact BPT out
+        (Some(i), None) => try_calc_token_in_given_exact_bpt_out(amp, &tokens, i, amount_out, supply, invariant, swap_fee),
+        // exit swap: BPT in, exact token out
+        (None, Some(o)) => {
+            let amounts_out = one_hot(tokens.len(), o, amount_out);
+            try_calc_bpt_in_given_exact_tokens_out(amp, &tokens, &amounts_out, supply, invariant, swap_fee)
+        }
+        (Some(i), Some(o)) => try_calc_in_given_out(amp, &tokens, i, o, amount_out, invariant, swap_fee),
+        (None, None) => Err(MathError::TokenIndexOutOfBounds),
+    }
+}
+
+/// A composable swap seen without the pool's own BPT.
+struct ComposableSwap {
+    /// Balances of the other tokens
+    tokens: Vec<U256>,
+    /// Virtual supply
+    supply: U256,
+    /// Positions of the swapped tokens among the others, `None` for BPT
+    index_in: Option<usize>,
+    index_out: Option<usize>,
+}
+
+fn split_composable(
+    balances: &[U256],
+    bpt_index: usize,
+    token_index_in: usize,
+    token_index_out: usize,
+    total_supply: U256,
+) -> Result<ComposableSwap, MathError> {
+    check_index(balances, bpt_index)?;
+    check_index(balances, token_index_in)?;
+    check_index(balances, token_index_out)?;
+    if token_index_in == token_index_out {
+        return Err(MathError::TokenIndexOutOfBounds);
+    }
+    let supply = try_virtual_supply(total_supply, balances[bpt_index])?;
+    let mut tokens = balances.to_vec();
+    tokens.remove(bpt_index);
+    let position = |i: usize| match i.cmp(&bpt_index) {
+        core::cmp::Ordering::Less => Some(i),
+        core::cmp::Ordering::Equal => None,
+        core::cmp::Ordering::Greater => Some(i - 1),
+    };
+    Ok(ComposableSwap {
+        tokens,
+        supply,
+        index_in: position(token_index_in),
+        index_out: position(token_index_out),
+    })
+}
+
+#[inline]
+fn one_hot(n: usize, index: usize, amount: U256) -> Vec<U256> {
+    let mut amounts = alloc::vec![U256::zero(); n];
+    amounts[index] = amount;
+    amounts
+}
+
+// ---------------- Raw (unscaled) checked arithmetic
+
+#[inline]
+fn mul(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+#[inline]
+fn div(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_div(b).ok_or(MathError::DivisionByZero)
+}
+
+#[inline]
+fn div_up_raw(a: U256, b: U256) -> Result<U256, MathError> {
+    if b.is_zero() {
+        Err(MathError::DivisionByZero)
+    } else if a.is_zero() {
+        Ok(U256::zero())
+    } else {
+        Ok((a - U256::one()) / b + U256::one())
+    }
+}
+
+#[inline]
+fn try_sum(balances: &[U256]) -> Result<U256, MathError> {
+    balances.iter().try_fold(U256::zero(), |acc, b| add(acc, *b))
+}
+
+#[inline]
+fn check_len(a: usize, b: usize) -> Result<(), MathError> {
+    if a == b { Ok(()) } else { Err(MathError::LengthMismatch) }
+}
+
+#[inline]
+fn check_index(balances: &[U256], index: usize) -> Result<(), MathError> {
+    if index < balances.len() { Ok(()) } else { Err(MathError::TokenIndexOutOfBounds) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abs_diff(a: U256, b: U256) -> U256 {
+        if a > b { a - b } else { b - a }
+    }
+
+    fn fp(v: u64) -> U256 {
+        U256::from(v) * fixed::ONE
+    }
+
+    fn amp(a: u64) -> U256 {
+        U256::from(a * AMP_PRECISION)
+    }
+
+    #[test]
+    fn balanced_invariant_is_the_sum() {
+        let balances = [fp(1_000), fp(1_000), fp(1_000)];
+        let d = calculate_invariant(amp(100), &balances);
+        assert!(abs_diff(d, fp(3_000)) <= U256::from(3u8));
+    }
+
+    #[test]
+    fn swap_near_parity_and_round_trips() {
+        let balances = [fp(1_000_000), fp(1_000_000)];
+        let a = amp(200);
+        let d = calculate_invariant(a, &balances);
+
+        let out = calc_out_given_in(a, &balances, 0, 1, fp(1_000), d, U256::zero());
+        // a small trade in a deep, balanced stable pool is almost 1:1
+        assert!(out < fp(1_000) && out > fp(999));
+
+        // buying exactly that output costs at least what was put in (rounding
+        // favours the pool), and not meaningfully more
+        let back_in = calc_in_given_out(a, &balances, 0, 1, out, d, U256::zero());
+        assert!(back_in >= fp(1_000) && back_in <= fp(1_000) + U256::from(10_000u32));
+    }
+
+    #[test]
+    fn proportional_join_mints_pro_rata() {
+        let balances = [fp(500), fp(500)];
+        let a = amp(100);
+        let d = calculate_invariant(a, &balances);
+        let bpt = calc_bpt_out_given_exact_tokens_in(a, &balances, &[fp(50), fp(50)], fp(1_000), d, fp(1) / 100);
+        // +10 % of every balance, no fee on a proportional join
+        assert!(abs_diff(bpt, fp(100)) <= U256::from(1_000u32));
+    }
+
+    #[test]
+    fn single_token_exit_pays_less_than_its_join() {
+        let balances = [fp(500), fp(500)];
+        let a = amp(100);
+        let d = calculate_invariant(a, &balances);
+        let fee = fp(1) / 100;
+        let cost = calc_token_in_given_exact_bpt_out(a, &balances, 0, fp(10), fp(1_000), d, fee);
+        let proceeds = calc_token_out_given_exact_bpt_in(a, &balances, 0, fp(10), fp(1_000), d, fee);
+        assert!(proceeds < cost);
+        let bpt_in = calc_bpt_in_given_exact_tokens_out(a, &balances, &[fp(5), fp(5)], fp(1_000), d, fee);
+        assert!(abs_diff(bpt_in, fp(10)) <= U256::from(1_000u32));
+    }
+
+    #[test]
+    fn spot_price_is_par_when_balanced_and_matches_a_small_trade() {
+        let a = amp(100);
+        let balances = [fp(1_000_000), fp(1_000_000)];
+        let d = calculate_invariant(a, &balances);
+        let par = calc_spot_price(a, &balances, 0, 1, d, U256::zero());
+        assert!(abs_diff(par, fixed::ONE) <= U256::from(1_000u32));
+        let fee = fp(1) / 100;
+        assert!(calc_spot_price(a, &balances, 0, 1, d, fee) > par * 100 / 99 - U256::from(1_000u32));
+
+        // out of balance the scarce token costs more, as a small trade finds
+        let balances = [fp(1_500_000), fp(500_000)];
+        let d = calculate_invariant(a, &balances);
+        let price = calc_spot_price(a, &balances, 0, 1, d, U256::zero());
+        assert!(price > fixed::ONE);
+        let cost = calc_in_given_out(a, &balances, 0, 1, fp(1), d, U256::zero());
+        assert!(abs_diff(price, cost) * 10_000u32 < price);
+        assert!(calc_spot_price(a, &balances, 1, 0, d, U256::zero()) < fixed::ONE);
+    }
+
+    #[test]
+    fn try_variants_report_errors() {
+        let balances = [fp(500), fp(500)];
+        let a = amp(100);
+        let d = calculate_invariant(a, &balances);
+        assert_eq!(
+            try_calc_out_given_in(a, &balances, 0, 2, fp(1), d, U256::zero()),
+            Err(MathError::TokenIndexOutOfBounds)
+        );
+        assert_eq!(
+            try_calc_in_given_out(a, &balances, 0, 1, fp(501), d, U256::zero()),
+            Err(MathError::Underflow)
+        );
+        assert_eq!(
+            try_calc_bpt_out_given_exact_tokens_in(a, &balances, &[fp(1)], fp(1_000), d, U256::zero()),
+            Err(MathError::LengthMismatch)
+        );
+        assert_eq!(try_calculate_invariant(a, &[U256::MAX, U256::one()]), Err(MathError::Overflow));
+        assert_eq!(try_calculate_invariant(a, &[]), Ok(U256::zero()));
+    }
+
+    #[test]
+    fn composable_swaps_join_and_exit_at_the_virtual_supply() {
+        // 1000 BPT in circulation out of 1M minted; the pool holds the rest
+        let a = amp(100);
+        let total = fp(1_000_000);
+        let balances = [fp(500), fp(999_000), fp(500)];
+        assert_eq!(virtual_supply(total, balances[1]), fp(1_000));
+
+        // buying BPT is a single token join over the other two tokens
+        let bpt_out = calc_composable_out_given_in(a, &balances, 1, 0, 1, fp(10), total, U256::zero());
+        let d = calculate_invariant(a, &[fp(500), fp(500)]);
+        let join = calc_bpt_out_given_exact_tokens_in(a, &[fp(500), fp(500)], &[fp(10), U256::zero()], fp(1_000), d, U256::zero());
+        assert_eq!(bpt_out, join);
+        assert!(bpt_out < fp(10) && bpt_out > fp(9));
+
+        // selling it back returns no more than went in
+        let back = calc_composable_out_given_in(a, &balances, 1, 1, 2, bpt_out, total, U256::zero());
+        assert!(back <= fp(10));
+        let cost = calc_composable_in_given_out(a, &balances, 1, 0, 1, bpt_out, total, U256::zero());
+        // buying the same BPT for an exact amount costs what was paid, within rounding
+        assert!(abs_diff(cost, fp(10)) <= U256::from(1_000_000u32), "{}", cost);
+
+        // token for token ignores the BPT balance entirely
+        let swap = calc_composable_out_given_in(a, &balances, 1, 0, 2, fp(10), total, U256::zero());
+        assert_eq!(swap, calc_out_given_in(a, &[fp(500), fp(500)], 0, 1, fp(10), d, U256::zero()));
+        assert_eq!(
+            try_calc_composable_out_given_in(a, &balances, 1, 1, 1, fp(1), total, U256::zero()),
+            Err(MathError::TokenIndexOutOfBounds)
+        );
+    }
+}
@@ -0,0 +1,206 @@
+// Symmetric‑Solana ─ WebAssembly bindings
+// ================================================================
+// Weighted‑pool quotes for browser frontends (feature `wasm`). The
+// exports run the same code as the on‑chain programs, so quotes are
+// bit‑identical. All values cross the boundary as decimal strings of
+// 18‑dec fixed‑point numbers, since JS numbers cannot hold 256 bits.
+// Failures throw a JS `Error` carrying the `MathError` message.
+// ================================================================
+use super::{weighted_math, MathError, U256};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, PartialEq, Eq)]
+enum QuoteError {
+    Parse(String),
+    Math(MathError),
+}
+
+impl From<MathError> for QuoteError {
+    fn from(e: MathError) -> Self {
+        QuoteError::Math(e)
+    }
+}
+
+impl From<QuoteError> for JsError {
+    fn from(e: QuoteError) -> Self {
+        match e {
+            QuoteError::Parse(v) => JsError::new(&format!("not a 256-bit decimal: {:?}", v)),
+            QuoteError::Math(e) => JsError::new(&e.to_string()),
+        }
+    }
+}
+
+fn parse(v: &str) -> Result<U256, QuoteError> {
+    U256::from_dec_str(v).map_err(|_| QuoteError::Parse(v.into()))
+}
+
+fn parse_all(vs: &[String]) -> Result<Vec<U256>, QuoteError> {
+    vs.iter().map(|v| parse(v)).collect()
+}
+
+fn out_given_in(args: [&str; 6]) -> Result<String, QuoteError> {
+    let [b_in, w_in, b_out, w_out, amount, fee] = args.map(parse);
+    Ok(weighted_math::try_calc_out_given_in(b_in?, w_in?, b_out?, w_out?, amount?, fee?)?.to_string())
+}
+
+fn in_given_out(args: [&str; 6]) -> Result<String, QuoteError> {
+    let [b_in, w_in, b_out, w_out, amount, fee] = args.map(parse);
+    Ok(weighted_math::try_calc_in_given_out(b_in?, w_in?, b_out?, w_out?, amount?, fee?)?.to_string())
+}
+
+fn spot_price(args: [&str; 5]) -> Result<String, QuoteError> {
+    let [b_in, w_in, b_out, w_out, fee] = args.map(parse);
+    Ok(weighted_math::try_calc_spot_price(b_in?, w_in?, b_out?, w_out?, fee?)?.to_string())
+}
+
+fn invariant(balances: &[String], weights: &[String]) -> Result<String, QuoteError> {
+    Ok(weighted_math::try_calculate_invariant(&parse_all(balances)?, &parse_all(weights)?)?.to_string())
+}
+
+fn bpt_out_given_exact_tokens_in(
+    balances: &[String],
+    weights: &[String],
+    amounts_in: &[String],
+    total_bpt: &str,
+    swap_fee: &str,
+) -> Result<String, QuoteError> {
+    let bpt_out = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+        &parse_all(balances)?,
+        &parse_all(weights)?,
+        &parse_all(amounts_in)?,
+        parse(total_bpt)?,
+        parse(swap_fee)?,
+    )?;
+    Ok(bpt_out.to_string())
+}
+
+fn tokens_out_given_exact_bpt_in(
+    balances: &[String],
+    bpt_in: &str,
+    total_bpt: &str,
+    exit_fee: &str,
+) -> Result<Vec<String>, QuoteError> {
+    let amounts = weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+        &parse_all(balances)?,
+        parse(bpt_in)?,
+        parse(total_bpt)?,
+        parse(exit_fee)?,
+    )?;
+    Ok(amounts.iter().map(|a| a.to_string()).collect())
+}
+
+/// `weighted_math::calc_out_given_in`
+#[wasm_bindgen(js_name = calcOutGivenIn)]
+pub fn calc_out_given_in(
+    balance_in: &str,
+    weight_in: &str,
+    balance_out: &str,
+    weight_out: &str,
+    amount_in: &str,
+    swap_fee: &str,
+) -> Result<String, JsError> {
+    Ok(out_given_in([balance_in, weight_in, balance_out, weight_out, amount_in, swap_fee])?)
+}
+
+/// `weighted_math::calc_in_given_out`
+#[wasm_bindgen(js_name = calcInGivenOut)]
+pub fn calc_in_given_out(
+    balance_in: &str,
+    weight_in: &str,
+    balance_out: &str,
+    weight_out: &str,
+    amount_out: &str,
+    swap_fee: &str,
+) -> Result<String, JsError> {
+    Ok(in_given_out([balance_in, weight_in, balance_out, weight_out, amount_out, swap_fee])?)
+}
+
+/// `weighted_math::calc_spot_price`
+#[wasm_bindgen(js_name = calcSpotPrice)]
+pub fn calc_spot_price(
+    balance_in: &str,
+    weight_in: &str,
+    balance_out: &str,
+    weight_out: &str,
+    swap_fee: &str,
+) -> Result<String, JsError> {
+    Ok(spot_price([balance_in, weight_in, balance_out, weight_out, swap_fee])?)
+}
+
+/// `weighted_math::calculate_invariant`
+#[wasm_bindgen(js_name = calculateInvariant)]
+pub fn calculate_invariant(balances: Vec<String>, weights: Vec<String>) -> Result<String, JsError> {
+    Ok(invariant(&balances, &weights)?)
+}
+
+/// `weighted_math::calc_bpt_out_given_exact_tokens_in`
+#[wasm_bindgen(js_name = calcBptOutGivenExactTokensIn)]
+pub fn calc_bpt_out_given_exact_tokens_in(
+    balances: Vec<String>,
+    weights: Vec<String>,
+    amounts_in: Vec<String>,
+    total_bpt: &str,
+    swap_fee: &str,
+) -> Result<String, JsError> {
+    Ok(bpt_out_given_exact_tokens_in(&balances, &weights, &amounts_in, total_bpt, swap_fee)?)
+}
+
+/// `weighted_math::calc_tokens_out_given_exact_bpt_in`
+#[wasm_bindgen(js_name = calcTokensOutGivenExactBptIn)]
+pub fn calc_tokens_out_given_exact_bpt_in(
+    balances: Vec<String>,
+    bpt_in: &str,
+    total_bpt: &str,
+    exit_fee: &str,
+) -> Result<Vec<String>, JsError> {
+    Ok(tokens_out_given_exact_bpt_in(&balances, bpt_in, total_bpt, exit_fee)?)
+}
+
+// The exports themselves need a JS host; these check the string layer
+// around them against the native functions.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::from_f64 as fp;
+
+    fn s(v: U256) -> String {
+        v.to_string()
+    }
+
+    #[test]
+    fn string_quotes_match_native_math() {
+        let (b_in, w_in, b_out, w_out, fee) = (fp(1_000.0), fp(0.8), fp(2_500.0), fp(0.2), fp(0.003));
+        let args = [s(b_in), s(w_in), s(b_out), s(w_out), s(fp(10.0)), s(fee)];
+        let args = args.each_ref().map(String::as_str);
+        assert_eq!(out_given_in(args), Ok(s(weighted_math::calc_out_given_in(b_in, w_in, b_out, w_out, fp(10.0), fee))));
+        assert_eq!(in_given_out(args), Ok(s(weighted_math::calc_in_given_out(b_in, w_in, b_out, w_out, fp(10.0), fee))));
+        let price_args = [args[0], args[1], args[2], args[3], args[5]];
+        assert_eq!(spot_price(price_args), Ok(s(weighted_math::calc_spot_price(b_in, w_in, b_out, w_out, fee))));
+
+        let (balances, weights) = (vec![s(b_in), s(b_out)], vec![s(w_in), s(w_out)]);
+        assert_eq!(invariant(&balances, &weights), Ok(s(weighted_math::calculate_invariant(&[b_in, b_out], &[w_in, w_out]))));
+        let amounts = vec![s(fp(10.0)), s(fp(1.0))];
+        assert_eq!(
+            bpt_out_given_exact_tokens_in(&balances, &weights, &amounts, &s(fp(100.0)), &s(fee)),
+            Ok(s(weighted_math::calc_bpt_out_given_exact_tokens_in(
+                &[b_in, b_out],
+                &[w_in, w_out],
+                &[fp(10.0), fp(1.0)],
+                fp(100.0),
+                fee,
+            )))
+        );
+        let out = weighted_math::calc_tokens_out_given_exact_bpt_in(&[b_in, b_out], fp(1.0), fp(100.0), U256::zero());
+        assert_eq!(tokens_out_given_exact_bpt_in(&balances, &s(fp(1.0)), &s(fp(100.0)), "0"), Ok(out.into_iter().map(s).collect()));
+    }
+
+    #[test]
+    fn bad_input_is_reported() {
+        assert_eq!(invariant(&["1".into()], &["1e18".into()]), Err(QuoteError::Parse("1e18".into())));
+        assert_eq!(invariant(&["1".into()], &[]), Err(QuoteError::Math(MathError::LengthMismatch)));
+        assert_eq!(spot_price(["1", "1", "0", "1", "0"]), Err(QuoteError::Math(MathError::DivisionByZero)));
+    }
+}
@@ -0,0 +1,101 @@
+// Differential test against Balancer V2's Solidity math: every vector in
+// fixtures/balancer_v2.json must be reproduced within 1 wei, and vectors
+// where Solidity reverts must fail here too. See
+// fixtures/generate_balancer_vectors.py for how they are produced.
+use math::{fixed, log_exp_math, weighted_math, MathError, I256, U256};
+use serde_json::Value;
+
+const VECTORS: &str = include_str!("fixtures/balancer_v2.json");
+
+fn uint(v: &Value) -> U256 {
+    U256::from_dec_str(v.as_str().expect("decimal string")).expect("uint256")
+}
+
+fn int(v: &Value) -> I256 {
+    let s = v.as_str().expect("decimal string");
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s),
+    };
+    I256::from_sign_and_abs(negative, U256::from_dec_str(digits).expect("int256")).expect("int256")
+}
+
+fn uints(v: &Value) -> Vec<U256> {
+    v.as_array().expect("array").iter().map(uint).collect()
+}
+
+fn from_signed(r: Result<I256, MathError>) -> Result<Vec<I256>, MathError> {
+    r.map(|v| vec![v])
+}
+
+fn from_unsigned(r: Result<U256, MathError>) -> Result<Vec<I256>, MathError> {
+    r.map(|v| vec![I256::from_unsigned(v).expect("fits int256")])
+}
+
+/// Runs one vector's operation; results are widened to `I256` so signed and
+/// unsigned outputs compare the same way.
+fn run(op: &str, a: &[Value]) -> Result<Vec<I256>, MathError> {
+    match op {
+        "mul_down" => from_unsigned(fixed::try_mul_down(uint(&a[0]), uint(&a[1]))),
+        "mul_up" => from_unsigned(fixed::try_mul_up(uint(&a[0]), uint(&a[1]))),
+        "div_down" => from_unsigned(fixed::try_div_down(uint(&a[0]), uint(&a[1]))),
+        "div_up" => from_unsigned(fixed::try_div_up(uint(&a[0]), uint(&a[1]))),
+        "pow" => from_unsigned(log_exp_math::try_pow(uint(&a[0]), uint(&a[1]))),
+        "pow_down" => from_unsigned(fixed::try_pow_down(uint(&a[0]), uint(&a[1]))),
+        "pow_up" => from_unsigned(fixed::try_pow_up(uint(&a[0]), uint(&a[1]))),
+        "exp" => from_signed(log_exp_math::try_exp(int(&a[0]))),
+        "ln" => from_signed(log_exp_math::try_ln(int(&a[0]))),
+        "calculate_invariant" => {
+            from_unsigned(weighted_math::try_calculate_invariant(&uints(&a[0]), &uints(&a[1])))
+        }
+        "calc_out_given_in" => from_unsigned(weighted_math::try_calc_out_given_in(
+            uint(&a[0]), uint(&a[1]), uint(&a[2]), uint(&a[3]), uint(&a[4]), uint(&a[5]),
+        )),
+        "calc_in_given_out" => from_unsigned(weighted_math::try_calc_in_given_out(
+            uint(&a[0]), uint(&a[1]), uint(&a[2]), uint(&a[3]), uint(&a[4]), uint(&a[5]),
+        )),
+        "calc_bpt_out_given_exact_tokens_in" => from_unsigned(weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+            &uints(&a[0]), &uints(&a[1]), &uints(&a[2]), uint(&a[3]), uint(&a[4]),
+        )),
+        "calc_bpt_in_given_exact_tokens_out" => from_unsigned(weighted_math::try_calc_bpt_in_given_exact_tokens_out(
+            &uints(&a[0]), &uints(&a[1]), &uints(&a[2]), uint(&a[3]), uint(&a[4]),
+        )),
+        "calc_token_in_given_exact_bpt_out" => from_unsigned(weighted_math::try_calc_token_in_given_exact_bpt_out(
+            uint(&a[0]), uint(&a[1]), uint(&a[2]), uint(&a[3]), uint(&a[4]),
+        )),
+        "calc_token_out_given_exact_bpt_in" => from_unsigned(weighted_math::try_calc_token_out_given_exact_bpt_in(
+            uint(&a[0]), uint(&a[1]), uint(&a[2]), uint(&a[3]), uint(&a[4]),
+        )),
+        "calc_tokens_out_given_exact_bpt_in" => weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+            &uints(&a[0]), uint(&a[1]), uint(&a[2]), U256::zero(),
+        )
+        .map(|out| out.into_iter().map(|v| I256::from_unsigned(v).expect("fits int256")).collect()),
+        _ => panic!("unknown op {op}"),
+    }
+}
+
+#[test]
+fn matches_balancer_v2_within_one_wei() {
+    let vectors: Vec<Value> = serde_json::from_str(VECTORS).expect("valid fixture json");
+    assert!(!vectors.is_empty());
+    let one_wei = U256::one();
+    let mut failures = Vec::new();
+    for (i, v) in vectors.iter().enumerate() {
+        let op = v["op"].as_str().expect("op");
+        let args = v["args"].as_array().expect("args");
+        let got = run(op, args);
+        let ok = match (&v["expected"], &got) {
+            (Value::Null, result) => result.is_err(),
+            (_, Err(_)) => false,
+            (Value::Array(expected), Ok(got)) => {
+                expected.len() == got.len()
+                    && expected.iter().zip(got).all(|(e, g)| (*g - int(e)).unsigned_abs() <= one_wei)
+            }
+            (expected, Ok(got)) => (got[0] - int(expected)).unsigned_abs() <= one_wei,
+        };
+        if !ok {
+            failures.push(format!("#{i} {op}({:?}): expected {}, got {:?}", args, v["expected"], got));
+        }
+    }
+    assert!(failures.is_empty(), "{} mismatches:\n{}", failures.len(), failures.join("\n"));
+}
@@ -0,0 +1,477 @@
+#!/usr/bin/env python3
+"""Reference vectors for math/tests/balancer_vectors.rs.
+
+Transcribes Balancer V2's `LogExpMath.sol`, `FixedPoint.sol` and
+`WeightedMath.sol` statement by statement onto Python integers, with
+Solidity semantics made explicit: signed division truncates toward zero,
+the remainder takes the sign of the dividend, and every `require` /
+checked‑arithmetic failure raises `Revert`. Each vector's `args` follow
+the Rust function of the same name; reverting cases are emitted with
+`"expected": null`.
+
+Regenerate with:
+
+    python3 math/tests/fixtures/generate_balancer_vectors.py > math/tests/fixtures/balancer_v2.json
+"""
+import json
+import random
+
+
+class Revert(Exception):
+    pass
+
+
+UINT256_MAX = 2**256 - 1
+
+
+def uint(x):
+    if x < 0 or x > UINT256_MAX:
+        raise Revert("uint overflow")
+    return x
+
+
+def sdiv(a, b):
+    if b == 0:
+        raise Revert("division by zero")
+    q = abs(a) // abs(b)
+    return q if (a >= 0) == (b >= 0) else -q
+
+
+def smod(a, b):
+    if b == 0:
+        raise Revert("division by zero")
+    r = abs(a) % abs(b)
+    return r if a >= 0 else -r
+
+
+# ---------------------------------------------------------------- LogExpMath
+ONE_18 = 10**18
+ONE_20 = 10**20
+ONE_36 = 10**36
+MAX_NATURAL_EXPONENT = 130 * 10**18
+MIN_NATURAL_EXPONENT = -41 * 10**18
+LN_36_LOWER_BOUND = ONE_18 - 10**17
+LN_36_UPPER_BOUND = ONE_18 + 10**17
+MILD_EXPONENT_BOUND = 2**254 // ONE_20
+
+x0 = 128000000000000000000
+a0 = 38877084059945950922200000000000000000000000000000000000
+x1 = 64000000000000000000
+a1 = 6235149080811616882910000000
+x2 = 3200000000000000000000
+a2 = 7896296018268069516100000000000000
+x3 = 1600000000000000000000
+a3 = 888611052050787263676000000
+x4 = 800000000000000000000
+a4 = 298095798704172827474000
+x5 = 400000000000000000000
+a5 = 5459815003314423907810
+x6 = 200000000000000000000
+a6 = 738905609893065022723
+x7 = 100000000000000000000
+a7 = 271828182845904523536
+x8 = 50000000000000000000
+a8 = 164872127070012814685
+x9 = 25000000000000000000
+a9 = 128402541668774148407
+x10 = 12500000000000000000
+a10 = 113314845306682631683
+x11 = 6250000000000000000
+a11 = 106449445891785942956
+
+
+def lem_pow(x, y):
+    if y == 0:
+        return ONE_18
+    if x == 0:
+        return 0
+    if x >> 255 != 0:
+        raise Revert("X_OUT_OF_BOUNDS")
+    x_int256 = x
+    if y >= MILD_EXPONENT_BOUND:
+        raise Revert("Y_OUT_OF_BOUNDS")
+    y_int256 = y
+    if LN_36_LOWER_BOUND < x_int256 < LN_36_UPPER_BOUND:
+        ln_36_x = _ln_36(x_int256)
+        logx_times_y = sdiv(ln_36_x, ONE_18) * y_int256 + sdiv(smod(ln_36_x, ONE_18) * y_int256, ONE_18)
+    else:
+        logx_times_y = _ln(x_int256) * y_int256
+    logx_times_y = sdiv(logx_times_y, ONE_18)
+    if not (MIN_NATURAL_EXPONENT <= logx_times_y <= MAX_NATURAL_EXPONENT):
+        raise Revert("PRODUCT_OUT_OF_BOUNDS")
+    return uint(lem_exp(logx_times_y))
+
+
+def lem_exp(x):
+    if not (MIN_NATURAL_EXPONENT <= x <= MAX_NATURAL_EXPONENT):
+        raise Revert("INVALID_EXPONENT")
+    if x < 0:
+        return sdiv(ONE_18 * ONE_18, lem_exp(-x))
+    if x >= x0:
+        x -= x0
+        firstAN = a0
+    elif x >= x1:
+        x -= x1
+        firstAN = a1
+    else:
+        firstAN = 1
+    x *= 100
+    product = ONE_20
+    for xn, an in ((x2, a2), (x3, a3), (x4, a4), (x5, a5), (x6, a6), (x7, a7), (x8, a8), (x9, a9)):
+        if x >= xn:
+            x -= xn
+            product = sdiv(product * an, ONE_20)
+    seriesSum = ONE_20
+    term = x
+    seriesSum += term
+    for n in range(2, 13):
+        term = sdiv(sdiv(term * x, ONE_20), n)
+        seriesSum += term
+    return sdiv(sdiv(product * seriesSum, ONE_20) * firstAN, 100)
+
+
+def lem_ln(a):
+    if a <= 0:
+        raise Revert("OUT_OF_BOUNDS")
+    if LN_36_LOWER_BOUND < a < LN_36_UPPER_BOUND:
+        return sdiv(_ln_36(a), ONE_18)
+    return _ln(a)
+
+
+def _ln(a):
+    if a < ONE_18:
+        return -_ln(sdiv(ONE_18 * ONE_18, a))
+    sum_ = 0
+    if a >= a0 * ONE_18:
+        a = sdiv(a, a0)
+        sum_ += x0
+    if a >= a1 * ONE_18:
+        a = sdiv(a, a1)
+        sum_ += x1
+    sum_ *= 100
+    a *= 100
+    for xn, an in ((x2, a2), (x3, a3), (x4, a4), (x5, a5), (x6, a6),
+                   (x7, a7), (x8, a8), (x9, a9), (x10, a10), (x11, a11)):
+        if a >= an:
+            a = sdiv(a * ONE_20, an)
+            sum_ += xn
+    z = sdiv((a - ONE_20) * ONE_20, a + ONE_20)
+    z_squared = sdiv(z * z, ONE_20)
+    num = z
+    seriesSum = num
+    for d in (3, 5, 7, 9, 11):
+        num = sdiv(num * z_squared, ONE_20)
+        seriesSum += sdiv(num, d)
+    seriesSum *= 2
+    return sdiv(sum_ + seriesSum, 100)
+
+
+def _ln_36(x):
+    x *= ONE_18
+    z = sdiv((x - ONE_36) * ONE_36, x + ONE_36)
+    z_squared = sdiv(z * z, ONE_36)
+    num = z
+    seriesSum = num
+    for d in (3, 5, 7, 9, 11, 13, 15):
+        num = sdiv(num * z_squared, ONE_36)
+        seriesSum += sdiv(num, d)
+    return seriesSum * 2
+
+
+# ---------------------------------------------------------------- FixedPoint
+ONE = ONE_18
+MAX_POW_RELATIVE_ERROR = 10000
+
+
+def add(a, b):
+    return uint(a + b)
+
+
+def sub(a, b):
+    return uint(a - b)
+
+
+def mulDown(a, b):
+    return uint(a * b) // ONE
+
+
+def mulUp(a, b):
+    product = uint(a * b)
+    return 0 if product == 0 else (product - 1) // ONE + 1
+
+
+def divDown(a, b):
+    if b == 0:
+        raise Revert("ZERO_DIVISION")
+    return uint(a * ONE) // b
+
+
+def divUp(a, b):
+    if b == 0:
+        raise Revert("ZERO_DIVISION")
+    if a == 0:
+        return 0
+    return (uint(a * ONE) - 1) // b + 1
+
+
+def powDown(x, y):
+    if y == ONE:
+        return x
+    if y == 2 * ONE:
+        return mulDown(x, x)
+    if y == 4 * ONE:
+        square = mulDown(x, x)
+        return mulDown(square, square)
+    raw = lem_pow(x, y)
+    maxError = add(mulUp(raw, MAX_POW_RELATIVE_ERROR), 1)
+    return 0 if raw < maxError else sub(raw, maxError)
+
+
+def powUp(x, y):
+    if y == ONE:
+        return x
+    if y == 2 * ONE:
+        return mulUp(x, x)
+    if y == 4 * ONE:
+        square = mulUp(x, x)
+        return mulUp(square, square)
+    raw = lem_pow(x, y)
+    maxError = add(mulUp(raw, MAX_POW_RELATIVE_ERROR), 1)
+    return add(raw, maxError)
+
+
+def complement(x):
+    return ONE - x if x < ONE else 0
+
+
+# -------------------------------------------------------------- WeightedMath
+# The pool's swap fee handling (BaseWeightedPool / BaseMinimalSwapInfoPool)
+# is folded into the swap functions, as the Rust API takes the fee.
+
+def calculateInvariant(normalizedWeights, balances):
+    invariant = ONE
+    for w, b in zip(normalizedWeights, balances):
+        invariant = mulDown(invariant, powDown(b, w))
+    if invariant <= 0:
+        raise Revert("ZERO_INVARIANT")
+    return invariant
+
+
+MAX_IN_RATIO = 3 * 10**17
+MAX_OUT_RATIO = 3 * 10**17
+
+
+def calcOutGivenIn(balanceIn, weightIn, balanceOut, weightOut, amountIn, swapFee):
+    amountIn = sub(amountIn, mulUp(amountIn, swapFee))
+    if amountIn > mulDown(balanceIn, MAX_IN_RATIO):
+        raise Revert("MAX_IN_RATIO")
+    denominator = add(balanceIn, amountIn)
+    base = divUp(balanceIn, denominator)
+    exponent = divDown(weightIn, weightOut)
+    power = powUp(base, exponent)
+    return mulDown(balanceOut, complement(power))
+
+
+def calcInGivenOut(balanceIn, weightIn, balanceOut, weightOut, amountOut, swapFee):
+    if amountOut > mulDown(balanceOut, MAX_OUT_RATIO):
+        raise Revert("MAX_OUT_RATIO")
+    base = divUp(balanceOut, sub(balanceOut, amountOut))
+    exponent = divUp(weightOut, weightIn)
+    power = powUp(base, exponent)
+    ratio = sub(power, ONE)
+    amountIn = mulUp(balanceIn, ratio)
+    return divUp(amountIn, complement(swapFee))
+
+
+def calcBptOutGivenExactTokensIn(balances, normalizedWeights, amountsIn, bptTotalSupply, swapFeePercentage):
+    balanceRatiosWithFee = []
+    invariantRatioWithFees = 0
+    for i in range(len(balances)):
+        balanceRatiosWithFee.append(divDown(add(balances[i], amountsIn[i]), balances[i]))
+        invariantRatioWithFees = add(invariantRatioWithFees, mulDown(balanceRatiosWithFee[i], normalizedWeights[i]))
+    invariantRatio = ONE
+    for i in range(len(balances)):
+        if balanceRatiosWithFee[i] > invariantRatioWithFees:
+            nonTaxableAmount = mulDown(balances[i], sub(invariantRatioWithFees, ONE))
+            taxableAmount = sub(amountsIn[i], nonTaxableAmount)
+            swapFee = mulUp(taxableAmount, swapFeePercentage)
+            amountInWithoutFee = add(nonTaxableAmount, sub(taxableAmount, swapFee))
+        else:
+            amountInWithoutFee = amountsIn[i]
+        balanceRatio = divDown(add(balances[i], amountInWithoutFee), balances[i])
+        invariantRatio = mulDown(invariantRatio, powDown(balanceRatio, normalizedWeights[i]))
+    if invariantRatio > ONE:
+        return mulDown(bptTotalSupply, sub(invariantRatio, ONE))
+    return 0
+
+
+def calcTokenInGivenExactBptOut(balance, normalizedWeight, bptAmountOut, bptTotalSupply, swapFeePercentage):
+    invariantRatio = divUp(add(bptTotalSupply, bptAmountOut), bptTotalSupply)
+    balanceRatio = powUp(invariantRatio, divUp(ONE, normalizedWeight))
+    amountInWithoutFee = mulUp(balance, sub(balanceRatio, ONE))
+    taxablePercentage = complement(normalizedWeight)
+    taxableAmount = mulUp(amountInWithoutFee, taxablePercentage)
+    nonTaxableAmount = sub(amountInWithoutFee, taxableAmount)
+    return add(nonTaxableAmount, divUp(taxableAmount, complement(swapFeePercentage)))
+
+
+def calcBptInGivenExactTokensOut(balances, normalizedWeights, amountsOut, bptTotalSupply, swapFeePercentage):
+    balanceRatiosWithoutFee = []
+    invariantRatioWithoutFees = 0
+    for i in range(len(balances)):
+        balanceRatiosWithoutFee.append(divUp(sub(balances[i], amountsOut[i]), balances[i]))
+        invariantRatioWithoutFees = add(invariantRatioWithoutFees, mulUp(balanceRatiosWithoutFee[i], normalizedWeights[i]))
+    invariantRatio = ONE
+    for i in range(len(balances)):
+        if invariantRatioWithoutFees > balanceRatiosWithoutFee[i]:
+            nonTaxableAmount = mulDown(balances[i], complement(invariantRatioWithoutFees))
+            taxableAmount = sub(amountsOut[i], nonTaxableAmount)
+            amountOutWithFee = add(nonTaxableAmount, divUp(taxableAmount, complement(swapFeePercentage)))
+        else:
+            amountOutWithFee = amountsOut[i]
+        balanceRatio = divDown(sub(balances[i], amountOutWithFee), balances[i])
+        invariantRatio = mulDown(invariantRatio, powDown(balanceRatio, normalizedWeights[i]))
+    return mulUp(bptTotalSupply, complement(invariantRatio))
+
+
+def calcTokenOutGivenExactBptIn(balance, normalizedWeight, bptAmountIn, bptTotalSupply, swapFeePercentage):
+    invariantRatio = divUp(sub(bptTotalSupply, bptAmountIn), bptTotalSupply)
+    balanceRatio = powUp(invariantRatio, divDown(ONE, normalizedWeight))
+    amountOutWithoutFee = mulDown(balance, complement(balanceRatio))
+    taxablePercentage = complement(normalizedWeight)
+    taxableAmount = mulUp(amountOutWithoutFee, taxablePercentage)
+    nonTaxableAmount = sub(amountOutWithoutFee, taxableAmount)
+    return add(nonTaxableAmount, mulDown(taxableAmount, complement(swapFeePercentage)))
+
+
+def calcTokensOutGivenExactBptIn(balances, bptAmountIn, totalBPT):
+    bptRatio = divDown(bptAmountIn, totalBPT)
+    return [mulDown(b, bptRatio) for b in balances]
+
+
+# ------------------------------------------------------------------ vectors
+rng = random.Random(0x5EED)
+PER_OP = 40
+
+
+def log_uniform(lo, hi):
+    """Integer spread evenly over the orders of magnitude in [lo, hi]."""
+    lo_d, hi_d = len(str(lo)) - 1, len(str(hi)) - 1
+    digits = rng.randint(lo_d, hi_d)
+    return max(lo, min(hi, rng.randint(10**digits, 10**(digits + 1) - 1)))
+
+
+def balance():
+    # 1e-6 .. 1e15 tokens
+    return log_uniform(10**12, 10**33)
+
+
+def weights(n):
+    raw = [rng.randint(1, 100) for _ in range(n)]
+    total = sum(raw)
+    ws = [max(10**16, r * ONE // total) for r in raw]
+    ws[-1] = ONE - sum(ws[:-1])
+    if ws[-1] < 10**16:
+        return weights(n)
+    return ws
+
+
+def fee():
+    return rng.choice([0, 10**12, rng.randint(10**12, 10**17)])
+
+
+def fraction(lo_ppm, hi_ppm):
+    return rng.randint(lo_ppm, hi_ppm) * 10**12
+
+
+def pow_case():
+    kind = rng.randint(0, 3)
+    if kind == 0:
+        # close to one: the 36‑decimal ln path
+        return rng.randint(LN_36_LOWER_BOUND + 1, LN_36_UPPER_BOUND - 1), rng.randint(1, 50 * ONE)
+    if kind == 1:
+        return log_uniform(1, 10**30), rng.randint(1, 3 * ONE)
+    if kind == 2:
+        # the integer exponents FixedPoint special‑cases
+        return log_uniform(10**15, 10**24), rng.choice([ONE, 2 * ONE, 4 * ONE])
+    return rng.randint(1, 2 * ONE), rng.randint(1, 100 * ONE)
+
+
+def s(v):
+    return [s(x) for x in v] if isinstance(v, list) else str(v)
+
+
+vectors = []
+
+
+def emit(op, fn, make_args):
+    made = 0
+    while made < PER_OP:
+        args = make_args()
+        try:
+            expected = s(fn(*args))
+        except Revert:
+            expected = None
+        vectors.append({"op": op, "args": [s(a) for a in args], "expected": expected})
+        made += 1
+
+
+emit("mul_down", mulDown, lambda: (log_uniform(1, 10**40), log_uniform(1, 10**30)))
+emit("mul_up", mulUp, lambda: (log_uniform(1, 10**40), log_uniform(1, 10**30)))
+emit("div_down", divDown, lambda: (log_uniform(1, 10**40), log_uniform(1, 10**30)))
+emit("div_up", divUp, lambda: (log_uniform(1, 10**40), log_uniform(1, 10**30)))
+emit("pow", lem_pow, pow_case)
+emit("pow_down", powDown, pow_case)
+emit("pow_up", powUp, pow_case)
+emit("exp", lem_exp, lambda: (rng.randint(-42 * ONE, 131 * ONE),))
+emit("ln", lem_ln, lambda: (rng.choice([
+    rng.randint(LN_36_LOWER_BOUND + 1, LN_36_UPPER_BOUND - 1),
+    log_uniform(1, 10**60),
+]),))
+
+
+def two_or_more():
+    n = rng.randint(2, 8)
+    return n, [balance() for _ in range(n)], weights(n)
+
+
+def invariant_case():
+    _, bs, ws = two_or_more()
+    return bs, ws
+
+
+def swap_case():
+    _, bs, ws = two_or_more()
+    return bs[0], ws[0], bs[1], ws[1]
+
+
+emit("calculate_invariant", lambda bs, ws: calculateInvariant(ws, bs), invariant_case)
+emit("calc_out_given_in", calcOutGivenIn, lambda: (
+    lambda b_in, w_in, b_out, w_out: (b_in, w_in, b_out, w_out, mulDown(b_in, fraction(1, 300_000)), fee())
+)(*swap_case()))
+emit("calc_in_given_out", calcInGivenOut, lambda: (
+    lambda b_in, w_in, b_out, w_out: (b_in, w_in, b_out, w_out, mulDown(b_out, fraction(1, 300_000)), fee())
+)(*swap_case()))
+
+
+def tokens_case(cap_ppm):
+    _, bs, ws = two_or_more()
+    amounts = [mulDown(b, fraction(0, cap_ppm)) for b in bs]
+    return bs, ws, amounts, log_uniform(10**18, 10**33), fee()
+
+
+def single_case(cap_ppm):
+    _, bs, ws = two_or_more()
+    total = log_uniform(10**18, 10**33)
+    return bs[0], ws[0], mulDown(total, fraction(1, cap_ppm)), total, fee()
+
+
+emit("calc_bpt_out_given_exact_tokens_in", calcBptOutGivenExactTokensIn, lambda: tokens_case(1_000_000))
+emit("calc_bpt_in_given_exact_tokens_out", calcBptInGivenExactTokensOut, lambda: tokens_case(300_000))
+emit("calc_token_in_given_exact_bpt_out", calcTokenInGivenExactBptOut, lambda: single_case(300_000))
+emit("calc_token_out_given_exact_bpt_in", calcTokenOutGivenExactBptIn, lambda: single_case(300_000))
+emit("calc_tokens_out_given_exact_bpt_in", calcTokensOutGivenExactBptIn, lambda: (
+    lambda bs, total: (bs, mulDown(total, fraction(1, 1_000_000)), total)
+)([balance() for _ in range(rng.randint(2, 8))], log_uniform(10**18, 10**33)))
+
+print("[\n" + ",\n".join(json.dumps(v) for v in vectors) + "\n]")
@@ -0,0 +1,202 @@
+// Property tests for the math crate: every rounding choice must leave
+// the pool no worse off, whatever the balances, weights and fees.
+#![allow(clippy::manual_div_ceil, clippy::assign_op_pattern)] // construct_uint! expansion
+
+use math::{fixed, weighted_math, U256};
+use proptest::prelude::*;
+use uint::construct_uint;
+
+construct_uint! {
+    /// Wide enough for r^q · 1e18^p with the exponents and roots tested below.
+    struct U2048(32);
+}
+
+const E18: u128 = 1_000_000_000_000_000_000;
+
+/// Fraction `ppm / 1e6` as an 18‑dec value.
+fn ppm(ppm: u64) -> U256 {
+    U256::from(ppm) * U256::from(1_000_000_000_000u64)
+}
+
+fn wide(v: U256) -> U2048 {
+    let mut bytes = [0u8; 32];
+    v.to_little_endian(&mut bytes);
+    U2048::from_little_endian(&bytes)
+}
+
+/// Exact comparison of r / 1e18 against (x / 1e18)^(p / q), by raising
+/// both sides to the q‑th power: r^q · 1e18^p against x^p · 1e18^q.
+fn cmp_pow(r: U256, x: U256, p: u32, q: u32) -> core::cmp::Ordering {
+    let one = wide(fixed::ONE);
+    let lhs = wide(r).pow(q.into()) * one.pow(p.into());
+    let rhs = wide(x).pow(p.into()) * one.pow(q.into());
+    lhs.cmp(&rhs)
+}
+
+/// Two‑token pool: balances between 1 and 1e12 tokens, complementary
+/// weights between 2 % and 98 %.
+fn pool() -> impl Strategy<Value = ([U256; 2], [U256; 2])> {
+    (1u128..=1_000_000_000_000, 1u128..=1_000_000_000_000, 20_000u64..=980_000).prop_map(|(b0, b1, w0)| {
+        let w0 = ppm(w0);
+        ([U256::from(b0 * E18), U256::from(b1 * E18)], [w0, fixed::ONE - w0])
+    })
+}
+
+/// Two‑token pool whose weight ratio w_in / w_out is p / q (up to the
+/// rounding of the weights, which only ever makes the pool's exponent
+/// more conservative): balances between 1e‑3 and 1e12 tokens.
+fn rational_pool() -> impl Strategy<Value = ([U256; 2], [U256; 2], (u32, u32))> {
+    (
+        1u128..=1_000_000_000_000_000,
+        1u128..=1_000_000_000_000_000,
+        prop::sample::select(vec![(1u32, 1u32), (1, 2), (2, 1), (1, 3), (3, 1), (1, 4), (4, 1), (2, 3), (3, 2), (1, 9), (9, 1)]),
+    )
+        .prop_map(|(b0, b1, (p, q))| {
+            let w_in = fixed::ONE * U256::from(p) / U256::from(p + q);
+            let scale = U256::from(1_000_000_000_000_000u64);
+            ([U256::from(b0) * scale, U256::from(b1) * scale], [w_in, fixed::ONE - w_in], (p, q))
+        })
+}
+
+proptest! {
+    #[test]
+    fn proportional_join_then_exit_never_returns_more(
+        (balances, weights) in pool(),
+        share in 1u64..=500_000,
+        total_bpt in 1u128..=1_000_000_000_000,
+        fee in 0u64..=100_000,
+    ) {
+        let total_bpt = U256::from(total_bpt * E18);
+        let amounts_in = balances.map(|b| fixed::mul_down(b, ppm(share)));
+        let bpt_out = weighted_math::calc_bpt_out_given_exact_tokens_in(
+            &balances, &weights, &amounts_in, total_bpt, ppm(fee),
+        );
+        let balances_after = [balances[0] + amounts_in[0], balances[1] + amounts_in[1]];
+        let amounts_out = weighted_math::calc_tokens_out_given_exact_bpt_in(
+            &balances_after, bpt_out, total_bpt + bpt_out, U256::zero(),
+        );
+        for i in 0..2 {
+            prop_assert!(amounts_out[i] <= amounts_in[i]);
+        }
+    }
+
+    #[test]
+    fn withdrawing_a_deposit_costs_at_least_the_bpt_it_minted(
+        (balances, weights) in pool(),
+        in0 in 0u64..=300_000,
+        in1 in 0u64..=300_000,
+        total_bpt in 1u128..=1_000_000_000_000,
+        fee in 0u64..=100_000,
+    ) {
+        let total_bpt = U256::from(total_bpt * E18);
+        let amounts = [fixed::mul_down(balances[0], ppm(in0)), fixed::mul_down(balances[1], ppm(in1))];
+        let bpt_out = weighted_math::calc_bpt_out_given_exact_tokens_in(
+            &balances, &weights, &amounts, total_bpt, ppm(fee),
+        );
+        let balances_after = [balances[0] + amounts[0], balances[1] + amounts[1]];
+        let bpt_in = weighted_math::calc_bpt_in_given_exact_tokens_out(
+            &balances_after, &weights, &amounts, total_bpt + bpt_out, ppm(fee),
+        );
+        prop_assert!(bpt_in >= bpt_out);
+    }
+
+    #[test]
+    fn in_given_out_inverts_out_given_in(
+        (balances, weights) in pool(),
+        ratio in 100u64..=300_000,
+        fee in 0u64..=100_000,
+    ) {
+        let amount_in = fixed::mul_down(balances[0], ppm(ratio));
+        let out = weighted_math::calc_out_given_in(
+            balances[0], weights[0], balances[1], weights[1], amount_in, ppm(fee),
+        );
+        prop_assume!(!out.is_zero());
+        // outputs past MAX_OUT_RATIO cannot be bought back exactly
+        let back_in = weighted_math::try_calc_in_given_out(
+            balances[0], weights[0], balances[1], weights[1], out, ppm(fee),
+        );
+        prop_assume!(back_in != Err(math::MathError::MaxOutRatio));
+        let back_in = back_in.unwrap();
+        // the pow error bound is relative to the pool, not to the trade
+        let tolerance = fixed::mul_up(balances[0], U256::from(1_000_000u64)) + U256::from(1_000u64);
+        let diff = if back_in > amount_in { back_in - amount_in } else { amount_in - back_in };
+        prop_assert!(diff <= tolerance, "in {} back {} diff {}", amount_in, back_in, diff);
+    }
+
+    #[test]
+    fn swap_fees_never_decrease_the_invariant(
+        (balances, weights) in pool(),
+        ratio in 100u64..=300_000,
+        fee in 100u64..=100_000,
+    ) {
+        let amount_in = fixed::mul_down(balances[0], ppm(ratio));
+        let out = weighted_math::calc_out_given_in(
+            balances[0], weights[0], balances[1], weights[1], amount_in, ppm(fee),
+        );
+        let before = weighted_math::calculate_invariant(&balances, &weights);
+        let after = weighted_math::calculate_invariant(&[balances[0] + amount_in, balances[1] - out], &weights);
+        prop_assert!(after >= before, "before {} after {}", before, after);
+    }
+
+    #[test]
+    fn pow_down_and_pow_up_bracket_the_exact_power(
+        x in 10_000_000_000_000_000u128..=100_000_000_000_000_000_000,
+        (p, q) in prop::sample::select(vec![
+            (1u32, 4u32), (1, 2), (3, 4), (1, 1), (5, 4), (3, 2), (7, 4), (2, 1), (5, 2), (3, 1), (7, 2), (4, 1),
+        ]),
+    ) {
+        use core::cmp::Ordering::{Greater, Less};
+        let x = U256::from(x);
+        let y = fixed::ONE * U256::from(p) / U256::from(q);
+        prop_assert_ne!(cmp_pow(fixed::pow_down(x, y), x, p, q), Greater);
+        prop_assert_ne!(cmp_pow(fixed::pow_up(x, y), x, p, q), Less);
+    }
+
+    #[test]
+    fn roots_round_down_to_within_a_wei_per_1e12(
+        x in 1_000_000_000_000u128..=u128::MAX,
+        n in 2u32..=fixed::MAX_ROOT_DEGREE,
+    ) {
+        use core::cmp::Ordering::Greater;
+        let x = U256::from(x);
+        let root = fixed::nth_root(x, n);
+        prop_assert_ne!(cmp_pow(root, x, 1, n), Greater);
+        let slack = if n == 2 { U256::one() } else { root / U256::from(1_000_000_000_000u64) + 1 };
+        prop_assert_eq!(cmp_pow(root + slack, x, 1, n), Greater);
+        if n == 2 {
+            prop_assert_eq!(fixed::sqrt(x), root);
+        }
+    }
+
+    #[test]
+    fn out_given_in_never_pays_more_than_the_exact_curve(
+        (balances, weights, (p, q)) in rational_pool(),
+        ratio in 1u64..=300_000,
+        fee in prop::sample::select(vec![0u64, 100, 3_000, 100_000]),
+    ) {
+        let [b_in, b_out] = balances;
+        let amount_in = fixed::mul_down(b_in, ppm(ratio));
+        let out = weighted_math::calc_out_given_in(b_in, weights[0], b_out, weights[1], amount_in, ppm(fee));
+        let net_in = amount_in - fixed::mul_up(amount_in, ppm(fee));
+        // exact curve: (b_out − out)^q · (b_in + net_in)^p ≥ b_out^q · b_in^p
+        let lhs = wide(b_out - out).pow(q.into()) * wide(b_in + net_in).pow(p.into());
+        let rhs = wide(b_out).pow(q.into()) * wide(b_in).pow(p.into());
+        prop_assert!(lhs >= rhs, "out {} for {} in", out, amount_in);
+    }
+
+    #[test]
+    fn in_given_out_never_charges_less_than_the_exact_curve(
+        (balances, weights, (p, q)) in rational_pool(),
+        ratio in 1u64..=300_000,
+        fee in prop::sample::select(vec![0u64, 100, 3_000, 100_000]),
+    ) {
+        let [b_in, b_out] = balances;
+        let amount_out = fixed::mul_down(b_out, ppm(ratio));
+        let amount_in = weighted_math::calc_in_given_out(b_in, weights[0], b_out, weights[1], amount_out, ppm(fee));
+        // the part of the amount in left after the fee, rounded against the pool
+        let net_in = fixed::mul_down(amount_in, fixed::ONE - ppm(fee));
+        let lhs = wide(b_out - amount_out).pow(q.into()) * wide(b_in + net_in).pow(p.into());
+        let rhs = wide(b_out).pow(q.into()) * wide(b_in).pow(p.into());
+        prop_assert!(lhs >= rhs, "in {} for {} out", amount_in, amount_out);
+    }
+}
@@ -0,0 +1,408 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use fee_distributor::Distributor;
+use vault::program::Vault;
+use vault::{Action, Authorizer, BatchSwapStep, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("FeeBurnnJ3pACDD6eWo2tzWGnZ5tqXFuwTyc8V39cXY");
+
+/// Fee tokens a burner may have limits for
+pub const MAX_BURN_TOKENS: usize = 16;
+/// Fixed‑point scale of `TokenLimit::min_price`
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Protocol fee burner: turns the Vault's collected protocol fees, held
+/// in many tokens, into one target token and forwards it to that token's
+/// fee distributor. Its ["burner-authority", fee_burner] PDA must hold
+/// the Vault's `CollectFees` role; holders of the same role run it. Each
+/// fee token is swapped through the protocol's own pools with
+/// `vault::batch_swap`, with steps chosen by the caller (e.g. from the
+/// SOR) but a floor price per token set beforehand, so a compromised or
+/// careless caller cannot sell fees cheaply.
+#[program]
+pub mod fee_burner {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create a vault's burner, paying into `distributor`
+    ---------------------------------------------------------------- */
+    pub fn initialize_fee_burner(ctx: Context<InitializeFeeBurner>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::CollectFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let burner = &mut ctx.accounts.fee_burner;
+        burner.vault          = ctx.accounts.vault_state.key();
+        burner.target_mint    = ctx.accounts.distributor.token_mint;
+        burner.distributor    = ctx.accounts.distributor.key();
+        burner.limits         = Vec::new();
+        burner.authority_bump = ctx.bumps.burner_authority;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Set the floor price a fee token is sold at – `CollectFees` only
+       `min_price`: target units per fee token unit, 18‑dec
+    ---------------------------------------------------------------- */
+    pub fn set_token_limit(ctx: Context<SetTokenLimit>, min_price: u128) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::CollectFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(min_price > 0, ErrorCode::InvalidLimit);
+        let burner = &mut ctx.accounts.fee_burner;
+        let mint   = ctx.accounts.token_mint.key();
+        require_keys_neq!(mint, burner.target_mint, ErrorCode::InvalidLimit);
+        match burner.limits.iter_mut().find(|l| l.mint == mint) {
+            Some(limit) => limit.min_price = min_price,
+            None => {
+                require!(burner.limits.len() < MAX_BURN_TOKENS, ErrorCode::TooManyTokens);
+                burner.limits.push(TokenLimit { mint, min_price });
+            }
+        }
+        emit!(TokenLimitSet { fee_burner: burner.key(), mint, min_price });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Withdraw `amount` of a fee token, swap it for the target token
+       and forward all the target token held – `CollectFees` only
+       remaining_accounts: batch swap accounts with assets
+       [fee token, target token], the burner's own token accounts as
+       the user side
+    ---------------------------------------------------------------- */
+    pub fn burn_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BurnFees<'info>>,
+        amount: u64,
+        steps: Vec<BatchSwapStep>,
+        deadline: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::CollectFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let burner   = &ctx.accounts.fee_burner;
+        let fee_mint = ctx.accounts.fee_mint.key();
+        let limit    = burner.limits.iter().find(|l| l.mint == fee_mint).ok_or(ErrorCode::InvalidLimit)?;
+        let min_out  = min_amount_out(amount, limit.min_price)?;
+
+        let rem = ctx.remaining_accounts;
+        require!(
+            rem.len() > 4
+                && rem[0].key() == ctx.accounts.burner_fee_account.key()
+                && rem[2].key() == ctx.accounts.burner_target_account.key(),
+            ErrorCode::InvalidSwapAccounts
+        );
+
+        let burner_key = burner.key();
+        let bump_arr   = [burner.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"burner-authority", burner_key.as_ref(), &bump_arr];
+
+        // 1. collect the fees
+        vault::cpi::withdraw_collected_fees(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_program.to_account_info(),
+                vault::cpi::accounts::WithdrawCollectedFees {
+                    vault_state:    ctx.accounts.vault_state.to_account_info(),
+                    fees_collector: ctx.accounts.fees_collector.clone(),
+                    authorizer:     ctx.accounts.authorizer.to_account_info(),
+                    fee_account:    ctx.accounts.fee_account.clone(),
+                    mint:           ctx.accounts.fee_mint.to_account_info(),
+                    recipient:      ctx.accounts.burner_fee_account.to_account_info(),
+                    authority:      ctx.accounts.burner_authority.clone(),
+                    token_program:  ctx.accounts.token_program.to_account_info(),
+                },
+                &[seed_slice],
+            ),
+            amount,
+        )?;
+
+        // 2. sell them at no less than the floor price
+        let deltas = vault::cpi::batch_swap(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_program.to_account_info(),
+                vault::cpi::accounts::BatchSwap {
+                    vault_state:      ctx.accounts.vault_state.to_account_info(),
+                    vault_authority:  ctx.accounts.vault_authority.clone(),
+                    user:             ctx.accounts.burner_authority.clone(),
+                    sender:           ctx.accounts.burner_authority.clone(),
+                    relayer_approval: None,
+                    permit_nonce:     None,
+                    instructions:     None,
+                    system_program:   None,
+                    fees_collector:   ctx.accounts.fees_collector.clone(),
+                    token_program:    ctx.accounts.token_program.to_account_info(),
+                },
+                &[seed_slice],
+            )
+            .with_remaining_accounts(rem.to_vec()),
+            steps,
+            vec![amount as i64, -(min_out as i64)],
+            deadline,
+        )?
+        .get();
+
+        // 3. forward everything bought, and anything left from before
+        ctx.accounts.burner_target_account.reload()?;
+        let forwarded = ctx.accounts.burner_target_account.amount;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.burner_target_account.to_account_info(),
+                    to:        ctx.accounts.distributor_token_vault.to_account_info(),
+                    authority: ctx.accounts.burner_authority.clone(),
+                },
+                &[seed_slice],
+            ),
+            forwarded,
+        )?;
+
+        emit!(FeesBurned {
+            fee_burner: burner_key,
+            mint:       fee_mint,
+            amount_in:  deltas[0].unsigned_abs(),
+            amount_out: deltas[1].unsigned_abs(),
+            forwarded,
+        });
+        Ok(())
+    }
+}
+
+/// Least target token `amount` fee tokens may fetch at `min_price`.
+pub fn min_amount_out(amount: u64, min_price: u128) -> Result<u64> {
+    let out = (amount as u128)
+        .checked_mul(min_price)
+        .ok_or(ErrorCode::MathOverflow)?
+        .div_ceil(PRICE_PRECISION);
+    // batch swap limits are i64
+    require!(out <= i64::MAX as u128, ErrorCode::MathOverflow);
+    Ok(out as u64)
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializeFeeBurner<'info> {
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Box<Account<'info, Authorizer>>,
+
+    /// Must hold the `CollectFees` role; pays for the burner
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Where the target token goes; its token is the target token
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(address = distributor.token_mint)]
+    pub target_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + FeeBurner::INIT_SPACE,
+        seeds = [b"fee-burner", vault_state.key().as_ref()],
+        bump
+    )]
+    pub fee_burner: Box<Account<'info, FeeBurner>>,
+
+    /// CHECK: PDA holding the burner's tokens; needs the `CollectFees` role
+    #[account(seeds = [b"burner-authority", fee_burner.key().as_ref()], bump)]
+    pub burner_authority: AccountInfo<'info>,
+
+    /// Target tokens bought, until forwarded
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"burner-tokens", fee_burner.key().as_ref(), target_mint.key().as_ref()],
+        bump,
+        token::mint = target_mint,
+        token::authority = burner_authority
+    )]
+    pub burner_target_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTokenLimit<'info> {
+    #[account(mut)]
+    pub fee_burner: Box<Account<'info, FeeBurner>>,
+
+    #[account(
+        seeds = [b"authorizer", fee_burner.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Box<Account<'info, Authorizer>>,
+
+    /// Must hold the `CollectFees` role; pays for the fee token account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA holding the burner's tokens
+    #[account(seeds = [b"burner-authority", fee_burner.key().as_ref()], bump = fee_burner.authority_bump)]
+    pub burner_authority: AccountInfo<'info>,
+
+    /// Fee tokens collected, until sold
+    #[account(
+        init_if_needed,
+        payer = authority,
+        seeds = [b"burner-tokens", fee_burner.key().as_ref(), token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = burner_authority
+    )]
+    pub burner_token_account: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnFees<'info> {
+    #[account(
+        seeds = [b"fee-burner", vault_state.key().as_ref()],
+        bump,
+        has_one = distributor
+    )]
+    pub fee_burner: Box<Account<'info, FeeBurner>>,
+
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Box<Account<'info, Authorizer>>,
+
+    /// Must hold the `CollectFees` role
+    pub authority: Signer<'info>,
+
+    /// CHECK: PDA holding the burner's tokens; signs the withdrawal and swap
+    #[account(mut, seeds = [b"burner-authority", fee_burner.key().as_ref()], bump = fee_burner.authority_bump)]
+    pub burner_authority: AccountInfo<'info>,
+
+    pub fee_mint: Account<'info, Mint>,
+
+    /// CHECK: the vault's fees collector; checked by the vault
+    pub fees_collector: AccountInfo<'info>,
+
+    /// CHECK: the collector's account of `fee_mint`; checked by the vault
+    #[account(mut)]
+    pub fee_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"burner-tokens", fee_burner.key().as_ref(), fee_mint.key().as_ref()],
+        bump
+    )]
+    pub burner_fee_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"burner-tokens", fee_burner.key().as_ref(), fee_burner.target_mint.as_ref()],
+        bump
+    )]
+    pub burner_target_account: Box<Account<'info, TokenAccount>>,
+
+    pub distributor: Box<Account<'info, Distributor>>,
+
+    #[account(mut, address = distributor.token_vault)]
+    pub distributor_token_vault: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: vault authority PDA; checked by the vault
+    #[account(mut)]
+    pub vault_authority: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, Vault>,
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// A vault's fee burner, PDA'd by ["fee-burner", vault]
+#[account]
+#[derive(InitSpace)]
+pub struct FeeBurner {
+    pub vault: Pubkey,
+    /// The token fees are converted into
+    pub target_mint: Pubkey,
+    /// Fee distributor of `target_mint` receiving the proceeds
+    pub distributor: Pubkey,
+    /// Floor prices of the fee tokens that may be sold
+    #[max_len(MAX_BURN_TOKENS)]
+    pub limits: Vec<TokenLimit>,
+    /// Bump of the ["burner-authority", fee_burner] PDA
+    pub authority_bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, InitSpace)]
+pub struct TokenLimit {
+    pub mint: Pubkey,
+    /// Least target units per fee token unit, 18‑dec
+    pub min_price: u128,
+}
+
+#[event]
+pub struct TokenLimitSet {
+    pub fee_burner: Pubkey,
+    pub mint: Pubkey,
+    pub min_price: u128,
+}
+
+#[event]
+pub struct FeesBurned {
+    pub fee_burner: Pubkey,
+    pub mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Target tokens sent to the distributor
+    pub forwarded: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Token has no usable floor price")]
+    InvalidLimit,
+    #[msg("Burner already has limits for the maximum number of tokens")]
+    TooManyTokens,
+    #[msg("Swap assets must be the burner's fee and target token accounts")]
+    InvalidSwapAccounts,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floor_price_rounds_the_minimum_up() {
+        // 1 fee unit ≥ 2.5 target units
+        assert_eq!(min_amount_out(1_000, 2_500_000_000_000_000_000).unwrap(), 2_500);
+        assert_eq!(min_amount_out(3, 333_333_333_333_333_334).unwrap(), 2);
+        assert!(min_amount_out(u64::MAX, PRICE_PRECISION).is_err());
+    }
+}
@@ -0,0 +1,362 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use voting_escrow::{Escrow, Lock, WEEK};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("FeeDeg1f2ZeqiKqULXKqNAZSxcByEBCiecN8m52PS6a");
+
+/// Protocol fee distributor: the fees the Vault's collector withdraws
+/// (or anyone sends) to a distributor's token account are checkpointed
+/// into the week they arrive in, and once that week is over every lock
+/// of the distributor's vote escrow may claim
+/// `week tokens · lock power / total power`, both powers read at the
+/// week's start. One distributor exists per (escrow, fee token).
+///
+/// Claims walk a holder's weeks forward from a cursor, so fees are paid
+/// at most once; weeks whose record is not passed earned nothing for the
+/// holder or may be skipped. A lock's past power is read from its
+/// history, so claim before the lock changes `LOCK_HISTORY` more times
+/// and before withdrawing it.
+#[program]
+pub mod fee_distributor {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create the distributor of one fee token for a vote escrow
+       Fees are distributed from the first week starting at or after
+       `start_time`.
+    ---------------------------------------------------------------- */
+    pub fn initialize_distributor(ctx: Context<InitializeDistributor>, start_time: i64) -> Result<()> {
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.escrow             = ctx.accounts.escrow.key();
+        distributor.token_mint         = ctx.accounts.token_mint.key();
+        distributor.token_vault        = ctx.accounts.token_vault.key();
+        distributor.start_week         = round_to_week(start_time + WEEK - 1);
+        distributor.last_token_balance = 0;
+        distributor.authority_bump     = ctx.bumps.distributor_authority;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Credit fees received since the last checkpoint to this week;
+       permissionless. `week` is the start of the current week.
+    ---------------------------------------------------------------- */
+    pub fn checkpoint_token(ctx: Context<CheckpointToken>, week: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let distributor = &mut ctx.accounts.distributor;
+        require!(week == round_to_week(now), ErrorCode::InvalidWeek);
+        require!(week >= distributor.start_week, ErrorCode::NotStarted);
+
+        let record = &mut ctx.accounts.week_record;
+        if record.distributor == Pubkey::default() {
+            record.distributor = distributor.key();
+            record.week        = week;
+        }
+        let balance  = ctx.accounts.token_vault.amount;
+        let received = balance.checked_sub(distributor.last_token_balance).ok_or(ErrorCode::MathOverflow)?;
+        record.tokens = record.tokens.checked_add(received).ok_or(ErrorCode::MathOverflow)?;
+        distributor.last_token_balance = balance;
+
+        emit!(TokensCheckpointed { distributor: distributor.key(), week, amount: received });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Claim the caller's share of finished weeks
+       Remaining accounts: the `WeekRecord`s to claim, weeks ascending
+       and not before the caller's cursor.
+    ---------------------------------------------------------------- */
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let current_week = round_to_week(now);
+        let distributor = &mut ctx.accounts.distributor;
+        let user_claim  = &mut ctx.accounts.user_claim;
+        if user_claim.distributor == Pubkey::default() {
+            user_claim.distributor = distributor.key();
+            user_claim.user        = ctx.accounts.user.key();
+            user_claim.week_cursor = distributor.start_week;
+        }
+
+        let mut amount = 0u64;
+        for info in ctx.remaining_accounts {
+            require_keys_eq!(*info.owner, crate::ID, ErrorCode::InvalidWeekRecord);
+            let record = WeekRecord::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+            require_keys_eq!(record.distributor, distributor.key(), ErrorCode::InvalidWeekRecord);
+            require!(record.week >= user_claim.week_cursor, ErrorCode::InvalidWeek);
+            require!(record.week < current_week, ErrorCode::WeekNotOver);
+
+            let share = claimable(&record, &ctx.accounts.escrow, &ctx.accounts.lock)?;
+            amount = amount.checked_add(share).ok_or(ErrorCode::MathOverflow)?;
+            user_claim.week_cursor = record.week + WEEK;
+        }
+
+        if amount > 0 {
+            distributor.last_token_balance = distributor
+                .last_token_balance
+                .checked_sub(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let distributor_key = distributor.key();
+            let bump_arr = [distributor.authority_bump];
+            let seed_slice: &[&[u8]] = &[b"distributor-authority", distributor_key.as_ref(), &bump_arr];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from:      ctx.accounts.token_vault.to_account_info(),
+                        to:        ctx.accounts.user_token_account.to_account_info(),
+                        authority: ctx.accounts.distributor_authority.to_account_info(),
+                    },
+                    &[seed_slice],
+                ),
+                amount,
+            )?;
+        }
+
+        emit!(FeesClaimed {
+            distributor: distributor.key(),
+            user: user_claim.user,
+            amount,
+            week_cursor: user_claim.week_cursor,
+        });
+        Ok(())
+    }
+}
+
+fn round_to_week(t: i64) -> i64 {
+    t.div_euclid(WEEK) * WEEK
+}
+
+/// A lock's share of one week's fees: `tokens · power / supply`, with
+/// both powers taken at the start of the week.
+pub fn claimable(record: &WeekRecord, escrow: &Escrow, lock: &Lock) -> Result<u64> {
+    let supply = escrow.supply_at_week(record.week).ok_or(ErrorCode::WeekTooOld)?;
+    let power  = lock.historical_balance(record.week).ok_or(ErrorCode::LockHistoryLost)?;
+    if supply == 0 || power == 0 {
+        return Ok(0);
+    }
+    // a lock's power never exceeds the total it is part of, bar rounding
+    let share = record.tokens as u128 * power.min(supply) as u128 / supply as u128;
+    Ok(share as u64)
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializeDistributor<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Distributor::INIT_SPACE,
+        seeds = [b"fee-distributor", escrow.key().as_ref(), token_mint.key().as_ref()],
+        bump
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    /// CHECK: PDA holding the undistributed fees
+    #[account(seeds = [b"distributor-authority", distributor.key().as_ref()], bump)]
+    pub distributor_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"distributor-tokens", distributor.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = distributor_authority
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(week: i64)]
+pub struct CheckpointToken<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut)]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(address = distributor.token_vault)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + WeekRecord::INIT_SPACE,
+        seeds = [b"fee-week", distributor.key().as_ref(), &week.to_le_bytes()],
+        bump
+    )]
+    pub week_record: Account<'info, WeekRecord>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    #[account(mut)]
+    pub distributor: Account<'info, Distributor>,
+
+    #[account(address = distributor.escrow)]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        seeds = [b"lock", escrow.key().as_ref(), user.key().as_ref()],
+        bump,
+        seeds::program = voting_escrow::ID
+    )]
+    pub lock: Account<'info, Lock>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserClaim::INIT_SPACE,
+        seeds = [b"fee-claim", distributor.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_claim: Account<'info, UserClaim>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = distributor.token_mint)]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = distributor.token_vault)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the undistributed fees
+    #[account(seeds = [b"distributor-authority", distributor.key().as_ref()], bump = distributor.authority_bump)]
+    pub distributor_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// Fee distributor, PDA'd by ["fee-distributor", escrow, token_mint]
+#[account]
+#[derive(InitSpace)]
+pub struct Distributor {
+    pub escrow: Pubkey,
+    pub token_mint: Pubkey,
+    /// Undistributed fees, PDA'd by ["distributor-tokens", distributor]
+    pub token_vault: Pubkey,
+    /// First week fees are credited to
+    pub start_week: i64,
+    /// `token_vault` balance already credited to some week
+    pub last_token_balance: u64,
+    pub authority_bump: u8,
+}
+
+/// Fees credited to one week, PDA'd by ["fee-week", distributor, week (LE i64)]
+#[account]
+#[derive(InitSpace)]
+pub struct WeekRecord {
+    pub distributor: Pubkey,
+    /// Start of the week
+    pub week: i64,
+    pub tokens: u64,
+}
+
+/// A holder's claim progress, PDA'd by ["fee-claim", distributor, user]
+#[account]
+#[derive(InitSpace)]
+pub struct UserClaim {
+    pub distributor: Pubkey,
+    pub user: Pubkey,
+    /// First week not yet claimed or skipped
+    pub week_cursor: i64,
+}
+
+#[event]
+pub struct TokensCheckpointed {
+    pub distributor: Pubkey,
+    pub week: i64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub distributor: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub week_cursor: i64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Week is not the current week or is behind the claim cursor")]
+    InvalidWeek,
+    #[msg("Distribution has not started")]
+    NotStarted,
+    #[msg("Week has not ended")]
+    WeekNotOver,
+    #[msg("Account is not a week record of this distributor")]
+    InvalidWeekRecord,
+    #[msg("Week is older than the escrow's supply history")]
+    WeekTooOld,
+    #[msg("Lock has changed too often since the week to know its power")]
+    LockHistoryLost,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use voting_escrow::{LockPoint, LOCK_HISTORY, SLOPE_WEEKS};
+
+    fn lock(ts: i64, amount: u64, end: i64) -> Lock {
+        let mut history = [LockPoint::default(); LOCK_HISTORY];
+        history[0] = LockPoint { ts, amount, end };
+        Lock { escrow: Pubkey::default(), owner: Pubkey::default(), amount, end, changes: 1, history }
+    }
+
+    #[test]
+    fn weekly_fees_split_by_power_at_week_start() {
+        let mut escrow = Escrow {
+            token_mint: Pubkey::default(),
+            token_vault: Pubkey::default(),
+            total_locked: 0,
+            bias: 0,
+            slope: 0,
+            last_checkpoint: 100 * WEEK,
+            slope_changes: [0; SLOPE_WEEKS],
+            week_supply: [0; SLOPE_WEEKS],
+            authority_bump: 0,
+        };
+        let a = lock(100 * WEEK + 1, 3_000_000, 200 * WEEK);
+        escrow.update(a.history[0].ts, (0, 0), (a.amount, a.end)).unwrap();
+        // `b` locks mid‑week 101, so it shares from week 102 on
+        let b = lock(101 * WEEK + 9, 1_000_000, 200 * WEEK);
+        escrow.update(b.history[0].ts, (0, 0), (b.amount, b.end)).unwrap();
+        escrow.checkpoint(103 * WEEK).unwrap();
+
+        let week = |w: i64| WeekRecord { distributor: Pubkey::default(), week: w * WEEK, tokens: 1_000_000 };
+        assert!(claimable(&week(101), &escrow, &a).unwrap().abs_diff(1_000_000) <= 1);
+        assert_eq!(claimable(&week(101), &escrow, &b).unwrap(), 0);
+
+        let (to_a, to_b) = (claimable(&week(102), &escrow, &a).unwrap(), claimable(&week(102), &escrow, &b).unwrap());
+        assert!(to_a + to_b <= 1_000_000);
+        assert!(to_a.abs_diff(750_000) <= 2 && to_b.abs_diff(250_000) <= 2, "{to_a} / {to_b}");
+
+        // nothing locked before the escrow's first week
+        assert_eq!(claimable(&week(100), &escrow, &a).unwrap(), 0);
+    }
+}
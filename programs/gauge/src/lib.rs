@@ -0,0 +1,687 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use pool_factory::PoolRecord;
+use vault::{Action, Authorizer};
+use voting_escrow::{Escrow, Lock};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("GaugXJkpP7ruoDBQvmmmR6G3yfoiujPiGX7GyBNCkqN");
+
+/// Fixed‑point scale of `reward_per_token`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// Share of a stake that always earns, boost or not (40 %)
+pub const TOKENLESS_PRODUCTION: u128 = 40;
+/// Shortest reward period a distributor may set
+pub const MIN_REWARD_DURATION: i64 = 24 * 60 * 60;
+
+/// Liquidity‑mining gauges: LPs stake a factory pool's BPT and accrue a
+/// reward token at a per‑second emission rate, Synthetix style. Anyone
+/// may create the gauge of a (pool, reward token) pair; only holders of
+/// the Vault's `ManageRewards` role fund it and set its rate.
+///
+/// Rewards accrue on *working* balances. Until a reward manager points
+/// the gauge at a vote escrow a stake works at its full size; after that
+/// [`working_balance`] boosts it from 40 % to 100 % of its size with the
+/// staker's share of voting power, refreshed on every deposit, withdrawal
+/// and claim.
+#[program]
+pub mod gauge {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create the gauge of a factory pool for one reward token
+    ---------------------------------------------------------------- */
+    pub fn create_gauge(ctx: Context<CreateGauge>) -> Result<()> {
+        let gauge = &mut ctx.accounts.gauge;
+        gauge.vault                   = ctx.accounts.pool_record.vault;
+        gauge.pool                    = ctx.accounts.pool_record.pool;
+        gauge.lp_mint                 = ctx.accounts.lp_mint.key();
+        gauge.reward_mint             = ctx.accounts.reward_mint.key();
+        gauge.stake_vault             = ctx.accounts.stake_vault.key();
+        gauge.reward_vault            = ctx.accounts.reward_vault.key();
+        gauge.creator                 = ctx.accounts.creator.key();
+        gauge.reward_rate             = 0;
+        gauge.period_finish           = 0;
+        gauge.last_update             = Clock::get()?.unix_timestamp;
+        gauge.reward_per_token_stored = 0;
+        gauge.total_supply            = 0;
+        gauge.working_supply          = 0;
+        gauge.voting_escrow           = Pubkey::default();
+        gauge.authority_bump          = ctx.bumps.gauge_authority;
+
+        emit!(GaugeCreated {
+            gauge: gauge.key(),
+            pool: gauge.pool,
+            reward_mint: gauge.reward_mint,
+            creator: gauge.creator,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Fund the next reward period – requires `ManageRewards`
+       Unspent rewards of a running period roll into the new rate.
+    ---------------------------------------------------------------- */
+    pub fn notify_reward_amount(ctx: Context<NotifyRewardAmount>, amount: u64, duration: i64) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageRewards, &ctx.accounts.distributor.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(duration >= MIN_REWARD_DURATION, ErrorCode::InvalidDuration);
+
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.gauge.checkpoint(now)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.distributor_reward_account.to_account_info(),
+                    to:        ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.distributor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        ctx.accounts.reward_vault.reload()?;
+
+        let gauge = &mut ctx.accounts.gauge;
+        let leftover = if now < gauge.period_finish {
+            (gauge.period_finish - now) as u128 * gauge.reward_rate as u128
+        } else {
+            0
+        };
+        let rate = (leftover + amount as u128) / duration as u128;
+        require!(rate > 0, ErrorCode::RewardTooSmall);
+        // the vault must hold the whole period's emissions
+        require!(
+            rate * duration as u128 <= ctx.accounts.reward_vault.amount as u128,
+            ErrorCode::RewardTooLarge
+        );
+        gauge.reward_rate   = rate as u64;
+        gauge.last_update   = now;
+        gauge.period_finish = now + duration;
+
+        emit!(RewardNotified {
+            gauge: gauge.key(),
+            amount,
+            reward_rate: gauge.reward_rate,
+            period_finish: gauge.period_finish,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Boost stakes with a vote escrow's power – requires `ManageRewards`
+       `Pubkey::default()` turns boosting off
+    ---------------------------------------------------------------- */
+    pub fn set_voting_escrow(ctx: Context<SetVotingEscrow>, voting_escrow: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageRewards, &ctx.accounts.manager.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.gauge.voting_escrow = voting_escrow;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Stake BPT
+    ---------------------------------------------------------------- */
+    pub fn deposit(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let gauge = &mut ctx.accounts.gauge;
+        let stake = &mut ctx.accounts.user_stake;
+        if stake.gauge == Pubkey::default() {
+            stake.gauge = gauge.key();
+            stake.user  = ctx.accounts.user.key();
+        }
+        gauge.checkpoint(now)?;
+        stake.settle(gauge)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.user_lp_account.to_account_info(),
+                    to:        ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        stake.balance      = stake.balance.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        gauge.total_supply = gauge.total_supply.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        let power = voting_power(gauge, ctx.accounts.escrow.as_deref(), ctx.accounts.user_lock.as_ref(), now)?;
+        gauge.update_working_balance(stake, power)?;
+
+        emit!(Staked { gauge: gauge.key(), user: stake.user, amount });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Unstake BPT; accrued rewards stay claimable
+    ---------------------------------------------------------------- */
+    pub fn withdraw(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let gauge = &mut ctx.accounts.gauge;
+        let stake = &mut ctx.accounts.user_stake;
+        require!(amount <= stake.balance, ErrorCode::InsufficientStake);
+        gauge.checkpoint(now)?;
+        stake.settle(gauge)?;
+
+        stake.balance      -= amount;
+        gauge.total_supply -= amount;
+        let power = voting_power(gauge, ctx.accounts.escrow.as_deref(), ctx.accounts.user_lock.as_ref(), now)?;
+        gauge.update_working_balance(stake, power)?;
+
+        let gauge_key = gauge.key();
+        let bump_arr  = [gauge.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"gauge-authority", gauge_key.as_ref(), &bump_arr];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.stake_vault.to_account_info(),
+                    to:        ctx.accounts.user_lp_account.to_account_info(),
+                    authority: ctx.accounts.gauge_authority.to_account_info(),
+                },
+                &[seed_slice],
+            ),
+            amount,
+        )?;
+
+        emit!(Withdrawn { gauge: gauge_key, user: stake.user, amount });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Claim accrued rewards
+    ---------------------------------------------------------------- */
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let gauge = &mut ctx.accounts.gauge;
+        let stake = &mut ctx.accounts.user_stake;
+        gauge.checkpoint(now)?;
+        stake.settle(gauge)?;
+        let power = voting_power(gauge, ctx.accounts.escrow.as_deref(), ctx.accounts.user_lock.as_ref(), now)?;
+        gauge.update_working_balance(stake, power)?;
+
+        let amount = stake.rewards_owed;
+        if amount > 0 {
+            stake.rewards_owed = 0;
+            let gauge_key = gauge.key();
+            let bump_arr  = [gauge.authority_bump];
+            let seed_slice: &[&[u8]] = &[b"gauge-authority", gauge_key.as_ref(), &bump_arr];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from:      ctx.accounts.reward_vault.to_account_info(),
+                        to:        ctx.accounts.user_reward_account.to_account_info(),
+                        authority: ctx.accounts.gauge_authority.to_account_info(),
+                    },
+                    &[seed_slice],
+                ),
+                amount,
+            )?;
+            emit!(RewardPaid { gauge: gauge_key, user: stake.user, amount });
+        }
+        Ok(())
+    }
+}
+
+/// Boost hook: the part of `balance` that earns rewards. With voting
+/// power `(user, total)` a stake earns on
+/// min(balance, 40 % · balance + 60 % · supply · user / total);
+/// without any (`None` or a zero total) it earns on all of it.
+pub fn working_balance(balance: u64, total_supply: u64, voting_power: Option<(u128, u128)>) -> u64 {
+    match voting_power {
+        Some((user, total)) if total > 0 => {
+            let base  = balance as u128 * TOKENLESS_PRODUCTION / 100;
+            let boost = (total_supply as u128 * user / total) * (100 - TOKENLESS_PRODUCTION) / 100;
+            (base + boost).min(balance as u128) as u64
+        }
+        _ => balance,
+    }
+}
+
+/// The staker's and the total voting power at `now`, or `None` when the
+/// gauge has no vote escrow. A missing lock counts as no power.
+fn voting_power(
+    gauge: &Gauge,
+    escrow: Option<&Account<Escrow>>,
+    user_lock: Option<&AccountInfo>,
+    now: i64,
+) -> Result<Option<(u128, u128)>> {
+    if gauge.voting_escrow == Pubkey::default() {
+        return Ok(None);
+    }
+    let (escrow, user_lock) = escrow.zip(user_lock).ok_or(ErrorCode::MissingVotingEscrow)?;
+    let user_power = if user_lock.data_is_empty() {
+        0
+    } else {
+        require_keys_eq!(*user_lock.owner, voting_escrow::ID, ErrorCode::MissingVotingEscrow);
+        Lock::try_deserialize(&mut &user_lock.try_borrow_data()?[..])?.balance_at(now)
+    };
+    Ok(Some((user_power as u128, escrow.supply_at(now) as u128)))
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct CreateGauge<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// The pool's factory record; only factory pools get gauges
+    #[account(
+        seeds = [pool_factory::POOL_RECORD_SEED, pool_record.pool.as_ref()],
+        bump,
+        seeds::program = pool_factory::ID
+    )]
+    pub pool_record: Account<'info, PoolRecord>,
+
+    #[account(address = pool_record.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Gauge::INIT_SPACE,
+        seeds = [b"gauge", pool_record.pool.as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub gauge: Account<'info, Gauge>,
+
+    /// CHECK: PDA holding the staked BPT and the rewards
+    #[account(seeds = [b"gauge-authority", gauge.key().as_ref()], bump)]
+    pub gauge_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"gauge-stake", gauge.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = gauge_authority
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"gauge-rewards", gauge.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = gauge_authority
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct NotifyRewardAmount<'info> {
+    #[account(mut)]
+    pub gauge: Account<'info, Gauge>,
+
+    /// The Authorizer of the vault the gauge's pool is registered with
+    #[account(
+        seeds = [b"authorizer", gauge.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `ManageRewards` role
+    pub distributor: Signer<'info>,
+
+    #[account(mut, token::mint = gauge.reward_mint)]
+    pub distributor_reward_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = gauge.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetVotingEscrow<'info> {
+    #[account(mut)]
+    pub gauge: Account<'info, Gauge>,
+
+    /// The Authorizer of the vault the gauge's pool is registered with
+    #[account(
+        seeds = [b"authorizer", gauge.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `ManageRewards` role
+    pub manager: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub gauge: Account<'info, Gauge>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserStake::INIT_SPACE,
+        seeds = [b"user-stake", gauge.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = gauge.lp_mint)]
+    pub user_lp_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = gauge.stake_vault)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the staked BPT
+    #[account(seeds = [b"gauge-authority", gauge.key().as_ref()], bump = gauge.authority_bump)]
+    pub gauge_authority: AccountInfo<'info>,
+
+    /// The gauge's vote escrow; required once one is set
+    #[account(address = gauge.voting_escrow)]
+    pub escrow: Option<Box<Account<'info, Escrow>>>,
+
+    /// CHECK: the user's lock in `escrow`; need not exist
+    #[account(
+        seeds = [b"lock", gauge.voting_escrow.as_ref(), user.key().as_ref()],
+        bump,
+        seeds::program = voting_escrow::ID
+    )]
+    pub user_lock: Option<AccountInfo<'info>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub gauge: Account<'info, Gauge>,
+
+    #[account(
+        mut,
+        seeds = [b"user-stake", gauge.key().as_ref(), user.key().as_ref()],
+        bump,
+        has_one = user
+    )]
+    pub user_stake: Account<'info, UserStake>,
+
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = gauge.reward_mint)]
+    pub user_reward_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = gauge.reward_vault)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the rewards
+    #[account(seeds = [b"gauge-authority", gauge.key().as_ref()], bump = gauge.authority_bump)]
+    pub gauge_authority: AccountInfo<'info>,
+
+    /// The gauge's vote escrow; required once one is set
+    #[account(address = gauge.voting_escrow)]
+    pub escrow: Option<Box<Account<'info, Escrow>>>,
+
+    /// CHECK: the user's lock in `escrow`; need not exist
+    #[account(
+        seeds = [b"lock", gauge.voting_escrow.as_ref(), user.key().as_ref()],
+        bump,
+        seeds::program = voting_escrow::ID
+    )]
+    pub user_lock: Option<AccountInfo<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// A pool's gauge for one reward token, PDA'd by ["gauge", pool, reward_mint]
+#[account]
+#[derive(InitSpace)]
+pub struct Gauge {
+    pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub reward_mint: Pubkey,
+    /// Staked BPT, PDA'd by ["gauge-stake", gauge]
+    pub stake_vault: Pubkey,
+    /// Undistributed rewards, PDA'd by ["gauge-rewards", gauge]
+    pub reward_vault: Pubkey,
+    pub creator: Pubkey,
+    /// Reward tokens emitted per second until `period_finish`
+    pub reward_rate: u64,
+    pub period_finish: i64,
+    pub last_update: i64,
+    /// Rewards per working unit since creation, times `REWARD_PRECISION`
+    pub reward_per_token_stored: u128,
+    /// Staked BPT
+    pub total_supply: u64,
+    /// Sum of the stakers' working balances
+    pub working_supply: u64,
+    /// Vote escrow boosting the stakes; `Pubkey::default()` = no boost
+    pub voting_escrow: Pubkey,
+    pub authority_bump: u8,
+}
+
+impl Gauge {
+    /// Rewards per working unit up to `now`.
+    pub fn reward_per_token(&self, now: i64) -> Result<u128> {
+        let until = now.min(self.period_finish);
+        if self.working_supply == 0 || until <= self.last_update {
+            return Ok(self.reward_per_token_stored);
+        }
+        let emitted = (until - self.last_update) as u128 * self.reward_rate as u128;
+        let per_unit = emitted
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(ErrorCode::MathOverflow)?
+            / self.working_supply as u128;
+        Ok(self.reward_per_token_stored.checked_add(per_unit).ok_or(ErrorCode::MathOverflow)?)
+    }
+
+    /// Bring `reward_per_token_stored` up to `now`.
+    pub fn checkpoint(&mut self, now: i64) -> Result<()> {
+        self.reward_per_token_stored = self.reward_per_token(now)?;
+        self.last_update = now.min(self.period_finish).max(self.last_update);
+        Ok(())
+    }
+
+    /// Recompute a stake's working balance and the gauge's working supply.
+    pub fn update_working_balance(&mut self, stake: &mut UserStake, voting_power: Option<(u128, u128)>) -> Result<()> {
+        let working = working_balance(stake.balance, self.total_supply, voting_power);
+        self.working_supply = self
+            .working_supply
+            .checked_sub(stake.working_balance)
+            .and_then(|s| s.checked_add(working))
+            .ok_or(ErrorCode::MathOverflow)?;
+        stake.working_balance = working;
+        Ok(())
+    }
+}
+
+/// A staker's position, PDA'd by ["user-stake", gauge, user]
+#[account]
+#[derive(InitSpace)]
+pub struct UserStake {
+    pub gauge: Pubkey,
+    pub user: Pubkey,
+    /// Staked BPT
+    pub balance: u64,
+    /// Part of `balance` earning rewards (see [`working_balance`])
+    pub working_balance: u64,
+    /// `reward_per_token_stored` when `rewards_owed` was last settled
+    pub reward_per_token_paid: u128,
+    /// Accrued, unclaimed rewards
+    pub rewards_owed: u64,
+}
+
+impl UserStake {
+    /// Rewards accrued since the last settlement, at the gauge's current
+    /// `reward_per_token_stored`.
+    pub fn pending(&self, gauge: &Gauge) -> Result<u64> {
+        let delta = gauge.reward_per_token_stored.saturating_sub(self.reward_per_token_paid);
+        let earned = (self.working_balance as u128)
+            .checked_mul(delta)
+            .ok_or(ErrorCode::MathOverflow)?
+            / REWARD_PRECISION;
+        Ok(u64::try_from(earned).map_err(|_| ErrorCode::MathOverflow)?)
+    }
+
+    /// Move pending rewards into `rewards_owed`; call after `Gauge::checkpoint`.
+    pub fn settle(&mut self, gauge: &Gauge) -> Result<()> {
+        self.rewards_owed = self.rewards_owed.checked_add(self.pending(gauge)?).ok_or(ErrorCode::MathOverflow)?;
+        self.reward_per_token_paid = gauge.reward_per_token_stored;
+        Ok(())
+    }
+}
+
+#[event]
+pub struct GaugeCreated {
+    pub gauge: Pubkey,
+    pub pool: Pubkey,
+    pub reward_mint: Pubkey,
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct RewardNotified {
+    pub gauge: Pubkey,
+    pub amount: u64,
+    pub reward_rate: u64,
+    pub period_finish: i64,
+}
+
+#[event]
+pub struct Staked {
+    pub gauge: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub gauge: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardPaid {
+    pub gauge: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Withdrawal exceeds the staked balance")]
+    InsufficientStake,
+    #[msg("Reward period is too short")]
+    InvalidDuration,
+    #[msg("Reward amount is too small for the period")]
+    RewardTooSmall,
+    #[msg("Reward rate exceeds the rewards held")]
+    RewardTooLarge,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("The gauge's vote escrow and the user's lock are required")]
+    MissingVotingEscrow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gauge(reward_rate: u64, period_finish: i64) -> Gauge {
+        Gauge {
+            vault: Pubkey::default(),
+            pool: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            reward_mint: Pubkey::default(),
+            stake_vault: Pubkey::default(),
+            reward_vault: Pubkey::default(),
+            creator: Pubkey::default(),
+            reward_rate,
+            period_finish,
+            last_update: 0,
+            reward_per_token_stored: 0,
+            total_supply: 0,
+            working_supply: 0,
+            voting_escrow: Pubkey::default(),
+            authority_bump: 0,
+        }
+    }
+
+    fn stake(g: &mut Gauge, s: &mut UserStake, now: i64, amount: u64) {
+        g.checkpoint(now).unwrap();
+        s.settle(g).unwrap();
+        s.balance += amount;
+        g.total_supply += amount;
+        g.update_working_balance(s, None).unwrap();
+    }
+
+    fn staker() -> UserStake {
+        UserStake {
+            gauge: Pubkey::default(),
+            user: Pubkey::new_unique(),
+            balance: 0,
+            working_balance: 0,
+            reward_per_token_paid: 0,
+            rewards_owed: 0,
+        }
+    }
+
+    #[test]
+    fn emissions_split_by_stake_and_time() {
+        // 100 tokens per second for 100 seconds
+        let mut g = gauge(100, 100);
+        let (mut a, mut b) = (staker(), staker());
+        stake(&mut g, &mut a, 0, 1_000);
+        // b joins halfway with three times a's stake
+        stake(&mut g, &mut b, 50, 3_000);
+
+        g.checkpoint(200).unwrap();
+        a.settle(&g).unwrap();
+        b.settle(&g).unwrap();
+        // a: 50 s alone + a quarter of the next 50 s
+        assert_eq!(a.rewards_owed, 5_000 + 1_250);
+        assert_eq!(b.rewards_owed, 3_750);
+        // nothing accrues after the period ends
+        assert_eq!(g.reward_per_token(1_000).unwrap(), g.reward_per_token_stored);
+    }
+
+    #[test]
+    fn voting_power_boosts_from_40_to_100_percent() {
+        assert_eq!(working_balance(1_000, 10_000, None), 1_000);
+        assert_eq!(working_balance(1_000, 10_000, Some((0, 0))), 1_000);
+        assert_eq!(working_balance(1_000, 10_000, Some((0, 100))), 400);
+        // 1 % of the votes on 10 % of the supply: 400 + 60 = 460
+        assert_eq!(working_balance(1_000, 10_000, Some((1, 100))), 460);
+        assert_eq!(working_balance(1_000, 10_000, Some((50, 100))), 1_000);
+    }
+}
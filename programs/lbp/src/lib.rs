@@ -0,0 +1,1004 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, MintTo, Token, Transfer};
+use common::pool_interface::SwapQuote;
+use common::pool_tokens::{self, TokenAccount};
+use common::{crank, derive_pool_id, Specialization};
+use math::weighted_math::{self, SwapParams};
+use math::{fees, fixed, MathError, U256};
+
+// Vault CPI interfaces and the program struct itself
+use vault::cpi::{register_pool as vault_register_pool, accounts::RegisterPool as VaultRegisterPool};
+use vault::program::Vault as VaultProgram;
+use vault::{PoolRegistration, ProtocolFeesCollector, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("LBPoo1VxcwrWVZweDCtZXhgsC7VLA6btymh3fSVipgi");
+
+/// Smallest weight a token may reach during the sale (1 %)
+pub const MIN_WEIGHT: u128 = 10_000_000_000_000_000;
+
+/// Liquidity‑bootstrapping pools: two‑token weighted pools for token
+/// launches. Only the creator adds liquidity; the weights move linearly
+/// from `start_weights` to `end_weights` over the sale window, and once
+/// the window closes the pool is terminal: swaps stop for good and the
+/// creator withdraws what is left.
+#[program]
+pub mod lbp {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Initialise a sale
+       remaining_accounts: [mint_0, mint_1]
+    ---------------------------------------------------------------- */
+    pub fn initialize_pool(ctx: Context<InitializePool>, params: SaleParams) -> Result<()> {
+        let rem = ctx.remaining_accounts;
+        require!(rem.len() == 2, ErrorCode::LengthMismatch);
+        params.validate(Clock::get()?.unix_timestamp)?;
+
+        let tokens   = [rem[0].key(), rem[1].key()];
+        let decimals = [mint_decimals(&rem[0])?, mint_decimals(&rem[1])?];
+        let lp_decimals = mint_decimals(&ctx.accounts.lp_mint)?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.vault         = ctx.accounts.vault_state.key();
+        pool.lp_mint       = ctx.accounts.lp_mint.key();
+        pool.owner         = ctx.accounts.payer.key();
+        pool.tokens        = tokens;
+        pool.decimals      = decimals;
+        pool.lp_decimals   = lp_decimals;
+        pool.start_weights = params.start_weights;
+        pool.end_weights   = params.end_weights;
+        pool.start_time    = params.start_time;
+        pool.end_time      = params.end_time;
+        pool.swap_fee      = params.swap_fee;
+        pool.swap_enabled  = params.swap_enabled;
+        pool.total_bpt     = 0;
+
+        let specialization = Specialization::TwoToken;
+        let pool_id = derive_pool_id(
+            &ctx.accounts.vault_state.key(),
+            &ctx.accounts.pool.key(),
+            specialization,
+            ctx.accounts.vault_state.pool_count,
+        );
+        let cpi_accounts = VaultRegisterPool {
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.payer.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        let registered = vault_register_pool(
+            CpiContext::new(ctx.accounts.vault_program.to_account_info(), cpi_accounts),
+            pool_id,
+            specialization,
+            tokens.to_vec(),
+        )?;
+        ctx.accounts.pool.pool_id = registered.get();
+
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Swap toggle – creator only, until the sale ends
+    ---------------------------------------------------------------- */
+    pub fn set_swap_enabled(ctx: Context<SetSwapEnabled>, swap_enabled: bool) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(!pool.is_ended(Clock::get()?.unix_timestamp), ErrorCode::SaleEnded);
+        pool.swap_enabled = swap_enabled;
+        emit!(SwapEnabledSet {
+            pool: pool.key(),
+            swap_enabled,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Crank – anyone may call once the sale window has closed: swaps
+       are switched off in state too and the sender collects the crank
+       bounty
+    ---------------------------------------------------------------- */
+    pub fn end_sale(ctx: Context<Crank>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        require!(
+            pool.swap_enabled && pool.is_ended(Clock::get()?.unix_timestamp),
+            ErrorCode::NothingToCrank
+        );
+        pool.swap_enabled = false;
+        let bounty = crank::pay_bounty(&pool.to_account_info(), &ctx.accounts.cranker)?;
+        emit!(SaleFinalized {
+            pool:    pool.key(),
+            cranker: ctx.accounts.cranker.key(),
+            bounty,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Join – creator only, exact tokens in for BPT out
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1]
+       The first join seeds both tokens. Later joins pay the swap fee on
+       their unbalanced part; the protocol's share is minted as BPT.
+    ---------------------------------------------------------------- */
+    pub fn join_exact_tokens_in_for_bpt_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        amounts_in: [u64; 2],
+        minimum_bpt_out: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.check_live(now)?;
+        let pool = &ctx.accounts.pool;
+        let rem  = ctx.remaining_accounts;
+        require_keys_eq!(ctx.accounts.user.key(), pool.owner, ErrorCode::Unauthorized);
+        require!(!pool.is_ended(now), ErrorCode::SaleEnded);
+        require!(rem.len() == 4, ErrorCode::LengthMismatch);
+
+        // 1. balances and weights
+        let balances = read_balances(pool, &ctx.accounts.pool_registration, [&rem[1], &rem[3]])?;
+        let weights  = pool.weights_at(now)?;
+        let amounts_fp = [
+            scale_up(amounts_in[0], pool.decimals[0])?,
+            scale_up(amounts_in[1], pool.decimals[1])?,
+        ];
+
+        // 2. maths
+        let (bpt_out, protocol_bpt) = if pool.total_bpt == 0 {
+            // seed: BPT = n · invariant, as for any weighted pool
+            require!(amounts_in.iter().all(|a| *a > 0), ErrorCode::InvalidInitialJoin);
+            let invariant = weighted_math::try_calculate_invariant(&amounts_fp, &weights).map_err(ErrorCode::from)?;
+            (scale_down(invariant * 2, pool.lp_decimals)?, 0)
+        } else {
+            let total_bpt_fp = scale_up(pool.total_bpt, pool.lp_decimals)?;
+            let bpt_out_fp = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                &balances,
+                &weights,
+                &amounts_fp,
+                total_bpt_fp,
+                U256::from(pool.swap_fee),
+            ).map_err(ErrorCode::from)?;
+            let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+            let protocol_bpt = if protocol_pct > 0 {
+                let bpt_no_fee_fp = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                    &balances,
+                    &weights,
+                    &amounts_fp,
+                    total_bpt_fp,
+                    U256::zero(),
+                ).map_err(ErrorCode::from)?;
+                let fee_bpt_fp = bpt_no_fee_fp.saturating_sub(bpt_out_fp);
+                scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+            } else {
+                0
+            };
+            (scale_down(bpt_out_fp, pool.lp_decimals)?, protocol_bpt)
+        };
+        require!(bpt_out > 0, ErrorCode::MathUnderflow);
+        require!(bpt_out >= minimum_bpt_out, ErrorCode::SlippageLimit);
+
+        // 3. user → vault, then mint
+        for i in 0..2 {
+            if amounts_in[i] > 0 {
+                ctx.accounts.pay_in(&rem[i * 2], &rem[i * 2 + 1], amounts_in[i])?;
+            }
+        }
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.mint_bpt(bump, &ctx.accounts.user_lp_account, bpt_out)?;
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        ctx.accounts.pool.total_bpt = ctx.accounts.pool
+            .total_bpt
+            .checked_add(bpt_out)
+            .and_then(|t| t.checked_add(protocol_bpt))
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Exit – exact BPT in, proportional tokens out (no fee)
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1]
+       Open to any BPT holder, during and after the sale.
+    ---------------------------------------------------------------- */
+    pub fn exit_exact_bpt_in_for_tokens_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        bpt_in: u64,
+        minimum_amounts_out: [u64; 2],
+    ) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+        let rem  = ctx.remaining_accounts;
+        require!(rem.len() == 4, ErrorCode::LengthMismatch);
+        require!(bpt_in > 0 && bpt_in <= pool.total_bpt, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, &ctx.accounts.pool_registration, [&rem[1], &rem[3]])?;
+
+        // 2. maths
+        let outs_fp = weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+            &balances,
+            scale_up(bpt_in, pool.lp_decimals)?,
+            scale_up(pool.total_bpt, pool.lp_decimals)?,
+            U256::zero(),
+        ).map_err(ErrorCode::from)?;
+        let mut amounts_out = [0u64; 2];
+        for i in 0..2 {
+            amounts_out[i] = scale_down(outs_fp[i], pool.decimals[i])?;
+            require!(amounts_out[i] >= minimum_amounts_out[i], ErrorCode::SlippageLimit);
+        }
+
+        // 3. burn, then vault → user
+        ctx.accounts.burn_bpt(bpt_in)?;
+        let bump = ctx.bumps.lp_mint_authority;
+        for i in 0..2 {
+            ctx.accounts.pay_out(bump, &rem[i * 2 + 1], &rem[i * 2], amounts_out[i])?;
+        }
+
+        // 4. bookkeeping
+        ctx.accounts.pool.total_bpt = ctx.accounts.pool
+            .total_bpt
+            .checked_sub(bpt_in)
+            .ok_or(ErrorCode::MathUnderflow)?;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – exact in → out at the current point of the weight schedule
+       Only while swaps are enabled and the sale has not ended.
+       Returns the amount sent out (read by the Vault's batch swap).
+    ---------------------------------------------------------------- */
+    pub fn swap_exact_token_in_for_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.vault_state.is_paused(now), ErrorCode::VaultPaused);
+        require!(!ctx.accounts.pool_registration.paused, ErrorCode::PoolPaused);
+
+        let pool = &ctx.accounts.pool;
+        let index_in = pool.index_of(&unpack_token_account(&ctx.accounts.vault_in)?.mint)?;
+        let (vault_in, vault_out) = (&ctx.accounts.vault_in, &ctx.accounts.vault_out);
+        let custody = if index_in == 0 { [vault_in, vault_out] } else { [vault_out, vault_in] };
+        let pool_balances = read_balances(pool, &ctx.accounts.pool_registration, custody)?;
+        let balances = [pool_balances[index_in], pool_balances[1 - index_in]];
+
+        let SwapQuote { amount_out, protocol_fee } = quote_exact_in(
+            pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            now,
+            index_in,
+            amount_in,
+            balances,
+        )?;
+        require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+
+        // transfer in (user → vault, protocol fee → collector)
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        let cpi_in = Transfer {
+            from:      ctx.accounts.user_token_account_in.clone(),
+            to:        ctx.accounts.vault_in.clone(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+        };
+        token::transfer(CpiContext::new(token_prog.clone(), cpi_in), amount_in - protocol_fee)?;
+        if protocol_fee > 0 {
+            check_fee_account(
+                &ctx.accounts.protocol_fee_account,
+                &ctx.accounts.fees_collector.key(),
+                &pool.tokens[index_in],
+            )?;
+            let cpi_fee = Transfer {
+                from:      ctx.accounts.user_token_account_in.clone(),
+                to:        ctx.accounts.protocol_fee_account.clone(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            token::transfer(CpiContext::new(token_prog.clone(), cpi_fee), protocol_fee)?;
+        }
+
+        // transfer out (vault → user)
+        let pool_key = pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_out = Transfer {
+            from:      ctx.accounts.vault_out.clone(),
+            to:        ctx.accounts.user_token_account_out.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(CpiContext::new_with_signer(token_prog, cpi_out, &[seed_slice]), amount_out)?;
+
+        Ok(amount_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Managed transfer – move idle cash to an asset manager
+       Only the Vault may ask (its asset manager authority PDA must sign).
+    ---------------------------------------------------------------- */
+    pub fn managed_transfer(ctx: Context<ManagedTransfer>, amount: u64) -> Result<()> {
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.from.clone(),
+            to:        ctx.accounts.to.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Query – price an exact‑in swap against caller‑supplied balances
+       Read‑only; fails like the swap would once swaps are off.
+    ---------------------------------------------------------------- */
+    pub fn query_swap(
+        ctx: Context<QuerySwap>,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Result<SwapQuote> {
+        let pool     = &ctx.accounts.pool;
+        let index_in = pool.index_of(&unpack_token_account(&ctx.accounts.pool_token_in)?.mint)?;
+        let balances = [
+            scale_up(balance_in, pool.decimals[index_in])?,
+            scale_up(balance_out, pool.decimals[1 - index_in])?,
+        ];
+        quote_exact_in(
+            pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            Clock::get()?.unix_timestamp,
+            index_in,
+            amount_in,
+            balances,
+        )
+    }
+}
+
+/// Exact‑in swap pricing shared by the swap and its query, at the weights
+/// of `now`: amount out and the protocol's part of the amount in.
+/// `balances` are (in, out).
+fn quote_exact_in(
+    pool: &LbpPool,
+    protocol_pct: u64,
+    now: i64,
+    index_in: usize,
+    amount_in: u64,
+    balances: [U256; 2],
+) -> Result<SwapQuote> {
+    require!(pool.swap_enabled, ErrorCode::SwapsDisabled);
+    require!(!pool.is_ended(now), ErrorCode::SaleEnded);
+
+    let index_out = 1 - index_in;
+    let weights = pool.weights_at(now)?;
+    let params = SwapParams {
+        balance_in:  balances[0],
+        weight_in:   weights[index_in],
+        balance_out: balances[1],
+        weight_out:  weights[index_out],
+        amount:      scale_up(amount_in, pool.decimals[index_in])?,
+        fee:         U256::from(pool.swap_fee),
+    };
+    let out_fp = params.try_out_given_in().map_err(ErrorCode::from)?;
+    let protocol_fee = if protocol_pct > 0 {
+        let fee_amount_fp = fees::try_fee_amount(params.amount, params.fee).map_err(ErrorCode::from)?;
+        scale_down(protocol_share(fee_amount_fp, protocol_pct)?, pool.decimals[index_in])?
+    } else {
+        0
+    };
+    Ok(SwapQuote {
+        amount_out: scale_down(out_fp, pool.decimals[index_out])?,
+        protocol_fee,
+    })
+}
+
+/// Pool balances as 18‑dec values in pool order. Custody accounts must be
+/// the registered ones; amounts held by asset managers count towards the
+/// balance.
+fn read_balances(pool: &LbpPool, registration: &PoolRegistration, custody: [&AccountInfo; 2]) -> Result<[U256; 2]> {
+    let mut balances = [U256::zero(); 2];
+    for (i, ai) in custody.into_iter().enumerate() {
+        let acct = unpack_token_account(ai)?;
+        require_keys_eq!(acct.mint, pool.tokens[i], ErrorCode::InvalidPoolTokenAccount);
+        let registered = registration.token_accounts.get(i).copied().unwrap_or_default();
+        if registered != Pubkey::default() {
+            require_keys_eq!(ai.key(), registered, ErrorCode::InvalidPoolTokenAccount);
+        }
+        let amount = acct
+            .amount
+            .checked_add(registration.managed_balance(&acct.mint))
+            .ok_or(ErrorCode::MathOverflow)?;
+        balances[i] = scale_up(amount, pool.decimals[i])?;
+    }
+    Ok(balances)
+}
+
+/// Protocol share (`pct`, 18‑dec) of a fee amount, rounded up.
+fn protocol_share(fee_amount_fp: U256, pct: u64) -> Result<U256> {
+    Ok(pool_tokens::protocol_share(fee_amount_fp, pct).map_err(ErrorCode::from)?)
+}
+
+/// Raw token amount as an 18‑dec value.
+fn scale_up(amount: u64, decimals: u8) -> Result<U256> {
+    Ok(pool_tokens::scale_up(amount, decimals).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec value as a raw token amount, rounded down (amounts paid out).
+fn scale_down(value_fp: U256, decimals: u8) -> Result<u64> {
+    Ok(pool_tokens::scale_down(value_fp, decimals).map_err(ErrorCode::from)?)
+}
+
+/// Decimals of a mint of either token program, checked to be scalable
+/// to 18‑dec.
+fn mint_decimals(ai: &AccountInfo) -> Result<u8> {
+    pool_tokens::mint_decimals(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A token account of either token program, its extensions skipped.
+fn unpack_token_account(ai: &AccountInfo) -> Result<TokenAccount> {
+    pool_tokens::unpack_token_account(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A protocol fee account must be a token account of `mint` held by the collector.
+fn check_fee_account(ai: &AccountInfo, collector: &Pubkey, mint: &Pubkey) -> Result<()> {
+    pool_tokens::check_fee_account(ai, collector, mint, ErrorCode::InvalidFeeAccount)
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    /// The sale's creator: the only account that may join or toggle swaps
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// An already‑initialized VaultState account
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// CHECK: the LP‑token mint for this pool; its decimals scale BPT amounts
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority for `lp_mint`; derived from `["lp-mint-authority", pool.key().as_ref()]`
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// The Pool state PDA itself
+    #[account(
+        init,
+        seeds = [b"pool-state", vault_state.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + LbpPool::INIT_SPACE
+    )]
+    pub pool: Account<'info, LbpPool>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSwapEnabled<'info> {
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, LbpPool>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Crank<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, LbpPool>,
+
+    /// Anyone; receives the crank bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PoolContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, LbpPool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: user's LP token account; BPT is minted to or burned from it
+    #[account(mut)]
+    pub user_lp_account: AccountInfo<'info>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's LP token account; checked when a protocol fee is minted
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> PoolContext<'info> {
+    fn check_live(&self, now: i64) -> Result<()> {
+        require!(!self.vault_state.is_paused(now), ErrorCode::VaultPaused);
+        require!(!self.pool_registration.paused, ErrorCode::PoolPaused);
+        Ok(())
+    }
+
+    /// user → vault, signed by the user
+    fn pay_in(&self, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::transfer(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    /// vault → user, signed by the pool authority PDA
+    fn pay_out(&self, bump: u8, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    fn mint_bpt(&self, bump: u8, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = MintTo {
+            mint:      self.lp_mint.clone(),
+            to:        to.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /// The protocol's share of a fee, minted as BPT to the collector.
+    fn mint_protocol_bpt(&self, bump: u8, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        check_fee_account(&self.protocol_fee_lp_account, &self.fees_collector.key(), &self.pool.lp_mint)?;
+        self.mint_bpt(bump, &self.protocol_fee_lp_account, amount)
+    }
+
+    fn burn_bpt(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint:      self.lp_mint.clone(),
+            from:      self.user_lp_account.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::burn(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts: swap context (same layout as every pool's, see
+   `common::pool_interface::SwapExactInAccounts`)
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct SwapContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, LbpPool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: custody account of the 'in' token; checked against the registration
+    #[account(mut)]
+    pub vault_in: AccountInfo<'info>,
+
+    /// CHECK: custody account of the 'out' token; checked against the registration
+    #[account(mut)]
+    pub vault_out: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_authority: Signer<'info>,
+
+    /// CHECK: User's token account for the 'in' mint
+    #[account(mut)]
+    pub user_token_account_in: AccountInfo<'info>,
+
+    /// CHECK: User's token account for the 'out' mint
+    #[account(mut)]
+    pub user_token_account_out: AccountInfo<'info>,
+
+    /// CHECK: PDA for LP mint authority; seed ensures correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's account for the 'in' token; checked when a protocol fee is paid
+    #[account(mut)]
+    pub protocol_fee_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+}
+
+#[derive(Accounts)]
+pub struct ManagedTransfer<'info> {
+    pub pool: Account<'info, LbpPool>,
+
+    /// The Vault's asset manager authority PDA; the Vault signs for it
+    /// only in `withdraw_managed`
+    #[account(
+        seeds = [b"asset-manager-authority", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub asset_manager_authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: pool custody account, one the Vault recorded for the pool;
+    /// the token program enforces ownership
+    #[account(
+        mut,
+        constraint = pool_registration.token_accounts.contains(&from.key()) @ ErrorCode::InvalidPoolTokenAccount
+    )]
+    pub from: AccountInfo<'info>,
+
+    /// CHECK: asset manager's destination, chosen and checked by the Vault
+    #[account(mut)]
+    pub to: AccountInfo<'info>,
+
+    /// CHECK: PDA holding the pool's tokens
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct QuerySwap<'info> {
+    pub pool: Account<'info, LbpPool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: pool custody account of the token in; identifies the token only
+    pub pool_token_in: AccountInfo<'info>,
+
+    /// CHECK: pool custody account of the token out; identifies the token only
+    pub pool_token_out: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// Sale configuration fixed at initialisation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SaleParams {
+    /// Weights (18‑dec, summing to one) until `start_time`
+    pub start_weights: [u128; 2],
+    /// Weights reached at `end_time`
+    pub end_weights: [u128; 2],
+    /// Start of the weight schedule
+    pub start_time: i64,
+    /// End of the schedule and of the sale: swaps stop for good
+    pub end_time: i64,
+    pub swap_fee: u64,
+    /// Whether swaps are open from the start
+    pub swap_enabled: bool,
+}
+
+impl SaleParams {
+    fn validate(&self, now: i64) -> Result<()> {
+        require!(self.start_time < self.end_time && now < self.end_time, ErrorCode::InvalidSchedule);
+        for weights in [self.start_weights, self.end_weights] {
+            require!(
+                weights.iter().all(|w| *w >= MIN_WEIGHT)
+                    && weights[0].checked_add(weights[1]) == Some(fixed::ONE.as_u128()),
+                ErrorCode::InvalidWeights
+            );
+        }
+        Ok(())
+    }
+}
+
+#[account]
+pub struct LbpPool {
+    pub vault: Pubkey,
+    pub lp_mint: Pubkey,
+    /// The sale's creator: sole liquidity provider, controls the swap toggle
+    pub owner: Pubkey,
+    /// Token mints, in Vault registration order
+    pub tokens: [Pubkey; 2],
+    /// Decimals of each token, scaling raw amounts to 18‑dec
+    pub decimals: [u8; 2],
+    /// Decimals of the LP mint, scaling BPT amounts to 18‑dec
+    pub lp_decimals: u8,
+    pub start_weights: [u128; 2],
+    pub end_weights: [u128; 2],
+    pub start_time: i64,
+    pub end_time: i64,
+    pub swap_fee: u64,
+    pub swap_enabled: bool,
+    pub total_bpt: u64,
+    /// PoolId assigned by the Vault at registration
+    pub pool_id: [u8; 32],
+}
+
+impl LbpPool {
+    pub const INIT_SPACE: usize = 32 * 3 + 32 * 2 + 2 + 1 + 16 * 2 * 2 + 8 + 8 + 8 + 1 + 8 + 32;
+
+    /// The sale is over: swaps are frozen for good.
+    pub fn is_ended(&self, now: i64) -> bool {
+        now >= self.end_time
+    }
+
+    /// Weights at `now` on the schedule.
+    pub fn weights_at(&self, now: i64) -> Result<[U256; 2]> {
+        let start = self.start_weights.map(U256::from);
+        let end = self.end_weights.map(U256::from);
+        let weights = weighted_math::try_interpolate_weights(&start, &end, self.start_time, self.end_time, now)
+            .map_err(ErrorCode::from)?;
+        Ok([weights[0], weights[1]])
+    }
+
+    fn index_of(&self, mint: &Pubkey) -> Result<usize> {
+        Ok(self.tokens.iter().position(|t| t == mint).ok_or(ErrorCode::InvalidPoolTokenAccount)?)
+    }
+}
+
+/// The creator opened or closed swaps
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapEnabledSet {
+    pub pool: Pubkey,
+    pub swap_enabled: bool,
+}
+
+/// A crank closed swaps on an ended sale
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaleFinalized {
+    pub pool: Pubkey,
+    pub cranker: Pubkey,
+    /// Lamports paid to the cranker
+    pub bounty: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Vector length mismatch")]
+    LengthMismatch,
+    #[msg("Math underflow or overflow")]
+    MathUnderflow,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Protocol fee account does not belong to the fees collector")]
+    InvalidFeeAccount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Swap amount in exceeds the maximum in ratio")]
+    MaxInRatio,
+    #[msg("Swap amount out exceeds the maximum out ratio")]
+    MaxOutRatio,
+    #[msg("Weights are out of range or do not sum to one")]
+    InvalidWeights,
+    #[msg("Math solver did not converge")]
+    MathConvergenceFailure,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("Sale must end after it starts, and in the future")]
+    InvalidSchedule,
+    #[msg("The sale has ended")]
+    SaleEnded,
+    #[msg("Swaps are disabled")]
+    SwapsDisabled,
+    #[msg("Token account does not hold one of the pool's tokens")]
+    InvalidPoolTokenAccount,
+    #[msg("The first join must add both tokens")]
+    InvalidInitialJoin,
+    #[msg("Amount is outside the caller's limit")]
+    SlippageLimit,
+    #[msg("No transition is due")]
+    NothingToCrank,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::LengthMismatch => ErrorCode::LengthMismatch,
+            MathError::WeightsNotNormalized | MathError::InvalidWeight => ErrorCode::InvalidWeights,
+            MathError::MaxInRatio => ErrorCode::MaxInRatio,
+            MathError::MaxOutRatio => ErrorCode::MaxOutRatio,
+            MathError::ConvergenceFailure => ErrorCode::MathConvergenceFailure,
+            MathError::TokenIndexOutOfBounds
+            | MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::InvalidParams
+            | MathError::AssetBoundsExceeded
+            | MathError::InvalidPercentage
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange => ErrorCode::MathInputOutOfBounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+    const E6: u64 = 1_000_000;
+
+    /// 90/10 → 30/70 over 1000 s, project token first
+    fn sale() -> LbpPool {
+        LbpPool {
+            vault: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            owner: Pubkey::default(),
+            tokens: [Pubkey::new_unique(), Pubkey::new_unique()],
+            decimals: [6, 6],
+            lp_decimals: 6,
+            start_weights: [9 * E18 / 10, E18 / 10],
+            end_weights: [3 * E18 / 10, 7 * E18 / 10],
+            start_time: 1_000,
+            end_time: 2_000,
+            swap_fee: 0,
+            swap_enabled: true,
+            total_bpt: 0,
+            pool_id: [0; 32],
+        }
+    }
+
+    /// (in, out) balances, in whole 6‑dec tokens
+    fn balances(a: u64, b: u64) -> [U256; 2] {
+        [scale_up(a * E6, 6).unwrap(), scale_up(b * E6, 6).unwrap()]
+    }
+
+    #[test]
+    fn weights_follow_the_schedule() {
+        let pool = sale();
+        assert_eq!(pool.weights_at(0).unwrap(), pool.start_weights.map(U256::from));
+        assert_eq!(pool.weights_at(1_500).unwrap(), [U256::from(6 * E18 / 10), U256::from(4 * E18 / 10)]);
+        assert_eq!(pool.weights_at(5_000).unwrap(), pool.end_weights.map(U256::from));
+    }
+
+    #[test]
+    fn project_token_gets_cheaper_as_the_sale_runs() {
+        let pool = sale();
+        // buy the project token (index 0) with 1000 units of collateral;
+        // balances are (collateral, project)
+        let early = quote_exact_in(&pool, 0, 1_000, 1, 1_000 * E6, balances(10_000, 1_000_000)).unwrap();
+        let late = quote_exact_in(&pool, 0, 1_900, 1, 1_000 * E6, balances(10_000, 1_000_000)).unwrap();
+        assert!(late.amount_out > early.amount_out, "{} {}", early.amount_out, late.amount_out);
+    }
+
+    #[test]
+    fn swaps_stop_when_disabled_or_over() {
+        let mut pool = sale();
+        let quote = |pool: &LbpPool, now| quote_exact_in(pool, 0, now, 1, E6, balances(10_000, 1_000_000));
+        assert!(quote(&pool, 1_999).is_ok());
+        assert_eq!(quote(&pool, 2_000).unwrap_err(), error!(ErrorCode::SaleEnded));
+        pool.swap_enabled = false;
+        assert_eq!(quote(&pool, 1_500).unwrap_err(), error!(ErrorCode::SwapsDisabled));
+    }
+
+    #[test]
+    fn sale_params_are_validated() {
+        let pool = sale();
+        let params = SaleParams {
+            start_weights: pool.start_weights,
+            end_weights: pool.end_weights,
+            start_time: pool.start_time,
+            end_time: pool.end_time,
+            swap_fee: 0,
+            swap_enabled: false,
+        };
+        assert!(params.validate(0).is_ok());
+        assert_eq!(params.validate(2_000).unwrap_err(), error!(ErrorCode::InvalidSchedule));
+        let backwards = SaleParams { end_time: 500, ..params };
+        assert_eq!(backwards.validate(0).unwrap_err(), error!(ErrorCode::InvalidSchedule));
+        let unnormalized = SaleParams { end_weights: [E18 / 2, E18 / 3], ..params };
+        assert_eq!(unnormalized.validate(0).unwrap_err(), error!(ErrorCode::InvalidWeights));
+        let tiny = SaleParams { end_weights: [E18 - MIN_WEIGHT / 2, MIN_WEIGHT / 2], ..params };
+        assert_eq!(tiny.validate(0).unwrap_err(), error!(ErrorCode::InvalidWeights));
+    }
+}
@@ -0,0 +1,484 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+
+use vault::program::Vault;
+use vault::{BatchSwapStep, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("LimitUjp1qzr6uDSZf5g7aCtfCSYrQ1ZqWEPZk4WJbW");
+
+/// Fixed‑point scale of limit prices
+pub const PRICE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Limit orders resting on pool liquidity. A maker escrows the tokens to
+/// sell with a limit price (least tokens out per token in), an expiry and
+/// a tip in lamports. Once the pools' price crosses the limit, any keeper
+/// fills the whole order through `vault::batch_swap` along the route of
+/// its choosing, and earns the tip; the Vault's limits guarantee the
+/// maker at least the limit price. Each order's ["order-authority",
+/// order] PDA is the batch swap's user and holds its escrow and proceeds.
+#[program]
+pub mod limit_orders {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Escrow `amount_in` of `mint_in` to buy `mint_out` at no less
+       than `limit_price` (18‑dec out per in) until `expiry`; `tip`
+       lamports go to the keeper filling it
+    ---------------------------------------------------------------- */
+    pub fn place_order(
+        ctx: Context<PlaceOrder>,
+        id: u64,
+        amount_in: u64,
+        limit_price: u128,
+        expiry: i64,
+        tip: u64,
+    ) -> Result<()> {
+        require!(amount_in > 0, ErrorCode::ZeroAmount);
+        require!(limit_price > 0, ErrorCode::InvalidLimitPrice);
+        require!(expiry > Clock::get()?.unix_timestamp, ErrorCode::OrderExpired);
+        require_keys_neq!(ctx.accounts.mint_in.key(), ctx.accounts.mint_out.key(), ErrorCode::SameToken);
+        let min_amount_out = min_amount_out(amount_in, limit_price)?;
+
+        let order = &mut ctx.accounts.order;
+        order.maker          = ctx.accounts.maker.key();
+        order.vault          = ctx.accounts.vault_state.key();
+        order.id             = id;
+        order.mint_in        = ctx.accounts.mint_in.key();
+        order.mint_out       = ctx.accounts.mint_out.key();
+        order.amount_in      = amount_in;
+        order.min_amount_out = min_amount_out;
+        order.expiry         = expiry;
+        order.tip            = tip;
+        order.authority_bump = ctx.bumps.order_authority;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.maker_token_in.to_account_info(),
+                    to:        ctx.accounts.escrow.to_account_info(),
+                    authority: ctx.accounts.maker.to_account_info(),
+                },
+            ),
+            amount_in,
+        )?;
+        if tip > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.maker.to_account_info(),
+                        to:   ctx.accounts.order.to_account_info(),
+                    },
+                ),
+                tip,
+            )?;
+        }
+
+        emit!(OrderPlaced {
+            order: ctx.accounts.order.key(),
+            maker: ctx.accounts.maker.key(),
+            mint_in: ctx.accounts.mint_in.key(),
+            mint_out: ctx.accounts.mint_out.key(),
+            amount_in,
+            min_amount_out,
+            expiry,
+            tip,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Fill an order – any keeper, before expiry
+       remaining_accounts: batch swap accounts with assets
+       [mint_in, mint_out, intermediates…], the order's escrow and
+       proceeds accounts as the user side of the first two
+       The whole escrow must be sold; the proceeds go to the maker and
+       the tip to the keeper.
+    ---------------------------------------------------------------- */
+    pub fn fill_order<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FillOrder<'info>>,
+        steps: Vec<BatchSwapStep>,
+        deadline: i64,
+    ) -> Result<()> {
+        let order = &ctx.accounts.order;
+        require!(Clock::get()?.unix_timestamp <= order.expiry, ErrorCode::OrderExpired);
+        let rem = ctx.remaining_accounts;
+        require!(
+            rem.len() > 4
+                && rem[0].key() == ctx.accounts.escrow.key()
+                && rem[2].key() == ctx.accounts.proceeds.key(),
+            ErrorCode::InvalidSwapAccounts
+        );
+        let mut limits = vec![0i64; limits_len(rem, &steps)?];
+        limits[0] = i64::try_from(order.amount_in).map_err(|_| ErrorCode::MathOverflow)?;
+        limits[1] = -i64::try_from(order.min_amount_out).map_err(|_| ErrorCode::MathOverflow)?;
+
+        let order_key = order.key();
+        let bump_arr  = [order.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"order-authority", order_key.as_ref(), &bump_arr];
+
+        vault::cpi::batch_swap(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_program.to_account_info(),
+                vault::cpi::accounts::BatchSwap {
+                    vault_state:      ctx.accounts.vault_state.to_account_info(),
+                    vault_authority:  ctx.accounts.vault_authority.clone(),
+                    user:             ctx.accounts.order_authority.clone(),
+                    sender:           ctx.accounts.order_authority.clone(),
+                    relayer_approval: None,
+                    permit_nonce:     None,
+                    instructions:     None,
+                    system_program:   None,
+                    fees_collector:   ctx.accounts.fees_collector.clone(),
+                    token_program:    ctx.accounts.token_program.to_account_info(),
+                },
+                &[seed_slice],
+            )
+            .with_remaining_accounts(rem.to_vec()),
+            steps,
+            limits,
+            deadline,
+        )?;
+
+        ctx.accounts.escrow.reload()?;
+        ctx.accounts.proceeds.reload()?;
+        require!(ctx.accounts.escrow.amount == 0, ErrorCode::PartialFill);
+        let amount_out = ctx.accounts.proceeds.amount;
+        ctx.accounts.send_proceeds(seed_slice, amount_out)?;
+        ctx.accounts.close_token_accounts(seed_slice)?;
+
+        // the tip, before the order closes to the maker
+        let tip = ctx.accounts.order.tip;
+        if tip > 0 {
+            **ctx.accounts.order.to_account_info().try_borrow_mut_lamports()? -= tip;
+            **ctx.accounts.keeper.to_account_info().try_borrow_mut_lamports()? += tip;
+        }
+
+        emit!(OrderFilled {
+            order:     order_key,
+            maker:     ctx.accounts.order.maker,
+            keeper:    ctx.accounts.keeper.key(),
+            amount_in: ctx.accounts.order.amount_in,
+            amount_out,
+            tip,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Cancel an order – the maker any time, anyone once expired
+       Everything escrowed, the tip and the rent go back to the maker.
+    ---------------------------------------------------------------- */
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        let order = &ctx.accounts.order;
+        require!(
+            ctx.accounts.caller.key() == order.maker || Clock::get()?.unix_timestamp > order.expiry,
+            ErrorCode::NotExpired
+        );
+        let order_key = order.key();
+        let bump_arr  = [order.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"order-authority", order_key.as_ref(), &bump_arr];
+        let token_prog = ctx.accounts.token_program.to_account_info();
+
+        let refund = ctx.accounts.escrow.amount;
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_prog.clone(),
+                Transfer {
+                    from:      ctx.accounts.escrow.to_account_info(),
+                    to:        ctx.accounts.maker_token_in.to_account_info(),
+                    authority: ctx.accounts.order_authority.clone(),
+                },
+                &[seed_slice],
+            ),
+            refund,
+        )?;
+        for account in [ctx.accounts.escrow.to_account_info(), ctx.accounts.proceeds.to_account_info()] {
+            token::close_account(CpiContext::new_with_signer(
+                token_prog.clone(),
+                CloseAccount {
+                    account,
+                    destination: ctx.accounts.maker.clone(),
+                    authority:   ctx.accounts.order_authority.clone(),
+                },
+                &[seed_slice],
+            ))?;
+        }
+
+        emit!(OrderCancelled { order: order_key, maker: order.maker, refund });
+        Ok(())
+    }
+}
+
+/// Least `mint_out` an order of `amount_in` fills for at `limit_price`.
+pub fn min_amount_out(amount_in: u64, limit_price: u128) -> Result<u64> {
+    let out = (amount_in as u128)
+        .checked_mul(limit_price)
+        .ok_or(ErrorCode::MathOverflow)?
+        .div_ceil(PRICE_PRECISION);
+    // batch swap limits are i64
+    require!(out > 0 && out <= i64::MAX as u128, ErrorCode::InvalidLimitPrice);
+    Ok(out as u64)
+}
+
+/// Number of batch assets: the remaining accounts before the step groups.
+fn limits_len(rem: &[AccountInfo], steps: &[BatchSwapStep]) -> Result<usize> {
+    let assets = steps
+        .iter()
+        .flat_map(|s| [s.asset_in_index, s.asset_out_index])
+        .max()
+        .map_or(0, |i| i as usize + 1);
+    require!(assets >= 2 && rem.len() >= assets * 2, ErrorCode::InvalidSwapAccounts);
+    Ok(assets)
+}
+
+impl<'info> FillOrder<'info> {
+    /// proceeds → maker
+    fn send_proceeds(&self, seed_slice: &[&[u8]], amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from:      self.proceeds.to_account_info(),
+                    to:        self.maker_token_out.to_account_info(),
+                    authority: self.order_authority.clone(),
+                },
+                &[seed_slice],
+            ),
+            amount,
+        )
+    }
+
+    /// Return the escrow and proceeds rent to the maker.
+    fn close_token_accounts(&self, seed_slice: &[&[u8]]) -> Result<()> {
+        for account in [self.escrow.to_account_info(), self.proceeds.to_account_info()] {
+            token::close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account,
+                    destination: self.maker.clone(),
+                    authority:   self.order_authority.clone(),
+                },
+                &[seed_slice],
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+#[instruction(id: u64)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    /// The vault whose pools fill the order
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    pub mint_in: Box<Account<'info, Mint>>,
+    pub mint_out: Box<Account<'info, Mint>>,
+
+    #[account(mut, token::mint = mint_in, token::authority = maker)]
+    pub maker_token_in: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Order::INIT_SPACE,
+        seeds = [b"order", maker.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub order: Box<Account<'info, Order>>,
+
+    /// CHECK: PDA trading for the order and holding its tokens
+    #[account(seeds = [b"order-authority", order.key().as_ref()], bump)]
+    pub order_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"order-escrow", order.key().as_ref()],
+        bump,
+        token::mint = mint_in,
+        token::authority = order_authority
+    )]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = maker,
+        seeds = [b"order-proceeds", order.key().as_ref()],
+        bump,
+        token::mint = mint_out,
+        token::authority = order_authority
+    )]
+    pub proceeds: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FillOrder<'info> {
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    #[account(mut, close = maker, has_one = maker)]
+    pub order: Box<Account<'info, Order>>,
+
+    /// CHECK: the order's maker; receives the rent back
+    #[account(mut)]
+    pub maker: AccountInfo<'info>,
+
+    #[account(mut, token::mint = order.mint_out, token::authority = order.maker)]
+    pub maker_token_out: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA trading for the order; the batch swap's user and sender
+    #[account(mut, seeds = [b"order-authority", order.key().as_ref()], bump = order.authority_bump)]
+    pub order_authority: AccountInfo<'info>,
+
+    #[account(mut, seeds = [b"order-escrow", order.key().as_ref()], bump)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"order-proceeds", order.key().as_ref()], bump)]
+    pub proceeds: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = order.vault)]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// CHECK: vault authority PDA; checked by the vault
+    #[account(mut)]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: the vault's fees collector; checked by the vault
+    pub fees_collector: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, Vault>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    /// The maker, or anyone once the order has expired
+    pub caller: Signer<'info>,
+
+    #[account(mut, close = maker, has_one = maker)]
+    pub order: Box<Account<'info, Order>>,
+
+    /// CHECK: the order's maker; receives the tip and rent back
+    #[account(mut)]
+    pub maker: AccountInfo<'info>,
+
+    #[account(mut, token::mint = order.mint_in, token::authority = order.maker)]
+    pub maker_token_in: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA holding the order's tokens
+    #[account(seeds = [b"order-authority", order.key().as_ref()], bump = order.authority_bump)]
+    pub order_authority: AccountInfo<'info>,
+
+    #[account(mut, seeds = [b"order-escrow", order.key().as_ref()], bump)]
+    pub escrow: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, seeds = [b"order-proceeds", order.key().as_ref()], bump)]
+    pub proceeds: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// A resting order, PDA'd by ["order", maker, id (LE u64)]
+#[account]
+#[derive(InitSpace)]
+pub struct Order {
+    pub maker: Pubkey,
+    pub vault: Pubkey,
+    pub id: u64,
+    /// Token sold
+    pub mint_in: Pubkey,
+    /// Token bought
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    /// `amount_in` at the limit price, rounded up
+    pub min_amount_out: u64,
+    pub expiry: i64,
+    /// Lamports held for the keeper, on top of the rent
+    pub tip: u64,
+    /// Bump of the ["order-authority", order] PDA
+    pub authority_bump: u8,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderPlaced {
+    pub order: Pubkey,
+    pub maker: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub amount_in: u64,
+    pub min_amount_out: u64,
+    pub expiry: i64,
+    pub tip: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderFilled {
+    pub order: Pubkey,
+    pub maker: Pubkey,
+    pub keeper: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub tip: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderCancelled {
+    pub order: Pubkey,
+    pub maker: Pubkey,
+    /// Tokens returned from escrow
+    pub refund: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Limit price is zero or too high")]
+    InvalidLimitPrice,
+    #[msg("Order tokens must differ")]
+    SameToken,
+    #[msg("Order has expired")]
+    OrderExpired,
+    #[msg("Only the maker may cancel before expiry")]
+    NotExpired,
+    #[msg("Swap assets must start with the order's escrow and proceeds accounts")]
+    InvalidSwapAccounts,
+    #[msg("The whole order must be filled")]
+    PartialFill,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_price_sets_a_rounded_up_minimum() {
+        // sell 1000 at ≥ 0.5 out each
+        assert_eq!(min_amount_out(1_000, PRICE_PRECISION / 2).unwrap(), 500);
+        assert_eq!(min_amount_out(3, PRICE_PRECISION / 2).unwrap(), 2);
+        assert!(min_amount_out(u64::MAX, 2 * PRICE_PRECISION).is_err());
+    }
+}
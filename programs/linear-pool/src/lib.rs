@@ -0,0 +1,849 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, MintTo, Token, Transfer};
+use anchor_lang::solana_program::program::{get_return_data, invoke};
+use common::pool_interface::SwapQuote;
+use common::pool_tokens::{self, TokenAccount};
+use common::{derive_pool_id, rate_provider, Specialization};
+use math::linear_math::{self, Balances, Params, BPT_INDEX, MAIN_INDEX, WRAPPED_INDEX};
+use math::{fixed, MathError, U256};
+
+// Vault CPI interfaces and the program struct itself
+use vault::cpi::accounts::{RegisterPool as VaultRegisterPool, RegisterTokens as VaultRegisterTokens};
+use vault::cpi::{register_pool as vault_register_pool, register_tokens as vault_register_tokens};
+use vault::program::Vault as VaultProgram;
+use vault::{Action, Authorizer, PoolRegistration, ProtocolFeesCollector, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("LPoo18FT7mUt6fJwr5d8o228PqGXE7AMicg9k1RvLcB");
+
+/// BPT a linear pool mints into its own custody at initialisation: its
+/// whole supply
+pub const PREMINTED_BPT: u64 = u64::MAX;
+
+/// Linear pools of a main token and a wrapped, yield‑bearing version of
+/// it (Balancer `LinearPool` maths, see `math::linear_math`), the building
+/// block under boosted pools. Main, wrapped and the pool's own BPT trade
+/// 1:1, the wrapped token at the rate of its rate provider; instead of a
+/// price curve a fee on the main balance moved outside a target range
+/// keeps a buffer of main tokens for cheap trading, and pays whoever
+/// brings the balance back into range. Wrapping and unwrapping happen
+/// outside the pool, by arbitrageurs earning those fees.
+///
+/// Like a composable stable pool, the whole BPT supply is pre‑minted into
+/// the pool's custody and listed as its last token: there are no joins or
+/// exits, only swaps into and out of BPT. The pool registers with the
+/// Vault as `General` with tokens [main, wrapped, BPT]; the main and
+/// wrapped custody accounts are then registered through the Vault as for
+/// any pool. Fees stay in the pool: there is no protocol swap fee.
+#[program]
+pub mod linear_pool {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Initialise a pool and pre‑mint its BPT
+       Targets are raw main token amounts; `rate_provider` is the
+       wrapped token's rate account, `Pubkey::default()` for none.
+    ---------------------------------------------------------------- */
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        lower_target: u64,
+        upper_target: u64,
+        swap_fee: u64,
+        rate_provider: Pubkey,
+    ) -> Result<()> {
+        require_keys_neq!(ctx.accounts.main_mint.key(), ctx.accounts.wrapped_mint.key(), ErrorCode::InvalidPoolTokenAccount);
+        let pool = &mut ctx.accounts.pool;
+        pool.vault             = ctx.accounts.vault_state.key();
+        pool.lp_mint           = ctx.accounts.lp_mint.key();
+        pool.main_mint         = ctx.accounts.main_mint.key();
+        pool.wrapped_mint      = ctx.accounts.wrapped_mint.key();
+        pool.decimals          = [
+            mint_decimals(&ctx.accounts.main_mint)?,
+            mint_decimals(&ctx.accounts.wrapped_mint)?,
+            mint_decimals(&ctx.accounts.lp_mint)?,
+        ];
+        pool.lower_target      = lower_target;
+        pool.upper_target      = upper_target;
+        pool.swap_fee          = swap_fee;
+        pool.total_bpt         = 0;
+        pool.rate_provider     = rate_provider;
+        pool.wrapped_rate      = fixed::ONE.as_u128();
+        pool.rate_updated_slot = 0;
+        pool.params()?.validate().map_err(ErrorCode::from)?;
+
+        // register with the Vault: BPT prices need every balance
+        let specialization = Specialization::General;
+        let pool_id = derive_pool_id(
+            &ctx.accounts.vault_state.key(),
+            &ctx.accounts.pool.key(),
+            specialization,
+            ctx.accounts.vault_state.pool_count,
+        );
+        let cpi_accounts = VaultRegisterPool {
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.payer.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        let registered = vault_register_pool(
+            CpiContext::new(ctx.accounts.vault_program.to_account_info(), cpi_accounts),
+            pool_id,
+            specialization,
+            ctx.accounts.pool.tokens().to_vec(),
+        )?;
+        ctx.accounts.pool.pool_id = registered.get();
+
+        ctx.accounts.premint_bpt(ctx.bumps.lp_mint_authority)
+    }
+
+    /* ---------------------------------------------------------------
+       Set the target range – requires the `SetSwapFee` role
+       The main balance must lie inside both the old and the new range,
+       so no fee owed or earned changes hands.
+    ---------------------------------------------------------------- */
+    pub fn set_targets(ctx: Context<SetParams>, lower_target: u64, upper_target: u64) -> Result<()> {
+        ctx.accounts.check_authority()?;
+        let main = ctx.accounts.main_balance()?;
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.within_targets(main), ErrorCode::MainBalanceOutsideTargets);
+        pool.lower_target = lower_target;
+        pool.upper_target = upper_target;
+        pool.params()?.validate().map_err(ErrorCode::from)?;
+        require!(pool.within_targets(main), ErrorCode::MainBalanceOutsideTargets);
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Set swap fee – requires the `SetSwapFee` role
+       Only while the main balance is inside the target range, where the
+       fee owed is zero.
+    ---------------------------------------------------------------- */
+    pub fn set_swap_fee(ctx: Context<SetParams>, swap_fee: u64) -> Result<()> {
+        ctx.accounts.check_authority()?;
+        let main = ctx.accounts.main_balance()?;
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.within_targets(main), ErrorCode::MainBalanceOutsideTargets);
+        pool.swap_fee = swap_fee;
+        pool.params()?.validate().map_err(ErrorCode::from)?;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Refresh the cached wrapped token rate – anyone may call
+    ---------------------------------------------------------------- */
+    pub fn update_wrapped_rate(ctx: Context<UpdateWrappedRate>) -> Result<()> {
+        let provider = &ctx.accounts.provider_program;
+        let rate_account = &ctx.accounts.rate_account;
+        require_keys_eq!(provider.key(), *rate_account.owner, ErrorCode::InvalidRateProvider);
+        invoke(
+            &rate_provider::get_rate(provider.key(), rate_account.key()),
+            &[rate_account.clone(), provider.clone()],
+        )?;
+        let pool = &mut ctx.accounts.pool;
+        pool.wrapped_rate = match get_return_data() {
+            Some((program_id, data)) if program_id == provider.key() => rate_provider::parse_rate(&data),
+            _ => None,
+        }
+        .ok_or(ErrorCode::InvalidRateProvider)?;
+        pool.rate_updated_slot = Clock::get()?.slot;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – exact in → out between any two of main, wrapped and BPT
+       remaining_accounts: [vault_main, vault_wrapped, vault_bpt], as the
+       Vault's batch swap passes them to General pools.
+       Returns the amount sent out (read by the Vault's batch swap).
+    ---------------------------------------------------------------- */
+    pub fn swap_exact_token_in_for_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        ctx.accounts.check_live()?;
+        let custody = ctx.remaining_accounts;
+        let raw     = read_balances(&ctx.accounts.pool, Some(&ctx.accounts.pool_registration), custody)?;
+        let (index_in, index_out) = swap_indices(custody, &ctx.accounts.vault_in, &ctx.accounts.vault_out)?;
+
+        let SwapQuote { amount_out, .. } = quote_exact_in(&ctx.accounts.pool, raw, index_in, index_out, amount_in)?;
+        require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+
+        ctx.accounts.settle(ctx.bumps.lp_mint_authority, amount_in, amount_out)?;
+        Ok(amount_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – in → exact out
+       remaining_accounts: [vault_main, vault_wrapped, vault_bpt]
+       Returns the amount taken in.
+    ---------------------------------------------------------------- */
+    pub fn swap_token_in_for_exact_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<u64> {
+        ctx.accounts.check_live()?;
+        let custody = ctx.remaining_accounts;
+        let raw     = read_balances(&ctx.accounts.pool, Some(&ctx.accounts.pool_registration), custody)?;
+        let (index_in, index_out) = swap_indices(custody, &ctx.accounts.vault_in, &ctx.accounts.vault_out)?;
+
+        let amount_in = quote_exact_out(&ctx.accounts.pool, raw, index_in, index_out, amount_out)?;
+        require!(amount_in <= maximum_amount_in, ErrorCode::SlippageLimit);
+
+        ctx.accounts.settle(ctx.bumps.lp_mint_authority, amount_in, amount_out)?;
+        Ok(amount_in)
+    }
+
+    /* ---------------------------------------------------------------
+       Managed transfer – move idle cash to an asset manager
+       Only the Vault may ask (its asset manager authority PDA must
+       sign); it checks the manager and tracks the managed balance.
+    ---------------------------------------------------------------- */
+    pub fn managed_transfer(ctx: Context<ManagedTransfer>, amount: u64) -> Result<()> {
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.from.clone(),
+            to:        ctx.accounts.to.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Query – price an exact‑in swap against caller‑supplied balances
+       remaining_accounts: [vault_main, vault_wrapped, vault_bpt]
+       Read‑only; used by the Vault's query_batch_swap. The balances of
+       the tokens swapped are the arguments; the other one is read from
+       its custody account (cash only). Prices at the cached rate.
+    ---------------------------------------------------------------- */
+    pub fn query_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, QuerySwap<'info>>,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Result<SwapQuote> {
+        let pool    = &ctx.accounts.pool;
+        let custody = ctx.remaining_accounts;
+        let mut raw = read_balances(pool, None, custody)?;
+        let (index_in, index_out) =
+            swap_indices(custody, &ctx.accounts.pool_token_in, &ctx.accounts.pool_token_out)?;
+        raw[index_in]  = balance_in;
+        raw[index_out] = balance_out;
+        quote_exact_in(pool, raw, index_in, index_out, amount_in)
+    }
+}
+
+/// Exact‑in swap pricing shared by the swap and its query. `raw` are the
+/// custody balances [main, wrapped, BPT held by the pool].
+fn quote_exact_in(pool: &LinearPool, raw: [u64; 3], index_in: usize, index_out: usize, amount_in: u64) -> Result<SwapQuote> {
+    let out_fp = linear_math::try_calc_out_given_in(
+        &pool.params()?,
+        &pool.balances(raw)?,
+        index_in,
+        index_out,
+        pool.upscale(index_in, amount_in)?,
+    ).map_err(ErrorCode::from)?;
+    Ok(SwapQuote {
+        amount_out:   pool.downscale_down(index_out, out_fp)?,
+        protocol_fee: 0,
+    })
+}
+
+/// Exact‑out swap pricing: the amount in, rounded up.
+fn quote_exact_out(pool: &LinearPool, raw: [u64; 3], index_in: usize, index_out: usize, amount_out: u64) -> Result<u64> {
+    let in_fp = linear_math::try_calc_in_given_out(
+        &pool.params()?,
+        &pool.balances(raw)?,
+        index_in,
+        index_out,
+        pool.upscale(index_out, amount_out)?,
+    ).map_err(ErrorCode::from)?;
+    pool.downscale_up(index_in, in_fp)
+}
+
+/// Raw custody balances [main, wrapped, BPT held]. With a registration,
+/// custody accounts must be the registered ones and amounts held by
+/// asset managers count towards the balance.
+fn read_balances(pool: &LinearPool, registration: Option<&PoolRegistration>, custody: &[AccountInfo]) -> Result<[u64; 3]> {
+    require!(custody.len() == 3, ErrorCode::LengthMismatch);
+    let tokens = pool.tokens();
+    let mut raw = [0u64; 3];
+    for (i, ai) in custody.iter().enumerate() {
+        let acct = unpack_token_account(ai)?;
+        require_keys_eq!(acct.mint, tokens[i], ErrorCode::InvalidPoolTokenAccount);
+        raw[i] = acct.amount;
+        if let Some(registration) = registration {
+            let registered = registration.token_accounts.get(i).copied().unwrap_or_default();
+            require_keys_eq!(ai.key(), registered, ErrorCode::InvalidPoolTokenAccount);
+            raw[i] = raw[i]
+                .checked_add(registration.managed_balance(&acct.mint))
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+    }
+    Ok(raw)
+}
+
+/// Positions of the custody accounts swapped among all of them.
+fn swap_indices(custody: &[AccountInfo], token_in: &AccountInfo, token_out: &AccountInfo) -> Result<(usize, usize)> {
+    let index_of = |ai: &AccountInfo| custody.iter().position(|c| c.key() == ai.key());
+    match (index_of(token_in), index_of(token_out)) {
+        (Some(i), Some(o)) if i != o => Ok((i, o)),
+        _ => err!(ErrorCode::InvalidSwapTokens),
+    }
+}
+
+/// Raw token amount as an 18‑dec value.
+fn scale_up(amount: u64, decimals: u8) -> Result<U256> {
+    Ok(pool_tokens::scale_up(amount, decimals).map_err(ErrorCode::from)?)
+}
+
+/// Decimals of a mint of either token program, checked to be scalable
+/// to 18‑dec.
+fn mint_decimals(ai: &AccountInfo) -> Result<u8> {
+    pool_tokens::mint_decimals(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A token account of either token program, its extensions skipped.
+fn unpack_token_account(ai: &AccountInfo) -> Result<TokenAccount> {
+    pool_tokens::unpack_token_account(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/* ------------------------------------------------------------------
+   Accounts: initialize & admin
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// An already‑initialized VaultState account
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// CHECK: the main token's mint; checked to be an SPL mint
+    pub main_mint: AccountInfo<'info>,
+
+    /// CHECK: the wrapped token's mint; checked to be an SPL mint
+    pub wrapped_mint: AccountInfo<'info>,
+
+    /// CHECK: the LP‑token mint for this pool, with `lp_mint_authority` as its authority
+    #[account(mut)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority for `lp_mint`; derived from `["lp-mint-authority", pool.key().as_ref()]`
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// The Pool state PDA itself, one per main/wrapped pair
+    #[account(
+        init,
+        seeds = [b"pool-state", vault_state.key().as_ref(), main_mint.key().as_ref(), wrapped_mint.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + LinearPool::INIT_SPACE
+    )]
+    pub pool: Account<'info, LinearPool>,
+
+    /// CHECK: the Vault's authority PDA; validated by the Vault
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: custody account for the pool's own BPT; created by the Vault, seeds checked there
+    #[account(mut)]
+    pub pool_bpt: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializePool<'info> {
+    /// Have the Vault create the custody account of the pool's own BPT,
+    /// then mint the whole supply into it.
+    fn premint_bpt(&mut self, bump: u8) -> Result<()> {
+        let cpi_accounts = VaultRegisterTokens {
+            vault_state:              self.vault_state.to_account_info(),
+            authorizer:               self.authorizer.clone(),
+            pool_registration:        self.pool_registration.clone(),
+            vault_authority:          self.vault_authority.clone(),
+            transfer_hook_allowlist:  None,
+            mint_extension_overrides: None,
+            authority:                self.payer.to_account_info(),
+            token_program:            self.token_program.to_account_info(),
+            system_program:           self.system_program.to_account_info(),
+        };
+        vault_register_tokens(
+            CpiContext::new(self.vault_program.to_account_info(), cpi_accounts)
+                .with_remaining_accounts(vec![self.lp_mint.clone(), self.pool_bpt.clone()]),
+            vec![Pubkey::default()],
+        )?;
+
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = MintTo {
+            mint:      self.lp_mint.clone(),
+            to:        self.pool_bpt.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            PREMINTED_BPT,
+        )?;
+        self.pool.total_bpt = PREMINTED_BPT;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetParams<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, LinearPool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: custody account of the main token; checked against the registration
+    pub vault_main: AccountInfo<'info>,
+}
+
+impl SetParams<'_> {
+    fn check_authority(&self) -> Result<()> {
+        require!(
+            self.authorizer.can_perform(Action::SetSwapFee, &self.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// Main balance, managed part included.
+    fn main_balance(&self) -> Result<u64> {
+        let registered = self.pool_registration.token_accounts.get(MAIN_INDEX).copied();
+        require!(registered == Some(self.vault_main.key()), ErrorCode::InvalidPoolTokenAccount);
+        let acct = unpack_token_account(&self.vault_main)?;
+        Ok(acct
+            .amount
+            .checked_add(self.pool_registration.managed_balance(&acct.mint))
+            .ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateWrappedRate<'info> {
+    #[account(mut, constraint = pool.rate_provider != Pubkey::default() @ ErrorCode::InvalidRateProvider)]
+    pub pool: Account<'info, LinearPool>,
+
+    /// CHECK: the wrapped token's rate account
+    #[account(address = pool.rate_provider @ ErrorCode::InvalidRateProvider)]
+    pub rate_account: AccountInfo<'info>,
+
+    /// CHECK: the program owning `rate_account`
+    pub provider_program: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   Accounts: swap context (same layout as every pool's, see
+   `common::pool_interface::SwapExactInAccounts`)
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct SwapContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, LinearPool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: custody account of the 'in' token; must be one of the remaining accounts
+    #[account(mut)]
+    pub vault_in: AccountInfo<'info>,
+
+    /// CHECK: custody account of the 'out' token; must be one of the remaining accounts
+    #[account(mut)]
+    pub vault_out: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_authority: Signer<'info>,
+
+    /// CHECK: User's token account for the 'in' mint
+    #[account(mut)]
+    pub user_token_account_in: AccountInfo<'info>,
+
+    /// CHECK: User's token account for the 'out' mint
+    #[account(mut)]
+    pub user_token_account_out: AccountInfo<'info>,
+
+    /// CHECK: PDA for LP mint authority; seed ensures correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The vault's protocol fee settings; unused, linear pools keep their fees
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's account for the 'in' token; unused
+    #[account(mut)]
+    pub protocol_fee_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+}
+
+impl<'info> SwapContext<'info> {
+    fn check_live(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!self.vault_state.is_paused(clock.unix_timestamp), ErrorCode::VaultPaused);
+        require!(!self.pool_registration.paused, ErrorCode::PoolPaused);
+        require!(self.pool.rate_is_fresh(clock.slot), ErrorCode::StaleTokenRates);
+        Ok(())
+    }
+
+    /// Move the tokens of a priced swap: the amount in to the pool, the
+    /// amount out to the user.
+    fn settle(&self, bump: u8, amount_in: u64, amount_out: u64) -> Result<()> {
+        let token_prog = self.token_program.to_account_info();
+        let cpi_in = Transfer {
+            from:      self.user_token_account_in.clone(),
+            to:        self.vault_in.clone(),
+            authority: self.user_authority.to_account_info(),
+        };
+        token::transfer(CpiContext::new(token_prog.clone(), cpi_in), amount_in)?;
+
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_out = Transfer {
+            from:      self.vault_out.clone(),
+            to:        self.user_token_account_out.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::transfer(CpiContext::new_with_signer(token_prog, cpi_out, &[seed_slice]), amount_out)
+    }
+}
+
+#[derive(Accounts)]
+pub struct ManagedTransfer<'info> {
+    pub pool: Account<'info, LinearPool>,
+
+    /// The Vault's asset manager authority PDA; the Vault signs for it
+    /// only in `withdraw_managed`
+    #[account(
+        seeds = [b"asset-manager-authority", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub asset_manager_authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: pool custody account, one the Vault recorded for the pool;
+    /// the token program enforces ownership
+    #[account(
+        mut,
+        constraint = pool_registration.token_accounts.contains(&from.key()) @ ErrorCode::InvalidPoolTokenAccount
+    )]
+    pub from: AccountInfo<'info>,
+
+    /// CHECK: asset manager's destination, chosen and checked by the Vault
+    #[account(mut)]
+    pub to: AccountInfo<'info>,
+
+    /// CHECK: PDA holding the pool's tokens
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct QuerySwap<'info> {
+    pub pool: Account<'info, LinearPool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: pool custody account of the token in; identifies the token only
+    pub pool_token_in: AccountInfo<'info>,
+
+    /// CHECK: pool custody account of the token out; identifies the token only
+    pub pool_token_out: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   State & Errors
+------------------------------------------------------------------ */
+#[account]
+#[derive(InitSpace)]
+pub struct LinearPool {
+    pub vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub main_mint: Pubkey,
+    pub wrapped_mint: Pubkey,
+    /// Decimals of [main, wrapped, BPT], scaling raw amounts to 18‑dec
+    pub decimals: [u8; 3],
+    /// Main balance below which swaps pay `swap_fee` (raw main units)
+    pub lower_target: u64,
+    /// Main balance above which swaps pay `swap_fee` (raw main units)
+    pub upper_target: u64,
+    pub swap_fee: u64,
+    /// BPT minted: the pre‑minted supply
+    pub total_bpt: u64,
+    /// PoolId assigned by the Vault at registration
+    pub pool_id: [u8; 32],
+    /// Rate account of the wrapped token, `Pubkey::default()` for none
+    pub rate_provider: Pubkey,
+    /// Cached rate of the wrapped token (18‑dec)
+    pub wrapped_rate: u128,
+    /// Slot `wrapped_rate` was last read from its provider
+    pub rate_updated_slot: u64,
+}
+
+impl LinearPool {
+    /// Mints in Vault registration order: [main, wrapped, BPT]
+    pub fn tokens(&self) -> [Pubkey; 3] {
+        [self.main_mint, self.wrapped_mint, self.lp_mint]
+    }
+
+    /// Whether prices may use the cached rate in `slot`: a wrapped token
+    /// with a rate provider needs it read in that same slot.
+    pub fn rate_is_fresh(&self, slot: u64) -> bool {
+        self.rate_updated_slot == slot || self.rate_provider == Pubkey::default()
+    }
+
+    /// Whether a raw main balance lies inside the target range.
+    pub fn within_targets(&self, main: u64) -> bool {
+        (self.lower_target..=self.upper_target).contains(&main)
+    }
+
+    /// Fee and target range in 18‑dec.
+    fn params(&self) -> Result<Params> {
+        Ok(Params {
+            fee:          U256::from(self.swap_fee),
+            lower_target: scale_up(self.lower_target, self.decimals[MAIN_INDEX])?,
+            upper_target: scale_up(self.upper_target, self.decimals[MAIN_INDEX])?,
+        })
+    }
+
+    /// 18‑dec balances of raw custody balances [main, wrapped, BPT held].
+    fn balances(&self, raw: [u64; 3]) -> Result<Balances> {
+        let supply = self.total_bpt.checked_sub(raw[BPT_INDEX]).ok_or(ErrorCode::MathUnderflow)?;
+        Ok(Balances {
+            main:           self.upscale(MAIN_INDEX, raw[MAIN_INDEX])?,
+            wrapped:        self.upscale(WRAPPED_INDEX, raw[WRAPPED_INDEX])?,
+            virtual_supply: self.upscale(BPT_INDEX, supply)?,
+        })
+    }
+
+    /// Token `i`'s rate: the wrapped token's, one for the others.
+    fn rate(&self, i: usize) -> U256 {
+        if i == WRAPPED_INDEX { U256::from(self.wrapped_rate) } else { fixed::ONE }
+    }
+
+    /// Raw amount of token `i` as an 18‑dec value at its rate.
+    fn upscale(&self, i: usize, amount: u64) -> Result<U256> {
+        let value = scale_up(amount, self.decimals[i])?;
+        Ok(fixed::try_mul_down(value, self.rate(i)).map_err(ErrorCode::from)?)
+    }
+
+    /// 18‑dec value at token `i`'s rate as a raw amount, rounded down (amounts paid out).
+    fn downscale_down(&self, i: usize, value_fp: U256) -> Result<u64> {
+        let value = fixed::try_div_down(value_fp, self.rate(i)).map_err(ErrorCode::from)?;
+        Ok(fixed::try_scale_down_rounding_down(value, self.decimals[i]).map_err(ErrorCode::from)?)
+    }
+
+    /// 18‑dec value at token `i`'s rate as a raw amount, rounded up (amounts paid in).
+    fn downscale_up(&self, i: usize, value_fp: U256) -> Result<u64> {
+        let value = fixed::try_div_up(value_fp, self.rate(i)).map_err(ErrorCode::from)?;
+        Ok(fixed::try_scale_down_rounding_up(value, self.decimals[i]).map_err(ErrorCode::from)?)
+    }
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Vector length mismatch")]
+    LengthMismatch,
+    #[msg("Math underflow or overflow")]
+    MathUnderflow,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("Fee must be below one and the lower target at most the upper")]
+    InvalidParams,
+    #[msg("Token account does not hold the pool token expected at its position")]
+    InvalidPoolTokenAccount,
+    #[msg("Swap tokens must be two different pool tokens")]
+    InvalidSwapTokens,
+    #[msg("Amount is outside the caller's limit")]
+    SlippageLimit,
+    #[msg("Main balance is outside the target range")]
+    MainBalanceOutsideTargets,
+    #[msg("Rate account or provider does not match the pool's, or returned no rate")]
+    InvalidRateProvider,
+    #[msg("Token rates must be updated earlier in the same slot")]
+    StaleTokenRates,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::LengthMismatch => ErrorCode::LengthMismatch,
+            MathError::TokenIndexOutOfBounds => ErrorCode::InvalidSwapTokens,
+            MathError::InvalidParams | MathError::InvalidPercentage => ErrorCode::InvalidParams,
+            MathError::WeightsNotNormalized
+            | MathError::InvalidWeight
+            | MathError::MaxInRatio
+            | MathError::MaxOutRatio
+            | MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::AssetBoundsExceeded
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange
+            | MathError::ConvergenceFailure => ErrorCode::MathInputOutOfBounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E6: u64 = 1_000_000;
+    const E9: u64 = 1_000_000_000;
+
+    /// 6‑decimal main, 9‑decimal wrapped and BPT; 1% fee, targets 1000..2000 main
+    fn pool() -> LinearPool {
+        LinearPool {
+            vault: Pubkey::default(),
+            lp_mint: Pubkey::new_unique(),
+            main_mint: Pubkey::new_unique(),
+            wrapped_mint: Pubkey::new_unique(),
+            decimals: [6, 9, 9],
+            lower_target: 1_000 * E6,
+            upper_target: 2_000 * E6,
+            swap_fee: 10_000_000_000_000_000,
+            total_bpt: PREMINTED_BPT,
+            pool_id: [0; 32],
+            rate_provider: Pubkey::default(),
+            wrapped_rate: fixed::ONE.as_u128(),
+            rate_updated_slot: 0,
+        }
+    }
+
+    #[test]
+    fn wraps_at_the_rate_inside_the_target_range() {
+        let mut pool = pool();
+        pool.wrapped_rate = 1_100_000_000_000_000_000;
+        // 1500 main, 1000 wrapped worth 1100, 2600 BPT out
+        let raw = [1_500 * E6, 1_000 * E9, PREMINTED_BPT - 2_600 * E9];
+        let quote = quote_exact_in(&pool, raw, MAIN_INDEX, WRAPPED_INDEX, 110 * E6).unwrap();
+        assert_eq!(quote.amount_out, 100 * E9);
+        assert_eq!(quote.protocol_fee, 0);
+        assert_eq!(quote_exact_out(&pool, raw, MAIN_INDEX, WRAPPED_INDEX, 100 * E9).unwrap(), 110 * E6);
+        // BPT trade at invariant / supply = 1
+        assert_eq!(quote_exact_in(&pool, raw, MAIN_INDEX, BPT_INDEX, 10 * E6).unwrap().amount_out, 10 * E9);
+    }
+
+    #[test]
+    fn draining_the_main_buffer_costs_the_fee() {
+        let pool = pool();
+        let raw = [1_100 * E6, 1_000 * E9, PREMINTED_BPT - 2_100 * E9];
+        // 200 main out leaves 900: the last 100 cost 1% more
+        assert_eq!(quote_exact_out(&pool, raw, WRAPPED_INDEX, MAIN_INDEX, 200 * E6).unwrap(), 201 * E9);
+        // and refilling it earns that fee back
+        let refill = [900 * E6, 1_201 * E9, PREMINTED_BPT - 2_100 * E9];
+        assert_eq!(quote_exact_in(&pool, refill, MAIN_INDEX, WRAPPED_INDEX, 200 * E6).unwrap().amount_out, 201 * E9);
+        assert!(pool.within_targets(1_100 * E6) && !pool.within_targets(900 * E6));
+    }
+
+    #[test]
+    fn rejects_bad_token_indices() {
+        let pool = pool();
+        let raw = [E6, E9, PREMINTED_BPT - E9];
+        assert_eq!(
+            quote_exact_in(&pool, raw, MAIN_INDEX, MAIN_INDEX, E6).unwrap_err(),
+            error!(ErrorCode::InvalidSwapTokens)
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use pool_factory::PoolRecord;
+use vault::{Action, Authorizer};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("FarmEZY8v7FsA9DoGStyX8jYM2X7NWLmKSUtEMaSFqs");
+
+/// Reward tokens a farm can emit at once
+pub const MAX_REWARDS: usize = 8;
+/// Fixed‑point scale of `reward_per_token`
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// Shortest reward period
+pub const MIN_REWARD_DURATION: i64 = 24 * 60 * 60;
+
+/// Multi‑reward liquidity mining: LPs stake a factory pool's BPT once and
+/// earn up to `MAX_REWARDS` reward tokens side by side, each with its own
+/// Synthetix‑style emission schedule. Holders of the Vault's
+/// `ManageRewards` role add a reward token to a farm and name the
+/// distributor allowed to fund it; that distributor then tops the reward
+/// up whenever it likes, each top‑up (plus what is left of the running
+/// period) spread over the reward's duration. Partners co‑incentivize a
+/// pool this way without going through its gauge.
+#[program]
+pub mod liquidity_mining {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create the farm of a factory pool; permissionless
+    ---------------------------------------------------------------- */
+    pub fn create_farm(ctx: Context<CreateFarm>) -> Result<()> {
+        let farm = &mut ctx.accounts.farm;
+        farm.vault          = ctx.accounts.pool_record.vault;
+        farm.pool           = ctx.accounts.pool_record.pool;
+        farm.lp_mint        = ctx.accounts.lp_mint.key();
+        farm.stake_vault    = ctx.accounts.stake_vault.key();
+        farm.total_supply   = 0;
+        farm.reward_count   = 0;
+        farm.rewards        = [RewardInfo::default(); MAX_REWARDS];
+        farm.authority_bump = ctx.bumps.farm_authority;
+
+        emit!(FarmCreated { farm: farm.key(), pool: farm.pool, creator: ctx.accounts.creator.key() });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Add a reward token and its distributor – requires `ManageRewards`
+    ---------------------------------------------------------------- */
+    pub fn add_reward(ctx: Context<AddReward>, distributor: Pubkey, duration: i64) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageRewards, &ctx.accounts.manager.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(duration >= MIN_REWARD_DURATION, ErrorCode::InvalidDuration);
+        let farm = &mut ctx.accounts.farm;
+        let index = farm.reward_count as usize;
+        require!(index < MAX_REWARDS, ErrorCode::TooManyRewards);
+
+        farm.rewards[index] = RewardInfo {
+            mint: ctx.accounts.reward_mint.key(),
+            reward_vault: ctx.accounts.reward_vault.key(),
+            distributor,
+            duration,
+            reward_rate: 0,
+            period_finish: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            reward_per_token_stored: 0,
+        };
+        farm.reward_count += 1;
+
+        emit!(RewardAdded { farm: farm.key(), reward_mint: ctx.accounts.reward_mint.key(), distributor, duration });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Fund reward `index` – only its distributor
+       The amount and the running period's remainder are emitted
+       evenly over the reward's duration from now.
+    ---------------------------------------------------------------- */
+    pub fn notify_reward_amount(ctx: Context<NotifyRewardAmount>, index: u8, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let farm  = &mut ctx.accounts.farm;
+        let index = farm.reward_index(index)?;
+        let reward = farm.rewards[index];
+        require_keys_eq!(ctx.accounts.distributor.key(), reward.distributor, ErrorCode::Unauthorized);
+        require_keys_eq!(ctx.accounts.reward_vault.key(), reward.reward_vault, ErrorCode::InvalidRewardAccount);
+
+        let now = Clock::get()?.unix_timestamp;
+        farm.checkpoint(now)?;
+        let farm_key = farm.key();
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.distributor_token_account.to_account_info(),
+                    to:        ctx.accounts.reward_vault.to_account_info(),
+                    authority: ctx.accounts.distributor.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        ctx.accounts.reward_vault.reload()?;
+
+        let reward = &mut farm.rewards[index];
+        let leftover = if now < reward.period_finish {
+            (reward.period_finish - now) as u128 * reward.reward_rate as u128
+        } else {
+            0
+        };
+        let rate = (leftover + amount as u128) / reward.duration as u128;
+        require!(rate > 0, ErrorCode::RewardTooSmall);
+        // the vault must hold the period's emissions; rewards owed to
+        // stakers but not yet claimed are covered by earlier funding
+        require!(
+            rate * reward.duration as u128 <= ctx.accounts.reward_vault.amount as u128,
+            ErrorCode::RewardTooLarge
+        );
+        reward.reward_rate   = rate as u64;
+        reward.last_update   = now;
+        reward.period_finish = now + reward.duration;
+
+        emit!(RewardNotified {
+            farm: farm_key,
+            reward_mint: reward.mint,
+            amount,
+            reward_rate: reward.reward_rate,
+            period_finish: reward.period_finish,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Stake BPT
+    ---------------------------------------------------------------- */
+    pub fn deposit(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let farm  = &mut ctx.accounts.farm;
+        let stake = &mut ctx.accounts.user_stake;
+        if stake.farm == Pubkey::default() {
+            stake.farm = farm.key();
+            stake.user = ctx.accounts.user.key();
+        }
+        farm.checkpoint(Clock::get()?.unix_timestamp)?;
+        stake.settle(farm)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.user_lp_account.to_account_info(),
+                    to:        ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+        stake.balance     = stake.balance.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        farm.total_supply = farm.total_supply.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+
+        emit!(Staked { farm: farm.key(), user: stake.user, amount });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Unstake BPT; accrued rewards stay claimable
+    ---------------------------------------------------------------- */
+    pub fn withdraw(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let farm  = &mut ctx.accounts.farm;
+        let stake = &mut ctx.accounts.user_stake;
+        require!(amount <= stake.balance, ErrorCode::InsufficientStake);
+        farm.checkpoint(Clock::get()?.unix_timestamp)?;
+        stake.settle(farm)?;
+
+        stake.balance     -= amount;
+        farm.total_supply -= amount;
+
+        let farm_key = farm.key();
+        let bump_arr = [farm.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"farm-authority", farm_key.as_ref(), &bump_arr];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.stake_vault.to_account_info(),
+                    to:        ctx.accounts.user_lp_account.to_account_info(),
+                    authority: ctx.accounts.farm_authority.to_account_info(),
+                },
+                &[seed_slice],
+            ),
+            amount,
+        )?;
+
+        emit!(Withdrawn { farm: farm_key, user: stake.user, amount });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Claim every reward accrued
+       remaining_accounts: [reward_vault_0, user_reward_account_0,
+                            reward_vault_1, …] for each reward, in order
+    ---------------------------------------------------------------- */
+    pub fn claim_rewards<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimRewards<'info>>) -> Result<()> {
+        let farm  = &mut ctx.accounts.farm;
+        let stake = &mut ctx.accounts.user_stake;
+        let rem   = ctx.remaining_accounts;
+        require!(rem.len() == farm.reward_count as usize * 2, ErrorCode::InvalidRewardAccount);
+        farm.checkpoint(Clock::get()?.unix_timestamp)?;
+        stake.settle(farm)?;
+
+        let farm_key = farm.key();
+        let bump_arr = [farm.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"farm-authority", farm_key.as_ref(), &bump_arr];
+        for (i, accounts) in rem.chunks(2).enumerate() {
+            let reward = &farm.rewards[i];
+            require_keys_eq!(accounts[0].key(), reward.reward_vault, ErrorCode::InvalidRewardAccount);
+            let amount = stake.rewards[i].owed;
+            if amount == 0 {
+                continue;
+            }
+            stake.rewards[i].owed = 0;
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from:      accounts[0].clone(),
+                        to:        accounts[1].clone(),
+                        authority: ctx.accounts.farm_authority.to_account_info(),
+                    },
+                    &[seed_slice],
+                ),
+                amount,
+            )?;
+            emit!(RewardPaid { farm: farm_key, user: stake.user, reward_mint: reward.mint, amount });
+        }
+        Ok(())
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct CreateFarm<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// The pool's factory record; only factory pools get farms
+    #[account(
+        seeds = [pool_factory::POOL_RECORD_SEED, pool_record.pool.as_ref()],
+        bump,
+        seeds::program = pool_factory::ID
+    )]
+    pub pool_record: Account<'info, PoolRecord>,
+
+    #[account(address = pool_record.lp_mint)]
+    pub lp_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Farm::INIT_SPACE,
+        seeds = [b"farm", pool_record.pool.as_ref()],
+        bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+
+    /// CHECK: PDA holding the staked BPT and the rewards
+    #[account(seeds = [b"farm-authority", farm.key().as_ref()], bump)]
+    pub farm_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"farm-stake", farm.key().as_ref()],
+        bump,
+        token::mint = lp_mint,
+        token::authority = farm_authority
+    )]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AddReward<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    /// The Authorizer of the vault the farm's pool is registered with
+    #[account(
+        seeds = [b"authorizer", farm.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `ManageRewards` role; pays for the reward vault
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    pub reward_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA holding the staked BPT and the rewards
+    #[account(seeds = [b"farm-authority", farm.key().as_ref()], bump = farm.authority_bump)]
+    pub farm_authority: AccountInfo<'info>,
+
+    /// `init` also rejects adding the same reward token twice
+    #[account(
+        init,
+        payer = manager,
+        seeds = [b"farm-rewards", farm.key().as_ref(), reward_mint.key().as_ref()],
+        bump,
+        token::mint = reward_mint,
+        token::authority = farm_authority
+    )]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct NotifyRewardAmount<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    /// The reward's whitelisted distributor
+    pub distributor: Signer<'info>,
+
+    #[account(mut)]
+    pub distributor_token_account: Account<'info, TokenAccount>,
+
+    /// Checked against the reward being funded
+    #[account(mut)]
+    pub reward_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + FarmStake::INIT_SPACE,
+        seeds = [b"farm-user", farm.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_stake: Box<Account<'info, FarmStake>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, token::mint = farm.lp_mint)]
+    pub user_lp_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = farm.stake_vault)]
+    pub stake_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the staked BPT
+    #[account(seeds = [b"farm-authority", farm.key().as_ref()], bump = farm.authority_bump)]
+    pub farm_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub farm: Box<Account<'info, Farm>>,
+
+    #[account(
+        mut,
+        seeds = [b"farm-user", farm.key().as_ref(), user.key().as_ref()],
+        bump,
+        has_one = user
+    )]
+    pub user_stake: Box<Account<'info, FarmStake>>,
+
+    pub user: Signer<'info>,
+
+    /// CHECK: PDA holding the rewards
+    #[account(seeds = [b"farm-authority", farm.key().as_ref()], bump = farm.authority_bump)]
+    pub farm_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// A pool's farm, PDA'd by ["farm", pool]
+#[account]
+#[derive(InitSpace)]
+pub struct Farm {
+    pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    /// Staked BPT, PDA'd by ["farm-stake", farm]
+    pub stake_vault: Pubkey,
+    /// Staked BPT
+    pub total_supply: u64,
+    /// Rewards in use, from the front of `rewards`
+    pub reward_count: u8,
+    pub rewards: [RewardInfo; MAX_REWARDS],
+    pub authority_bump: u8,
+}
+
+/// One reward token of a farm and its emission schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct RewardInfo {
+    pub mint: Pubkey,
+    /// Undistributed rewards, PDA'd by ["farm-rewards", farm, mint]
+    pub reward_vault: Pubkey,
+    /// The only account allowed to fund this reward
+    pub distributor: Pubkey,
+    /// Length of the period each top‑up is spread over
+    pub duration: i64,
+    /// Reward tokens emitted per second until `period_finish`
+    pub reward_rate: u64,
+    pub period_finish: i64,
+    pub last_update: i64,
+    /// Rewards per staked unit since the reward was added, times `REWARD_PRECISION`
+    pub reward_per_token_stored: u128,
+}
+
+impl RewardInfo {
+    /// Rewards per staked unit up to `now`, given the farm's stake.
+    pub fn reward_per_token(&self, total_supply: u64, now: i64) -> Result<u128> {
+        let until = now.min(self.period_finish);
+        if total_supply == 0 || until <= self.last_update {
+            return Ok(self.reward_per_token_stored);
+        }
+        let emitted = (until - self.last_update) as u128 * self.reward_rate as u128;
+        let per_unit = emitted
+            .checked_mul(REWARD_PRECISION)
+            .ok_or(ErrorCode::MathOverflow)?
+            / total_supply as u128;
+        Ok(self.reward_per_token_stored.checked_add(per_unit).ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+impl Farm {
+    fn reward_index(&self, index: u8) -> Result<usize> {
+        require!(index < self.reward_count, ErrorCode::InvalidRewardAccount);
+        Ok(index as usize)
+    }
+
+    /// Bring every reward's `reward_per_token_stored` up to `now`.
+    pub fn checkpoint(&mut self, now: i64) -> Result<()> {
+        for reward in self.rewards.iter_mut().take(self.reward_count as usize) {
+            reward.reward_per_token_stored = reward.reward_per_token(self.total_supply, now)?;
+            reward.last_update = now.min(reward.period_finish).max(reward.last_update);
+        }
+        Ok(())
+    }
+}
+
+/// A staker's position in a farm, PDA'd by ["farm-user", farm, user]
+#[account]
+#[derive(InitSpace)]
+pub struct FarmStake {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    /// Staked BPT
+    pub balance: u64,
+    /// Accrual per reward, indexed like `Farm::rewards`
+    pub rewards: [UserReward; MAX_REWARDS],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, InitSpace)]
+pub struct UserReward {
+    /// `reward_per_token_stored` when `owed` was last settled
+    pub reward_per_token_paid: u128,
+    /// Accrued, unclaimed rewards
+    pub owed: u64,
+}
+
+impl FarmStake {
+    /// Move every reward accrued since the last settlement into `owed`;
+    /// call after `Farm::checkpoint`.
+    pub fn settle(&mut self, farm: &Farm) -> Result<()> {
+        for (reward, user) in farm.rewards.iter().zip(self.rewards.iter_mut()).take(farm.reward_count as usize) {
+            let delta = reward.reward_per_token_stored.saturating_sub(user.reward_per_token_paid);
+            let earned = (self.balance as u128)
+                .checked_mul(delta)
+                .ok_or(ErrorCode::MathOverflow)?
+                / REWARD_PRECISION;
+            let earned = u64::try_from(earned).map_err(|_| ErrorCode::MathOverflow)?;
+            user.owed = user.owed.checked_add(earned).ok_or(ErrorCode::MathOverflow)?;
+            user.reward_per_token_paid = reward.reward_per_token_stored;
+        }
+        Ok(())
+    }
+}
+
+#[event]
+pub struct FarmCreated {
+    pub farm: Pubkey,
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+}
+
+#[event]
+pub struct RewardAdded {
+    pub farm: Pubkey,
+    pub reward_mint: Pubkey,
+    pub distributor: Pubkey,
+    pub duration: i64,
+}
+
+#[event]
+pub struct RewardNotified {
+    pub farm: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub reward_rate: u64,
+    pub period_finish: i64,
+}
+
+#[event]
+pub struct Staked {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RewardPaid {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Withdrawal exceeds the staked balance")]
+    InsufficientStake,
+    #[msg("Reward period is too short")]
+    InvalidDuration,
+    #[msg("Farm already emits the maximum number of rewards")]
+    TooManyRewards,
+    #[msg("Reward index or reward accounts do not match the farm")]
+    InvalidRewardAccount,
+    #[msg("Reward amount is too small for the period")]
+    RewardTooSmall,
+    #[msg("Reward rate exceeds the rewards held")]
+    RewardTooLarge,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn farm(rewards: &[(u64, i64)]) -> Farm {
+        let mut farm = Farm {
+            vault: Pubkey::default(),
+            pool: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            stake_vault: Pubkey::default(),
+            total_supply: 0,
+            reward_count: rewards.len() as u8,
+            rewards: [RewardInfo::default(); MAX_REWARDS],
+            authority_bump: 0,
+        };
+        for (i, &(reward_rate, period_finish)) in rewards.iter().enumerate() {
+            farm.rewards[i] = RewardInfo { reward_rate, period_finish, ..Default::default() };
+        }
+        farm
+    }
+
+    fn staker() -> FarmStake {
+        FarmStake {
+            farm: Pubkey::default(),
+            user: Pubkey::new_unique(),
+            balance: 0,
+            rewards: [UserReward::default(); MAX_REWARDS],
+        }
+    }
+
+    fn stake(f: &mut Farm, s: &mut FarmStake, now: i64, amount: u64) {
+        f.checkpoint(now).unwrap();
+        s.settle(f).unwrap();
+        s.balance += amount;
+        f.total_supply += amount;
+    }
+
+    #[test]
+    fn rewards_accrue_on_independent_schedules() {
+        // reward 0: 100/s until 100; reward 1: 10/s until 400
+        let mut f = farm(&[(100, 100), (10, 400)]);
+        let (mut a, mut b) = (staker(), staker());
+        stake(&mut f, &mut a, 0, 1_000);
+        stake(&mut f, &mut b, 50, 1_000);
+
+        f.checkpoint(500).unwrap();
+        a.settle(&f).unwrap();
+        b.settle(&f).unwrap();
+        // reward 0: a alone for 50 s, then halves; reward 1 likewise until 400
+        assert_eq!((a.rewards[0].owed, b.rewards[0].owed), (7_500, 2_500));
+        assert_eq!((a.rewards[1].owed, b.rewards[1].owed), (500 + 1_750, 1_750));
+        // unused slots never accrue
+        assert_eq!(a.rewards[2].owed, 0);
+    }
+}
@@ -0,0 +1,1730 @@
+#![allow(clippy::needless_range_loop)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, MintTo, Token, Transfer};
+use common::pool_interface::SwapQuote;
+use common::pool_tokens::{self, TokenAccount};
+use common::{crank, derive_pool_id, Specialization, MAX_POOL_TOKENS};
+use math::weighted_math::{self, SwapParams};
+use math::{fees, fixed, MathError, U256};
+
+// Vault CPI interfaces and the program struct itself
+use vault::cpi::accounts::{RegisterPool as VaultRegisterPool, RegisterTokens as VaultRegisterTokens};
+use vault::cpi::{
+    deregister_tokens as vault_deregister_tokens, register_pool as vault_register_pool,
+    register_tokens as vault_register_tokens,
+};
+use vault::program::Vault as VaultProgram;
+use vault::{PoolRegistration, ProtocolFeesCollector, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("MPoo1avmd1GWhS6n2Gcmi2qYPkQhZXonNFfGMw2uNHS");
+
+/// Fewest tokens a managed pool holds; removing a token stops here
+pub const MIN_TOKENS: usize = 2;
+/// Smallest weight any token may have (1 %)
+pub const MIN_WEIGHT: u128 = 10_000_000_000_000_000;
+/// Highest swap fee the owner may schedule (10 %)
+pub const MAX_SWAP_FEE: u64 = 100_000_000_000_000_000;
+/// Highest yearly management fee on assets under management (10 %)
+pub const MAX_MANAGEMENT_AUM_FEE: u64 = 100_000_000_000_000_000;
+/// Most addresses the LP allowlist holds
+pub const MAX_ALLOWED_LPS: usize = 32;
+
+/// Managed pools: weighted pools run by an owner, for index‑fund style
+/// products. The owner adds and removes tokens while the pool is live,
+/// moves weights and the swap fee gradually along linear schedules,
+/// charges a yearly management fee on assets under management (minted as
+/// BPT), and may restrict joins to an allowlist of LPs. Exits are always
+/// open. Swaps need only the pair's balances, so the pool registers with
+/// the Vault as `MinimalSwapInfo` whatever its token count.
+#[program]
+pub mod managed_pool {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Initialise a pool; the payer becomes its owner
+       remaining_accounts: [mint_0, …, mint_{N-1}]
+    ---------------------------------------------------------------- */
+    pub fn initialize_pool(ctx: Context<InitializePool>, params: ManagedPoolParams) -> Result<()> {
+        let n = ctx.remaining_accounts.len();
+        require!((MIN_TOKENS..=MAX_POOL_TOKENS).contains(&n), ErrorCode::InvalidTokenCount);
+        require!(params.weights.len() == n, ErrorCode::LengthMismatch);
+        let weights: Vec<U256> = params.weights.iter().map(|w| U256::from(*w)).collect();
+        check_weights(&weights)?;
+        require!(params.swap_fee <= MAX_SWAP_FEE, ErrorCode::SwapFeeTooHigh);
+        require!(params.management_aum_fee <= MAX_MANAGEMENT_AUM_FEE, ErrorCode::ManagementFeeTooHigh);
+
+        let mut tokens   = Vec::with_capacity(n);
+        let mut decimals = Vec::with_capacity(n);
+        for ai in ctx.remaining_accounts.iter() {
+            tokens.push(ai.key());
+            decimals.push(mint_decimals(ai)?);
+        }
+        let lp_decimals = mint_decimals(&ctx.accounts.lp_mint)?;
+        let now = Clock::get()?.unix_timestamp;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.vault                   = ctx.accounts.vault_state.key();
+        pool.lp_mint                 = ctx.accounts.lp_mint.key();
+        pool.owner                   = ctx.accounts.payer.key();
+        pool.tokens                  = tokens.clone();
+        pool.decimals                = decimals;
+        pool.lp_decimals             = lp_decimals;
+        pool.start_weights           = params.weights.clone();
+        pool.end_weights             = params.weights;
+        pool.weight_start_time       = now;
+        pool.weight_end_time         = now;
+        pool.start_swap_fee          = params.swap_fee;
+        pool.end_swap_fee            = params.swap_fee;
+        pool.fee_start_time          = now;
+        pool.fee_end_time            = now;
+        pool.management_aum_fee      = params.management_aum_fee;
+        pool.last_aum_fee_collection = now;
+        pool.must_allowlist_lps      = params.must_allowlist_lps;
+        pool.allowed_lps             = Vec::new();
+        pool.total_bpt               = 0;
+
+        // register with the Vault: tokens come and go, so never TwoToken
+        let specialization = Specialization::MinimalSwapInfo;
+        let pool_id = derive_pool_id(
+            &ctx.accounts.vault_state.key(),
+            &ctx.accounts.pool.key(),
+            specialization,
+            ctx.accounts.vault_state.pool_count,
+        );
+        let cpi_accounts = VaultRegisterPool {
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.payer.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        let registered = vault_register_pool(
+            CpiContext::new(ctx.accounts.vault_program.to_account_info(), cpi_accounts),
+            pool_id,
+            specialization,
+            tokens,
+        )?;
+        ctx.accounts.pool.pool_id = registered.get();
+
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Weight schedule – owner only
+       Weights move linearly from where they are now to `end_weights`
+       between `start_time` (no earlier than now) and `end_time`.
+    ---------------------------------------------------------------- */
+    pub fn update_weights_gradually(
+        ctx: Context<OwnerOnly>,
+        start_time: i64,
+        end_time: i64,
+        end_weights: Vec<u128>,
+    ) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        require!(end_weights.len() == pool.tokens.len(), ErrorCode::LengthMismatch);
+        check_weights(&end_weights.iter().map(|w| U256::from(*w)).collect::<Vec<_>>())?;
+        let start_time = start_time.max(now);
+        require!(end_time >= start_time, ErrorCode::InvalidSchedule);
+
+        pool.start_weights     = pool.weights_at(now)?.iter().map(|w| w.as_u128()).collect();
+        pool.end_weights       = end_weights.clone();
+        pool.weight_start_time = start_time;
+        pool.weight_end_time   = end_time;
+        emit!(GradualWeightUpdateScheduled {
+            pool: pool.key(),
+            start_time,
+            end_time,
+            end_weights,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Swap fee schedule – owner only, same shape as the weights'
+    ---------------------------------------------------------------- */
+    pub fn update_swap_fee_gradually(
+        ctx: Context<OwnerOnly>,
+        start_time: i64,
+        end_time: i64,
+        start_swap_fee: u64,
+        end_swap_fee: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            start_swap_fee <= MAX_SWAP_FEE && end_swap_fee <= MAX_SWAP_FEE,
+            ErrorCode::SwapFeeTooHigh
+        );
+        let start_time = start_time.max(now);
+        require!(end_time >= start_time, ErrorCode::InvalidSchedule);
+
+        let pool = &mut ctx.accounts.pool;
+        pool.start_swap_fee = start_swap_fee;
+        pool.end_swap_fee   = end_swap_fee;
+        pool.fee_start_time = start_time;
+        pool.fee_end_time   = end_time;
+        emit!(GradualSwapFeeUpdateScheduled {
+            pool: pool.key(),
+            start_time,
+            end_time,
+            start_swap_fee,
+            end_swap_fee,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Management fee – owner only
+       Fees accrued at the old rate are collected first.
+    ---------------------------------------------------------------- */
+    pub fn set_management_aum_fee(ctx: Context<SetManagementAumFee>, management_aum_fee: u64) -> Result<()> {
+        require!(management_aum_fee <= MAX_MANAGEMENT_AUM_FEE, ErrorCode::ManagementFeeTooHigh);
+        let now = Clock::get()?.unix_timestamp;
+        collect_aum_fee(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.lp_mint_authority,
+            ctx.bumps.lp_mint_authority,
+            &ctx.accounts.manager_lp_account,
+            &ctx.accounts.token_program.to_account_info(),
+            now,
+        )?;
+        let pool = &mut ctx.accounts.pool;
+        pool.management_aum_fee = management_aum_fee;
+        pool.last_aum_fee_collection = now;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Collect the management fee accrued so far – anyone may call;
+       it is minted as BPT to the owner's LP account
+    ---------------------------------------------------------------- */
+    pub fn collect_aum_management_fees(ctx: Context<CollectAumFees>) -> Result<()> {
+        collect_aum_fee(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.lp_mint_authority,
+            ctx.bumps.lp_mint_authority,
+            &ctx.accounts.manager_lp_account,
+            &ctx.accounts.token_program.to_account_info(),
+            Clock::get()?.unix_timestamp,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Crank – anyone may call once a weight or swap fee schedule has
+       run its course; the schedule's end values become the pool's
+       fixed ones and the sender collects the crank bounty
+    ---------------------------------------------------------------- */
+    pub fn finalize_schedules(ctx: Context<Crank>) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let pool = &mut ctx.accounts.pool;
+        let weights_done = pool.weight_start_time < pool.weight_end_time && now >= pool.weight_end_time;
+        let fee_done     = pool.fee_start_time < pool.fee_end_time && now >= pool.fee_end_time;
+        require!(weights_done || fee_done, ErrorCode::NothingToCrank);
+
+        if weights_done {
+            let end_weights: Vec<U256> = pool.end_weights.iter().map(|w| U256::from(*w)).collect();
+            let end_time = pool.weight_end_time;
+            pool.set_weights(&end_weights, end_time);
+        }
+        if fee_done {
+            pool.start_swap_fee = pool.end_swap_fee;
+            pool.fee_start_time = pool.fee_end_time;
+        }
+        let bounty = crank::pay_bounty(&pool.to_account_info(), &ctx.accounts.cranker)?;
+        emit!(SchedulesFinalized {
+            pool:     pool.key(),
+            weights:  weights_done,
+            swap_fee: fee_done,
+            cranker:  ctx.accounts.cranker.key(),
+            bounty,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       LP allowlist – owner only
+    ---------------------------------------------------------------- */
+    pub fn set_must_allowlist_lps(ctx: Context<OwnerOnly>, must_allowlist_lps: bool) -> Result<()> {
+        ctx.accounts.pool.must_allowlist_lps = must_allowlist_lps;
+        Ok(())
+    }
+
+    pub fn add_allowed_address(ctx: Context<OwnerOnly>, lp: Pubkey) -> Result<()> {
+        let allowed = &mut ctx.accounts.pool.allowed_lps;
+        require!(!allowed.contains(&lp), ErrorCode::AddressAlreadyAllowed);
+        require!(allowed.len() < MAX_ALLOWED_LPS, ErrorCode::AllowlistFull);
+        allowed.push(lp);
+        Ok(())
+    }
+
+    pub fn remove_allowed_address(ctx: Context<OwnerOnly>, lp: Pubkey) -> Result<()> {
+        let allowed = &mut ctx.accounts.pool.allowed_lps;
+        let at = allowed
+            .iter()
+            .position(|a| *a == lp)
+            .ok_or(ErrorCode::AddressNotAllowed)?;
+        allowed.remove(at);
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Add a token – owner only, no weight change in progress
+       The owner must hold the Vault's `RegisterPool` role: the Vault
+       creates the custody account. The owner funds it with `amount_in`;
+       the new token takes `weight`, the others shrink by (1 − weight),
+       and the owner is minted total_bpt · weight / (1 − weight) BPT.
+    ---------------------------------------------------------------- */
+    pub fn add_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, ChangeTokens<'info>>,
+        weight: u128,
+        amount_in: u64,
+        asset_manager: Pubkey,
+    ) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.mint.key();
+        let pool = &ctx.accounts.pool;
+        require!(pool.tokens.len() < MAX_POOL_TOKENS, ErrorCode::InvalidTokenCount);
+        require!(!pool.tokens.contains(&mint), ErrorCode::TokenAlreadyInPool);
+        require!(!pool.is_weight_change_in_progress(now), ErrorCode::WeightChangeInProgress);
+        require!(amount_in > 0, ErrorCode::ZeroAmount);
+        let decimals    = mint_decimals(&ctx.accounts.mint)?;
+        let weight      = U256::from(weight);
+        let new_weights = weights_with(&pool.weights_at(now)?, weight)?;
+
+        // 1. fees up to now, then the BPT that leaves everyone's share of the old tokens as it was
+        ctx.accounts.collect_aum_fee(ctx.bumps.lp_mint_authority, now)?;
+        let pool = &ctx.accounts.pool;
+        let bpt_out_fp = fixed::try_div_down(
+            fixed::try_mul_down(scale_up(pool.total_bpt, pool.lp_decimals)?, weight).map_err(ErrorCode::from)?,
+            fixed::complement(weight),
+        ).map_err(ErrorCode::from)?;
+        let bpt_out = scale_down(bpt_out_fp, pool.lp_decimals)?;
+
+        // 2. the Vault creates the custody account and lists the token
+        ctx.accounts.vault_tokens_cpi(|cpi_program, cpi_accounts, rem| {
+            vault_register_tokens(CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(rem), vec![asset_manager])
+        })?;
+
+        // 3. owner → custody, then mint
+        let cpi_in = Transfer {
+            from:      ctx.accounts.owner_token_account.clone(),
+            to:        ctx.accounts.pool_token.clone(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        token::transfer(CpiContext::new(ctx.accounts.token_program.to_account_info(), cpi_in), amount_in)?;
+        if bpt_out > 0 {
+            mint_bpt(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.lp_mint,
+                &ctx.accounts.lp_mint_authority,
+                &ctx.accounts.pool.key(),
+                ctx.bumps.lp_mint_authority,
+                &ctx.accounts.manager_lp_account,
+                bpt_out,
+            )?;
+        }
+
+        // 4. bookkeeping
+        let pool = &mut ctx.accounts.pool;
+        pool.tokens.push(mint);
+        pool.decimals.push(decimals);
+        pool.set_weights(&new_weights, now);
+        pool.total_bpt = pool.total_bpt.checked_add(bpt_out).ok_or(ErrorCode::MathOverflow)?;
+        emit!(TokenAdded {
+            pool: pool.key(),
+            mint,
+            weight: weight.as_u128(),
+            amount_in,
+            bpt_out,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Remove a token – owner only, no weight change in progress
+       The whole balance goes to the owner, who burns total_bpt · weight
+       BPT for it; the others grow by 1 / (1 − weight) and the Vault
+       closes the custody account. None of the token may be with an
+       asset manager.
+    ---------------------------------------------------------------- */
+    pub fn remove_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, ChangeTokens<'info>>,
+        maximum_bpt_in: u64,
+    ) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let mint = ctx.accounts.mint.key();
+        let pool = &ctx.accounts.pool;
+        require!(pool.tokens.len() > MIN_TOKENS, ErrorCode::InvalidTokenCount);
+        require!(!pool.is_weight_change_in_progress(now), ErrorCode::WeightChangeInProgress);
+        let i = pool.index_of(&mint)?;
+        let registration = &ctx.accounts.pool_registration;
+        require_keys_eq!(
+            ctx.accounts.pool_token.key(),
+            registration.token_accounts.get(i).copied().unwrap_or_default(),
+            ErrorCode::InvalidPoolTokenAccount
+        );
+        require!(registration.managed_balance(&mint) == 0, ErrorCode::TokenIsManaged);
+        let weights     = pool.weights_at(now)?;
+        let new_weights = weights_without(&weights, i)?;
+        let amount_out  = unpack_token_account(&ctx.accounts.pool_token)?.amount;
+
+        // 1. fees up to now, then the BPT worth the token's share of the pool
+        ctx.accounts.collect_aum_fee(ctx.bumps.lp_mint_authority, now)?;
+        let pool = &ctx.accounts.pool;
+        let bpt_in_fp = fixed::try_mul_up(scale_up(pool.total_bpt, pool.lp_decimals)?, weights[i])
+            .map_err(ErrorCode::from)?;
+        let bpt_in = scale_down_up(bpt_in_fp, pool.lp_decimals)?;
+        require!(bpt_in < pool.total_bpt, ErrorCode::MathUnderflow);
+        require!(bpt_in <= maximum_bpt_in, ErrorCode::SlippageLimit);
+
+        // 2. burn, then custody → owner
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        let cpi_burn = Burn {
+            mint:      ctx.accounts.lp_mint.clone(),
+            from:      ctx.accounts.manager_lp_account.clone(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        token::burn(CpiContext::new(token_prog.clone(), cpi_burn), bpt_in)?;
+        if amount_out > 0 {
+            let pool_key = ctx.accounts.pool.key();
+            let bump_arr = [ctx.bumps.lp_mint_authority];
+            let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+            let cpi_out = Transfer {
+                from:      ctx.accounts.pool_token.clone(),
+                to:        ctx.accounts.owner_token_account.clone(),
+                authority: ctx.accounts.lp_mint_authority.clone(),
+            };
+            token::transfer(CpiContext::new_with_signer(token_prog, cpi_out, &[seed_slice]), amount_out)?;
+        }
+
+        // 3. the Vault closes the now empty custody account
+        ctx.accounts.vault_tokens_cpi(|cpi_program, cpi_accounts, rem| {
+            vault_deregister_tokens(CpiContext::new(cpi_program, cpi_accounts).with_remaining_accounts(rem))
+        })?;
+
+        // 4. bookkeeping
+        let pool = &mut ctx.accounts.pool;
+        pool.tokens.remove(i);
+        pool.decimals.remove(i);
+        pool.set_weights(&new_weights, now);
+        pool.total_bpt = pool.total_bpt.checked_sub(bpt_in).ok_or(ErrorCode::MathUnderflow)?;
+        emit!(TokenRemoved {
+            pool: pool.key(),
+            mint,
+            amount_out,
+            bpt_in,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Join – exact tokens in, BPT out; allowlisted LPs only when the
+       allowlist is on
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       The first join seeds every token. Later joins pay the swap fee on
+       their unbalanced part; the protocol's share is minted as BPT.
+    ---------------------------------------------------------------- */
+    pub fn join_exact_tokens_in_for_bpt_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        amounts_in: Vec<u64>,
+        minimum_bpt_out: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.check_live(now)?;
+        require!(ctx.accounts.pool.is_allowed_lp(&ctx.accounts.user.key()), ErrorCode::LpNotAllowed);
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.collect_aum_fee(bump, now)?;
+
+        let pool = &ctx.accounts.pool;
+        let n    = pool.tokens.len();
+        let rem  = ctx.remaining_accounts;
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(amounts_in.len() == n, ErrorCode::LengthMismatch);
+
+        // 1. balances and weights
+        let balances = read_balances(pool, &ctx.accounts.pool_registration, rem.iter().skip(1).step_by(2))?;
+        let weights  = pool.weights_at(now)?;
+        let mut amounts_fp = Vec::with_capacity(n);
+        for i in 0..n {
+            amounts_fp.push(scale_up(amounts_in[i], pool.decimals[i])?);
+        }
+
+        // 2. maths
+        let (bpt_out, protocol_bpt) = if pool.total_bpt == 0 {
+            // seed: BPT = n · invariant
+            require!(amounts_in.iter().all(|a| *a > 0), ErrorCode::InvalidInitialJoin);
+            let invariant = weighted_math::try_calculate_invariant(&amounts_fp, &weights).map_err(ErrorCode::from)?;
+            (scale_down(invariant * n, pool.lp_decimals)?, 0)
+        } else {
+            let total_bpt_fp = scale_up(pool.total_bpt, pool.lp_decimals)?;
+            let swap_fee     = pool.swap_fee_at(now)?;
+            let bpt_out_fp = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                &balances,
+                &weights,
+                &amounts_fp,
+                total_bpt_fp,
+                swap_fee,
+            ).map_err(ErrorCode::from)?;
+            let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+            let protocol_bpt = if protocol_pct > 0 {
+                let bpt_no_fee_fp = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                    &balances,
+                    &weights,
+                    &amounts_fp,
+                    total_bpt_fp,
+                    U256::zero(),
+                ).map_err(ErrorCode::from)?;
+                let fee_bpt_fp = bpt_no_fee_fp.saturating_sub(bpt_out_fp);
+                scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+            } else {
+                0
+            };
+            (scale_down(bpt_out_fp, pool.lp_decimals)?, protocol_bpt)
+        };
+        require!(bpt_out > 0, ErrorCode::MathUnderflow);
+        require!(bpt_out >= minimum_bpt_out, ErrorCode::SlippageLimit);
+
+        // 3. user → vault, then mint
+        for i in 0..n {
+            if amounts_in[i] > 0 {
+                ctx.accounts.pay_in(&rem[i * 2], &rem[i * 2 + 1], amounts_in[i])?;
+            }
+        }
+        ctx.accounts.mint_bpt(bump, &ctx.accounts.user_lp_account, bpt_out)?;
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        ctx.accounts.pool.total_bpt = ctx.accounts.pool
+            .total_bpt
+            .checked_add(bpt_out)
+            .and_then(|t| t.checked_add(protocol_bpt))
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Exit – exact BPT in, proportional tokens out (no fee)
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       Open to every BPT holder, allowlisted or not.
+    ---------------------------------------------------------------- */
+    pub fn exit_exact_bpt_in_for_tokens_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        bpt_in: u64,
+        minimum_amounts_out: Vec<u64>,
+    ) -> Result<()> {
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.collect_aum_fee(bump, Clock::get()?.unix_timestamp)?;
+
+        let pool = &ctx.accounts.pool;
+        let n    = pool.tokens.len();
+        let rem  = ctx.remaining_accounts;
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(minimum_amounts_out.len() == n, ErrorCode::LengthMismatch);
+        require!(bpt_in > 0 && bpt_in <= pool.total_bpt, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, &ctx.accounts.pool_registration, rem.iter().skip(1).step_by(2))?;
+
+        // 2. maths
+        let outs_fp = weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+            &balances,
+            scale_up(bpt_in, pool.lp_decimals)?,
+            scale_up(pool.total_bpt, pool.lp_decimals)?,
+            U256::zero(),
+        ).map_err(ErrorCode::from)?;
+        let mut amounts_out = Vec::with_capacity(n);
+        for i in 0..n {
+            let amount_out = scale_down(outs_fp[i], pool.decimals[i])?;
+            require!(amount_out >= minimum_amounts_out[i], ErrorCode::SlippageLimit);
+            amounts_out.push(amount_out);
+        }
+
+        // 3. burn, then vault → user
+        ctx.accounts.burn_bpt(bpt_in)?;
+        for i in 0..n {
+            ctx.accounts.pay_out(bump, &rem[i * 2 + 1], &rem[i * 2], amounts_out[i])?;
+        }
+
+        // 4. bookkeeping
+        ctx.accounts.pool.total_bpt = ctx.accounts.pool
+            .total_bpt
+            .checked_sub(bpt_in)
+            .ok_or(ErrorCode::MathUnderflow)?;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – exact in → out at the current weights and swap fee
+       Returns the amount sent out (read by the Vault's batch swap).
+    ---------------------------------------------------------------- */
+    pub fn swap_exact_token_in_for_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.vault_state.is_paused(now), ErrorCode::VaultPaused);
+        require!(!ctx.accounts.pool_registration.paused, ErrorCode::PoolPaused);
+
+        let pool = &ctx.accounts.pool;
+        let registration = &ctx.accounts.pool_registration;
+        let (index_in, balance_in)   = read_balance(pool, registration, &ctx.accounts.vault_in)?;
+        let (index_out, balance_out) = read_balance(pool, registration, &ctx.accounts.vault_out)?;
+        require!(index_in != index_out, ErrorCode::InvalidSwapTokens);
+
+        let SwapQuote { amount_out, protocol_fee } = quote_exact_in(
+            pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            now,
+            (index_in, index_out),
+            amount_in,
+            [balance_in, balance_out],
+        )?;
+        require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+
+        // transfer in (user → vault, protocol fee → collector)
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        let cpi_in = Transfer {
+            from:      ctx.accounts.user_token_account_in.clone(),
+            to:        ctx.accounts.vault_in.clone(),
+            authority: ctx.accounts.user_authority.to_account_info(),
+        };
+        token::transfer(CpiContext::new(token_prog.clone(), cpi_in), amount_in - protocol_fee)?;
+        if protocol_fee > 0 {
+            check_fee_account(
+                &ctx.accounts.protocol_fee_account,
+                &ctx.accounts.fees_collector.key(),
+                &pool.tokens[index_in],
+            )?;
+            let cpi_fee = Transfer {
+                from:      ctx.accounts.user_token_account_in.clone(),
+                to:        ctx.accounts.protocol_fee_account.clone(),
+                authority: ctx.accounts.user_authority.to_account_info(),
+            };
+            token::transfer(CpiContext::new(token_prog.clone(), cpi_fee), protocol_fee)?;
+        }
+
+        // transfer out (vault → user)
+        let pool_key = pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_out = Transfer {
+            from:      ctx.accounts.vault_out.clone(),
+            to:        ctx.accounts.user_token_account_out.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(CpiContext::new_with_signer(token_prog, cpi_out, &[seed_slice]), amount_out)?;
+
+        Ok(amount_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Managed transfer – move idle cash to an asset manager
+       Only the Vault may ask (its asset manager authority PDA must sign).
+    ---------------------------------------------------------------- */
+    pub fn managed_transfer(ctx: Context<ManagedTransfer>, amount: u64) -> Result<()> {
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.from.clone(),
+            to:        ctx.accounts.to.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Query – price an exact‑in swap against caller‑supplied balances
+       Read‑only; used by the Vault's query_batch_swap.
+    ---------------------------------------------------------------- */
+    pub fn query_swap(
+        ctx: Context<QuerySwap>,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Result<SwapQuote> {
+        let pool      = &ctx.accounts.pool;
+        let index_in  = pool.index_of(&unpack_token_account(&ctx.accounts.pool_token_in)?.mint)?;
+        let index_out = pool.index_of(&unpack_token_account(&ctx.accounts.pool_token_out)?.mint)?;
+        require!(index_in != index_out, ErrorCode::InvalidSwapTokens);
+        quote_exact_in(
+            pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            Clock::get()?.unix_timestamp,
+            (index_in, index_out),
+            amount_in,
+            [
+                scale_up(balance_in, pool.decimals[index_in])?,
+                scale_up(balance_out, pool.decimals[index_out])?,
+            ],
+        )
+    }
+}
+
+/// Exact‑in swap pricing shared by the swap and its query, at the weights
+/// and swap fee of `now`: amount out and the protocol's part of the amount
+/// in. `balances` are (in, out), 18‑dec.
+fn quote_exact_in(
+    pool: &ManagedPool,
+    protocol_pct: u64,
+    now: i64,
+    (index_in, index_out): (usize, usize),
+    amount_in: u64,
+    balances: [U256; 2],
+) -> Result<SwapQuote> {
+    let weights = pool.weights_at(now)?;
+    let params = SwapParams {
+        balance_in:  balances[0],
+        weight_in:   weights[index_in],
+        balance_out: balances[1],
+        weight_out:  weights[index_out],
+        amount:      scale_up(amount_in, pool.decimals[index_in])?,
+        fee:         pool.swap_fee_at(now)?,
+    };
+    let out_fp = params.try_out_given_in().map_err(ErrorCode::from)?;
+    let protocol_fee = if protocol_pct > 0 {
+        let fee_amount_fp = fees::try_fee_amount(params.amount, params.fee).map_err(ErrorCode::from)?;
+        scale_down(protocol_share(fee_amount_fp, protocol_pct)?, pool.decimals[index_in])?
+    } else {
+        0
+    };
+    Ok(SwapQuote {
+        amount_out: scale_down(out_fp, pool.decimals[index_out])?,
+        protocol_fee,
+    })
+}
+
+/// Weights after adding a token at `weight`: the others shrink by
+/// (1 − weight) and the new token, last, takes what remains of one.
+fn weights_with(weights: &[U256], weight: U256) -> Result<Vec<U256>> {
+    require!(weight < fixed::ONE, ErrorCode::InvalidWeights);
+    let keep = fixed::complement(weight);
+    let mut new_weights = Vec::with_capacity(weights.len() + 1);
+    for w in weights {
+        new_weights.push(fixed::try_mul_down(*w, keep).map_err(ErrorCode::from)?);
+    }
+    new_weights.push(remainder(&new_weights)?);
+    check_weights(&new_weights)?;
+    Ok(new_weights)
+}
+
+/// Weights after dropping token `index`: the others grow by
+/// 1 / (1 − its weight) and the last of them takes what remains of one.
+fn weights_without(weights: &[U256], index: usize) -> Result<Vec<U256>> {
+    require!(index < weights.len() && weights.len() > MIN_TOKENS, ErrorCode::InvalidTokenCount);
+    let keep = fixed::complement(weights[index]);
+    let mut new_weights = Vec::with_capacity(weights.len() - 1);
+    for (i, w) in weights.iter().enumerate() {
+        if i != index {
+            new_weights.push(fixed::try_div_down(*w, keep).map_err(ErrorCode::from)?);
+        }
+    }
+    new_weights.pop();
+    new_weights.push(remainder(&new_weights)?);
+    check_weights(&new_weights)?;
+    Ok(new_weights)
+}
+
+/// One less the sum of `weights`.
+fn remainder(weights: &[U256]) -> Result<U256> {
+    let mut sum = U256::zero();
+    for w in weights {
+        sum = fixed::try_add(sum, *w).map_err(ErrorCode::from)?;
+    }
+    Ok(fixed::try_sub(fixed::ONE, sum).map_err(ErrorCode::from)?)
+}
+
+/// Weights must each be at least `MIN_WEIGHT` and sum to exactly one.
+fn check_weights(weights: &[U256]) -> Result<()> {
+    require!(
+        weights.iter().all(|w| *w >= U256::from(MIN_WEIGHT)) && remainder(weights)? == U256::zero(),
+        ErrorCode::InvalidWeights
+    );
+    Ok(())
+}
+
+/// Pool balances as 18‑dec values in pool order. Custody accounts must be
+/// the registered ones; amounts held by asset managers count towards the
+/// balance.
+fn read_balances<'a, 'info: 'a>(
+    pool: &ManagedPool,
+    registration: &PoolRegistration,
+    custody: impl IntoIterator<Item = &'a AccountInfo<'info>>,
+) -> Result<Vec<U256>> {
+    let mut balances = Vec::with_capacity(pool.tokens.len());
+    for (i, ai) in custody.into_iter().enumerate() {
+        let (index, balance) = read_balance(pool, registration, ai)?;
+        require!(index == i, ErrorCode::InvalidPoolTokenAccount);
+        balances.push(balance);
+    }
+    require!(balances.len() == pool.tokens.len(), ErrorCode::LengthMismatch);
+    Ok(balances)
+}
+
+/// Index and 18‑dec balance (cash plus managed) of one registered custody account.
+fn read_balance(pool: &ManagedPool, registration: &PoolRegistration, ai: &AccountInfo) -> Result<(usize, U256)> {
+    let acct = unpack_token_account(ai)?;
+    let i = pool.index_of(&acct.mint)?;
+    require_keys_eq!(
+        ai.key(),
+        registration.token_accounts.get(i).copied().unwrap_or_default(),
+        ErrorCode::InvalidPoolTokenAccount
+    );
+    let amount = acct
+        .amount
+        .checked_add(registration.managed_balance(&acct.mint))
+        .ok_or(ErrorCode::MathOverflow)?;
+    Ok((i, scale_up(amount, pool.decimals[i])?))
+}
+
+/// Mint the management fee accrued up to `now` to the owner's LP account.
+fn collect_aum_fee<'info>(
+    pool: &mut Account<'info, ManagedPool>,
+    lp_mint: &AccountInfo<'info>,
+    lp_mint_authority: &AccountInfo<'info>,
+    bump: u8,
+    manager_lp_account: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    now: i64,
+) -> Result<()> {
+    let minted = pool.accrue_aum_fee(now)?;
+    if minted == 0 {
+        return Ok(());
+    }
+    let acct = unpack_token_account(manager_lp_account)?;
+    require!(acct.owner == pool.owner && acct.mint == pool.lp_mint, ErrorCode::InvalidManagerAccount);
+    mint_bpt(token_program, lp_mint, lp_mint_authority, &pool.key(), bump, manager_lp_account, minted)?;
+    emit!(ManagementAumFeeCollected {
+        pool: pool.key(),
+        bpt: minted,
+    });
+    Ok(())
+}
+
+/// Mint BPT, signed by the pool authority PDA.
+fn mint_bpt<'info>(
+    token_program: &AccountInfo<'info>,
+    lp_mint: &AccountInfo<'info>,
+    lp_mint_authority: &AccountInfo<'info>,
+    pool: &Pubkey,
+    bump: u8,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let bump_arr = [bump];
+    let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool.as_ref(), &bump_arr];
+    let cpi_accounts = MintTo {
+        mint:      lp_mint.clone(),
+        to:        to.clone(),
+        authority: lp_mint_authority.clone(),
+    };
+    token::mint_to(
+        CpiContext::new_with_signer(token_program.clone(), cpi_accounts, &[seed_slice]),
+        amount,
+    )
+}
+
+/// Protocol share (`pct`, 18‑dec) of a fee amount, rounded up.
+fn protocol_share(fee_amount_fp: U256, pct: u64) -> Result<U256> {
+    Ok(pool_tokens::protocol_share(fee_amount_fp, pct).map_err(ErrorCode::from)?)
+}
+
+/// Raw token amount as an 18‑dec value.
+fn scale_up(amount: u64, decimals: u8) -> Result<U256> {
+    Ok(pool_tokens::scale_up(amount, decimals).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec value as a raw token amount, rounded down (amounts paid out).
+fn scale_down(value_fp: U256, decimals: u8) -> Result<u64> {
+    Ok(pool_tokens::scale_down(value_fp, decimals).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec value as a raw token amount, rounded up (amounts paid in).
+fn scale_down_up(value_fp: U256, decimals: u8) -> Result<u64> {
+    Ok(pool_tokens::scale_down_up(value_fp, decimals).map_err(ErrorCode::from)?)
+}
+
+/// Decimals of a mint of either token program, checked to be scalable
+/// to 18‑dec.
+fn mint_decimals(ai: &AccountInfo) -> Result<u8> {
+    pool_tokens::mint_decimals(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A token account of either token program, its extensions skipped.
+fn unpack_token_account(ai: &AccountInfo) -> Result<TokenAccount> {
+    pool_tokens::unpack_token_account(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A protocol fee account must be a token account of `mint` held by the collector.
+fn check_fee_account(ai: &AccountInfo, collector: &Pubkey, mint: &Pubkey) -> Result<()> {
+    pool_tokens::check_fee_account(ai, collector, mint, ErrorCode::InvalidFeeAccount)
+}
+
+/* ------------------------------------------------------------------
+   Accounts: initialize & owner contexts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    /// Becomes the pool's owner
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// An already‑initialized VaultState account
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// CHECK: the LP‑token mint for this pool; its decimals scale BPT amounts
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority for `lp_mint`; derived from `["lp-mint-authority", pool.key().as_ref()]`
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// The Pool state PDA itself
+    #[account(
+        init,
+        seeds = [b"pool-state", vault_state.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + ManagedPool::INIT_SPACE
+    )]
+    pub pool: Account<'info, ManagedPool>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct OwnerOnly<'info> {
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, ManagedPool>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Crank<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ManagedPool>,
+
+    /// Anyone; receives the crank bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectAumFees<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ManagedPool>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// CHECK: owner's LP token account; checked when fees are minted
+    #[account(mut)]
+    pub manager_lp_account: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct SetManagementAumFee<'info> {
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, ManagedPool>,
+
+    pub owner: Signer<'info>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// CHECK: owner's LP token account; checked when fees are minted
+    #[account(mut)]
+    pub manager_lp_account: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeTokens<'info> {
+    #[account(mut, has_one = owner @ ErrorCode::Unauthorized)]
+    pub pool: Account<'info, ManagedPool>,
+
+    /// Must also hold the Vault's `RegisterPool` role; pays (and is refunded) the custody account rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// The vault this pool is registered with
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault
+    pub authorizer: AccountInfo<'info>,
+
+    /// The pool's Vault registration, updated by the Vault
+    #[account(
+        mut,
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: the Vault's authority PDA; validated by the Vault
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: mint of the token added or removed; must be an SPL mint
+    pub mint: AccountInfo<'info>,
+
+    /// CHECK: custody account of `mint`, created or closed by the Vault (seeds checked there)
+    #[account(mut)]
+    pub pool_token: AccountInfo<'info>,
+
+    /// CHECK: owner's token account for `mint`; funds an added token, receives a removed one
+    #[account(mut)]
+    pub owner_token_account: AccountInfo<'info>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority and custody owner; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// CHECK: owner's LP token account; BPT is minted to it on add and burned from it on remove
+    #[account(mut)]
+    pub manager_lp_account: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ChangeTokens<'info> {
+    fn collect_aum_fee(&mut self, bump: u8, now: i64) -> Result<()> {
+        collect_aum_fee(
+            &mut self.pool,
+            &self.lp_mint,
+            &self.lp_mint_authority,
+            bump,
+            &self.manager_lp_account,
+            &self.token_program.to_account_info(),
+            now,
+        )
+    }
+
+    /// Call the Vault's register/deregister_tokens for `mint` and its
+    /// custody account, with the owner as the `RegisterPool` authority.
+    fn vault_tokens_cpi<F>(&self, call: F) -> Result<()>
+    where
+        F: FnOnce(AccountInfo<'info>, VaultRegisterTokens<'info>, Vec<AccountInfo<'info>>) -> Result<()>,
+    {
+        let cpi_accounts = VaultRegisterTokens {
+            vault_state:              self.vault_state.to_account_info(),
+            authorizer:               self.authorizer.clone(),
+            pool_registration:        self.pool_registration.to_account_info(),
+            vault_authority:          self.vault_authority.clone(),
+            transfer_hook_allowlist:  None,
+            mint_extension_overrides: None,
+            authority:                self.owner.to_account_info(),
+            token_program:            self.token_program.to_account_info(),
+            system_program:           self.system_program.to_account_info(),
+        };
+        call(
+            self.vault_program.to_account_info(),
+            cpi_accounts,
+            vec![self.mint.clone(), self.pool_token.clone()],
+        )
+    }
+}
+
+#[derive(Accounts)]
+pub struct PoolContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ManagedPool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: user's LP token account; BPT is minted to or burned from it
+    #[account(mut)]
+    pub user_lp_account: AccountInfo<'info>,
+
+    /// CHECK: owner's LP token account; management fees accrued since the last collection are minted to it
+    #[account(mut)]
+    pub manager_lp_account: AccountInfo<'info>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's LP token account; checked when a protocol fee is minted
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> PoolContext<'info> {
+    fn check_live(&self, now: i64) -> Result<()> {
+        require!(!self.vault_state.is_paused(now), ErrorCode::VaultPaused);
+        require!(!self.pool_registration.paused, ErrorCode::PoolPaused);
+        Ok(())
+    }
+
+    fn collect_aum_fee(&mut self, bump: u8, now: i64) -> Result<()> {
+        collect_aum_fee(
+            &mut self.pool,
+            &self.lp_mint,
+            &self.lp_mint_authority,
+            bump,
+            &self.manager_lp_account,
+            &self.token_program.to_account_info(),
+            now,
+        )
+    }
+
+    /// user → vault, signed by the user
+    fn pay_in(&self, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::transfer(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    /// vault → user, signed by the pool authority PDA
+    fn pay_out(&self, bump: u8, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    fn mint_bpt(&self, bump: u8, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        mint_bpt(
+            &self.token_program.to_account_info(),
+            &self.lp_mint,
+            &self.lp_mint_authority,
+            &self.pool.key(),
+            bump,
+            to,
+            amount,
+        )
+    }
+
+    /// The protocol's share of a fee, minted as BPT to the collector.
+    fn mint_protocol_bpt(&self, bump: u8, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        check_fee_account(&self.protocol_fee_lp_account, &self.fees_collector.key(), &self.pool.lp_mint)?;
+        self.mint_bpt(bump, &self.protocol_fee_lp_account, amount)
+    }
+
+    fn burn_bpt(&self, amount: u64) -> Result<()> {
+        let cpi_accounts = Burn {
+            mint:      self.lp_mint.clone(),
+            from:      self.user_lp_account.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::burn(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts: swap context (same layout as every pool's, see
+   `common::pool_interface::SwapExactInAccounts`)
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct SwapContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ManagedPool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: custody account of the 'in' token; checked against the registration
+    #[account(mut)]
+    pub vault_in: AccountInfo<'info>,
+
+    /// CHECK: custody account of the 'out' token; checked against the registration
+    #[account(mut)]
+    pub vault_out: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_authority: Signer<'info>,
+
+    /// CHECK: User's token account for the 'in' mint
+    #[account(mut)]
+    pub user_token_account_in: AccountInfo<'info>,
+
+    /// CHECK: User's token account for the 'out' mint
+    #[account(mut)]
+    pub user_token_account_out: AccountInfo<'info>,
+
+    /// CHECK: PDA for LP mint authority; seed ensures correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's account for the 'in' token; checked when a protocol fee is paid
+    #[account(mut)]
+    pub protocol_fee_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+}
+
+#[derive(Accounts)]
+pub struct ManagedTransfer<'info> {
+    pub pool: Account<'info, ManagedPool>,
+
+    /// The Vault's asset manager authority PDA; the Vault signs for it
+    /// only in `withdraw_managed`
+    #[account(
+        seeds = [b"asset-manager-authority", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub asset_manager_authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: pool custody account, one the Vault recorded for the pool;
+    /// the token program enforces ownership
+    #[account(
+        mut,
+        constraint = pool_registration.token_accounts.contains(&from.key()) @ ErrorCode::InvalidPoolTokenAccount
+    )]
+    pub from: AccountInfo<'info>,
+
+    /// CHECK: asset manager's destination, chosen and checked by the Vault
+    #[account(mut)]
+    pub to: AccountInfo<'info>,
+
+    /// CHECK: PDA holding the pool's tokens
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct QuerySwap<'info> {
+    pub pool: Account<'info, ManagedPool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: pool custody account of the token in; identifies the token only
+    pub pool_token_in: AccountInfo<'info>,
+
+    /// CHECK: pool custody account of the token out; identifies the token only
+    pub pool_token_out: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// Pool configuration chosen at initialisation.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ManagedPoolParams {
+    /// Initial weights (18‑dec, one per mint, summing to one)
+    pub weights: Vec<u128>,
+    pub swap_fee: u64,
+    /// Yearly fee on assets under management (18‑dec), minted to the owner as BPT
+    pub management_aum_fee: u64,
+    /// Only allowlisted LPs (and the owner) may join
+    pub must_allowlist_lps: bool,
+}
+
+#[account]
+pub struct ManagedPool {
+    pub vault: Pubkey,
+    pub lp_mint: Pubkey,
+    /// Manages tokens, weights, fees and the allowlist; collects the management fee
+    pub owner: Pubkey,
+    /// Token mints, in Vault registration order
+    pub tokens: Vec<Pubkey>,
+    /// Decimals of each token, scaling raw amounts to 18‑dec
+    pub decimals: Vec<u8>,
+    /// Decimals of the LP mint, scaling BPT amounts to 18‑dec
+    pub lp_decimals: u8,
+    /// Weights until `weight_start_time`
+    pub start_weights: Vec<u128>,
+    /// Weights from `weight_end_time`
+    pub end_weights: Vec<u128>,
+    pub weight_start_time: i64,
+    pub weight_end_time: i64,
+    /// Swap fee until `fee_start_time`
+    pub start_swap_fee: u64,
+    /// Swap fee from `fee_end_time`
+    pub end_swap_fee: u64,
+    pub fee_start_time: i64,
+    pub fee_end_time: i64,
+    /// Yearly management fee on assets under management (18‑dec)
+    pub management_aum_fee: u64,
+    /// Management fees are accrued up to here
+    pub last_aum_fee_collection: i64,
+    pub must_allowlist_lps: bool,
+    pub allowed_lps: Vec<Pubkey>,
+    pub total_bpt: u64,
+    /// PoolId assigned by the Vault at registration
+    pub pool_id: [u8; 32],
+}
+
+impl ManagedPool {
+    pub const INIT_SPACE: usize = 32 * 3
+        + (4 + MAX_POOL_TOKENS * 32)
+        + (4 + MAX_POOL_TOKENS)
+        + 1
+        + 2 * (4 + MAX_POOL_TOKENS * 16)
+        + 8 * 2
+        + 8 * 2
+        + 8 * 2
+        + 8
+        + 8
+        + 1
+        + (4 + MAX_ALLOWED_LPS * 32)
+        + 8
+        + 32;
+
+    /// Weights at `now` on the weight schedule.
+    pub fn weights_at(&self, now: i64) -> Result<Vec<U256>> {
+        let start: Vec<U256> = self.start_weights.iter().map(|w| U256::from(*w)).collect();
+        let end: Vec<U256> = self.end_weights.iter().map(|w| U256::from(*w)).collect();
+        Ok(
+            weighted_math::try_interpolate_weights(&start, &end, self.weight_start_time, self.weight_end_time, now)
+                .map_err(ErrorCode::from)?,
+        )
+    }
+
+    /// Swap fee at `now` on the fee schedule.
+    pub fn swap_fee_at(&self, now: i64) -> Result<U256> {
+        Ok(fees::try_interpolate_fee(
+            U256::from(self.start_swap_fee),
+            U256::from(self.end_swap_fee),
+            self.fee_start_time,
+            self.fee_end_time,
+            now,
+        ).map_err(ErrorCode::from)?)
+    }
+
+    pub fn is_weight_change_in_progress(&self, now: i64) -> bool {
+        now < self.weight_end_time && self.start_weights != self.end_weights
+    }
+
+    /// Whether `lp` may join.
+    pub fn is_allowed_lp(&self, lp: &Pubkey) -> bool {
+        !self.must_allowlist_lps || *lp == self.owner || self.allowed_lps.contains(lp)
+    }
+
+    /// Fix the weights at `weights` from `now` on.
+    fn set_weights(&mut self, weights: &[U256], now: i64) {
+        self.start_weights = weights.iter().map(|w| w.as_u128()).collect();
+        self.end_weights = self.start_weights.clone();
+        self.weight_start_time = now;
+        self.weight_end_time = now;
+    }
+
+    /// Management fee BPT accrued since the last collection, added to
+    /// `total_bpt`; the caller mints it. The clock only moves on once some
+    /// BPT is due, so frequent collections on a small pool lose nothing
+    /// to rounding.
+    fn accrue_aum_fee(&mut self, now: i64) -> Result<u64> {
+        let elapsed = u64::try_from(now.saturating_sub(self.last_aum_fee_collection)).unwrap_or(0);
+        if self.management_aum_fee == 0 || self.total_bpt == 0 {
+            self.last_aum_fee_collection = self.last_aum_fee_collection.max(now);
+            return Ok(0);
+        }
+        let minted_fp = fees::try_aum_fee_bpt(
+            scale_up(self.total_bpt, self.lp_decimals)?,
+            U256::from(self.management_aum_fee),
+            elapsed,
+        ).map_err(ErrorCode::from)?;
+        let minted = scale_down(minted_fp, self.lp_decimals)?;
+        if minted > 0 {
+            self.last_aum_fee_collection = now;
+            self.total_bpt = self.total_bpt.checked_add(minted).ok_or(ErrorCode::MathOverflow)?;
+        }
+        Ok(minted)
+    }
+
+    fn index_of(&self, mint: &Pubkey) -> Result<usize> {
+        Ok(self.tokens.iter().position(|t| t == mint).ok_or(ErrorCode::InvalidPoolTokenAccount)?)
+    }
+}
+
+/// The owner scheduled a weight change
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradualWeightUpdateScheduled {
+    pub pool: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub end_weights: Vec<u128>,
+}
+
+/// The owner scheduled a swap fee change
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradualSwapFeeUpdateScheduled {
+    pub pool: Pubkey,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub start_swap_fee: u64,
+    pub end_swap_fee: u64,
+}
+
+/// A crank fixed the end values of completed schedules
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchedulesFinalized {
+    pub pool: Pubkey,
+    /// The weight schedule was finalized
+    pub weights: bool,
+    /// The swap fee schedule was finalized
+    pub swap_fee: bool,
+    pub cranker: Pubkey,
+    /// Lamports paid to the cranker
+    pub bounty: u64,
+}
+
+/// Management fee minted to the owner
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManagementAumFeeCollected {
+    pub pool: Pubkey,
+    pub bpt: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenAdded {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub weight: u128,
+    pub amount_in: u64,
+    pub bpt_out: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenRemoved {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub amount_out: u64,
+    pub bpt_in: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Vector length mismatch")]
+    LengthMismatch,
+    #[msg("Math underflow or overflow")]
+    MathUnderflow,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Protocol fee account does not belong to the fees collector")]
+    InvalidFeeAccount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Swap amount in exceeds the maximum in ratio")]
+    MaxInRatio,
+    #[msg("Swap amount out exceeds the maximum out ratio")]
+    MaxOutRatio,
+    #[msg("Weights are out of range or do not sum to one")]
+    InvalidWeights,
+    #[msg("Math solver did not converge")]
+    MathConvergenceFailure,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("Pool must hold between 2 and the vault's maximum number of tokens")]
+    InvalidTokenCount,
+    #[msg("Token account does not hold one of the pool's tokens")]
+    InvalidPoolTokenAccount,
+    #[msg("Swap tokens must be two different tokens of the pool")]
+    InvalidSwapTokens,
+    #[msg("The first join must add every token")]
+    InvalidInitialJoin,
+    #[msg("Amount is outside the caller's limit")]
+    SlippageLimit,
+    #[msg("Schedule must end no earlier than it starts")]
+    InvalidSchedule,
+    #[msg("Swap fee above the maximum")]
+    SwapFeeTooHigh,
+    #[msg("Management fee above the maximum")]
+    ManagementFeeTooHigh,
+    #[msg("LP is not on the pool's allowlist")]
+    LpNotAllowed,
+    #[msg("Address is already on the allowlist")]
+    AddressAlreadyAllowed,
+    #[msg("Address is not on the allowlist")]
+    AddressNotAllowed,
+    #[msg("Allowlist is full")]
+    AllowlistFull,
+    #[msg("Token is already in the pool")]
+    TokenAlreadyInPool,
+    #[msg("Tokens cannot change during a weight change")]
+    WeightChangeInProgress,
+    #[msg("Part of the token's balance is with its asset manager")]
+    TokenIsManaged,
+    #[msg("Management fees go to an LP token account of the owner")]
+    InvalidManagerAccount,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("No transition is due")]
+    NothingToCrank,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::LengthMismatch => ErrorCode::LengthMismatch,
+            MathError::WeightsNotNormalized | MathError::InvalidWeight => ErrorCode::InvalidWeights,
+            MathError::MaxInRatio => ErrorCode::MaxInRatio,
+            MathError::MaxOutRatio => ErrorCode::MaxOutRatio,
+            MathError::ConvergenceFailure => ErrorCode::MathConvergenceFailure,
+            MathError::TokenIndexOutOfBounds => ErrorCode::InvalidSwapTokens,
+            MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::InvalidParams
+            | MathError::AssetBoundsExceeded
+            | MathError::InvalidPercentage
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange => ErrorCode::MathInputOutOfBounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E16: u128 = 10_000_000_000_000_000;
+    const E6: u64 = 1_000_000;
+
+    fn fp(weights: &[u128]) -> Vec<U256> {
+        weights.iter().map(|w| U256::from(*w)).collect()
+    }
+
+    /// 50/30/20 index, 1 % fee, 2 % a year management fee
+    fn index() -> ManagedPool {
+        ManagedPool {
+            vault: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            owner: Pubkey::new_unique(),
+            tokens: vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()],
+            decimals: vec![6, 6, 6],
+            lp_decimals: 6,
+            start_weights: vec![50 * E16, 30 * E16, 20 * E16],
+            end_weights: vec![50 * E16, 30 * E16, 20 * E16],
+            weight_start_time: 0,
+            weight_end_time: 0,
+            start_swap_fee: E16 as u64,
+            end_swap_fee: E16 as u64,
+            fee_start_time: 0,
+            fee_end_time: 0,
+            management_aum_fee: 2 * E16 as u64,
+            last_aum_fee_collection: 0,
+            must_allowlist_lps: false,
+            allowed_lps: Vec::new(),
+            total_bpt: 1_000 * E6,
+            pool_id: [0; 32],
+        }
+    }
+
+    #[test]
+    fn adding_and_removing_a_token_rescales_the_others() {
+        let weights = fp(&[50 * E16, 30 * E16, 20 * E16]);
+        let added = weights_with(&weights, U256::from(20 * E16)).unwrap();
+        assert_eq!(added, fp(&[40 * E16, 24 * E16, 16 * E16, 20 * E16]));
+        assert_eq!(weights_without(&added, 3).unwrap(), weights);
+        assert_eq!(weights_without(&weights, 0).unwrap(), fp(&[60 * E16, 40 * E16]));
+
+        // nothing may fall below the minimum, nor the pool below two tokens
+        let tiny = weights_with(&fp(&[98 * E16, 2 * E16]), U256::from(60 * E16));
+        assert_eq!(tiny.unwrap_err(), error!(ErrorCode::InvalidWeights));
+        let pair = weights_without(&fp(&[50 * E16, 50 * E16]), 0);
+        assert_eq!(pair.unwrap_err(), error!(ErrorCode::InvalidTokenCount));
+    }
+
+    #[test]
+    fn weights_and_fee_follow_their_schedules() {
+        let mut pool = index();
+        pool.end_weights = vec![20 * E16, 30 * E16, 50 * E16];
+        pool.weight_end_time = 100;
+        pool.end_swap_fee = 3 * E16 as u64;
+        pool.fee_end_time = 100;
+        assert!(pool.is_weight_change_in_progress(50));
+        assert_eq!(pool.weights_at(50).unwrap(), fp(&[35 * E16, 30 * E16, 35 * E16]));
+        assert_eq!(pool.swap_fee_at(50).unwrap(), U256::from(2 * E16));
+        assert!(!pool.is_weight_change_in_progress(100));
+        assert_eq!(pool.weights_at(200).unwrap(), fp(&pool.end_weights));
+    }
+
+    #[test]
+    fn management_fee_accrues_pro_rata() {
+        let mut pool = index();
+        // a year at 2 %: the owner ends with 2 % of 1020.4… BPT
+        let minted = pool.accrue_aum_fee(fees::SECONDS_PER_YEAR as i64).unwrap();
+        assert_eq!(minted, 20_408_163);
+        assert_eq!(pool.total_bpt, 1_000 * E6 + minted);
+        assert_eq!(pool.last_aum_fee_collection, fees::SECONDS_PER_YEAR as i64);
+        // nothing more is due at the same instant
+        assert_eq!(pool.accrue_aum_fee(fees::SECONDS_PER_YEAR as i64).unwrap(), 0);
+    }
+
+    #[test]
+    fn allowlist_gates_joins_only_when_on() {
+        let mut pool = index();
+        let lp = Pubkey::new_unique();
+        assert!(pool.is_allowed_lp(&lp));
+        pool.must_allowlist_lps = true;
+        assert!(!pool.is_allowed_lp(&lp));
+        assert!(pool.is_allowed_lp(&pool.owner));
+        pool.allowed_lps.push(lp);
+        assert!(pool.is_allowed_lp(&lp));
+    }
+
+    #[test]
+    fn quotes_at_the_current_weights() {
+        let mut pool = index();
+        pool.start_swap_fee = 0;
+        pool.end_swap_fee = 0;
+        let balances = [scale_up(1_000 * E6, 6).unwrap(), scale_up(600 * E6, 6).unwrap()];
+        // 50/30 at balances 1000/600 prices the pair at 1:1
+        let quote = quote_exact_in(&pool, 0, 0, (0, 1), E6, balances).unwrap();
+        assert!((998_000..E6).contains(&quote.amount_out), "{}", quote.amount_out);
+    }
+}
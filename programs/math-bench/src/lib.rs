@@ -0,0 +1,102 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::log::sol_log_compute_units;
+use core::hint::black_box;
+use math::{fixed, log_exp_math, weighted_math, U256};
+
+// ---------------------------------------------------------------------
+// Compute‑unit benchmarks for the math crate. Each call runs one
+// operation between two `sol_log_compute_units` markers; the difference
+// of the logged budgets is its cost. tests/math-bench.ts drives it.
+// ---------------------------------------------------------------------
+declare_id!("MathBenchBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBB");
+
+#[program]
+pub mod math_bench {
+    use super::*;
+
+    pub fn bench(_ctx: Context<Bench>, op: BenchOp) -> Result<()> {
+        sol_log_compute_units();
+        let result = run(black_box(op));
+        sol_log_compute_units();
+        msg!("{:?} = {}", op, result);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Bench {}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchOp {
+    MulDown,
+    DivDown,
+    Pow,
+    PowNearOne,
+    PowDown,
+    PowUp,
+    PowDownInteger,
+    Invariant2,
+    Invariant2EqualWeights,
+    Invariant8,
+    OutGivenIn,
+    OutGivenInEqualWeights,
+    InGivenOut,
+    BptOutGivenExactTokensIn,
+    TokenInGivenExactBptOut,
+    TokensOutGivenExactBptIn,
+    TokenOutGivenExactBptIn,
+}
+
+fn fp(v: u128) -> U256 {
+    U256::from(v) * fixed::ONE
+}
+
+/// `ppm / 1e6` as an 18‑dec value.
+fn ppm(ppm: u64) -> U256 {
+    U256::from(ppm) * U256::from(1_000_000_000_000u64)
+}
+
+/// Same inputs as math/benches/math.rs, so host and chain numbers line up.
+fn run(op: BenchOp) -> U256 {
+    let (b_in, w_in, b_out, w_out) = (fp(1_000_000), ppm(800_000), fp(2_500_000), ppm(200_000));
+    let fee = ppm(3_000);
+    let total_bpt = fp(1_000_000);
+    match op {
+        BenchOp::MulDown => fixed::mul_down(black_box(fp(1_234_567)), black_box(ppm(987_654))),
+        BenchOp::DivDown => fixed::div_down(black_box(fp(1_234_567)), black_box(ppm(987_654))),
+        BenchOp::Pow => log_exp_math::pow(black_box(ppm(1_250_000)), black_box(ppm(333_333))),
+        BenchOp::PowNearOne => log_exp_math::pow(black_box(ppm(1_050_000)), black_box(ppm(333_333))),
+        BenchOp::PowDown => fixed::pow_down(black_box(ppm(1_250_000)), black_box(ppm(333_333))),
+        BenchOp::PowUp => fixed::pow_up(black_box(ppm(1_250_000)), black_box(ppm(333_333))),
+        BenchOp::PowDownInteger => fixed::pow_down(black_box(ppm(1_250_000)), black_box(fp(2))),
+        BenchOp::Invariant2 => weighted_math::calculate_invariant(black_box(&[b_in, b_out]), &[w_in, w_out]),
+        BenchOp::Invariant2EqualWeights => {
+            weighted_math::calculate_invariant(black_box(&[b_in, b_out]), &[ppm(500_000); 2])
+        }
+        BenchOp::Invariant8 => {
+            let balances: [U256; 8] = core::array::from_fn(|i| fp(100_000 * (i as u128 + 1)));
+            weighted_math::calculate_invariant(black_box(&balances), &[ppm(125_000); 8])
+        }
+        BenchOp::OutGivenIn => weighted_math::calc_out_given_in(b_in, w_in, b_out, w_out, black_box(fp(1_000)), fee),
+        BenchOp::OutGivenInEqualWeights => {
+            weighted_math::calc_out_given_in(b_in, ppm(500_000), b_out, ppm(500_000), black_box(fp(1_000)), fee)
+        }
+        BenchOp::InGivenOut => weighted_math::calc_in_given_out(b_in, w_in, b_out, w_out, black_box(fp(1_000)), fee),
+        BenchOp::BptOutGivenExactTokensIn => weighted_math::calc_bpt_out_given_exact_tokens_in(
+            &[b_in, b_out],
+            &[w_in, w_out],
+            black_box(&[fp(10_000), fp(1_000)]),
+            total_bpt,
+            fee,
+        ),
+        BenchOp::TokenInGivenExactBptOut => {
+            weighted_math::calc_token_in_given_exact_bpt_out(b_in, w_in, black_box(fp(1_000)), total_bpt, fee)
+        }
+        BenchOp::TokensOutGivenExactBptIn => {
+            weighted_math::calc_tokens_out_given_exact_bpt_in(&[b_in, b_out], black_box(fp(1_000)), total_bpt, U256::zero())[0]
+        }
+        BenchOp::TokenOutGivenExactBptIn => {
+            weighted_math::calc_token_out_given_exact_bpt_in(b_in, w_in, black_box(fp(1_000)), total_bpt, fee)
+        }
+    }
+}
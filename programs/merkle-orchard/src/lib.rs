@@ -0,0 +1,454 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("MrkRchKvkHsp594ctBnzG4gow9ajFTd5vfuEF6PfyV1");
+
+/// Distributions a claim bitmap covers
+pub const DISTRIBUTIONS_PER_WORD: u64 = 256;
+
+/// Merkle orchard: reward funders post off‑chain computed incentives as
+/// one Merkle root per distribution, funding it in the same instruction,
+/// and claimers collect with proofs. Distributions are numbered 0, 1, 2…
+/// per (reward token, funder); which of them a claimer has collected is
+/// kept in bitmaps of 256 distributions, so one small account records a
+/// claimer's claims over many epochs. `claim_distributions` collects any
+/// number of distributions, of any tokens and funders, at once.
+///
+/// A leaf is [`leaf`]`(claimer, amount)`; a proof's nodes are combined in
+/// sorted order (see [`verify_proof`]).
+#[program]
+pub mod merkle_orchard {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Post and fund the funder's next distribution of a token
+    ---------------------------------------------------------------- */
+    pub fn create_distribution(
+        ctx: Context<CreateDistribution>,
+        distribution_id: u64,
+        merkle_root: [u8; 32],
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let funder_state = &mut ctx.accounts.funder_state;
+        require!(distribution_id == funder_state.next_distribution_id, ErrorCode::InvalidDistributionId);
+        funder_state.next_distribution_id += 1;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.funder_token_account.to_account_info(),
+                    to:        ctx.accounts.token_vault.to_account_info(),
+                    authority: ctx.accounts.funder.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let distribution = &mut ctx.accounts.distribution;
+        distribution.token_mint      = ctx.accounts.token_mint.key();
+        distribution.funder          = ctx.accounts.funder.key();
+        distribution.distribution_id = distribution_id;
+        distribution.merkle_root     = merkle_root;
+        distribution.token_vault     = ctx.accounts.token_vault.key();
+        distribution.amount          = amount;
+        distribution.claimed         = 0;
+
+        emit!(DistributionCreated {
+            token_mint: distribution.token_mint,
+            funder: distribution.funder,
+            distribution_id,
+            merkle_root,
+            amount,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Create a claimer's bitmap for distributions
+       [word · 256, (word + 1) · 256) of a (token, funder)
+    ---------------------------------------------------------------- */
+    pub fn initialize_claim_bitmap(
+        ctx: Context<InitializeClaimBitmap>,
+        token_mint: Pubkey,
+        funder: Pubkey,
+        word: u64,
+    ) -> Result<()> {
+        let bitmap = &mut ctx.accounts.claim_bitmap;
+        bitmap.token_mint = token_mint;
+        bitmap.funder     = funder;
+        bitmap.claimer    = ctx.accounts.claimer.key();
+        bitmap.word       = word;
+        bitmap.bits       = [0; 4];
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Claim several distributions at once
+       Remaining accounts, `ClaimArgs::ACCOUNTS` per claim:
+         [distribution, claim_bitmap, token_vault, claimer_token_account]
+       Consecutive claims paying the same vault into the same account
+       are settled with one transfer.
+    ---------------------------------------------------------------- */
+    pub fn claim_distributions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimDistributions<'info>>,
+        claims: Vec<ClaimArgs>,
+    ) -> Result<()> {
+        let rem = ctx.remaining_accounts;
+        require!(!claims.is_empty(), ErrorCode::MalformedClaim);
+        require!(rem.len() == claims.len() * ClaimArgs::ACCOUNTS, ErrorCode::MalformedClaim);
+
+        let claimer = ctx.accounts.claimer.key();
+        let bump_arr = [ctx.bumps.orchard_authority];
+        let seed_slice: &[&[u8]] = &[b"orchard-authority", &bump_arr];
+        let transfer = |from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64| {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    Transfer {
+                        from:      from.clone(),
+                        to:        to.clone(),
+                        authority: ctx.accounts.orchard_authority.to_account_info(),
+                    },
+                    &[seed_slice],
+                ),
+                amount,
+            )
+        };
+
+        // (vault, destination, amount) not yet transferred
+        let mut pending: Option<(usize, usize, u64)> = None;
+        for (k, (claim, group)) in claims.iter().zip(rem.chunks(ClaimArgs::ACCOUNTS)).enumerate() {
+            let mut distribution = Account::<Distribution>::try_from(&group[0])?;
+            let mut bitmap       = Account::<ClaimBitmap>::try_from(&group[1])?;
+            let destination      = Account::<TokenAccount>::try_from(&group[3])?;
+            require!(distribution.distribution_id == claim.distribution_id, ErrorCode::MalformedClaim);
+            require_keys_eq!(group[2].key(), distribution.token_vault, ErrorCode::MalformedClaim);
+            require_keys_eq!(destination.mint, distribution.token_mint, ErrorCode::MalformedClaim);
+            require!(
+                bitmap.claimer == claimer
+                    && bitmap.token_mint == distribution.token_mint
+                    && bitmap.funder == distribution.funder
+                    && bitmap.word == claim.distribution_id / DISTRIBUTIONS_PER_WORD,
+                ErrorCode::MalformedClaim
+            );
+
+            require!(
+                verify_proof(&claim.proof, distribution.merkle_root, leaf(&claimer, claim.amount)),
+                ErrorCode::InvalidProof
+            );
+            bitmap.set_claimed(claim.distribution_id)?;
+            distribution.claimed = distribution
+                .claimed
+                .checked_add(claim.amount)
+                .filter(|c| *c <= distribution.amount)
+                .ok_or(ErrorCode::DistributionExhausted)?;
+            distribution.exit(&crate::ID)?;
+            bitmap.exit(&crate::ID)?;
+
+            emit!(DistributionClaimed {
+                token_mint: distribution.token_mint,
+                funder: distribution.funder,
+                distribution_id: claim.distribution_id,
+                claimer,
+                amount: claim.amount,
+            });
+
+            let at = k * ClaimArgs::ACCOUNTS;
+            pending = match pending {
+                Some((v, d, amount)) if rem[v].key() == group[2].key() && rem[d].key() == group[3].key() => {
+                    Some((v, d, amount.checked_add(claim.amount).ok_or(ErrorCode::MathOverflow)?))
+                }
+                Some((v, d, amount)) => {
+                    transfer(&rem[v], &rem[d], amount)?;
+                    Some((at + 2, at + 3, claim.amount))
+                }
+                None => Some((at + 2, at + 3, claim.amount)),
+            };
+        }
+        if let Some((v, d, amount)) = pending {
+            transfer(&rem[v], &rem[d], amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// Leaf of `claimer`'s `amount`: sha256(claimer ‖ amount (LE u64)).
+pub fn leaf(claimer: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[claimer.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+/// Whether `proof` leads from `leaf` to `root`, each step hashing the
+/// pair of nodes in ascending byte order.
+pub fn verify_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let node = proof.iter().fold(leaf, |node, sibling| {
+        let (a, b) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&a, &b]).to_bytes()
+    });
+    node == root
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+#[instruction(distribution_id: u64)]
+pub struct CreateDistribution<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(mut, token::mint = token_mint)]
+    pub funder_token_account: Account<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + FunderState::INIT_SPACE,
+        seeds = [b"orchard-funder", token_mint.key().as_ref(), funder.key().as_ref()],
+        bump
+    )]
+    pub funder_state: Account<'info, FunderState>,
+
+    #[account(
+        init,
+        payer = funder,
+        space = 8 + Distribution::INIT_SPACE,
+        seeds = [
+            b"distribution",
+            token_mint.key().as_ref(),
+            funder.key().as_ref(),
+            &distribution_id.to_le_bytes()
+        ],
+        bump
+    )]
+    pub distribution: Account<'info, Distribution>,
+
+    /// CHECK: PDA holding every distribution's tokens
+    #[account(seeds = [b"orchard-authority"], bump)]
+    pub orchard_authority: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = funder,
+        seeds = [b"orchard-tokens", token_mint.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = orchard_authority
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(token_mint: Pubkey, funder: Pubkey, word: u64)]
+pub struct InitializeClaimBitmap<'info> {
+    #[account(mut)]
+    pub claimer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = claimer,
+        space = 8 + ClaimBitmap::INIT_SPACE,
+        seeds = [
+            b"claim-bitmap",
+            token_mint.as_ref(),
+            funder.as_ref(),
+            claimer.key().as_ref(),
+            &word.to_le_bytes()
+        ],
+        bump
+    )]
+    pub claim_bitmap: Account<'info, ClaimBitmap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimDistributions<'info> {
+    pub claimer: Signer<'info>,
+
+    /// CHECK: PDA holding every distribution's tokens
+    #[account(seeds = [b"orchard-authority"], bump)]
+    pub orchard_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// Next distribution id of a funder, PDA'd by ["orchard-funder", token_mint, funder]
+#[account]
+#[derive(InitSpace)]
+pub struct FunderState {
+    pub next_distribution_id: u64,
+}
+
+/// One posted root, PDA'd by ["distribution", token_mint, funder, id (LE u64)]
+#[account]
+#[derive(InitSpace)]
+pub struct Distribution {
+    pub token_mint: Pubkey,
+    pub funder: Pubkey,
+    pub distribution_id: u64,
+    pub merkle_root: [u8; 32],
+    /// Tokens of `token_mint`, PDA'd by ["orchard-tokens", token_mint]
+    pub token_vault: Pubkey,
+    /// Tokens funded
+    pub amount: u64,
+    /// Tokens claimed so far, never above `amount`
+    pub claimed: u64,
+}
+
+/// A claimer's claims on 256 distributions of a (token, funder),
+/// PDA'd by ["claim-bitmap", token_mint, funder, claimer, word (LE u64)]
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimBitmap {
+    pub token_mint: Pubkey,
+    pub funder: Pubkey,
+    pub claimer: Pubkey,
+    /// Covers distributions `word · 256` to `word · 256 + 255`
+    pub word: u64,
+    pub bits: [u64; 4],
+}
+
+impl ClaimBitmap {
+    fn position(distribution_id: u64) -> (usize, u64) {
+        let bit = distribution_id % DISTRIBUTIONS_PER_WORD;
+        ((bit / 64) as usize, 1 << (bit % 64))
+    }
+
+    pub fn is_claimed(&self, distribution_id: u64) -> bool {
+        let (i, mask) = Self::position(distribution_id);
+        self.bits[i] & mask != 0
+    }
+
+    /// Mark a distribution claimed, failing if it already was.
+    pub fn set_claimed(&mut self, distribution_id: u64) -> Result<()> {
+        require!(!self.is_claimed(distribution_id), ErrorCode::AlreadyClaimed);
+        let (i, mask) = Self::position(distribution_id);
+        self.bits[i] |= mask;
+        Ok(())
+    }
+}
+
+/// One distribution to claim
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ClaimArgs {
+    pub distribution_id: u64,
+    /// The claimer's amount, as in the leaf
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+impl ClaimArgs {
+    /// remaining_accounts consumed per claim
+    pub const ACCOUNTS: usize = 4;
+}
+
+#[event]
+pub struct DistributionCreated {
+    pub token_mint: Pubkey,
+    pub funder: Pubkey,
+    pub distribution_id: u64,
+    pub merkle_root: [u8; 32],
+    pub amount: u64,
+}
+
+#[event]
+pub struct DistributionClaimed {
+    pub token_mint: Pubkey,
+    pub funder: Pubkey,
+    pub distribution_id: u64,
+    pub claimer: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Distribution ids are sequential per token and funder")]
+    InvalidDistributionId,
+    #[msg("Claim accounts do not match the claims")]
+    MalformedClaim,
+    #[msg("Merkle proof does not match the distribution's root")]
+    InvalidProof,
+    #[msg("Distribution already claimed")]
+    AlreadyClaimed,
+    #[msg("Claims exceed the distribution's funding")]
+    DistributionExhausted,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Root and per‑leaf proofs of a sorted‑pair tree; odd nodes move up as is.
+    fn tree(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let mut proofs = vec![Vec::new(); leaves.len()];
+        let mut level: Vec<[u8; 32]> = leaves.to_vec();
+        let mut owners: Vec<Vec<usize>> = (0..leaves.len()).map(|i| vec![i]).collect();
+        while level.len() > 1 {
+            let (mut next, mut next_owners) = (Vec::new(), Vec::new());
+            for (pair, who) in level.chunks(2).zip(owners.chunks(2)) {
+                if let [a, b] = pair {
+                    who[0].iter().for_each(|&i| proofs[i].push(*b));
+                    who[1].iter().for_each(|&i| proofs[i].push(*a));
+                    let (x, y) = if a <= b { (a, b) } else { (b, a) };
+                    next.push(hashv(&[x, y]).to_bytes());
+                    next_owners.push([who[0].clone(), who[1].clone()].concat());
+                } else {
+                    next.push(pair[0]);
+                    next_owners.push(who[0].clone());
+                }
+            }
+            (level, owners) = (next, next_owners);
+        }
+        (level[0], proofs)
+    }
+
+    #[test]
+    fn proofs_verify_only_their_own_leaf() {
+        let claimers: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = claimers.iter().enumerate().map(|(i, c)| leaf(c, 100 * (i as u64 + 1))).collect();
+        let (root, proofs) = tree(&leaves);
+
+        for (i, c) in claimers.iter().enumerate() {
+            assert!(verify_proof(&proofs[i], root, leaf(c, 100 * (i as u64 + 1))));
+            assert!(!verify_proof(&proofs[i], root, leaf(c, 100 * (i as u64 + 1) + 1)));
+        }
+        assert!(!verify_proof(&proofs[0], root, leaf(&claimers[1], 200)));
+        // a single leaf is its own root
+        assert!(verify_proof(&[], leaves[3], leaves[3]));
+    }
+
+    #[test]
+    fn bitmap_tracks_each_distribution_once() {
+        let mut bitmap = ClaimBitmap {
+            token_mint: Pubkey::default(),
+            funder: Pubkey::default(),
+            claimer: Pubkey::default(),
+            word: 1,
+            bits: [0; 4],
+        };
+        for id in [256, 319, 320, 511] {
+            bitmap.set_claimed(id).unwrap();
+        }
+        assert!(bitmap.set_claimed(320).is_err());
+        assert!(bitmap.is_claimed(511) && !bitmap.is_claimed(257));
+        assert_eq!(bitmap.bits, [1 << 63 | 1, 1, 0, 1 << 63]);
+    }
+}
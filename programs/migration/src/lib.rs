@@ -0,0 +1,237 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+
+use weighted_pool::program::WeightedPool;
+use weighted_pool::Pool;
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("Migr8hCT3cSzPMLevngjAeYMzJGw8NAJjPt9rVQvVJN");
+
+/// Liquidity migration: move an LP position from one weighted pool to
+/// another holding the same tokens – e.g. off a pool with a parameter bug
+/// or onto a new factory's pool – in one instruction. The position is
+/// exited proportionally and the largest part of the proceeds in the new
+/// pool's own proportions is joined, so no swap fee is charged for an
+/// imbalance; the few units left over stay with the LP. An empty new pool
+/// is seeded with all of the proceeds.
+#[program]
+pub mod migration {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Exit `bpt_in` from the old pool, join the new one
+       remaining_accounts: [old pool exit accounts…, new pool join accounts…]
+       i.e. the old pool's n × [user_tok, vault_tok], then the new pool's
+       n × [user_tok, vault_tok]. Returns the new BPT minted.
+    ---------------------------------------------------------------- */
+    pub fn migrate<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Migrate<'info>>,
+        bpt_in: u64,
+        min_bpt_out: u64,
+    ) -> Result<u64> {
+        require!(bpt_in > 0, ErrorCode::ZeroAmount);
+        let (old, new) = (&ctx.accounts.old_pool, &ctx.accounts.new_pool);
+        require_keys_neq!(old.pool.key(), new.pool.key(), ErrorCode::SamePool);
+        let n = old.pool.weights.len();
+        require!(new.pool.weights.len() == n, ErrorCode::TokenMismatch);
+        let n_exit = n * 2;
+        let rem = ctx.remaining_accounts;
+        require!(rem.len() == n_exit + n * 2, ErrorCode::InvalidRemainingAccounts);
+        let (exit_accounts, join_accounts) = rem.split_at(n_exit);
+
+        // 1. exit the old pool
+        let before = balances(exit_accounts, n)?;
+        weighted_pool::cpi::exit_exact_bpt_in_for_tokens_out(
+            old.context(&ctx.accounts.user, &ctx.accounts.token_program, exit_accounts),
+            bpt_in,
+            vec![0; n],
+        )?;
+        let after = balances(exit_accounts, n)?;
+
+        // 2. line the proceeds up with the new pool's tokens
+        let mut proceeds = vec![0u64; n];
+        let mut pool_balances = Vec::with_capacity(n);
+        for (i, proceed) in proceeds.iter_mut().enumerate() {
+            let user_acct  = token_account(&join_accounts[i * 2])?;
+            let vault_acct = token_account(&join_accounts[i * 2 + 1])?;
+            require_keys_eq!(user_acct.mint, vault_acct.mint, ErrorCode::TokenMismatch);
+            let j = (0..n)
+                .find(|&j| token_account(&exit_accounts[j * 2]).map(|a| a.mint) == Ok(user_acct.mint))
+                .ok_or(ErrorCode::TokenMismatch)?;
+            *proceed = after[j].saturating_sub(before[j]);
+            pool_balances.push(vault_acct.amount);
+        }
+        let amounts_in = if new.pool.total_bpt == 0 {
+            proceeds
+        } else {
+            proportional_amounts(&proceeds, &pool_balances)?
+        };
+
+        // 3. join the new pool
+        let lp_before = token_account(&new.user_lp_account.to_account_info())?.amount;
+        weighted_pool::cpi::join_exact_tokens_in_for_bpt_out(
+            new.context(&ctx.accounts.user, &ctx.accounts.token_program, join_accounts),
+            amounts_in,
+        )?;
+        let bpt_out = token_account(&new.user_lp_account.to_account_info())?.amount.saturating_sub(lp_before);
+        require!(bpt_out >= min_bpt_out, ErrorCode::SlippageExceeded);
+
+        emit!(Migrated {
+            user:     ctx.accounts.user.key(),
+            old_pool: old.pool.key(),
+            new_pool: new.pool.key(),
+            bpt_in,
+            bpt_out,
+        });
+        Ok(bpt_out)
+    }
+}
+
+/// The largest amounts, none above `amounts`, in the proportions of
+/// `balances`: every amount is scaled to the token whose amount is the
+/// smallest share of its balance.
+pub fn proportional_amounts(amounts: &[u64], balances: &[u64]) -> Result<Vec<u64>> {
+    require!(amounts.len() == balances.len(), ErrorCode::TokenMismatch);
+    require!(balances.iter().all(|b| *b > 0), ErrorCode::TokenMismatch);
+    // k minimizes amounts[k] / balances[k]
+    let k = (0..amounts.len())
+        .min_by(|&a, &b| {
+            (amounts[a] as u128 * balances[b] as u128).cmp(&(amounts[b] as u128 * balances[a] as u128))
+        })
+        .ok_or(ErrorCode::TokenMismatch)?;
+    Ok(balances
+        .iter()
+        .map(|b| (*b as u128 * amounts[k] as u128 / balances[k] as u128) as u64)
+        .collect())
+}
+
+fn balances(accounts: &[AccountInfo], n: usize) -> Result<Vec<u64>> {
+    (0..n).map(|i| Ok(token_account(&accounts[i * 2])?.amount)).collect()
+}
+
+/// Read an SPL token account passed as a remaining account.
+fn token_account(info: &AccountInfo) -> Result<TokenAccount> {
+    require_keys_eq!(*info.owner, token::ID, ErrorCode::InvalidRemainingAccounts);
+    TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+impl<'info> PoolAccounts<'info> {
+    fn context(&self, user: &Signer<'info>, token_program: &Program<'info, Token>, accounts: &[AccountInfo<'info>])
+        -> CpiContext<'_, '_, '_, 'info, weighted_pool::cpi::accounts::PoolContext<'info>> {
+        CpiContext::new(
+            self.weighted_pool_program.to_account_info(),
+            weighted_pool::cpi::accounts::PoolContext {
+                pool:                    self.pool.to_account_info(),
+                vault_state:             self.vault_state.clone(),
+                lp_mint:                 self.lp_mint.clone(),
+                lp_mint_authority:       self.lp_mint_authority.clone(),
+                user:                    user.to_account_info(),
+                user_lp_account:         self.user_lp_account.to_account_info(),
+                fees_collector:          self.fees_collector.to_account_info(),
+                protocol_fee_lp_account: self.protocol_fee_lp_account.clone(),
+                locked_bpt_account:      self.locked_bpt_account.clone(),
+                pool_registration:       self.pool_registration.clone(),
+                token_program:           token_program.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(accounts.to_vec())
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+   The weighted pool program validates the accounts passed through.
+------------------------------------------------------------------ */
+/// A weighted pool's join/exit accounts
+#[derive(Accounts)]
+pub struct PoolAccounts<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// CHECK: the pool's vault; checked by the pool
+    pub vault_state: AccountInfo<'info>,
+
+    /// CHECK: the pool's LP mint; checked by the pool
+    #[account(mut)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: the pool's mint authority PDA; checked by the pool
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_lp_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Box<Account<'info, vault::ProtocolFeesCollector>>,
+
+    /// CHECK: collector's LP token account; checked by the pool
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// CHECK: the pool's locked BPT account, for seeding an empty pool;
+    /// checked by the pool
+    #[account(mut)]
+    pub locked_bpt_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: the pool's Vault registration; checked by the pool
+    pub pool_registration: AccountInfo<'info>,
+
+    pub weighted_pool_program: Program<'info, WeightedPool>,
+}
+
+#[derive(Accounts)]
+pub struct Migrate<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub old_pool: PoolAccounts<'info>,
+    pub new_pool: PoolAccounts<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   Events & errors
+------------------------------------------------------------------ */
+#[event]
+pub struct Migrated {
+    pub user: Pubkey,
+    pub old_pool: Pubkey,
+    pub new_pool: Pubkey,
+    pub bpt_in: u64,
+    pub bpt_out: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Old and new pool are the same")]
+    SamePool,
+    #[msg("New pool does not hold the old pool's tokens")]
+    TokenMismatch,
+    #[msg("Remaining accounts do not match the pools")]
+    InvalidRemainingAccounts,
+    #[msg("Join minted less BPT than the minimum")]
+    SlippageExceeded,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_only_the_new_pools_proportions() {
+        // the scarcer share of its balance sets the scale
+        assert_eq!(proportional_amounts(&[500, 300], &[1_000, 1_000]).unwrap(), vec![300, 300]);
+        assert_eq!(proportional_amounts(&[500, 300], &[2_000, 1_000]).unwrap(), vec![500, 250]);
+        // already proportional: nothing is held back
+        assert_eq!(proportional_amounts(&[200, 100, 50], &[4_000, 2_000, 1_000]).unwrap(), vec![200, 100, 50]);
+    }
+}
@@ -0,0 +1,486 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, CreateAccount, Transfer as SystemTransfer};
+use anchor_spl::token::Token;
+use anchor_spl::token_2022::Token2022;
+use anchor_spl::token_interface::spl_token_2022::extension::ExtensionType;
+use anchor_spl::token_interface::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_interface::spl_token_2022::state::Mint as Mint2022;
+use anchor_spl::token_interface::{self as token, InitializeMint2, SetAuthority};
+use anchor_spl::token_2022_extensions::spl_token_metadata_interface::state::TokenMetadata;
+use anchor_spl::token_2022_extensions::{
+    metadata_pointer_initialize, token_metadata_initialize, MetadataPointerInitialize, TokenMetadataInitialize,
+};
+use common::{canonical_pool_hash, seeds, MAX_POOL_TOKENS};
+use math::fixed;
+use spl_token::state::{Account as SplAccount, Mint as SplMint};
+
+use vault::cpi::{accounts::RegisterTokens as VaultRegisterTokens, register_tokens as vault_register_tokens};
+use vault::program::Vault as VaultProgram;
+use vault::{PoolRegistration, PoolRegistryPage, VaultState};
+use weighted_pool::cpi::{
+    accounts::InitializeCanonicalPool as WeightedInitializeCanonicalPool,
+    initialize_canonical_pool as weighted_initialize_canonical_pool,
+};
+use weighted_pool::program::WeightedPool as WeightedPoolProgram;
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("PFacMxYKvd8HXwLxnUuSBqFYGfyuy1YZD4vYpfwBft5");
+
+/// Smallest weight of any token (1 %)
+pub const MIN_WEIGHT: u128 = 10_000_000_000_000_000;
+/// Lowest swap fee a factory pool may charge: any weighted pool's (0.0001 %)
+pub const MIN_SWAP_FEE: u64 = weighted_pool::MIN_SWAP_FEE;
+/// Highest swap fee a factory pool may charge: any weighted pool's (10 %)
+pub const MAX_SWAP_FEE: u64 = weighted_pool::MAX_SWAP_FEE;
+/// Decimals of the LP mints the factory creates
+pub const LP_DECIMALS: u8 = 9;
+
+/// Factory authority PDA: ["factory-authority", vault_state]
+pub const FACTORY_AUTHORITY_SEED: &[u8] = b"factory-authority";
+/// LP mint PDA: ["lp-mint", pool]
+pub const LP_MINT_SEED: &[u8] = b"lp-mint";
+/// Pool record PDA: ["pool-record", pool]
+pub const POOL_RECORD_SEED: &[u8] = b"pool-record";
+
+/// Pool factory: permissionless deployment of weighted pools. Each
+/// configuration (sorted mints, weights, swap fee) has exactly one pool
+/// address, so there are no duplicate or look‑alike pools for routers to
+/// choose between. The factory creates the LP mint, registers the pool
+/// and its custody accounts with the Vault, and records the creator.
+///
+/// The Vault admin grants the `RegisterPool` role to the factory authority
+/// PDA once per vault. That PDA fronts the Vault's rent and is reimbursed
+/// by the creator in the same instruction; it never keeps a balance.
+#[program]
+pub mod pool_factory {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create a weighted pool at its canonical address
+       remaining_accounts: [mint_0, custody_0, mint_1, custody_1, …]
+       with the mints in ascending order; custody accounts are the
+       Vault's ["pool-token", pool_registration, mint] PDAs
+       With `lp_token_metadata` the BPT is a Token‑2022 mint carrying its
+       own name, symbol and URI (MetadataPointer to itself plus
+       TokenMetadata, updated by the pool's mint authority) instead of a
+       classic SPL Token mint named through a Metaplex account.
+    ---------------------------------------------------------------- */
+    pub fn create_weighted_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateWeightedPool<'info>>,
+        weights: Vec<u128>,
+        swap_fee: u64,
+        lp_token_metadata: Option<LpTokenMetadata>,
+    ) -> Result<()> {
+        let rem = ctx.remaining_accounts;
+        let n = weights.len();
+        require!((2..=MAX_POOL_TOKENS).contains(&n), ErrorCode::InvalidTokenCount);
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        check_weights(&weights)?;
+        require!((MIN_SWAP_FEE..=MAX_SWAP_FEE).contains(&swap_fee), ErrorCode::InvalidSwapFee);
+
+        let mints: Vec<AccountInfo<'info>> = rem.iter().step_by(2).cloned().collect();
+        let tokens: Vec<Pubkey> = mints.iter().map(|m| m.key()).collect();
+        let config_hash = canonical_pool_hash(&tokens, &weights, swap_fee).ok_or(ErrorCode::UnsortedTokens)?;
+
+        // 1. front the Vault's rent: registration, a new registry page if
+        //    the tail one does not exist yet, and one custody account per token
+        let rent = Rent::get()?;
+        let mut vault_rent = rent.minimum_balance(8 + PoolRegistration::LEN)
+            + rent.minimum_balance(SplAccount::LEN) * n as u64;
+        if ctx.accounts.registry_page.data_is_empty() {
+            vault_rent += rent.minimum_balance(8 + PoolRegistryPage::LEN);
+        }
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                SystemTransfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to:   ctx.accounts.factory_authority.to_account_info(),
+                },
+            ),
+            vault_rent,
+        )?;
+
+        let vault_key = ctx.accounts.vault_state.key();
+        let bump_arr = [ctx.bumps.factory_authority];
+        let authority_seeds: &[&[u8]] = &[FACTORY_AUTHORITY_SEED, vault_key.as_ref(), &bump_arr];
+        let signer_seeds = &[authority_seeds];
+        ctx.accounts.create_lp_mint(ctx.bumps.lp_mint, authority_seeds, lp_token_metadata)?;
+
+        // 2. create the pool state and register it with the Vault
+        let init_accounts = WeightedInitializeCanonicalPool {
+            payer:             ctx.accounts.creator.to_account_info(),
+            authority:         ctx.accounts.factory_authority.to_account_info(),
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            vault_program:     ctx.accounts.vault_program.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            lp_mint_authority: ctx.accounts.lp_mint_authority.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        weighted_initialize_canonical_pool(
+            CpiContext::new_with_signer(
+                ctx.accounts.weighted_pool_program.to_account_info(),
+                init_accounts,
+                signer_seeds,
+            )
+            .with_remaining_accounts(mints),
+            config_hash,
+            weights,
+            swap_fee,
+        )?;
+
+        // 3. create its custody accounts
+        let register_accounts = VaultRegisterTokens {
+            vault_state:              ctx.accounts.vault_state.to_account_info(),
+            authorizer:               ctx.accounts.authorizer.to_account_info(),
+            pool_registration:        ctx.accounts.pool_registration.to_account_info(),
+            vault_authority:          ctx.accounts.vault_authority.to_account_info(),
+            transfer_hook_allowlist:  None,
+            mint_extension_overrides: None,
+            authority:                ctx.accounts.factory_authority.to_account_info(),
+            token_program:            ctx.accounts.token_program.to_account_info(),
+            system_program:           ctx.accounts.system_program.to_account_info(),
+        };
+        vault_register_tokens(
+            CpiContext::new_with_signer(
+                ctx.accounts.vault_program.to_account_info(),
+                register_accounts,
+                signer_seeds,
+            )
+            .with_remaining_accounts(rem.to_vec()),
+            vec![Pubkey::default(); n],
+        )?;
+
+        // 4. hand back whatever the Vault did not spend
+        let leftover = ctx.accounts.factory_authority.lamports();
+        if leftover > 0 {
+            system_program::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    SystemTransfer {
+                        from: ctx.accounts.factory_authority.to_account_info(),
+                        to:   ctx.accounts.creator.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                leftover,
+            )?;
+        }
+
+        // 5. record the deployment
+        let record = &mut ctx.accounts.pool_record;
+        record.vault       = vault_key;
+        record.pool        = ctx.accounts.pool.key();
+        record.lp_mint     = ctx.accounts.lp_mint.key();
+        record.creator     = ctx.accounts.creator.key();
+        record.config_hash = config_hash;
+        record.tokens      = tokens.clone();
+        record.created_at  = Clock::get()?.unix_timestamp;
+
+        emit!(PoolCreated {
+            vault: vault_key,
+            pool: record.pool,
+            lp_mint: record.lp_mint,
+            creator: record.creator,
+            tokens,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> CreateWeightedPool<'info> {
+    /// Create the LP mint at its PDA, minted by the pool's
+    /// `lp-mint-authority`: under SPL Token, or under Token‑2022 with the
+    /// metadata on the mint itself. Token‑2022 only lets the mint
+    /// authority initialize the metadata, so the factory authority holds
+    /// that role until the metadata is written.
+    fn create_lp_mint(&self, bump: u8, authority_seeds: &[&[u8]], metadata: Option<LpTokenMetadata>) -> Result<()> {
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let mint_seeds: &[&[u8]] = &[LP_MINT_SEED, pool_key.as_ref(), &bump_arr];
+        let Some(metadata) = metadata else {
+            self.create_mint_account(&self.token_program.to_account_info(), SplMint::LEN, SplMint::LEN, mint_seeds)?;
+            return token::initialize_mint2(
+                CpiContext::new(
+                    self.token_program.to_account_info(),
+                    InitializeMint2 { mint: self.lp_mint.to_account_info() },
+                ),
+                LP_DECIMALS,
+                self.lp_mint_authority.key,
+                None,
+            );
+        };
+
+        let token_2022 = self.token_2022_program.as_ref().ok_or(ErrorCode::MissingToken2022Program)?.to_account_info();
+        let space = ExtensionType::try_calculate_account_len::<Mint2022>(&[ExtensionType::MetadataPointer])?;
+        // the metadata is appended by the token program: fund it up front
+        let state = TokenMetadata {
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            ..Default::default()
+        };
+        let funded = space + state.tlv_size_of()?;
+        self.create_mint_account(&token_2022, space, funded, mint_seeds)?;
+        metadata_pointer_initialize(
+            CpiContext::new(
+                token_2022.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: token_2022.clone(),
+                    mint:             self.lp_mint.to_account_info(),
+                },
+            ),
+            Some(self.lp_mint_authority.key()),
+            Some(self.lp_mint.key()),
+        )?;
+        token::initialize_mint2(
+            CpiContext::new(token_2022.clone(), InitializeMint2 { mint: self.lp_mint.to_account_info() }),
+            LP_DECIMALS,
+            self.factory_authority.key,
+            None,
+        )?;
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                token_2022.clone(),
+                TokenMetadataInitialize {
+                    program_id:       token_2022.clone(),
+                    metadata:         self.lp_mint.to_account_info(),
+                    update_authority: self.lp_mint_authority.to_account_info(),
+                    mint_authority:   self.factory_authority.to_account_info(),
+                    mint:             self.lp_mint.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        )?;
+        token::set_authority(
+            CpiContext::new_with_signer(
+                token_2022,
+                SetAuthority {
+                    current_authority: self.factory_authority.to_account_info(),
+                    account_or_mint:   self.lp_mint.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            AuthorityType::MintTokens,
+            Some(self.lp_mint_authority.key()),
+        )
+    }
+
+    /// Allocate `space` bytes for the LP mint under `owner`, holding rent
+    /// for `funded` bytes; paid by the creator.
+    fn create_mint_account(&self, owner: &AccountInfo<'info>, space: usize, funded: usize, mint_seeds: &[&[u8]]) -> Result<()> {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.creator.to_account_info(),
+                    to:   self.lp_mint.to_account_info(),
+                },
+                &[mint_seeds],
+            ),
+            Rent::get()?.minimum_balance(funded),
+            space as u64,
+            owner.key,
+        )
+    }
+}
+
+/// Canonical weighted pool address of a configuration, or `None` when the
+/// tokens are not in ascending order.
+pub fn canonical_pool_address(vault: &Pubkey, tokens: &[Pubkey], weights: &[u128], swap_fee: u64) -> Option<Pubkey> {
+    let config_hash = canonical_pool_hash(tokens, weights, swap_fee)?;
+    let (pool, _) = Pubkey::find_program_address(
+        &[seeds::POOL_STATE, vault.as_ref(), config_hash.as_ref()],
+        &weighted_pool::ID,
+    );
+    Some(pool)
+}
+
+/// Address of the factory's record for `pool`; the pool came from the
+/// factory exactly when this account exists.
+pub fn pool_record_address(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[POOL_RECORD_SEED, pool.as_ref()], &ID).0
+}
+
+/// Weights must each be at least `MIN_WEIGHT` and sum to exactly one.
+fn check_weights(weights: &[u128]) -> Result<()> {
+    let total = weights
+        .iter()
+        .try_fold(0u128, |acc, w| acc.checked_add(*w))
+        .ok_or(ErrorCode::InvalidWeights)?;
+    require!(
+        weights.iter().all(|w| *w >= MIN_WEIGHT) && total == fixed::ONE.as_u128(),
+        ErrorCode::InvalidWeights
+    );
+    Ok(())
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct CreateWeightedPool<'info> {
+    /// Pays for the pool, its LP mint and record, and reimburses the Vault's rent
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// Holds the Vault's `RegisterPool` role; fronts the Vault's rent within the instruction
+    #[account(
+        mut,
+        seeds = [FACTORY_AUTHORITY_SEED, vault_state.key().as_ref()],
+        bump
+    )]
+    pub factory_authority: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    /// CHECK: the Vault's token authority PDA; seeds checked there
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: canonical pool state PDA, created by the weighted pool program; seeds checked there
+    #[account(mut)]
+    pub pool: AccountInfo<'info>,
+
+    /// CHECK: the pool's LP mint PDA, created here under SPL Token or
+    /// Token‑2022 and minted by the pool's `lp-mint-authority` PDA
+    #[account(
+        mut,
+        seeds = [LP_MINT_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint: UncheckedAccount<'info>,
+
+    /// CHECK: the pool's mint authority under the weighted pool program
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump,
+        seeds::program = weighted_pool::ID
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PoolRecord::INIT_SPACE,
+        seeds = [POOL_RECORD_SEED, pool.key().as_ref()],
+        bump
+    )]
+    pub pool_record: Account<'info, PoolRecord>,
+
+    pub weighted_pool_program: Program<'info, WeightedPoolProgram>,
+    pub vault_program: Program<'info, VaultProgram>,
+    /// Owns the pool tokens, and the LP mint unless it carries metadata
+    pub token_program: Program<'info, Token>,
+    /// Owns an LP mint created with `lp_token_metadata`
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+    pub system_program: Program<'info, System>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// One pool deployed by the factory
+#[account]
+pub struct PoolRecord {
+    pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub creator: Pubkey,
+    /// `common::canonical_pool_hash` of the pool's configuration
+    pub config_hash: [u8; 32],
+    /// Pool tokens in ascending order
+    pub tokens: Vec<Pubkey>,
+    pub created_at: i64,
+}
+impl PoolRecord {
+    pub const INIT_SPACE: usize = 32 * 4 + 32 + (4 + MAX_POOL_TOKENS * 32) + 8;
+}
+
+/// Name, symbol and URI a Token‑2022 BPT carries on its mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LpTokenMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolCreated {
+    pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub creator: Pubkey,
+    pub tokens: Vec<Pubkey>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Vector length mismatch")]
+    LengthMismatch,
+    #[msg("Pools hold between two and MAX_POOL_TOKENS tokens")]
+    InvalidTokenCount,
+    #[msg("Token mints must be distinct and in ascending order")]
+    UnsortedTokens,
+    #[msg("Weights are out of range or do not sum to one")]
+    InvalidWeights,
+    #[msg("Swap fee is out of range")]
+    InvalidSwapFee,
+    #[msg("A BPT with metadata needs the Token-2022 program")]
+    MissingToken2022Program,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn weights_must_be_normalized_and_above_the_minimum() {
+        assert!(check_weights(&[8 * E18 / 10, 2 * E18 / 10]).is_ok());
+        assert!(check_weights(&[E18 / 3, E18 / 3, E18 - 2 * (E18 / 3)]).is_ok());
+        assert!(check_weights(&[E18 / 2, E18 / 2 - 1]).is_err());
+        assert!(check_weights(&[E18 - MIN_WEIGHT + 1, MIN_WEIGHT - 1]).is_err());
+        assert!(check_weights(&[u128::MAX, 2]).is_err());
+    }
+
+    #[test]
+    fn each_configuration_has_one_address() {
+        let vault = Pubkey::new_unique();
+        let mut tokens = [Pubkey::new_unique(), Pubkey::new_unique()];
+        tokens.sort();
+        let weights = [E18 / 2, E18 / 2];
+        let fee = 3_000_000_000_000_000;
+
+        let pool = canonical_pool_address(&vault, &tokens, &weights, fee).unwrap();
+        assert_eq!(Some(pool), canonical_pool_address(&vault, &tokens, &weights, fee));
+        assert_ne!(Some(pool), canonical_pool_address(&vault, &tokens, &weights, fee + 1));
+        assert_ne!(Some(pool), canonical_pool_address(&Pubkey::new_unique(), &tokens, &weights, fee));
+        // the same pair in the other order is not a second pool
+        tokens.reverse();
+        assert_eq!(canonical_pool_address(&vault, &tokens, &weights, fee), None);
+    }
+}
@@ -0,0 +1,352 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+use anchor_spl::token_2022::spl_token_2022::extension::interest_bearing_mint::InterestBearingConfig;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::{self, state::Mint as SplMint};
+use math::log_exp_math::try_exp;
+use math::{fixed, I256};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("RateProv24E5vi8q2B8sdb4kdyZ6frTkf4c75y6c8Hh");
+
+/// Rate source PDA: ["rate-source", source]
+pub const RATE_SOURCE_SEED: &[u8] = b"rate-source";
+
+/// The SPL stake pool program (jitoSOL and most other LSTs)
+pub const SPL_STAKE_POOL_PROGRAM_ID: Pubkey = pubkey!("SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy");
+
+/// Token‑2022's year for interest‑bearing mints: 365.24 days
+pub const SECONDS_PER_YEAR: i128 = 31_556_736;
+
+/// Rate provider adapter: serves the standard `get_rate` interface of
+/// `common::rate_provider` – an 18‑dec u128 in return data – for tokens
+/// whose value grows against an underlying, so boosted and linear pools
+/// price them at that value. Each `RateSource` caches one token's rate,
+/// read from:
+///   • `InterestBearingMint` – the Token‑2022 mint's own interest
+///     config, compounded continuously since its initialisation;
+///   • `StakePool` – an SPL stake pool's lamports per pool token, only
+///     once the pool is updated for the current epoch;
+///   • `Pushed` – a rate its authority pushes (e.g. mSOL, whose state
+///     is read off‑chain by a keeper).
+/// `refresh_rate` re‑reads the source, and `get_rate` refuses to serve a
+/// cached rate older than the source's `max_age`, so a pool can never
+/// price with a rate that stopped updating.
+#[program]
+pub mod rate_provider {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create the rate source of `source` and read its first rate
+       `max_age`: seconds a cached rate is served for
+    ---------------------------------------------------------------- */
+    pub fn create_rate_source(ctx: Context<CreateRateSource>, kind: SourceKind, max_age: i64, initial_rate: u128) -> Result<()> {
+        require!(max_age > 0, ErrorCode::InvalidMaxAge);
+        let now = Clock::get()?.unix_timestamp;
+        let rate = match kind {
+            SourceKind::Pushed => initial_rate,
+            _ => read_rate(kind, &ctx.accounts.source)?,
+        };
+        require!(rate > 0, ErrorCode::InvalidRate);
+        let rate_source = &mut ctx.accounts.rate_source;
+        rate_source.source     = ctx.accounts.source.key();
+        rate_source.kind       = kind;
+        rate_source.authority  = ctx.accounts.authority.key();
+        rate_source.max_age    = max_age;
+        rate_source.rate       = rate;
+        rate_source.updated_at = now;
+        rate_source.bump       = ctx.bumps.rate_source;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Re‑read the rate from its source – anyone may call
+    ---------------------------------------------------------------- */
+    pub fn refresh_rate(ctx: Context<RefreshRate>) -> Result<()> {
+        let rate_source = &mut ctx.accounts.rate_source;
+        require!(rate_source.kind != SourceKind::Pushed, ErrorCode::WrongSourceKind);
+        rate_source.rate       = read_rate(rate_source.kind, &ctx.accounts.source)?;
+        rate_source.updated_at = Clock::get()?.unix_timestamp;
+        emit!(RateUpdated { rate_source: rate_source.key(), rate: rate_source.rate });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Push a rate – `Pushed` sources, authority only
+    ---------------------------------------------------------------- */
+    pub fn push_rate(ctx: Context<SetRateSource>, rate: u128) -> Result<()> {
+        let rate_source = &mut ctx.accounts.rate_source;
+        require!(rate_source.kind == SourceKind::Pushed, ErrorCode::WrongSourceKind);
+        require!(rate > 0, ErrorCode::InvalidRate);
+        rate_source.rate       = rate;
+        rate_source.updated_at = Clock::get()?.unix_timestamp;
+        emit!(RateUpdated { rate_source: rate_source.key(), rate });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Change how long a cached rate is served for – authority only
+    ---------------------------------------------------------------- */
+    pub fn set_max_age(ctx: Context<SetRateSource>, max_age: i64) -> Result<()> {
+        require!(max_age > 0, ErrorCode::InvalidMaxAge);
+        ctx.accounts.rate_source.max_age = max_age;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       The standard rate interface: the cached rate as 16 bytes LE in
+       return data, failing once it is older than `max_age`
+    ---------------------------------------------------------------- */
+    pub fn get_rate(ctx: Context<GetRate>) -> Result<()> {
+        let rate_source = &ctx.accounts.rate_account;
+        require!(rate_source.is_fresh(Clock::get()?.unix_timestamp), ErrorCode::StaleRate);
+        set_return_data(&rate_source.rate.to_le_bytes());
+        Ok(())
+    }
+}
+
+/// The current rate of `source`, read as `kind` says.
+fn read_rate(kind: SourceKind, source: &AccountInfo) -> Result<u128> {
+    match kind {
+        SourceKind::InterestBearingMint => {
+            require_keys_eq!(*source.owner, spl_token_2022::ID, ErrorCode::InvalidSource);
+            let data = source.try_borrow_data()?;
+            let mint = StateWithExtensions::<SplMint>::unpack(&data).map_err(|_| ErrorCode::InvalidSource)?;
+            let config = mint.get_extension::<InterestBearingConfig>().map_err(|_| ErrorCode::InvalidSource)?;
+            interest_bearing_rate(config, Clock::get()?.unix_timestamp)
+        }
+        SourceKind::StakePool => {
+            require_keys_eq!(*source.owner, SPL_STAKE_POOL_PROGRAM_ID, ErrorCode::InvalidSource);
+            let pool = StakePoolTotals::unpack(&source.try_borrow_data()?)?;
+            require!(pool.last_update_epoch == Clock::get()?.epoch, ErrorCode::StaleSource);
+            pool.rate()
+        }
+        SourceKind::Pushed => err!(ErrorCode::WrongSourceKind),
+    }
+}
+
+/// Value of one raw unit of an interest‑bearing mint in raw units
+/// without interest at `now`, 18‑dec: e^(average rate · years since
+/// initialisation), as Token‑2022 itself scales UI amounts.
+pub fn interest_bearing_rate(config: &InterestBearingConfig, now: i64) -> Result<u128> {
+    let average = config.time_weighted_average_rate(now).ok_or(ErrorCode::InvalidSource)?;
+    let elapsed = now
+        .checked_sub(i64::from(config.initialization_timestamp))
+        .ok_or(ErrorCode::InvalidSource)?;
+    let exponent = (average as i128)
+        .checked_mul(elapsed as i128)
+        .and_then(|e| e.checked_mul(fixed::ONE.as_u128() as i128))
+        .ok_or(ErrorCode::MathOverflow)?
+        / (SECONDS_PER_YEAR * 10_000);
+    let rate = try_exp(I256::from(exponent)).map_err(|_| ErrorCode::MathOverflow)?;
+    Ok(rate.to_i128().ok_or(ErrorCode::MathOverflow)? as u128)
+}
+
+/// The totals of an SPL stake pool account the rate needs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StakePoolTotals {
+    pub total_lamports: u64,
+    pub pool_token_supply: u64,
+    pub last_update_epoch: u64,
+}
+
+impl StakePoolTotals {
+    /// `AccountType::StakePool`
+    const ACCOUNT_TYPE: u8 = 1;
+    /// Offset of `total_lamports`: account type, three authorities, a
+    /// bump seed and five more addresses before it
+    const TOTALS_OFFSET: usize = 1 + 3 * 32 + 1 + 5 * 32;
+
+    pub fn unpack(data: &[u8]) -> Result<Self> {
+        require!(data.first() == Some(&Self::ACCOUNT_TYPE), ErrorCode::InvalidSource);
+        let field = |i: usize| -> Result<u64> {
+            let at = Self::TOTALS_OFFSET + 8 * i;
+            let bytes = data.get(at..at + 8).ok_or(ErrorCode::InvalidSource)?;
+            Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+        };
+        Ok(StakePoolTotals { total_lamports: field(0)?, pool_token_supply: field(1)?, last_update_epoch: field(2)? })
+    }
+
+    /// Lamports per pool token, 18‑dec; one for an empty pool.
+    pub fn rate(&self) -> Result<u128> {
+        if self.pool_token_supply == 0 {
+            return Ok(fixed::ONE.as_u128());
+        }
+        let rate = self.total_lamports as u128 * fixed::ONE.as_u128() / self.pool_token_supply as u128;
+        require!(rate > 0, ErrorCode::InvalidRate);
+        Ok(rate)
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct CreateRateSource<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + RateSource::LEN,
+        seeds = [RATE_SOURCE_SEED, source.key().as_ref()],
+        bump
+    )]
+    pub rate_source: Account<'info, RateSource>,
+
+    /// CHECK: the account the rate is read from – for a `Pushed` source,
+    /// the token it is the rate of; checked by `read_rate`
+    pub source: AccountInfo<'info>,
+
+    /// May push rates and change `max_age`
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshRate<'info> {
+    #[account(mut)]
+    pub rate_source: Account<'info, RateSource>,
+
+    /// CHECK: the rate source's source, read by `read_rate`
+    #[account(address = rate_source.source @ ErrorCode::InvalidSource)]
+    pub source: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRateSource<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub rate_source: Account<'info, RateSource>,
+
+    pub authority: Signer<'info>,
+}
+
+/// The one read‑only account of `common::rate_provider::get_rate`.
+#[derive(Accounts)]
+pub struct GetRate<'info> {
+    pub rate_account: Account<'info, RateSource>,
+}
+
+/* ------------------------------------------------------------------
+   State
+------------------------------------------------------------------ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SourceKind {
+    InterestBearingMint,
+    StakePool,
+    Pushed,
+}
+
+#[account]
+pub struct RateSource {
+    /// The mint or stake pool the rate is read from
+    pub source: Pubkey,
+    pub kind: SourceKind,
+    /// May push rates and change `max_age`
+    pub authority: Pubkey,
+    /// Seconds a cached rate is served for
+    pub max_age: i64,
+    /// Cached rate (18‑dec)
+    pub rate: u128,
+    /// Unix time `rate` was read or pushed
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl RateSource {
+    pub const LEN: usize = 32 + 1 + 32 + 8 + 16 + 8 + 1;
+
+    /// Whether `get_rate` may serve the cached rate at `now`.
+    pub fn is_fresh(&self, now: i64) -> bool {
+        now.saturating_sub(self.updated_at) <= self.max_age
+    }
+}
+
+#[event]
+pub struct RateUpdated {
+    pub rate_source: Pubkey,
+    pub rate: u128,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Source account is not of the rate source's kind")]
+    InvalidSource,
+    #[msg("Instruction does not apply to this kind of rate source")]
+    WrongSourceKind,
+    #[msg("Rate must be positive")]
+    InvalidRate,
+    #[msg("Max age must be positive")]
+    InvalidMaxAge,
+    #[msg("Cached rate is older than the source's max age")]
+    StaleRate,
+    #[msg("Stake pool is not updated for the current epoch")]
+    StaleSource,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::extension::interest_bearing_mint::BasisPoints;
+
+    const ONE: u128 = 1_000_000_000_000_000_000;
+
+    fn config(rate_bps: i16) -> InterestBearingConfig {
+        InterestBearingConfig {
+            initialization_timestamp: 1_000.into(),
+            pre_update_average_rate: BasisPoints::from(rate_bps),
+            last_update_timestamp: 1_000.into(),
+            current_rate: BasisPoints::from(rate_bps),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn interest_compounds_continuously() {
+        let year = SECONDS_PER_YEAR as i64;
+        assert_eq!(interest_bearing_rate(&config(500), 1_000).unwrap(), ONE);
+        // 5% for a year: e^0.05
+        let rate = interest_bearing_rate(&config(500), 1_000 + year).unwrap();
+        assert!(rate.abs_diff(1_051_271_096_376_024_040) < 1_000, "{rate}");
+        // a negative rate shrinks the value
+        assert!(interest_bearing_rate(&config(-500), 1_000 + year).unwrap() < ONE);
+    }
+
+    #[test]
+    fn stake_pool_rate_is_lamports_per_token() {
+        let mut data = vec![0u8; StakePoolTotals::TOTALS_OFFSET + 24];
+        data[0] = 1;
+        let totals = StakePoolTotals { total_lamports: 1_100, pool_token_supply: 1_000, last_update_epoch: 7 };
+        for (i, v) in [totals.total_lamports, totals.pool_token_supply, totals.last_update_epoch].iter().enumerate() {
+            let at = StakePoolTotals::TOTALS_OFFSET + 8 * i;
+            data[at..at + 8].copy_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(StakePoolTotals::unpack(&data).unwrap(), totals);
+        assert_eq!(totals.rate().unwrap(), 1_100_000_000_000_000_000);
+        data[0] = 2;
+        assert!(StakePoolTotals::unpack(&data).is_err());
+    }
+
+    #[test]
+    fn stale_rates_are_not_served() {
+        let source = RateSource {
+            source: Pubkey::new_unique(),
+            kind: SourceKind::Pushed,
+            authority: Pubkey::new_unique(),
+            max_age: 60,
+            rate: ONE,
+            updated_at: 1_000,
+            bump: 0,
+        };
+        assert!(source.is_fresh(1_060));
+        assert!(!source.is_fresh(1_061));
+    }
+}
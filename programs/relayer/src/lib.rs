@@ -0,0 +1,494 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Approve, Revoke, Token, TokenAccount};
+
+use gauge::program::Gauge;
+use vault::program::Vault;
+use vault::BatchSwapStep;
+use weighted_pool::program::WeightedPool;
+use weighted_pool::Pool;
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("Re1ayVEM1KCoxKmnMxmyw3iSKsEfFo4QXhmgSuL8juk");
+
+/// Relayer: compound user operations – swap then join, join then stake,
+/// unstake then exit then swap – in one instruction signed by the user
+/// alone. The user's signature carries into the weighted pool and gauge
+/// CPIs; swaps go through the Vault's `batch_swap` with this program's
+/// ["relayer-authority"] PDA as the sender, so the user must first approve
+/// that PDA with `vault::set_relayer_approval`. For the duration of the
+/// swap the relayer makes the vault authority SPL delegate of the user's
+/// input accounts for their positive limits, and revokes it afterwards.
+///
+/// Amounts chain between the legs: a join adds what the swap paid out of
+/// each pool token, and a step‑0 amount of 0 swaps everything the exit
+/// paid out of that token. Slippage is bounded by the swap limits and by
+/// `min_bpt_out` on joins.
+#[program]
+pub mod relayer {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Swap, then join a weighted pool with the proceeds
+       remaining_accounts: [batch swap accounts…, join accounts…]
+       with the join's n × [user_tok, vault_tok] pairs last.
+       The join pays in `amounts_in[i]` plus whatever the swap paid
+       out of pool token i. Returns the BPT minted.
+    ---------------------------------------------------------------- */
+    pub fn swap_and_join<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SwapAndJoin<'info>>,
+        steps: Vec<BatchSwapStep>,
+        limits: Vec<i64>,
+        deadline: i64,
+        amounts_in: Vec<u64>,
+        min_bpt_out: u64,
+    ) -> Result<u64> {
+        let rem = ctx.remaining_accounts;
+        let n_join = ctx.accounts.pool.pool.weights.len() * 2;
+        require!(rem.len() >= limits.len() * 2 + n_join, ErrorCode::InvalidRemainingAccounts);
+        let (swap_accounts, join_accounts) = rem.split_at(rem.len() - n_join);
+
+        let deltas = ctx.accounts.swap.batch_swap(
+            &ctx.accounts.user,
+            &ctx.accounts.relayer_authority,
+            ctx.bumps.relayer_authority,
+            &ctx.accounts.pool,
+            &ctx.accounts.token_program,
+            swap_accounts,
+            steps,
+            limits,
+            deadline,
+        )?;
+
+        // add the swap's proceeds of each pool token to the join
+        let mut amounts_in = amounts_in;
+        require!(amounts_in.len() * 2 == n_join, ErrorCode::InvalidRemainingAccounts);
+        for (i, amount) in amounts_in.iter_mut().enumerate() {
+            let mint = token_account(&join_accounts[i * 2])?.mint;
+            for (a, delta) in deltas.iter().enumerate() {
+                if *delta < 0 && token_account(&swap_accounts[a * 2])?.mint == mint {
+                    *amount = amount.checked_add(delta.unsigned_abs()).ok_or(ErrorCode::MathOverflow)?;
+                }
+            }
+        }
+
+        let bpt_out = ctx.accounts.pool.join(&ctx.accounts.user, &ctx.accounts.token_program, join_accounts, amounts_in)?;
+        require!(bpt_out >= min_bpt_out, ErrorCode::SlippageExceeded);
+        Ok(bpt_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Join a weighted pool, then stake the BPT in its gauge
+       remaining_accounts: the join's n × [user_tok, vault_tok]
+       Returns the BPT staked.
+    ---------------------------------------------------------------- */
+    pub fn join_and_stake<'info>(
+        ctx: Context<'_, '_, 'info, 'info, JoinAndStake<'info>>,
+        amounts_in: Vec<u64>,
+        min_bpt_out: u64,
+    ) -> Result<u64> {
+        let bpt_out = ctx.accounts.pool.join(
+            &ctx.accounts.user,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            amounts_in,
+        )?;
+        require!(bpt_out >= min_bpt_out, ErrorCode::SlippageExceeded);
+        ctx.accounts.gauge.deposit(
+            &ctx.accounts.user,
+            &ctx.accounts.pool.user_lp_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            bpt_out,
+        )?;
+        Ok(bpt_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Unstake BPT, exit the pool with it, then swap the proceeds
+       remaining_accounts: [exit accounts…, batch swap accounts…]
+       with the exit's n × [user_tok, vault_tok] pairs first. A step‑0
+       amount of 0 swaps all the exit paid out of its input token.
+       Returns the swap's net deltas.
+    ---------------------------------------------------------------- */
+    pub fn unstake_exit_and_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UnstakeExitAndSwap<'info>>,
+        bpt_in: u64,
+        steps: Vec<BatchSwapStep>,
+        limits: Vec<i64>,
+        deadline: i64,
+    ) -> Result<Vec<i64>> {
+        require!(bpt_in > 0, ErrorCode::ZeroAmount);
+        let rem = ctx.remaining_accounts;
+        let n = ctx.accounts.pool.pool.weights.len();
+        let n_exit = n * 2;
+        require!(rem.len() >= n_exit, ErrorCode::InvalidRemainingAccounts);
+        let (exit_accounts, swap_accounts) = rem.split_at(n_exit);
+
+        ctx.accounts.gauge.withdraw(
+            &ctx.accounts.user,
+            &ctx.accounts.pool.user_lp_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.system_program,
+            bpt_in,
+        )?;
+        let amounts_out = ctx.accounts.pool.exit(&ctx.accounts.user, &ctx.accounts.token_program, exit_accounts, bpt_in)?;
+
+        let mut steps = steps;
+        if let Some(first) = steps.first_mut().filter(|s| s.amount == 0) {
+            let user_in = swap_accounts
+                .get(first.asset_in_index as usize * 2)
+                .ok_or(ErrorCode::InvalidRemainingAccounts)?;
+            let mint = token_account(user_in)?.mint;
+            for (i, amount) in amounts_out.iter().enumerate() {
+                if token_account(&exit_accounts[i * 2])?.mint == mint {
+                    first.amount = *amount;
+                }
+            }
+            require!(first.amount > 0, ErrorCode::ZeroAmount);
+        }
+
+        ctx.accounts.swap.batch_swap(
+            &ctx.accounts.user,
+            &ctx.accounts.relayer_authority,
+            ctx.bumps.relayer_authority,
+            &ctx.accounts.pool,
+            &ctx.accounts.token_program,
+            swap_accounts,
+            steps,
+            limits,
+            deadline,
+        )
+    }
+}
+
+/* ------------------------------------------------------------------
+   CPI legs
+------------------------------------------------------------------ */
+impl<'info> VaultSwap<'info> {
+    /// Relay a batch swap for `user`, `relayer_authority` as the sender.
+    #[allow(clippy::too_many_arguments)]
+    fn batch_swap(
+        &self,
+        user: &Signer<'info>,
+        relayer_authority: &AccountInfo<'info>,
+        relayer_bump: u8,
+        pool: &PoolAccounts<'info>,
+        token_program: &Program<'info, Token>,
+        accounts: &[AccountInfo<'info>],
+        steps: Vec<BatchSwapStep>,
+        limits: Vec<i64>,
+        deadline: i64,
+    ) -> Result<Vec<i64>> {
+        require!(accounts.len() >= limits.len() * 2, ErrorCode::InvalidRemainingAccounts);
+
+        // let the vault pull at most each positive limit
+        let pulled: Vec<(usize, u64)> = limits
+            .iter()
+            .enumerate()
+            .filter(|(_, limit)| **limit > 0)
+            .map(|(a, limit)| (a * 2, *limit as u64))
+            .collect();
+        for &(at, limit) in &pulled {
+            token::approve(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    Approve {
+                        to:        accounts[at].clone(),
+                        delegate:  self.vault_authority.clone(),
+                        authority: user.to_account_info(),
+                    },
+                ),
+                limit,
+            )?;
+        }
+
+        let bump_arr = [relayer_bump];
+        let seed_slice: &[&[u8]] = &[b"relayer-authority", &bump_arr];
+        let deltas = vault::cpi::batch_swap(
+            CpiContext::new_with_signer(
+                self.vault_program.to_account_info(),
+                vault::cpi::accounts::BatchSwap {
+                    vault_state:      pool.vault_state.clone(),
+                    vault_authority:  self.vault_authority.clone(),
+                    user:             user.to_account_info(),
+                    sender:           relayer_authority.clone(),
+                    relayer_approval: Some(self.relayer_approval.clone()),
+                    permit_nonce:     None,
+                    instructions:     None,
+                    system_program:   None,
+                    fees_collector:   pool.fees_collector.to_account_info(),
+                    token_program:    token_program.to_account_info(),
+                },
+                &[seed_slice],
+            )
+            .with_remaining_accounts(accounts.to_vec()),
+            steps,
+            limits,
+            deadline,
+        )?
+        .get();
+
+        for &(at, _) in &pulled {
+            token::revoke(CpiContext::new(
+                token_program.to_account_info(),
+                Revoke { source: accounts[at].clone(), authority: user.to_account_info() },
+            ))?;
+        }
+        Ok(deltas)
+    }
+}
+
+impl<'info> PoolAccounts<'info> {
+    fn context(&self, user: &Signer<'info>, token_program: &Program<'info, Token>, accounts: &[AccountInfo<'info>])
+        -> CpiContext<'_, '_, '_, 'info, weighted_pool::cpi::accounts::PoolContext<'info>> {
+        CpiContext::new(
+            self.weighted_pool_program.to_account_info(),
+            weighted_pool::cpi::accounts::PoolContext {
+                pool:                    self.pool.to_account_info(),
+                vault_state:             self.vault_state.clone(),
+                lp_mint:                 self.lp_mint.clone(),
+                lp_mint_authority:       self.lp_mint_authority.clone(),
+                user:                    user.to_account_info(),
+                user_lp_account:         self.user_lp_account.to_account_info(),
+                fees_collector:          self.fees_collector.to_account_info(),
+                protocol_fee_lp_account: self.protocol_fee_lp_account.clone(),
+                locked_bpt_account:      self.locked_bpt_account.clone(),
+                pool_registration:       self.pool_registration.clone(),
+                token_program:           token_program.to_account_info(),
+            },
+        )
+        .with_remaining_accounts(accounts.to_vec())
+    }
+
+    /// Join for `user`; returns the BPT it minted to them.
+    fn join(
+        &self,
+        user: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        accounts: &[AccountInfo<'info>],
+        amounts_in: Vec<u64>,
+    ) -> Result<u64> {
+        let before = token_account(&self.user_lp_account.to_account_info())?.amount;
+        weighted_pool::cpi::join_exact_tokens_in_for_bpt_out(self.context(user, token_program, accounts), amounts_in)?;
+        let after = token_account(&self.user_lp_account.to_account_info())?.amount;
+        Ok(after.saturating_sub(before))
+    }
+
+    /// Exit for `user`; returns what each pool token paid out to them.
+    fn exit(
+        &self,
+        user: &Signer<'info>,
+        token_program: &Program<'info, Token>,
+        accounts: &[AccountInfo<'info>],
+        bpt_in: u64,
+    ) -> Result<Vec<u64>> {
+        let n = self.pool.weights.len();
+        let balances = |accounts: &[AccountInfo<'info>]| -> Result<Vec<u64>> {
+            (0..n).map(|i| Ok(token_account(&accounts[i * 2])?.amount)).collect()
+        };
+        let before = balances(accounts)?;
+        weighted_pool::cpi::exit_exact_bpt_in_for_tokens_out(self.context(user, token_program, accounts), bpt_in, vec![0; n])?;
+        let after = balances(accounts)?;
+        Ok(after.iter().zip(before).map(|(a, b)| a.saturating_sub(b)).collect())
+    }
+}
+
+impl<'info> GaugeAccounts<'info> {
+    fn context(
+        &self,
+        user: &Signer<'info>,
+        user_lp_account: &Account<'info, TokenAccount>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+    ) -> CpiContext<'_, '_, '_, 'info, gauge::cpi::accounts::Stake<'info>> {
+        CpiContext::new(
+            self.gauge_program.to_account_info(),
+            gauge::cpi::accounts::Stake {
+                gauge:           self.gauge.clone(),
+                user_stake:      self.user_stake.clone(),
+                user:            user.to_account_info(),
+                user_lp_account: user_lp_account.to_account_info(),
+                stake_vault:     self.stake_vault.clone(),
+                gauge_authority: self.gauge_authority.clone(),
+                escrow:          self.escrow.clone(),
+                user_lock:       self.user_lock.clone(),
+                token_program:   token_program.to_account_info(),
+                system_program:  system_program.to_account_info(),
+            },
+        )
+    }
+
+    fn deposit(
+        &self,
+        user: &Signer<'info>,
+        user_lp_account: &Account<'info, TokenAccount>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        amount: u64,
+    ) -> Result<()> {
+        gauge::cpi::deposit(self.context(user, user_lp_account, token_program, system_program), amount)
+    }
+
+    fn withdraw(
+        &self,
+        user: &Signer<'info>,
+        user_lp_account: &Account<'info, TokenAccount>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        amount: u64,
+    ) -> Result<()> {
+        gauge::cpi::withdraw(self.context(user, user_lp_account, token_program, system_program), amount)
+    }
+}
+
+/// Read an SPL token account passed as a remaining account.
+fn token_account(info: &AccountInfo) -> Result<TokenAccount> {
+    require_keys_eq!(*info.owner, token::ID, ErrorCode::InvalidRemainingAccounts);
+    TokenAccount::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+   The target programs validate the accounts passed through to them.
+------------------------------------------------------------------ */
+/// Vault accounts of a relayed batch swap
+#[derive(Accounts)]
+pub struct VaultSwap<'info> {
+    /// CHECK: vault authority PDA; checked by the vault
+    #[account(mut)]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: the user's approval of `relayer_authority`; checked by the vault
+    pub relayer_approval: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, Vault>,
+}
+
+/// A weighted pool's join/exit accounts
+#[derive(Accounts)]
+pub struct PoolAccounts<'info> {
+    #[account(mut)]
+    pub pool: Box<Account<'info, Pool>>,
+
+    /// CHECK: the pool's vault; checked by the pool
+    pub vault_state: AccountInfo<'info>,
+
+    /// CHECK: the pool's LP mint; checked by the pool
+    #[account(mut)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: the pool's mint authority PDA; checked by the pool
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_lp_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Box<Account<'info, vault::ProtocolFeesCollector>>,
+
+    /// CHECK: collector's LP token account; checked by the pool
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// CHECK: the pool's locked BPT account, for seeding an empty pool;
+    /// checked by the pool
+    #[account(mut)]
+    pub locked_bpt_account: Option<AccountInfo<'info>>,
+
+    /// CHECK: the pool's Vault registration; checked by the pool
+    pub pool_registration: AccountInfo<'info>,
+
+    pub weighted_pool_program: Program<'info, WeightedPool>,
+}
+
+/// A gauge's staking accounts
+#[derive(Accounts)]
+pub struct GaugeAccounts<'info> {
+    /// CHECK: checked by the gauge
+    #[account(mut)]
+    pub gauge: AccountInfo<'info>,
+
+    /// CHECK: the user's stake; checked (and created) by the gauge
+    #[account(mut)]
+    pub user_stake: AccountInfo<'info>,
+
+    /// CHECK: checked by the gauge
+    #[account(mut)]
+    pub stake_vault: AccountInfo<'info>,
+
+    /// CHECK: checked by the gauge
+    pub gauge_authority: AccountInfo<'info>,
+
+    /// CHECK: the gauge's vote escrow, once it has one; checked by the gauge
+    pub escrow: Option<AccountInfo<'info>>,
+
+    /// CHECK: the user's lock in `escrow`; checked by the gauge
+    pub user_lock: Option<AccountInfo<'info>>,
+
+    pub gauge_program: Program<'info, Gauge>,
+}
+
+#[derive(Accounts)]
+pub struct SwapAndJoin<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: PDA the user approves as their Vault relayer
+    #[account(mut, seeds = [b"relayer-authority"], bump)]
+    pub relayer_authority: AccountInfo<'info>,
+
+    pub swap: VaultSwap<'info>,
+    pub pool: PoolAccounts<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct JoinAndStake<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub pool: PoolAccounts<'info>,
+    pub gauge: GaugeAccounts<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UnstakeExitAndSwap<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: PDA the user approves as their Vault relayer
+    #[account(mut, seeds = [b"relayer-authority"], bump)]
+    pub relayer_authority: AccountInfo<'info>,
+
+    pub gauge: GaugeAccounts<'info>,
+    pub pool: PoolAccounts<'info>,
+    pub swap: VaultSwap<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+/* ------------------------------------------------------------------
+   Errors
+------------------------------------------------------------------ */
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Remaining accounts do not match the operation")]
+    InvalidRemainingAccounts,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Join minted less BPT than the minimum")]
+    SlippageExceeded,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
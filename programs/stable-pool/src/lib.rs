@@ -0,0 +1,1695 @@
+#![allow(clippy::needless_range_loop)]
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Burn, MintTo, Token, Transfer};
+use anchor_lang::solana_program::program::{get_return_data, invoke};
+use common::pool_interface::SwapQuote;
+use common::pool_tokens::{self, TokenAccount};
+use common::{derive_pool_id, rate_provider, Specialization};
+use math::stable_math::{self, AMP_PRECISION, MAX_AMP, MIN_AMP};
+use math::{fees, fixed, MathError, U256};
+
+// Vault CPI interfaces and the program struct itself
+use vault::cpi::accounts::{RegisterPool as VaultRegisterPool, RegisterTokens as VaultRegisterTokens};
+use vault::cpi::{register_pool as vault_register_pool, register_tokens as vault_register_tokens};
+use vault::program::Vault as VaultProgram;
+use vault::{Action, Authorizer, PoolRegistration, ProtocolFeesCollector, VaultState};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("SPoo1vozJoLnep3wRM5knYu4nFWMX6ubrreoFnQTses");
+
+/// Fewest tokens a stable pool holds
+pub const MIN_TOKENS: usize = 2;
+/// Most tokens a stable pool holds; every swap solves the invariant over all of them
+pub const MAX_TOKENS: usize = 5;
+/// BPT a composable pool mints into its own custody at initialisation: its
+/// whole supply
+pub const PREMINTED_BPT: u64 = u64::MAX;
+
+/// StableSwap pools of 2–5 like‑priced tokens (Balancer `ComposableStablePool`
+/// maths). Every price depends on every balance, so the pool registers with
+/// the Vault as `General` and each swap is shown all custody accounts. Raw
+/// token amounts are scaled to 18 decimals with each mint's decimals; BPT
+/// with the LP mint's.
+///
+/// A token may have a rate provider (see `common::rate_provider`), making
+/// the pool "boosted": its balances and amounts are multiplied by the
+/// token's rate before any maths. Rates are cached on the pool by
+/// `update_token_rates`, which must run earlier in the same slot as any
+/// instruction that prices with them.
+///
+/// A composable pool also lists its own BPT as its last token, with the
+/// whole supply pre‑minted into its custody. Buying or selling BPT is then a
+/// plain swap (priced as a single token join or exit against the virtual
+/// supply), and joins and exits pay BPT out of and back into custody
+/// instead of minting and burning.
+#[program]
+pub mod stable_pool {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Initialise a pool
+       remaining_accounts: [mint_0, …, mint_{N-1}], 2 ≤ N ≤ 5
+       A composable pool also needs `vault_authority` and `pool_bpt`,
+       and the payer's `RegisterPool` role then creates its BPT custody.
+       `rate_providers` is empty or holds one rate account per mint
+       (`Pubkey::default()` for tokens without one).
+    ---------------------------------------------------------------- */
+    pub fn initialize_pool(
+        ctx: Context<InitializePool>,
+        amplification: u64,
+        swap_fee: u64,
+        composable: bool,
+        rate_providers: Vec<Pubkey>,
+    ) -> Result<()> {
+        let n = ctx.remaining_accounts.len();
+        require!((MIN_TOKENS..=MAX_TOKENS).contains(&n), ErrorCode::InvalidTokenCount);
+        require!((MIN_AMP..=MAX_AMP).contains(&amplification), ErrorCode::InvalidAmplification);
+        let mut rate_providers = if rate_providers.is_empty() { vec![Pubkey::default(); n] } else { rate_providers };
+        require!(rate_providers.len() == n, ErrorCode::LengthMismatch);
+
+        let mut tokens   = Vec::with_capacity(n);
+        let mut decimals = Vec::with_capacity(n);
+        for ai in ctx.remaining_accounts.iter() {
+            tokens.push(ai.key());
+            decimals.push(mint_decimals(ai)?);
+        }
+        let lp_decimals = mint_decimals(&ctx.accounts.lp_mint)?;
+        // a composable pool's own BPT comes last
+        let bpt_index = if composable {
+            tokens.push(ctx.accounts.lp_mint.key());
+            decimals.push(lp_decimals);
+            rate_providers.push(Pubkey::default());
+            Some(n as u8)
+        } else {
+            None
+        };
+
+        let pool = &mut ctx.accounts.pool;
+        pool.vault       = ctx.accounts.vault_state.key();
+        pool.lp_mint     = ctx.accounts.lp_mint.key();
+        pool.tokens      = tokens.clone();
+        pool.decimals    = decimals;
+        pool.lp_decimals = lp_decimals;
+        pool.amp         = amplification * AMP_PRECISION;
+        pool.swap_fee    = swap_fee;
+        pool.total_bpt   = 0;
+        pool.bpt_index   = bpt_index;
+        pool.rates       = vec![fixed::ONE.as_u128(); rate_providers.len()];
+        pool.rate_providers     = rate_providers;
+        pool.rates_updated_slot = 0;
+
+        // register with the Vault: the invariant needs every balance
+        let specialization = Specialization::General;
+        let pool_id = derive_pool_id(
+            &ctx.accounts.vault_state.key(),
+            &ctx.accounts.pool.key(),
+            specialization,
+            ctx.accounts.vault_state.pool_count,
+        );
+        let cpi_accounts = VaultRegisterPool {
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.payer.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        let registered = vault_register_pool(
+            CpiContext::new(ctx.accounts.vault_program.to_account_info(), cpi_accounts),
+            pool_id,
+            specialization,
+            tokens,
+        )?;
+        ctx.accounts.pool.pool_id = registered.get();
+
+        if composable {
+            ctx.accounts.premint_bpt(ctx.bumps.lp_mint_authority)?;
+        }
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Set swap fee – requires the `SetSwapFee` role in the Authorizer
+    ---------------------------------------------------------------- */
+    pub fn set_swap_fee(ctx: Context<SetSwapFee>, swap_fee: u64) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::SetSwapFee, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.pool.swap_fee = swap_fee;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Refresh the cached token rates – anyone may call
+       remaining_accounts: [rate_account, provider_program] for each
+       token with a rate provider, in pool order
+    ---------------------------------------------------------------- */
+    pub fn update_token_rates<'info>(ctx: Context<'_, '_, '_, 'info, UpdateTokenRates<'info>>) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        let mut rem = ctx.remaining_accounts.chunks(2);
+        for i in 0..pool.tokens.len() {
+            if pool.rate_providers[i] == Pubkey::default() {
+                continue;
+            }
+            let (rate_account, provider) = match rem.next() {
+                Some([rate_account, provider]) => (rate_account, provider),
+                _ => return err!(ErrorCode::LengthMismatch),
+            };
+            require_keys_eq!(rate_account.key(), pool.rate_providers[i], ErrorCode::InvalidRateProvider);
+            require_keys_eq!(provider.key(), *rate_account.owner, ErrorCode::InvalidRateProvider);
+            invoke(
+                &rate_provider::get_rate(provider.key(), rate_account.key()),
+                &[rate_account.clone(), provider.clone()],
+            )?;
+            pool.rates[i] = match get_return_data() {
+                Some((program_id, data)) if program_id == provider.key() => rate_provider::parse_rate(&data),
+                _ => None,
+            }
+            .ok_or(ErrorCode::InvalidRateProvider)?;
+        }
+        require!(rem.next().is_none(), ErrorCode::LengthMismatch);
+        pool.rates_updated_slot = Clock::get()?.slot;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Join – exact tokens in, BPT out
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       The first join seeds the pool with every token and mints the
+       invariant as BPT. Later joins pay the swap fee on the part of each
+       amount above the proportional share; the protocol's share of that
+       fee is minted as BPT to the collector.
+    ---------------------------------------------------------------- */
+    pub fn join_exact_tokens_in_for_bpt_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        amounts_in: Vec<u64>,
+        minimum_bpt_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.check_live()?;
+        let supply = ctx.accounts.bpt_supply()?;
+        let pool   = &ctx.accounts.pool;
+        let n      = pool.token_count();
+        let rem    = ctx.remaining_accounts;
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(amounts_in.len() == n, ErrorCode::LengthMismatch);
+
+        // 1. balances
+        let balances = read_balances(pool, Some(&ctx.accounts.pool_registration), rem.iter().skip(1).step_by(2), n)?;
+        let mut amounts_fp = Vec::with_capacity(n);
+        for i in 0..n {
+            amounts_fp.push(pool.upscale(i, amounts_in[i])?);
+        }
+
+        // 2. maths
+        let amp = U256::from(pool.amp);
+        let (bpt_out, protocol_bpt) = if supply == 0 {
+            require!(amounts_in.iter().all(|a| *a > 0), ErrorCode::InvalidInitialJoin);
+            let invariant = stable_math::try_calculate_invariant(amp, &amounts_fp).map_err(ErrorCode::from)?;
+            (scale_down(invariant, pool.lp_decimals)?, 0)
+        } else {
+            let invariant    = stable_math::try_calculate_invariant(amp, &balances).map_err(ErrorCode::from)?;
+            let total_bpt_fp = scale_up(supply, pool.lp_decimals)?;
+            let bpt_out_fp = stable_math::try_calc_bpt_out_given_exact_tokens_in(
+                amp,
+                &balances,
+                &amounts_fp,
+                total_bpt_fp,
+                invariant,
+                U256::from(pool.swap_fee),
+            ).map_err(ErrorCode::from)?;
+            let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+            let protocol_bpt = if protocol_pct > 0 {
+                let bpt_no_fee_fp = stable_math::try_calc_bpt_out_given_exact_tokens_in(
+                    amp,
+                    &balances,
+                    &amounts_fp,
+                    total_bpt_fp,
+                    invariant,
+                    U256::zero(),
+                ).map_err(ErrorCode::from)?;
+                let fee_bpt_fp = bpt_no_fee_fp.saturating_sub(bpt_out_fp);
+                scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+            } else {
+                0
+            };
+            (scale_down(bpt_out_fp, pool.lp_decimals)?, protocol_bpt)
+        };
+        require!(bpt_out > 0, ErrorCode::MathUnderflow);
+        require!(bpt_out >= minimum_bpt_out, ErrorCode::SlippageLimit);
+
+        // 3. user → vault, then mint
+        for i in 0..n {
+            ctx.accounts.pay_in(&rem[i * 2], &rem[i * 2 + 1], amounts_in[i])?;
+        }
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.mint_bpt(bump, &ctx.accounts.user_lp_account, bpt_out)?;
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        ctx.accounts.joined(amounts_in, bpt_out, protocol_bpt)
+    }
+
+    /* ---------------------------------------------------------------
+       Join – one token in, exact BPT out
+       remaining_accounts: [user_tok, vault_tok0, vault_tok1, …]
+    ---------------------------------------------------------------- */
+    pub fn join_token_in_for_exact_bpt_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        token_index: u8,
+        bpt_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<()> {
+        ctx.accounts.check_live()?;
+        let supply = ctx.accounts.bpt_supply()?;
+        let pool   = &ctx.accounts.pool;
+        let n      = pool.token_count();
+        let i      = token_index as usize;
+        let rem    = ctx.remaining_accounts;
+        require!(rem.len() == n + 1, ErrorCode::LengthMismatch);
+        require!(i < n, ErrorCode::InvalidSwapTokens);
+        require!(supply > 0, ErrorCode::InvalidInitialJoin);
+        require!(bpt_out > 0, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, Some(&ctx.accounts.pool_registration), &rem[1..], n)?;
+
+        // 2. maths
+        let amp          = U256::from(pool.amp);
+        let invariant    = stable_math::try_calculate_invariant(amp, &balances).map_err(ErrorCode::from)?;
+        let total_bpt_fp = scale_up(supply, pool.lp_decimals)?;
+        let bpt_out_fp   = scale_up(bpt_out, pool.lp_decimals)?;
+        let amount_in_fp = stable_math::try_calc_token_in_given_exact_bpt_out(
+            amp,
+            &balances,
+            i,
+            bpt_out_fp,
+            total_bpt_fp,
+            invariant,
+            U256::from(pool.swap_fee),
+        ).map_err(ErrorCode::from)?;
+        let amount_in = pool.downscale_up(i, amount_in_fp)?;
+        require!(amount_in <= maximum_amount_in, ErrorCode::SlippageLimit);
+
+        // the fee is the BPT the amount in would have minted without it
+        let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+        let protocol_bpt = if protocol_pct > 0 {
+            let bpt_no_fee_fp = stable_math::try_calc_bpt_out_given_exact_tokens_in(
+                amp,
+                &balances,
+                &one_hot(n, i, amount_in_fp),
+                total_bpt_fp,
+                invariant,
+                U256::zero(),
+            ).map_err(ErrorCode::from)?;
+            let fee_bpt_fp = bpt_no_fee_fp.saturating_sub(bpt_out_fp);
+            scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+        } else {
+            0
+        };
+
+        // 3. user → vault, then mint
+        ctx.accounts.pay_in(&rem[0], &rem[1 + i], amount_in)?;
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.mint_bpt(bump, &ctx.accounts.user_lp_account, bpt_out)?;
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        let mut amounts_in = vec![0; n];
+        amounts_in[i] = amount_in;
+        ctx.accounts.joined(amounts_in, bpt_out, protocol_bpt)
+    }
+
+    /* ---------------------------------------------------------------
+       Exit – exact BPT in, proportional tokens out (no fee)
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       Needs no token rates: they cancel out of a proportional share.
+    ---------------------------------------------------------------- */
+    pub fn exit_exact_bpt_in_for_tokens_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        bpt_in: u64,
+        minimum_amounts_out: Vec<u64>,
+    ) -> Result<()> {
+        let supply = ctx.accounts.bpt_supply()?;
+        let pool   = &ctx.accounts.pool;
+        let n      = pool.token_count();
+        let rem    = ctx.remaining_accounts;
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(minimum_amounts_out.len() == n, ErrorCode::LengthMismatch);
+        require!(bpt_in > 0 && bpt_in <= supply, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, Some(&ctx.accounts.pool_registration), rem.iter().skip(1).step_by(2), n)?;
+
+        // 2. maths: every balance shrinks by bpt_in / supply
+        let ratio = fixed::try_div_down(
+            scale_up(bpt_in, pool.lp_decimals)?,
+            scale_up(supply, pool.lp_decimals)?,
+        ).map_err(ErrorCode::from)?;
+        let mut amounts_out = Vec::with_capacity(n);
+        for i in 0..n {
+            let out_fp = fixed::try_mul_down(balances[i], ratio).map_err(ErrorCode::from)?;
+            let amount_out = pool.downscale_down(i, out_fp)?;
+            require!(amount_out >= minimum_amounts_out[i], ErrorCode::SlippageLimit);
+            amounts_out.push(amount_out);
+        }
+
+        // 3. burn, then vault → user
+        ctx.accounts.burn_bpt(bpt_in)?;
+        let bump = ctx.bumps.lp_mint_authority;
+        for i in 0..n {
+            ctx.accounts.pay_out(bump, &rem[i * 2 + 1], &rem[i * 2], amounts_out[i])?;
+        }
+
+        // 4. bookkeeping
+        ctx.accounts.exited(bpt_in, amounts_out, 0)
+    }
+
+    /* ---------------------------------------------------------------
+       Exit – exact BPT in, one token out
+       remaining_accounts: [user_tok, vault_tok0, vault_tok1, …]
+    ---------------------------------------------------------------- */
+    pub fn exit_exact_bpt_in_for_one_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        token_index: u8,
+        bpt_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.check_live()?;
+        let supply = ctx.accounts.bpt_supply()?;
+        let pool   = &ctx.accounts.pool;
+        let n      = pool.token_count();
+        let i      = token_index as usize;
+        let rem    = ctx.remaining_accounts;
+        require!(rem.len() == n + 1, ErrorCode::LengthMismatch);
+        require!(i < n, ErrorCode::InvalidSwapTokens);
+        require!(bpt_in > 0 && bpt_in <= supply, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, Some(&ctx.accounts.pool_registration), &rem[1..], n)?;
+
+        // 2. maths
+        let amp           = U256::from(pool.amp);
+        let invariant     = stable_math::try_calculate_invariant(amp, &balances).map_err(ErrorCode::from)?;
+        let total_bpt_fp  = scale_up(supply, pool.lp_decimals)?;
+        let bpt_in_fp     = scale_up(bpt_in, pool.lp_decimals)?;
+        let amount_out_fp = stable_math::try_calc_token_out_given_exact_bpt_in(
+            amp,
+            &balances,
+            i,
+            bpt_in_fp,
+            total_bpt_fp,
+            invariant,
+            U256::from(pool.swap_fee),
+        ).map_err(ErrorCode::from)?;
+        let amount_out = pool.downscale_down(i, amount_out_fp)?;
+        require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+
+        // the fee is the BPT burned beyond what the amount out costs without it
+        let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+        let protocol_bpt = if protocol_pct > 0 {
+            let bpt_no_fee_fp = stable_math::try_calc_bpt_in_given_exact_tokens_out(
+                amp,
+                &balances,
+                &one_hot(n, i, amount_out_fp),
+                total_bpt_fp,
+                invariant,
+                U256::zero(),
+            ).map_err(ErrorCode::from)?;
+            let fee_bpt_fp = bpt_in_fp.saturating_sub(bpt_no_fee_fp);
+            scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+        } else {
+            0
+        };
+
+        // 3. burn, then vault → user
+        ctx.accounts.burn_bpt(bpt_in)?;
+        let bump = ctx.bumps.lp_mint_authority;
+        ctx.accounts.pay_out(bump, &rem[1 + i], &rem[0], amount_out)?;
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        let mut amounts_out = vec![0; n];
+        amounts_out[i] = amount_out;
+        ctx.accounts.exited(bpt_in, amounts_out, protocol_bpt)
+    }
+
+    /* ---------------------------------------------------------------
+       Exit – BPT in, exact tokens out
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+    ---------------------------------------------------------------- */
+    pub fn exit_bpt_in_for_exact_tokens_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        amounts_out: Vec<u64>,
+        maximum_bpt_in: u64,
+    ) -> Result<()> {
+        ctx.accounts.check_live()?;
+        let supply = ctx.accounts.bpt_supply()?;
+        let pool   = &ctx.accounts.pool;
+        let n      = pool.token_count();
+        let rem    = ctx.remaining_accounts;
+        require!(rem.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(amounts_out.len() == n, ErrorCode::LengthMismatch);
+        require!(supply > 0, ErrorCode::MathUnderflow);
+
+        // 1. balances
+        let balances = read_balances(pool, Some(&ctx.accounts.pool_registration), rem.iter().skip(1).step_by(2), n)?;
+        let mut amounts_fp = Vec::with_capacity(n);
+        for i in 0..n {
+            amounts_fp.push(pool.upscale(i, amounts_out[i])?);
+        }
+
+        // 2. maths
+        let amp          = U256::from(pool.amp);
+        let invariant    = stable_math::try_calculate_invariant(amp, &balances).map_err(ErrorCode::from)?;
+        let total_bpt_fp = scale_up(supply, pool.lp_decimals)?;
+        let bpt_in_fp = stable_math::try_calc_bpt_in_given_exact_tokens_out(
+            amp,
+            &balances,
+            &amounts_fp,
+            total_bpt_fp,
+            invariant,
+            U256::from(pool.swap_fee),
+        ).map_err(ErrorCode::from)?;
+        let bpt_in = scale_down_up(bpt_in_fp, pool.lp_decimals)?;
+        require!(bpt_in > 0 && bpt_in <= supply, ErrorCode::MathUnderflow);
+        require!(bpt_in <= maximum_bpt_in, ErrorCode::SlippageLimit);
+
+        let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+        let protocol_bpt = if protocol_pct > 0 {
+            let bpt_no_fee_fp = stable_math::try_calc_bpt_in_given_exact_tokens_out(
+                amp,
+                &balances,
+                &amounts_fp,
+                total_bpt_fp,
+                invariant,
+                U256::zero(),
+            ).map_err(ErrorCode::from)?;
+            let fee_bpt_fp = bpt_in_fp.saturating_sub(bpt_no_fee_fp);
+            scale_down(protocol_share(fee_bpt_fp, protocol_pct)?, pool.lp_decimals)?
+        } else {
+            0
+        };
+
+        // 3. burn, then vault → user
+        ctx.accounts.burn_bpt(bpt_in)?;
+        let bump = ctx.bumps.lp_mint_authority;
+        for i in 0..n {
+            if amounts_out[i] > 0 {
+                ctx.accounts.pay_out(bump, &rem[i * 2 + 1], &rem[i * 2], amounts_out[i])?;
+            }
+        }
+        ctx.accounts.mint_protocol_bpt(bump, protocol_bpt)?;
+
+        // 4. bookkeeping
+        ctx.accounts.exited(bpt_in, amounts_out, protocol_bpt)
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – exact in → out between any two of the pool's tokens
+       remaining_accounts: [vault_tok0, vault_tok1, …], as the Vault's
+       batch swap passes them to General pools. The protocol's share of
+       the fee is paid straight to the collector. In a composable pool
+       either token may be the pool's own BPT.
+       Returns the amount sent out (read by the Vault's batch swap).
+    ---------------------------------------------------------------- */
+    pub fn swap_exact_token_in_for_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<u64> {
+        ctx.accounts.check_live()?;
+        let custody  = ctx.remaining_accounts;
+        let balances = read_balances(&ctx.accounts.pool, Some(&ctx.accounts.pool_registration), custody, ctx.accounts.pool.tokens.len())?;
+        let (index_in, index_out) = swap_indices(custody, &ctx.accounts.vault_in, &ctx.accounts.vault_out)?;
+
+        let SwapQuote { amount_out, protocol_fee } = quote_exact_in(
+            &ctx.accounts.pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            &balances,
+            index_in,
+            index_out,
+            amount_in,
+        )?;
+        require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+
+        ctx.accounts.settle(ctx.bumps.lp_mint_authority, &balances, index_in, index_out, amount_in, protocol_fee, amount_out)?;
+        Ok(amount_out)
+    }
+
+    /* ---------------------------------------------------------------
+       Swap – in → exact out
+       remaining_accounts: [vault_tok0, vault_tok1, …]
+       Returns the amount taken in, fee included.
+    ---------------------------------------------------------------- */
+    pub fn swap_token_in_for_exact_token_out<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_out: u64,
+        maximum_amount_in: u64,
+    ) -> Result<u64> {
+        ctx.accounts.check_live()?;
+        let custody  = ctx.remaining_accounts;
+        let balances = read_balances(&ctx.accounts.pool, Some(&ctx.accounts.pool_registration), custody, ctx.accounts.pool.tokens.len())?;
+        let (index_in, index_out) = swap_indices(custody, &ctx.accounts.vault_in, &ctx.accounts.vault_out)?;
+
+        let (amount_in, protocol_fee) = quote_exact_out(
+            &ctx.accounts.pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            &balances,
+            index_in,
+            index_out,
+            amount_out,
+        )?;
+        require!(amount_in <= maximum_amount_in, ErrorCode::SlippageLimit);
+
+        ctx.accounts.settle(ctx.bumps.lp_mint_authority, &balances, index_in, index_out, amount_in, protocol_fee, amount_out)?;
+        Ok(amount_in)
+    }
+
+    /* ---------------------------------------------------------------
+       Managed transfer – move idle cash to an asset manager
+       Only the Vault may ask (its asset manager authority PDA must
+       sign); it checks the manager and tracks the managed balance.
+    ---------------------------------------------------------------- */
+    pub fn managed_transfer(ctx: Context<ManagedTransfer>, amount: u64) -> Result<()> {
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.from.clone(),
+            to:        ctx.accounts.to.clone(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Query – price an exact‑in swap against caller‑supplied balances
+       remaining_accounts: [vault_tok0, vault_tok1, …]
+       Read‑only; used by the Vault's query_batch_swap. The balances of
+       the tokens swapped are the arguments; the others are read from
+       their custody accounts (cash only). Prices at the cached rates.
+    ---------------------------------------------------------------- */
+    pub fn query_swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, QuerySwap<'info>>,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Result<SwapQuote> {
+        let pool     = &ctx.accounts.pool;
+        let custody  = ctx.remaining_accounts;
+        let mut balances = read_balances(pool, None, custody, pool.tokens.len())?;
+        let (index_in, index_out) =
+            swap_indices(custody, &ctx.accounts.pool_token_in, &ctx.accounts.pool_token_out)?;
+        balances[index_in]  = pool.upscale(index_in, balance_in)?;
+        balances[index_out] = pool.upscale(index_out, balance_out)?;
+        quote_exact_in(
+            pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            &balances,
+            index_in,
+            index_out,
+            amount_in,
+        )
+    }
+}
+
+/// Exact‑in swap pricing shared by the swap and its query: amount out and
+/// the protocol's part of the amount in.
+fn quote_exact_in(
+    pool: &StablePool,
+    protocol_pct: u64,
+    balances: &[U256],
+    index_in: usize,
+    index_out: usize,
+    amount_in: u64,
+) -> Result<SwapQuote> {
+    let fee          = U256::from(pool.swap_fee);
+    let amount_in_fp = pool.upscale(index_in, amount_in)?;
+    let out_fp       = calc_out_given_in(pool, balances, index_in, index_out, amount_in_fp, fee)?;
+    let protocol_fee = if protocol_pct > 0 {
+        let fee_amount_fp = swap_fee_fp(pool, balances, index_in, index_out, amount_in_fp, out_fp)?;
+        pool.downscale_down(index_in, protocol_share(fee_amount_fp, protocol_pct)?)?
+    } else {
+        0
+    };
+    Ok(SwapQuote {
+        amount_out: pool.downscale_down(index_out, out_fp)?,
+        protocol_fee,
+    })
+}
+
+/// Exact‑out swap pricing: the amount in, fee included and rounded up, and
+/// the protocol's part of it.
+fn quote_exact_out(
+    pool: &StablePool,
+    protocol_pct: u64,
+    balances: &[U256],
+    index_in: usize,
+    index_out: usize,
+    amount_out: u64,
+) -> Result<(u64, u64)> {
+    let fee           = U256::from(pool.swap_fee);
+    let amount_out_fp = pool.upscale(index_out, amount_out)?;
+    let in_fp         = calc_in_given_out(pool, balances, index_in, index_out, amount_out_fp, fee)?;
+    let protocol_fee = if protocol_pct > 0 {
+        let fee_amount_fp = swap_fee_fp(pool, balances, index_in, index_out, in_fp, amount_out_fp)?;
+        pool.downscale_down(index_in, protocol_share(fee_amount_fp, protocol_pct)?)?
+    } else {
+        0
+    };
+    Ok((pool.downscale_up(index_in, in_fp)?, protocol_fee))
+}
+
+/// Swap fee (18‑dec) of a swap of `amount_in_fp` for `amount_out_fp`.
+/// Joins and exits charge only their unbalanced part: the fee is whatever
+/// the amount in exceeds the fee‑free price of the amount out.
+fn swap_fee_fp(
+    pool: &StablePool,
+    balances: &[U256],
+    index_in: usize,
+    index_out: usize,
+    amount_in_fp: U256,
+    amount_out_fp: U256,
+) -> Result<U256> {
+    if pool.is_bpt_swap(index_in, index_out) {
+        let without_fee = calc_in_given_out(pool, balances, index_in, index_out, amount_out_fp, U256::zero())?;
+        Ok(amount_in_fp.saturating_sub(without_fee))
+    } else {
+        Ok(fees::try_fee_amount(amount_in_fp, U256::from(pool.swap_fee)).map_err(ErrorCode::from)?)
+    }
+}
+
+/// Marginal price (18‑dec, fee included) of token `index_out` in token
+/// `index_in` on `balances`, among the tokens other than the pool's own
+/// BPT; `None` for a swap of that BPT or where the balances cannot be
+/// priced.
+fn spot_price(pool: &StablePool, balances: &[U256], index_in: usize, index_out: usize) -> Option<u128> {
+    if pool.is_bpt_swap(index_in, index_out) {
+        return None;
+    }
+    let mut tokens = balances.to_vec();
+    let (mut index_in, mut index_out) = (index_in, index_out);
+    if let Some(bpt_index) = pool.bpt_index.map(usize::from) {
+        tokens.remove(bpt_index);
+        index_in -= usize::from(index_in > bpt_index);
+        index_out -= usize::from(index_out > bpt_index);
+    }
+    let amp = U256::from(pool.amp);
+    let price = stable_math::try_calculate_invariant(amp, &tokens).and_then(|invariant| {
+        stable_math::try_calc_spot_price(amp, &tokens, index_in, index_out, invariant, U256::from(pool.swap_fee))
+    });
+    price.ok().and_then(|p| u128::try_from(p).ok())
+}
+
+/// Amount out for an 18‑dec amount in at `fee`, over all of `balances`
+/// (the pool's own BPT included when composable).
+fn calc_out_given_in(
+    pool: &StablePool,
+    balances: &[U256],
+    index_in: usize,
+    index_out: usize,
+    amount_in_fp: U256,
+    fee: U256,
+) -> Result<U256> {
+    let amp = U256::from(pool.amp);
+    let out_fp = match pool.bpt_index {
+        Some(bpt_index) => stable_math::try_calc_composable_out_given_in(
+            amp,
+            balances,
+            bpt_index as usize,
+            index_in,
+            index_out,
+            amount_in_fp,
+            scale_up(pool.total_bpt, pool.lp_decimals)?,
+            fee,
+        ),
+        None => stable_math::try_calculate_invariant(amp, balances).and_then(|invariant| {
+            stable_math::try_calc_out_given_in(amp, balances, index_in, index_out, amount_in_fp, invariant, fee)
+        }),
+    };
+    Ok(out_fp.map_err(ErrorCode::from)?)
+}
+
+/// Amount in, fee included, for an 18‑dec amount out at `fee`; see `calc_out_given_in`.
+fn calc_in_given_out(
+    pool: &StablePool,
+    balances: &[U256],
+    index_in: usize,
+    index_out: usize,
+    amount_out_fp: U256,
+    fee: U256,
+) -> Result<U256> {
+    let amp = U256::from(pool.amp);
+    let in_fp = match pool.bpt_index {
+        Some(bpt_index) => stable_math::try_calc_composable_in_given_out(
+            amp,
+            balances,
+            bpt_index as usize,
+            index_in,
+            index_out,
+            amount_out_fp,
+            scale_up(pool.total_bpt, pool.lp_decimals)?,
+            fee,
+        ),
+        None => stable_math::try_calculate_invariant(amp, balances).and_then(|invariant| {
+            stable_math::try_calc_in_given_out(amp, balances, index_in, index_out, amount_out_fp, invariant, fee)
+        }),
+    };
+    Ok(in_fp.map_err(ErrorCode::from)?)
+}
+
+/// Balances of the first `n` pool tokens as 18‑dec values, in pool order.
+/// With a registration, custody accounts must be the registered ones and
+/// amounts held by asset managers count towards the balance.
+fn read_balances<'a, 'info: 'a>(
+    pool: &StablePool,
+    registration: Option<&PoolRegistration>,
+    custody: impl IntoIterator<Item = &'a AccountInfo<'info>>,
+    n: usize,
+) -> Result<Vec<U256>> {
+    let mut balances = Vec::with_capacity(n);
+    for (i, ai) in custody.into_iter().enumerate() {
+        require!(i < n, ErrorCode::LengthMismatch);
+        let acct = unpack_token_account(ai)?;
+        require_keys_eq!(acct.mint, pool.tokens[i], ErrorCode::InvalidPoolTokenAccount);
+        let mut amount = acct.amount;
+        if let Some(registration) = registration {
+            let registered = registration.token_accounts.get(i).copied().unwrap_or_default();
+            if registered != Pubkey::default() {
+                require_keys_eq!(ai.key(), registered, ErrorCode::InvalidPoolTokenAccount);
+            }
+            amount = amount
+                .checked_add(registration.managed_balance(&acct.mint))
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        balances.push(pool.upscale(i, amount)?);
+    }
+    require!(balances.len() == n, ErrorCode::LengthMismatch);
+    Ok(balances)
+}
+
+/// Positions of the custody accounts swapped among all of them.
+fn swap_indices(custody: &[AccountInfo], token_in: &AccountInfo, token_out: &AccountInfo) -> Result<(usize, usize)> {
+    let index_of = |ai: &AccountInfo| custody.iter().position(|c| c.key() == ai.key());
+    match (index_of(token_in), index_of(token_out)) {
+        (Some(i), Some(o)) if i != o => Ok((i, o)),
+        _ => err!(ErrorCode::InvalidSwapTokens),
+    }
+}
+
+/// `amount` at `index`, zero for every other of the `n` tokens.
+fn one_hot(n: usize, index: usize, amount: U256) -> Vec<U256> {
+    let mut amounts = vec![U256::zero(); n];
+    amounts[index] = amount;
+    amounts
+}
+
+/// Protocol share (`pct`, 18‑dec) of a fee amount, rounded up.
+fn protocol_share(fee_amount_fp: U256, pct: u64) -> Result<U256> {
+    Ok(pool_tokens::protocol_share(fee_amount_fp, pct).map_err(ErrorCode::from)?)
+}
+
+/// Raw token amount as an 18‑dec value.
+fn scale_up(amount: u64, decimals: u8) -> Result<U256> {
+    Ok(pool_tokens::scale_up(amount, decimals).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec value as a raw token amount, rounded down (amounts paid out).
+fn scale_down(value_fp: U256, decimals: u8) -> Result<u64> {
+    Ok(pool_tokens::scale_down(value_fp, decimals).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec value as a raw token amount, rounded up (amounts paid in).
+fn scale_down_up(value_fp: U256, decimals: u8) -> Result<u64> {
+    Ok(pool_tokens::scale_down_up(value_fp, decimals).map_err(ErrorCode::from)?)
+}
+
+/// Decimals of a mint of either token program, checked to be scalable
+/// to 18‑dec.
+fn mint_decimals(ai: &AccountInfo) -> Result<u8> {
+    pool_tokens::mint_decimals(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A token account of either token program, its extensions skipped.
+fn unpack_token_account(ai: &AccountInfo) -> Result<TokenAccount> {
+    pool_tokens::unpack_token_account(ai, ErrorCode::InvalidPoolTokenAccount)
+}
+
+/// A protocol fee account must be a token account of `mint` held by the collector.
+fn check_fee_account(ai: &AccountInfo, collector: &Pubkey, mint: &Pubkey) -> Result<()> {
+    pool_tokens::check_fee_account(ai, collector, mint, ErrorCode::InvalidFeeAccount)
+}
+
+/* ------------------------------------------------------------------
+   Accounts: initialize & pool contexts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializePool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// An already‑initialized VaultState account
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// CHECK: the LP‑token mint for this pool; its decimals scale BPT amounts
+    #[account(mut)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority for `lp_mint`; derived from `["lp-mint-authority", pool.key().as_ref()]`
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// The Pool state PDA itself
+    #[account(
+        init,
+        seeds = [b"pool-state", vault_state.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + StablePool::INIT_SPACE
+    )]
+    pub pool: Account<'info, StablePool>,
+
+    /// CHECK: the Vault's authority PDA (composable pools only); validated by the Vault
+    pub vault_authority: Option<AccountInfo<'info>>,
+
+    /// CHECK: custody account for the pool's own BPT (composable pools only); created by the Vault, seeds checked there
+    #[account(mut)]
+    pub pool_bpt: Option<AccountInfo<'info>>,
+
+    pub token_program: Program<'info, Token>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializePool<'info> {
+    /// Have the Vault create the custody account of the pool's own BPT,
+    /// then mint the whole supply into it.
+    fn premint_bpt(&mut self, bump: u8) -> Result<()> {
+        let (Some(vault_authority), Some(pool_bpt)) = (&self.vault_authority, &self.pool_bpt) else {
+            return err!(ErrorCode::MissingBptCustody);
+        };
+        let cpi_accounts = VaultRegisterTokens {
+            vault_state:              self.vault_state.to_account_info(),
+            authorizer:               self.authorizer.clone(),
+            pool_registration:        self.pool_registration.clone(),
+            vault_authority:          vault_authority.clone(),
+            transfer_hook_allowlist:  None,
+            mint_extension_overrides: None,
+            authority:                self.payer.to_account_info(),
+            token_program:            self.token_program.to_account_info(),
+            system_program:           self.system_program.to_account_info(),
+        };
+        vault_register_tokens(
+            CpiContext::new(self.vault_program.to_account_info(), cpi_accounts)
+                .with_remaining_accounts(vec![self.lp_mint.clone(), pool_bpt.clone()]),
+            vec![Pubkey::default()],
+        )?;
+
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = MintTo {
+            mint:      self.lp_mint.clone(),
+            to:        pool_bpt.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            PREMINTED_BPT,
+        )?;
+        self.pool.total_bpt = PREMINTED_BPT;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct PoolContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, StablePool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the pool's LP mint
+    #[account(mut, address = pool.lp_mint)]
+    pub lp_mint: AccountInfo<'info>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: user's LP token account; BPT is minted to or burned from it
+    #[account(mut)]
+    pub user_lp_account: AccountInfo<'info>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's LP token account; checked when a protocol fee is minted
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: custody account of the pool's own BPT (composable pools only); checked against the registration
+    #[account(mut)]
+    pub pool_bpt: Option<AccountInfo<'info>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> PoolContext<'info> {
+    fn check_live(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!self.vault_state.is_paused(clock.unix_timestamp), ErrorCode::VaultPaused);
+        require!(!self.pool_registration.paused, ErrorCode::PoolPaused);
+        require!(self.pool.rates_are_fresh(clock.slot), ErrorCode::StaleTokenRates);
+        Ok(())
+    }
+
+    /// The BPT custody account of a composable pool, checked to be the registered one.
+    fn pool_bpt(&self) -> Result<Option<&AccountInfo<'info>>> {
+        let Some(bpt_index) = self.pool.bpt_index else {
+            return Ok(None);
+        };
+        let pool_bpt = self.pool_bpt.as_ref().ok_or(ErrorCode::MissingBptCustody)?;
+        let registered = self.pool_registration.token_accounts.get(bpt_index as usize).copied();
+        require!(registered == Some(pool_bpt.key()), ErrorCode::MissingBptCustody);
+        Ok(Some(pool_bpt))
+    }
+
+    /// BPT in circulation: all of it, or for a composable pool the virtual
+    /// supply (what the pool itself does not hold).
+    fn bpt_supply(&self) -> Result<u64> {
+        match self.pool_bpt()? {
+            Some(pool_bpt) => Ok(self.pool.total_bpt.checked_sub(unpack_token_account(pool_bpt)?.amount)
+                .ok_or(ErrorCode::MathUnderflow)?),
+            None => Ok(self.pool.total_bpt),
+        }
+    }
+
+    /// user → vault, signed by the user
+    fn pay_in(&self, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::transfer(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    /// vault → user, signed by the pool authority PDA
+    fn pay_out(&self, bump: u8, from: &AccountInfo<'info>, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      from.clone(),
+            to:        to.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::transfer(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /// Issue BPT: minted, or paid out of custody in a composable pool.
+    fn mint_bpt(&self, bump: u8, to: &AccountInfo<'info>, amount: u64) -> Result<()> {
+        if let Some(pool_bpt) = self.pool_bpt()? {
+            return self.pay_out(bump, pool_bpt, to, amount);
+        }
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = MintTo {
+            mint:      self.lp_mint.clone(),
+            to:        to.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::mint_to(
+            CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            amount,
+        )
+    }
+
+    /// The protocol's share of a fee, minted as BPT to the collector.
+    fn mint_protocol_bpt(&self, bump: u8, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        check_fee_account(&self.protocol_fee_lp_account, &self.fees_collector.key(), &self.pool.lp_mint)?;
+        self.mint_bpt(bump, &self.protocol_fee_lp_account, amount)
+    }
+
+    /// Retire the user's BPT: burned, or paid back into custody in a composable pool.
+    fn burn_bpt(&self, amount: u64) -> Result<()> {
+        if let Some(pool_bpt) = self.pool_bpt()? {
+            return self.pay_in(&self.user_lp_account, pool_bpt, amount);
+        }
+        let cpi_accounts = Burn {
+            mint:      self.lp_mint.clone(),
+            from:      self.user_lp_account.clone(),
+            authority: self.user.to_account_info(),
+        };
+        token::burn(CpiContext::new(self.token_program.to_account_info(), cpi_accounts), amount)
+    }
+
+    /// Record minted BPT; a composable pool's supply never changes.
+    fn add_bpt(&mut self, minted: u64, protocol_bpt: u64) -> Result<()> {
+        if self.pool.bpt_index.is_some() {
+            return Ok(());
+        }
+        self.pool.total_bpt = self.pool
+            .total_bpt
+            .checked_add(minted)
+            .and_then(|t| t.checked_add(protocol_bpt))
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    fn remove_bpt(&mut self, burned: u64, protocol_bpt: u64) -> Result<()> {
+        if self.pool.bpt_index.is_some() {
+            return Ok(());
+        }
+        self.pool.total_bpt = self.pool
+            .total_bpt
+            .checked_sub(burned)
+            .and_then(|t| t.checked_add(protocol_bpt))
+            .ok_or(ErrorCode::MathUnderflow)?;
+        Ok(())
+    }
+
+    /// Book a join and emit its event; amounts in pool order.
+    fn joined(&mut self, amounts_in: Vec<u64>, bpt_out: u64, protocol_bpt: u64) -> Result<()> {
+        self.add_bpt(bpt_out, protocol_bpt)?;
+        emit!(JoinEvent {
+            pool:    self.pool.key(),
+            pool_id: self.pool.pool_id,
+            user:    self.user.key(),
+            amounts_in,
+            bpt_out,
+            protocol_bpt,
+        });
+        Ok(())
+    }
+
+    /// Book an exit and emit its event; amounts in pool order.
+    fn exited(&mut self, bpt_in: u64, amounts_out: Vec<u64>, protocol_bpt: u64) -> Result<()> {
+        self.remove_bpt(bpt_in, protocol_bpt)?;
+        emit!(ExitEvent {
+            pool:          self.pool.key(),
+            pool_id:       self.pool.pool_id,
+            user:          self.user.key(),
+            bpt_in,
+            protocol_fees: vec![0; amounts_out.len()],
+            amounts_out,
+            protocol_bpt,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct UpdateTokenRates<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, StablePool>,
+}
+
+#[derive(Accounts)]
+pub struct SetSwapFee<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, StablePool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+}
+
+/* ------------------------------------------------------------------
+   Accounts: swap context (same layout as every pool's, see
+   `common::pool_interface::SwapExactInAccounts`)
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct SwapContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, StablePool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: custody account of the 'in' token; must be one of the remaining accounts
+    #[account(mut)]
+    pub vault_in: AccountInfo<'info>,
+
+    /// CHECK: custody account of the 'out' token; must be one of the remaining accounts
+    #[account(mut)]
+    pub vault_out: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub user_authority: Signer<'info>,
+
+    /// CHECK: User's token account for the 'in' mint
+    #[account(mut)]
+    pub user_token_account_in: AccountInfo<'info>,
+
+    /// CHECK: User's token account for the 'out' mint
+    #[account(mut)]
+    pub user_token_account_out: AccountInfo<'info>,
+
+    /// CHECK: PDA for LP mint authority; seed ensures correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's account for the 'in' token; checked when a protocol fee is paid
+    #[account(mut)]
+    pub protocol_fee_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (custody accounts, managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+}
+
+impl<'info> SwapContext<'info> {
+    fn check_live(&self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!self.vault_state.is_paused(clock.unix_timestamp), ErrorCode::VaultPaused);
+        require!(!self.pool_registration.paused, ErrorCode::PoolPaused);
+        require!(self.pool.rates_are_fresh(clock.slot), ErrorCode::StaleTokenRates);
+        Ok(())
+    }
+
+    /// Move the tokens of a swap priced on `balances`: the amount in (less
+    /// the protocol fee) to the pool, the protocol fee to the collector,
+    /// the amount out to the user, and emit the swap's event.
+    #[allow(clippy::too_many_arguments)]
+    fn settle(
+        &self,
+        bump: u8,
+        balances: &[U256],
+        index_in: usize,
+        index_out: usize,
+        amount_in: u64,
+        protocol_fee: u64,
+        amount_out: u64,
+    ) -> Result<()> {
+        let token_prog = self.token_program.to_account_info();
+        let cpi_in = Transfer {
+            from:      self.user_token_account_in.clone(),
+            to:        self.vault_in.clone(),
+            authority: self.user_authority.to_account_info(),
+        };
+        let kept = amount_in.checked_sub(protocol_fee).ok_or(ErrorCode::MathUnderflow)?;
+        token::transfer(CpiContext::new(token_prog.clone(), cpi_in), kept)?;
+        if protocol_fee > 0 {
+            check_fee_account(&self.protocol_fee_account, &self.fees_collector.key(), &self.pool.tokens[index_in])?;
+            let cpi_fee = Transfer {
+                from:      self.user_token_account_in.clone(),
+                to:        self.protocol_fee_account.clone(),
+                authority: self.user_authority.to_account_info(),
+            };
+            token::transfer(CpiContext::new(token_prog.clone(), cpi_fee), protocol_fee)?;
+        }
+
+        let pool_key = self.pool.key();
+        let bump_arr = [bump];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_out = Transfer {
+            from:      self.vault_out.clone(),
+            to:        self.user_token_account_out.clone(),
+            authority: self.lp_mint_authority.clone(),
+        };
+        token::transfer(CpiContext::new_with_signer(token_prog, cpi_out, &[seed_slice]), amount_out)?;
+
+        let pool = &self.pool;
+        let amount_in_fp  = pool.upscale(index_in, amount_in)?;
+        let amount_out_fp = pool.upscale(index_out, amount_out)?;
+        let swap_fee_amount = swap_fee_fp(pool, balances, index_in, index_out, amount_in_fp, amount_out_fp)?;
+        let mut after = balances.to_vec();
+        after[index_in]  = after[index_in].saturating_add(pool.upscale(index_in, kept)?);
+        after[index_out] = after[index_out].saturating_sub(amount_out_fp);
+        let as_u128 = |v: U256| u128::try_from(v).unwrap_or(u128::MAX);
+        emit!(SwapEvent {
+            pool:              pool_key,
+            pool_id:           pool.pool_id,
+            user:              self.user_authority.key(),
+            token_in:          pool.tokens[index_in],
+            token_out:         pool.tokens[index_out],
+            amount_in,
+            amount_out,
+            protocol_fee,
+            spot_price_before: spot_price(pool, balances, index_in, index_out).unwrap_or_default(),
+            spot_price_after:  spot_price(pool, &after, index_in, index_out).unwrap_or_default(),
+            swap_fee_amount:   pool.downscale_down(index_in, swap_fee_amount)?,
+            balance_in:        as_u128(balances[index_in]),
+            balance_out:       as_u128(balances[index_out]),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ManagedTransfer<'info> {
+    pub pool: Account<'info, StablePool>,
+
+    /// The Vault's asset manager authority PDA; the Vault signs for it
+    /// only in `withdraw_managed`
+    #[account(
+        seeds = [b"asset-manager-authority", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub asset_manager_authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: pool custody account, one the Vault recorded for the pool;
+    /// the token program enforces ownership
+    #[account(
+        mut,
+        constraint = pool_registration.token_accounts.contains(&from.key()) @ ErrorCode::InvalidPoolTokenAccount
+    )]
+    pub from: AccountInfo<'info>,
+
+    /// CHECK: asset manager's destination, chosen and checked by the Vault
+    #[account(mut)]
+    pub to: AccountInfo<'info>,
+
+    /// CHECK: PDA holding the pool's tokens
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct QuerySwap<'info> {
+    pub pool: Account<'info, StablePool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: pool custody account of the token in; identifies the token only
+    pub pool_token_in: AccountInfo<'info>,
+
+    /// CHECK: pool custody account of the token out; identifies the token only
+    pub pool_token_out: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   State & Errors
+------------------------------------------------------------------ */
+#[account]
+pub struct StablePool {
+    pub vault: Pubkey,
+    pub lp_mint: Pubkey,
+    /// Token mints, in Vault registration order
+    pub tokens: Vec<Pubkey>,
+    /// Decimals of each token, scaling raw amounts to 18‑dec
+    pub decimals: Vec<u8>,
+    /// Decimals of the LP mint, scaling BPT amounts to 18‑dec
+    pub lp_decimals: u8,
+    /// Amplification parameter times `AMP_PRECISION`
+    pub amp: u64,
+    pub swap_fee: u64,
+    /// BPT minted (for a composable pool, the pre‑minted supply)
+    pub total_bpt: u64,
+    /// PoolId assigned by the Vault at registration
+    pub pool_id: [u8; 32],
+    /// Position of the pool's own BPT among `tokens`, if composable
+    pub bpt_index: Option<u8>,
+    /// Rate account of each token, `Pubkey::default()` for none
+    pub rate_providers: Vec<Pubkey>,
+    /// Cached rate of each token (18‑dec; one without a provider)
+    pub rates: Vec<u128>,
+    /// Slot `rates` were last read from their providers
+    pub rates_updated_slot: u64,
+}
+impl StablePool {
+    pub const INIT_SPACE: usize = 32
+        + 32
+        + (4 + (MAX_TOKENS + 1) * 32)
+        + (4 + MAX_TOKENS + 1)
+        + 1
+        + 8
+        + 8
+        + 8
+        + 32
+        + 2
+        + (4 + (MAX_TOKENS + 1) * 32)
+        + (4 + (MAX_TOKENS + 1) * 16)
+        + 8;
+
+    /// Whether prices may use the cached rates in `slot`: tokens with a
+    /// rate provider need them read in that same slot.
+    pub fn rates_are_fresh(&self, slot: u64) -> bool {
+        self.rates_updated_slot == slot || self.rate_providers.iter().all(|p| *p == Pubkey::default())
+    }
+
+    /// Raw amount of token `i` as an 18‑dec value at its rate.
+    fn upscale(&self, i: usize, amount: u64) -> Result<U256> {
+        let value = scale_up(amount, self.decimals[i])?;
+        Ok(fixed::try_mul_down(value, U256::from(self.rates[i])).map_err(ErrorCode::from)?)
+    }
+
+    /// 18‑dec value at token `i`'s rate as a raw amount, rounded down (amounts paid out).
+    fn downscale_down(&self, i: usize, value_fp: U256) -> Result<u64> {
+        let value = fixed::try_div_down(value_fp, U256::from(self.rates[i])).map_err(ErrorCode::from)?;
+        scale_down(value, self.decimals[i])
+    }
+
+    /// 18‑dec value at token `i`'s rate as a raw amount, rounded up (amounts paid in).
+    fn downscale_up(&self, i: usize, value_fp: U256) -> Result<u64> {
+        let value = fixed::try_div_up(value_fp, U256::from(self.rates[i])).map_err(ErrorCode::from)?;
+        scale_down_up(value, self.decimals[i])
+    }
+
+    /// Tokens other than the pool's own BPT
+    pub fn token_count(&self) -> usize {
+        self.tokens.len() - usize::from(self.bpt_index.is_some())
+    }
+
+    /// Whether a swap buys or sells the pool's own BPT.
+    pub fn is_bpt_swap(&self, index_in: usize, index_out: usize) -> bool {
+        self.bpt_index.is_some_and(|b| usize::from(b) == index_in || usize::from(b) == index_out)
+    }
+}
+
+/// Tokens were swapped through the pool, directly or in a Vault batch swap
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub token_in: Pubkey,
+    pub token_out: Pubkey,
+    /// Taken from the user, protocol fee included
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Part of `amount_in` paid to the protocol fee collector
+    pub protocol_fee: u64,
+    /// Marginal price of `token_out` in `token_in` (18‑dec, fee included)
+    /// before and after the swap; zero for swaps of a composable pool's
+    /// own BPT
+    pub spot_price_before: u128,
+    pub spot_price_after: u128,
+    /// Swap fee charged on `amount_in`, `protocol_fee` included
+    pub swap_fee_amount: u64,
+    /// Balances of `token_in` and `token_out` the swap was priced on
+    /// (custody plus managed, 18‑dec at the tokens' decimals and rates)
+    pub balance_in: u128,
+    pub balance_out: u128,
+}
+
+/// Tokens were deposited for BPT; amounts in pool order
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct JoinEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub amounts_in: Vec<u64>,
+    pub bpt_out: u64,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+}
+
+/// BPT was burned for tokens; amounts in pool order
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub bpt_in: u64,
+    pub amounts_out: Vec<u64>,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+    /// Tokens paid to the protocol fee collector
+    pub protocol_fees: Vec<u64>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Vector length mismatch")]
+    LengthMismatch,
+    #[msg("Math underflow or overflow")]
+    MathUnderflow,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Protocol fee account does not belong to the fees collector")]
+    InvalidFeeAccount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Math solver did not converge")]
+    MathConvergenceFailure,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("A stable pool holds between 2 and 5 tokens")]
+    InvalidTokenCount,
+    #[msg("Amplification parameter out of range")]
+    InvalidAmplification,
+    #[msg("Token account does not hold the pool token expected at its position")]
+    InvalidPoolTokenAccount,
+    #[msg("Swap tokens must be two different pool tokens")]
+    InvalidSwapTokens,
+    #[msg("The first join must add every token and later joins need an initialised pool")]
+    InvalidInitialJoin,
+    #[msg("Amount is outside the caller's limit")]
+    SlippageLimit,
+    #[msg("A composable pool needs its registered BPT custody account")]
+    MissingBptCustody,
+    #[msg("Rate account or provider does not match the pool's, or returned no rate")]
+    InvalidRateProvider,
+    #[msg("Token rates must be updated earlier in the same slot")]
+    StaleTokenRates,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::LengthMismatch => ErrorCode::LengthMismatch,
+            MathError::TokenIndexOutOfBounds => ErrorCode::InvalidSwapTokens,
+            MathError::ConvergenceFailure => ErrorCode::MathConvergenceFailure,
+            MathError::WeightsNotNormalized
+            | MathError::InvalidWeight
+            | MathError::MaxInRatio
+            | MathError::MaxOutRatio
+            | MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::InvalidParams
+            | MathError::AssetBoundsExceeded
+            | MathError::InvalidPercentage
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange => ErrorCode::MathInputOutOfBounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(decimals: &[u8], amplification: u64, swap_fee: u64) -> StablePool {
+        StablePool {
+            vault: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            tokens: decimals.iter().map(|_| Pubkey::new_unique()).collect(),
+            decimals: decimals.to_vec(),
+            lp_decimals: 9,
+            amp: amplification * AMP_PRECISION,
+            swap_fee,
+            total_bpt: 0,
+            pool_id: [0; 32],
+            bpt_index: None,
+            rate_providers: vec![Pubkey::default(); decimals.len()],
+            rates: vec![fixed::ONE.as_u128(); decimals.len()],
+            rates_updated_slot: 0,
+        }
+    }
+
+    fn balances(pool: &StablePool, amounts: &[u64]) -> Vec<U256> {
+        amounts.iter().enumerate().map(|(i, a)| pool.upscale(i, *a).unwrap()).collect()
+    }
+
+    const E6: u64 = 1_000_000;
+    const E9: u64 = 1_000_000_000;
+
+    #[test]
+    fn swaps_across_decimals_near_parity() {
+        // 1M of a 6‑decimal and a 9‑decimal stablecoin, plus a third token
+        let pool = pool(&[6, 9, 6], 200, 0);
+        let balances = balances(&pool, &[1_000_000 * E6, 1_000_000 * E9, 1_000_000 * E6]);
+
+        let quote = quote_exact_in(&pool, 0, &balances, 0, 1, 1_000 * E6).unwrap();
+        // 1000 units in: a little under 1000 units out, in the out token's decimals
+        assert!(quote.amount_out < 1_000 * E9 && quote.amount_out > 999 * E9, "{}", quote.amount_out);
+        assert_eq!(quote.protocol_fee, 0);
+
+        // buying that output back costs at least the input
+        let (amount_in, _) = quote_exact_out(&pool, 0, &balances, 0, 1, quote.amount_out).unwrap();
+        assert!((1_000 * E6..=1_000 * E6 + 1).contains(&amount_in), "{}", amount_in);
+    }
+
+    #[test]
+    fn protocol_takes_its_share_of_the_swap_fee() {
+        // 1% fee, half to the protocol
+        let pool = pool(&[6, 6], 100, 10_000_000_000_000_000);
+        let balances = balances(&pool, &[500_000 * E6, 500_000 * E6]);
+        let quote = quote_exact_in(&pool, 500_000_000_000_000_000, &balances, 1, 0, 100 * E6).unwrap();
+        assert_eq!(quote.protocol_fee, E6 / 2);
+        assert!(quote.amount_out < 99 * E6 && quote.amount_out > 98 * E6, "{}", quote.amount_out);
+
+        let (amount_in, protocol_fee) =
+            quote_exact_out(&pool, 500_000_000_000_000_000, &balances, 1, 0, 99 * E6).unwrap();
+        assert!(amount_in > 100 * E6);
+        // half of 1% of the amount in, give or take the rounding of each
+        assert!(protocol_fee.abs_diff(amount_in / 200) <= 1, "{} {}", protocol_fee, amount_in);
+    }
+
+    #[test]
+    fn composable_pool_sells_and_buys_its_own_bpt() {
+        // two 6‑decimal stablecoins and the pool's 9‑decimal BPT, 1000 BPT out
+        let mut pool = pool(&[6, 6, 9], 100, 10_000_000_000_000_000);
+        pool.bpt_index = Some(2);
+        pool.total_bpt = PREMINTED_BPT;
+        let held = PREMINTED_BPT - 1_000 * E9;
+        let balances = balances(&pool, &[500 * E6, 500 * E6, held]);
+        assert!(pool.is_bpt_swap(0, 2) && !pool.is_bpt_swap(0, 1));
+        assert_eq!(pool.token_count(), 2);
+
+        // 10 tokens buy just under 10 BPT; half the fee on the unbalanced part goes to the protocol
+        let quote = quote_exact_in(&pool, 500_000_000_000_000_000, &balances, 0, 2, 10 * E6).unwrap();
+        assert!(quote.amount_out < 10 * E9 && quote.amount_out > 9 * E9, "{}", quote.amount_out);
+        assert!(quote.protocol_fee > 0 && quote.protocol_fee < 10 * E6 / 200, "{}", quote.protocol_fee);
+
+        // selling those BPT returns less than was paid for them
+        let back = quote_exact_in(&pool, 0, &balances, 2, 1, quote.amount_out).unwrap();
+        assert!(back.amount_out < 10 * E6, "{}", back.amount_out);
+        // and buying exactly that much BPT costs about the same 10 tokens
+        let (cost, _) = quote_exact_out(&pool, 0, &balances, 0, 2, quote.amount_out).unwrap();
+        assert!(cost.abs_diff(10 * E6) <= 10 * E6 / 1_000, "{}", cost);
+    }
+
+    #[test]
+    fn boosted_pool_prices_at_the_token_rates() {
+        // a staked token worth 1.05 of the other, both 9 decimals
+        let mut pool = pool(&[9, 9], 200, 0);
+        pool.rate_providers[0] = Pubkey::new_unique();
+        pool.rates[0] = 1_050_000_000_000_000_000;
+        assert!(!pool.rates_are_fresh(7));
+        pool.rates_updated_slot = 7;
+        assert!(pool.rates_are_fresh(7));
+
+        // equal value on both sides: 1M staked against 1.05M plain
+        let balances = balances(&pool, &[1_000_000 * E9, 1_050_000 * E9]);
+        assert_eq!(balances[0], balances[1]);
+        let quote = quote_exact_in(&pool, 0, &balances, 0, 1, 100 * E9).unwrap();
+        assert!(quote.amount_out < 105 * E9 && quote.amount_out > 10_499 * E9 / 100, "{}", quote.amount_out);
+        let (amount_in, _) = quote_exact_out(&pool, 0, &balances, 1, 0, 100 * E9).unwrap();
+        assert!(amount_in > 105 * E9 && amount_in < 10_501 * E9 / 100, "{}", amount_in);
+    }
+
+    #[test]
+    fn rejects_bad_token_indices() {
+        let pool = pool(&[6, 6], 100, 0);
+        let balances = balances(&pool, &[E6, E6]);
+        assert_eq!(
+            quote_exact_in(&pool, 0, &balances, 0, 2, E6).unwrap_err(),
+            error!(ErrorCode::InvalidSwapTokens)
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("TLockKuD9kvEwrBvNtoBeYeVu8GKwKSJAuBkWe3WBcH");
+
+/// Shortest delay a timelock may be configured with
+pub const MIN_DELAY: i64 = 60 * 60;
+/// Longest delay a timelock may be configured with
+pub const MAX_DELAY: i64 = 30 * 24 * 60 * 60;
+/// How long a ready action stays executable before it goes stale
+pub const GRACE_PERIOD: i64 = 14 * 24 * 60 * 60;
+/// Accounts an action's instruction may take
+pub const MAX_ACTION_ACCOUNTS: usize = 32;
+/// Bytes of instruction data an action may carry
+pub const MAX_ACTION_DATA: usize = 1024;
+
+/// Governance timelock: privileged instructions are queued here in full
+/// (program, accounts and data), sit in a public `QueuedAction` account
+/// for at least the timelock's delay, and are then executed by anyone,
+/// signed by the timelock's authority PDA. Protected parameters are put
+/// behind the timelock by making that PDA their only authority, e.g. by
+/// granting it `SetSwapFee` and `SetProtocolFees` in the Vault's
+/// Authorizer (or handing it the Authorizer admin role) and revoking the
+/// direct grants. The guardian's `Pause` role is left out so emergency
+/// pauses stay immediate.
+///
+/// The delay and the proposer can only be changed by the timelock itself,
+/// i.e. through a queued action.
+#[program]
+pub mod timelock {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create a timelock, PDA'd by the initial proposer
+    ---------------------------------------------------------------- */
+    pub fn initialize_timelock(ctx: Context<InitializeTimelock>, delay: i64) -> Result<()> {
+        require!((MIN_DELAY..=MAX_DELAY).contains(&delay), ErrorCode::InvalidDelay);
+        let timelock = &mut ctx.accounts.timelock;
+        timelock.proposer       = ctx.accounts.proposer.key();
+        timelock.delay          = delay;
+        timelock.action_count   = 0;
+        timelock.bump           = ctx.bumps.timelock;
+        timelock.authority_bump = ctx.bumps.timelock_authority;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Queue an instruction for execution at `eta` – proposer only
+    ---------------------------------------------------------------- */
+    pub fn queue_action(
+        ctx: Context<QueueAction>,
+        program_id: Pubkey,
+        accounts: Vec<ActionAccount>,
+        data: Vec<u8>,
+        eta: i64,
+    ) -> Result<()> {
+        require!(accounts.len() <= MAX_ACTION_ACCOUNTS, ErrorCode::ActionTooLarge);
+        require!(data.len() <= MAX_ACTION_DATA, ErrorCode::ActionTooLarge);
+        let now = Clock::get()?.unix_timestamp;
+        let timelock = &mut ctx.accounts.timelock;
+        require!(eta >= now + timelock.delay, ErrorCode::EtaTooSoon);
+
+        let action = &mut ctx.accounts.action;
+        action.timelock   = timelock.key();
+        action.id         = timelock.action_count;
+        action.program_id = program_id;
+        action.accounts   = accounts;
+        action.data       = data;
+        action.queued_at  = now;
+        action.eta        = eta;
+        action.status     = ActionStatus::Queued;
+        timelock.action_count += 1;
+
+        emit!(ActionQueued {
+            timelock:   action.timelock,
+            action:     action.key(),
+            id:         action.id,
+            program_id,
+            eta,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Drop a queued action – proposer only
+    ---------------------------------------------------------------- */
+    pub fn cancel_action(ctx: Context<CancelAction>) -> Result<()> {
+        let action = &mut ctx.accounts.action;
+        require!(action.status == ActionStatus::Queued, ErrorCode::ActionNotQueued);
+        action.status = ActionStatus::Cancelled;
+        emit!(ActionCancelled { timelock: action.timelock, action: action.key(), id: action.id });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Execute a ready action; permissionless
+       remaining_accounts: [program, action.accounts…] in queued order
+    ---------------------------------------------------------------- */
+    pub fn execute_action<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteAction<'info>>) -> Result<()> {
+        let action = &mut ctx.accounts.action;
+        action.check_ready(Clock::get()?.unix_timestamp)?;
+        let rem = ctx.remaining_accounts;
+        require!(rem.len() == action.accounts.len() + 1, ErrorCode::InvalidActionAccounts);
+        require_keys_eq!(rem[0].key(), action.program_id, ErrorCode::InvalidActionAccounts);
+        for (info, queued) in rem[1..].iter().zip(action.accounts.iter()) {
+            require_keys_eq!(info.key(), queued.pubkey, ErrorCode::InvalidActionAccounts);
+        }
+        action.status = ActionStatus::Executed;
+
+        let ix = Instruction {
+            program_id: action.program_id,
+            accounts:   action.accounts.iter().map(AccountMeta::from).collect(),
+            data:       action.data.clone(),
+        };
+        let timelock_key = ctx.accounts.timelock.key();
+        let bump_arr = [ctx.accounts.timelock.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"timelock-authority", timelock_key.as_ref(), &bump_arr];
+        invoke_signed(&ix, rem, &[seed_slice])?;
+
+        emit!(ActionExecuted { timelock: timelock_key, action: action.key(), id: action.id });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Self‑governance – signed by the timelock authority, i.e. only
+       reachable through `execute_action`
+    ---------------------------------------------------------------- */
+    pub fn set_delay(ctx: Context<SelfCall>, delay: i64) -> Result<()> {
+        require!((MIN_DELAY..=MAX_DELAY).contains(&delay), ErrorCode::InvalidDelay);
+        ctx.accounts.timelock.delay = delay;
+        emit!(DelayChanged { timelock: ctx.accounts.timelock.key(), delay });
+        Ok(())
+    }
+
+    pub fn set_proposer(ctx: Context<SelfCall>, proposer: Pubkey) -> Result<()> {
+        ctx.accounts.timelock.proposer = proposer;
+        emit!(ProposerChanged { timelock: ctx.accounts.timelock.key(), proposer });
+        Ok(())
+    }
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializeTimelock<'info> {
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Timelock::INIT_SPACE,
+        seeds = [b"timelock", proposer.key().as_ref()],
+        bump
+    )]
+    pub timelock: Account<'info, Timelock>,
+
+    /// CHECK: PDA that signs executed actions
+    #[account(seeds = [b"timelock-authority", timelock.key().as_ref()], bump)]
+    pub timelock_authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey, accounts: Vec<ActionAccount>, data: Vec<u8>)]
+pub struct QueueAction<'info> {
+    #[account(mut, has_one = proposer)]
+    pub timelock: Account<'info, Timelock>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = QueuedAction::space(accounts.len(), data.len()),
+        seeds = [b"timelock-action", timelock.key().as_ref(), &timelock.action_count.to_le_bytes()],
+        bump
+    )]
+    pub action: Account<'info, QueuedAction>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAction<'info> {
+    #[account(has_one = proposer)]
+    pub timelock: Account<'info, Timelock>,
+
+    #[account(mut, has_one = timelock)]
+    pub action: Account<'info, QueuedAction>,
+
+    pub proposer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteAction<'info> {
+    pub timelock: Account<'info, Timelock>,
+
+    #[account(mut, has_one = timelock)]
+    pub action: Account<'info, QueuedAction>,
+}
+
+#[derive(Accounts)]
+pub struct SelfCall<'info> {
+    #[account(mut)]
+    pub timelock: Account<'info, Timelock>,
+
+    #[account(seeds = [b"timelock-authority", timelock.key().as_ref()], bump = timelock.authority_bump)]
+    pub timelock_authority: Signer<'info>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// PDA'd by ["timelock", initial proposer]
+#[account]
+#[derive(InitSpace)]
+pub struct Timelock {
+    /// May queue and cancel actions
+    pub proposer: Pubkey,
+    /// Minimum seconds between queueing an action and executing it
+    pub delay: i64,
+    /// Actions ever queued; the next action's id
+    pub action_count: u64,
+    pub bump: u8,
+    /// Bump of the ["timelock-authority", timelock] PDA
+    pub authority_bump: u8,
+}
+
+/// One account of a queued instruction
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ActionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+impl ActionAccount {
+    pub const SIZE: usize = 32 + 1 + 1;
+}
+
+impl From<&ActionAccount> for AccountMeta {
+    fn from(a: &ActionAccount) -> Self {
+        AccountMeta { pubkey: a.pubkey, is_signer: a.is_signer, is_writable: a.is_writable }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActionStatus {
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+/// A privileged instruction waiting out the delay, PDA'd by
+/// ["timelock-action", timelock, id LE]. Kept after execution or
+/// cancellation as a public record.
+#[account]
+pub struct QueuedAction {
+    pub timelock: Pubkey,
+    pub id: u64,
+    pub program_id: Pubkey,
+    pub accounts: Vec<ActionAccount>,
+    pub data: Vec<u8>,
+    pub queued_at: i64,
+    /// Earliest execution time; stale after `eta + GRACE_PERIOD`
+    pub eta: i64,
+    pub status: ActionStatus,
+}
+
+impl QueuedAction {
+    pub fn space(accounts: usize, data: usize) -> usize {
+        8 + 32 + 8 + 32 + (4 + accounts * ActionAccount::SIZE) + (4 + data) + 8 + 8 + 1
+    }
+
+    /// Whether the action may execute at `now`
+    pub fn check_ready(&self, now: i64) -> Result<()> {
+        require!(self.status == ActionStatus::Queued, ErrorCode::ActionNotQueued);
+        require!(now >= self.eta, ErrorCode::ActionNotReady);
+        require!(now <= self.eta + GRACE_PERIOD, ErrorCode::ActionStale);
+        Ok(())
+    }
+}
+
+#[event]
+pub struct ActionQueued {
+    pub timelock: Pubkey,
+    pub action: Pubkey,
+    pub id: u64,
+    pub program_id: Pubkey,
+    pub eta: i64,
+}
+
+#[event]
+pub struct ActionCancelled {
+    pub timelock: Pubkey,
+    pub action: Pubkey,
+    pub id: u64,
+}
+
+#[event]
+pub struct ActionExecuted {
+    pub timelock: Pubkey,
+    pub action: Pubkey,
+    pub id: u64,
+}
+
+#[event]
+pub struct DelayChanged {
+    pub timelock: Pubkey,
+    pub delay: i64,
+}
+
+#[event]
+pub struct ProposerChanged {
+    pub timelock: Pubkey,
+    pub proposer: Pubkey,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Delay is outside the allowed range")]
+    InvalidDelay,
+    #[msg("Action has too many accounts or too much data")]
+    ActionTooLarge,
+    #[msg("Execution time is earlier than the delay allows")]
+    EtaTooSoon,
+    #[msg("Action is not queued")]
+    ActionNotQueued,
+    #[msg("Action's delay has not passed")]
+    ActionNotReady,
+    #[msg("Action's grace period has passed")]
+    ActionStale,
+    #[msg("Accounts do not match the queued action")]
+    InvalidActionAccounts,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_execute_only_inside_their_window() {
+        let mut action = QueuedAction {
+            timelock: Pubkey::default(),
+            id: 0,
+            program_id: Pubkey::default(),
+            accounts: vec![],
+            data: vec![],
+            queued_at: 0,
+            eta: 1_000,
+            status: ActionStatus::Queued,
+        };
+        assert!(action.check_ready(999).is_err());
+        assert!(action.check_ready(1_000).is_ok());
+        assert!(action.check_ready(1_000 + GRACE_PERIOD).is_ok());
+        assert!(action.check_ready(1_001 + GRACE_PERIOD).is_err());
+
+        action.status = ActionStatus::Executed;
+        assert!(action.check_ready(1_000).is_err());
+    }
+}
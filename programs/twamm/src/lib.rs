@@ -0,0 +1,816 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+use common::crank;
+use math::U256;
+use vault::program::Vault;
+use vault::{BatchSwapStep, PoolRegistration, VaultState};
+use weighted_pool::Pool as WeightedPool;
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("TWAMMJrVEXF88yJvuDwVQ3kS1JgB7aaRbE9nupRtpQX");
+
+/// Orders end on multiples of this many seconds, so that execution only
+/// has to settle expiries at these boundaries
+pub const ORDER_INTERVAL: i64 = 3_600;
+
+/// Longest order, in intervals (~1 year)
+pub const MAX_ORDER_INTERVALS: i64 = 24 * 365;
+
+/// Fixed‑point scale of sell rates (tokens per second)
+pub const RATE_PRECISION: u128 = 1_000_000;
+
+/// Fixed‑point scale of proceeds per unit of sell rate
+pub const EARNINGS_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Time‑weighted AMM over a two‑token weighted pool. A long‑term order
+/// deposits the tokens to sell and streams them at a constant rate until
+/// an interval boundary; every order selling the same token forms one
+/// order pool with a combined sell rate.
+///
+/// `execute` – a permissionless crank – virtually sells everything that
+/// has streamed since the last execution: the two directions are first
+/// matched against each other at the pool's spot price, and only the
+/// excess is swapped through `vault::batch_swap`, with the ["twamm-
+/// authority", twamm] PDA as user. Proceeds are shared pro rata to sell
+/// rate through a per‑direction proceeds‑per‑rate accumulator, which an
+/// order's expiry bucket snapshots when execution crosses its end.
+/// Placing and cancelling orders change the sell rates, so they need an
+/// execution up to the current time first (e.g. in the same transaction).
+#[program]
+pub mod twamm {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Open the TWAMM of a two‑token weighted pool – anyone
+    ---------------------------------------------------------------- */
+    pub fn initialize_twamm(ctx: Context<InitializeTwamm>) -> Result<()> {
+        let registration = &ctx.accounts.pool_registration;
+        require!(
+            registration.tokens.len() == 2 && ctx.accounts.pool.weights.len() == 2,
+            ErrorCode::NotTwoTokenPool
+        );
+
+        let twamm = &mut ctx.accounts.twamm;
+        twamm.vault             = ctx.accounts.pool.vault;
+        twamm.pool              = ctx.accounts.pool.key();
+        twamm.pool_registration = registration.key();
+        twamm.pool_id           = ctx.accounts.pool.pool_id;
+        twamm.mints             = [ctx.accounts.mint_0.key(), ctx.accounts.mint_1.key()];
+        twamm.sell_rate         = [0; 2];
+        twamm.earnings_per_rate = [0; 2];
+        twamm.last_execution    = Clock::get()?.unix_timestamp;
+        twamm.authority_bump    = ctx.bumps.twamm_authority;
+
+        emit!(TwammInitialized { twamm: twamm.key(), pool: twamm.pool, mints: twamm.mints });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Long‑term order: sell `amount` of token `side` (0 or 1) evenly
+       from now until `end`, a future multiple of ORDER_INTERVAL
+       The TWAMM must have executed up to now.
+    ---------------------------------------------------------------- */
+    pub fn place_order(ctx: Context<PlaceOrder>, id: u64, side: u8, amount: u64, end: i64) -> Result<()> {
+        require!(side < 2, ErrorCode::InvalidSide);
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            end > now && end % ORDER_INTERVAL == 0 && end - now <= MAX_ORDER_INTERVALS * ORDER_INTERVAL,
+            ErrorCode::InvalidOrderEnd
+        );
+        let twamm = &mut ctx.accounts.twamm;
+        require!(twamm.last_execution == now, ErrorCode::NotExecuted);
+        let s = side as usize;
+        let rate = sell_rate(amount, end - now)?;
+
+        twamm.sell_rate[s] = twamm.sell_rate[s].checked_add(rate).ok_or(ErrorCode::MathOverflow)?;
+        let expiry = &mut ctx.accounts.expiry;
+        expiry.twamm = twamm.key();
+        expiry.end   = end;
+        expiry.rate_ending[s] = expiry.rate_ending[s].checked_add(rate).ok_or(ErrorCode::MathOverflow)?;
+
+        let order = &mut ctx.accounts.order;
+        order.twamm                  = twamm.key();
+        order.owner                  = ctx.accounts.owner.key();
+        order.id                     = id;
+        order.side                   = side;
+        order.sell_rate              = rate;
+        order.end                    = end;
+        order.earnings_per_rate_paid = twamm.earnings_per_rate[s];
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.owner_token_in.to_account_info(),
+                    to:        ctx.accounts.twamm_token_in.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(LongTermOrderPlaced {
+            twamm: twamm.key(),
+            order: order.key(),
+            owner: order.owner,
+            side,
+            amount,
+            sell_rate: rate,
+            end,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Execute – anyone; sells what streamed since the last execution
+       and stops at the next interval boundary (call again to go on)
+       `expiry` is that boundary's bucket address, initialised or not.
+       remaining_accounts: when an excess is left to swap, batch swap
+       accounts with assets [mint_0, mint_1], the TWAMM's token
+       accounts as the user side, and the step group for the excess
+    ---------------------------------------------------------------- */
+    pub fn execute<'info>(ctx: Context<'_, '_, 'info, 'info, Execute<'info>>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let twamm = &ctx.accounts.twamm;
+        let from = twamm.last_execution;
+        require!(now > from, ErrorCode::NothingToCrank);
+        let boundary = next_boundary(from);
+        let to = now.min(boundary);
+
+        let elapsed = (to - from) as u128;
+        let mut sold = [0u64; 2];
+        for (s, rate) in twamm.sell_rate.iter().enumerate() {
+            sold[s] = u64::try_from(mul_div(*rate, elapsed, RATE_PRECISION)?).map_err(|_| ErrorCode::MathOverflow)?;
+        }
+        let registration = &ctx.accounts.pool_registration;
+        let balances = [
+            pool_balance(&ctx.accounts.pool_token_0, registration)?,
+            pool_balance(&ctx.accounts.pool_token_1, registration)?,
+        ];
+        let weights = [ctx.accounts.pool.weights[0], ctx.accounts.pool.weights[1]];
+        let Flows { mut proceeds, net_side, net_amount } = match_flows(sold, balances, weights)?;
+
+        if net_amount > 0 {
+            proceeds[net_side] = proceeds[net_side]
+                .checked_add(ctx.accounts.swap_excess(ctx.remaining_accounts, net_side, net_amount)?)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        let twamm_key = ctx.accounts.twamm.key();
+        let twamm = &mut ctx.accounts.twamm;
+        for (s, amount) in proceeds.iter().enumerate() {
+            if twamm.sell_rate[s] > 0 {
+                let per_rate = mul_div(*amount as u128, EARNINGS_PRECISION, twamm.sell_rate[s])?;
+                // only differences of the accumulator are ever used
+                twamm.earnings_per_rate[s] = twamm.earnings_per_rate[s].wrapping_add(per_rate);
+            }
+        }
+        if to == boundary {
+            let (address, _) =
+                Pubkey::find_program_address(&[b"twamm-expiry", twamm_key.as_ref(), &boundary.to_le_bytes()], &crate::ID);
+            require_keys_eq!(ctx.accounts.expiry.key(), address, ErrorCode::InvalidExpiry);
+            // no bucket: no order ends here
+            if ctx.accounts.expiry.owner == &crate::ID {
+                let mut data = ctx.accounts.expiry.try_borrow_mut_data()?;
+                let mut expiry = ExpiryBucket::try_deserialize(&mut &data[..])?;
+                for s in 0..2 {
+                    twamm.sell_rate[s] = twamm.sell_rate[s]
+                        .checked_sub(expiry.rate_ending[s])
+                        .ok_or(ErrorCode::MathOverflow)?;
+                }
+                expiry.earnings_per_rate = twamm.earnings_per_rate;
+                expiry.crossed = true;
+                expiry.try_serialize(&mut &mut data[..])?;
+            }
+        }
+        twamm.last_execution = to;
+
+        let bounty = crank::pay_bounty(&twamm.to_account_info(), &ctx.accounts.cranker)?;
+        emit!(TwammExecuted {
+            twamm: twamm_key,
+            from,
+            to,
+            sold,
+            proceeds,
+            swapped_side: net_side as u8,
+            swapped_in: net_amount,
+            cranker: ctx.accounts.cranker.key(),
+            bounty,
+        });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Withdraw an order's proceeds so far – owner only
+       Once execution has crossed the order's end this settles it and
+       closes the order.
+    ---------------------------------------------------------------- */
+    pub fn withdraw_proceeds(ctx: Context<WithdrawProceeds>) -> Result<()> {
+        let order = &ctx.accounts.order;
+        let expiry = &ctx.accounts.expiry;
+        let s = order.side as usize;
+        let earnings_per_rate = if expiry.crossed {
+            expiry.earnings_per_rate[s]
+        } else {
+            ctx.accounts.twamm.earnings_per_rate[s]
+        };
+        let amount = earned(order, earnings_per_rate)?;
+        ctx.accounts.order.earnings_per_rate_paid = earnings_per_rate;
+        ctx.accounts.pay(ctx.accounts.twamm_token_out.to_account_info(), ctx.accounts.owner_token_out.to_account_info(), amount)?;
+
+        let settled = expiry.crossed;
+        emit!(ProceedsWithdrawn { order: ctx.accounts.order.key(), owner: ctx.accounts.owner.key(), amount, settled });
+        if settled {
+            ctx.accounts.order.close(ctx.accounts.owner.to_account_info())?;
+        }
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Cancel an active order – owner only
+       Pays the proceeds so far and refunds what has not streamed yet.
+       The TWAMM must have executed up to now.
+    ---------------------------------------------------------------- */
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let order = &ctx.accounts.order;
+        require!(now < order.end, ErrorCode::OrderEnded);
+        require!(ctx.accounts.twamm.last_execution == now, ErrorCode::NotExecuted);
+        let s = order.side as usize;
+        let proceeds = earned(order, ctx.accounts.twamm.earnings_per_rate[s])?;
+        let refund = u64::try_from(mul_div(order.sell_rate, (order.end - now) as u128, RATE_PRECISION)?)
+            .map_err(|_| ErrorCode::MathOverflow)?;
+
+        let rate = order.sell_rate;
+        let twamm = &mut ctx.accounts.twamm;
+        twamm.sell_rate[s] = twamm.sell_rate[s].checked_sub(rate).ok_or(ErrorCode::MathOverflow)?;
+        let expiry = &mut ctx.accounts.expiry;
+        expiry.rate_ending[s] = expiry.rate_ending[s].checked_sub(rate).ok_or(ErrorCode::MathOverflow)?;
+
+        let accounts = &ctx.accounts;
+        accounts.pay(accounts.twamm_token_out.to_account_info(), accounts.owner_token_out.to_account_info(), proceeds)?;
+        accounts.pay(accounts.twamm_token_in.to_account_info(), accounts.owner_token_in.to_account_info(), refund)?;
+
+        emit!(LongTermOrderCancelled { order: order.key(), owner: order.owner, proceeds, refund });
+        Ok(())
+    }
+}
+
+/// First interval boundary after `t`.
+pub fn next_boundary(t: i64) -> i64 {
+    (t.div_euclid(ORDER_INTERVAL) + 1) * ORDER_INTERVAL
+}
+
+/// Sell rate streaming `amount` over `duration` seconds, rounded down so
+/// that an order never sells more than it deposited.
+pub fn sell_rate(amount: u64, duration: i64) -> Result<u128> {
+    let rate = (amount as u128 * RATE_PRECISION) / duration as u128;
+    require!(rate > 0, ErrorCode::ZeroAmount);
+    Ok(rate)
+}
+
+/// Proceeds an order has earned since it was last paid, given the
+/// direction's accumulator.
+pub fn earned(order: &LongTermOrder, earnings_per_rate: u128) -> Result<u64> {
+    let delta = earnings_per_rate.wrapping_sub(order.earnings_per_rate_paid);
+    u64::try_from(mul_div(order.sell_rate, delta, EARNINGS_PRECISION)?).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// The outcome of matching one execution's flows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Flows {
+    /// Proceeds of each direction from matching alone: token 1 for the
+    /// sellers of token 0, and token 0 for those of token 1
+    pub proceeds: [u64; 2],
+    /// Direction with an unmatched excess
+    pub net_side: usize,
+    /// Excess to swap through the pool
+    pub net_amount: u64,
+}
+
+/// Match `sold` tokens of both directions against each other at the
+/// pool's spot price (token 1 per token 0 = b1·w0 / (b0·w1)); what is left
+/// of the larger side must be swapped. Matching rounds against the side
+/// being paid, so it never hands out more than was sold.
+pub fn match_flows(sold: [u64; 2], balances: [u64; 2], weights: [u128; 2]) -> Result<Flows> {
+    let den = |i: usize| U256::from(balances[i]) * U256::from(weights[1 - i]);
+    let num = |i: usize| U256::from(balances[1 - i]) * U256::from(weights[i]);
+    require!(!den(0).is_zero() && !den(1).is_zero(), ErrorCode::MathOverflow);
+    // value of each side's sale in the other token
+    let value = |i: usize| -> Result<u64> {
+        u64::try_from(U256::from(sold[i]) * num(i) / den(i)).map_err(|_| ErrorCode::MathOverflow.into())
+    };
+    let value_0 = value(0)?;
+    if value_0 >= sold[1] {
+        // all of side 1 matched; side 0 has an excess
+        let matched_0 = value(1)?;
+        Ok(Flows { proceeds: [sold[1], matched_0], net_side: 0, net_amount: sold[0] - matched_0 })
+    } else {
+        Ok(Flows { proceeds: [value_0, sold[0]], net_side: 1, net_amount: sold[1] - value_0 })
+    }
+}
+
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    u128::try_from(U256::from(a) * U256::from(b) / U256::from(c)).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Cash plus managed balance of a pool token, as the pool prices it.
+fn pool_balance(custody: &TokenAccount, registration: &PoolRegistration) -> Result<u64> {
+    custody
+        .amount
+        .checked_add(registration.managed_balance(&custody.mint))
+        .ok_or(ErrorCode::MathOverflow.into())
+}
+
+impl<'info> Execute<'info> {
+    /// Swap `amount` of the `side` token for the other through the Vault;
+    /// returns the amount received.
+    fn swap_excess(&mut self, rem: &[AccountInfo<'info>], side: usize, amount: u64) -> Result<u64> {
+        require!(
+            rem.len() > 4 && rem[0].key() == self.twamm_token_0.key() && rem[2].key() == self.twamm_token_1.key(),
+            ErrorCode::InvalidSwapAccounts
+        );
+        let mut limits = vec![0i64; 2];
+        limits[side] = i64::try_from(amount).map_err(|_| ErrorCode::MathOverflow)?;
+        let step = BatchSwapStep {
+            pool_id:         self.twamm.pool_id,
+            asset_in_index:  side as u8,
+            asset_out_index: 1 - side as u8,
+            amount,
+        };
+        let before = self.out_balance(side)?;
+
+        let twamm_key = self.twamm.key();
+        let bump_arr  = [self.twamm.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"twamm-authority", twamm_key.as_ref(), &bump_arr];
+        vault::cpi::batch_swap(
+            CpiContext::new_with_signer(
+                self.vault_program.to_account_info(),
+                vault::cpi::accounts::BatchSwap {
+                    vault_state:      self.vault_state.to_account_info(),
+                    vault_authority:  self.vault_authority.clone(),
+                    user:             self.twamm_authority.clone(),
+                    sender:           self.twamm_authority.clone(),
+                    relayer_approval: None,
+                    permit_nonce:     None,
+                    instructions:     None,
+                    system_program:   None,
+                    fees_collector:   self.fees_collector.clone(),
+                    token_program:    self.token_program.to_account_info(),
+                },
+                &[seed_slice],
+            )
+            .with_remaining_accounts(rem.to_vec()),
+            vec![step],
+            limits,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        self.twamm_token_0.reload()?;
+        self.twamm_token_1.reload()?;
+        Ok(self.out_balance(side)?.saturating_sub(before))
+    }
+
+    fn out_balance(&self, side: usize) -> Result<u64> {
+        Ok(if side == 0 { self.twamm_token_1.amount } else { self.twamm_token_0.amount })
+    }
+}
+
+impl<'info> WithdrawProceeds<'info> {
+    fn pay(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_from_twamm(&self.twamm, &self.twamm_authority, &self.token_program, from, to, amount)
+    }
+}
+
+impl<'info> CancelOrder<'info> {
+    fn pay(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        transfer_from_twamm(&self.twamm, &self.twamm_authority, &self.token_program, from, to, amount)
+    }
+}
+
+/// TWAMM token account → an owner's, signed by the TWAMM authority.
+fn transfer_from_twamm<'info>(
+    twamm: &Account<'info, Twamm>,
+    authority: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    from: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let twamm_key = twamm.key();
+    let bump_arr  = [twamm.authority_bump];
+    let seed_slice: &[&[u8]] = &[b"twamm-authority", twamm_key.as_ref(), &bump_arr];
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer { from, to, authority: authority.clone() },
+            &[seed_slice],
+        ),
+        amount,
+    )
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializeTwamm<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub pool: Box<Account<'info, WeightedPool>>,
+
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID,
+        constraint = pool_registration.pool == pool.key() @ ErrorCode::NotTwoTokenPool
+    )]
+    pub pool_registration: Box<Account<'info, PoolRegistration>>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Twamm::INIT_SPACE,
+        seeds = [b"twamm", pool.key().as_ref()],
+        bump
+    )]
+    pub twamm: Box<Account<'info, Twamm>>,
+
+    /// CHECK: PDA trading for the TWAMM and holding its tokens
+    #[account(seeds = [b"twamm-authority", twamm.key().as_ref()], bump)]
+    pub twamm_authority: AccountInfo<'info>,
+
+    #[account(address = pool_registration.tokens[0])]
+    pub mint_0: Box<Account<'info, Mint>>,
+    #[account(address = pool_registration.tokens[1])]
+    pub mint_1: Box<Account<'info, Mint>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), mint_0.key().as_ref()],
+        bump,
+        token::mint = mint_0,
+        token::authority = twamm_authority
+    )]
+    pub twamm_token_0: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), mint_1.key().as_ref()],
+        bump,
+        token::mint = mint_1,
+        token::authority = twamm_authority
+    )]
+    pub twamm_token_1: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u64, side: u8, amount: u64, end: i64)]
+pub struct PlaceOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub twamm: Box<Account<'info, Twamm>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + LongTermOrder::INIT_SPACE,
+        seeds = [b"twamm-order", twamm.key().as_ref(), owner.key().as_ref(), &id.to_le_bytes()],
+        bump
+    )]
+    pub order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + ExpiryBucket::INIT_SPACE,
+        seeds = [b"twamm-expiry", twamm.key().as_ref(), &end.to_le_bytes()],
+        bump
+    )]
+    pub expiry: Box<Account<'info, ExpiryBucket>>,
+
+    #[account(mut, token::authority = owner)]
+    pub owner_token_in: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), owner_token_in.mint.as_ref()],
+        bump,
+        constraint = twamm.mints.get(side as usize) == Some(&owner_token_in.mint) @ ErrorCode::InvalidSide
+    )]
+    pub twamm_token_in: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    /// Anyone; receives the crank bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    #[account(mut)]
+    pub twamm: Box<Account<'info, Twamm>>,
+
+    /// CHECK: ["twamm-expiry", twamm, next boundary] – checked in the
+    /// handler when execution reaches that boundary; may be uninitialised
+    #[account(mut)]
+    pub expiry: AccountInfo<'info>,
+
+    #[account(address = twamm.pool)]
+    pub pool: Box<Account<'info, WeightedPool>>,
+
+    #[account(address = twamm.pool_registration)]
+    pub pool_registration: Box<Account<'info, PoolRegistration>>,
+
+    #[account(address = pool_registration.token_accounts[0])]
+    pub pool_token_0: Box<Account<'info, TokenAccount>>,
+    #[account(address = pool_registration.token_accounts[1])]
+    pub pool_token_1: Box<Account<'info, TokenAccount>>,
+
+    /// CHECK: PDA trading for the TWAMM; the batch swap's user and sender
+    #[account(mut, seeds = [b"twamm-authority", twamm.key().as_ref()], bump = twamm.authority_bump)]
+    pub twamm_authority: AccountInfo<'info>,
+
+    #[account(mut, seeds = [b"twamm-tokens", twamm.key().as_ref(), twamm.mints[0].as_ref()], bump)]
+    pub twamm_token_0: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"twamm-tokens", twamm.key().as_ref(), twamm.mints[1].as_ref()], bump)]
+    pub twamm_token_1: Box<Account<'info, TokenAccount>>,
+
+    #[account(address = twamm.vault)]
+    pub vault_state: Box<Account<'info, VaultState>>,
+
+    /// CHECK: vault authority PDA; checked by the vault
+    #[account(mut)]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: the vault's fees collector; checked by the vault
+    pub fees_collector: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, Vault>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProceeds<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub twamm: Box<Account<'info, Twamm>>,
+
+    #[account(mut, has_one = owner, has_one = twamm)]
+    pub order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(seeds = [b"twamm-expiry", twamm.key().as_ref(), &order.end.to_le_bytes()], bump)]
+    pub expiry: Box<Account<'info, ExpiryBucket>>,
+
+    /// CHECK: PDA holding the TWAMM's tokens
+    #[account(seeds = [b"twamm-authority", twamm.key().as_ref()], bump = twamm.authority_bump)]
+    pub twamm_authority: AccountInfo<'info>,
+
+    /// The token bought by the order
+    #[account(
+        mut,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), twamm.mints[1 - order.side as usize].as_ref()],
+        bump
+    )]
+    pub twamm_token_out: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = twamm_token_out.mint)]
+    pub owner_token_out: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOrder<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub twamm: Box<Account<'info, Twamm>>,
+
+    #[account(mut, close = owner, has_one = owner, has_one = twamm)]
+    pub order: Box<Account<'info, LongTermOrder>>,
+
+    #[account(mut, seeds = [b"twamm-expiry", twamm.key().as_ref(), &order.end.to_le_bytes()], bump)]
+    pub expiry: Box<Account<'info, ExpiryBucket>>,
+
+    /// CHECK: PDA holding the TWAMM's tokens
+    #[account(seeds = [b"twamm-authority", twamm.key().as_ref()], bump = twamm.authority_bump)]
+    pub twamm_authority: AccountInfo<'info>,
+
+    /// The token sold by the order
+    #[account(
+        mut,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), twamm.mints[order.side as usize].as_ref()],
+        bump
+    )]
+    pub twamm_token_in: Box<Account<'info, TokenAccount>>,
+
+    /// The token bought by the order
+    #[account(
+        mut,
+        seeds = [b"twamm-tokens", twamm.key().as_ref(), twamm.mints[1 - order.side as usize].as_ref()],
+        bump
+    )]
+    pub twamm_token_out: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = twamm_token_in.mint)]
+    pub owner_token_in: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut, token::mint = twamm_token_out.mint)]
+    pub owner_token_out: Box<Account<'info, TokenAccount>>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// The TWAMM of a weighted pool, PDA'd by ["twamm", pool]
+#[account]
+#[derive(InitSpace)]
+pub struct Twamm {
+    pub vault: Pubkey,
+    pub pool: Pubkey,
+    pub pool_registration: Pubkey,
+    pub pool_id: [u8; 32],
+    /// The pool's tokens, in pool order; side `i` sells `mints[i]`
+    pub mints: [Pubkey; 2],
+    /// Combined sell rate of each side's active orders (RATE_PRECISION)
+    pub sell_rate: [u128; 2],
+    /// Proceeds per unit of sell rate ever paid to each side
+    /// (EARNINGS_PRECISION, wrapping)
+    pub earnings_per_rate: [u128; 2],
+    /// Everything streamed until this time has been sold
+    pub last_execution: i64,
+    /// Bump of the ["twamm-authority", twamm] PDA
+    pub authority_bump: u8,
+}
+
+/// A long‑term order, PDA'd by ["twamm-order", twamm, owner, id (LE u64)]
+#[account]
+#[derive(InitSpace)]
+pub struct LongTermOrder {
+    pub twamm: Pubkey,
+    pub owner: Pubkey,
+    pub id: u64,
+    /// Index of the token sold
+    pub side: u8,
+    pub sell_rate: u128,
+    /// Interval boundary at which the order stops selling
+    pub end: i64,
+    /// The side's `earnings_per_rate` when the order was last paid
+    pub earnings_per_rate_paid: u128,
+}
+
+/// Orders ending at one boundary, PDA'd by ["twamm-expiry", twamm, end (LE i64)]
+#[account]
+#[derive(InitSpace)]
+pub struct ExpiryBucket {
+    pub twamm: Pubkey,
+    pub end: i64,
+    /// Sell rate leaving each side at `end`
+    pub rate_ending: [u128; 2],
+    /// Each side's `earnings_per_rate` at `end`, once crossed
+    pub earnings_per_rate: [u128; 2],
+    /// Execution has passed `end`
+    pub crossed: bool,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwammInitialized {
+    pub twamm: Pubkey,
+    pub pool: Pubkey,
+    pub mints: [Pubkey; 2],
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LongTermOrderPlaced {
+    pub twamm: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub side: u8,
+    pub amount: u64,
+    pub sell_rate: u128,
+    pub end: i64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwammExecuted {
+    pub twamm: Pubkey,
+    pub from: i64,
+    pub to: i64,
+    /// Tokens each side sold
+    pub sold: [u64; 2],
+    /// Tokens each side received, matched and swapped
+    pub proceeds: [u64; 2],
+    /// Side whose excess went through the pool
+    pub swapped_side: u8,
+    pub swapped_in: u64,
+    pub cranker: Pubkey,
+    pub bounty: u64,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProceedsWithdrawn {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// The order has ended and is closed
+    pub settled: bool,
+}
+
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LongTermOrderCancelled {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub proceeds: u64,
+    /// Tokens not yet sold, returned
+    pub refund: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The TWAMM needs a two‑token weighted pool")]
+    NotTwoTokenPool,
+    #[msg("Side must be 0 or 1, selling that pool token")]
+    InvalidSide,
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Order end must be a future interval boundary within the maximum duration")]
+    InvalidOrderEnd,
+    #[msg("Execute the TWAMM up to the current time first")]
+    NotExecuted,
+    #[msg("Nothing to execute")]
+    NothingToCrank,
+    #[msg("Expiry account is not the next boundary's bucket")]
+    InvalidExpiry,
+    #[msg("Swap assets must be the TWAMM's token accounts")]
+    InvalidSwapAccounts,
+    #[msg("Order has ended; withdraw its proceeds instead")]
+    OrderEnded,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn opposing_flows_match_at_spot_price_before_swapping() {
+        // 80/20 pool pricing token 0 at 1_000·0.8 / (4_000·0.2) = 1 token 1
+        let flows = match_flows([100, 40], [4_000, 1_000], [8 * E18 / 10, 2 * E18 / 10]).unwrap();
+        assert_eq!(flows, Flows { proceeds: [40, 40], net_side: 0, net_amount: 60 });
+
+        // 50/50 pool at 2 token 1 per token 0; side 1 has the excess
+        let flows = match_flows([10, 50], [1_000, 2_000], [E18 / 2, E18 / 2]).unwrap();
+        assert_eq!(flows, Flows { proceeds: [20, 10], net_side: 1, net_amount: 30 });
+    }
+
+    #[test]
+    fn proceeds_are_shared_pro_rata_to_sell_rate() {
+        let rate = |amount| sell_rate(amount, 3_600).unwrap();
+        let (a, b) = (rate(3_600), rate(10_800));
+        let order = |sell_rate| LongTermOrder {
+            twamm: Pubkey::default(),
+            owner: Pubkey::default(),
+            id: 0,
+            side: 0,
+            sell_rate,
+            end: 0,
+            earnings_per_rate_paid: u128::MAX - 5,
+        };
+        // 400 proceeds on a total rate of a + b, across an accumulator wrap
+        let acc = (u128::MAX - 5).wrapping_add(mul_div(400, EARNINGS_PRECISION, a + b).unwrap());
+        assert_eq!(earned(&order(a), acc).unwrap(), 100);
+        assert_eq!(earned(&order(b), acc).unwrap(), 300);
+        assert_eq!(next_boundary(3_600), 7_200);
+        assert_eq!(next_boundary(3_599), 3_600);
+    }
+}
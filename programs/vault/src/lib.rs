@@ -1,4 +1,28 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program::{get_return_data, invoke, invoke_signed};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_lang::Ids;
+use anchor_spl::token_interface::spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use anchor_spl::token_interface::spl_token_2022::extension::confidential_transfer::ConfidentialTransferMint;
+use anchor_spl::token_interface::spl_token_2022::extension::default_account_state::DefaultAccountState;
+use anchor_spl::token_interface::spl_token_2022::extension::non_transferable::NonTransferable;
+use anchor_spl::token_interface::spl_token_2022::extension::permanent_delegate::PermanentDelegate;
+use anchor_spl::token_interface::spl_token_2022::extension::{transfer_hook, BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_interface::spl_token_2022::onchain;
+use anchor_spl::token_interface::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_interface::spl_token_2022::state::{Account as SplAccount, AccountState, Mint as SplMint};
+use anchor_spl::token_interface::{
+    self as token, CloseAccount, GetAccountDataSize, InitializeAccount3, Mint, SetAuthority, TokenAccount,
+    TokenInterface, Transfer,
+};
+use common::{crank, derive_pool_id, pool_authority, pool_interface, seeds, Specialization, MAX_POOL_TOKENS};
+use math::MathError;
 
 declare_id!("CsSfsxZcni7DTeLvxTvzbFsLa3PdvyQCKmakzmXeM2fz");
 
@@ -10,19 +34,1423 @@ pub mod vault {
     pub fn initialize(ctx: Context<Initialize>, owner: Pubkey) -> Result<()> {
         let vault_state = &mut ctx.accounts.vault_state;
         vault_state.owner = owner;
+        vault_state.pending_owner = Pubkey::default();
+        vault_state.creator = ctx.accounts.payer.key();
         vault_state.pool_count = 0;
+        vault_state.registry_tail = 0;
+        vault_state.pause_window_end = Clock::get()?
+            .unix_timestamp
+            .checked_add(VaultState::PAUSE_WINDOW_DURATION)
+            .ok_or(ErrorCode::Overflow)?;
+        vault_state.paused_until = 0;
         Ok(())
     }
 
-    /// Register a new pool by bumping the pool_count
-    pub fn register_pool(ctx: Context<RegisterPool>) -> Result<()> {
+    /// Register a pool: records it in a `PoolRegistration` PDA keyed by its
+    /// PoolId and bumps the pool_count. Returns the PoolId.
+    pub fn register_pool(
+        ctx: Context<RegisterPool>,
+        pool_id: [u8; 32],
+        specialization: Specialization,
+        tokens: Vec<Pubkey>,
+    ) -> Result<[u8; 32]> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(!tokens.is_empty() && tokens.len() <= MAX_POOL_TOKENS, ErrorCode::InvalidTokens);
+        if specialization == Specialization::TwoToken {
+            require!(tokens.len() == 2, ErrorCode::InvalidTokens);
+        }
+        for (i, t) in tokens.iter().enumerate() {
+            require!(!tokens[..i].contains(t), ErrorCode::InvalidTokens);
+        }
+
+        let vault_key = ctx.accounts.vault_state.key();
+        let pool_key  = ctx.accounts.pool.key();
+        let expected  = derive_pool_id(&vault_key, &pool_key, specialization, ctx.accounts.vault_state.pool_count);
+        require!(pool_id == expected, ErrorCode::InvalidPoolId);
+
+        let registration = &mut ctx.accounts.pool_registration;
+        registration.vault          = vault_key;
+        registration.pool_id        = pool_id;
+        registration.pool_program   = *ctx.accounts.pool.owner;
+        registration.pool           = pool_key;
+        registration.specialization = specialization;
+        registration.token_accounts = vec![Pubkey::default(); tokens.len()];
+        registration.asset_managers = vec![Pubkey::default(); tokens.len()];
+        registration.managed        = vec![0; tokens.len()];
+        registration.paused         = false;
+        registration.token_programs = vec![Pubkey::default(); tokens.len()];
+        registration.freeze_authorities = vec![Pubkey::default(); tokens.len()];
+        registration.tokens         = tokens.clone();
+        registration.lp_mint        = ctx.accounts.lp_mint.key();
+
+        emit!(PoolRegistered {
+            vault: vault_key,
+            pool_id,
+            pool: pool_key,
+            pool_program: *ctx.accounts.pool.owner,
+            specialization,
+            tokens,
+        });
+
+        // append to the tail page of the pool registry
+        let tail = ctx.accounts.vault_state.registry_tail;
+        let page = &mut ctx.accounts.registry_page;
+        if page.vault == Pubkey::default() {
+            page.vault = vault_key;
+            page.index = tail;
+        }
+        page.entries.push(RegistryEntry { pool_id, pool: pool_key });
+        if page.entries.len() == PoolRegistryPage::CAPACITY {
+            let next_index = tail.checked_add(1).ok_or(ErrorCode::Overflow)?;
+            page.next = PoolRegistryPage::address(&vault_key, next_index, ctx.program_id);
+            ctx.accounts.vault_state.registry_tail = next_index;
+        }
+
         let vault_state = &mut ctx.accounts.vault_state;
         vault_state.pool_count = vault_state
             .pool_count
             .checked_add(1)
             .ok_or(ErrorCode::Overflow)?;
+        Ok(pool_id)
+    }
+
+    /// Deregister a pool: its LP supply must be zero and every custody
+    /// account empty. Custody accounts are closed, the registration PDA is
+    /// closed, and rent is returned to `authority`.
+    ///
+    /// remaining_accounts: the registration's non‑default `token_accounts`, in
+    /// order, then the token program of a mixed pool's other custody accounts
+    pub fn deregister_pool<'info>(ctx: Context<'_, '_, 'info, 'info, DeregisterPool<'info>>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+
+        // the LP mint is the one recorded at registration
+        let registration = &ctx.accounts.pool_registration;
+        require_keys_eq!(ctx.accounts.lp_mint.key(), registration.lp_mint, ErrorCode::InvalidLpMint);
+        require!(ctx.accounts.lp_mint.supply == 0, ErrorCode::BptSupplyNotZero);
+        require!(registration.managed.iter().all(|m| *m == 0), ErrorCode::TokenBalanceNotZero);
+
+        let custody: Vec<(Pubkey, Pubkey)> = registration
+            .token_accounts
+            .iter()
+            .copied()
+            .zip(registration.token_programs.iter().copied())
+            .filter(|(k, _)| *k != Pubkey::default())
+            .collect();
+        let (rem, programs) = ctx.remaining_accounts.split_at(custody.len().min(ctx.remaining_accounts.len()));
+        require!(rem.len() == custody.len() && programs.len() <= 1, ErrorCode::InvalidTokens);
+
+        let vault_key = ctx.accounts.vault_state.key();
+        let auth_bump = [ctx.bumps.vault_authority];
+        let auth_seeds: &[&[u8]] = &[seeds::VAULT_AUTHORITY, vault_key.as_ref(), &auth_bump];
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        for (token_account, (expected, program)) in rem.iter().zip(custody) {
+            require_keys_eq!(token_account.key(), expected, ErrorCode::InvalidTokens);
+            require!(unpack_token_account(token_account)?.amount == 0, ErrorCode::TokenBalanceNotZero);
+            token::close_account(CpiContext::new_with_signer(
+                token_program_account(&program, &token_prog, programs)?.clone(),
+                CloseAccount {
+                    account:     token_account.clone(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority:   ctx.accounts.vault_authority.to_account_info(),
+                },
+                &[auth_seeds],
+            ))?;
+        }
+
+        // drop it from the registry; pages are not compacted
+        let pool_id = ctx.accounts.pool_registration.pool_id;
+        let entries = &mut ctx.accounts.registry_page.entries;
+        let at = entries
+            .iter()
+            .position(|e| e.pool_id == pool_id)
+            .ok_or(ErrorCode::UnregisteredPool)?;
+        entries.remove(at);
+
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.pool_count = vault_state
+            .pool_count
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+
+    /// List one page of the pool registry (at most
+    /// `PoolRegistryPage::CAPACITY` entries). Pages run from 0 to
+    /// `vault_state.registry_tail`; each links to the next via `next`.
+    pub fn get_pools(ctx: Context<GetPools>, _page: u32) -> Result<Vec<RegistryEntry>> {
+        Ok(ctx.accounts.registry_page.entries.clone())
+    }
+
+    /// Create the custody token account for each given mint of a registered
+    /// pool. Accounts live at ["pool-token", pool_registration, mint]; the
+    /// vault keeps the close authority and hands ownership to the pool
+    /// authority PDA. Mints not yet listed on the registration are appended.
+    /// `asset_managers[i]` may move the i‑th token's idle balance
+    /// (`Pubkey::default()` = unmanaged). A Token‑2022 mint with a transfer
+    /// hook is only accepted when its hook program is on the vault's
+    /// allowlist, as every transfer of it will call that program. Each
+    /// mint's freeze authority is recorded; while the vault rejects
+    /// freezable mints (`set_freeze_authority_policy`), one with a freeze
+    /// authority needs the owner's acknowledgment in the mint overrides.
+    ///
+    /// remaining_accounts: [mint_0, token_account_0, mint_1, token_account_1, …]
+    pub fn register_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RegisterTokens<'info>>,
+        asset_managers: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let rem = ctx.remaining_accounts;
+        require!(!rem.is_empty() && rem.len().is_multiple_of(2), ErrorCode::InvalidTokens);
+        require!(asset_managers.len() * 2 == rem.len(), ErrorCode::InvalidTokens);
+
+        let registration_key = ctx.accounts.pool_registration.key();
+        let owner = pool_authority(
+            &ctx.accounts.pool_registration.pool,
+            &ctx.accounts.pool_registration.pool_program,
+        );
+        let vault_key = ctx.accounts.vault_state.key();
+        let auth_bump = [ctx.bumps.vault_authority];
+        let auth_seeds: &[&[u8]] = &[seeds::VAULT_AUTHORITY, vault_key.as_ref(), &auth_bump];
+        let rent = Rent::get()?;
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        let vault_auth = ctx.accounts.vault_authority.to_account_info();
+        let mut added = TokensRegistered {
+            vault:          vault_key,
+            pool_id:        ctx.accounts.pool_registration.pool_id,
+            tokens:         Vec::with_capacity(rem.len() / 2),
+            token_accounts: Vec::with_capacity(rem.len() / 2),
+        };
+
+        for (pair, manager) in rem.chunks(2).zip(asset_managers) {
+            let (mint, token_account) = (&pair[0], &pair[1]);
+            require_keys_eq!(*mint.owner, token_prog.key(), ErrorCode::InvalidTokens);
+            let overridden = || MintExtensionOverrides::allows(ctx.accounts.mint_extension_overrides.as_ref(), mint.key);
+            if has_dangerous_extension(mint)? {
+                require!(overridden()?, ErrorCode::DangerousMintExtension);
+            }
+            let freezer = freeze_authority(mint)?;
+            if freezer != Pubkey::default() && ctx.accounts.vault_state.reject_freezable_mints {
+                require!(overridden()?, ErrorCode::FreezableMint);
+            }
+            if let Some(hook_program) = transfer_hook_program(mint)? {
+                require!(
+                    TransferHookAllowlist::allows(ctx.accounts.transfer_hook_allowlist.as_ref(), &hook_program)?,
+                    ErrorCode::TransferHookNotAllowed
+                );
+            }
+            let (expected, bump) = Pubkey::find_program_address(
+                &[seeds::POOL_TOKEN, registration_key.as_ref(), mint.key.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(token_account.key(), expected, ErrorCode::InvalidTokens);
+
+            // record it on the registration
+            let registration = &mut ctx.accounts.pool_registration;
+            match registration.tokens.iter().position(|t| t == mint.key) {
+                Some(i) => {
+                    require!(
+                        registration.token_accounts[i] == Pubkey::default(),
+                        ErrorCode::TokenAlreadyRegistered
+                    );
+                    registration.token_accounts[i] = expected;
+                    registration.asset_managers[i] = manager;
+                    registration.token_programs[i] = token_prog.key();
+                    registration.freeze_authorities[i] = freezer;
+                }
+                None => {
+                    require!(registration.tokens.len() < MAX_POOL_TOKENS, ErrorCode::InvalidTokens);
+                    registration.tokens.push(mint.key());
+                    registration.token_accounts.push(expected);
+                    registration.asset_managers.push(manager);
+                    registration.managed.push(0);
+                    registration.token_programs.push(token_prog.key());
+                    registration.freeze_authorities.push(freezer);
+                }
+            }
+
+            // create + initialize under the vault authority, then hand over ownership;
+            // Token‑2022 mints may need room for account extensions
+            let space = token::get_account_data_size(
+                CpiContext::new(token_prog.clone(), GetAccountDataSize { mint: mint.clone() }),
+                &[],
+            )?;
+            let bump_arr = [bump];
+            let account_seeds: &[&[u8]] = &[seeds::POOL_TOKEN, registration_key.as_ref(), mint.key.as_ref(), &bump_arr];
+            system_program::create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to:   token_account.clone(),
+                    },
+                    &[account_seeds],
+                ),
+                rent.minimum_balance(space as usize),
+                space,
+                token_prog.key,
+            )?;
+            token::initialize_account3(CpiContext::new(
+                token_prog.clone(),
+                InitializeAccount3 {
+                    account:   token_account.clone(),
+                    mint:      mint.clone(),
+                    authority: vault_auth.clone(),
+                },
+            ))?;
+            token::set_authority(
+                CpiContext::new_with_signer(
+                    token_prog.clone(),
+                    SetAuthority {
+                        current_authority: vault_auth.clone(),
+                        account_or_mint:   token_account.clone(),
+                    },
+                    &[auth_seeds],
+                ),
+                AuthorityType::CloseAccount,
+                Some(vault_auth.key()),
+            )?;
+            token::set_authority(
+                CpiContext::new_with_signer(
+                    token_prog.clone(),
+                    SetAuthority {
+                        current_authority: vault_auth.clone(),
+                        account_or_mint:   token_account.clone(),
+                    },
+                    &[auth_seeds],
+                ),
+                AuthorityType::AccountOwner,
+                Some(owner),
+            )?;
+            added.tokens.push(mint.key());
+            added.token_accounts.push(expected);
+        }
+        emit!(added);
+        Ok(())
+    }
+
+    /// Remove tokens from a registered pool. Their custody accounts must be
+    /// empty; they are closed and the rent returned to `authority`.
+    ///
+    /// remaining_accounts: [mint_0, token_account_0, mint_1, token_account_1, …],
+    /// then the token program of a mixed pool's other custody accounts
+    pub fn deregister_tokens<'info>(ctx: Context<'_, '_, 'info, 'info, RegisterTokens<'info>>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let (rem, programs) = ctx.remaining_accounts.split_at(ctx.remaining_accounts.len() & !1);
+        require!(!rem.is_empty() && programs.len() <= 1, ErrorCode::InvalidTokens);
+
+        let vault_key = ctx.accounts.vault_state.key();
+        let auth_bump = [ctx.bumps.vault_authority];
+        let auth_seeds: &[&[u8]] = &[seeds::VAULT_AUTHORITY, vault_key.as_ref(), &auth_bump];
+        let token_prog = ctx.accounts.token_program.to_account_info();
+
+        for pair in rem.chunks(2) {
+            let (mint, token_account) = (&pair[0], &pair[1]);
+            let registration = &mut ctx.accounts.pool_registration;
+            let i = registration
+                .tokens
+                .iter()
+                .position(|t| t == mint.key)
+                .ok_or(ErrorCode::TokenNotRegistered)?;
+            require!(registration.managed[i] == 0, ErrorCode::TokenBalanceNotZero);
+
+            if registration.token_accounts[i] != Pubkey::default() {
+                require_keys_eq!(token_account.key(), registration.token_accounts[i], ErrorCode::InvalidTokens);
+                require!(unpack_token_account(token_account)?.amount == 0, ErrorCode::TokenBalanceNotZero);
+                token::close_account(CpiContext::new_with_signer(
+                    token_program_account(&registration.token_programs[i], &token_prog, programs)?.clone(),
+                    CloseAccount {
+                        account:     token_account.clone(),
+                        destination: ctx.accounts.authority.to_account_info(),
+                        authority:   ctx.accounts.vault_authority.to_account_info(),
+                    },
+                    &[auth_seeds],
+                ))?;
+            }
+            registration.tokens.remove(i);
+            registration.token_accounts.remove(i);
+            registration.asset_managers.remove(i);
+            registration.managed.remove(i);
+            registration.token_programs.remove(i);
+            registration.freeze_authorities.remove(i);
+        }
+        Ok(())
+    }
+
+    /// Allow (or stop allowing) Token‑2022 mints whose transfer hook runs
+    /// `hook_program` to be registered; requires the `ManageTokenPolicy`
+    /// role. Tokens already registered are not affected.
+    pub fn set_transfer_hook_program(
+        ctx: Context<SetTransferHookProgram>,
+        hook_program: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageTokenPolicy, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let allowlist = &mut ctx.accounts.transfer_hook_allowlist;
+        allowlist.vault = ctx.accounts.vault_state.key();
+        let listed = allowlist.programs.iter().position(|p| *p == hook_program);
+        match (listed, allowed) {
+            (None, true) => {
+                require!(
+                    allowlist.programs.len() < TransferHookAllowlist::CAPACITY,
+                    ErrorCode::TooManyHookPrograms
+                );
+                allowlist.programs.push(hook_program);
+            }
+            (Some(i), false) => {
+                allowlist.programs.swap_remove(i);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Allow (or stop allowing) `mint` to be registered despite extensions
+    /// that can strand or confiscate pool liquidity – a permanent
+    /// delegate, accounts frozen by default, confidential transfers – or,
+    /// while the vault rejects freezable mints, despite a freeze
+    /// authority; requires the `ManageTokenPolicy` role. Non‑transferable
+    /// mints are never registered.
+    pub fn set_mint_extension_override(
+        ctx: Context<SetMintExtensionOverride>,
+        mint: Pubkey,
+        allowed: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageTokenPolicy, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let overrides = &mut ctx.accounts.mint_extension_overrides;
+        overrides.vault = ctx.accounts.vault_state.key();
+        let listed = overrides.mints.iter().position(|m| *m == mint);
+        match (listed, allowed) {
+            (None, true) => {
+                require!(
+                    overrides.mints.len() < MintExtensionOverrides::CAPACITY,
+                    ErrorCode::TooManyMintOverrides
+                );
+                overrides.mints.push(mint);
+            }
+            (Some(i), false) => {
+                overrides.mints.swap_remove(i);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Asset manager: move `amount` of idle cash out of the pool's custody
+    /// account into `destination` (e.g. a lending market). The pool program
+    /// signs the transfer; the pool keeps pricing against cash + managed.
+    /// The asset manager authority PDA asks for it, and signs nothing else.
+    pub fn withdraw_managed(ctx: Context<ManagePoolBalance>, amount: u64) -> Result<()> {
+        let i = ctx.accounts.managed_index()?;
+        let vault_key = ctx.accounts.vault_state.key();
+        let bump_arr = [ctx.bumps.asset_manager_authority];
+        let seed_slice: &[&[u8]] = &[seeds::ASSET_MANAGER_AUTHORITY, vault_key.as_ref(), &bump_arr];
+        let registration = &ctx.accounts.pool_registration;
+        let ix = pool_interface::managed_transfer(
+            registration.pool_program,
+            &pool_interface::ManagedTransferAccounts {
+                pool:                    registration.pool,
+                asset_manager_authority: ctx.accounts.asset_manager_authority.key(),
+                pool_registration:       registration.key(),
+                from:                    ctx.accounts.custody.key(),
+                to:                      ctx.accounts.counterparty.key(),
+                pool_authority:          ctx.accounts.pool_authority.key(),
+                token_program:           ctx.accounts.token_program.key(),
+            },
+            amount,
+        );
+        invoke_signed(
+            &ix,
+            &[
+                ctx.accounts.pool.to_account_info(),
+                ctx.accounts.asset_manager_authority.to_account_info(),
+                ctx.accounts.pool_registration.to_account_info(),
+                ctx.accounts.custody.to_account_info(),
+                ctx.accounts.counterparty.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_program.to_account_info(),
+            ],
+            &[seed_slice],
+        )?;
+
+        let registration = &mut ctx.accounts.pool_registration;
+        registration.managed[i] = registration.managed[i].checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        ctx.accounts.emit_managed(i, -i128::from(amount), i128::from(amount))
+    }
+
+    /// Asset manager: return `amount` from the managed side to the pool's
+    /// custody account, paid from the manager's `counterparty` account.
+    pub fn deposit_managed(ctx: Context<ManagePoolBalance>, amount: u64) -> Result<()> {
+        let i = ctx.accounts.managed_index()?;
+        let registration = &mut ctx.accounts.pool_registration;
+        registration.managed[i] = registration.managed[i]
+            .checked_sub(amount)
+            .ok_or(ErrorCode::ManagedBalanceTooLow)?;
+        transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.counterparty.to_account_info(),
+                    to:        ctx.accounts.custody.to_account_info(),
+                    authority: ctx.accounts.manager.to_account_info(),
+                },
+            ),
+            None,
+            &[],
+            amount,
+        )?;
+        ctx.accounts.emit_managed(i, i128::from(amount), -i128::from(amount))
+    }
+
+    /// Asset manager: report the current value of the managed balance
+    /// (yield earned or losses taken). No tokens move.
+    pub fn update_managed_balance(ctx: Context<ManagePoolBalance>, managed_balance: u64) -> Result<()> {
+        let i = ctx.accounts.managed_index()?;
+        let registration = &mut ctx.accounts.pool_registration;
+        let delta = i128::from(managed_balance) - i128::from(registration.managed[i]);
+        registration.managed[i] = managed_balance;
+        ctx.accounts.emit_managed(i, 0, delta)
+    }
+
+    /// Create the vault's Authorizer with the vault owner as its admin and no
+    /// grants. The owner's one privilege: there is no Authorizer to ask yet.
+    pub fn initialize_authorizer(ctx: Context<InitializeAuthorizer>) -> Result<()> {
+        let authorizer = &mut ctx.accounts.authorizer;
+        authorizer.vault  = ctx.accounts.vault_state.key();
+        authorizer.admin  = ctx.accounts.owner.key();
+        authorizer.grants = Vec::new();
+        Ok(())
+    }
+
+    /// Allow `account` to perform `action`
+    pub fn grant_role(ctx: Context<ManageRoles>, action: Action, account: Pubkey) -> Result<()> {
+        let authorizer = &mut ctx.accounts.authorizer;
+        if authorizer.can_perform(action, &account) {
+            return Ok(());
+        }
+        require!(authorizer.grants.len() < Authorizer::MAX_GRANTS, ErrorCode::TooManyGrants);
+        authorizer.grants.push(Grant { action, account });
+        Ok(())
+    }
+
+    /// Revoke a previous grant of `action` to `account`
+    pub fn revoke_role(ctx: Context<ManageRoles>, action: Action, account: Pubkey) -> Result<()> {
+        let authorizer = &mut ctx.accounts.authorizer;
+        let before = authorizer.grants.len();
+        authorizer.grants.retain(|g| !(g.action == action && g.account == account));
+        require!(authorizer.grants.len() < before, ErrorCode::GrantNotFound);
+        Ok(())
+    }
+
+    /// Hand the Authorizer admin role (grant/revoke) to another key, e.g. a DAO
+    pub fn set_authorizer_admin(ctx: Context<ManageRoles>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.authorizer.admin = new_admin;
+        Ok(())
+    }
+
+    /// Guardian halt of swaps and joins. Only possible inside the pause window,
+    /// and the pause lapses on its own after `MAX_PAUSE_DURATION`.
+    pub fn emergency_pause(ctx: Context<SetPaused>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::Pause, &ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        let now = Clock::get()?.unix_timestamp;
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(now < vault_state.pause_window_end, ErrorCode::PauseWindowExpired);
+        vault_state.paused_until = now
+            .checked_add(VaultState::MAX_PAUSE_DURATION)
+            .ok_or(ErrorCode::Overflow)?;
+        emit!(PoolPaused {
+            vault:        vault_state.key(),
+            pool_id:      None,
+            guardian:     ctx.accounts.guardian.key(),
+            paused_until: vault_state.paused_until,
+        });
+        Ok(())
+    }
+
+    /// Lift an emergency pause early
+    pub fn unpause(ctx: Context<SetPaused>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::Pause, &ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault_state.paused_until = 0;
+        emit!(PoolPaused {
+            vault:        ctx.accounts.vault_state.key(),
+            pool_id:      None,
+            guardian:     ctx.accounts.guardian.key(),
+            paused_until: 0,
+        });
+        Ok(())
+    }
+
+    /// Crank: clear a vault‑wide pause that has lapsed, paying the sender
+    /// the crank bounty. Lapsed pauses already stop blocking operations;
+    /// this only brings the stored state (and indexers) up to date.
+    pub fn expire_pause(ctx: Context<ExpirePause>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vault_state = &mut ctx.accounts.vault_state;
+        require!(
+            vault_state.paused_until != 0 && !vault_state.is_paused(now),
+            ErrorCode::NothingToCrank
+        );
+        vault_state.paused_until = 0;
+        let bounty = crank::pay_bounty(&vault_state.to_account_info(), &ctx.accounts.cranker)?;
+        emit!(PauseExpired {
+            vault:   vault_state.key(),
+            cranker: ctx.accounts.cranker.key(),
+            bounty,
+        });
+        Ok(())
+    }
+
+    /// Guardian halt (or resume) of swaps and joins on a single pool, e.g.
+    /// a compromised one. Unlike the vault‑wide pause this has no window
+    /// and does not lapse; exits stay open.
+    pub fn set_pool_paused(ctx: Context<SetPoolPaused>, paused: bool) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::Pause, &ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        let registration = &mut ctx.accounts.pool_registration;
+        registration.paused = paused;
+        emit!(PoolPaused {
+            vault:        ctx.accounts.vault_state.key(),
+            pool_id:      Some(registration.pool_id),
+            guardian:     ctx.accounts.guardian.key(),
+            paused_until: if paused { i64::MAX } else { 0 },
+        });
+        Ok(())
+    }
+
+    /// Propose `new_owner` as the vault's owner; requires the
+    /// `TransferOwnership` role. Takes effect once they call
+    /// `accept_ownership`; proposing `Pubkey::default()` cancels.
+    pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::TransferOwnership, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault_state.pending_owner = new_owner;
+        Ok(())
+    }
+
+    /// Refuse (or again accept) registering mints that have a freeze
+    /// authority, which could freeze a pool's custody account; requires the
+    /// `ManageTokenPolicy` role, whose holders acknowledge individual mints
+    /// with `set_mint_extension_override`. Tokens already registered are
+    /// not affected.
+    pub fn set_freeze_authority_policy(ctx: Context<SetFreezeAuthorityPolicy>, reject: bool) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::ManageTokenPolicy, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault_state.reject_freezable_mints = reject;
+        Ok(())
+    }
+
+    /// Complete a transfer started by `transfer_ownership`
+    pub fn accept_ownership(ctx: Context<AcceptOwnership>) -> Result<()> {
+        let vault_state = &mut ctx.accounts.vault_state;
+        vault_state.owner = vault_state.pending_owner;
+        vault_state.pending_owner = Pubkey::default();
+        Ok(())
+    }
+
+    /// Create the vault's ProtocolFeesCollector with zero protocol fees;
+    /// requires the `SetProtocolFees` role
+    pub fn initialize_fees_collector(ctx: Context<InitializeFeesCollector>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::SetProtocolFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let collector = &mut ctx.accounts.fees_collector;
+        collector.vault                     = ctx.accounts.vault_state.key();
+        collector.swap_fee_percentage       = 0;
+        collector.flash_loan_fee_percentage = 0;
+        Ok(())
+    }
+
+    /// Create the collector's fee token account for `mint` (permissionless)
+    pub fn create_fee_account(_ctx: Context<CreateFeeAccount>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Set the protocol's share of swap fees and the flash‑loan fee (18‑dec)
+    pub fn set_protocol_fee_percentages(
+        ctx: Context<SetProtocolFeePercentages>,
+        swap_fee_percentage: u64,
+        flash_loan_fee_percentage: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::SetProtocolFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            swap_fee_percentage <= ProtocolFeesCollector::MAX_SWAP_FEE_PERCENTAGE,
+            ErrorCode::ProtocolFeeTooHigh
+        );
+        require!(
+            flash_loan_fee_percentage <= ProtocolFeesCollector::MAX_FLASH_LOAN_FEE_PERCENTAGE,
+            ErrorCode::ProtocolFeeTooHigh
+        );
+        let collector = &mut ctx.accounts.fees_collector;
+        collector.swap_fee_percentage       = swap_fee_percentage;
+        collector.flash_loan_fee_percentage = flash_loan_fee_percentage;
+        Ok(())
+    }
+
+    /// Sweep collected fees of one token to `recipient`
+    ///
+    /// remaining_accounts: the mint's transfer‑hook accounts, if it has a hook
+    pub fn withdraw_collected_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawCollectedFees<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::CollectFees, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let vault_key = ctx.accounts.vault_state.key();
+        let bump_arr  = [ctx.bumps.fees_collector];
+        let seed_slice: &[&[u8]] = &[seeds::FEES_COLLECTOR, vault_key.as_ref(), &bump_arr];
+        let signer_seeds = &[seed_slice];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.fee_account.to_account_info(),
+            to:        ctx.accounts.recipient.to_account_info(),
+            authority: ctx.accounts.fees_collector.to_account_info(),
+        };
+        transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+                signer_seeds,
+            ),
+            Some(&ctx.accounts.mint.to_account_info()),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+        emit!(FeesCollected {
+            vault:     vault_key,
+            mint:      ctx.accounts.fee_account.mint,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Approve (or revoke) `relayer` to act on the signer's behalf in vault
+    /// operations. Relayers move funds through the vault authority, which the
+    /// user must have set as SPL delegate on the token accounts involved.
+    pub fn set_relayer_approval(
+        ctx: Context<SetRelayerApproval>,
+        relayer: Pubkey,
+        approved: bool,
+    ) -> Result<()> {
+        let approval = &mut ctx.accounts.relayer_approval;
+        approval.vault    = ctx.accounts.vault_state.key();
+        approval.user     = ctx.accounts.user.key();
+        approval.relayer  = relayer;
+        approval.approved = approved;
+        Ok(())
+    }
+
+    /// Execute a sequence of exact‑in swaps across one or more pools, settling
+    /// only the net amount of each asset with the user.
+    ///
+    /// `limits[i]` bounds the net delta of asset `i` (positive = max the user
+    /// pays in, negative = min the user receives). Returns the net deltas.
+    ///
+    /// remaining_accounts:
+    ///   [user_tok_0, vault_tok_0, …, user_tok_{A-1}, vault_tok_{A-1},   (one pair per asset)
+    ///    pool_registration, pool_program, pool, pool_tok_in,            (one group per step)
+    ///    pool_tok_out, pool_authority, protocol_fee_tok_in,
+    ///    [pool_tok_0, …, pool_tok_{N-1}],                               (General pools only)
+    ///    …,
+    ///    [mint_0, …, mint_{A-1}, hook accounts…]]                       (optional)
+    ///
+    /// A pool's specialization decides how many balances it is shown:
+    /// General pools get every custody account, the others only the pair swapped.
+    ///
+    /// With the asset mints, every transfer is checked and each step's pool
+    /// is passed `[mint_in, mint_out, hook accounts…]` after its balances;
+    /// Token‑2022 needs them for mints with transfer fees or hooks. The hook
+    /// accounts are every hooked asset's hook program and extra accounts. Transfer fees are borne by
+    /// the user: pulls are grossed up so the vault side receives what the
+    /// step needs, and the deltas count what the user actually pays and
+    /// receives. Assets of the token program the context does not name
+    /// (pools mixing SPL Token and Token‑2022) need the mints, with that
+    /// program among the hook accounts.
+    ///
+    /// `sender` is either `user`, a relayer `user` has approved, or a relayer
+    /// holding a one‑time permit: an ed25519 signature by `user` over
+    /// [`RelayerPermit`], verified by an Ed25519 program instruction earlier
+    /// in the same transaction.
+    pub fn batch_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchSwap<'info>>,
+        steps: Vec<BatchSwapStep>,
+        limits: Vec<i64>,
+        deadline: i64,
+    ) -> Result<Vec<i64>> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(!ctx.accounts.vault_state.is_paused(now), ErrorCode::VaultPaused);
+        require!(now <= deadline, ErrorCode::SwapDeadline);
+
+        let n_assets = limits.len();
+        let rem = ctx.remaining_accounts;
+        require!(!steps.is_empty(), ErrorCode::MalformedSwap);
+        require!(
+            rem.len() >= n_assets * 2 + steps.len() * BatchSwapStep::ACCOUNTS,
+            ErrorCode::MalformedSwap
+        );
+
+        let via_relayer = match ctx.accounts.permit_nonce.as_mut() {
+            Some(permit_nonce) if ctx.accounts.user.key() != ctx.accounts.sender.key() => {
+                let instructions = ctx.accounts.instructions.as_ref().ok_or(ErrorCode::InvalidPermit)?;
+                let action = RelayerPermit::batch_swap_action(&steps, &limits, deadline)?;
+                consume_permit(
+                    permit_nonce,
+                    instructions,
+                    ctx.accounts.vault_state.key(),
+                    ctx.accounts.user.key(),
+                    ctx.accounts.sender.key(),
+                    action,
+                )?;
+                true
+            }
+            _ => authenticate_for(
+                &ctx.accounts.user,
+                &ctx.accounts.sender,
+                ctx.accounts.relayer_approval.as_ref(),
+            )?,
+        };
+
+        // 1. validate asset accounts: user side must belong to the user,
+        //    vault side must be held by the vault authority
+        //    and every asset must be distinct, or netting could double count
+        let user_key        = ctx.accounts.user.key();
+        let vault_authority = ctx.accounts.vault_authority.key();
+        let mut mints: Vec<Pubkey> = Vec::with_capacity(n_assets);
+        for a in 0..n_assets {
+            require_keys_eq!(*rem[a * 2].owner, *rem[a * 2 + 1].owner, ErrorCode::InvalidAssetAccount);
+            let user_acct  = unpack_token_account(&rem[a * 2])?;
+            let vault_acct = unpack_token_account(&rem[a * 2 + 1])?;
+            require_keys_eq!(user_acct.owner, user_key, ErrorCode::InvalidAssetAccount);
+            require_keys_eq!(vault_acct.owner, vault_authority, ErrorCode::InvalidAssetAccount);
+            require_keys_eq!(vault_acct.mint, user_acct.mint, ErrorCode::InvalidAssetAccount);
+            require!(!mints.contains(&vault_acct.mint), ErrorCode::DuplicateAsset);
+            mints.push(vault_acct.mint);
+        }
+        // the mint accounts after the step groups are recognized by their keys
+        let mint_start = (n_assets * 2..rem.len().saturating_sub(n_assets) + 1)
+            .find(|&at| rem[at..at + n_assets].iter().zip(&mints).all(|(ai, mint)| ai.key() == *mint));
+        let steps_end     = mint_start.unwrap_or(rem.len());
+        let mint_accounts = mint_start.map(|at| &rem[at..at + n_assets]);
+        let hook_accounts = mint_start.map_or(&rem[rem.len()..], |at| &rem[at + n_assets..]);
+        let mint_of = |a: usize| mint_accounts.map(|m| &m[a]);
+
+        // each asset moves through the token program owning its accounts:
+        // the context's, or one passed among the trailing accounts
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        let programs: Vec<AccountInfo<'info>> = (0..n_assets)
+            .map(|a| token_program_account(rem[a * 2].owner, &token_prog, hook_accounts).cloned())
+            .collect::<Result<_>>()?;
+
+        let vault_state_key = ctx.accounts.vault_state.key();
+        let bump_arr = [ctx.bumps.vault_authority];
+        let seed_slice: &[&[u8]] = &[seeds::VAULT_AUTHORITY, vault_state_key.as_ref(), &bump_arr];
+        let signer_seeds = &[seed_slice];
+
+        let vault_auth = ctx.accounts.vault_authority.to_account_info();
+        // a relayer pulls the user's funds through the vault authority's delegation
+        let user_auth  = if via_relayer {
+            vault_auth.clone()
+        } else {
+            ctx.accounts.user.to_account_info()
+        };
+
+        let mut net = NetSettlement::new(n_assets);
+        let mut cursor = n_assets * 2;
+
+        // 2. execute steps
+        for step in steps.iter() {
+            let (a_in, a_out, amount_in) = net.resolve(step)?;
+
+            // fund the vault side from earlier outputs first, then from the user
+            let pull = net.fund(a_in, amount_in)?;
+            if pull > 0 {
+                let gross = gross_of_transfer_fee(mint_of(a_in), pull)?;
+                let before = unpack_token_account(&rem[a_in * 2 + 1])?.amount;
+                let cpi_accounts = Transfer {
+                    from:      rem[a_in * 2].clone(),
+                    to:        rem[a_in * 2 + 1].clone(),
+                    authority: user_auth.clone(),
+                };
+                transfer(
+                    CpiContext::new_with_signer(programs[a_in].clone(), cpi_accounts, signer_seeds),
+                    mint_of(a_in),
+                    hook_accounts,
+                    gross,
+                )?;
+                // the fee rounds in the pool's favour: credit any excess back
+                let received = unpack_token_account(&rem[a_in * 2 + 1])?.amount.saturating_sub(before);
+                require!(received >= pull, ErrorCode::PoolSettlementMismatch);
+                net.charge(a_in, gross - pull, received - pull)?;
+            }
+
+            // swap inside the pool, the vault authority acting as the trader
+            let group = rem.get(cursor..cursor + BatchSwapStep::ACCOUNTS).ok_or(ErrorCode::MalformedSwap)?;
+            let (pool_program, pool, pool_tok_in, pool_tok_out, pool_authority, fee_tok_in) =
+                (&group[1], &group[2], &group[3], &group[4], &group[5], &group[6]);
+            let registration = Account::<PoolRegistration>::try_from(&group[0])?;
+            require!(
+                registration.pool_id == step.pool_id
+                    && registration.vault == vault_state_key
+                    && registration.pool == pool.key()
+                    && registration.pool_program == pool_program.key(),
+                ErrorCode::UnregisteredPool
+            );
+            require!(!registration.paused, ErrorCode::PoolPaused);
+            cursor += BatchSwapStep::ACCOUNTS;
+            let n_balances = registration.extra_balance_accounts();
+            let balances = rem.get(cursor..cursor + n_balances).ok_or(ErrorCode::MalformedSwap)?;
+            cursor += n_balances;
+            let (mint_in, mint_out) = registration.check_swap_accounts(pool_tok_in, pool_tok_out, balances)?;
+            require!(
+                mint_in == mints[a_in] && mint_out == mints[a_out],
+                ErrorCode::InvalidAssetAccount
+            );
+
+            let mut ix = pool_interface::swap_exact_in(
+                pool_program.key(),
+                &pool_interface::SwapExactInAccounts {
+                    pool:           pool.key(),
+                    vault_state:    vault_state_key,
+                    pool_token_in:  pool_tok_in.key(),
+                    pool_token_out: pool_tok_out.key(),
+                    authority:      vault_authority,
+                    user_token_in:  rem[a_in * 2 + 1].key(),
+                    user_token_out: rem[a_out * 2 + 1].key(),
+                    pool_authority: pool_authority.key(),
+                    token_program:  token_prog.key(),
+                    fees_collector: ctx.accounts.fees_collector.key(),
+                    protocol_fee_account: fee_tok_in.key(),
+                    pool_registration: group[0].key(),
+                },
+                amount_in,
+                0, // limits are enforced on the net deltas below
+            );
+            ix.accounts.extend(balances.iter().map(|b| AccountMeta::new_readonly(b.key(), false)));
+            let step_mints: Vec<AccountInfo<'info>> = match mint_accounts {
+                Some(m) => [m[a_in].clone(), m[a_out].clone()].into_iter().chain(hook_accounts.iter().cloned()).collect(),
+                None => vec![],
+            };
+            ix.accounts.extend(step_mints.iter().map(|m| AccountMeta {
+                pubkey:      m.key(),
+                is_signer:   false,
+                is_writable: m.is_writable,
+            }));
+            let in_before  = unpack_token_account(&rem[a_in * 2 + 1])?.amount;
+            let out_before = unpack_token_account(&rem[a_out * 2 + 1])?.amount;
+            invoke_signed(
+                &ix,
+                &[
+                    pool.clone(),
+                    ctx.accounts.vault_state.to_account_info(),
+                    pool_tok_in.clone(),
+                    pool_tok_out.clone(),
+                    vault_auth.clone(),
+                    rem[a_in * 2 + 1].clone(),
+                    rem[a_out * 2 + 1].clone(),
+                    pool_authority.clone(),
+                    token_prog.clone(),
+                    ctx.accounts.fees_collector.to_account_info(),
+                    fee_tok_in.clone(),
+                    group[0].clone(),
+                    pool_program.clone(),
+                ]
+                .into_iter()
+                .chain(balances.iter().cloned())
+                .chain(step_mints)
+                .collect::<Vec<_>>(),
+                signer_seeds,
+            )?;
+
+            let amount_out = match get_return_data() {
+                Some((program_id, data)) if program_id == pool_program.key() && data.len() == 8 => {
+                    u64::from_le_bytes(data[..8].try_into().unwrap())
+                }
+                _ => return err!(ErrorCode::InvalidPoolReturn),
+            };
+
+            // never trust the pool's report: the vault‑side balances must move
+            // by exactly the amounts in and out, less the out mint's fee
+            let received  = amount_out - transfer_fee(mint_of(a_out), amount_out)?;
+            let in_after  = unpack_token_account(&rem[a_in * 2 + 1])?.amount;
+            let out_after = unpack_token_account(&rem[a_out * 2 + 1])?.amount;
+            require!(
+                in_before.checked_sub(in_after) == Some(amount_in)
+                    && out_after.checked_sub(out_before) == Some(received),
+                ErrorCode::PoolSettlementMismatch
+            );
+            net.receive(a_out, received)?;
+        }
+        require!(cursor == steps_end, ErrorCode::MalformedSwap);
+
+        // 3. settle: hand every remaining credit back to the user, who
+        //    receives it less any transfer fee
+        let (credit, mut deltas) = net.finish()?;
+        for (a, &amount) in credit.iter().enumerate() {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = Transfer {
+                from:      rem[a * 2 + 1].clone(),
+                to:        rem[a * 2].clone(),
+                authority: vault_auth.clone(),
+            };
+            transfer(
+                CpiContext::new_with_signer(programs[a].clone(), cpi_accounts, signer_seeds),
+                mint_of(a),
+                hook_accounts,
+                amount,
+            )?;
+            let fee = i64::try_from(transfer_fee(mint_of(a), amount)?).map_err(|_| ErrorCode::Overflow)?;
+            deltas[a] = deltas[a].checked_add(fee).ok_or(ErrorCode::Overflow)?;
+        }
+
+        // 4. aggregate limits
+        enforce_limits(&deltas, &limits)?;
+
+        Ok(deltas)
+    }
+
+    /// Price a batch swap against current pool balances without moving any
+    /// funds. Returns the net deltas `batch_swap` would settle for the same
+    /// steps (positive = user pays in, negative = user receives).
+    ///
+    /// Each step asks its pool for a quote via `query_swap`, feeding it the
+    /// pool balances as left by the earlier steps of the batch. A General
+    /// pool is passed its custody accounts, any other pool its
+    /// registration, which places the tokens swapped.
+    ///
+    /// remaining_accounts:
+    ///   [pool_registration, pool_program, pool, pool_tok_in, pool_tok_out,   (one group per step)
+    ///    [pool_tok_0, …, pool_tok_{N-1}],                                    (General pools only)
+    ///    …]
+    pub fn query_batch_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, QueryBatchSwap<'info>>,
+        steps: Vec<BatchSwapStep>,
+        n_assets: u8,
+    ) -> Result<Vec<i64>> {
+        let rem = ctx.remaining_accounts;
+        require!(!steps.is_empty(), ErrorCode::MalformedSwap);
+        require!(
+            rem.len() >= steps.len() * QueryBatchSwap::ACCOUNTS_PER_STEP,
+            ErrorCode::MalformedSwap
+        );
+
+        let vault_state_key = ctx.accounts.vault_state.key();
+        let mut net = NetSettlement::new(n_assets as usize);
+        // pool token balances as the batch so far would have left them
+        let mut balances: Vec<(Pubkey, u64)> = Vec::new();
+        let mut cursor = 0;
+
+        for step in steps.iter() {
+            let (a_in, a_out, amount_in) = net.resolve(step)?;
+            net.fund(a_in, amount_in)?;
+
+            let group = rem
+                .get(cursor..cursor + QueryBatchSwap::ACCOUNTS_PER_STEP)
+                .ok_or(ErrorCode::MalformedSwap)?;
+            let (pool_program, pool, pool_tok_in, pool_tok_out) = (&group[1], &group[2], &group[3], &group[4]);
+            let registration = Account::<PoolRegistration>::try_from(&group[0])?;
+            require!(
+                registration.pool_id == step.pool_id
+                    && registration.vault == vault_state_key
+                    && registration.pool == pool.key()
+                    && registration.pool_program == pool_program.key(),
+                ErrorCode::UnregisteredPool
+            );
+            cursor += QueryBatchSwap::ACCOUNTS_PER_STEP;
+            let n_balances = registration.extra_balance_accounts();
+            let pool_balances = rem.get(cursor..cursor + n_balances).ok_or(ErrorCode::MalformedSwap)?;
+            cursor += n_balances;
+            registration.check_swap_accounts(pool_tok_in, pool_tok_out, pool_balances)?;
+
+            let balance_in  = simulated_balance(&mut balances, pool_tok_in, &registration)?;
+            let balance_out = simulated_balance(&mut balances, pool_tok_out, &registration)?;
+            let mut ix = pool_interface::query_swap(
+                pool_program.key(),
+                &pool_interface::QuerySwapAccounts {
+                    pool:           pool.key(),
+                    fees_collector: ctx.accounts.fees_collector.key(),
+                    pool_token_in:  pool_tok_in.key(),
+                    pool_token_out: pool_tok_out.key(),
+                },
+                amount_in,
+                balances[balance_in].1,
+                balances[balance_out].1,
+            );
+            let pool_accounts = if n_balances > 0 { pool_balances } else { &group[..1] };
+            ix.accounts.extend(pool_accounts.iter().map(|b| AccountMeta::new_readonly(b.key(), false)));
+            invoke(
+                &ix,
+                &[
+                    pool.clone(),
+                    ctx.accounts.fees_collector.to_account_info(),
+                    pool_tok_in.clone(),
+                    pool_tok_out.clone(),
+                    pool_program.clone(),
+                ]
+                .into_iter()
+                    .chain(pool_accounts.iter().cloned())
+                    .collect::<Vec<_>>(),
+            )?;
+            let quote = match get_return_data() {
+                Some((program_id, data)) if program_id == pool_program.key() => {
+                    pool_interface::SwapQuote::try_from_slice(&data)
+                        .map_err(|_| ErrorCode::InvalidPoolReturn)?
+                }
+                _ => return err!(ErrorCode::InvalidPoolReturn),
+            };
+
+            // the protocol fee bypasses the pool, so only the rest lands in it
+            let kept = amount_in.checked_sub(quote.protocol_fee).ok_or(ErrorCode::InvalidPoolReturn)?;
+            balances[balance_in].1 = balances[balance_in].1.checked_add(kept).ok_or(ErrorCode::Overflow)?;
+            balances[balance_out].1 = balances[balance_out]
+                .1
+                .checked_sub(quote.amount_out)
+                .ok_or(ErrorCode::InvalidPoolReturn)?;
+            net.receive(a_out, quote.amount_out)?;
+        }
+        require!(cursor == rem.len(), ErrorCode::MalformedSwap);
+
+        Ok(net.finish()?.1)
+    }
+}
+
+/// Index of `ai` in the simulated balance table, loading its on‑chain
+/// balance (cash + managed) the first time it is seen.
+fn simulated_balance(
+    balances: &mut Vec<(Pubkey, u64)>,
+    ai: &AccountInfo,
+    registration: &PoolRegistration,
+) -> Result<usize> {
+    if let Some(i) = balances.iter().position(|(key, _)| *key == ai.key()) {
+        return Ok(i);
+    }
+    let acct = unpack_token_account(ai)?;
+    let total = acct
+        .amount
+        .checked_add(registration.managed_balance(&acct.mint))
+        .ok_or(ErrorCode::Overflow)?;
+    balances.push((ai.key(), total));
+    Ok(balances.len() - 1)
+}
+
+/// Net‑settlement bookkeeping shared by `batch_swap` and `query_batch_swap`.
+///
+/// `credit[a]` is the amount of asset `a` produced by earlier steps and
+/// parked in the vault; `deltas[a]` what the user has paid in so far.
+struct NetSettlement {
+    credit: Vec<u64>,
+    deltas: Vec<i64>,
+    last_out: Option<(usize, u64)>,
+}
+
+impl NetSettlement {
+    fn new(n_assets: usize) -> Self {
+        Self {
+            credit: vec![0; n_assets],
+            deltas: vec![0; n_assets],
+            last_out: None,
+        }
+    }
+
+    /// Validate a step's asset indices and resolve its amount in:
+    /// `amount == 0` chains the previous step's output into this one.
+    fn resolve(&self, step: &BatchSwapStep) -> Result<(usize, usize, u64)> {
+        let n_assets = self.credit.len();
+        let a_in  = step.asset_in_index as usize;
+        let a_out = step.asset_out_index as usize;
+        require!(a_in < n_assets && a_out < n_assets && a_in != a_out, ErrorCode::MalformedSwap);
+        let amount_in = if step.amount == 0 {
+            match self.last_out {
+                Some((a, amount)) if a == a_in => amount,
+                _ => return err!(ErrorCode::MalformedSwap),
+            }
+        } else {
+            step.amount
+        };
+        Ok((a_in, a_out, amount_in))
+    }
+
+    /// Fund `amount` of asset `a` from credit first; returns what the user
+    /// must still pay in.
+    fn fund(&mut self, a: usize, amount: u64) -> Result<u64> {
+        let from_credit = self.credit[a].min(amount);
+        self.credit[a] -= from_credit;
+        let pull = amount - from_credit;
+        self.deltas[a] = self.deltas[a]
+            .checked_add(i64::try_from(pull).map_err(|_| ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?;
+        Ok(pull)
+    }
+
+    /// Record a pull of asset `a` that cost the user `fee` on top and left
+    /// `excess` beyond what the step needed in the vault.
+    fn charge(&mut self, a: usize, fee: u64, excess: u64) -> Result<()> {
+        self.deltas[a] = self.deltas[a]
+            .checked_add(i64::try_from(fee).map_err(|_| ErrorCode::Overflow)?)
+            .ok_or(ErrorCode::Overflow)?;
+        self.credit[a] = self.credit[a].checked_add(excess).ok_or(ErrorCode::Overflow)?;
         Ok(())
     }
+
+    /// Record a step's output of asset `a`.
+    fn receive(&mut self, a: usize, amount: u64) -> Result<()> {
+        self.credit[a] = self.credit[a].checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        self.last_out = Some((a, amount));
+        Ok(())
+    }
+
+    /// Remaining credit (owed to the user) and the final net deltas.
+    fn finish(mut self) -> Result<(Vec<u64>, Vec<i64>)> {
+        for (delta, &credit) in self.deltas.iter_mut().zip(&self.credit) {
+            *delta = delta
+                .checked_sub(i64::try_from(credit).map_err(|_| ErrorCode::Overflow)?)
+                .ok_or(ErrorCode::Overflow)?;
+        }
+        Ok((self.credit, self.deltas))
+    }
+}
+
+/// Net asset deltas must respect the caller's signed limits: a positive
+/// limit caps what the user pays in, a negative one sets the minimum they
+/// receive.
+fn enforce_limits(deltas: &[i64], limits: &[i64]) -> Result<()> {
+    require!(deltas.len() == limits.len(), ErrorCode::MalformedSwap);
+    for (delta, limit) in deltas.iter().zip(limits) {
+        require!(delta <= limit, ErrorCode::SwapLimit);
+    }
+    Ok(())
+}
+
+/// Check that `sender` may act for `user`: either they are the same signer,
+/// or `approval` is `user`'s live approval of `sender`. Returns whether a
+/// relayer is acting.
+fn authenticate_for(
+    user: &AccountInfo,
+    sender: &Signer,
+    approval: Option<&Account<RelayerApproval>>,
+) -> Result<bool> {
+    if user.key() == sender.key() {
+        return Ok(false);
+    }
+    let approval = approval.ok_or(ErrorCode::RelayerNotApproved)?;
+    require!(
+        approval.approved && approval.user == user.key() && approval.relayer == sender.key(),
+        ErrorCode::RelayerNotApproved
+    );
+    Ok(true)
+}
+
+/// Accept a one‑time relayer permit: the instruction right before this one
+/// must be an Ed25519 program check of `user`'s signature over the
+/// [`RelayerPermit`] for `action` and the user's current nonce, which is
+/// then consumed.
+fn consume_permit(
+    permit_nonce: &mut Account<PermitNonce>,
+    instructions: &AccountInfo,
+    vault: Pubkey,
+    user: Pubkey,
+    relayer: Pubkey,
+    action: [u8; 32],
+) -> Result<()> {
+    // a fresh nonce account is created zeroed by init_if_needed
+    if permit_nonce.user == Pubkey::default() {
+        permit_nonce.vault = vault;
+        permit_nonce.user  = user;
+    }
+    let message = RelayerPermit {
+        vault,
+        user,
+        relayer,
+        nonce: permit_nonce.nonce,
+        action,
+    }
+    .try_to_vec()?;
+
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::InvalidPermit);
+    let ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ErrorCode::InvalidPermit);
+    verify_ed25519_data(&ix.data, &user, &message)?;
+
+    permit_nonce.nonce = permit_nonce.nonce.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    Ok(())
+}
+
+/// Check that Ed25519 program instruction data verifies exactly one
+/// signature, by `signer` over `message`, with every offset pointing into
+/// the instruction itself.
+fn verify_ed25519_data(data: &[u8], signer: &Pubkey, message: &[u8]) -> Result<()> {
+    // [num_signatures: u8, padding: u8, offsets: 7 × u16, …payload]
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+    require!(data.len() >= OFFSETS_START + OFFSETS_LEN && data[0] == 1, ErrorCode::InvalidPermit);
+    let field = |i: usize| {
+        let at = OFFSETS_START + i * 2;
+        u16::from_le_bytes([data[at], data[at + 1]]) as usize
+    };
+    let (sig_ix, pubkey_offset, pubkey_ix) = (field(1), field(2), field(3));
+    let (msg_offset, msg_len, msg_ix) = (field(4), field(5), field(6));
+    let this_ix = u16::MAX as usize;
+    require!(
+        sig_ix == this_ix && pubkey_ix == this_ix && msg_ix == this_ix,
+        ErrorCode::InvalidPermit
+    );
+    require!(
+        data.get(pubkey_offset..pubkey_offset + 32) == Some(signer.as_ref()),
+        ErrorCode::InvalidPermit
+    );
+    require!(
+        msg_len == message.len() && data.get(msg_offset..msg_offset + msg_len) == Some(message),
+        ErrorCode::InvalidPermit
+    );
+    Ok(())
+}
+
+/// Deserialize a token account of either token program from raw account
+/// data, skipping any Token‑2022 extensions.
+fn unpack_token_account(ai: &AccountInfo) -> Result<SplAccount> {
+    require!(TokenInterface::ids().contains(ai.owner), ErrorCode::InvalidAssetAccount);
+    let data = ai.try_borrow_data()?;
+    Ok(StateWithExtensions::<SplAccount>::unpack(&data)?.base)
+}
+
+/// The account of token program `key` among `token_program` and `others`:
+/// a pool mixing SPL Token and Token‑2022 mints passes the program the
+/// context does not name along with its other accounts.
+fn token_program_account<'a, 'info>(
+    key: &Pubkey,
+    token_program: &'a AccountInfo<'info>,
+    others: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>> {
+    std::iter::once(token_program)
+        .chain(others)
+        .find(|ai| ai.key == key && TokenInterface::ids().contains(key))
+        .ok_or_else(|| error!(ErrorCode::MissingTokenProgram))
+}
+
+/// Transfer through either token program: checked against `mint` when
+/// given, which Token‑2022 requires for mints with transfer fees or hooks;
+/// plain otherwise (asset‑managed transfers, whose tokens have neither).
+/// A hooked mint's hook program and extra accounts are picked out of
+/// `hook_accounts`.
+#[allow(deprecated)]
+fn transfer<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Transfer<'info>>,
+    mint: Option<&AccountInfo<'info>>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let Some(mint) = mint else {
+        return token::transfer(ctx, amount);
+    };
+    let decimals = {
+        let data = mint.try_borrow_data()?;
+        StateWithExtensions::<SplMint>::unpack(&data)?.base.decimals
+    };
+    onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        mint.clone(),
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        hook_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// The program `mint`'s transfer hook calls, if it has one.
+fn transfer_hook_program(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    let data = mint.try_borrow_data()?;
+    Ok(transfer_hook::get_program_id(&StateWithExtensions::<SplMint>::unpack(&data)?))
+}
+
+/// Whether `mint` has an extension that lets its issuer take or lock
+/// custody balances (a permanent delegate, frozen‑by‑default accounts)
+/// or hide them (confidential transfers). Non‑transferable mints are an
+/// error: no pool can ever move them.
+fn has_dangerous_extension(mint: &AccountInfo) -> Result<bool> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    require!(state.get_extension::<NonTransferable>().is_err(), ErrorCode::NonTransferableMint);
+    let delegate = state.get_extension::<PermanentDelegate>().is_ok_and(|e| Option::<Pubkey>::from(e.delegate).is_some());
+    let frozen = state
+        .get_extension::<DefaultAccountState>()
+        .is_ok_and(|e| e.state == AccountState::Frozen as u8);
+    let confidential = state.get_extension::<ConfidentialTransferMint>().is_ok();
+    Ok(delegate || frozen || confidential)
+}
+
+/// The freeze authority of `mint` (default key = none).
+fn freeze_authority(mint: &AccountInfo) -> Result<Pubkey> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    Ok(Option::<Pubkey>::from(state.base.freeze_authority).unwrap_or_default())
+}
+
+/// The TransferFee config of `mint` for this epoch, if it has one.
+fn with_transfer_fee<T>(
+    mint: Option<&AccountInfo>,
+    f: impl FnOnce(&TransferFeeConfig, u64) -> Option<T>,
+) -> Result<Option<T>> {
+    let Some(mint) = mint else { return Ok(None) };
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<SplMint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(Some(f(config, Clock::get()?.epoch).ok_or(ErrorCode::Overflow)?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// What `mint`'s transfer fee withholds from a transfer of `amount`; zero
+/// without a mint or the extension.
+fn transfer_fee(mint: Option<&AccountInfo>, amount: u64) -> Result<u64> {
+    Ok(with_transfer_fee(mint, |config, epoch| config.calculate_epoch_fee(epoch, amount))?.unwrap_or(0))
+}
+
+/// The amount to send for at least `received` to arrive after `mint`'s
+/// transfer fee.
+fn gross_of_transfer_fee(mint: Option<&AccountInfo>, received: u64) -> Result<u64> {
+    let fee = with_transfer_fee(mint, |config, epoch| config.calculate_inverse_epoch_fee(epoch, received))?;
+    received.checked_add(fee.unwrap_or(0)).ok_or(error!(ErrorCode::Overflow))
 }
 
 #[derive(Accounts)]
@@ -45,37 +1473,1211 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-/// On-chain Vault state: owner and pool count
+/// On-chain Vault state: owner, pool count and emergency pause window
 #[account]
 pub struct VaultState {
     pub owner: Pubkey,
     pub pool_count: u64,
+    /// Unix time after which the vault can no longer be paused
+    pub pause_window_end: i64,
+    /// Unix time at which the current pause lapses (0 = not paused)
+    pub paused_until: i64,
+    /// Proposed next owner (default = no transfer in progress)
+    pub pending_owner: Pubkey,
+    /// Payer at initialization; seeds the PDA, so it never changes with ownership
+    pub creator: Pubkey,
+    /// Index of the pool registry page new pools are appended to
+    pub registry_tail: u32,
+    /// Refuse to register mints with a freeze authority unless the owner
+    /// acknowledges them in the mint overrides
+    pub reject_freezable_mints: bool,
 }
 
 #[derive(Accounts)]
-pub struct RegisterPool<'info> {
-    /// The vault state must be mutable, PDA'd by ["vault-state", owner]
+pub struct TransferOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `TransferOwnership` role
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetFreezeAuthorityPolicy<'info> {
     #[account(
         mut,
-        seeds = [b"vault-state", vault_state.owner.as_ref()],
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `ManageTokenPolicy` role
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
         bump,
-        has_one = owner
+        constraint = vault_state.pending_owner != Pubkey::default()
+            && vault_state.pending_owner == pending_owner.key() @ ErrorCode::NotPendingOwner
     )]
     pub vault_state: Account<'info, VaultState>,
 
-    /// Must match `vault_state.owner`
-    pub owner: Signer<'info>,
+    /// Must match `vault_state.pending_owner`
+    pub pending_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(pool_id: [u8; 32])]
+pub struct RegisterPool<'info> {
+    /// The vault state must be mutable, PDA'd by ["vault-state", creator]
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The vault's Authorizer, PDA'd by ["authorizer", vault_state]
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Registration record, PDA'd by ["pool-registration", vault_state, pool_id]
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolRegistration::LEN,
+        seeds = [b"pool-registration", vault_state.key().as_ref(), pool_id.as_ref()],
+        bump
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// Tail page of the pool registry, created once the previous one is full
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PoolRegistryPage::LEN,
+        seeds = [b"pool-registry", vault_state.key().as_ref(), vault_state.registry_tail.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub registry_page: Account<'info, PoolRegistryPage>,
+
+    /// CHECK: the pool's state account; its owner is recorded as the pool program
+    pub pool: AccountInfo<'info>,
+
+    /// The pool's LP mint, minted by the pool authority PDA; recorded so
+    /// that deregistration checks its supply
+    #[account(
+        constraint = lp_mint.mint_authority == Some(pool_authority(&pool.key(), pool.owner)).into()
+            @ ErrorCode::InvalidLpMint
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// Must hold the `RegisterPool` role; pays for the registration
+    #[account(mut)]
+    pub authority: Signer<'info>,
 
-    /// Required for CPI safety, though we don't invoke it here
+    /// System program for the registration account
     pub system_program: Program<'info, System>,
 }
 
-impl VaultState {
-    pub const LEN: usize = 32 + 8;
-}
+#[derive(Accounts)]
+pub struct DeregisterPool<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Overflow adding pool")]
-    Overflow,
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"pool-registration", vault_state.key().as_ref(), pool_registration.pool_id.as_ref()],
+        bump
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// Registry page listing the pool
+    #[account(
+        mut,
+        seeds = [b"pool-registry", vault_state.key().as_ref(), registry_page.index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub registry_page: Account<'info, PoolRegistryPage>,
+
+    /// The pool's LP mint, as recorded at registration; its supply must be zero
+    #[account(mint::token_program = token_program)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: vault authority PDA; close authority of custody accounts
+    #[account(
+        seeds = [b"vault-authority", vault_state.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// Must hold the `RegisterPool` role; receives the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct GetPools<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"pool-registry", vault_state.key().as_ref(), page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub registry_page: Account<'info, PoolRegistryPage>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterTokens<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-registration", vault_state.key().as_ref(), pool_registration.pool_id.as_ref()],
+        bump
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: vault authority PDA; initial owner and permanent close authority of custody accounts
+    #[account(
+        seeds = [b"vault-authority", vault_state.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: the vault's transfer‑hook allowlist PDA, which may not exist
+    /// yet; read only for hooked mints, which are refused without it
+    #[account(
+        seeds = [b"transfer-hook-allowlist", vault_state.key().as_ref()],
+        bump
+    )]
+    pub transfer_hook_allowlist: Option<AccountInfo<'info>>,
+
+    /// CHECK: the vault's mint extension override PDA, which may not exist
+    /// yet; read only for mints with dangerous extensions, which are
+    /// refused without it
+    #[account(
+        seeds = [b"mint-extension-overrides", vault_state.key().as_ref()],
+        bump
+    )]
+    pub mint_extension_overrides: Option<AccountInfo<'info>>,
+
+    /// Must hold the `RegisterPool` role; pays (and is refunded) the account rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetTransferHookProgram<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Allowlist PDA, one per vault; created on first use
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TransferHookAllowlist::LEN,
+        seeds = [b"transfer-hook-allowlist", vault_state.key().as_ref()],
+        bump
+    )]
+    pub transfer_hook_allowlist: Account<'info, TransferHookAllowlist>,
+
+    /// Must hold the `ManageTokenPolicy` role; pays for the account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetMintExtensionOverride<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Override PDA, one per vault; created on first use
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + MintExtensionOverrides::LEN,
+        seeds = [b"mint-extension-overrides", vault_state.key().as_ref()],
+        bump
+    )]
+    pub mint_extension_overrides: Account<'info, MintExtensionOverrides>,
+
+    /// Must hold the `ManageTokenPolicy` role; pays for the account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAuthorizer<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump,
+        has_one = owner
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Authorizer PDA, one per vault
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Authorizer::LEN,
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must match `vault_state.owner`; becomes the first admin
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    #[account(
+        mut,
+        seeds = [b"authorizer", authorizer.vault.as_ref()],
+        bump,
+        has_one = admin
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must match `authorizer.admin`
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `Pause` role
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExpirePause<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Anyone; receives the crank bounty
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPoolPaused<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-registration", vault_state.key().as_ref(), pool_registration.pool_id.as_ref()],
+        bump
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// Must hold the `Pause` role
+    pub guardian: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BatchSwap<'info> {
+    /// The vault whose authority settles the batch
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: PDA owning the vault‑side asset accounts; signs the pool swaps
+    #[account(
+        mut,
+        seeds = [b"vault-authority", vault_state.key().as_ref()],
+        bump
+    )]
+    pub vault_authority: AccountInfo<'info>,
+
+    /// CHECK: trader whose funds are swapped; authenticated against `sender`
+    pub user: AccountInfo<'info>,
+
+    /// `user` itself, or a relayer `user` has approved or given a permit
+    #[account(mut)]
+    pub sender: Signer<'info>,
+
+    /// Required when `sender` is a relayer
+    #[account(
+        seeds = [b"relayer-approval", vault_state.key().as_ref(), user.key().as_ref(), sender.key().as_ref()],
+        bump
+    )]
+    pub relayer_approval: Option<Account<'info, RelayerApproval>>,
+
+    /// Required when `sender` acts on a permit; created on first use
+    #[account(
+        init_if_needed,
+        payer = sender,
+        space = 8 + PermitNonce::LEN,
+        seeds = [b"permit-nonce", vault_state.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub permit_nonce: Option<Account<'info, PermitNonce>>,
+
+    /// CHECK: instructions sysvar, required with a permit
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: Option<AccountInfo<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+
+    /// Receives the protocol share of each pool's swap fee
+    #[account(
+        seeds = [b"fees-collector", vault_state.key().as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ManagePoolBalance<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: asset manager authority PDA; proves to the pool program that
+    /// the vault asked, and signs nothing but managed transfers
+    #[account(
+        seeds = [b"asset-manager-authority", vault_state.key().as_ref()],
+        bump
+    )]
+    pub asset_manager_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"pool-registration", vault_state.key().as_ref(), pool_registration.pool_id.as_ref()],
+        bump,
+        has_one = pool,
+        has_one = pool_program
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// CHECK: the registered pool program
+    pub pool_program: AccountInfo<'info>,
+
+    /// CHECK: the registered pool state account
+    pub pool: AccountInfo<'info>,
+
+    /// CHECK: the pool authority PDA; the pool program checks its seeds
+    pub pool_authority: AccountInfo<'info>,
+
+    /// The pool's custody account for the managed token
+    #[account(mut, token::token_program = token_program)]
+    pub custody: InterfaceAccount<'info, TokenAccount>,
+
+    /// Where withdrawn funds go, or where deposited funds come from
+    #[account(mut, token::token_program = token_program)]
+    pub counterparty: InterfaceAccount<'info, TokenAccount>,
+
+    /// The token's registered asset manager
+    pub manager: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl ManagePoolBalance<'_> {
+    /// Index of the custody account's token, checking `manager` manages it
+    fn managed_index(&self) -> Result<usize> {
+        let registration = &self.pool_registration;
+        let i = registration
+            .token_accounts
+            .iter()
+            .position(|k| *k == self.custody.key() && *k != Pubkey::default())
+            .ok_or(ErrorCode::TokenNotRegistered)?;
+        require!(
+            registration.asset_managers[i] != Pubkey::default()
+                && registration.asset_managers[i] == self.manager.key(),
+            ErrorCode::Unauthorized
+        );
+        require_keys_eq!(self.counterparty.mint, registration.tokens[i], ErrorCode::InvalidTokens);
+        Ok(i)
+    }
+
+    fn emit_managed(&self, i: usize, cash_delta: i128, managed_delta: i128) -> Result<()> {
+        emit!(PoolBalanceManaged {
+            vault:         self.vault_state.key(),
+            pool_id:       self.pool_registration.pool_id,
+            mint:          self.pool_registration.tokens[i],
+            manager:       self.manager.key(),
+            cash_delta:    i64::try_from(cash_delta).map_err(|_| ErrorCode::Overflow)?,
+            managed_delta: i64::try_from(managed_delta).map_err(|_| ErrorCode::Overflow)?,
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct QueryBatchSwap<'info> {
+    /// The vault the pools are registered with
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Protocol fee settings the pools quote with
+    #[account(
+        seeds = [b"fees-collector", vault_state.key().as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+}
+
+impl QueryBatchSwap<'_> {
+    /// remaining_accounts consumed per step
+    pub const ACCOUNTS_PER_STEP: usize = 5;
+}
+
+#[derive(Accounts)]
+pub struct InitializeFeesCollector<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Collector PDA, one per vault
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ProtocolFeesCollector::LEN,
+        seeds = [b"fees-collector", vault_state.key().as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// Must hold the `SetProtocolFees` role; pays for the collector
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateFeeAccount<'info> {
+    #[account(
+        seeds = [b"fees-collector", fees_collector.vault.as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// Fee token account, PDA'd by ["protocol-fees", fees_collector, mint]
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"protocol-fees", fees_collector.key().as_ref(), mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = fees_collector,
+        token::token_program = token_program
+    )]
+    pub fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolFeePercentages<'info> {
+    #[account(
+        mut,
+        seeds = [b"fees-collector", fees_collector.vault.as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    #[account(
+        seeds = [b"authorizer", fees_collector.vault.as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must hold the `SetProtocolFees` role
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCollectedFees<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    #[account(
+        seeds = [b"fees-collector", vault_state.key().as_ref()],
+        bump
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    #[account(
+        seeds = [b"authorizer", vault_state.key().as_ref()],
+        bump
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    #[account(
+        mut,
+        token::authority = fees_collector,
+        token::token_program = token_program
+    )]
+    pub fee_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = fee_account.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = fee_account.mint,
+        token::token_program = token_program
+    )]
+    pub recipient: InterfaceAccount<'info, TokenAccount>,
+
+    /// Must hold the `CollectFees` role
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(relayer: Pubkey)]
+pub struct SetRelayerApproval<'info> {
+    #[account(
+        seeds = [b"vault-state", vault_state.creator.as_ref()],
+        bump
+    )]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// Approval PDA, PDA'd by ["relayer-approval", vault_state, user, relayer]
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + RelayerApproval::LEN,
+        seeds = [b"relayer-approval", vault_state.key().as_ref(), user.key().as_ref(), relayer.as_ref()],
+        bump
+    )]
+    pub relayer_approval: Account<'info, RelayerApproval>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Protocol fee settings; fee token accounts are owned by this PDA
+#[account]
+pub struct ProtocolFeesCollector {
+    pub vault: Pubkey,
+    /// Share of every swap fee taken by the protocol (18‑dec)
+    pub swap_fee_percentage: u64,
+    /// Fee charged on flash loans (18‑dec)
+    pub flash_loan_fee_percentage: u64,
+}
+
+impl ProtocolFeesCollector {
+    pub const LEN: usize = 32 + 8 + 8;
+    /// 50%
+    pub const MAX_SWAP_FEE_PERCENTAGE: u64 = 500_000_000_000_000_000;
+    /// 1%
+    pub const MAX_FLASH_LOAN_FEE_PERCENTAGE: u64 = 10_000_000_000_000_000;
+}
+
+/// A user's approval of a relayer
+#[account]
+pub struct RelayerApproval {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub relayer: Pubkey,
+    pub approved: bool,
+}
+
+impl RelayerApproval {
+    pub const LEN: usize = 32 + 32 + 32 + 1;
+}
+
+/// Transfer‑hook programs whose Token‑2022 mints may be registered, PDA'd
+/// by ["transfer-hook-allowlist", vault_state]
+#[account]
+pub struct TransferHookAllowlist {
+    pub vault: Pubkey,
+    pub programs: Vec<Pubkey>,
+}
+
+impl TransferHookAllowlist {
+    pub const CAPACITY: usize = 16;
+    pub const LEN: usize = 32 + 4 + Self::CAPACITY * 32;
+
+    /// Whether the allowlist at `ai` (possibly not given or not created
+    /// yet) lists `hook_program`.
+    pub fn allows(ai: Option<&AccountInfo>, hook_program: &Pubkey) -> Result<bool> {
+        let Some(ai) = ai.filter(|ai| !ai.data_is_empty()) else {
+            return Ok(false);
+        };
+        let allowlist = TransferHookAllowlist::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+        Ok(allowlist.programs.contains(hook_program))
+    }
+}
+
+/// Mints the owner allows despite dangerous Token‑2022 extensions or a
+/// freeze authority, PDA'd by ["mint-extension-overrides", vault_state]
+#[account]
+pub struct MintExtensionOverrides {
+    pub vault: Pubkey,
+    pub mints: Vec<Pubkey>,
+}
+
+impl MintExtensionOverrides {
+    pub const CAPACITY: usize = 32;
+    pub const LEN: usize = 32 + 4 + Self::CAPACITY * 32;
+
+    /// Whether the overrides at `ai` (possibly not given or not created
+    /// yet) list `mint`.
+    pub fn allows(ai: Option<&AccountInfo>, mint: &Pubkey) -> Result<bool> {
+        let Some(ai) = ai.filter(|ai| !ai.data_is_empty()) else {
+            return Ok(false);
+        };
+        let overrides = MintExtensionOverrides::try_deserialize(&mut &ai.try_borrow_data()?[..])?;
+        Ok(overrides.mints.contains(mint))
+    }
+}
+
+/// Next permit nonce of a user; each permit is valid for one nonce only
+#[account]
+pub struct PermitNonce {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub nonce: u64,
+}
+
+impl PermitNonce {
+    pub const LEN: usize = 32 + 32 + 8;
+}
+
+/// Message a user signs (ed25519, Borsh‑encoded) to let `relayer` perform
+/// one action on their behalf without a prior approval transaction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RelayerPermit {
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub relayer: Pubkey,
+    /// The user's current `PermitNonce.nonce`
+    pub nonce: u64,
+    /// Hash binding the permit to the exact action (see `batch_swap_action`)
+    pub action: [u8; 32],
+}
+
+impl RelayerPermit {
+    /// sha256("batch_swap" ‖ borsh(steps) ‖ borsh(limits) ‖ deadline)
+    pub fn batch_swap_action(steps: &[BatchSwapStep], limits: &[i64], deadline: i64) -> Result<[u8; 32]> {
+        let steps  = steps.to_vec().try_to_vec()?;
+        let limits = limits.to_vec().try_to_vec()?;
+        Ok(hashv(&[b"batch_swap", &steps, &limits, &deadline.to_le_bytes()]).to_bytes())
+    }
+}
+
+/// A pool registered with the vault
+#[account]
+pub struct PoolRegistration {
+    pub vault: Pubkey,
+    /// PoolId (see `common::derive_pool_id`)
+    pub pool_id: [u8; 32],
+    /// Program owning `pool`; the vault CPIs into it to swap
+    pub pool_program: Pubkey,
+    /// The pool's state account
+    pub pool: Pubkey,
+    pub specialization: Specialization,
+    /// Token mints, in pool order
+    pub tokens: Vec<Pubkey>,
+    /// Custody token account of each mint (default key until registered)
+    pub token_accounts: Vec<Pubkey>,
+    /// Asset manager of each token (default key = unmanaged)
+    pub asset_managers: Vec<Pubkey>,
+    /// Balance of each token held by its asset manager rather than in custody
+    pub managed: Vec<u64>,
+    /// Swaps and joins halted by the guardian (see `set_pool_paused`)
+    pub paused: bool,
+    /// Token program owning each token's custody (default key until
+    /// registered); a pool may mix SPL Token and Token‑2022 mints
+    pub token_programs: Vec<Pubkey>,
+    /// Freeze authority of each mint when registered (default key = none):
+    /// it can lock the token's custody, leaving only recovery exits
+    pub freeze_authorities: Vec<Pubkey>,
+    /// The pool's LP mint
+    pub lp_mint: Pubkey,
+}
+
+impl PoolRegistration {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 1 + 5 * (4 + MAX_POOL_TOKENS * 32) + (4 + MAX_POOL_TOKENS * 8) + 1 + 32;
+
+    /// Managed (off‑custody) balance of `mint`; pools price against cash + managed
+    pub fn managed_balance(&self, mint: &Pubkey) -> u64 {
+        self.tokens
+            .iter()
+            .position(|t| t == mint)
+            .map_or(0, |i| self.managed[i])
+    }
+
+    /// Token program of the `i`‑th token, as recorded when its custody
+    /// account was created.
+    pub fn token_program(&self, i: usize) -> Result<Pubkey> {
+        match self.token_programs.get(i) {
+            Some(program) if *program != Pubkey::default() => Ok(*program),
+            _ => err!(ErrorCode::TokenNotRegistered),
+        }
+    }
+
+    /// Balance accounts a swap forwards beyond the pair being swapped:
+    /// General pools price against every balance, the others need only the pair.
+    pub fn extra_balance_accounts(&self) -> usize {
+        match self.specialization {
+            Specialization::General => self.token_accounts.len(),
+            Specialization::MinimalSwapInfo | Specialization::TwoToken => 0,
+        }
+    }
+
+    /// Check the pool token accounts given for a swap: in and out must hold
+    /// registered tokens (in their custody account, once one exists) and a
+    /// General pool must be shown every custody account, in order.
+    /// Returns the mints in and out.
+    pub fn check_swap_accounts(
+        &self,
+        token_in: &AccountInfo,
+        token_out: &AccountInfo,
+        balances: &[AccountInfo],
+    ) -> Result<(Pubkey, Pubkey)> {
+        let mint_in  = self.check_custody(token_in)?;
+        let mint_out = self.check_custody(token_out)?;
+        require!(balances.len() == self.extra_balance_accounts(), ErrorCode::MalformedSwap);
+        for (ai, expected) in balances.iter().zip(&self.token_accounts) {
+            require!(*expected != Pubkey::default(), ErrorCode::TokenNotRegistered);
+            require_keys_eq!(ai.key(), *expected, ErrorCode::TokenNotRegistered);
+        }
+        Ok((mint_in, mint_out))
+    }
+
+    fn check_custody(&self, ai: &AccountInfo) -> Result<Pubkey> {
+        let mint = unpack_token_account(ai)?.mint;
+        let i = self
+            .tokens
+            .iter()
+            .position(|t| *t == mint)
+            .ok_or(ErrorCode::TokenNotRegistered)?;
+        if self.token_accounts[i] != Pubkey::default() {
+            require_keys_eq!(ai.key(), self.token_accounts[i], ErrorCode::TokenNotRegistered);
+        }
+        Ok(mint)
+    }
+}
+
+/// One page of the vault's pool registry, PDA'd by
+/// ["pool-registry", vault_state, index (u32 LE)]
+#[account]
+pub struct PoolRegistryPage {
+    pub vault: Pubkey,
+    pub index: u32,
+    /// Next page once this one is full (default key until then)
+    pub next: Pubkey,
+    pub entries: Vec<RegistryEntry>,
+}
+
+impl PoolRegistryPage {
+    /// Entries per page; keeps `get_pools` within the 1 KiB return data limit
+    pub const CAPACITY: usize = 15;
+    pub const LEN: usize = 32 + 4 + 32 + 4 + Self::CAPACITY * RegistryEntry::LEN;
+
+    pub fn address(vault: &Pubkey, index: u32, program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[seeds::POOL_REGISTRY, vault.as_ref(), &index.to_le_bytes()],
+            program_id,
+        )
+        .0
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub pool_id: [u8; 32],
+    pub pool: Pubkey,
+}
+
+impl RegistryEntry {
+    pub const LEN: usize = 32 + 32;
+}
+
+/// Maps privileged actions to the keys allowed to perform them.
+/// Pool programs read this account directly (it is a PDA of the vault).
+#[account]
+pub struct Authorizer {
+    pub vault: Pubkey,
+    /// May grant and revoke roles
+    pub admin: Pubkey,
+    pub grants: Vec<Grant>,
+}
+
+impl Authorizer {
+    pub const MAX_GRANTS: usize = 32;
+    pub const LEN: usize = 32 + 32 + 4 + Self::MAX_GRANTS * Grant::LEN;
+
+    /// Whether `account` holds the role for `action`
+    pub fn can_perform(&self, action: Action, account: &Pubkey) -> bool {
+        self.grants.iter().any(|g| g.action == action && g.account == *account)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct Grant {
+    pub action: Action,
+    pub account: Pubkey,
+}
+
+impl Grant {
+    pub const LEN: usize = 1 + 32;
+}
+
+/// Privileged actions across the vault and pool programs
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    RegisterPool,
+    SetSwapFee,
+    Pause,
+    CollectFees,
+    SetProtocolFees,
+    /// Fund liquidity‑mining gauges and set their emission rate
+    ManageRewards,
+    /// Allow transfer‑hook programs and mint extensions, and set the
+    /// freeze‑authority policy for registering tokens
+    ManageTokenPolicy,
+    /// Propose a new vault owner
+    TransferOwnership,
+}
+
+/// One hop of a batch swap.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchSwapStep {
+    /// PoolId of the registered pool to swap against
+    pub pool_id: [u8; 32],
+    /// Index into the batch's asset list of the token sent to the pool
+    pub asset_in_index: u8,
+    /// Index into the batch's asset list of the token received from the pool
+    pub asset_out_index: u8,
+    /// Exact amount in; 0 uses the previous step's output
+    pub amount: u64,
+}
+
+impl BatchSwapStep {
+    /// remaining_accounts consumed per step
+    pub const ACCOUNTS: usize = 7;
+}
+
+impl VaultState {
+    pub const LEN: usize = 32 + 8 + 8 + 8 + 32 + 32 + 4 + 1;
+    /// The guardian may pause during the first 90 days after initialization
+    pub const PAUSE_WINDOW_DURATION: i64 = 90 * 24 * 60 * 60;
+    /// A single pause lasts at most 30 days
+    pub const MAX_PAUSE_DURATION: i64 = 30 * 24 * 60 * 60;
+
+    /// Swaps and joins are halted while paused; exits always remain open
+    pub fn is_paused(&self, now: i64) -> bool {
+        now < self.paused_until
+    }
+}
+
+/// A pool was registered with the vault
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolRegistered {
+    pub vault: Pubkey,
+    pub pool_id: [u8; 32],
+    pub pool: Pubkey,
+    pub pool_program: Pubkey,
+    pub specialization: Specialization,
+    pub tokens: Vec<Pubkey>,
+}
+
+/// Custody accounts were created for a pool's tokens
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokensRegistered {
+    pub vault: Pubkey,
+    pub pool_id: [u8; 32],
+    pub tokens: Vec<Pubkey>,
+    pub token_accounts: Vec<Pubkey>,
+}
+
+/// The vault's pools, or just `pool_id`, were paused, or resumed when
+/// `paused_until == 0`. A single pool pause has no expiry (`i64::MAX`).
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolPaused {
+    pub vault: Pubkey,
+    pub pool_id: Option<[u8; 32]>,
+    pub guardian: Pubkey,
+    pub paused_until: i64,
+}
+
+/// A lapsed vault‑wide pause was cleared by a crank
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PauseExpired {
+    pub vault: Pubkey,
+    pub cranker: Pubkey,
+    /// Lamports paid to the cranker
+    pub bounty: u64,
+}
+
+/// An asset manager moved or revalued a pool token's managed balance
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolBalanceManaged {
+    pub vault: Pubkey,
+    pub pool_id: [u8; 32],
+    pub mint: Pubkey,
+    pub manager: Pubkey,
+    pub cash_delta: i64,
+    pub managed_delta: i64,
+}
+
+/// Protocol fees were withdrawn from the collector
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeesCollected {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Batch swap steps or accounts are malformed")]
+    MalformedSwap,
+    #[msg("Asset account is not a valid token account for this batch")]
+    InvalidAssetAccount,
+    #[msg("Pool did not return the swapped amount")]
+    InvalidPoolReturn,
+    #[msg("Net asset delta exceeds its limit")]
+    SwapLimit,
+    #[msg("Asset appears more than once in the batch")]
+    DuplicateAsset,
+    #[msg("Pool moved different amounts than it reported")]
+    PoolSettlementMismatch,
+    #[msg("Swap deadline has passed")]
+    SwapDeadline,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Authorizer grant list is full")]
+    TooManyGrants,
+    #[msg("No such grant")]
+    GrantNotFound,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pause window has expired")]
+    PauseWindowExpired,
+    #[msg("PoolId does not match the pool and vault")]
+    InvalidPoolId,
+    #[msg("Pool token list is empty, too long or has duplicates")]
+    InvalidTokens,
+    #[msg("Pool is not registered with this vault")]
+    UnregisteredPool,
+    #[msg("Sender is not a relayer approved by the user")]
+    RelayerNotApproved,
+    #[msg("Protocol fee percentage above maximum")]
+    ProtocolFeeTooHigh,
+    #[msg("Token already has a custody account")]
+    TokenAlreadyRegistered,
+    #[msg("Token is not registered for this pool")]
+    TokenNotRegistered,
+    #[msg("Token balance must be zero")]
+    TokenBalanceNotZero,
+    #[msg("LP mint is not controlled by the pool authority")]
+    InvalidLpMint,
+    #[msg("LP token supply must be zero")]
+    BptSupplyNotZero,
+    #[msg("Signer is not the pending owner")]
+    NotPendingOwner,
+    #[msg("Managed balance is lower than the amount deposited")]
+    ManagedBalanceTooLow,
+    #[msg("Missing or invalid relayer permit")]
+    InvalidPermit,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Math underflow")]
+    MathUnderflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Swap amount in exceeds the maximum in ratio")]
+    MaxInRatio,
+    #[msg("Swap amount out exceeds the maximum out ratio")]
+    MaxOutRatio,
+    #[msg("Weights are out of range or do not sum to one")]
+    InvalidWeights,
+    #[msg("Math solver did not converge")]
+    MathConvergenceFailure,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("No transition is due")]
+    NothingToCrank,
+    #[msg("Token's transfer hook program is not on the vault's allowlist")]
+    TransferHookNotAllowed,
+    #[msg("Transfer hook allowlist is full")]
+    TooManyHookPrograms,
+    #[msg("Non-transferable mints cannot be pooled")]
+    NonTransferableMint,
+    #[msg("Mint has a permanent delegate, frozen default state or confidential transfers and no owner override")]
+    DangerousMintExtension,
+    #[msg("Mint extension override list is full")]
+    TooManyMintOverrides,
+    #[msg("Token program of a pool token was not passed")]
+    MissingTokenProgram,
+    #[msg("Mint has a freeze authority the vault owner has not acknowledged")]
+    FreezableMint,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::Overflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::WeightsNotNormalized | MathError::InvalidWeight => ErrorCode::InvalidWeights,
+            MathError::MaxInRatio => ErrorCode::MaxInRatio,
+            MathError::MaxOutRatio => ErrorCode::MaxOutRatio,
+            MathError::ConvergenceFailure => ErrorCode::MathConvergenceFailure,
+            MathError::LengthMismatch
+            | MathError::TokenIndexOutOfBounds
+            | MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::InvalidParams
+            | MathError::AssetBoundsExceeded
+            | MathError::InvalidPercentage
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange => ErrorCode::MathInputOutOfBounds,
+        }
+    }
 }
@@ -0,0 +1,614 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+// ---------------------------------------------------------------------
+// Program ID
+// ---------------------------------------------------------------------
+declare_id!("VEscG6rrHmKwjJP6zDSu4fMReYbQNz2M1SzskHtgUHr");
+
+/// Lock ends are rounded down to whole weeks
+pub const WEEK: i64 = 7 * 24 * 60 * 60;
+/// Shortest lock
+pub const MIN_LOCK_TIME: i64 = WEEK;
+/// Longest lock; a maximal lock of `x` tokens starts at `x` votes
+pub const MAX_LOCK_TIME: i64 = 4 * 365 * 24 * 60 * 60;
+/// Fixed‑point scale of slopes and biases
+pub const SLOPE_PRECISION: u128 = 1_000_000_000_000_000_000;
+/// Weeks tracked by the slope‑change ring; covers any lock end
+pub const SLOPE_WEEKS: usize = (MAX_LOCK_TIME / WEEK) as usize + 1;
+/// Past states kept per lock for reading its power at earlier weeks
+pub const LOCK_HISTORY: usize = 16;
+
+/// Vote escrow: holders lock the governance token for one week to four
+/// years and receive voting power `amount · remaining / MAX_LOCK_TIME`,
+/// decaying linearly to zero at unlock, Curve style. Total voting power
+/// is tracked as a (bias, slope) line with scheduled slope changes at
+/// each week where locks end, so both a lock's and the total power at
+/// any time are read without iterating over locks.
+#[program]
+pub mod voting_escrow {
+    use super::*;
+
+    /* ---------------------------------------------------------------
+       Create the escrow of a governance token
+    ---------------------------------------------------------------- */
+    pub fn initialize_escrow(ctx: Context<InitializeEscrow>) -> Result<()> {
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.token_mint      = ctx.accounts.token_mint.key();
+        escrow.token_vault     = ctx.accounts.token_vault.key();
+        escrow.total_locked    = 0;
+        escrow.bias            = 0;
+        escrow.slope           = 0;
+        escrow.last_checkpoint = Clock::get()?.unix_timestamp;
+        escrow.slope_changes   = [0; SLOPE_WEEKS];
+        escrow.week_supply     = [0; SLOPE_WEEKS];
+        escrow.authority_bump  = ctx.bumps.escrow_authority;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Lock `amount` until `unlock_time` (rounded down to a week)
+    ---------------------------------------------------------------- */
+    pub fn create_lock(ctx: Context<CreateLock>, amount: u64, unlock_time: i64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let end = round_to_week(unlock_time);
+        check_lock_end(end, now)?;
+
+        let lock = &mut ctx.accounts.lock;
+        lock.escrow = ctx.accounts.escrow.key();
+        lock.owner  = ctx.accounts.owner.key();
+        ctx.accounts.escrow.update(now, (0, 0), (amount, end))?;
+        lock.amount = amount;
+        lock.end    = end;
+        lock.record(now);
+
+        deposit(
+            &ctx.accounts.owner,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_vault,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        emit!(LockUpdated { owner: lock.owner, amount: lock.amount, end });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Add tokens to a live lock, keeping its end
+    ---------------------------------------------------------------- */
+    pub fn increase_amount(ctx: Context<UpdateLock>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::ZeroAmount);
+        let now  = Clock::get()?.unix_timestamp;
+        let lock = &mut ctx.accounts.lock;
+        require!(lock.end > now, ErrorCode::LockExpired);
+
+        let new_amount = lock.amount.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        ctx.accounts.escrow.update(now, (lock.amount, lock.end), (new_amount, lock.end))?;
+        lock.amount = new_amount;
+        lock.record(now);
+
+        deposit(
+            &ctx.accounts.owner,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.token_vault,
+            &ctx.accounts.token_program,
+            amount,
+        )?;
+        emit!(LockUpdated { owner: lock.owner, amount: lock.amount, end: lock.end });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Push a live lock's end later
+    ---------------------------------------------------------------- */
+    pub fn increase_unlock_time(ctx: Context<UpdateLock>, unlock_time: i64) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let lock = &mut ctx.accounts.lock;
+        require!(lock.end > now, ErrorCode::LockExpired);
+        let end = round_to_week(unlock_time);
+        require!(end > lock.end, ErrorCode::InvalidUnlockTime);
+        check_lock_end(end, now)?;
+
+        ctx.accounts.escrow.update(now, (lock.amount, lock.end), (lock.amount, end))?;
+        lock.end = end;
+        lock.record(now);
+        emit!(LockUpdated { owner: lock.owner, amount: lock.amount, end });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Take back the tokens of an expired lock and close it
+    ---------------------------------------------------------------- */
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let now  = Clock::get()?.unix_timestamp;
+        let lock = &mut ctx.accounts.lock;
+        require!(lock.end <= now, ErrorCode::LockNotExpired);
+        let amount = lock.amount;
+        require!(amount > 0, ErrorCode::ZeroAmount);
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.update(now, (amount, lock.end), (0, 0))?;
+        lock.amount = 0;
+
+        let escrow_key = escrow.key();
+        let bump_arr   = [escrow.authority_bump];
+        let seed_slice: &[&[u8]] = &[b"escrow-authority", escrow_key.as_ref(), &bump_arr];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from:      ctx.accounts.token_vault.to_account_info(),
+                    to:        ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.escrow_authority.to_account_info(),
+                },
+                &[seed_slice],
+            ),
+            amount,
+        )?;
+        emit!(LockUpdated { owner: lock.owner, amount: 0, end: lock.end });
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Advance the total‑power line; permissionless upkeep
+    ---------------------------------------------------------------- */
+    pub fn checkpoint(ctx: Context<Checkpoint>) -> Result<()> {
+        ctx.accounts.escrow.checkpoint(Clock::get()?.unix_timestamp)
+    }
+}
+
+fn round_to_week(t: i64) -> i64 {
+    t.div_euclid(WEEK) * WEEK
+}
+
+fn check_lock_end(end: i64, now: i64) -> Result<()> {
+    require!(end >= now + MIN_LOCK_TIME, ErrorCode::InvalidUnlockTime);
+    require!(end <= now + MAX_LOCK_TIME, ErrorCode::InvalidUnlockTime);
+    Ok(())
+}
+
+/// Slope of a lock of `amount`, times `SLOPE_PRECISION`.
+fn slope_of(amount: u64) -> u128 {
+    amount as u128 * SLOPE_PRECISION / MAX_LOCK_TIME as u128
+}
+
+/// Move `amount` of the owner's tokens into the escrow.
+fn deposit<'info>(
+    owner: &Signer<'info>,
+    from: &Account<'info, TokenAccount>,
+    vault: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            Transfer {
+                from:      from.to_account_info(),
+                to:        vault.to_account_info(),
+                authority: owner.to_account_info(),
+            },
+        ),
+        amount,
+    )
+}
+
+/* ------------------------------------------------------------------
+   Accounts
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct InitializeEscrow<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_mint: Account<'info, Mint>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [b"voting-escrow", token_mint.key().as_ref()],
+        bump
+    )]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    /// CHECK: PDA holding the locked tokens
+    #[account(seeds = [b"escrow-authority", escrow.key().as_ref()], bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"escrow-tokens", escrow.key().as_ref()],
+        bump,
+        token::mint = token_mint,
+        token::authority = escrow_authority
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLock<'info> {
+    #[account(mut)]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Lock::INIT_SPACE,
+        seeds = [b"lock", escrow.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub lock: Account<'info, Lock>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, token::mint = escrow.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = escrow.token_vault)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLock<'info> {
+    #[account(mut)]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        mut,
+        seeds = [b"lock", escrow.key().as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = escrow
+    )]
+    pub lock: Account<'info, Lock>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut, token::mint = escrow.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = escrow.token_vault)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub escrow: Box<Account<'info, Escrow>>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"lock", escrow.key().as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        has_one = escrow
+    )]
+    pub lock: Account<'info, Lock>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut, token::mint = escrow.token_mint)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut, address = escrow.token_vault)]
+    pub token_vault: Account<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the locked tokens
+    #[account(seeds = [b"escrow-authority", escrow.key().as_ref()], bump = escrow.authority_bump)]
+    pub escrow_authority: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Checkpoint<'info> {
+    #[account(mut)]
+    pub escrow: Box<Account<'info, Escrow>>,
+}
+
+/* ------------------------------------------------------------------
+   State, events & errors
+------------------------------------------------------------------ */
+/// Escrow of one governance token, PDA'd by ["voting-escrow", token_mint]
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub token_mint: Pubkey,
+    /// Locked tokens, PDA'd by ["escrow-tokens", escrow]
+    pub token_vault: Pubkey,
+    pub total_locked: u64,
+    /// Total voting power at `last_checkpoint`, times `SLOPE_PRECISION`
+    pub bias: u128,
+    /// Total decay per second, times `SLOPE_PRECISION`
+    pub slope: u128,
+    pub last_checkpoint: i64,
+    /// Slope ending at each week, indexed by week number mod `SLOPE_WEEKS`
+    pub slope_changes: [u128; SLOPE_WEEKS],
+    /// Total voting power at the start of each of the last `SLOPE_WEEKS`
+    /// weeks, times `SLOPE_PRECISION`, indexed like `slope_changes`
+    pub week_supply: [u128; SLOPE_WEEKS],
+    pub authority_bump: u8,
+}
+
+impl Escrow {
+    fn ring_index(week_start: i64) -> usize {
+        (week_start / WEEK).rem_euclid(SLOPE_WEEKS as i64) as usize
+    }
+
+    /// The (bias, slope) line advanced from the last checkpoint to `t`,
+    /// applying the slope changes of the weeks it passes.
+    fn line_at(&self, t: i64) -> (u128, u128) {
+        let (mut bias, mut slope, mut at) = (self.bias, self.slope, self.last_checkpoint);
+        let mut week = round_to_week(at) + WEEK;
+        while week <= t {
+            bias  = bias.saturating_sub(slope * (week - at) as u128);
+            slope = slope.saturating_sub(self.slope_changes[Self::ring_index(week)]);
+            at = week;
+            week += WEEK;
+        }
+        if t > at {
+            bias = bias.saturating_sub(slope * (t - at) as u128);
+        }
+        (bias, slope)
+    }
+
+    /// Total voting power at `t` (not before the last checkpoint).
+    pub fn supply_at(&self, t: i64) -> u64 {
+        let (bias, _) = self.line_at(t.max(self.last_checkpoint));
+        (bias / SLOPE_PRECISION) as u64
+    }
+
+    /// Total voting power at the start of `week`, before any lock change
+    /// made at that instant. `None` for weeks older than the ring.
+    pub fn supply_at_week(&self, week: i64) -> Option<u64> {
+        if week % WEEK != 0 {
+            return None;
+        }
+        if week > self.last_checkpoint {
+            return Some(self.supply_at(week));
+        }
+        if week <= round_to_week(self.last_checkpoint) - SLOPE_WEEKS as i64 * WEEK {
+            return None;
+        }
+        Some((self.week_supply[Self::ring_index(week)] / SLOPE_PRECISION) as u64)
+    }
+
+    /// Advance the stored line to `now`, recording the power at each week
+    /// start passed and clearing the slope changes applied.
+    pub fn checkpoint(&mut self, now: i64) -> Result<()> {
+        if now <= self.last_checkpoint {
+            return Ok(());
+        }
+        let (mut bias, mut slope, mut at) = (self.bias, self.slope, self.last_checkpoint);
+        let mut week = round_to_week(at) + WEEK;
+        while week <= now {
+            let i = Self::ring_index(week);
+            bias  = bias.saturating_sub(slope * (week - at) as u128);
+            slope = slope.saturating_sub(self.slope_changes[i]);
+            self.week_supply[i]   = bias;
+            self.slope_changes[i] = 0;
+            at = week;
+            week += WEEK;
+        }
+        self.bias  = bias.saturating_sub(slope * (now - at) as u128);
+        self.slope = slope;
+        self.last_checkpoint = now;
+        Ok(())
+    }
+
+    /// Replace a lock's `(amount, end)` in the totals at `now`.
+    pub fn update(&mut self, now: i64, old: (u64, i64), new: (u64, i64)) -> Result<()> {
+        self.checkpoint(now)?;
+        if old.1 > now {
+            let slope = slope_of(old.0);
+            self.bias  = self.bias.saturating_sub(slope * (old.1 - now) as u128);
+            self.slope = self.slope.saturating_sub(slope);
+            let i = Self::ring_index(old.1);
+            self.slope_changes[i] = self.slope_changes[i].saturating_sub(slope);
+        }
+        if new.1 > now {
+            let slope = slope_of(new.0);
+            self.bias  = self.bias.checked_add(slope * (new.1 - now) as u128).ok_or(ErrorCode::MathOverflow)?;
+            self.slope = self.slope.checked_add(slope).ok_or(ErrorCode::MathOverflow)?;
+            let i = Self::ring_index(new.1);
+            self.slope_changes[i] = self.slope_changes[i].checked_add(slope).ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.total_locked = self
+            .total_locked
+            .checked_add(new.0)
+            .and_then(|t| t.checked_sub(old.0))
+            .ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+}
+
+/// An owner's lock, PDA'd by ["lock", escrow, owner]
+#[account]
+#[derive(InitSpace)]
+pub struct Lock {
+    pub escrow: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// Unlock time, a whole week
+    pub end: i64,
+    /// Number of changes recorded in `history`
+    pub changes: u32,
+    /// The lock after each of its last `LOCK_HISTORY` changes, indexed
+    /// by change number mod `LOCK_HISTORY`
+    pub history: [LockPoint; LOCK_HISTORY],
+}
+
+/// A lock's `(amount, end)` as set at `ts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, Debug, InitSpace)]
+pub struct LockPoint {
+    pub ts: i64,
+    pub amount: u64,
+    pub end: i64,
+}
+
+impl Lock {
+    /// Voting power at `t`, zero once the lock has ended.
+    pub fn balance_at(&self, t: i64) -> u64 {
+        power(self.amount, self.end, t)
+    }
+
+    /// Voting power at `t` as the lock stood just before `t`, so that it
+    /// adds up to `Escrow::supply_at_week`. `None` once the changes made
+    /// since `t` have pushed the state at `t` out of `history`.
+    pub fn historical_balance(&self, t: i64) -> Option<u64> {
+        let changes  = self.changes as usize;
+        let retained = changes.min(LOCK_HISTORY);
+        let mut at = None;
+        for n in changes - retained..changes {
+            let p = &self.history[n % LOCK_HISTORY];
+            if p.ts < t {
+                at = Some(p);
+            }
+        }
+        match at {
+            Some(p) => Some(power(p.amount, p.end, t)),
+            None if changes > LOCK_HISTORY => None,
+            None => Some(0),
+        }
+    }
+
+    fn record(&mut self, now: i64) {
+        self.history[self.changes as usize % LOCK_HISTORY] = LockPoint { ts: now, amount: self.amount, end: self.end };
+        self.changes += 1;
+    }
+}
+
+fn power(amount: u64, end: i64, t: i64) -> u64 {
+    if end <= t {
+        return 0;
+    }
+    (slope_of(amount) * (end - t) as u128 / SLOPE_PRECISION) as u64
+}
+
+#[event]
+pub struct LockUpdated {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub end: i64,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Amount must be positive")]
+    ZeroAmount,
+    #[msg("Unlock time must be one week to four years away, and later than the current one")]
+    InvalidUnlockTime,
+    #[msg("Lock has expired")]
+    LockExpired,
+    #[msg("Lock has not expired")]
+    LockNotExpired,
+    #[msg("Math overflow")]
+    MathOverflow,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(now: i64) -> Escrow {
+        Escrow {
+            token_mint: Pubkey::default(),
+            token_vault: Pubkey::default(),
+            total_locked: 0,
+            bias: 0,
+            slope: 0,
+            last_checkpoint: now,
+            slope_changes: [0; SLOPE_WEEKS],
+            week_supply: [0; SLOPE_WEEKS],
+            authority_bump: 0,
+        }
+    }
+
+    fn lock(amount: u64, end: i64) -> Lock {
+        Lock {
+            escrow: Pubkey::default(),
+            owner: Pubkey::default(),
+            amount,
+            end,
+            changes: 0,
+            history: [LockPoint::default(); LOCK_HISTORY],
+        }
+    }
+
+    #[test]
+    fn voting_power_decays_linearly_to_unlock() {
+        let now = 100 * WEEK;
+        let lock = lock(1_000_000, now + MAX_LOCK_TIME);
+        assert_eq!(lock.balance_at(now), 999_999);
+        assert_eq!(lock.balance_at(now + MAX_LOCK_TIME / 2), 499_999);
+        assert_eq!(lock.balance_at(lock.end), 0);
+    }
+
+    #[test]
+    fn total_power_tracks_the_sum_of_locks() {
+        let now = 100 * WEEK + 3;
+        let mut e = escrow(now);
+        let a = lock(5_000_000, 110 * WEEK);
+        let b = lock(2_000_000, 150 * WEEK);
+        e.update(now, (0, 0), (a.amount, a.end)).unwrap();
+        e.update(now, (0, 0), (b.amount, b.end)).unwrap();
+
+        for t in [now, 105 * WEEK, 110 * WEEK, 120 * WEEK + 17, 150 * WEEK, 200 * WEEK] {
+            let sum = a.balance_at(t) + b.balance_at(t);
+            let total = e.supply_at(t);
+            assert!(total.abs_diff(sum) <= 2, "t={t}: {total} vs {sum}");
+        }
+
+        // checkpointing past `a`'s end retires its slope
+        e.checkpoint(120 * WEEK).unwrap();
+        assert_eq!(e.slope, slope_of(b.amount));
+        e.update(160 * WEEK, (b.amount, b.end), (0, 0)).unwrap();
+        assert_eq!((e.bias, e.slope, e.total_locked), (0, 0, 5_000_000));
+    }
+
+    #[test]
+    fn week_starts_are_snapshotted_before_same_instant_changes() {
+        let mut e = escrow(100 * WEEK + 5);
+        let mut a = lock(3_000_000, 130 * WEEK);
+        e.update(100 * WEEK + 5, (0, 0), (a.amount, a.end)).unwrap();
+        a.record(100 * WEEK + 5);
+
+        // a lock made exactly at the start of week 102 counts from week 103
+        let mut b = lock(1_000_000, 140 * WEEK);
+        e.update(102 * WEEK, (0, 0), (b.amount, b.end)).unwrap();
+        b.record(102 * WEEK);
+        e.checkpoint(104 * WEEK + 1).unwrap();
+
+        for week in [101, 102, 103, 104] {
+            let t = week * WEEK;
+            let sum = a.historical_balance(t).unwrap() + b.historical_balance(t).unwrap();
+            let total = e.supply_at_week(t).unwrap();
+            assert!(total.abs_diff(sum) <= 2, "week {week}: {total} vs {sum}");
+        }
+        assert_eq!(b.historical_balance(102 * WEEK), Some(0));
+        assert_eq!(e.supply_at_week(100 * WEEK), Some(0));
+        assert_eq!(e.supply_at_week(102 * WEEK + 1), None);
+
+        // history that has rolled over is unknown rather than zero
+        for n in 0..LOCK_HISTORY as i64 {
+            b.amount += 1;
+            b.record(105 * WEEK + n);
+        }
+        assert_eq!(b.historical_balance(103 * WEEK), None);
+        assert!(b.historical_balance(106 * WEEK).is_some());
+    }
+}
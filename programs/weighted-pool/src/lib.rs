@@ -1,21 +1,52 @@
+#![allow(clippy::needless_range_loop)]
+
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program_pack::Pack;
-use anchor_spl::token::{self, Burn, MintTo, Token, Transfer};
-use math::{fixed, weighted_math, U256};
-use spl_token::state::Account as SplAccount;
+use anchor_lang::system_program::{self, Transfer as SystemTransfer};
+use anchor_lang::Discriminator;
+use anchor_spl::token_interface::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_interface::spl_token_2022::onchain;
+use anchor_spl::token_interface::spl_token_2022::state::{Account as SplAccount, Mint as SplMint};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata, UpdateMetadataAccountsV2};
+use anchor_spl::token_interface::{self as token, Burn, Mint, MintTo, TokenAccount, TokenInterface, Transfer};
+use common::pool_interface::SwapQuote;
+use common::{canonical_pool_hash, derive_pool_id, Specialization, MAX_POOL_TOKENS};
+use math::oracle::{try_geometric_mean, Accumulator};
+use math::weighted_math::{self, SwapParams};
+use math::{fees, fixed, MathError, I256, U256};
 
-// Import the Vault CPI interfaces
-// bring in your Vault CPI…
+// The Vault's registration CPI and program
 use vault::cpi::{register_pool as vault_register_pool, accounts::RegisterPool as VaultRegisterPool};
-// …and the program struct itself
 use vault::program::Vault as VaultProgram;
-use vault::VaultState;
+use vault::{Action, Authorizer, PoolRegistration, ProtocolFeesCollector, VaultState};
 
 // ---------------------------------------------------------------------
 // Program ID
 // ---------------------------------------------------------------------
 declare_id!("WPoo1QeY5T2r8j6YfGLwRoTSesFiNUFDXL9uBebzh1e");
 
+/// Samples kept by a pool oracle
+pub const ORACLE_SAMPLES: usize = 160;
+/// Shortest time between two oracle samples (160 samples ≈ 13 h)
+pub const ORACLE_SAMPLE_INTERVAL: i64 = 5 * 60;
+/// Lowest swap fee a pool may charge (0.0001 %)
+pub const MIN_SWAP_FEE: u64 = 1_000_000_000_000;
+/// Highest swap fee a pool may charge (10 %)
+pub const MAX_SWAP_FEE: u64 = 100_000_000_000_000_000;
+/// Smallest nonzero amount a join pays in or a swap trades, in raw units
+/// of the token, whatever its decimals. The pool's 18‑dec values are raw
+/// amounts times `ONE`, with no decimal normalisation, and its rounding
+/// costs at most a raw unit per step; so a raw floor is what holds that
+/// rounding to 0.1 % of any trade, for a 6‑ and a 9‑decimal token alike
+pub const MIN_TRADE_AMOUNT: u64 = 1_000;
+/// BPT the first join mints to the pool authority's own LP account,
+/// which nothing spends: the supply never falls below it, so no LP can
+/// shrink it to a few units and inflate what each is worth
+pub const MINIMUM_BPT: u64 = 1_000;
+/// Layout version of pools this program creates; `migrate_pool_state`
+/// brings older pools up to it
+pub const POOL_VERSION: u8 = 1;
+
 #[program]
 pub mod weighted_pool {
     use super::*;
@@ -28,81 +59,264 @@ pub mod weighted_pool {
         weights: Vec<u128>,
         swap_fee: u64,
     ) -> Result<()> {
-        // ensure one vault per weight
+        // ensure one token mint per weight
         require!(weights.len() == ctx.remaining_accounts.len(), ErrorCode::LengthMismatch);
+        require!(weights.len() <= MAX_POOL_TOKENS, ErrorCode::LengthMismatch);
+        let tokens: Vec<Pubkey> = ctx.remaining_accounts.iter().map(|ai| ai.key()).collect();
+
+        let cpi_accounts = VaultRegisterPool {
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.payer.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
+        };
+        setup_pool(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.vault_state,
+            ctx.accounts.lp_mint.key(),
+            ctx.accounts.vault_program.to_account_info(),
+            cpi_accounts,
+            tokens,
+            weights,
+            swap_fee,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Initialise a canonical pool at ["pool-state", vault, config_hash]:
+       one address per (tokens, weights, swap fee), see
+       `common::canonical_pool_hash`. `authority` holds the `RegisterPool`
+       role and pays the registration; `payer` pays for the pool state.
+       remaining_accounts: [mint_0, …, mint_{N-1}] in ascending order
+    ---------------------------------------------------------------- */
+    pub fn initialize_canonical_pool(
+        ctx: Context<InitializeCanonicalPool>,
+        config_hash: [u8; 32],
+        weights: Vec<u128>,
+        swap_fee: u64,
+    ) -> Result<()> {
+        require!(weights.len() == ctx.remaining_accounts.len(), ErrorCode::LengthMismatch);
+        require!(weights.len() <= MAX_POOL_TOKENS, ErrorCode::LengthMismatch);
+        let tokens: Vec<Pubkey> = ctx.remaining_accounts.iter().map(|ai| ai.key()).collect();
+        require!(
+            canonical_pool_hash(&tokens, &weights, swap_fee) == Some(config_hash),
+            ErrorCode::InvalidConfigHash
+        );
 
-        // initialize our pool state
-        let pool = &mut ctx.accounts.pool;
-        pool.vault     = ctx.accounts.vault_state.key();
-        pool.lp_mint   = ctx.accounts.lp_mint.key();
-        pool.weights   = weights;
-        pool.swap_fee  = swap_fee;
-        pool.total_bpt = 0;
-
-        // Now register this pool in the Vault program via CPI
-        let cpi_program = ctx.accounts.vault_program.to_account_info();
         let cpi_accounts = VaultRegisterPool {
-            vault_state:    ctx.accounts.vault_state.to_account_info(),
-            owner:          ctx.accounts.payer.to_account_info(),
-            system_program: ctx.accounts.system_program.to_account_info(),
+            vault_state:       ctx.accounts.vault_state.to_account_info(),
+            authorizer:        ctx.accounts.authorizer.to_account_info(),
+            pool_registration: ctx.accounts.pool_registration.to_account_info(),
+            registry_page:     ctx.accounts.registry_page.to_account_info(),
+            pool:              ctx.accounts.pool.to_account_info(),
+            lp_mint:           ctx.accounts.lp_mint.to_account_info(),
+            authority:         ctx.accounts.authority.to_account_info(),
+            system_program:    ctx.accounts.system_program.to_account_info(),
         };
-        vault_register_pool(CpiContext::new(cpi_program, cpi_accounts))?;
+        setup_pool(
+            &mut ctx.accounts.pool,
+            &ctx.accounts.vault_state,
+            ctx.accounts.lp_mint.key(),
+            ctx.accounts.vault_program.to_account_info(),
+            cpi_accounts,
+            tokens,
+            weights,
+            swap_fee,
+        )
+    }
 
+    /* ---------------------------------------------------------------
+       Set swap fee – requires the `SetSwapFee` role in the Authorizer
+    ---------------------------------------------------------------- */
+    pub fn set_swap_fee(ctx: Context<SetSwapFee>, swap_fee: u64) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::SetSwapFee, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        check_swap_fee(swap_fee)?;
+        ctx.accounts.pool.swap_fee = swap_fee;
         Ok(())
     }
 
+    /* ---------------------------------------------------------------
+       Create the LP mint's token metadata (name, symbol, URI) so
+       wallets show BPT by name – requires the `RegisterPool` role
+       The lp‑mint‑authority PDA signs as mint and update authority:
+       only `set_lp_metadata` changes it afterwards.
+    ---------------------------------------------------------------- */
+    pub fn create_lp_metadata(ctx: Context<CreateLpMetadata>, name: String, symbol: String, uri: String) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let pool_key = ctx.accounts.pool.key();
+        let seeds: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &[ctx.bumps.lp_mint_authority]];
+        let signer = &[seeds];
+        let cpi_accounts = CreateMetadataAccountsV3 {
+            metadata:         ctx.accounts.metadata.to_account_info(),
+            mint:             ctx.accounts.lp_mint.to_account_info(),
+            mint_authority:   ctx.accounts.lp_mint_authority.to_account_info(),
+            payer:            ctx.accounts.payer.to_account_info(),
+            update_authority: ctx.accounts.lp_mint_authority.to_account_info(),
+            system_program:   ctx.accounts.system_program.to_account_info(),
+            rent:             ctx.accounts.rent.to_account_info(),
+        };
+        metadata::create_metadata_accounts_v3(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts, signer),
+            lp_metadata(name, symbol, uri),
+            true,
+            true,
+            None,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Replace the LP mint's name, symbol and URI – `RegisterPool` role
+    ---------------------------------------------------------------- */
+    pub fn set_lp_metadata(ctx: Context<SetLpMetadata>, name: String, symbol: String, uri: String) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::RegisterPool, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let pool_key = ctx.accounts.pool.key();
+        let seeds: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &[ctx.bumps.lp_mint_authority]];
+        let signer = &[seeds];
+        let cpi_accounts = UpdateMetadataAccountsV2 {
+            metadata:         ctx.accounts.metadata.to_account_info(),
+            update_authority: ctx.accounts.lp_mint_authority.to_account_info(),
+        };
+        metadata::update_metadata_accounts_v2(
+            CpiContext::new_with_signer(ctx.accounts.token_metadata_program.to_account_info(), cpi_accounts, signer),
+            None,
+            Some(lp_metadata(name, symbol, uri)),
+            None,
+            None,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Migrate – grow a pool account written under an older layout to
+       the current size and upgrade it to POOL_VERSION; requires the
+       Authorizer's admin. The payer tops up the rent.
+       The pool is read raw: an old layout may be too short for `Pool`.
+       Version 0 predates `version` and `reserved`, which the grown
+       account's zeroes fill.
+    ---------------------------------------------------------------- */
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        let pool = ctx.accounts.pool.to_account_info();
+        {
+            let data = pool.try_borrow_data()?;
+            require!(data.len() >= 8 + 32 && data[..8] == *Pool::DISCRIMINATOR, ErrorCode::InvalidPoolAccount);
+            require!(data[8..40] == ctx.accounts.authorizer.vault.to_bytes(), ErrorCode::Unauthorized);
+        }
+
+        let space = 8 + Pool::INIT_SPACE;
+        if pool.data_len() < space {
+            let top_up = Rent::get()?.minimum_balance(space).saturating_sub(pool.lamports());
+            if top_up > 0 {
+                let cpi_accounts = SystemTransfer { from: ctx.accounts.payer.to_account_info(), to: pool.clone() };
+                system_program::transfer(CpiContext::new(ctx.accounts.system_program.to_account_info(), cpi_accounts), top_up)?;
+            }
+            pool.realloc(space, true)?;
+        }
+
+        let mut state = Pool::try_deserialize(&mut &pool.try_borrow_data()?[..])?;
+        require!(state.version < POOL_VERSION, ErrorCode::PoolUpToDate);
+        while state.version < POOL_VERSION {
+            match state.version {
+                // `version` and `reserved` read as zero from the grown account
+                0 => state.version = 1,
+                _ => return err!(ErrorCode::PoolUpToDate),
+            }
+        }
+        let mut data = pool.try_borrow_mut_data()?;
+        state.try_serialize(&mut &mut data[..])
+    }
+
     /* ---------------------------------------------------------------
        Join – deposit all tokens, mint BPT
        remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       optionally followed by [mint0, mint1, …, hook accounts…] for
+       checked transfers
+       Each token moves through the token program the Vault recorded for
+       it; a pool mixing SPL Token and Token‑2022 passes the program
+       `token_program` is not among the hook accounts.
+       The protocol's share of the fee is minted as BPT to the collector.
+       Priced on what custody received, net of any transfer fee, against
+       cash plus managed balances; each nonzero amount received must
+       reach MIN_TRADE_AMOUNT.
+       The first join locks MINIMUM_BPT of what it mints in
+       `locked_bpt_account`, which it then requires.
     ---------------------------------------------------------------- */
     pub fn join_exact_tokens_in_for_bpt_out<'info>(
         ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
         amounts_in: Vec<u64>,
     ) -> Result<()> {
+        enter(&mut ctx.accounts.pool)?;
         let pool = &ctx.accounts.pool;
         let n    = pool.weights.len();
 
-        require!(ctx.remaining_accounts.len() == n * 2, ErrorCode::LengthMismatch);
+        require!(
+            !ctx.accounts.vault_state.is_paused(Clock::get()?.unix_timestamp),
+            ErrorCode::VaultPaused
+        );
+        require!(!ctx.accounts.pool_registration.paused, ErrorCode::PoolPaused);
+        let (mints, hook_accounts) = trailing_mints(ctx.remaining_accounts, n * 2, n)?;
         require!(amounts_in.len() == n, ErrorCode::LengthMismatch);
-
-        // 1. read vault balances
-        let mut balances_fp = Vec::with_capacity(n);
+        require!(amounts_in.iter().any(|a| *a > 0), ErrorCode::ZeroAmount);
+        require!(amounts_in.iter().all(|a| *a == 0 || *a >= MIN_TRADE_AMOUNT), ErrorCode::DustAmount);
+        check_token_pairs(ctx.remaining_accounts, &ctx.accounts.pool_registration, ctx.accounts.user.key)?;
         for i in 0..n {
-            let vault_ai = &ctx.remaining_accounts[i * 2 + 1];
-            let data     = vault_ai.try_borrow_data()?;
-            let acct     = SplAccount::unpack_from_slice(&data)?;
-            balances_fp.push(U256::from(acct.amount) * fixed::ONE);
+            let program = ctx.accounts.pool_registration.token_program(i)?;
+            live_custody(&ctx.remaining_accounts[i * 2 + 1], &program, ctx.accounts.lp_mint_authority.key)?;
         }
 
-        // 2. maths
-        let weights_fp: Vec<U256> = pool.weights.iter().map(|w| U256::from(*w)).collect();
-        let amounts_fp: Vec<U256> = amounts_in.iter().map(|a| U256::from(*a) * fixed::ONE).collect();
-        let bpt_out_fp = weighted_math::calc_bpt_out_given_exact_tokens_in(
-            &balances_fp,
-            &weights_fp,
-            &amounts_fp,
-            U256::from(pool.total_bpt) * fixed::ONE,
-            U256::from(pool.swap_fee),
-        );
-        require!(bpt_out_fp > U256::zero(), ErrorCode::MathUnderflow);
-        let bpt_out = (bpt_out_fp / fixed::ONE).as_u64();
-
-        // 3. CPI transfers (user → vault)
+        // 1. CPI transfers (user → vault), each custody balance read before
+        //    and after: a transfer fee leaves less than was sent
         let token_prog = ctx.accounts.token_program.to_account_info();
         let user_auth  = ctx.accounts.user.to_account_info();
+        let mut balances_fp = Vec::with_capacity(n);
+        let mut amounts_in  = amounts_in;
         for i in 0..n {
+            let vault_ai = &ctx.remaining_accounts[i * 2 + 1];
+            let program  = ctx.accounts.pool_registration.token_program(i)?;
+            let before = token_balance(vault_ai, &program)?;
             let cpi_accounts = Transfer {
                 from:      ctx.remaining_accounts[i * 2].clone(),
-                to:        ctx.remaining_accounts[i * 2 + 1].clone(),
+                to:        vault_ai.clone(),
                 authority: user_auth.clone(),
             };
-            token::transfer(
-                CpiContext::new(token_prog.clone(), cpi_accounts),
+            transfer(
+                CpiContext::new(token_program_account(&program, &token_prog, hook_accounts)?.clone(), cpi_accounts),
+                mints.map(|m| &m[i]),
+                hook_accounts,
                 amounts_in[i],
             )?;
+            balances_fp.push(U256::from(pool_balance(&ctx.accounts.pool_registration, i, before)?) * fixed::ONE);
+            amounts_in[i] = token_balance(vault_ai, &program)?
+                .checked_sub(before)
+                .ok_or(ErrorCode::MathUnderflow)?;
         }
+        // the dust floor holds for what arrived, net of any transfer fee
+        require!(amounts_in.iter().all(|a| *a == 0 || *a >= MIN_TRADE_AMOUNT), ErrorCode::DustAmount);
+
+        // 2. maths – BPT out and the protocol's share of the fee in BPT
+        let amounts_fp: Vec<U256> = amounts_in.iter().map(|a| U256::from(*a) * fixed::ONE).collect();
+        let JoinQuote { bpt_out, protocol_bpt, locked_bpt } = quote_join(
+            &ctx.accounts.pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            &balances_fp,
+            &amounts_fp,
+            ctx.accounts.lp_mint.supply,
+        )?;
+        // what rounds to no BPT mints none for the tokens paid in
+        require!(bpt_out > 0, ErrorCode::ZeroAmountOut);
 
-        // 4. mint BPT
+        // 3. mint BPT
         let bump         = ctx.bumps.lp_mint_authority;
         let pool_key     = ctx.accounts.pool.key();
         let bump_arr     = [bump];
@@ -115,67 +329,125 @@ pub mod weighted_pool {
         let mint_ctx = CpiContext::new_with_signer(
             token_prog.clone(),
             MintTo {
-                mint:      ctx.accounts.lp_mint.clone(),
-                to:        ctx.accounts.user_lp_account.clone(),
+                mint:      ctx.accounts.lp_mint.to_account_info(),
+                to:        ctx.accounts.user_lp_account.to_account_info(),
                 authority: ctx.accounts.lp_mint_authority.clone(),
             },
             signer_seeds,
         );
         token::mint_to(mint_ctx, bpt_out)?;
 
-        // 5. bookkeeping
-        ctx.accounts.pool.total_bpt = ctx.accounts.pool
-            .total_bpt
-            .checked_add(bpt_out)
-            .ok_or(ErrorCode::MathUnderflow)?;
+        if locked_bpt > 0 {
+            let locked = ctx.accounts.locked_bpt_account.as_ref().ok_or(ErrorCode::InvalidLockedBptAccount)?;
+            let acct = unpack_token_account(locked, token_prog.key)?;
+            require!(
+                acct.owner == ctx.accounts.lp_mint_authority.key() && acct.mint == ctx.accounts.pool.lp_mint,
+                ErrorCode::InvalidLockedBptAccount
+            );
+            let lock_ctx = CpiContext::new_with_signer(
+                token_prog.clone(),
+                MintTo {
+                    mint:      ctx.accounts.lp_mint.to_account_info(),
+                    to:        locked.clone(),
+                    authority: ctx.accounts.lp_mint_authority.clone(),
+                },
+                signer_seeds,
+            );
+            token::mint_to(lock_ctx, locked_bpt)?;
+        }
+
+        if protocol_bpt > 0 {
+            check_fee_account(
+                &ctx.accounts.protocol_fee_lp_account,
+                token_prog.key,
+                &ctx.accounts.fees_collector.key(),
+                &ctx.accounts.pool.lp_mint,
+            )?;
+            let fee_ctx = CpiContext::new_with_signer(
+                token_prog.clone(),
+                MintTo {
+                    mint:      ctx.accounts.lp_mint.to_account_info(),
+                    to:        ctx.accounts.protocol_fee_lp_account.clone(),
+                    authority: ctx.accounts.lp_mint_authority.clone(),
+                },
+                signer_seeds,
+            );
+            token::mint_to(fee_ctx, protocol_bpt)?;
+        }
+
+        // 4. bookkeeping
+        ctx.accounts.sync_total_bpt()?;
+        if n == 2 {
+            let after = [balances_fp[0] + amounts_fp[0], balances_fp[1] + amounts_fp[1]];
+            record_oracle(&mut ctx.accounts.pool, after, Clock::get()?.unix_timestamp)?;
+        }
+        emit!(JoinEvent {
+            pool:         ctx.accounts.pool.key(),
+            pool_id:      ctx.accounts.pool.pool_id,
+            user:         ctx.accounts.user.key(),
+            amounts_in,
+            bpt_out,
+            protocol_bpt,
+        });
+        leave(&mut ctx.accounts.pool);
         Ok(())
     }
 
     /* ---------------------------------------------------------------
-       Exit – burn BPT, withdraw proportional tokens
+       Exit – burn BPT, withdraw proportional tokens, each at least
+       its `minimum_amounts_out`. A proportional exit moves no price, so
+       it is charged no swap fee and pays the protocol nothing.
+       Shares are of cash plus managed balances, and each must fit in
+       the cash custody holds.
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       optionally followed by [mint0, mint1, …, hook accounts…] for
+       checked transfers (with the other token program of a mixed pool)
+       Amounts out are what custody sends; a transfer fee comes out of
+       what the user receives.
     ---------------------------------------------------------------- */
     pub fn exit_exact_bpt_in_for_tokens_out<'info>(
         ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
         bpt_in: u64,
+        minimum_amounts_out: Vec<u64>,
     ) -> Result<()> {
+        enter(&mut ctx.accounts.pool)?;
         let pool = &ctx.accounts.pool;
         let n    = pool.weights.len();
+        require!(minimum_amounts_out.len() == n, ErrorCode::LengthMismatch);
 
-        require!(ctx.remaining_accounts.len() == n * 2, ErrorCode::LengthMismatch);
-        require!(bpt_in > 0 && bpt_in <= pool.total_bpt, ErrorCode::MathUnderflow);
+        let (mints, hook_accounts) = trailing_mints(ctx.remaining_accounts, n * 2, n)?;
+        check_token_pairs(ctx.remaining_accounts, &ctx.accounts.pool_registration, ctx.accounts.user.key)?;
+        let total_bpt = ctx.accounts.lp_mint.supply;
+        require!(bpt_in > 0, ErrorCode::ZeroAmount);
+        require!(bpt_in <= total_bpt, ErrorCode::MathUnderflow);
 
-        // 1. balances
+        // 1. balances (cash plus managed), and the token program of each
         let mut balances_fp = Vec::with_capacity(n);
+        let mut cash        = Vec::with_capacity(n);
+        let mut programs    = Vec::with_capacity(n);
         for i in 0..n {
-            let vault_ai = &ctx.remaining_accounts[i * 2 + 1];
-            let data     = vault_ai.try_borrow_data()?;
-            let acct     = SplAccount::unpack_from_slice(&data)?;
-            balances_fp.push(U256::from(acct.amount) * fixed::ONE);
+            let program = ctx.accounts.pool_registration.token_program(i)?;
+            let acct = live_custody(&ctx.remaining_accounts[i * 2 + 1], &program, ctx.accounts.lp_mint_authority.key)?;
+            balances_fp.push(U256::from(pool_balance(&ctx.accounts.pool_registration, i, acct.amount)?) * fixed::ONE);
+            cash.push(acct.amount);
+            programs.push(program);
         }
 
-        // 2. maths
-        let mut tokens_out = Vec::with_capacity(n);
-        let bpt_in_fp      = U256::from(bpt_in) * fixed::ONE;
-        let total_bpt_fp   = U256::from(pool.total_bpt) * fixed::ONE;
-        let fee_fp         = U256::from(pool.swap_fee);
-        for i in 0..n {
-            let out_fp = weighted_math::calc_token_out_given_exact_bpt_in(
-                balances_fp[i],
-                U256::from(pool.weights[i]),
-                bpt_in_fp,
-                total_bpt_fp,
-                fee_fp,
-            );
-            tokens_out.push((out_fp / fixed::ONE).as_u64());
+        // 2. maths; what asset managers hold is priced but cannot be paid
+        let tokens_out = quote_exit(&balances_fp, bpt_in, total_bpt)?;
+        for ((out, min), cash) in tokens_out.iter().zip(&minimum_amounts_out).zip(&cash) {
+            require!(out >= min, ErrorCode::SlippageLimit);
+            require!(out <= cash, ErrorCode::InsufficientCash);
         }
+        require!(tokens_out.iter().any(|t| *t > 0), ErrorCode::ZeroAmountOut);
 
         // 3. burn BPT
         let token_prog = ctx.accounts.token_program.to_account_info();
         let burn_ctx = CpiContext::new(
             token_prog.clone(),
             Burn {
-                mint:      ctx.accounts.lp_mint.clone(),
-                from:      ctx.accounts.user_lp_account.clone(),
+                mint:      ctx.accounts.lp_mint.to_account_info(),
+                from:      ctx.accounts.user_lp_account.to_account_info(),
                 authority: ctx.accounts.user.to_account_info(),
             },
         );
@@ -197,88 +469,895 @@ pub mod weighted_pool {
                 to:        ctx.remaining_accounts[i * 2].clone(),
                 authority: ctx.accounts.lp_mint_authority.clone(),
             };
-            token::transfer(
-                CpiContext::new_with_signer(token_prog.clone(), cpi_accounts, signer_seeds),
+            let program = token_program_account(&programs[i], &token_prog, hook_accounts)?;
+            transfer(
+                CpiContext::new_with_signer(program.clone(), cpi_accounts, signer_seeds),
+                mints.map(|m| &m[i]),
+                hook_accounts,
                 tokens_out[i],
             )?;
         }
 
         // 5. bookkeeping
-        ctx.accounts.pool.total_bpt = ctx.accounts.pool
-            .total_bpt
-            .checked_sub(bpt_in)
-            .ok_or(ErrorCode::MathUnderflow)?;
+        ctx.accounts.sync_total_bpt()?;
+        if n == 2 {
+            let mut after = [U256::zero(); 2];
+            for i in 0..2 {
+                after[i] = balances_fp[i].saturating_sub(U256::from(tokens_out[i]) * fixed::ONE);
+            }
+            record_oracle(&mut ctx.accounts.pool, after, Clock::get()?.unix_timestamp)?;
+        }
+        emit!(ExitEvent {
+            pool:          ctx.accounts.pool.key(),
+            pool_id:       ctx.accounts.pool.pool_id,
+            user:          ctx.accounts.user.key(),
+            bpt_in,
+            amounts_out:   tokens_out,
+            protocol_bpt:  0,
+            protocol_fees: vec![0; n],
+        });
+        leave(&mut ctx.accounts.pool);
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Recovery exit – burn BPT for a proportional share of every token
+       that can still move
+       remaining_accounts: [user_tok0, vault_tok0, user_tok1, vault_tok1, …]
+       optionally followed by [mint0, mint1, …, hook accounts…] for
+       checked transfers
+       A token whose custody or user account is frozen by its mint's
+       freeze authority, or whose custody was closed or is no longer the
+       pool's, is skipped rather than failing the exit: its share stays
+       with the pool for the remaining LPs. No swap or protocol fee is
+       charged. Shares are of cash plus managed balances, and each must
+       fit in the cash custody holds.
+       Only open while a guardian has paused the pool or the Vault, or
+       while some token is skipped; otherwise `exit_exact_bpt_in_for_tokens_out`
+       is the way out.
+    ---------------------------------------------------------------- */
+    pub fn recovery_exit<'info>(
+        ctx: Context<'_, '_, '_, 'info, PoolContext<'info>>,
+        bpt_in: u64,
+    ) -> Result<()> {
+        enter(&mut ctx.accounts.pool)?;
+        let pool = &ctx.accounts.pool;
+        let n    = pool.weights.len();
+        let (mints, hook_accounts) = trailing_mints(ctx.remaining_accounts, n * 2, n)?;
+        check_token_pairs(ctx.remaining_accounts, &ctx.accounts.pool_registration, ctx.accounts.user.key)?;
+        let total_bpt = ctx.accounts.lp_mint.supply;
+        require!(bpt_in > 0, ErrorCode::ZeroAmount);
+        require!(bpt_in <= total_bpt, ErrorCode::MathUnderflow);
+
+        // 1. proportional shares of the tokens that can still move
+        let registration = &ctx.accounts.pool_registration;
+        let paused = registration.paused || ctx.accounts.vault_state.is_paused(Clock::get()?.unix_timestamp);
+        let mut skipped     = false;
+        let mut amounts_out = vec![0u64; n];
+        let mut programs    = Vec::with_capacity(n);
+        for i in 0..n {
+            let program  = registration.token_program(i)?;
+            let vault_ai = &ctx.remaining_accounts[i * 2 + 1];
+            programs.push(program);
+            let Ok(custody) = live_custody(vault_ai, &program, ctx.accounts.lp_mint_authority.key) else {
+                msg!("skipping token {}", i);
+                skipped = true;
+                continue;
+            };
+            if unpack_token_account(&ctx.remaining_accounts[i * 2], &program)?.is_frozen() {
+                msg!("skipping token {}: user account {} is frozen", i, ctx.remaining_accounts[i * 2].key);
+                skipped = true;
+                continue;
+            }
+            let balance = pool_balance(registration, i, custody.amount)?;
+            let share = U256::from(balance) * U256::from(bpt_in) / U256::from(total_bpt);
+            amounts_out[i] = share.as_u64();
+            require!(amounts_out[i] <= custody.amount, ErrorCode::InsufficientCash);
+        }
+        require!(paused || skipped, ErrorCode::RecoveryNotNeeded);
+        require!(amounts_out.iter().any(|a| *a > 0), ErrorCode::ZeroAmountOut);
+
+        // 2. burn BPT
+        let token_prog = ctx.accounts.token_program.to_account_info();
+        token::burn(
+            CpiContext::new(
+                token_prog.clone(),
+                Burn {
+                    mint:      ctx.accounts.lp_mint.to_account_info(),
+                    from:      ctx.accounts.user_lp_account.to_account_info(),
+                    authority: ctx.accounts.user.to_account_info(),
+                },
+            ),
+            bpt_in,
+        )?;
+
+        // 3. vault → user transfers
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        for i in (0..n).filter(|i| amounts_out[*i] > 0) {
+            let cpi_accounts = Transfer {
+                from:      ctx.remaining_accounts[i * 2 + 1].clone(),
+                to:        ctx.remaining_accounts[i * 2].clone(),
+                authority: ctx.accounts.lp_mint_authority.clone(),
+            };
+            let program = token_program_account(&programs[i], &token_prog, hook_accounts)?;
+            transfer(
+                CpiContext::new_with_signer(program.clone(), cpi_accounts, &[seed_slice]),
+                mints.map(|m| &m[i]),
+                hook_accounts,
+                amounts_out[i],
+            )?;
+        }
+
+        // 4. bookkeeping
+        ctx.accounts.sync_total_bpt()?;
+        emit!(ExitEvent {
+            pool:          ctx.accounts.pool.key(),
+            pool_id:       ctx.accounts.pool.pool_id,
+            user:          ctx.accounts.user.key(),
+            bpt_in,
+            amounts_out,
+            protocol_bpt:  0,
+            protocol_fees: vec![0; n],
+        });
+        leave(&mut ctx.accounts.pool);
         Ok(())
     }
 
     /* ---------------------------------------------------------------
        Swap – exact in → out across two tokens
+       remaining_accounts: optionally [mint_in, mint_out, hook accounts…]
+       for checked transfers (with the other token program of a mixed pool)
+       Priced on what custody received, net of any transfer fee, which
+       must reach MIN_TRADE_AMOUNT; the protocol's share of the fee is
+       paid from custody to the collector.
+       Returns the amount sent out (read by the Vault's batch swap).
     ---------------------------------------------------------------- */
     pub fn swap_exact_token_in_for_token_out<'info>(
         ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
         amount_in: u64,
         minimum_amount_out: u64,
-    ) -> Result<()> {
-        // 1. read vault balances
-        let balance_in_fp = {
-            let data = ctx.accounts.vault_in.try_borrow_data()?;
-            U256::from(SplAccount::unpack_from_slice(&data)?.amount) * fixed::ONE
+    ) -> Result<u64> {
+        swap_exact_in(ctx, amount_in, minimum_amount_out, None)
+    }
+
+    /* ---------------------------------------------------------------
+       Swap with a price limit – the exact‑in swap, also failing when
+       the post‑trade spot price of the token out in units of the token
+       in (18‑dec, fee included) exceeds `max_price`. The limit holds
+       whatever amount reaches the swap, so it guards the later legs
+       of a route whose amounts are unknown upfront. The Vault's batch
+       swap calls the plain swap, whose arguments every pool shares.
+    ---------------------------------------------------------------- */
+    pub fn swap_exact_token_in_for_token_out_with_max_price<'info>(
+        ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        max_price: Option<u128>,
+    ) -> Result<u64> {
+        swap_exact_in(ctx, amount_in, minimum_amount_out, max_price)
+    }
+
+    /* ---------------------------------------------------------------
+       Managed transfer – move idle cash to an asset manager
+       Only the Vault may ask (its asset manager authority PDA must
+       sign); it checks the manager and tracks the managed balance.
+    ---------------------------------------------------------------- */
+    pub fn managed_transfer(ctx: Context<ManagedTransfer>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.pool.entered, ErrorCode::Reentrancy);
+        let pool_key = ctx.accounts.pool.key();
+        let bump_arr = [ctx.bumps.lp_mint_authority];
+        let seed_slice: &[&[u8]] = &[b"lp-mint-authority", pool_key.as_ref(), &bump_arr];
+        let cpi_accounts = Transfer {
+            from:      ctx.accounts.from.to_account_info(),
+            to:        ctx.accounts.to.to_account_info(),
+            authority: ctx.accounts.lp_mint_authority.clone(),
         };
-        let balance_out_fp = {
-            let data = ctx.accounts.vault_out.try_borrow_data()?;
-            U256::from(SplAccount::unpack_from_slice(&data)?.amount) * fixed::ONE
+        transfer(
+            CpiContext::new_with_signer(ctx.accounts.token_program.to_account_info(), cpi_accounts, &[seed_slice]),
+            None,
+            &[],
+            amount,
+        )
+    }
+
+    /* ---------------------------------------------------------------
+       Query – price an exact‑in swap against caller‑supplied balances
+       Read‑only; used by the Vault's query_batch_swap.
+       remaining_accounts: [pool_registration], which places the tokens
+    ---------------------------------------------------------------- */
+    pub fn query_swap<'info>(
+        ctx: Context<'_, '_, 'info, 'info, QuerySwap<'info>>,
+        amount_in: u64,
+        balance_in: u64,
+        balance_out: u64,
+    ) -> Result<SwapQuote> {
+        let registration = ctx.remaining_accounts.first().ok_or(ErrorCode::InvalidSwapTokens)?;
+        let registration = Account::<PoolRegistration>::try_from(registration)?;
+        require_keys_eq!(registration.pool, ctx.accounts.pool.key(), ErrorCode::InvalidSwapTokens);
+        let custody_index = |ai: &AccountInfo| {
+            registration
+                .token_accounts
+                .iter()
+                .position(|a| *a == ai.key())
+                .ok_or(ErrorCode::InvalidSwapTokens)
         };
+        let index_in  = custody_index(&ctx.accounts.pool_token_in)?;
+        let index_out = custody_index(&ctx.accounts.pool_token_out)?;
+        require!(index_in != index_out, ErrorCode::InvalidSwapTokens);
+        quote_exact_in(
+            &ctx.accounts.pool,
+            ctx.accounts.fees_collector.swap_fee_percentage,
+            (index_in, index_out),
+            amount_in,
+            balance_in,
+            balance_out,
+        )
+    }
 
-        // 2. maths: how much out?
-        let fee_fp      = U256::from(ctx.accounts.pool.swap_fee);
-        let weights     = &ctx.accounts.pool.weights;
-        let weight_in_fp  = U256::from(weights[0]);
-        let weight_out_fp = U256::from(weights[1]);
-        let amount_in_fp  = U256::from(amount_in) * fixed::ONE;
-        let out_fp = weighted_math::calc_out_given_in(
-            balance_in_fp,
-            balance_out_fp,
-            weight_in_fp,
-            weight_out_fp,
-            amount_in_fp,
-            fee_fp,
-        );
-        let amount_out = (out_fp / fixed::ONE).as_u64();
-        require!(amount_out >= minimum_amount_out, ErrorCode::MathUnderflow);
+    /* ---------------------------------------------------------------
+       Query – the protocol fee a join of `amounts_in` would take at the
+       current cash plus managed balances, as BPT minted to the fee
+       collector.
+       Swap fees are paid to the collector as they are charged and
+       proportional exits pay none, so nothing else accrues. Read‑only;
+       the amount is returned for simulating callers.
+       remaining_accounts: [vault_tok0, vault_tok1, …] in pool order
+    ---------------------------------------------------------------- */
+    pub fn query_due_protocol_fees(
+        ctx: Context<QueryDueProtocolFees>,
+        amounts_in: Vec<u64>,
+    ) -> Result<DueProtocolFees> {
+        let pool = &ctx.accounts.pool;
+        let registration = &ctx.accounts.pool_registration;
+        let n = pool.weights.len();
+        require!(ctx.remaining_accounts.len() == n, ErrorCode::LengthMismatch);
+        require!(amounts_in.len() == n, ErrorCode::LengthMismatch);
+        let mut balances_fp = Vec::with_capacity(n);
+        for (i, ai) in ctx.remaining_accounts.iter().enumerate() {
+            let expected = registration.token_accounts.get(i).ok_or(ErrorCode::UnknownCustodyAccount)?;
+            require_keys_eq!(ai.key(), *expected, ErrorCode::UnknownCustodyAccount);
+            let acct = custody_account(ai, &registration.token_program(i)?, ctx.accounts.lp_mint_authority.key)?;
+            balances_fp.push(U256::from(pool_balance(registration, i, acct.amount)?) * fixed::ONE);
+        }
 
-        // 3. transfer in (user → vault)
-        let token_prog = ctx.accounts.token_program.to_account_info();
-        let cpi_in = Transfer {
-            from:      ctx.accounts.user_token_account_in.clone(),
-            to:        ctx.accounts.vault_in.clone(),
-            authority: ctx.accounts.user_authority.to_account_info(),
+        // the first join pays no fee
+        let protocol_pct = ctx.accounts.fees_collector.swap_fee_percentage;
+        let total_bpt = ctx.accounts.lp_mint.supply;
+        if total_bpt == 0 {
+            return Ok(DueProtocolFees { protocol_bpt: 0 });
+        }
+        let amounts_fp: Vec<U256> = amounts_in.iter().map(|a| U256::from(*a) * fixed::ONE).collect();
+        let JoinQuote { protocol_bpt, .. } = quote_join(pool, protocol_pct, &balances_fp, &amounts_fp, total_bpt)?;
+        Ok(DueProtocolFees { protocol_bpt })
+    }
+
+    /* ---------------------------------------------------------------
+       Verify – permissionless health probe: recompute the invariant
+       from custody (plus managed) balances, compare the LP mint's
+       supply with `total_bpt`, sum the weights, and emit the findings
+       as a `PoolHealth` event, also returned for simulating callers.
+       Changes nothing.
+       remaining_accounts: [vault_tok0, vault_tok1, …] in pool order
+       A frozen custody account is still read; a closed one fails with
+       `InvalidVaultOwner`.
+    ---------------------------------------------------------------- */
+    pub fn verify_pool(ctx: Context<VerifyPool>) -> Result<PoolHealth> {
+        let pool = &ctx.accounts.pool;
+        let registration = &ctx.accounts.pool_registration;
+        let n = pool.weights.len();
+        require!(ctx.remaining_accounts.len() == n, ErrorCode::LengthMismatch);
+        let mut balances = Vec::with_capacity(n);
+        for (i, ai) in ctx.remaining_accounts.iter().enumerate() {
+            let expected = registration.token_accounts.get(i).ok_or(ErrorCode::UnknownCustodyAccount)?;
+            require_keys_eq!(ai.key(), *expected, ErrorCode::UnknownCustodyAccount);
+            let acct = custody_account(ai, &registration.token_program(i)?, ctx.accounts.lp_mint_authority.key)?;
+            balances.push(pool_balance(registration, i, acct.amount)?);
+        }
+
+        let balances_fp: Vec<U256> = balances.iter().map(|b| U256::from(*b) * fixed::ONE).collect();
+        let weights_fp: Vec<U256> = pool.weights.iter().map(|w| U256::from(*w)).collect();
+        // an empty pool, or one with a drained token, has no invariant
+        let invariant = weighted_math::try_calculate_invariant(&balances_fp, &weights_fp).unwrap_or_default();
+        let weight_sum = pool.weights.iter().try_fold(0u128, |sum, w| sum.checked_add(*w));
+        let bpt_supply = ctx.accounts.lp_mint.supply;
+        let healthy = bpt_supply == pool.total_bpt
+            && weight_sum == Some(fixed::ONE.as_u128())
+            && invariant.is_zero() == (bpt_supply == 0);
+        let health = PoolHealth {
+            pool:       pool.key(),
+            pool_id:    pool.pool_id,
+            balances,
+            invariant:  invariant.as_u128(),
+            bpt_supply,
+            total_bpt:  pool.total_bpt,
+            weight_sum: weight_sum.unwrap_or(u128::MAX),
+            healthy,
         };
-        token::transfer(CpiContext::new(token_prog.clone(), cpi_in), amount_in)?;
+        emit!(health.clone());
+        Ok(health)
+    }
 
-        // 4. transfer out (vault → user)
-        let bump      = ctx.bumps.lp_mint_authority;
-        let pool_key  = ctx.accounts.pool.key();
-        let bump_arr  = [bump];
-        let seed_slice: &[&[u8]] = &[
-            b"lp-mint-authority",
-            pool_key.as_ref(),
-            &bump_arr,
-        ];
-        let signer_seeds = &[seed_slice];
-        let cpi_out = Transfer {
-            from:      ctx.accounts.vault_out.clone(),
-            to:        ctx.accounts.user_token_account_out.clone(),
+    /* ---------------------------------------------------------------
+       Oracle – turn on TWAP accumulation for a two‑token pool and
+       create its sample buffer; requires the `SetSwapFee` role.
+       Accumulation starts with the next swap, join or exit.
+    ---------------------------------------------------------------- */
+    pub fn enable_oracle(ctx: Context<EnableOracle>) -> Result<()> {
+        require!(
+            ctx.accounts.authorizer.can_perform(Action::SetSwapFee, &ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+        let pool = &mut ctx.accounts.pool;
+        require!(pool.weights.len() == 2, ErrorCode::OracleUnsupported);
+        pool.oracle.enabled = true;
+
+        let oracle = &mut ctx.accounts.oracle;
+        oracle.pool = pool.key();
+        oracle.next = 0;
+        oracle.count = 0;
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Oracle – snapshot the pool's accumulators into the sample
+       buffer; permissionless, at most once per ORACLE_SAMPLE_INTERVAL
+    ---------------------------------------------------------------- */
+    pub fn update_oracle(ctx: Context<UpdateOracle>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let current = &ctx.accounts.pool.oracle;
+        require!(current.timestamp > 0, ErrorCode::OracleNotReady);
+        let oracle = &mut ctx.accounts.oracle;
+        if let Some(last) = oracle.latest() {
+            require!(now >= last.timestamp + ORACLE_SAMPLE_INTERVAL, ErrorCode::OracleSampleTooSoon);
+        }
+        let mut sample = OracleSample { timestamp: now, accumulated: [0; 3] };
+        for variable in OracleVariable::ALL {
+            sample.accumulated[variable as usize] = current.accumulated_at(variable, now)?;
+        }
+        oracle.push(sample);
+        Ok(())
+    }
+
+    /* ---------------------------------------------------------------
+       Oracle – time‑weighted geometric means (18‑dec), one per query,
+       each over [now − ago − seconds, now − ago]. Read‑only.
+    ---------------------------------------------------------------- */
+    pub fn query_twap(ctx: Context<QueryTwap>, queries: Vec<TwapQuery>) -> Result<Vec<u128>> {
+        let now = Clock::get()?.unix_timestamp;
+        let (current, oracle) = (&ctx.accounts.pool.oracle, &ctx.accounts.oracle);
+        require!(current.timestamp > 0, ErrorCode::OracleNotReady);
+        queries
+            .iter()
+            .map(|q| {
+                require!(q.seconds > 0 && q.ago >= 0, ErrorCode::InvalidTwapQuery);
+                let end   = now.checked_sub(q.ago).ok_or(ErrorCode::InvalidTwapQuery)?;
+                let start = end.checked_sub(q.seconds).ok_or(ErrorCode::InvalidTwapQuery)?;
+                let acc_start = oracle.accumulated_at(current, q.variable, start, now)?;
+                let acc_end   = oracle.accumulated_at(current, q.variable, end, now)?;
+                let mean = try_geometric_mean((start, I256::from(acc_start)), (end, I256::from(acc_end)))
+                    .map_err(ErrorCode::from)?;
+                require!(mean <= U256::from(u128::MAX), ErrorCode::MathOverflow);
+                Ok(mean.as_u128())
+            })
+            .collect()
+    }
+}
+
+/// Write the pool state and register the pool with the Vault: shared by
+/// `initialize_pool` and `initialize_canonical_pool`. Two‑token pools use
+/// the `TwoToken` specialization, larger ones `MinimalSwapInfo`.
+#[allow(clippy::too_many_arguments)]
+fn setup_pool<'info>(
+    pool: &mut Account<'info, Pool>,
+    vault_state: &Account<'info, VaultState>,
+    lp_mint: Pubkey,
+    vault_program: AccountInfo<'info>,
+    cpi_accounts: VaultRegisterPool<'info>,
+    tokens: Vec<Pubkey>,
+    weights: Vec<u128>,
+    swap_fee: u64,
+) -> Result<()> {
+    check_swap_fee(swap_fee)?;
+    pool.vault     = vault_state.key();
+    pool.lp_mint   = lp_mint;
+    pool.weights   = weights;
+    pool.swap_fee  = swap_fee;
+    pool.total_bpt = 0;
+    pool.version   = POOL_VERSION;
+
+    let specialization = if tokens.len() == 2 {
+        Specialization::TwoToken
+    } else {
+        Specialization::MinimalSwapInfo
+    };
+    let pool_id = derive_pool_id(&vault_state.key(), &pool.key(), specialization, vault_state.pool_count);
+    let registered = vault_register_pool(
+        CpiContext::new(vault_program, cpi_accounts),
+        pool_id,
+        specialization,
+        tokens,
+    )?;
+    pool.pool_id = registered.get();
+    Ok(())
+}
+
+/// The exact‑in swap behind `swap_exact_token_in_for_token_out` and its
+/// price‑limited form; `max_price` bounds the post‑trade spot price.
+fn swap_exact_in<'info>(
+    ctx: Context<'_, '_, '_, 'info, SwapContext<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    max_price: Option<u128>,
+) -> Result<u64> {
+    enter(&mut ctx.accounts.pool)?;
+    let now = Clock::get()?.unix_timestamp;
+    require!(!ctx.accounts.vault_state.is_paused(now), ErrorCode::VaultPaused);
+    require!(!ctx.accounts.pool_registration.paused, ErrorCode::PoolPaused);
+    require!(amount_in > 0, ErrorCode::ZeroAmount);
+
+    // 1. read vault balances: cash in custody plus what asset managers hold
+    let registration = &ctx.accounts.pool_registration;
+    let authority = ctx.accounts.lp_mint_authority.key;
+    let (balance_in, mint_in, program_in) = custody_balance(registration, &ctx.accounts.vault_in, authority)?;
+    let (balance_out, mint_out, program_out) = custody_balance(registration, &ctx.accounts.vault_out, authority)?;
+    require!(
+        ctx.accounts.user_token_account_in.mint == mint_in && ctx.accounts.user_token_account_out.mint == mint_out,
+        ErrorCode::InvalidUserTokenAccount
+    );
+    let indices = (token_index(registration, &mint_in)?, token_index(registration, &mint_out)?);
+    require!(indices.0 != indices.1, ErrorCode::InvalidSwapTokens);
+
+    let (mints, hook_accounts) = trailing_mints(ctx.remaining_accounts, 0, 2)?;
+    if let Some(mints) = mints {
+        require!(
+            mints[0].key() == mint_in && mints[1].key() == mint_out,
+            ErrorCode::InvalidTokenAccount
+        );
+    }
+    let (mint_in_ai, mint_out_ai) = (mints.map(|m| &m[0]), mints.map(|m| &m[1]));
+    let token_prog = ctx.accounts.token_program.to_account_info();
+    let prog_in  = token_program_account(&program_in, &token_prog, hook_accounts)?.clone();
+    let prog_out = token_program_account(&program_out, &token_prog, hook_accounts)?.clone();
+
+    // 2. transfer in (user → vault); a transfer fee leaves custody
+    //    less than was sent, and the pool prices what arrived
+    let cash_in = token_balance(&ctx.accounts.vault_in, &program_in)?;
+    let cpi_in = Transfer {
+        from:      ctx.accounts.user_token_account_in.to_account_info(),
+        to:        ctx.accounts.vault_in.clone(),
+        authority: ctx.accounts.user_authority.to_account_info(),
+    };
+    transfer(CpiContext::new(prog_in.clone(), cpi_in), mint_in_ai, hook_accounts, amount_in)?;
+    let received = token_balance(&ctx.accounts.vault_in, &program_in)?
+        .checked_sub(cash_in)
+        .ok_or(ErrorCode::MathUnderflow)?;
+    require!(received >= MIN_TRADE_AMOUNT, ErrorCode::DustAmount);
+
+    // 3. maths: how much out?
+    let SwapQuote { amount_out, protocol_fee } = quote_exact_in(
+        &ctx.accounts.pool,
+        ctx.accounts.fees_collector.swap_fee_percentage,
+        indices,
+        received,
+        balance_in,
+        balance_out,
+    )?;
+    require!(amount_out > 0, ErrorCode::ZeroAmountOut);
+    require!(amount_out >= minimum_amount_out, ErrorCode::SlippageLimit);
+    require!(amount_out <= token_balance(&ctx.accounts.vault_out, &program_out)?, ErrorCode::InsufficientCash);
+    let in_before  = U256::from(balance_in) * fixed::ONE;
+    let out_before = U256::from(balance_out) * fixed::ONE;
+    let in_after   = (U256::from(balance_in) + U256::from(received - protocol_fee)) * fixed::ONE;
+    let out_after  = U256::from(balance_out.saturating_sub(amount_out)) * fixed::ONE;
+    // the price the next trade in would start at; one that cannot be
+    // priced does not meet a limit
+    let spot_after = spot_price(&ctx.accounts.pool, indices, in_after, out_after);
+    if let Some(max_price) = max_price {
+        require!(spot_after.is_some_and(|p| p <= max_price), ErrorCode::PriceLimitExceeded);
+    }
+
+    // 4. transfers out (vault → collector, vault → user)
+    let bump      = ctx.bumps.lp_mint_authority;
+    let pool_key  = ctx.accounts.pool.key();
+    let bump_arr  = [bump];
+    let seed_slice: &[&[u8]] = &[
+        b"lp-mint-authority",
+        pool_key.as_ref(),
+        &bump_arr,
+    ];
+    let signer_seeds = &[seed_slice];
+    if protocol_fee > 0 {
+        check_fee_account(
+            &ctx.accounts.protocol_fee_account,
+            &program_in,
+            &ctx.accounts.fees_collector.key(),
+            &mint_in,
+        )?;
+        let cpi_fee = Transfer {
+            from:      ctx.accounts.vault_in.clone(),
+            to:        ctx.accounts.protocol_fee_account.clone(),
             authority: ctx.accounts.lp_mint_authority.clone(),
         };
-        token::transfer(
-            CpiContext::new_with_signer(token_prog, cpi_out, signer_seeds),
-            amount_out,
+        transfer(
+            CpiContext::new_with_signer(prog_in, cpi_fee, signer_seeds),
+            mint_in_ai,
+            hook_accounts,
+            protocol_fee,
         )?;
+    }
+    let cpi_out = Transfer {
+        from:      ctx.accounts.vault_out.clone(),
+        to:        ctx.accounts.user_token_account_out.to_account_info(),
+        authority: ctx.accounts.lp_mint_authority.clone(),
+    };
+    transfer(
+        CpiContext::new_with_signer(prog_out, cpi_out, signer_seeds),
+        mint_out_ai,
+        hook_accounts,
+        amount_out,
+    )?;
 
-        Ok(())
+    // 5. oracle, with the balances in pool order
+    let after = if ctx.accounts.pool_registration.tokens.first() == Some(&mint_in) {
+        [in_after, out_after]
+    } else {
+        [out_after, in_after]
+    };
+    if ctx.accounts.pool.weights.len() == 2 {
+        record_oracle(&mut ctx.accounts.pool, after, now)?;
+    }
+
+    let pool = &ctx.accounts.pool;
+    let swap_fee_amount = fees::try_fee_amount(U256::from(received) * fixed::ONE, U256::from(pool.swap_fee))
+        .map_err(ErrorCode::from)?;
+    emit!(SwapEvent {
+        pool:              pool.key(),
+        pool_id:           pool.pool_id,
+        user:              ctx.accounts.user_authority.key(),
+        token_in:          mint_in,
+        token_out:         mint_out,
+        amount_in,
+        amount_out,
+        protocol_fee,
+        spot_price_before: spot_price(pool, indices, in_before, out_before).unwrap_or_default(),
+        spot_price_after:  spot_after.unwrap_or_default(),
+        swap_fee_amount:   to_amount_out(swap_fee_amount)?,
+        balance_in:        in_before.as_u128(),
+        balance_out:       out_before.as_u128(),
+    });
+    leave(&mut ctx.accounts.pool);
+    Ok(amount_out)
+}
+
+/// Exact‑in swap pricing shared by the swap and its query: amount out and
+/// the protocol's part of the amount in. `indices` are the pool positions
+/// of the tokens (in, out).
+fn quote_exact_in(
+    pool: &Pool,
+    protocol_pct: u64,
+    indices: (usize, usize),
+    amount_in: u64,
+    balance_in: u64,
+    balance_out: u64,
+) -> Result<SwapQuote> {
+    let params = SwapParams {
+        balance_in:  U256::from(balance_in) * fixed::ONE,
+        weight_in:   weight(pool, indices.0)?,
+        balance_out: U256::from(balance_out) * fixed::ONE,
+        weight_out:  weight(pool, indices.1)?,
+        amount:      U256::from(amount_in) * fixed::ONE,
+        fee:         U256::from(pool.swap_fee),
+    };
+    let out_fp = params.try_out_given_in().map_err(ErrorCode::from)?;
+    let protocol_fee = if protocol_pct > 0 {
+        let fee_amount_fp = fees::try_fee_amount(params.amount, params.fee).map_err(ErrorCode::from)?;
+        to_amount_out(protocol_share(fee_amount_fp, protocol_pct)?)?
+    } else {
+        0
+    };
+    Ok(SwapQuote {
+        amount_out: to_amount_out(out_fp)?,
+        protocol_fee,
+    })
+}
+
+/// Marginal price (18‑dec, fee included) of the out token on 18‑dec
+/// balances of the tokens at `indices` (in, out); `None` where the
+/// balances cannot be priced.
+fn spot_price(pool: &Pool, indices: (usize, usize), balance_in: U256, balance_out: U256) -> Option<u128> {
+    let price = weighted_math::try_calc_spot_price(
+        balance_in,
+        weight(pool, indices.0).ok()?,
+        balance_out,
+        weight(pool, indices.1).ok()?,
+        U256::from(pool.swap_fee),
+    );
+    price.ok().and_then(|p| u128::try_from(p).ok())
+}
+
+/// Normalised weight (18‑dec) of the token at pool position `i`.
+fn weight(pool: &Pool, i: usize) -> Result<U256> {
+    pool.weights.get(i).map(|w| U256::from(*w)).ok_or_else(|| error!(ErrorCode::LengthMismatch))
+}
+
+/// Position of `mint` in the pool's token order, as the Vault registered it.
+fn token_index(registration: &PoolRegistration, mint: &Pubkey) -> Result<usize> {
+    registration
+        .tokens
+        .iter()
+        .position(|t| t == mint)
+        .ok_or_else(|| error!(ErrorCode::InvalidSwapTokens))
+}
+
+/// Exact‑tokens‑in join pricing shared by the join and
+/// `query_due_protocol_fees`: BPT out and the protocol's part of the
+/// swap fee on the unbalanced part of the join, in BPT. The first join
+/// seeds every balance, pays no fee and mints the invariant times the
+/// token count, MINIMUM_BPT of it locked; whatever custody already holds
+/// is seeded along with it.
+fn quote_join(
+    pool: &Pool,
+    protocol_pct: u64,
+    balances_fp: &[U256],
+    amounts_fp: &[U256],
+    total_bpt: u64,
+) -> Result<JoinQuote> {
+    let weights_fp: Vec<U256> = pool.weights.iter().map(|w| U256::from(*w)).collect();
+    if total_bpt == 0 {
+        require!(amounts_fp.iter().all(|a| !a.is_zero()), ErrorCode::ZeroAmount);
+        let seeded = balances_fp
+            .iter()
+            .zip(amounts_fp)
+            .map(|(b, a)| b.checked_add(*a).ok_or(ErrorCode::MathOverflow))
+            .collect::<std::result::Result<Vec<U256>, _>>()?;
+        let minted_fp = weighted_math::try_calculate_invariant(&seeded, &weights_fp)
+            .map_err(ErrorCode::from)?
+            .checked_mul(U256::from(weights_fp.len()))
+            .ok_or(ErrorCode::MathOverflow)?;
+        let bpt_out = to_amount_out(minted_fp)?.saturating_sub(MINIMUM_BPT);
+        return Ok(JoinQuote { bpt_out, protocol_bpt: 0, locked_bpt: MINIMUM_BPT });
+    }
+    let total_bpt_fp = U256::from(total_bpt) * fixed::ONE;
+    let bpt_out_given = |fee: U256| {
+        weighted_math::try_calc_bpt_out_given_exact_tokens_in(balances_fp, &weights_fp, amounts_fp, total_bpt_fp, fee)
+            .map_err(ErrorCode::from)
+    };
+    let bpt_out_fp = bpt_out_given(U256::from(pool.swap_fee))?;
+    let protocol_bpt = if protocol_pct > 0 {
+        let fee_bpt_fp = bpt_out_given(U256::zero())?.saturating_sub(bpt_out_fp);
+        to_amount_out(protocol_share(fee_bpt_fp, protocol_pct)?)?
+    } else {
+        0
+    };
+    Ok(JoinQuote { bpt_out: to_amount_out(bpt_out_fp)?, protocol_bpt, locked_bpt: 0 })
+}
+
+/// Exact‑BPT‑in exit pricing: the share of every balance that
+/// `bpt_in` of `total_bpt` redeems, in pool order, with no fee.
+fn quote_exit(balances_fp: &[U256], bpt_in: u64, total_bpt: u64) -> Result<Vec<u64>> {
+    weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+        balances_fp,
+        U256::from(bpt_in) * fixed::ONE,
+        U256::from(total_bpt) * fixed::ONE,
+        U256::zero(),
+    )
+    .map_err(ErrorCode::from)?
+    .iter()
+    .map(|o| to_amount_out(*o))
+    .collect()
+}
+
+/// Feed a two‑token pool's post‑operation balances (18‑dec, pool order)
+/// to its oracle accumulators, if enabled. Balances too small to price
+/// leave the last values in place rather than failing the operation.
+fn record_oracle(pool: &mut Pool, balances: [U256; 2], now: i64) -> Result<()> {
+    if !pool.oracle.enabled || pool.total_bpt == 0 || balances.iter().any(|b| b.is_zero()) {
+        return Ok(());
+    }
+    let values = oracle_values(&pool.weights, balances, pool.total_bpt).map_err(ErrorCode::from)?;
+    pool.oracle.record(now, values)
+}
+
+/// The `OracleVariable`s of a two‑token pool: price of token 1 in
+/// token 0, BPT price in token 0 and the invariant.
+fn oracle_values(weights: &[u128], balances: [U256; 2], total_bpt: u64) -> core::result::Result<[U256; 3], MathError> {
+    let weights = [U256::from(weights[0]), U256::from(weights[1])];
+    let per_weight_0 = fixed::try_div_down(balances[0], weights[0])?;
+    let per_weight_1 = fixed::try_div_down(balances[1], weights[1])?;
+    Ok([
+        fixed::try_div_down(per_weight_0, per_weight_1)?,
+        fixed::try_div_down(per_weight_0, U256::from(total_bpt) * fixed::ONE)?,
+        weighted_math::try_calculate_invariant(&balances, &weights)?,
+    ])
+}
+
+/// Protocol share (`pct`, 18‑dec) of a fee amount, rounded up.
+fn protocol_share(fee_amount_fp: U256, pct: u64) -> Result<U256> {
+    Ok(fees::try_protocol_share(fee_amount_fp, U256::from(pct)).map_err(ErrorCode::from)?)
+}
+
+/// 18‑dec fixed value of an amount the pool pays – tokens out, BPT
+/// minted, fees out of custody – rounded down. Joins, exits and swaps here
+/// take exact amounts in and exact BPT in, so nothing rounds up
+/// (`fixed::try_to_amount_in`); the math rounds ratios against the user.
+fn to_amount_out(value_fp: U256) -> Result<u64> {
+    Ok(fixed::try_to_amount_out(value_fp).map_err(ErrorCode::from)?)
+}
+
+
+/// A protocol fee account must be a token account of `mint` held by the collector.
+fn check_fee_account(ai: &AccountInfo, token_program: &Pubkey, collector: &Pubkey, mint: &Pubkey) -> Result<()> {
+    let acct = unpack_token_account(ai, token_program)?;
+    require!(acct.owner == *collector && acct.mint == *mint, ErrorCode::InvalidFeeAccount);
+    Ok(())
+}
+
+/// Deserialize a token account of `token_program` (SPL Token or
+/// Token‑2022), skipping any extensions.
+fn unpack_token_account(ai: &AccountInfo, token_program: &Pubkey) -> Result<SplAccount> {
+    require_keys_eq!(*ai.owner, *token_program, ErrorCode::InvalidTokenAccount);
+    let data = ai.try_borrow_data()?;
+    Ok(StateWithExtensions::<SplAccount>::unpack(&data)?.base)
+}
+
+/// Unpack a custody account before any math or transfer, naming why it
+/// cannot serve: closed, no longer an account of its token program, or
+/// not held by the pool authority (`InvalidVaultOwner`). A frozen
+/// account still unpacks; see `live_custody`.
+fn custody_account(ai: &AccountInfo, token_program: &Pubkey, authority: &Pubkey) -> Result<SplAccount> {
+    if ai.owner != token_program || ai.data_is_empty() {
+        msg!("custody account {} is closed or not a {} account", ai.key, token_program);
+        return err!(ErrorCode::InvalidVaultOwner);
+    }
+    let acct = unpack_token_account(ai, token_program)?;
+    require_keys_eq!(acct.owner, *authority, ErrorCode::InvalidVaultOwner);
+    Ok(acct)
+}
+
+/// A custody account a join, exit or swap can move tokens through: a
+/// `custody_account` its mint has not frozen. `recovery_exit` skips the
+/// tokens that fail either check instead.
+fn live_custody(ai: &AccountInfo, token_program: &Pubkey, authority: &Pubkey) -> Result<SplAccount> {
+    let acct = custody_account(ai, token_program, authority)?;
+    if acct.is_frozen() {
+        msg!("custody account {} is frozen", ai.key);
+        return err!(ErrorCode::TokenAccountFrozen);
+    }
+    Ok(acct)
+}
+
+/// The optional accounts trailing the `fixed` accounts of `rem`: none,
+/// or `n` mints followed by any transfer‑hook accounts.
+type CheckedAccounts<'a, 'info> = (Option<&'a [AccountInfo<'info>]>, &'a [AccountInfo<'info>]);
+fn trailing_mints<'a, 'info>(rem: &'a [AccountInfo<'info>], fixed: usize, n: usize) -> Result<CheckedAccounts<'a, 'info>> {
+    match rem.len().checked_sub(fixed) {
+        Some(0) => Ok((None, &[])),
+        Some(k) if k >= n => Ok((Some(&rem[fixed..fixed + n]), &rem[fixed + n..])),
+        _ => err!(ErrorCode::LengthMismatch),
     }
 }
 
+/// Check the `[user_tok0, vault_tok0, user_tok1, vault_tok1, …]` pairs
+/// leading a join's or exit's remaining accounts: pair i must be `user`'s
+/// account of the pool's i‑th token, then the Vault's registered custody
+/// account of it. A mismatch logs the offending index.
+fn check_token_pairs(rem: &[AccountInfo], registration: &PoolRegistration, user: &Pubkey) -> Result<()> {
+    for (i, pair) in rem.chunks_exact(2).take(registration.tokens.len()).enumerate() {
+        let user_account = unpack_token_account(&pair[0], &registration.token_program(i)?);
+        if !matches!(user_account, Ok(a) if a.mint == registration.tokens[i] && a.owner == *user) {
+            msg!("remaining account {}: not the user's account of token {}", i * 2, i);
+            return err!(ErrorCode::InvalidUserTokenAccount);
+        }
+        if pair[1].key() != registration.token_accounts[i] {
+            msg!("remaining account {}: not the custody account of token {}", i * 2 + 1, i);
+            return err!(ErrorCode::UnknownCustodyAccount);
+        }
+    }
+    Ok(())
+}
+
+/// A swap fee must lie within [`MIN_SWAP_FEE`, `MAX_SWAP_FEE`].
+fn check_swap_fee(swap_fee: u64) -> Result<()> {
+    require!(swap_fee >= MIN_SWAP_FEE, ErrorCode::SwapFeeTooLow);
+    require!(swap_fee <= MAX_SWAP_FEE, ErrorCode::SwapFeeTooHigh);
+    Ok(())
+}
+
+/// Reentrancy guard: marks `pool` entered in its account data before any
+/// CPI, so an invocation nested in one – through a token's transfer hook
+/// or a router – finds the mark and fails. A failed instruction reverts
+/// the mark; `leave` clears it on success.
+fn enter(pool: &mut Account<Pool>) -> Result<()> {
+    require!(!pool.entered, ErrorCode::Reentrancy);
+    pool.entered = true;
+    pool.exit(&crate::ID)
+}
+
+/// Clear the mark set by `enter`; Anchor writes it back on exit.
+fn leave(pool: &mut Account<Pool>) {
+    pool.entered = false;
+}
+
+/// A custody account's balance (cash plus what asset managers hold),
+/// mint and token program. The account must be one the Vault recorded
+/// for the pool, a `live_custody` account of the program it recorded.
+fn custody_balance(registration: &PoolRegistration, ai: &AccountInfo, authority: &Pubkey) -> Result<(u64, Pubkey, Pubkey)> {
+    let i = registration
+        .token_accounts
+        .iter()
+        .position(|a| *a == ai.key())
+        .ok_or(ErrorCode::UnknownCustodyAccount)?;
+    let program = registration.token_program(i)?;
+    let acct = live_custody(ai, &program, authority)?;
+    Ok((pool_balance(registration, i, acct.amount)?, acct.mint, program))
+}
+
+/// What the pool prices the `i`‑th token at: `cash` in custody plus
+/// what its asset manager holds.
+fn pool_balance(registration: &PoolRegistration, i: usize, cash: u64) -> Result<u64> {
+    let managed = registration.managed.get(i).ok_or(ErrorCode::UnknownCustodyAccount)?;
+    cash.checked_add(*managed).ok_or_else(|| error!(ErrorCode::MathOverflow))
+}
+
+/// The account of token program `key` among `token_program` and `others`:
+/// a pool mixing SPL Token and Token‑2022 mints is passed the program its
+/// context does not name among the trailing accounts.
+fn token_program_account<'a, 'info>(
+    key: &Pubkey,
+    token_program: &'a AccountInfo<'info>,
+    others: &'a [AccountInfo<'info>],
+) -> Result<&'a AccountInfo<'info>> {
+    std::iter::once(token_program)
+        .chain(others)
+        .find(|ai| ai.key == key)
+        .ok_or_else(|| error!(ErrorCode::MissingTokenProgram))
+}
+
+/// Amount held by a token account of `token_program`.
+fn token_balance(ai: &AccountInfo, token_program: &Pubkey) -> Result<u64> {
+    Ok(unpack_token_account(ai, token_program)?.amount)
+}
+
+/// Transfer through either token program: checked against `mint` when
+/// given, which Token‑2022 requires for mints with transfer fees or hooks;
+/// plain otherwise. A hooked mint's hook program and extra accounts are
+/// picked out of `hook_accounts`; the Vault vets hook programs when the
+/// token is registered.
+#[allow(deprecated)]
+fn transfer<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, Transfer<'info>>,
+    mint: Option<&AccountInfo<'info>>,
+    hook_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let Some(mint) = mint else {
+        return token::transfer(ctx, amount);
+    };
+    let decimals = {
+        let data = mint.try_borrow_data()?;
+        StateWithExtensions::<SplMint>::unpack(&data)?.base.decimals
+    };
+    onchain::invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        mint.clone(),
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        hook_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Token metadata of a BPT: no royalties, creators or collection.
+fn lp_metadata(name: String, symbol: String, uri: String) -> DataV2 {
+    DataV2 { name, symbol, uri, seller_fee_basis_points: 0, creators: None, collection: None, uses: None }
+}
+
 /* ------------------------------------------------------------------
    Accounts: initialize & pool contexts
 ------------------------------------------------------------------ */
@@ -288,15 +1367,30 @@ pub struct InitializePool<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// CHECK: an already‐initialized VaultState account
+    /// The vault the pool registers with
     #[account(mut)]
     pub vault_state: Account<'info, VaultState>,
 
-    pub vault_program: Program<'info, VaultProgram>,
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
 
-    /// CHECK: The LP‐token mint for this pool (must match the one in `pool.lp_mint`)
+    /// CHECK: registration PDA created by the Vault; seeds checked there
     #[account(mut)]
-    pub lp_mint: AccountInfo<'info>,
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// The LP‐token mint for this pool, minted by its authority PDA alone
+    #[account(
+        mut,
+        mint::authority = lp_mint_authority,
+        mint::token_program = token_program
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: PDA mint authority for `lp_mint`; derived from `["lp-mint-authority", pool.key().as_ref()]`
     #[account(
@@ -315,38 +1409,258 @@ pub struct InitializePool<'info> {
     )]
     pub pool: Account<'info, Pool>,
 
-    /// CHECK: Token program, used for minting; standard program
-    pub token_program: Program<'info, Token>,
+    /// SPL Token or Token‑2022, whichever owns the LP mint
+    pub token_program: Interface<'info, TokenInterface>,
 
-    /// CHECK: System program, used for account init; standard program
+    /// System program, used for account init
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PoolContext<'info> {
+#[instruction(config_hash: [u8; 32])]
+pub struct InitializeCanonicalPool<'info> {
+    /// Pays for the pool state
     #[account(mut)]
-    pub pool: Account<'info, Pool>,
+    pub payer: Signer<'info>,
 
-    /// CHECK: Same LP mint account as in InitializePool
+    /// Holds the Vault's `RegisterPool` role; pays for the registration
     #[account(mut)]
-    pub lp_mint: AccountInfo<'info>,
+    pub authority: Signer<'info>,
 
-    /// CHECK: PDA mint authority; seed ensures the correct authority
-    #[account(
-        seeds = [b"lp-mint-authority", pool.key().as_ref()],
-        bump
-    )]
-    pub lp_mint_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: the vault's Authorizer; validated by the Vault during registration
+    pub authorizer: AccountInfo<'info>,
+
+    /// CHECK: registration PDA created by the Vault; seeds checked there
+    #[account(mut)]
+    pub pool_registration: AccountInfo<'info>,
+
+    /// CHECK: the Vault's pool registry tail page; seeds checked there
+    #[account(mut)]
+    pub registry_page: AccountInfo<'info>,
+
+    pub vault_program: Program<'info, VaultProgram>,
+
+    /// The LP‐token mint for this pool, of either token program, minted
+    /// by its authority PDA alone
+    #[account(mint::authority = lp_mint_authority)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA mint authority for `lp_mint`; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// The canonical pool state PDA; `init` rejects a second pool with the same configuration
+    #[account(
+        init,
+        seeds = [b"pool-state", vault_state.key().as_ref(), config_hash.as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + Pool::INIT_SPACE
+    )]
+    pub pool: Account<'info, Pool>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PoolContext<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// The pool's LP mint
+    #[account(
+        mut,
+        address = pool.lp_mint,
+        mint::token_program = token_program
+    )]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA mint authority; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
 
     #[account(mut)]
     pub user: Signer<'info>,
 
-    /// CHECK: User's LP token account for receiving minted BPT
+    /// User's LP token account: receives minted BPT on joins and has it
+    /// burnt on exits
+    #[account(
+        mut,
+        token::mint = pool.lp_mint,
+        token::authority = user,
+        token::token_program = token_program
+    )]
+    pub user_lp_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's LP token account; checked when a protocol fee is minted
+    #[account(mut)]
+    pub protocol_fee_lp_account: AccountInfo<'info>,
+
+    /// CHECK: LP token account of the mint authority, which no instruction
+    /// spends; only the first join needs it, and checks it
+    #[account(mut)]
+    pub locked_bpt_account: Option<AccountInfo<'info>>,
+
+    /// The pool's Vault registration (per‑pool pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// SPL Token or Token‑2022: owns the LP mint and, unless the pool mixes
+    /// both programs, every token account
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> PoolContext<'info> {
+    /// Reload the LP mint once BPT was minted or burnt, and copy its
+    /// supply into `pool.total_bpt`.
+    fn sync_total_bpt(&mut self) -> Result<()> {
+        self.lp_mint.reload()?;
+        self.pool.total_bpt = self.lp_mint.supply;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct SetSwapFee<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLpMetadata<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The pool's LP mint
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA mint authority; signs as mint and update authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// CHECK: the LP mint's metadata PDA, created by the metadata program
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), pool.lp_mint.as_ref()],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    pub system_program: Program<'info, System>,
+
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct SetLpMetadata<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+
+    /// CHECK: PDA update authority of the LP mint's metadata
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    /// CHECK: the LP mint's metadata PDA
+    #[account(
+        mut,
+        seeds = [b"metadata", Metadata::id().as_ref(), pool.lp_mint.as_ref()],
+        bump,
+        seeds::program = Metadata::id()
+    )]
+    pub metadata: AccountInfo<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    /// CHECK: a pool under any layout version; `migrate_pool_state`
+    /// checks the discriminator and that its vault is the Authorizer's
+    #[account(mut, owner = crate::ID)]
+    pub pool: UncheckedAccount<'info>,
+
+    /// The Authorizer of the vault the pool is registered with
+    #[account(
+        seeds = [b"authorizer", authorizer.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    /// Must match `authorizer.admin`
+    pub admin: Signer<'info>,
+
     #[account(mut)]
-    pub user_lp_account: AccountInfo<'info>,
+    pub payer: Signer<'info>,
 
-    /// CHECK: Token program, used for transfers and minting
-    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 /* ------------------------------------------------------------------
@@ -357,24 +1671,36 @@ pub struct SwapContext<'info> {
     #[account(mut)]
     pub pool: Account<'info, Pool>,
 
-    /// CHECK: Vault account for the 'in' token; validated by seed off-chain
+    /// The vault this pool is registered with (pause state)
+    #[account(address = pool.vault)]
+    pub vault_state: Account<'info, VaultState>,
+
+    /// CHECK: Vault account for the 'in' token; must be the registration's
+    /// custody account of its mint
     #[account(mut)]
     pub vault_in: AccountInfo<'info>,
 
-    /// CHECK: Vault account for the 'out' token; validated by seed off-chain
+    /// CHECK: Vault account for the 'out' token; must be the registration's
+    /// custody account of its mint
     #[account(mut)]
     pub vault_out: AccountInfo<'info>,
 
     #[account(mut)]
     pub user_authority: Signer<'info>,
 
-    /// CHECK: User's token account for the 'in' mint; must be owned by user
-    #[account(mut)]
-    pub user_token_account_in: AccountInfo<'info>,
+    /// User's token account for the 'in' mint; checked against `vault_in`
+    #[account(
+        mut,
+        constraint = user_token_account_in.owner == user_authority.key() @ ErrorCode::InvalidUserTokenAccount
+    )]
+    pub user_token_account_in: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: User's token account for the 'out' mint; must be owned by user
-    #[account(mut)]
-    pub user_token_account_out: AccountInfo<'info>,
+    /// User's token account for the 'out' mint; checked against `vault_out`
+    #[account(
+        mut,
+        constraint = user_token_account_out.owner == user_authority.key() @ ErrorCode::InvalidUserTokenAccount
+    )]
+    pub user_token_account_out: InterfaceAccount<'info, TokenAccount>,
 
     /// CHECK: PDA for LP mint authority; seed ensures correct authority
     #[account(
@@ -383,8 +1709,199 @@ pub struct SwapContext<'info> {
     )]
     pub lp_mint_authority: AccountInfo<'info>,
 
-    /// CHECK: Token program, used for transfers
-    pub token_program: Program<'info, Token>,
+    /// SPL Token or Token‑2022; pool tokens of the other program (see
+    /// `PoolRegistration::token_programs`) pass it among the trailing accounts
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: Collector's account for the 'in' token; checked when a protocol fee is paid
+    #[account(mut)]
+    pub protocol_fee_account: AccountInfo<'info>,
+
+    /// The pool's Vault registration (managed balances, pause flag)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+}
+
+#[derive(Accounts)]
+pub struct ManagedTransfer<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The Vault's asset manager authority PDA; the Vault signs for it
+    /// only in `withdraw_managed`
+    #[account(
+        seeds = [b"asset-manager-authority", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub asset_manager_authority: Signer<'info>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// Pool custody account, one the Vault recorded for the pool
+    #[account(
+        mut,
+        constraint = from.owner == lp_mint_authority.key() @ ErrorCode::InvalidVaultOwner,
+        constraint = pool_registration.token_accounts.contains(&from.key()) @ ErrorCode::UnknownCustodyAccount
+    )]
+    pub from: InterfaceAccount<'info, TokenAccount>,
+
+    /// Asset manager's destination of the same mint, chosen by the Vault
+    #[account(
+        mut,
+        token::mint = from.mint
+    )]
+    pub to: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: PDA holding the pool's tokens
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct QuerySwap<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// CHECK: pool custody account of the token in; identifies the token only
+    pub pool_token_in: AccountInfo<'info>,
+
+    /// CHECK: pool custody account of the token out; identifies the token only
+    pub pool_token_out: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueryDueProtocolFees<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The vault's protocol fee settings
+    #[account(
+        seeds = [b"fees-collector", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub fees_collector: Account<'info, ProtocolFeesCollector>,
+
+    /// The pool's Vault registration (custody accounts)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// The pool's LP mint
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA holding the custody accounts; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPool<'info> {
+    pub pool: Account<'info, Pool>,
+
+    /// The pool's Vault registration (custody accounts, managed balances)
+    #[account(
+        seeds = [b"pool-registration", pool.vault.as_ref(), pool.pool_id.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub pool_registration: Account<'info, PoolRegistration>,
+
+    /// The pool's LP mint
+    #[account(address = pool.lp_mint)]
+    pub lp_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: PDA holding the custody accounts; seed ensures the correct authority
+    #[account(
+        seeds = [b"lp-mint-authority", pool.key().as_ref()],
+        bump
+    )]
+    pub lp_mint_authority: AccountInfo<'info>,
+}
+
+/* ------------------------------------------------------------------
+   Accounts: oracle
+------------------------------------------------------------------ */
+#[derive(Accounts)]
+pub struct EnableOracle<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, Pool>,
+
+    /// The Authorizer of the vault this pool is registered with
+    #[account(
+        seeds = [b"authorizer", pool.vault.as_ref()],
+        bump,
+        seeds::program = vault::ID
+    )]
+    pub authorizer: Account<'info, Authorizer>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        seeds = [b"pool-oracle", pool.key().as_ref()],
+        bump,
+        payer = payer,
+        space = 8 + PoolOracle::INIT_SPACE
+    )]
+    pub oracle: Box<Account<'info, PoolOracle>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOracle<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(mut, seeds = [b"pool-oracle", pool.key().as_ref()], bump)]
+    pub oracle: Box<Account<'info, PoolOracle>>,
+}
+
+#[derive(Accounts)]
+pub struct QueryTwap<'info> {
+    pub pool: Account<'info, Pool>,
+
+    #[account(seeds = [b"pool-oracle", pool.key().as_ref()], bump)]
+    pub oracle: Box<Account<'info, PoolOracle>>,
 }
 
 /* ------------------------------------------------------------------
@@ -396,10 +1913,263 @@ pub struct Pool {
     pub lp_mint: Pubkey,
     pub weights: Vec<u128>,
     pub swap_fee: u64,
+    /// The LP mint's supply after the last join or exit, which price
+    /// against the mint itself; swaps, which take no mint, read this copy
     pub total_bpt: u64,
+    /// PoolId assigned by the Vault at registration
+    pub pool_id: [u8; 32],
+    /// TWAP accumulators, fed by every swap, join and exit once enabled
+    pub oracle: OracleAccumulators,
+    /// Set while a join, exit or swap runs; see `enter`
+    pub entered: bool,
+    /// Layout version; see `POOL_VERSION` and `migrate_pool_state`
+    pub version: u8,
+    /// Zeroed space for fields later versions add without a realloc
+    pub reserved: [u8; Pool::RESERVED],
 }
 impl Pool {
-    pub const INIT_SPACE: usize = 252;
+    pub const RESERVED: usize = 64;
+    pub const INIT_SPACE: usize =
+        32 + 32 + (4 + MAX_POOL_TOKENS * 16) + 8 + 8 + 32 + OracleAccumulators::SPACE + 1 + 1 + Self::RESERVED;
+}
+
+/// Quantities a pool oracle averages; see `oracle_values`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OracleVariable {
+    /// Spot price of token 1 in token 0
+    PairPrice,
+    /// Price of one BPT in token 0
+    BptPrice,
+    Invariant,
+}
+
+impl OracleVariable {
+    pub const ALL: [OracleVariable; 3] = [Self::PairPrice, Self::BptPrice, Self::Invariant];
+}
+
+/// Running ∫ ln(variable) dt of each `OracleVariable`; the values set
+/// by an operation hold until the next one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OracleAccumulators {
+    pub enabled: bool,
+    /// Time of the last update; zero until the first
+    pub timestamp: i64,
+    /// ln of each variable at `timestamp` (18‑dec)
+    pub log_values: [i128; 3],
+    /// ∫ ln(variable) dt up to `timestamp` (18‑dec · seconds)
+    pub accumulated: [i128; 3],
+}
+
+impl OracleAccumulators {
+    pub const SPACE: usize = 1 + 8 + 3 * 16 + 3 * 16;
+
+    fn accumulator(&self, variable: OracleVariable) -> Accumulator {
+        let i = variable as usize;
+        Accumulator {
+            timestamp: self.timestamp,
+            log_value: I256::from(self.log_values[i]),
+            accumulated: I256::from(self.accumulated[i]),
+        }
+    }
+
+    /// Close the previous values' interval at `now` and record `values`.
+    pub fn record(&mut self, now: i64, values: [U256; 3]) -> Result<()> {
+        for variable in OracleVariable::ALL {
+            let i = variable as usize;
+            let next = if self.timestamp == 0 {
+                Accumulator::try_new(now, values[i])
+            } else {
+                self.accumulator(variable).try_update(now, values[i])
+            }
+            .map_err(ErrorCode::from)?;
+            self.log_values[i]  = next.log_value.to_i128().ok_or(ErrorCode::MathOverflow)?;
+            self.accumulated[i] = next.accumulated.to_i128().ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.timestamp = now;
+        Ok(())
+    }
+
+    /// Accumulator of `variable` at `t` ≥ `timestamp`.
+    pub fn accumulated_at(&self, variable: OracleVariable, t: i64) -> Result<i128> {
+        let acc = self.accumulator(variable).try_accumulated_at(t).map_err(ErrorCode::from)?;
+        Ok(acc.to_i128().ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+/// Snapshot of a pool's accumulators
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OracleSample {
+    pub timestamp: i64,
+    pub accumulated: [i128; 3],
+}
+
+impl OracleSample {
+    pub const SPACE: usize = 8 + 3 * 16;
+}
+
+/// Sample buffer of a pool oracle, PDA'd by ["pool-oracle", pool]
+#[account]
+pub struct PoolOracle {
+    pub pool: Pubkey,
+    /// Slot the next sample goes to
+    pub next: u32,
+    /// Samples written, at most `ORACLE_SAMPLES`
+    pub count: u32,
+    pub samples: [OracleSample; ORACLE_SAMPLES],
+}
+
+impl PoolOracle {
+    pub const INIT_SPACE: usize = 32 + 4 + 4 + ORACLE_SAMPLES * OracleSample::SPACE;
+
+    /// The `k`‑th oldest sample.
+    fn sample(&self, k: usize) -> &OracleSample {
+        let oldest = if (self.count as usize) < ORACLE_SAMPLES { 0 } else { self.next as usize };
+        &self.samples[(oldest + k) % ORACLE_SAMPLES]
+    }
+
+    pub fn latest(&self) -> Option<&OracleSample> {
+        (self.count > 0).then(|| self.sample(self.count as usize - 1))
+    }
+
+    pub fn push(&mut self, sample: OracleSample) {
+        self.samples[self.next as usize] = sample;
+        self.next  = (self.next + 1) % ORACLE_SAMPLES as u32;
+        self.count = (self.count + 1).min(ORACLE_SAMPLES as u32);
+    }
+
+    /// Accumulator of `variable` at `t` ≤ `now`: exact from the pool's
+    /// accumulators back to their last update, interpolated between the
+    /// samples (and that update) before it.
+    pub fn accumulated_at(
+        &self,
+        current: &OracleAccumulators,
+        variable: OracleVariable,
+        t: i64,
+        now: i64,
+    ) -> Result<i128> {
+        require!(t <= now, ErrorCode::InvalidTwapQuery);
+        if t >= current.timestamp {
+            return current.accumulated_at(variable, t);
+        }
+        // last sample at or before `t`
+        let (mut lo, mut hi) = (0, self.count as usize);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.sample(mid).timestamp <= t { lo = mid + 1 } else { hi = mid }
+        }
+        require!(lo > 0, ErrorCode::OracleQueryTooOld);
+        let i = variable as usize;
+        let before = self.sample(lo - 1);
+        let after = if lo < self.count as usize {
+            (self.sample(lo).timestamp, self.sample(lo).accumulated[i])
+        } else {
+            (current.timestamp, current.accumulated[i])
+        };
+        if after.0 <= before.timestamp {
+            return Ok(before.accumulated[i]);
+        }
+        let slope = (after.1 - before.accumulated[i]) / (after.0 - before.timestamp) as i128;
+        Ok(before.accumulated[i] + slope * (t - before.timestamp) as i128)
+    }
+}
+
+/// One `query_twap` window: the `seconds` ending `ago` seconds before now
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct TwapQuery {
+    pub variable: OracleVariable,
+    pub seconds: i64,
+    pub ago: i64,
+}
+
+/// A join's BPT out, the part of its fee minted to the protocol and,
+/// on the first join, the BPT locked for good
+struct JoinQuote {
+    bpt_out: u64,
+    protocol_bpt: u64,
+    locked_bpt: u64,
+}
+
+/// What `query_due_protocol_fees` finds the protocol would take
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct DueProtocolFees {
+    /// BPT minted to the protocol fee collector on the join
+    pub protocol_bpt: u64,
+}
+
+/// Tokens were swapped through the pool, directly or in a Vault batch swap
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub token_in: Pubkey,
+    pub token_out: Pubkey,
+    /// Taken from the user, protocol fee included
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Part of `amount_in` paid to the protocol fee collector
+    pub protocol_fee: u64,
+    /// Marginal price of `token_out` in `token_in` (18‑dec, fee included)
+    /// before and after the swap; zero where the balances cannot be priced
+    pub spot_price_before: u128,
+    pub spot_price_after: u128,
+    /// Swap fee charged on the amount the pool received, `protocol_fee`
+    /// included
+    pub swap_fee_amount: u64,
+    /// Balances of `token_in` and `token_out` the swap was priced on
+    /// (custody plus managed, 18‑dec)
+    pub balance_in: u128,
+    pub balance_out: u128,
+}
+
+/// Tokens were deposited for BPT; amounts in pool order
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct JoinEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub amounts_in: Vec<u64>,
+    pub bpt_out: u64,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+}
+
+/// BPT was burned for tokens; amounts in pool order
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExitEvent {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    pub user: Pubkey,
+    pub bpt_in: u64,
+    pub amounts_out: Vec<u64>,
+    /// BPT minted to the protocol fee collector
+    pub protocol_bpt: u64,
+    /// Tokens paid to the protocol fee collector
+    pub protocol_fees: Vec<u64>,
+}
+
+/// A `verify_pool` probe's findings
+#[event]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoolHealth {
+    pub pool: Pubkey,
+    pub pool_id: [u8; 32],
+    /// Custody plus managed balance of each token, in pool order
+    pub balances: Vec<u64>,
+    /// Invariant of `balances` (18‑dec); zero where it has none
+    pub invariant: u128,
+    /// The LP mint's supply
+    pub bpt_supply: u64,
+    /// The pool's stored copy of the supply
+    pub total_bpt: u64,
+    /// Sum of the weights (18‑dec); `u128::MAX` if it overflows
+    pub weight_sum: u128,
+    /// Supply and `total_bpt` agree, the weights sum to one, and the
+    /// pool has an invariant exactly when it has BPT
+    pub healthy: bool,
 }
 
 #[error_code]
@@ -408,4 +2178,262 @@ pub enum ErrorCode {
     LengthMismatch,
     #[msg("Math underflow or overflow")]
     MathUnderflow,
+    #[msg("Signer is not authorized for this action")]
+    Unauthorized,
+    #[msg("Vault is paused")]
+    VaultPaused,
+    #[msg("Pool is paused")]
+    PoolPaused,
+    #[msg("Protocol fee account does not belong to the fees collector")]
+    InvalidFeeAccount,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Division by zero")]
+    DivisionByZero,
+    #[msg("Swap amount in exceeds the maximum in ratio")]
+    MaxInRatio,
+    #[msg("Swap amount out exceeds the maximum out ratio")]
+    MaxOutRatio,
+    #[msg("Weights are out of range or do not sum to one")]
+    InvalidWeights,
+    #[msg("Math solver did not converge")]
+    MathConvergenceFailure,
+    #[msg("Math input out of bounds")]
+    MathInputOutOfBounds,
+    #[msg("Swap tokens must be two different pool tokens")]
+    InvalidSwapTokens,
+    #[msg("Config hash does not match the sorted tokens, weights and swap fee")]
+    InvalidConfigHash,
+    #[msg("Only two-token pools have an oracle")]
+    OracleUnsupported,
+    #[msg("Oracle has not recorded any value yet")]
+    OracleNotReady,
+    #[msg("Oracle was sampled too recently")]
+    OracleSampleTooSoon,
+    #[msg("TWAP window must be positive and in the past")]
+    InvalidTwapQuery,
+    #[msg("TWAP window starts before the oldest oracle sample")]
+    OracleQueryTooOld,
+    #[msg("Token account is not owned by the given token program")]
+    InvalidTokenAccount,
+    #[msg("Token program of a pool token was not passed")]
+    MissingTokenProgram,
+    #[msg("Token account is not the pool's registered custody account")]
+    UnknownCustodyAccount,
+    #[msg("Pool was re-entered while a join, exit or swap is in progress")]
+    Reentrancy,
+    #[msg("Swap fee is below the minimum")]
+    SwapFeeTooLow,
+    #[msg("Swap fee is above the maximum")]
+    SwapFeeTooHigh,
+    #[msg("Token account is not the user's account of the pool token")]
+    InvalidUserTokenAccount,
+    #[msg("Amount is zero")]
+    ZeroAmount,
+    #[msg("Amount is below the minimum trade size")]
+    DustAmount,
+    #[msg("Operation would pay out nothing")]
+    ZeroAmountOut,
+    #[msg("Account is not a weighted pool")]
+    InvalidPoolAccount,
+    #[msg("Pool state is already at the current version")]
+    PoolUpToDate,
+    #[msg("Pool custody account is frozen")]
+    TokenAccountFrozen,
+    #[msg("Pool custody account is closed or not held by the pool")]
+    InvalidVaultOwner,
+    #[msg("Post-trade spot price is above the limit")]
+    PriceLimitExceeded,
+    #[msg("Amount is outside the caller's limit")]
+    SlippageLimit,
+    #[msg("Payout exceeds the cash in custody")]
+    InsufficientCash,
+    #[msg("Recovery exit needs a paused pool or a token that cannot move")]
+    RecoveryNotNeeded,
+    #[msg("First join needs the mint authority's LP account to lock BPT in")]
+    InvalidLockedBptAccount,
+}
+
+/// Math failures abort with the matching error code instead of a panic.
+impl From<MathError> for ErrorCode {
+    fn from(e: MathError) -> Self {
+        match e {
+            MathError::Overflow => ErrorCode::MathOverflow,
+            MathError::Underflow => ErrorCode::MathUnderflow,
+            MathError::DivisionByZero => ErrorCode::DivisionByZero,
+            MathError::LengthMismatch => ErrorCode::LengthMismatch,
+            MathError::WeightsNotNormalized | MathError::InvalidWeight => ErrorCode::InvalidWeights,
+            MathError::MaxInRatio => ErrorCode::MaxInRatio,
+            MathError::MaxOutRatio => ErrorCode::MaxOutRatio,
+            MathError::ConvergenceFailure => ErrorCode::MathConvergenceFailure,
+            MathError::TokenIndexOutOfBounds
+            | MathError::BaseOutOfBounds
+            | MathError::ExponentOutOfBounds
+            | MathError::InvalidParams
+            | MathError::AssetBoundsExceeded
+            | MathError::InvalidPercentage
+            | MathError::DecimalsOutOfBounds
+            | MathError::InvalidTimeRange => ErrorCode::MathInputOutOfBounds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(weights: &[u128], swap_fee: u64) -> Pool {
+        Pool {
+            vault: Pubkey::default(),
+            lp_mint: Pubkey::default(),
+            weights: weights.to_vec(),
+            swap_fee,
+            total_bpt: 0,
+            pool_id: [0; 32],
+            oracle: OracleAccumulators::default(),
+            entered: false,
+            version: POOL_VERSION,
+            reserved: [0; Pool::RESERVED],
+        }
+    }
+
+    const E18: u128 = 1_000_000_000_000_000_000;
+
+    // Regression: the quote used to pass (balance_in, balance_out,
+    // weight_in, weight_out) to a function taking (balance_in, weight_in,
+    // balance_out, weight_out), so every swap was mispriced or failed.
+    #[test]
+    fn quotes_an_80_20_swap_at_the_weighted_price() {
+        let pool = pool(&[8 * E18 / 10, 2 * E18 / 10], 0);
+        let quote = quote_exact_in(&pool, 0, (0, 1), 10, 1_000, 2_500).unwrap();
+        // 2500 · (1 − (1000 / 1010)^4) = 97.54…
+        assert_eq!(quote.amount_out, 97);
+        assert_eq!(quote.protocol_fee, 0);
+
+        let expected = weighted_math::calc_out_given_in(
+            U256::from(1_000u64) * fixed::ONE,
+            U256::from(8 * E18 / 10),
+            U256::from(2_500u64) * fixed::ONE,
+            U256::from(2 * E18 / 10),
+            U256::from(10u64) * fixed::ONE,
+            U256::zero(),
+        );
+        assert_eq!(U256::from(quote.amount_out), expected / fixed::ONE);
+    }
+
+    #[test]
+    fn quotes_a_50_50_swap_like_constant_product() {
+        let pool = pool(&[E18 / 2, E18 / 2], 0);
+        // 1000 · 100 / 1100 = 90.9…
+        assert_eq!(quote_exact_in(&pool, 0, (0, 1), 100, 1_000, 1_000).unwrap().amount_out, 90);
+        // 1% fee, half of it to the protocol
+        let pool = Pool { swap_fee: (E18 / 100) as u64, ..pool };
+        let quote = quote_exact_in(&pool, (E18 / 2) as u64, (0, 1), 100, 1_000, 1_000).unwrap();
+        assert_eq!(quote.amount_out, 90);
+        assert_eq!(quote.protocol_fee, 0); // 0.5 of a 1‑token fee, rounded down to whole units
+    }
+
+    // Regression: the quote priced every swap at weights[0] in and
+    // weights[1] out, so a 1 → 0 swap of an 80/20 pool paid out 155,621
+    // instead of about 9,937.
+    #[test]
+    fn quotes_a_swap_at_the_weights_of_the_tokens_traded() {
+        let pool = pool(&[8 * E18 / 10, 2 * E18 / 10], 0);
+        let quote = quote_exact_in(&pool, 0, (1, 0), 10_000, 1_000_000, 4_000_000).unwrap();
+        // 4M · (1 − (1M / 1.01M)^(0.2 / 0.8)) = 9,937.9…
+        let expected = weighted_math::calc_out_given_in(
+            U256::from(1_000_000u64) * fixed::ONE,
+            U256::from(2 * E18 / 10),
+            U256::from(4_000_000u64) * fixed::ONE,
+            U256::from(8 * E18 / 10),
+            U256::from(10_000u64) * fixed::ONE,
+            U256::zero(),
+        );
+        assert_eq!(U256::from(quote.amount_out), expected / fixed::ONE);
+        assert!((9_900..=9_937).contains(&quote.amount_out));
+    }
+
+    #[test]
+    fn quotes_a_three_token_swap_at_the_weights_of_the_tokens_traded() {
+        let pool = pool(&[5 * E18 / 10, 3 * E18 / 10, 2 * E18 / 10], 0);
+        let fp = |x: u64| U256::from(x) * fixed::ONE;
+        for (index_in, index_out) in [(2, 1), (1, 2), (0, 2), (2, 0)] {
+            let quote = quote_exact_in(&pool, 0, (index_in, index_out), 1_000, 300_000, 200_000).unwrap();
+            let expected = weighted_math::calc_out_given_in(
+                fp(300_000),
+                U256::from(pool.weights[index_in]),
+                fp(200_000),
+                U256::from(pool.weights[index_out]),
+                fp(1_000),
+                U256::zero(),
+            );
+            assert_eq!(U256::from(quote.amount_out), expected / fixed::ONE);
+        }
+        // 0.2 in for 0.3 out pays less than at even weights, 0.3 in for 0.2 out more
+        let out = |indices| quote_exact_in(&pool, 0, indices, 1_000, 300_000, 200_000).unwrap().amount_out;
+        assert!(out((2, 1)) < 666 && out((1, 2)) > 666);
+        assert!(quote_exact_in(&pool, 0, (0, 3), 1_000, 300_000, 200_000).is_err());
+    }
+
+    #[test]
+    fn spot_price_rises_as_the_out_token_leaves() {
+        let fp = |x: u64| U256::from(x) * fixed::ONE;
+        let pool = pool(&[E18 / 2, E18 / 2], 0);
+        assert_eq!(spot_price(&pool, (0, 1), fp(1_000), fp(1_000)), Some(E18));
+        assert_eq!(spot_price(&pool, (0, 1), fp(1_100), fp(910)), Some(1_208_791_208_791_208_792));
+        // a drained out balance cannot be priced
+        assert_eq!(spot_price(&pool, (0, 1), fp(1_000), U256::zero()), None);
+    }
+
+    #[test]
+    fn oracle_averages_prices_over_time() {
+        let fp = |x: u64| U256::from(x) * fixed::ONE;
+        let mut current = OracleAccumulators { enabled: true, ..Default::default() };
+        let mut oracle = PoolOracle {
+            pool: Pubkey::default(),
+            next: 0,
+            count: 0,
+            samples: [OracleSample::default(); ORACLE_SAMPLES],
+        };
+        let snapshot = |current: &OracleAccumulators, now: i64| OracleSample {
+            timestamp: now,
+            accumulated: OracleVariable::ALL.map(|v| current.accumulated_at(v, now).unwrap()),
+        };
+
+        // price 1 for 600 s, then 4 for 600 s, then 16
+        current.record(1_000, [fp(1), fp(1), fp(1)]).unwrap();
+        oracle.push(snapshot(&current, 1_000));
+        current.record(1_600, [fp(4), fp(4), fp(4)]).unwrap();
+        oracle.push(snapshot(&current, 1_900));
+        current.record(2_200, [fp(16), fp(16), fp(16)]).unwrap();
+
+        let twap = |start: i64, end: i64| {
+            let a = oracle.accumulated_at(&current, OracleVariable::PairPrice, start, 3_000).unwrap();
+            let b = oracle.accumulated_at(&current, OracleVariable::PairPrice, end, 3_000).unwrap();
+            try_geometric_mean((start, I256::from(a)), (end, I256::from(b))).unwrap()
+        };
+        let close = |a: U256, b: U256| (if a > b { a - b } else { b - a }) < U256::from(1_000_000u64);
+        assert!(close(twap(1_000, 2_200), fp(2)));
+        // read from the pool past its last update, from samples before it
+        assert!(close(twap(2_200, 3_000), fp(16)));
+        // 1 for 600 s and 4 for 300 s: 4^(1/3)
+        assert!(close(twap(1_000, 1_900), U256::from(1_587_401_051_968_199_474u128)));
+
+        assert!(oracle.accumulated_at(&current, OracleVariable::PairPrice, 999, 3_000).is_err());
+        assert!(oracle.accumulated_at(&current, OracleVariable::PairPrice, 3_001, 3_000).is_err());
+    }
+
+    #[test]
+    fn oracle_values_of_a_50_50_pool() {
+        let values = oracle_values(
+            &[E18 / 2, E18 / 2],
+            [U256::from(1_000u64) * fixed::ONE, U256::from(4_000u64) * fixed::ONE],
+            100,
+        )
+        .unwrap();
+        // token 1 is worth a quarter of token 0; BPT = 2000 token 0 / 100; √(1000 · 4000)
+        assert_eq!(values[0], fixed::ONE / 4);
+        assert_eq!(values[1], U256::from(20u64) * fixed::ONE);
+        assert!(values[2].abs_diff(U256::from(2_000u64) * fixed::ONE) < U256::from(1_000_000u64));
+    }
 }
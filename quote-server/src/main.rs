@@ -0,0 +1,91 @@
+// Symmetric‑Solana ─ quote-server
+// ================================================================
+// Swap, join and exit quotes over REST/JSON for web frontends and
+// partners, so they integrate without running Rust themselves:
+//   • indexes the given Vaults' pools in process (indexer::index);
+//   • prices with the quoter, as the programs settle (routes);
+//   • listens on all interfaces by default and allows any origin;
+//     put it behind a TLS proxy when exposed.
+// ================================================================
+use std::error::Error;
+use std::net::TcpListener;
+use std::process::ExitCode;
+use std::sync::{Arc, RwLock};
+
+use anchor_lang::prelude::Pubkey;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use indexer::view::PoolView;
+use indexer::{api, index, ws_url};
+use solana_commitment_config::CommitmentConfig;
+
+mod routes;
+
+fn cli() -> Command {
+    Command::new("quote-server")
+        .about("REST/JSON quotes for Symmetric pools")
+        .arg(
+            Arg::new("url")
+                .long("url")
+                .short('u')
+                .default_value("http://localhost:8899")
+                .help("RPC URL"),
+        )
+        .arg(Arg::new("ws").long("ws").help("Websocket URL [default: the RPC URL on ws(s), port + 1]"))
+        .arg(
+            Arg::new("vault")
+                .long("vault")
+                .value_name("ADDRESS")
+                .value_parser(|s: &str| s.parse::<Pubkey>().map_err(|_| format!("invalid address: {s}")))
+                .action(ArgAction::Append)
+                .required(true)
+                .help("Vault whose pools are quoted; repeat per Vault"),
+        )
+        .arg(Arg::new("listen").long("listen").default_value("0.0.0.0:8080").help("Address to serve on"))
+        .arg(
+            Arg::new("commitment")
+                .long("commitment")
+                .value_parser(["processed", "confirmed", "finalized"])
+                .default_value("confirmed"),
+        )
+}
+
+fn run(matches: ArgMatches) -> Result<(), Box<dyn Error>> {
+    let commitment = match matches.get_one::<String>("commitment").map(String::as_str) {
+        Some("processed") => CommitmentConfig::processed(),
+        Some("finalized") => CommitmentConfig::finalized(),
+        _ => CommitmentConfig::confirmed(),
+    };
+    let url = matches.get_one::<String>("url").expect("defaulted");
+    let ws = matches.get_one::<String>("ws").cloned().unwrap_or_else(|| ws_url(url));
+    let vaults: Vec<Pubkey> = matches.get_many::<Pubkey>("vault").expect("required").copied().collect();
+    let view = Arc::new(RwLock::new(PoolView::new(vaults)));
+
+    let listener = TcpListener::bind(matches.get_one::<String>("listen").expect("defaulted"))?;
+    println!("serving on {}", listener.local_addr()?);
+    let served = view.clone();
+    std::thread::spawn(move || api::serve(listener, served, routes::route));
+    index(url, ws, commitment, view)
+}
+
+fn main() -> ExitCode {
+    match run(cli().get_matches()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requires_a_vault() {
+        cli().debug_assert();
+        assert!(cli().try_get_matches_from(["quote-server"]).is_err());
+        let m = cli().try_get_matches_from(["quote-server", "--vault", &Pubkey::new_unique().to_string()]).unwrap();
+        assert_eq!(m.get_one::<String>("listen").unwrap(), "0.0.0.0:8080");
+    }
+}
@@ -0,0 +1,100 @@
+// Symmetric‑Solana ─ Quote server routes
+// ================================================================
+// The public endpoints, over the indexer's pool view:
+//   • /health, /pools, /pools/<address>   as the indexer serves them;
+//   • /quote/swap?in=&out=&amount=[&pool=][&exact=out]
+//         the best pool's swap quote, or `pool`'s;
+//   • /quote/join?pool=&amounts=a,b,…     BPT out for exact amounts in
+//         of every token, in pool order;
+//   • /quote/exit?pool=&bpt=              tokens out for exact BPT in.
+// Raw token units throughout; 18‑dec values as decimal strings. A pool
+// whose accounts have not all arrived yet answers 503.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use indexer::api::{self, param, required, swap_quote};
+use indexer::view::PoolView;
+use quoter::PoolState;
+use serde_json::{json, Value};
+
+type RouteResult = Result<Value, (u16, String)>;
+
+/// Status and JSON body of a GET of `target`.
+pub fn route(view: &PoolView, target: &str) -> (u16, Value) {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let result = match path.trim_end_matches('/') {
+        "/quote/swap" => swap_quote(view, query),
+        "/quote/join" => join_quote(view, query),
+        "/quote/exit" => exit_quote(view, query),
+        "/health" | "/pools" => return api::route(view, target),
+        path if path.starts_with("/pools/") => return api::route(view, target),
+        _ => Err((404, "not found".to_string())),
+    };
+    match result {
+        Ok(body) => (200, body),
+        Err((status, error)) => (status, json!({ "error": error })),
+    }
+}
+
+/// The `pool` of the query and its priced state with its slot.
+fn priced_pool<'a>(view: &'a PoolView, query: &str) -> Result<(Pubkey, u64, &'a PoolState), (u16, String)> {
+    let address: Pubkey = required(query, "pool")?;
+    let pool = view.pool(&address).ok_or((404, "pool not indexed".to_string()))?;
+    match &pool.state {
+        Some(state) => Ok((address, pool.slot, state)),
+        None => Err((503, format!("pool not priced yet: {}", pool.error.as_deref().unwrap_or("accounts missing")))),
+    }
+}
+
+/// Comma‑separated raw amounts (`,` possibly percent‑encoded).
+fn amounts(query: &str, name: &str) -> Result<Vec<u64>, (u16, String)> {
+    let value = param(query, name).ok_or((400, format!("missing `{name}`")))?;
+    value.replace("%2C", ",").replace("%2c", ",").split(',').map(|a| a.parse().map_err(|_| (400, format!("invalid `{name}`")))).collect()
+}
+
+fn join_quote(view: &PoolView, query: &str) -> RouteResult {
+    let (address, slot, state) = priced_pool(view, query)?;
+    let amounts_in = amounts(query, "amounts")?;
+    let q = state.join_exact_tokens_in(&amounts_in).map_err(|err| (400, err.to_string()))?;
+    Ok(json!({
+        "pool": address.to_string(),
+        "slot": slot,
+        "amounts_in": amounts_in,
+        "bpt_out": q.bpt_out,
+        "protocol_bpt": q.protocol_bpt,
+    }))
+}
+
+fn exit_quote(view: &PoolView, query: &str) -> RouteResult {
+    let (address, slot, state) = priced_pool(view, query)?;
+    let bpt_in: u64 = required(query, "bpt")?;
+    let q = state.exit_exact_bpt_in(bpt_in).map_err(|err| (400, err.to_string()))?;
+    Ok(json!({
+        "pool": address.to_string(),
+        "slot": slot,
+        "bpt_in": bpt_in,
+        "amounts_out": q.amounts_out,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_quotes_and_indexer_paths() {
+        let view = PoolView::new([Pubkey::new_unique()]);
+        assert_eq!(route(&view, "/health"), (200, json!({ "slot": 0, "pools": 0 })));
+        assert_eq!(route(&view, "/pools").1, json!([]));
+        assert_eq!(route(&view, "/quote").0, 404);
+
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (status, body) = route(&view, &format!("/quote/swap?in={a}&out={b}&amount=10"));
+        assert_eq!((status, body["error"].as_str()), (404, Some("no indexed pool quotes this swap")));
+        assert_eq!(route(&view, "/quote/join?amounts=1,2").0, 400);
+        let (status, body) = route(&view, &format!("/quote/exit?pool={a}&bpt=5"));
+        assert_eq!((status, body["error"].as_str()), (404, Some("pool not indexed")));
+
+        assert_eq!(amounts("amounts=1%2C2,3", "amounts"), Ok(vec![1, 2, 3]));
+        assert_eq!(amounts("amounts=1,x", "amounts").unwrap_err().0, 400);
+    }
+}
@@ -0,0 +1,109 @@
+// Symmetric‑Solana ─ Quoter cache
+// ================================================================
+// Pool states kept by the slot they were read at, so quotes for the
+// same slot share one account fetch:
+//   • An entry is served for its slot or any earlier one asked for; a
+//     later slot misses and is reloaded.
+//   • Inserting never replaces a state with an older one.
+//   • `prune` drops states read before a slot.
+// ================================================================
+use std::collections::HashMap;
+
+use anchor_lang::prelude::Pubkey;
+
+use crate::PoolState;
+
+/// Pool states keyed by pool address, each with the slot it was read at.
+#[derive(Clone, Default)]
+pub struct SlotCache {
+    entries: HashMap<Pubkey, (u64, PoolState)>,
+}
+
+impl SlotCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// State of `pool` read at `slot` or later.
+    pub fn get(&self, pool: &Pubkey, slot: u64) -> Option<&PoolState> {
+        self.entries.get(pool).filter(|(read_at, _)| *read_at >= slot).map(|(_, state)| state)
+    }
+
+    /// Keep `state` of `pool` read at `slot` unless a newer one is held.
+    pub fn insert(&mut self, pool: Pubkey, slot: u64, state: PoolState) {
+        match self.entries.get(&pool) {
+            Some((read_at, _)) if *read_at > slot => {}
+            _ => {
+                self.entries.insert(pool, (slot, state));
+            }
+        }
+    }
+
+    /// State of `pool` at `slot` or later, loading it with `load` on a miss.
+    pub fn get_or_insert_with<E>(
+        &mut self,
+        pool: Pubkey,
+        slot: u64,
+        load: impl FnOnce() -> Result<PoolState, E>,
+    ) -> Result<&PoolState, E> {
+        if self.get(&pool, slot).is_none() {
+            self.insert(pool, slot, load()?);
+        }
+        Ok(&self.entries[&pool].1)
+    }
+
+    /// Drop every state read before `slot`.
+    pub fn prune(&mut self, slot: u64) {
+        self.entries.retain(|_, (read_at, _)| *read_at >= slot);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weighted::tests::{weighted, E18};
+
+    fn state(balance: u64) -> PoolState {
+        PoolState::Weighted(weighted(&[balance, balance], &[E18 / 2, E18 / 2], 0, 1_000))
+    }
+
+    fn cash(state: &PoolState) -> u64 {
+        match state {
+            PoolState::Weighted(s) => s.cash[0],
+            PoolState::Stable(s) => s.cash[0],
+        }
+    }
+
+    #[test]
+    fn serves_states_no_older_than_the_slot_asked_for() {
+        let pool = Pubkey::new_unique();
+        let mut cache = SlotCache::new();
+        cache.insert(pool, 10, state(1));
+        assert_eq!(cache.get(&pool, 9).map(cash), Some(1));
+        assert!(cache.get(&pool, 11).is_none());
+
+        // an older read never replaces a newer one
+        cache.insert(pool, 8, state(2));
+        assert_eq!(cache.get(&pool, 10).map(cash), Some(1));
+
+        let mut loads = 0;
+        let mut load = |b| {
+            loads += 1;
+            Ok::<_, ()>(state(b))
+        };
+        assert_eq!(cache.get_or_insert_with(pool, 10, || load(3)).map(cash), Ok(1));
+        assert_eq!(cache.get_or_insert_with(pool, 12, || load(4)).map(cash), Ok(4));
+        assert_eq!(loads, 1);
+
+        cache.prune(13);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,306 @@
+// Symmetric‑Solana ─ Quoter
+// ================================================================
+// Exact off‑chain quotes of what the pool programs settle:
+//   • Pool, registration, custody and fee‑collector accounts read with
+//     the programs' own Anchor types into a pool state (PoolState).
+//   • Weighted and stable swap, join and exit pricing with the same
+//     `math` calls and rounding as the instructions (weighted, stable).
+//   • Price impact of every swap against the pool's spot price.
+//   • A slot‑keyed cache of pool states (cache).
+// A quote holds for the state it was computed at; callers still pass
+// slippage limits to the instruction.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token_interface::TokenAccount;
+use math::{fixed, MathError, U256};
+use stable_pool::StablePool;
+use vault::{PoolRegistration, ProtocolFeesCollector};
+use weighted_pool::Pool as WeightedPool;
+
+pub mod cache;
+pub mod stable;
+pub mod weighted;
+
+pub use cache::SlotCache;
+pub use stable::StableState;
+pub use weighted::WeightedState;
+
+/// A swap priced at a pool's state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Part of the amount in paid to the protocol fee account
+    pub protocol_fee: u64,
+    /// Shortfall of the trade's price from the spot price (18‑dec)
+    pub price_impact: U256,
+}
+
+/// A join with exact amounts in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JoinQuote {
+    pub bpt_out: u64,
+    /// BPT minted to the protocol fee collector on top of `bpt_out`
+    pub protocol_bpt: u64,
+}
+
+/// An exit with exact BPT in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExitQuote {
+    /// Amount of each token paid out, in pool order
+    pub amounts_out: Vec<u64>,
+}
+
+/// Why a quote could not be computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuoteError {
+    Math(MathError),
+    /// A token the pool does not hold, or the same token in and out
+    UnknownToken,
+    /// Amounts or balances not one per pool token
+    LengthMismatch,
+    /// The trade rounds down to nothing
+    ZeroOut,
+    /// Zero, or more than the pool can take
+    InvalidAmount,
+    /// Not an account of the expected type, or not the pool's
+    InvalidAccount(Pubkey),
+    /// Not owned by a pool program the quoter knows
+    UnknownPool(Pubkey),
+    /// The pool program has no such trade
+    Unsupported,
+}
+
+impl From<MathError> for QuoteError {
+    fn from(err: MathError) -> Self {
+        QuoteError::Math(err)
+    }
+}
+
+impl core::fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            QuoteError::Math(err) => write!(f, "math: {err:?}"),
+            QuoteError::UnknownToken => f.write_str("token not in pool"),
+            QuoteError::LengthMismatch => f.write_str("one amount per pool token expected"),
+            QuoteError::ZeroOut => f.write_str("trade rounds to zero"),
+            QuoteError::InvalidAmount => f.write_str("invalid amount"),
+            QuoteError::InvalidAccount(a) => write!(f, "account {a} has unexpected data"),
+            QuoteError::UnknownPool(a) => write!(f, "account {a} is not a known pool"),
+            QuoteError::Unsupported => f.write_str("not supported by this pool"),
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
+
+/// 18‑dec value as a whole token amount, rounded down.
+pub(crate) fn to_amount(value_fp: U256) -> Result<u64, QuoteError> {
+    let amount = value_fp / fixed::ONE;
+    if amount > U256::from(u64::MAX) {
+        return Err(MathError::Overflow.into());
+    }
+    Ok(amount.as_u64())
+}
+
+/// Shortfall of `effective` from `spot` as a fraction of `spot`; zero
+/// when the trade does at least as well.
+pub(crate) fn price_impact(spot: U256, effective: U256) -> Result<U256, QuoteError> {
+    if spot.is_zero() || effective >= spot {
+        return Ok(U256::zero());
+    }
+    Ok(fixed::complement(fixed::try_div_down(effective, spot)?))
+}
+
+/// Raw data of an account with its owner.
+#[derive(Clone, Copy, Debug)]
+pub struct AccountData<'a> {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+    pub data: &'a [u8],
+}
+
+fn deserialize<T: AccountDeserialize>(account: &AccountData) -> Result<T, QuoteError> {
+    T::try_deserialize(&mut &account.data[..]).map_err(|_| QuoteError::InvalidAccount(account.address))
+}
+
+/// A pool's pricing state at one slot.
+#[derive(Clone)]
+pub enum PoolState {
+    Weighted(WeightedState),
+    Stable(StableState),
+}
+
+impl PoolState {
+    /// Read a pool's state from its account, its Vault registration, the
+    /// registered custody account of each token (in registration order)
+    /// and the Vault's protocol fees collector.
+    pub fn from_accounts(
+        pool: &AccountData,
+        registration: &AccountData,
+        custody: &[AccountData],
+        fees_collector: &AccountData,
+    ) -> Result<Self, QuoteError> {
+        let registration: PoolRegistration = deserialize(registration)?;
+        let collector: ProtocolFeesCollector = deserialize(fees_collector)?;
+        if registration.pool != pool.address || collector.vault != registration.vault {
+            return Err(QuoteError::InvalidAccount(pool.address));
+        }
+        if custody.len() != registration.tokens.len() {
+            return Err(QuoteError::LengthMismatch);
+        }
+        let mut cash = Vec::with_capacity(custody.len());
+        for (i, account) in custody.iter().enumerate() {
+            let token: TokenAccount = deserialize(account)?;
+            let registered = registration.token_accounts[i];
+            if token.mint != registration.tokens[i] || (registered != Pubkey::default() && registered != account.address) {
+                return Err(QuoteError::InvalidAccount(account.address));
+            }
+            cash.push(token.amount);
+        }
+        let managed = registration.tokens.iter().map(|m| registration.managed_balance(m)).collect();
+        let protocol_pct = collector.swap_fee_percentage;
+
+        if pool.owner == weighted_pool::ID {
+            let state: WeightedPool = deserialize(pool)?;
+            if state.pool_id != registration.pool_id || state.weights.len() != cash.len() {
+                return Err(QuoteError::InvalidAccount(pool.address));
+            }
+            Ok(PoolState::Weighted(WeightedState { pool: state, tokens: registration.tokens, cash, managed, protocol_pct }))
+        } else if pool.owner == stable_pool::ID {
+            let state: StablePool = deserialize(pool)?;
+            if state.pool_id != registration.pool_id || state.tokens != registration.tokens {
+                return Err(QuoteError::InvalidAccount(pool.address));
+            }
+            Ok(PoolState::Stable(StableState { pool: state, cash, managed, protocol_pct }))
+        } else {
+            Err(QuoteError::UnknownPool(pool.address))
+        }
+    }
+
+    /// Token mints, in pool order.
+    pub fn tokens(&self) -> &[Pubkey] {
+        match self {
+            PoolState::Weighted(s) => &s.tokens,
+            PoolState::Stable(s) => &s.pool.tokens,
+        }
+    }
+
+    fn index_of(&self, mint: &Pubkey) -> Result<usize, QuoteError> {
+        self.tokens().iter().position(|t| t == mint).ok_or(QuoteError::UnknownToken)
+    }
+
+    /// Exact‑in swap of `mint_in` for `mint_out`.
+    pub fn swap_exact_in(&self, mint_in: &Pubkey, mint_out: &Pubkey, amount_in: u64) -> Result<SwapQuote, QuoteError> {
+        let (i, o) = (self.index_of(mint_in)?, self.index_of(mint_out)?);
+        match self {
+            PoolState::Weighted(s) => s.swap_exact_in(i, o, amount_in),
+            PoolState::Stable(s) => s.swap_exact_in(i, o, amount_in),
+        }
+    }
+
+    /// Swap for exactly `amount_out` of `mint_out`; stable pools only.
+    pub fn swap_exact_out(&self, mint_in: &Pubkey, mint_out: &Pubkey, amount_out: u64) -> Result<SwapQuote, QuoteError> {
+        let (i, o) = (self.index_of(mint_in)?, self.index_of(mint_out)?);
+        match self {
+            PoolState::Weighted(_) => Err(QuoteError::Unsupported),
+            PoolState::Stable(s) => s.swap_exact_out(i, o, amount_out),
+        }
+    }
+
+    /// Join with exactly `amounts_in`, one per token (a composable
+    /// pool's own BPT excluded).
+    pub fn join_exact_tokens_in(&self, amounts_in: &[u64]) -> Result<JoinQuote, QuoteError> {
+        match self {
+            PoolState::Weighted(s) => s.join_exact_tokens_in(amounts_in),
+            PoolState::Stable(s) => s.join_exact_tokens_in(amounts_in),
+        }
+    }
+
+    /// Exit burning exactly `bpt_in`.
+    pub fn exit_exact_bpt_in(&self, bpt_in: u64) -> Result<ExitQuote, QuoteError> {
+        match self {
+            PoolState::Weighted(s) => s.exit_exact_bpt_in(bpt_in),
+            PoolState::Stable(s) => s.exit_exact_bpt_in(bpt_in),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use anchor_spl::token::spl_token;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use common::Specialization;
+
+    fn token_account(mint: Pubkey, amount: u64) -> Vec<u8> {
+        let account = spl_token::state::Account {
+            mint,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        account.pack_into_slice(&mut data);
+        data
+    }
+
+    fn serialize(account: &impl AccountSerialize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn reads_a_weighted_pool_from_its_accounts() {
+        let weighted = weighted::tests::weighted(&[0, 0], &[weighted::tests::E18 / 2; 2], 0, 2_000_000);
+        let (pool_key, vault_state) = (Pubkey::new_unique(), weighted.pool.vault);
+        let custody_keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let registration = PoolRegistration {
+            vault: vault_state,
+            pool_id: weighted.pool.pool_id,
+            pool_program: weighted_pool::ID,
+            pool: pool_key,
+            specialization: Specialization::TwoToken,
+            tokens: weighted.tokens.clone(),
+            token_accounts: custody_keys.to_vec(),
+            asset_managers: vec![Pubkey::default(); 2],
+            managed: vec![0, 500_000],
+            paused: false,
+            token_programs: vec![anchor_spl::token::ID; 2],
+            freeze_authorities: vec![Pubkey::default(); 2],
+            lp_mint: Pubkey::new_unique(),
+        };
+        let collector = ProtocolFeesCollector { vault: vault_state, swap_fee_percentage: 7, flash_loan_fee_percentage: 0 };
+
+        let (pool_data, registration_data, collector_data) =
+            (serialize(&weighted.pool), serialize(&registration), serialize(&collector));
+        let custody_data = [token_account(weighted.tokens[0], 1_000_000), token_account(weighted.tokens[1], 3_500_000)];
+        let account = |address, owner, data| AccountData { address, owner, data };
+        let custody: Vec<_> = (0..2).map(|i| account(custody_keys[i], spl_token::ID, &custody_data[i][..])).collect();
+        let pool = account(pool_key, weighted_pool::ID, &pool_data);
+
+        let state = PoolState::from_accounts(
+            &pool,
+            &account(Pubkey::new_unique(), vault::ID, &registration_data),
+            &custody,
+            &account(Pubkey::new_unique(), vault::ID, &collector_data),
+        )
+        .unwrap();
+        let PoolState::Weighted(read) = &state else { panic!("not a weighted pool") };
+        assert_eq!((read.cash.clone(), read.managed.clone(), read.protocol_pct), (vec![1_000_000, 3_500_000], vec![0, 500_000], 7));
+        assert!(state.swap_exact_in(&weighted.tokens[0], &weighted.tokens[1], 1_000).unwrap().amount_out > 0);
+        assert!(matches!(state.swap_exact_out(&weighted.tokens[0], &weighted.tokens[1], 1), Err(QuoteError::Unsupported)));
+
+        // a pool account owned by another program is not priced
+        let foreign = account(pool_key, Pubkey::new_unique(), &pool_data);
+        let registration = account(Pubkey::new_unique(), vault::ID, &registration_data);
+        let collector = account(Pubkey::new_unique(), vault::ID, &collector_data);
+        assert!(matches!(
+            PoolState::from_accounts(&foreign, &registration, &custody, &collector),
+            Err(QuoteError::UnknownPool(a)) if a == pool_key
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+// Symmetric‑Solana ─ Stable pool quotes
+// ================================================================
+// The stable pool program's pricing, step for step: raw amounts are
+// scaled to 18‑dec at each token's cached rate (rounded down), amounts
+// paid out are scaled back rounded down and amounts paid in rounded up.
+//   • Swaps price against cash plus managed balances of every token, a
+//     composable pool's own BPT included, with the pre‑minted supply.
+//   • Joins and exits price the other tokens against the BPT in
+//     circulation (supply less the pool's own holding).
+// The cached rates are used as they are; a pool with rate providers
+// needs `update_token_rates` in the transaction that trades.
+// ================================================================
+use math::{fees, fixed, stable_math, U256};
+use stable_pool::StablePool;
+
+use crate::{price_impact, ExitQuote, JoinQuote, QuoteError, SwapQuote};
+
+/// Fraction of the balance in (1 / SPOT_DIVISOR) a spot price is read at
+const SPOT_DIVISOR: u64 = 1_000_000;
+
+/// A stable pool and its balances at one slot.
+#[derive(Clone)]
+pub struct StableState {
+    pub pool: StablePool,
+    /// Custody balance of each of `pool.tokens`
+    pub cash: Vec<u64>,
+    /// Balance of each token held by its asset manager
+    pub managed: Vec<u64>,
+    /// The Vault's protocol share of swap fees (18‑dec)
+    pub protocol_pct: u64,
+}
+
+impl StableState {
+    fn upscale(&self, i: usize, amount: u64) -> Result<U256, QuoteError> {
+        let value = fixed::try_scale_up(amount, self.pool.decimals[i])?;
+        Ok(fixed::try_mul_down(value, U256::from(self.pool.rates[i]))?)
+    }
+
+    fn downscale_down(&self, i: usize, value_fp: U256) -> Result<u64, QuoteError> {
+        let value = fixed::try_div_down(value_fp, U256::from(self.pool.rates[i]))?;
+        Ok(fixed::try_scale_down_rounding_down(value, self.pool.decimals[i])?)
+    }
+
+    fn downscale_up(&self, i: usize, value_fp: U256) -> Result<u64, QuoteError> {
+        let value = fixed::try_div_up(value_fp, U256::from(self.pool.rates[i]))?;
+        Ok(fixed::try_scale_down_rounding_up(value, self.pool.decimals[i])?)
+    }
+
+    /// 18‑dec balances of the first `n` tokens, managed amounts included.
+    fn balances(&self, n: usize) -> Result<Vec<U256>, QuoteError> {
+        if self.cash.len() != self.pool.tokens.len() || self.managed.len() != self.cash.len() {
+            return Err(QuoteError::LengthMismatch);
+        }
+        (0..n)
+            .map(|i| {
+                let amount = self.cash[i].checked_add(self.managed[i]).ok_or(math::MathError::Overflow)?;
+                self.upscale(i, amount)
+            })
+            .collect()
+    }
+
+    /// BPT in circulation.
+    fn supply(&self) -> Result<u64, QuoteError> {
+        match self.pool.bpt_index {
+            Some(b) => self.pool.total_bpt.checked_sub(self.cash[b as usize]).ok_or(QuoteError::InvalidAmount),
+            None => Ok(self.pool.total_bpt),
+        }
+    }
+
+    fn is_bpt_swap(&self, index_in: usize, index_out: usize) -> bool {
+        self.pool.bpt_index.is_some_and(|b| usize::from(b) == index_in || usize::from(b) == index_out)
+    }
+
+    fn check_pair(&self, index_in: usize, index_out: usize) -> Result<(), QuoteError> {
+        let n = self.pool.tokens.len();
+        if index_in == index_out || index_in >= n || index_out >= n {
+            return Err(QuoteError::UnknownToken);
+        }
+        Ok(())
+    }
+
+    fn calc_out_given_in(&self, balances: &[U256], index_in: usize, index_out: usize, amount_in_fp: U256, fee: U256) -> Result<U256, QuoteError> {
+        let amp = U256::from(self.pool.amp);
+        Ok(match self.pool.bpt_index {
+            Some(bpt_index) => stable_math::try_calc_composable_out_given_in(
+                amp,
+                balances,
+                bpt_index as usize,
+                index_in,
+                index_out,
+                amount_in_fp,
+                fixed::try_scale_up(self.pool.total_bpt, self.pool.lp_decimals)?,
+                fee,
+            ),
+            None => stable_math::try_calculate_invariant(amp, balances).and_then(|invariant| {
+                stable_math::try_calc_out_given_in(amp, balances, index_in, index_out, amount_in_fp, invariant, fee)
+            }),
+        }?)
+    }
+
+    fn calc_in_given_out(&self, balances: &[U256], index_in: usize, index_out: usize, amount_out_fp: U256, fee: U256) -> Result<U256, QuoteError> {
+        let amp = U256::from(self.pool.amp);
+        Ok(match self.pool.bpt_index {
+            Some(bpt_index) => stable_math::try_calc_composable_in_given_out(
+                amp,
+                balances,
+                bpt_index as usize,
+                index_in,
+                index_out,
+                amount_out_fp,
+                fixed::try_scale_up(self.pool.total_bpt, self.pool.lp_decimals)?,
+                fee,
+            ),
+            None => stable_math::try_calculate_invariant(amp, balances).and_then(|invariant| {
+                stable_math::try_calc_in_given_out(amp, balances, index_in, index_out, amount_out_fp, invariant, fee)
+            }),
+        }?)
+    }
+
+    /// Protocol share of a swap's fee, in token `index_in`.
+    fn protocol_fee(&self, balances: &[U256], index_in: usize, index_out: usize, in_fp: U256, out_fp: U256) -> Result<u64, QuoteError> {
+        if self.protocol_pct == 0 {
+            return Ok(0);
+        }
+        let fee_amount_fp = if self.is_bpt_swap(index_in, index_out) {
+            // joins and exits charge only their unbalanced part
+            let without_fee = self.calc_in_given_out(balances, index_in, index_out, out_fp, U256::zero())?;
+            in_fp.saturating_sub(without_fee)
+        } else {
+            fees::try_fee_amount(in_fp, U256::from(self.pool.swap_fee))?
+        };
+        self.downscale_down(index_in, fees::try_protocol_share(fee_amount_fp, U256::from(self.protocol_pct))?)
+    }
+
+    /// Out per in, fee included, of a trade of a millionth of the balance in.
+    fn spot_price(&self, balances: &[U256], index_in: usize, index_out: usize) -> Result<U256, QuoteError> {
+        let probe = (balances[index_in] / U256::from(SPOT_DIVISOR)).max(U256::one());
+        let out = self.calc_out_given_in(balances, index_in, index_out, probe, U256::from(self.pool.swap_fee))?;
+        Ok(fixed::try_div_down(out, probe)?)
+    }
+
+    /// Exact‑in swap of token `index_in` for token `index_out`.
+    pub fn swap_exact_in(&self, index_in: usize, index_out: usize, amount_in: u64) -> Result<SwapQuote, QuoteError> {
+        self.check_pair(index_in, index_out)?;
+        let balances = self.balances(self.pool.tokens.len())?;
+        let fee = U256::from(self.pool.swap_fee);
+        let amount_in_fp = self.upscale(index_in, amount_in)?;
+        let out_fp = self.calc_out_given_in(&balances, index_in, index_out, amount_in_fp, fee)?;
+        let protocol_fee = self.protocol_fee(&balances, index_in, index_out, amount_in_fp, out_fp)?;
+        let amount_out = self.downscale_down(index_out, out_fp)?;
+        let effective = fixed::try_div_down(self.upscale(index_out, amount_out)?, amount_in_fp)?;
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            protocol_fee,
+            price_impact: price_impact(self.spot_price(&balances, index_in, index_out)?, effective)?,
+        })
+    }
+
+    /// Swap for exactly `amount_out` of token `index_out`; the amount in
+    /// is rounded up.
+    pub fn swap_exact_out(&self, index_in: usize, index_out: usize, amount_out: u64) -> Result<SwapQuote, QuoteError> {
+        self.check_pair(index_in, index_out)?;
+        let balances = self.balances(self.pool.tokens.len())?;
+        let fee = U256::from(self.pool.swap_fee);
+        let amount_out_fp = self.upscale(index_out, amount_out)?;
+        let in_fp = self.calc_in_given_out(&balances, index_in, index_out, amount_out_fp, fee)?;
+        let protocol_fee = self.protocol_fee(&balances, index_in, index_out, in_fp, amount_out_fp)?;
+        let amount_in = self.downscale_up(index_in, in_fp)?;
+        let effective = fixed::try_div_down(amount_out_fp, self.upscale(index_in, amount_in)?)?;
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            protocol_fee,
+            price_impact: price_impact(self.spot_price(&balances, index_in, index_out)?, effective)?,
+        })
+    }
+
+    /// Join with exactly `amounts_in` of every token but the pool's BPT.
+    pub fn join_exact_tokens_in(&self, amounts_in: &[u64]) -> Result<JoinQuote, QuoteError> {
+        let n = self.pool.tokens.len() - usize::from(self.pool.bpt_index.is_some());
+        if amounts_in.len() != n {
+            return Err(QuoteError::LengthMismatch);
+        }
+        let supply = self.supply()?;
+        let balances = self.balances(n)?;
+        let amounts_fp = (0..n).map(|i| self.upscale(i, amounts_in[i])).collect::<Result<Vec<_>, _>>()?;
+        let amp = U256::from(self.pool.amp);
+        let lp_decimals = self.pool.lp_decimals;
+        let (bpt_out, protocol_bpt) = if supply == 0 {
+            if amounts_in.contains(&0) {
+                return Err(QuoteError::InvalidAmount);
+            }
+            let invariant = stable_math::try_calculate_invariant(amp, &amounts_fp)?;
+            (fixed::try_scale_down_rounding_down(invariant, lp_decimals)?, 0)
+        } else {
+            let invariant = stable_math::try_calculate_invariant(amp, &balances)?;
+            let total_bpt_fp = fixed::try_scale_up(supply, lp_decimals)?;
+            let bpt_out = |fee| {
+                stable_math::try_calc_bpt_out_given_exact_tokens_in(amp, &balances, &amounts_fp, total_bpt_fp, invariant, fee)
+            };
+            let bpt_out_fp = bpt_out(U256::from(self.pool.swap_fee))?;
+            let protocol_bpt = if self.protocol_pct > 0 {
+                let fee_bpt_fp = bpt_out(U256::zero())?.saturating_sub(bpt_out_fp);
+                fixed::try_scale_down_rounding_down(
+                    fees::try_protocol_share(fee_bpt_fp, U256::from(self.protocol_pct))?,
+                    lp_decimals,
+                )?
+            } else {
+                0
+            };
+            (fixed::try_scale_down_rounding_down(bpt_out_fp, lp_decimals)?, protocol_bpt)
+        };
+        if bpt_out == 0 {
+            return Err(QuoteError::ZeroOut);
+        }
+        Ok(JoinQuote { bpt_out, protocol_bpt })
+    }
+
+    /// Exit burning exactly `bpt_in` for a proportional share; no fee.
+    pub fn exit_exact_bpt_in(&self, bpt_in: u64) -> Result<ExitQuote, QuoteError> {
+        let n = self.pool.tokens.len() - usize::from(self.pool.bpt_index.is_some());
+        let supply = self.supply()?;
+        if bpt_in == 0 || bpt_in > supply {
+            return Err(QuoteError::InvalidAmount);
+        }
+        let balances = self.balances(n)?;
+        let ratio = fixed::try_div_down(
+            fixed::try_scale_up(bpt_in, self.pool.lp_decimals)?,
+            fixed::try_scale_up(supply, self.pool.lp_decimals)?,
+        )?;
+        let amounts_out = balances
+            .iter()
+            .enumerate()
+            .map(|(i, b)| self.downscale_down(i, fixed::try_mul_down(*b, ratio)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ExitQuote { amounts_out })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weighted::tests::E18;
+    use anchor_lang::prelude::Pubkey;
+
+    fn stable(balances: &[u64], decimals: &[u8], total_bpt: u64) -> StableState {
+        let n = balances.len();
+        StableState {
+            pool: StablePool {
+                vault: Pubkey::new_unique(),
+                lp_mint: Pubkey::new_unique(),
+                tokens: (0..n).map(|_| Pubkey::new_unique()).collect(),
+                decimals: decimals.to_vec(),
+                lp_decimals: 6,
+                amp: 200_000,
+                swap_fee: (E18 / 1_000) as u64,
+                total_bpt,
+                pool_id: [2; 32],
+                bpt_index: None,
+                rate_providers: vec![Pubkey::default(); n],
+                rates: vec![E18; n],
+                rates_updated_slot: 0,
+            },
+            cash: balances.to_vec(),
+            managed: vec![0; n],
+            protocol_pct: 0,
+        }
+    }
+
+    #[test]
+    fn scales_decimals_and_rounds_exact_out_up() {
+        // 1M of a 6‑dec and of a 9‑dec stablecoin
+        let state = stable(&[1_000_000_000_000, 1_000_000_000_000_000], &[6, 9], 2_000_000_000_000);
+        let quote = state.swap_exact_in(0, 1, 1_000_000).unwrap();
+        // 1 unit in, a little under 1 unit (in 9‑dec) out after the 0.1% fee
+        assert!(quote.amount_out < 1_000_000_000 && quote.amount_out > 998_000_000);
+        assert!(quote.price_impact < U256::from(E18 / 1_000_000));
+
+        let back = state.swap_exact_out(0, 1, quote.amount_out).unwrap();
+        assert!(back.amount_in >= 1_000_000 && back.amount_in <= 1_000_001);
+
+        let large = state.swap_exact_in(0, 1, 500_000_000_000).unwrap();
+        assert!(large.price_impact > quote.price_impact);
+    }
+
+    #[test]
+    fn exits_are_proportional_to_circulating_bpt() {
+        let mut state = stable(&[1_000_000, 3_000_000], &[6, 6], 4_000_000);
+        let exit = state.exit_exact_bpt_in(400_000).unwrap();
+        assert_eq!(exit.amounts_out, vec![100_000, 300_000]);
+
+        // composable: the pool's own BPT is last and not in circulation
+        state.pool.tokens.push(state.pool.lp_mint);
+        state.pool.decimals.push(6);
+        state.pool.rates.push(E18);
+        state.pool.bpt_index = Some(2);
+        state.cash.push(1_000_000);
+        state.managed.push(0);
+        state.pool.total_bpt = 5_000_000;
+        assert_eq!(state.exit_exact_bpt_in(400_000).unwrap().amounts_out, vec![100_000, 300_000]);
+        assert!(matches!(state.exit_exact_bpt_in(4_000_001), Err(QuoteError::InvalidAmount)));
+    }
+}
@@ -0,0 +1,274 @@
+// Symmetric‑Solana ─ Weighted pool quotes
+// ================================================================
+// The weighted pool program's pricing, step for step: the same
+// `math` calls on the same 18‑dec values, rounded the same way, so a
+// quote is what the instruction settles at the same state.
+//   • Swaps, joins and exits price against cash plus managed balances,
+//     as the program reads them.
+//   • Exits pay a proportional share of every balance, with no fee, and
+//     no more of a token than its cash.
+//   • The first join of an empty pool mints the invariant of what it
+//     pays in plus what custody already holds, times the token count,
+//     less the MINIMUM_BPT it locks.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use math::weighted_math::{self, SwapParams};
+use math::{fees, fixed, MathError, U256};
+use weighted_pool::{Pool, MINIMUM_BPT, MIN_TRADE_AMOUNT};
+
+use crate::{price_impact, to_amount, ExitQuote, JoinQuote, QuoteError, SwapQuote};
+
+/// A weighted pool and its balances at one slot.
+#[derive(Clone)]
+pub struct WeightedState {
+    pub pool: Pool,
+    /// Token mints, in pool (registration) order
+    pub tokens: Vec<Pubkey>,
+    /// Custody balance of each token, in pool order
+    pub cash: Vec<u64>,
+    /// Balance of each token held by its asset manager
+    pub managed: Vec<u64>,
+    /// The Vault's protocol share of swap fees (18‑dec)
+    pub protocol_pct: u64,
+}
+
+impl WeightedState {
+    fn balance(&self, i: usize) -> Result<u64, QuoteError> {
+        self.cash[i].checked_add(self.managed[i]).ok_or(QuoteError::Math(math::MathError::Overflow))
+    }
+
+    fn balances_fp(&self) -> Result<Vec<U256>, QuoteError> {
+        (0..self.cash.len()).map(|i| Ok(U256::from(self.balance(i)?) * fixed::ONE)).collect()
+    }
+
+    fn check_index(&self, i: usize) -> Result<(), QuoteError> {
+        if i < self.cash.len() && i < self.pool.weights.len() { Ok(()) } else { Err(QuoteError::UnknownToken) }
+    }
+
+    fn swap_params(&self, index_in: usize, index_out: usize, amount_in: u64) -> Result<SwapParams, QuoteError> {
+        self.check_index(index_in)?;
+        self.check_index(index_out)?;
+        if index_in == index_out || self.pool.weights.len() < 2 {
+            return Err(QuoteError::UnknownToken);
+        }
+        Ok(SwapParams {
+            balance_in:  U256::from(self.balance(index_in)?) * fixed::ONE,
+            weight_in:   U256::from(self.pool.weights[index_in]),
+            balance_out: U256::from(self.balance(index_out)?) * fixed::ONE,
+            weight_out:  U256::from(self.pool.weights[index_out]),
+            amount:      U256::from(amount_in) * fixed::ONE,
+            fee:         U256::from(self.pool.swap_fee),
+        })
+    }
+
+    /// Exact‑in swap of token `index_in` for token `index_out`.
+    pub fn swap_exact_in(&self, index_in: usize, index_out: usize, amount_in: u64) -> Result<SwapQuote, QuoteError> {
+        let params = self.swap_params(index_in, index_out, amount_in)?;
+        if amount_in < MIN_TRADE_AMOUNT {
+            return Err(QuoteError::InvalidAmount);
+        }
+        let out_fp = params.try_out_given_in()?;
+        let protocol_fee = if self.protocol_pct > 0 {
+            let fee_amount_fp = fees::try_fee_amount(params.amount, params.fee)?;
+            to_amount(fees::try_protocol_share(fee_amount_fp, U256::from(self.protocol_pct))?)?
+        } else {
+            0
+        };
+        let amount_out = to_amount(out_fp)?;
+        if amount_out == 0 {
+            return Err(QuoteError::ZeroOut);
+        }
+        if amount_out > self.cash[index_out] {
+            return Err(QuoteError::InvalidAmount);
+        }
+
+        // spot price net of the fee: (b_out / w_out) / (b_in / w_in) · (1 − fee)
+        let spot = fixed::try_mul_down(
+            fixed::try_div_down(
+                fixed::try_div_down(params.balance_out, params.weight_out)?,
+                fixed::try_div_down(params.balance_in, params.weight_in)?,
+            )?,
+            fixed::complement(params.fee),
+        )?;
+        let effective = fixed::try_div_down(U256::from(amount_out) * fixed::ONE, params.amount)?;
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            protocol_fee,
+            price_impact: price_impact(spot, effective)?,
+        })
+    }
+
+    /// Join with exactly `amounts_in` of every token.
+    pub fn join_exact_tokens_in(&self, amounts_in: &[u64]) -> Result<JoinQuote, QuoteError> {
+        let n = self.pool.weights.len();
+        if amounts_in.len() != n || self.cash.len() != n {
+            return Err(QuoteError::LengthMismatch);
+        }
+        if amounts_in.iter().all(|a| *a == 0) || amounts_in.iter().any(|a| *a != 0 && *a < MIN_TRADE_AMOUNT) {
+            return Err(QuoteError::InvalidAmount);
+        }
+        let balances_fp = self.balances_fp()?;
+        let weights_fp: Vec<U256> = self.pool.weights.iter().map(|w| U256::from(*w)).collect();
+        let amounts_fp: Vec<U256> = amounts_in.iter().map(|a| U256::from(*a) * fixed::ONE).collect();
+        let total_bpt_fp = U256::from(self.pool.total_bpt) * fixed::ONE;
+        let initial = self.pool.total_bpt == 0;
+        let bpt_out_fp = if initial {
+            if amounts_in.contains(&0) {
+                return Err(QuoteError::InvalidAmount);
+            }
+            let seeded = balances_fp
+                .iter()
+                .zip(&amounts_fp)
+                .map(|(b, a)| b.checked_add(*a).ok_or(MathError::Overflow))
+                .collect::<Result<Vec<U256>, _>>()?;
+            weighted_math::try_calculate_invariant(&seeded, &weights_fp)?
+                .checked_mul(U256::from(n))
+                .ok_or(MathError::Overflow)?
+                .saturating_sub(U256::from(MINIMUM_BPT) * fixed::ONE)
+        } else {
+            weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                &balances_fp,
+                &weights_fp,
+                &amounts_fp,
+                total_bpt_fp,
+                U256::from(self.pool.swap_fee),
+            )?
+        };
+        if bpt_out_fp.is_zero() {
+            return Err(QuoteError::ZeroOut);
+        }
+        let protocol_bpt = if self.protocol_pct > 0 && !initial {
+            let bpt_no_fee_fp = weighted_math::try_calc_bpt_out_given_exact_tokens_in(
+                &balances_fp,
+                &weights_fp,
+                &amounts_fp,
+                total_bpt_fp,
+                U256::zero(),
+            )?;
+            let fee_bpt_fp = bpt_no_fee_fp.saturating_sub(bpt_out_fp);
+            to_amount(fees::try_protocol_share(fee_bpt_fp, U256::from(self.protocol_pct))?)?
+        } else {
+            0
+        };
+        Ok(JoinQuote { bpt_out: to_amount(bpt_out_fp)?, protocol_bpt })
+    }
+
+    /// Exit burning exactly `bpt_in` for a proportional share; no fee.
+    pub fn exit_exact_bpt_in(&self, bpt_in: u64) -> Result<ExitQuote, QuoteError> {
+        if bpt_in == 0 || bpt_in > self.pool.total_bpt {
+            return Err(QuoteError::InvalidAmount);
+        }
+        let balances_fp = self.balances_fp()?;
+        let amounts_out = weighted_math::try_calc_tokens_out_given_exact_bpt_in(
+            &balances_fp,
+            U256::from(bpt_in) * fixed::ONE,
+            U256::from(self.pool.total_bpt) * fixed::ONE,
+            U256::zero(),
+        )?
+        .iter()
+        .map(|o| to_amount(*o))
+        .collect::<Result<Vec<_>, _>>()?;
+        if amounts_out.iter().all(|a| *a == 0) {
+            return Err(QuoteError::ZeroOut);
+        }
+        if amounts_out.iter().zip(&self.cash).any(|(out, cash)| out > cash) {
+            return Err(QuoteError::InvalidAmount);
+        }
+        Ok(ExitQuote { amounts_out })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use weighted_pool::OracleAccumulators;
+
+    pub(crate) const E18: u128 = 1_000_000_000_000_000_000;
+
+    pub(crate) fn weighted(balances: &[u64], weights: &[u128], swap_fee: u64, total_bpt: u64) -> WeightedState {
+        WeightedState {
+            pool: Pool {
+                vault: Pubkey::new_unique(),
+                lp_mint: Pubkey::new_unique(),
+                weights: weights.to_vec(),
+                swap_fee,
+                total_bpt,
+                pool_id: [1; 32],
+                oracle: OracleAccumulators::default(),
+                entered: false,
+                version: weighted_pool::POOL_VERSION,
+                reserved: [0; Pool::RESERVED],
+            },
+            tokens: balances.iter().map(|_| Pubkey::new_unique()).collect(),
+            cash: balances.to_vec(),
+            managed: vec![0; balances.len()],
+            protocol_pct: 0,
+        }
+    }
+
+    #[test]
+    fn swaps_match_the_math_and_report_growing_impact() {
+        let mut state = weighted(&[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0, 2_000_000);
+        let params = state.swap_params(0, 1, 10_000).unwrap();
+        let small = state.swap_exact_in(0, 1, 10_000).unwrap();
+        assert_eq!(small.amount_out, (params.out_given_in() / fixed::ONE).as_u64());
+        let large = state.swap_exact_in(0, 1, 200_000).unwrap();
+        assert!(large.price_impact > small.price_impact);
+
+        // managed balances count towards the swap's balances
+        state.managed = vec![1_000_000, 1_000_000];
+        assert!(state.swap_exact_in(0, 1, 200_000).unwrap().price_impact < large.price_impact);
+
+        state.protocol_pct = (E18 / 2) as u64;
+        state.pool.swap_fee = (E18 / 100) as u64;
+        assert_eq!(state.swap_exact_in(0, 1, 10_000).unwrap().protocol_fee, 50);
+        assert!(matches!(state.swap_exact_in(0, 0, 1), Err(QuoteError::UnknownToken)));
+        // below the program's minimum trade, as the instruction rejects it
+        assert!(matches!(state.swap_exact_in(0, 1, MIN_TRADE_AMOUNT - 1), Err(QuoteError::InvalidAmount)));
+        assert!(matches!(state.join_exact_tokens_in(&[0, 0]), Err(QuoteError::InvalidAmount)));
+    }
+
+    #[test]
+    fn swaps_price_at_the_weights_of_the_tokens_traded() {
+        let state = weighted(&[4_000_000, 1_000_000], &[8 * E18 / 10, 2 * E18 / 10], 0, 2_000_000);
+        // 4M · (1 − (1M / 1.01M)^(0.2 / 0.8)) = 9,937.9…
+        assert_eq!(state.swap_exact_in(1, 0, 10_000).unwrap().amount_out, 9_937);
+        let params = state.swap_params(1, 0, 10_000).unwrap();
+        assert_eq!((params.weight_in, params.weight_out), (U256::from(2 * E18 / 10), U256::from(8 * E18 / 10)));
+    }
+
+    #[test]
+    fn exits_price_each_token_as_the_program_does() {
+        let state = weighted(&[1_000_000, 4_000_000], &[E18 / 2, E18 / 2], 0, 2_000_000);
+        // 10% of the BPT takes 10% of every balance
+        let exit = state.exit_exact_bpt_in(200_000).unwrap();
+        assert_eq!(exit.amounts_out, vec![100_000, 400_000]);
+        assert!(matches!(state.exit_exact_bpt_in(2_000_001), Err(QuoteError::InvalidAmount)));
+        // and neither the swap fee nor the protocol takes a cut of it
+        let mut charged = weighted(&[1_000_000, 4_000_000], &[E18 / 2, E18 / 2], (E18 / 100) as u64, 2_000_000);
+        charged.protocol_pct = (E18 / 2) as u64;
+        assert_eq!(charged.exit_exact_bpt_in(200_000).unwrap(), exit);
+        // managed balances count towards the share, but it is paid in cash
+        let mut managed = weighted(&[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0, 2_000_000);
+        managed.managed = vec![0, 3_000_000];
+        assert_eq!(managed.exit_exact_bpt_in(200_000).unwrap(), exit);
+        assert!(matches!(managed.exit_exact_bpt_in(600_000), Err(QuoteError::InvalidAmount)));
+        let join = state.join_exact_tokens_in(&[100_000, 400_000]).unwrap();
+        // a proportional join, rounded down in the pool's favour
+        assert_eq!(join.bpt_out, 199_999);
+
+        // the first join mints n · ∏ amountᵢ^wᵢ = 2 · √(100k · 400k), less
+        // the MINIMUM_BPT it locks
+        let mut empty = weighted(&[0, 0], &[E18 / 2, E18 / 2], 0, 0);
+        empty.protocol_pct = (E18 / 2) as u64;
+        let seed = empty.join_exact_tokens_in(&[100_000, 400_000]).unwrap();
+        assert!((398_990..=399_000).contains(&seed.bpt_out));
+        assert_eq!(seed.protocol_bpt, 0);
+        // and seeds what custody already holds along with its own
+        let dusted = weighted(&[100_000, 400_000], &[E18 / 2, E18 / 2], 0, 0);
+        let seed = dusted.join_exact_tokens_in(&[100_000, 400_000]).unwrap();
+        assert!((798_990..=799_000).contains(&seed.bpt_out));
+        assert!(matches!(empty.join_exact_tokens_in(&[100_000, 0]), Err(QuoteError::InvalidAmount)));
+    }
+}
@@ -0,0 +1,183 @@
+// Symmetric‑Solana ─ SOR batch swap builder
+// ================================================================
+// Turns a route into the Vault's `batch_swap`: one asset per token the
+// route touches, one exact‑in step per hop (later hops of a path swap
+// the previous step's output), and limits that only bound the token in
+// and the token out. Intermediate tokens net to zero.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use common::seeds;
+use vault::BatchSwapStep;
+
+use crate::{Route, SorError};
+
+/// Fixed accounts of a `batch_swap`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchSwapAccounts {
+    pub vault_state: Pubkey,
+    /// Trader whose funds are swapped
+    pub user: Pubkey,
+    /// Signer: `user`, or a relayer `user` has approved
+    pub sender: Pubkey,
+}
+
+/// The user's and the Vault's token accounts of one batch asset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AssetAccounts {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+}
+
+impl Route {
+    /// Batch assets: token in, token out, then intermediate tokens in
+    /// order of first use.
+    pub fn assets(&self) -> Vec<Pubkey> {
+        let mut assets = vec![self.token_in, self.token_out];
+        for hop in self.splits.iter().flat_map(|s| &s.path) {
+            if !assets.contains(&hop.token_out) {
+                assets.push(hop.token_out);
+            }
+        }
+        assets
+    }
+
+    /// `batch_swap` steps over [`Route::assets`].
+    pub fn steps(&self) -> Vec<BatchSwapStep> {
+        let assets = self.assets();
+        let index = |mint: &Pubkey| assets.iter().position(|a| a == mint).unwrap() as u8;
+        self.splits
+            .iter()
+            .flat_map(|split| {
+                split.path.iter().enumerate().map(move |(i, hop)| (i, hop, split.amount_in))
+            })
+            .map(|(i, hop, amount_in)| BatchSwapStep {
+                pool_id: hop.pool_id,
+                asset_in_index: index(&hop.token_in),
+                asset_out_index: index(&hop.token_out),
+                amount: if i == 0 { amount_in } else { 0 },
+            })
+            .collect()
+    }
+
+    /// `batch_swap` limits over [`Route::assets`]: pay at most the amount
+    /// in, receive at least `min_amount_out`.
+    pub fn limits(&self, min_amount_out: u64) -> Result<Vec<i64>, SorError> {
+        let mut limits = vec![0i64; self.assets().len()];
+        limits[0] = i64::try_from(self.amount_in).map_err(|_| SorError::AmountTooLarge)?;
+        limits[1] = -i64::try_from(min_amount_out).map_err(|_| SorError::AmountTooLarge)?;
+        Ok(limits)
+    }
+
+    /// Lowest acceptable output at `slippage_bps` below the quote.
+    pub fn min_amount_out(&self, slippage_bps: u16) -> u64 {
+        let kept = 10_000u128.saturating_sub(slippage_bps as u128);
+        (self.amount_out as u128 * kept / 10_000) as u64
+    }
+}
+
+/// The Vault `batch_swap` instruction executing `route`.
+/// `assets` holds the token accounts of each of [`Route::assets`], in order.
+pub fn batch_swap_instruction(
+    route: &Route,
+    accounts: &BatchSwapAccounts,
+    assets: &[AssetAccounts],
+    min_amount_out: u64,
+    deadline: i64,
+) -> Result<Instruction, SorError> {
+    let mints = route.assets();
+    if assets.len() != mints.len() {
+        return Err(SorError::AssetAccountsMismatch);
+    }
+    let vault_authority = vault_pda(&[seeds::VAULT_AUTHORITY, accounts.vault_state.as_ref()]);
+    let fees_collector  = vault_pda(&[seeds::FEES_COLLECTOR, accounts.vault_state.as_ref()]);
+
+    let mut metas = vault::accounts::BatchSwap {
+        vault_state: accounts.vault_state,
+        vault_authority,
+        user: accounts.user,
+        sender: accounts.sender,
+        relayer_approval: None,
+        permit_nonce: None,
+        instructions: None,
+        system_program: None,
+        fees_collector,
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+
+    for asset in assets {
+        metas.push(AccountMeta::new(asset.user, false));
+        metas.push(AccountMeta::new(asset.vault, false));
+    }
+    for hop in route.splits.iter().flat_map(|s| &s.path) {
+        let step = &hop.accounts;
+        let fee_account = vault_pda(&[seeds::PROTOCOL_FEES, fees_collector.as_ref(), hop.token_in.as_ref()]);
+        metas.extend([
+            AccountMeta::new_readonly(step.registration, false),
+            AccountMeta::new_readonly(step.pool_program, false),
+            AccountMeta::new(step.pool, false),
+            AccountMeta::new(step.pool_token_in, false),
+            AccountMeta::new(step.pool_token_out, false),
+            AccountMeta::new_readonly(step.pool_authority, false),
+            AccountMeta::new(fee_account, false),
+        ]);
+        metas.extend(step.balances.iter().map(|b| AccountMeta::new_readonly(*b, false)));
+    }
+
+    let data = vault::instruction::BatchSwap {
+        steps: route.steps(),
+        limits: route.limits(min_amount_out)?,
+        deadline,
+    }
+    .data();
+    Ok(Instruction { program_id: vault::ID, accounts: metas, data })
+}
+
+fn vault_pda(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &vault::ID).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::tests::{weighted, E18};
+    use crate::{RouterConfig, Sor};
+
+    #[test]
+    fn builds_chained_steps_for_a_multi_hop_route() {
+        let t: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let sor = Sor::new(vec![
+            weighted(&[t[0], t[1]], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+            weighted(&[t[1], t[2]], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+        ]);
+        let route = sor.route(&t[0], &t[2], 1_000, &RouterConfig::default()).unwrap();
+        assert_eq!(route.assets(), vec![t[0], t[2], t[1]]);
+
+        let steps = route.steps();
+        assert_eq!(steps.len(), 2);
+        assert_eq!((steps[0].asset_in_index, steps[0].asset_out_index, steps[0].amount), (0, 2, 1_000));
+        assert_eq!((steps[1].asset_in_index, steps[1].asset_out_index, steps[1].amount), (2, 1, 0));
+
+        let min_out = route.min_amount_out(50);
+        assert_eq!(route.limits(min_out).unwrap(), vec![1_000, -(min_out as i64), 0]);
+
+        let accounts = BatchSwapAccounts {
+            vault_state: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+            sender: Pubkey::new_unique(),
+        };
+        let assets: Vec<AssetAccounts> = (0..3)
+            .map(|_| AssetAccounts { user: Pubkey::new_unique(), vault: Pubkey::new_unique() })
+            .collect();
+        let ix = batch_swap_instruction(&route, &accounts, &assets, min_out, 0).unwrap();
+        assert_eq!(ix.program_id, vault::ID);
+        // 10 fixed accounts, an account pair per asset, 7 per step
+        assert_eq!(ix.accounts.len(), 10 + 3 * 2 + 2 * BatchSwapStep::ACCOUNTS);
+        assert_eq!(
+            batch_swap_instruction(&route, &accounts, &assets[..2], min_out, 0),
+            Err(SorError::AssetAccountsMismatch)
+        );
+    }
+}
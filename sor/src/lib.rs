@@ -0,0 +1,62 @@
+// Symmetric‑Solana ─ Smart order router
+// ================================================================
+// Off‑chain routing for aggregators and frontends:
+//   • Pool snapshots (weights, amplification, balances, fees) priced
+//     with the same math as the pool programs (pool).
+//   • Path enumeration and order splitting across pools (router).
+//   • Routes rendered as the Vault's `batch_swap` steps, limits and
+//     instruction (builder).
+//   • Native SOL in and out through a temporary wSOL account (native).
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+
+pub mod builder;
+pub mod native;
+pub mod pool;
+pub mod router;
+
+pub use builder::{batch_swap_instruction, AssetAccounts, BatchSwapAccounts};
+pub use native::{batch_swap_instructions_native, NativeSol, NATIVE_MINT};
+pub use pool::{PoolKind, PoolSnapshot};
+pub use router::{Hop, Path, RouterConfig, Sor, Split, StepAccounts};
+
+/// An exact‑in order routed across one or more paths.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub token_in: Pubkey,
+    pub token_out: Pubkey,
+    pub amount_in: u64,
+    /// Expected output at the snapshots the route was built from
+    pub amount_out: u64,
+    pub splits: Vec<Split>,
+}
+
+/// Why an order could not be routed or built.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SorError {
+    /// Zero amount, or the same token in and out
+    InvalidOrder,
+    /// No path connects the tokens, or none can take the amount
+    NoRoute,
+    /// An amount does not fit the instruction's signed limits
+    AmountTooLarge,
+    /// Asset account pairs do not match the route's assets
+    AssetAccountsMismatch,
+    /// The route has no native SOL, or its wSOL account is not the temporary one
+    NativeAccountMismatch,
+}
+
+impl core::fmt::Display for SorError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let msg = match self {
+            SorError::InvalidOrder => "invalid order",
+            SorError::NoRoute => "no route",
+            SorError::AmountTooLarge => "amount too large",
+            SorError::AssetAccountsMismatch => "asset accounts do not match the route",
+            SorError::NativeAccountMismatch => "native SOL account does not match the route",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for SorError {}
@@ -0,0 +1,146 @@
+// Symmetric‑Solana ─ Native SOL
+// ================================================================
+// Pools and the Vault only hold SPL tokens, so native SOL trades as
+// wSOL. Instead of asking users to wrap first, a flow is bracketed by a
+// temporary wSOL account: created and funded with the SOL to spend
+// (`sync_native` credits it), used as the user's token account, then
+// closed, which returns the SOL received and the rent to the owner.
+//
+// This works for any flow: batch swaps (see
+// `batch_swap_instructions_native`) as well as pool joins and exits,
+// whose instructions go through `NativeSol::wrap_around` with the
+// temporary account as the user's wSOL account.
+// ================================================================
+use anchor_lang::prelude::{Pubkey, Rent};
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::system_instruction;
+use anchor_spl::token::spl_token;
+
+use crate::builder::{batch_swap_instruction, AssetAccounts, BatchSwapAccounts};
+use crate::{Route, SorError};
+
+/// The wSOL mint
+pub const NATIVE_MINT: Pubkey = spl_token::native_mint::ID;
+
+/// A temporary wSOL account standing in for native SOL in one transaction.
+/// `account` is a fresh keypair's address; it signs the transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NativeSol {
+    /// Owner of the SOL, and of the account while it exists
+    pub owner: Pubkey,
+    pub account: Pubkey,
+}
+
+impl NativeSol {
+    /// Rent‑exempt minimum of a token account, refunded on close.
+    pub fn account_rent() -> u64 {
+        Rent::default().minimum_balance(spl_token::state::Account::LEN)
+    }
+
+    /// Create the account holding `lamports` of wSOL (zero to only
+    /// receive SOL).
+    pub fn wrap(&self, lamports: u64) -> Vec<Instruction> {
+        let mut ixs = vec![
+            system_instruction::create_account(
+                &self.owner,
+                &self.account,
+                Self::account_rent(),
+                spl_token::state::Account::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_account3(&spl_token::ID, &self.account, &NATIVE_MINT, &self.owner)
+                .expect("valid token program id"),
+        ];
+        if lamports > 0 {
+            ixs.push(system_instruction::transfer(&self.owner, &self.account, lamports));
+            ixs.push(spl_token::instruction::sync_native(&spl_token::ID, &self.account).expect("valid token program id"));
+        }
+        ixs
+    }
+
+    /// Close the account: every lamport it holds goes back to the owner as SOL.
+    pub fn unwrap(&self) -> Instruction {
+        spl_token::instruction::close_account(&spl_token::ID, &self.account, &self.owner, &self.owner, &[])
+            .expect("valid token program id")
+    }
+
+    /// `ixs` between wrapping `lamports` and unwrapping.
+    pub fn wrap_around(&self, lamports: u64, ixs: impl IntoIterator<Item = Instruction>) -> Vec<Instruction> {
+        let mut wrapped = self.wrap(lamports);
+        wrapped.extend(ixs);
+        wrapped.push(self.unwrap());
+        wrapped
+    }
+}
+
+/// The `batch_swap` executing `route` with native SOL in or out.
+/// The asset account pair of [`NATIVE_MINT`] must use `native.account` as
+/// the user account; SOL in is wrapped for the route's amount in.
+pub fn batch_swap_instructions_native(
+    route: &Route,
+    accounts: &BatchSwapAccounts,
+    assets: &[AssetAccounts],
+    min_amount_out: u64,
+    deadline: i64,
+    native: &NativeSol,
+) -> Result<Vec<Instruction>, SorError> {
+    let index = route.assets().iter().position(|m| *m == NATIVE_MINT);
+    match index.and_then(|i| assets.get(i)) {
+        Some(pair) if pair.user == native.account && accounts.user == native.owner => {}
+        _ => return Err(SorError::NativeAccountMismatch),
+    }
+    let lamports = if route.token_in == NATIVE_MINT { route.amount_in } else { 0 };
+    let swap = batch_swap_instruction(route, accounts, assets, min_amount_out, deadline)?;
+    Ok(native.wrap_around(lamports, [swap]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::tests::{weighted, E18};
+    use crate::{RouterConfig, Sor};
+
+    fn setup(token_in: Pubkey, token_out: Pubkey) -> (Route, BatchSwapAccounts, NativeSol) {
+        let sor = Sor::new(vec![weighted(&[token_in, token_out], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0)]);
+        let route = sor.route(&token_in, &token_out, 1_000, &RouterConfig::default()).unwrap();
+        let owner = Pubkey::new_unique();
+        let accounts = BatchSwapAccounts { vault_state: Pubkey::new_unique(), user: owner, sender: owner };
+        (route, accounts, NativeSol { owner, account: Pubkey::new_unique() })
+    }
+
+    #[test]
+    fn wraps_sol_in_and_closes_the_account_after() {
+        let usdc = Pubkey::new_unique();
+        let (route, accounts, native) = setup(NATIVE_MINT, usdc);
+        let assets = [
+            AssetAccounts { user: native.account, vault: Pubkey::new_unique() },
+            AssetAccounts { user: Pubkey::new_unique(), vault: Pubkey::new_unique() },
+        ];
+        let ixs = batch_swap_instructions_native(&route, &accounts, &assets, 0, 0, &native).unwrap();
+        // create, initialize, fund, sync, swap, close
+        assert_eq!(ixs.len(), 6);
+        assert_eq!(ixs[2], system_instruction::transfer(&native.owner, &native.account, 1_000));
+        assert_eq!(ixs[4].program_id, vault::ID);
+        assert_eq!(ixs[5], native.unwrap());
+    }
+
+    #[test]
+    fn only_opens_an_account_to_receive_sol() {
+        let usdc = Pubkey::new_unique();
+        let (route, accounts, native) = setup(usdc, NATIVE_MINT);
+        let mut assets = [
+            AssetAccounts { user: Pubkey::new_unique(), vault: Pubkey::new_unique() },
+            AssetAccounts { user: native.account, vault: Pubkey::new_unique() },
+        ];
+        let ixs = batch_swap_instructions_native(&route, &accounts, &assets, 0, 0, &native).unwrap();
+        // create, initialize, swap, close
+        assert_eq!(ixs.len(), 4);
+
+        assets[1].user = Pubkey::new_unique();
+        assert_eq!(
+            batch_swap_instructions_native(&route, &accounts, &assets, 0, 0, &native),
+            Err(SorError::NativeAccountMismatch)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+// Symmetric‑Solana ─ SOR pool snapshots
+// ================================================================
+// What the router knows about a pool: its Vault registration, custody
+// balances and pricing parameters, as read from chain. Quotes mirror
+// the pool programs' exact‑in swaps (fee taken from the amount in,
+// results rounded down) so routes price what `batch_swap` settles.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use common::{pool_authority, PoolId, Specialization};
+use math::stable_math;
+use math::weighted_math::SwapParams;
+use math::{fixed, U256};
+
+/// Pricing parameters of a pool type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    /// Weighted pool; one 18‑dec weight per token
+    Weighted { weights: Vec<u128> },
+    /// Stable pool: amplification times `AMP_PRECISION` and, per token,
+    /// the 18‑dec factor taking a token amount to the pool's 18‑dec
+    /// balances (10^(18 − decimals) times the token rate)
+    Stable { amp: u128, scaling_factors: Vec<u128> },
+}
+
+/// One registered pool as the router sees it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolSnapshot {
+    pub pool_id: PoolId,
+    /// The pool's Vault registration account
+    pub registration: Pubkey,
+    pub pool_program: Pubkey,
+    /// The pool state account
+    pub pool: Pubkey,
+    pub specialization: Specialization,
+    /// Registered mints, in registration order
+    pub tokens: Vec<Pubkey>,
+    /// Custody account of each token
+    pub custody: Vec<Pubkey>,
+    /// Custody balance of each token
+    pub balances: Vec<u64>,
+    /// 18‑dec swap fee
+    pub swap_fee: u64,
+    pub kind: PoolKind,
+}
+
+impl PoolSnapshot {
+    /// Position of `mint` among the pool's tokens.
+    pub fn index_of(&self, mint: &Pubkey) -> Option<usize> {
+        self.tokens.iter().position(|t| t == mint)
+    }
+
+    /// The PDA holding the pool's tokens.
+    pub fn authority(&self) -> Pubkey {
+        pool_authority(&self.pool, &self.pool_program)
+    }
+
+    /// Amount of token `index_out` an exact‑in swap of `amount_in` of
+    /// token `index_in` returns, or `None` when the pool cannot take it.
+    pub fn quote_out(&self, index_in: usize, index_out: usize, amount_in: u64) -> Option<u64> {
+        if index_in == index_out || index_in >= self.tokens.len() || index_out >= self.tokens.len() {
+            return None;
+        }
+        let out = match &self.kind {
+            PoolKind::Weighted { weights } => {
+                let params = SwapParams {
+                    balance_in:  U256::from(self.balances[index_in]) * fixed::ONE,
+                    weight_in:   U256::from(*weights.get(index_in)?),
+                    balance_out: U256::from(self.balances[index_out]) * fixed::ONE,
+                    weight_out:  U256::from(*weights.get(index_out)?),
+                    amount:      U256::from(amount_in) * fixed::ONE,
+                    fee:         U256::from(self.swap_fee),
+                };
+                params.try_out_given_in().ok()? / fixed::ONE
+            }
+            PoolKind::Stable { amp, scaling_factors } => {
+                let upscale = |i: usize, amount: u64| {
+                    fixed::try_mul_down(U256::from(amount), U256::from(*scaling_factors.get(i)?)).ok()
+                };
+                let balances = (0..self.tokens.len())
+                    .map(|i| upscale(i, self.balances[i]))
+                    .collect::<Option<Vec<_>>>()?;
+                let amp = U256::from(*amp);
+                let invariant = stable_math::try_calculate_invariant(amp, &balances).ok()?;
+                let out_fp = stable_math::try_calc_out_given_in(
+                    amp,
+                    &balances,
+                    index_in,
+                    index_out,
+                    upscale(index_in, amount_in)?,
+                    invariant,
+                    U256::from(self.swap_fee),
+                )
+                .ok()?;
+                fixed::try_div_down(out_fp, U256::from(scaling_factors[index_out])).ok()?
+            }
+        };
+        (out <= U256::from(u64::MAX)).then(|| out.as_u64())
+    }
+
+    /// Apply an exact‑in swap to the snapshot's balances.
+    pub(crate) fn apply(&mut self, index_in: usize, index_out: usize, amount_in: u64, amount_out: u64) {
+        self.balances[index_in] = self.balances[index_in].saturating_add(amount_in);
+        self.balances[index_out] = self.balances[index_out].saturating_sub(amount_out);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub const E18: u128 = 1_000_000_000_000_000_000;
+
+    pub fn weighted(tokens: &[Pubkey], balances: &[u64], weights: &[u128], swap_fee: u64) -> PoolSnapshot {
+        let pool = Pubkey::new_unique();
+        PoolSnapshot {
+            pool_id: pool.to_bytes(),
+            registration: Pubkey::new_unique(),
+            pool_program: Pubkey::new_unique(),
+            pool,
+            specialization: if tokens.len() == 2 { Specialization::TwoToken } else { Specialization::MinimalSwapInfo },
+            tokens: tokens.to_vec(),
+            custody: tokens.iter().map(|_| Pubkey::new_unique()).collect(),
+            balances: balances.to_vec(),
+            swap_fee,
+            kind: PoolKind::Weighted { weights: weights.to_vec() },
+        }
+    }
+
+    #[test]
+    fn weighted_quote_matches_the_pool_math() {
+        let tokens = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let pool = weighted(&tokens, &[1_000, 1_000], &[E18 / 2, E18 / 2], 0);
+        // 1000 · 100 / 1100 = 90.9…
+        assert_eq!(pool.quote_out(0, 1, 100), Some(90));
+        assert_eq!(pool.quote_out(0, 0, 100), None);
+        assert_eq!(pool.quote_out(0, 2, 100), None);
+    }
+
+    #[test]
+    fn stable_quote_is_near_par_across_decimals() {
+        let tokens = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let pool = PoolSnapshot {
+            kind: PoolKind::Stable {
+                amp: 200 * stable_math::AMP_PRECISION as u128,
+                // 6 and 9 decimals
+                scaling_factors: vec![1_000_000_000_000 * E18, 1_000_000_000 * E18],
+            },
+            specialization: Specialization::General,
+            ..weighted(&tokens, &[1_000_000_000_000, 1_000_000_000_000_000], &[], 0)
+        };
+        let out = pool.quote_out(0, 1, 1_000_000).unwrap();
+        assert!((999_000_000..1_000_000_000).contains(&out), "{out}");
+    }
+}
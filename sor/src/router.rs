@@ -0,0 +1,309 @@
+// Symmetric‑Solana ─ SOR path search and order splitting
+// ================================================================
+// Candidate paths are simple walks token_in → token_out through at
+// most `max_hops` distinct pools. The order is cut into `parts` equal
+// chunks and each chunk goes to whichever candidate path pays most for
+// it given the chunks already placed, so splits stop where the next
+// chunk would move a pool's price past another path's. The result is
+// kept only if it beats the best single path.
+// ================================================================
+use anchor_lang::prelude::Pubkey;
+use common::PoolId;
+
+use crate::pool::PoolSnapshot;
+use crate::{Route, SorError};
+
+/// Longest path the router considers by default.
+pub const DEFAULT_MAX_HOPS: usize = 3;
+
+/// Router tuning.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouterConfig {
+    /// Most pools a single path may cross
+    pub max_hops: usize,
+    /// Most paths an order may be split across
+    pub max_paths: usize,
+    /// Number of chunks the order is cut into while splitting
+    pub parts: u64,
+}
+
+impl Default for RouterConfig {
+    fn default() -> Self {
+        Self { max_hops: DEFAULT_MAX_HOPS, max_paths: 4, parts: 20 }
+    }
+}
+
+/// Accounts `batch_swap` needs for one step, besides the batch's assets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepAccounts {
+    pub registration: Pubkey,
+    pub pool_program: Pubkey,
+    pub pool: Pubkey,
+    pub pool_token_in: Pubkey,
+    pub pool_token_out: Pubkey,
+    pub pool_authority: Pubkey,
+    /// Every custody account of a General pool, none otherwise
+    pub balances: Vec<Pubkey>,
+}
+
+/// One swap of a path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hop {
+    /// Position of the pool in the router's pool list
+    pub pool_index: usize,
+    pub pool_id: PoolId,
+    pub token_in: Pubkey,
+    pub token_out: Pubkey,
+    pub accounts: StepAccounts,
+}
+
+/// A walk token_in → token_out through distinct pools.
+pub type Path = Vec<Hop>;
+
+/// Part of an order sent down one path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Split {
+    pub path: Path,
+    pub amount_in: u64,
+    /// Expected output, with the earlier splits of the route already executed
+    pub amount_out: u64,
+}
+
+/// Smart order router over a set of pool snapshots.
+#[derive(Clone, Debug, Default)]
+pub struct Sor {
+    pools: Vec<PoolSnapshot>,
+}
+
+impl Sor {
+    pub fn new(pools: Vec<PoolSnapshot>) -> Self {
+        Self { pools }
+    }
+
+    pub fn pools(&self) -> &[PoolSnapshot] {
+        &self.pools
+    }
+
+    /// Replace a pool's snapshot (matched by pool id), or add it.
+    pub fn upsert_pool(&mut self, pool: PoolSnapshot) {
+        match self.pools.iter_mut().find(|p| p.pool_id == pool.pool_id) {
+            Some(existing) => *existing = pool,
+            None => self.pools.push(pool),
+        }
+    }
+
+    /// Every path from `token_in` to `token_out` of at most `max_hops`
+    /// pools, never visiting a pool or a token twice.
+    pub fn paths(&self, token_in: &Pubkey, token_out: &Pubkey, max_hops: usize) -> Vec<Path> {
+        let mut found = Vec::new();
+        let mut path  = Vec::new();
+        let mut seen  = vec![*token_in];
+        self.walk(token_in, token_out, max_hops, &mut path, &mut seen, &mut found);
+        found
+    }
+
+    fn walk(
+        &self,
+        at: &Pubkey,
+        target: &Pubkey,
+        hops_left: usize,
+        path: &mut Path,
+        seen: &mut Vec<Pubkey>,
+        found: &mut Vec<Path>,
+    ) {
+        if hops_left == 0 {
+            return;
+        }
+        for (pool_index, pool) in self.pools.iter().enumerate() {
+            if path.iter().any(|h| h.pool_index == pool_index) {
+                continue;
+            }
+            let Some(index_in) = pool.index_of(at) else { continue };
+            for (index_out, next) in pool.tokens.iter().enumerate() {
+                if index_out == index_in || (seen.contains(next) && next != target) {
+                    continue;
+                }
+                path.push(hop(pool_index, pool, index_in, index_out));
+                if next == target {
+                    found.push(path.clone());
+                } else {
+                    seen.push(*next);
+                    self.walk(next, target, hops_left - 1, path, seen, found);
+                    seen.pop();
+                }
+                path.pop();
+            }
+        }
+    }
+
+    /// Output of `amount_in` down `path` at the current snapshots.
+    pub fn quote_path(&self, path: &[Hop], amount_in: u64) -> Option<u64> {
+        simulate(&mut self.pools.clone(), path, amount_in)
+    }
+
+    /// Best route for an exact‑in order of `amount_in` of `token_in`.
+    pub fn route(
+        &self,
+        token_in: &Pubkey,
+        token_out: &Pubkey,
+        amount_in: u64,
+        config: &RouterConfig,
+    ) -> Result<Route, SorError> {
+        if amount_in == 0 || token_in == token_out {
+            return Err(SorError::InvalidOrder);
+        }
+        // candidate paths, best single‑path quote first
+        let mut candidates: Vec<(Path, u64)> = self
+            .paths(token_in, token_out, config.max_hops)
+            .into_iter()
+            .filter_map(|p| self.quote_path(&p, amount_in).map(|out| (p, out)))
+            .collect();
+        if candidates.is_empty() {
+            return Err(SorError::NoRoute);
+        }
+        candidates.sort_by_key(|c| core::cmp::Reverse(c.1));
+        candidates.truncate(config.max_paths.max(1));
+
+        let (best_path, _) = &candidates[0];
+        let single = self.finish(token_in, token_out, vec![(best_path.clone(), amount_in)]);
+
+        // greedy split: each chunk to the path paying most for it
+        let parts = config.parts.clamp(1, amount_in);
+        let chunk = amount_in / parts;
+        let mut state = self.pools.clone();
+        let mut allocated = vec![0u64; candidates.len()];
+        for part in 0..parts {
+            let size = if part + 1 == parts { amount_in - chunk * (parts - 1) } else { chunk };
+            let best = candidates
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (p, _))| simulate(&mut state.clone(), p, size).map(|out| (i, out)))
+                .max_by_key(|&(_, out)| out);
+            let Some((i, _)) = best else { return single };
+            simulate(&mut state, &candidates[i].0, size);
+            allocated[i] += size;
+        }
+        let splits = candidates
+            .into_iter()
+            .zip(allocated)
+            .filter(|(_, amount)| *amount > 0)
+            .map(|((p, _), amount)| (p, amount))
+            .collect();
+        let split = self.finish(token_in, token_out, splits);
+
+        match (single, split) {
+            (Ok(s), Ok(m)) => Ok(if m.amount_out > s.amount_out { m } else { s }),
+            (s, m) => s.or(m),
+        }
+    }
+
+    /// Quote the splits in execution order, as `batch_swap` runs them.
+    fn finish(&self, token_in: &Pubkey, token_out: &Pubkey, splits: Vec<(Path, u64)>) -> Result<Route, SorError> {
+        let mut state = self.pools.clone();
+        let mut route = Route {
+            token_in: *token_in,
+            token_out: *token_out,
+            amount_in: 0,
+            amount_out: 0,
+            splits: Vec::with_capacity(splits.len()),
+        };
+        for (path, amount_in) in splits {
+            let amount_out = simulate(&mut state, &path, amount_in).ok_or(SorError::NoRoute)?;
+            route.amount_in  += amount_in;
+            route.amount_out = route.amount_out.checked_add(amount_out).ok_or(SorError::AmountTooLarge)?;
+            route.splits.push(Split { path, amount_in, amount_out });
+        }
+        Ok(route)
+    }
+}
+
+fn hop(pool_index: usize, pool: &PoolSnapshot, index_in: usize, index_out: usize) -> Hop {
+    let balances = match pool.specialization {
+        common::Specialization::General => pool.custody.clone(),
+        _ => Vec::new(),
+    };
+    Hop {
+        pool_index,
+        pool_id: pool.pool_id,
+        token_in: pool.tokens[index_in],
+        token_out: pool.tokens[index_out],
+        accounts: StepAccounts {
+            registration: pool.registration,
+            pool_program: pool.pool_program,
+            pool: pool.pool,
+            pool_token_in: pool.custody[index_in],
+            pool_token_out: pool.custody[index_out],
+            pool_authority: pool.authority(),
+            balances,
+        },
+    }
+}
+
+/// Run `amount_in` down `path`, updating the pools' balances.
+fn simulate(pools: &mut [PoolSnapshot], path: &[Hop], amount_in: u64) -> Option<u64> {
+    let mut amount = amount_in;
+    for h in path {
+        let pool = pools.get_mut(h.pool_index)?;
+        let (i, o) = (pool.index_of(&h.token_in)?, pool.index_of(&h.token_out)?);
+        let out = pool.quote_out(i, o, amount)?;
+        if out == 0 {
+            return None;
+        }
+        pool.apply(i, o, amount, out);
+        amount = out;
+    }
+    Some(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::tests::{weighted, E18};
+
+    fn mints(n: usize) -> Vec<Pubkey> {
+        (0..n).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn finds_direct_and_multi_hop_paths() {
+        let t = mints(3);
+        let sor = Sor::new(vec![
+            weighted(&[t[0], t[1]], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+            weighted(&[t[1], t[2]], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+            weighted(&[t[0], t[2]], &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+        ]);
+        let paths = sor.paths(&t[0], &t[2], 3);
+        assert_eq!(paths.len(), 2);
+        assert!(paths.iter().any(|p| p.len() == 1));
+        assert!(paths.iter().any(|p| p.len() == 2 && p[0].token_out == t[1]));
+        assert_eq!(sor.paths(&t[0], &t[2], 1).len(), 1);
+    }
+
+    #[test]
+    fn splits_a_large_order_across_equal_pools() {
+        let t = mints(2);
+        let pools = vec![
+            weighted(&t, &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+            weighted(&t, &[1_000_000, 1_000_000], &[E18 / 2, E18 / 2], 0),
+        ];
+        let sor = Sor::new(pools);
+        let route = sor.route(&t[0], &t[1], 200_000, &RouterConfig::default()).unwrap();
+        assert_eq!(route.splits.len(), 2);
+        assert_eq!(route.amount_in, 200_000);
+        // 1e6 · 1e5 / 1.1e6 twice beats 1e6 · 2e5 / 1.2e6 once
+        assert!(route.amount_out > 166_666, "{}", route.amount_out);
+        assert!(route.amount_out <= 181_818, "{}", route.amount_out);
+
+        // a small order stays on one pool
+        let route = sor.route(&t[0], &t[1], 10, &RouterConfig::default()).unwrap();
+        assert_eq!(route.splits.len(), 1);
+    }
+
+    #[test]
+    fn rejects_orders_without_a_route() {
+        let t = mints(3);
+        let sor = Sor::new(vec![weighted(&[t[0], t[1]], &[1_000, 1_000], &[E18 / 2, E18 / 2], 0)]);
+        assert_eq!(sor.route(&t[0], &t[2], 10, &RouterConfig::default()), Err(SorError::NoRoute));
+        assert_eq!(sor.route(&t[0], &t[1], 0, &RouterConfig::default()), Err(SorError::InvalidOrder));
+    }
+}
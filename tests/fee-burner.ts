@@ -0,0 +1,21 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { FeeBurner } from "../target/types/fee_burner";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.FeeBurner as Program<FeeBurner>;
+
+describe("fee-burner", () => {
+  it("sells fees only within a floor price set beforehand", async () => {
+    const burn = program.idl.instructions.find((i) => i.name === "burnFees");
+    assert.ok(burn);
+    assert.deepEqual(burn.args.map((a) => a.name), ["amount", "steps", "deadline"]);
+
+    const limit = program.idl.instructions.find((i) => i.name === "setTokenLimit");
+    assert.deepEqual(limit.args.map((a) => a.name), ["minPrice"]);
+  });
+});
@@ -0,0 +1,110 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+
+import { VotingEscrow }   from "../target/types/voting_escrow";
+import { FeeDistributor } from "../target/types/fee_distributor";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const escrowProgram      = anchor.workspace.VotingEscrow   as Program<VotingEscrow>;
+const distributorProgram = anchor.workspace.FeeDistributor as Program<FeeDistributor>;
+
+const WEEK = 7 * 24 * 60 * 60;
+
+function pda(seeds: Buffer[], programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+function weekSeed(week: number): Buffer {
+  const b = Buffer.alloc(8);
+  b.writeBigInt64LE(BigInt(week));
+  return b;
+}
+
+describe("fee-distributor", () => {
+  const owner = provider.wallet.publicKey;
+  let escrow: anchor.web3.PublicKey;
+  let feeMint: anchor.web3.PublicKey;
+  let distributor: anchor.web3.PublicKey;
+  let tokenVault: anchor.web3.PublicKey;
+
+  before(async () => {
+    const veMint = await createMint(provider.connection, provider.wallet.payer, owner, null, 6);
+    escrow = pda([Buffer.from("voting-escrow"), veMint.toBuffer()], escrowProgram.programId);
+    await escrowProgram.methods.initializeEscrow().accounts({ payer: owner, tokenMint: veMint }).rpc();
+
+    feeMint = await createMint(provider.connection, provider.wallet.payer, owner, null, 6);
+    distributor = pda(
+      [Buffer.from("fee-distributor"), escrow.toBuffer(), feeMint.toBuffer()],
+      distributorProgram.programId
+    );
+    tokenVault = pda([Buffer.from("distributor-tokens"), distributor.toBuffer()], distributorProgram.programId);
+  });
+
+  it("starts distributing at a week boundary", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    await distributorProgram.methods
+      .initializeDistributor(new anchor.BN(now - WEEK))
+      .accounts({ payer: owner, escrow, tokenMint: feeMint })
+      .rpc();
+
+    const state = await distributorProgram.account.distributor.fetch(distributor);
+    assert.equal(state.startWeek.toNumber() % WEEK, 0);
+    assert.ok(state.tokenVault.equals(tokenVault));
+  });
+
+  it("credits received fees to the current week", async () => {
+    await mintTo(provider.connection, provider.wallet.payer, feeMint, tokenVault, owner, 5_000_000);
+
+    const week = Math.floor(Math.floor(Date.now() / 1000) / WEEK) * WEEK;
+    const weekRecord = pda(
+      [Buffer.from("fee-week"), distributor.toBuffer(), weekSeed(week)],
+      distributorProgram.programId
+    );
+    await distributorProgram.methods
+      .checkpointToken(new anchor.BN(week))
+      .accounts({ payer: owner, distributor, tokenVault })
+      .rpc();
+
+    const record = await distributorProgram.account.weekRecord.fetch(weekRecord);
+    assert.equal(record.tokens.toNumber(), 5_000_000);
+    const state = await distributorProgram.account.distributor.fetch(distributor);
+    assert.equal(state.lastTokenBalance.toNumber(), 5_000_000);
+  });
+
+  it("does not pay out a week that has not ended", async () => {
+    const veMint = (await escrowProgram.account.escrow.fetch(escrow)).tokenMint;
+    const ownerVe = await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, veMint, owner);
+    await mintTo(provider.connection, provider.wallet.payer, veMint, ownerVe.address, owner, 1_000_000);
+    await escrowProgram.methods
+      .createLock(new anchor.BN(1_000_000), new anchor.BN(Math.floor(Date.now() / 1000) + 52 * WEEK))
+      .accounts({ escrow, owner, ownerTokenAccount: ownerVe.address })
+      .rpc();
+
+    const ownerFees = await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, feeMint, owner);
+    const week = Math.floor(Math.floor(Date.now() / 1000) / WEEK) * WEEK;
+    try {
+      await distributorProgram.methods
+        .claim()
+        .accounts({ distributor, escrow, user: owner, userTokenAccount: ownerFees.address, tokenVault })
+        .remainingAccounts([
+          {
+            pubkey: pda([Buffer.from("fee-week"), distributor.toBuffer(), weekSeed(week)], distributorProgram.programId),
+            isWritable: false,
+            isSigner: false,
+          },
+        ])
+        .rpc();
+      assert.fail("claimed an unfinished week");
+    } catch (e) {
+      assert.include(String(e), "WeekNotOver");
+    }
+  });
+});
@@ -0,0 +1,172 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+
+import { Vault }        from "../target/types/vault";
+import { WeightedPool } from "../target/types/weighted_pool";
+import { PoolFactory }  from "../target/types/pool_factory";
+import { Gauge }        from "../target/types/gauge";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const vaultProgram    = anchor.workspace.Vault        as Program<Vault>;
+const weightedProgram = anchor.workspace.WeightedPool as Program<WeightedPool>;
+const factoryProgram  = anchor.workspace.PoolFactory  as Program<PoolFactory>;
+const gaugeProgram    = anchor.workspace.Gauge        as Program<Gauge>;
+
+const E18 = new anchor.BN("1000000000000000000");
+const DAY = 24 * 60 * 60;
+
+function pda(seeds: Buffer[], programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+/** Mirrors `common::canonical_pool_hash`. */
+function canonicalPoolHash(tokens: anchor.web3.PublicKey[], weights: anchor.BN[], swapFee: anchor.BN): Buffer {
+  const h = createHash("sha256").update(Buffer.from("canonical-pool"));
+  tokens.forEach((t) => h.update(t.toBuffer()));
+  weights.forEach((w) => h.update(w.toArrayLike(Buffer, "le", 16)));
+  return h.update(swapFee.toArrayLike(Buffer, "le", 8)).digest();
+}
+
+/** Mirrors `common::derive_pool_id`. */
+function derivePoolId(vaultState: anchor.web3.PublicKey, pool: anchor.web3.PublicKey, specialization: number, nonce: anchor.BN): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("pool-id"))
+    .update(vaultState.toBuffer())
+    .update(pool.toBuffer())
+    .update(Buffer.from([specialization]))
+    .update(nonce.toArrayLike(Buffer, "le", 8))
+    .digest();
+}
+
+describe("gauge", () => {
+  const owner      = provider.wallet.publicKey;
+  const vaultState = pda([Buffer.from("vault-state"), owner.toBuffer()], vaultProgram.programId);
+  const authorizer = pda([Buffer.from("authorizer"), vaultState.toBuffer()], vaultProgram.programId);
+  const factoryAuthority = pda([Buffer.from("factory-authority"), vaultState.toBuffer()], factoryProgram.programId);
+
+  let pool: anchor.web3.PublicKey;
+  let rewardMint: anchor.web3.PublicKey;
+  let gauge: anchor.web3.PublicKey;
+
+  before(async () => {
+    try {
+      await vaultProgram.account.vaultState.fetch(vaultState);
+    } catch {
+      await vaultProgram.methods
+        .initialize(owner)
+        .accounts({ vaultState, payer: owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    try {
+      await vaultProgram.account.authorizer.fetch(authorizer);
+    } catch {
+      await vaultProgram.methods
+        .initializeAuthorizer()
+        .accounts({ vaultState, authorizer, owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    await vaultProgram.methods
+      .grantRole({ registerPool: {} }, factoryAuthority)
+      .accounts({ authorizer, admin: owner })
+      .rpc();
+    await vaultProgram.methods
+      .grantRole({ manageRewards: {} }, owner)
+      .accounts({ authorizer, admin: owner })
+      .rpc();
+
+    // a factory pool to attach the gauge to
+    const tokens = [];
+    for (let i = 0; i < 2; i++) {
+      tokens.push(await createMint(provider.connection, provider.wallet.payer, owner, null, 6));
+    }
+    tokens.sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    const weights = [E18.divn(2), E18.divn(2)];
+    const swapFee = E18.divn(1000);
+    pool = pda(
+      [Buffer.from("pool-state"), vaultState.toBuffer(), canonicalPoolHash(tokens, weights, swapFee)],
+      weightedProgram.programId
+    );
+    const { poolCount, registryTail } = await vaultProgram.account.vaultState.fetch(vaultState);
+    const poolRegistration = pda(
+      [Buffer.from("pool-registration"), vaultState.toBuffer(), derivePoolId(vaultState, pool, 2, poolCount)],
+      vaultProgram.programId
+    );
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(registryTail);
+    await factoryProgram.methods
+      .createWeightedPool(weights, swapFee)
+      .accounts({
+        creator: owner,
+        factoryAuthority,
+        vaultState,
+        authorizer,
+        poolRegistration,
+        registryPage: pda([Buffer.from("pool-registry"), vaultState.toBuffer(), pageIndex], vaultProgram.programId),
+        vaultAuthority: pda([Buffer.from("vault-authority"), vaultState.toBuffer()], vaultProgram.programId),
+        pool,
+        weightedPoolProgram: weightedProgram.programId,
+        vaultProgram: vaultProgram.programId,
+      })
+      .remainingAccounts(
+        tokens.flatMap((mint) => [
+          { pubkey: mint, isWritable: false, isSigner: false },
+          {
+            pubkey: pda([Buffer.from("pool-token"), poolRegistration.toBuffer(), mint.toBuffer()], vaultProgram.programId),
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+      )
+      .rpc();
+
+    rewardMint = await createMint(provider.connection, provider.wallet.payer, owner, null, 6);
+  });
+
+  it("creates a gauge for a factory pool", async () => {
+    const poolRecord = pda([Buffer.from("pool-record"), pool.toBuffer()], factoryProgram.programId);
+    const { lpMint } = await factoryProgram.account.poolRecord.fetch(poolRecord);
+    gauge = pda([Buffer.from("gauge"), pool.toBuffer(), rewardMint.toBuffer()], gaugeProgram.programId);
+
+    await gaugeProgram.methods
+      .createGauge()
+      .accounts({ creator: owner, poolRecord, lpMint, rewardMint })
+      .rpc();
+
+    const state = await gaugeProgram.account.gauge.fetch(gauge);
+    assert.ok(state.pool.equals(pool));
+    assert.ok(state.lpMint.equals(lpMint));
+    assert.equal(state.rewardRate.toNumber(), 0);
+  });
+
+  it("starts emissions when funded by a reward manager", async () => {
+    const funding = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      rewardMint,
+      owner
+    );
+    await mintTo(provider.connection, provider.wallet.payer, rewardMint, funding.address, owner, 7 * DAY * 10);
+
+    await gaugeProgram.methods
+      .notifyRewardAmount(new anchor.BN(7 * DAY * 10), new anchor.BN(7 * DAY))
+      .accounts({
+        gauge,
+        distributor: owner,
+        distributorRewardAccount: funding.address,
+        rewardVault: pda([Buffer.from("gauge-rewards"), gauge.toBuffer()], gaugeProgram.programId),
+      })
+      .rpc();
+
+    const state = await gaugeProgram.account.gauge.fetch(gauge);
+    assert.equal(state.rewardRate.toNumber(), 10);
+  });
+});
@@ -0,0 +1,21 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { LimitOrders } from "../target/types/limit_orders";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.LimitOrders as Program<LimitOrders>;
+
+describe("limit-orders", () => {
+  it("lets keepers fill resting orders through the vault for a tip", async () => {
+    const place = program.idl.instructions.find((i) => i.name === "placeOrder");
+    assert.deepEqual(place.args.map((a) => a.name), ["id", "amountIn", "limitPrice", "expiry", "tip"]);
+
+    const fill = program.idl.instructions.find((i) => i.name === "fillOrder");
+    assert.deepEqual(fill.args.map((a) => a.name), ["steps", "deadline"]);
+    assert.ok(fill.accounts.find((a) => a.name === "keeper"));
+  });
+});
@@ -0,0 +1,29 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { LinearPool } from "../target/types/linear_pool";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.LinearPool as Program<LinearPool>;
+
+describe("linear-pool", () => {
+  it("trades only by swaps, with a target range on the main balance", async () => {
+    const names = program.idl.instructions.map((i) => i.name);
+    assert.includeMembers(names, [
+      "initializePool",
+      "setTargets",
+      "setSwapFee",
+      "updateWrappedRate",
+      "swapExactTokenInForTokenOut",
+      "swapTokenInForExactTokenOut",
+      "querySwap",
+    ]);
+    assert.notInclude(names.join(","), "join");
+
+    const init = program.idl.instructions.find((i) => i.name === "initializePool");
+    assert.deepEqual(init.args.map((a) => a.name), ["lowerTarget", "upperTarget", "swapFee", "rateProvider"]);
+  });
+});
@@ -0,0 +1,204 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+
+import { Vault }        from "../target/types/vault";
+import { WeightedPool } from "../target/types/weighted_pool";
+import { PoolFactory }  from "../target/types/pool_factory";
+import { LiquidityMining } from "../target/types/liquidity_mining";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const vaultProgram    = anchor.workspace.Vault        as Program<Vault>;
+const weightedProgram = anchor.workspace.WeightedPool as Program<WeightedPool>;
+const factoryProgram  = anchor.workspace.PoolFactory  as Program<PoolFactory>;
+const farmProgram     = anchor.workspace.LiquidityMining as Program<LiquidityMining>;
+
+const E18 = new anchor.BN("1000000000000000000");
+const DAY = 24 * 60 * 60;
+const MAX_REWARDS = 8;
+
+function pda(seeds: Buffer[], programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+/** Mirrors `common::canonical_pool_hash`. */
+function canonicalPoolHash(tokens: anchor.web3.PublicKey[], weights: anchor.BN[], swapFee: anchor.BN): Buffer {
+  const h = createHash("sha256").update(Buffer.from("canonical-pool"));
+  tokens.forEach((t) => h.update(t.toBuffer()));
+  weights.forEach((w) => h.update(w.toArrayLike(Buffer, "le", 16)));
+  return h.update(swapFee.toArrayLike(Buffer, "le", 8)).digest();
+}
+
+/** Mirrors `common::derive_pool_id`. */
+function derivePoolId(vaultState: anchor.web3.PublicKey, pool: anchor.web3.PublicKey, specialization: number, nonce: anchor.BN): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("pool-id"))
+    .update(vaultState.toBuffer())
+    .update(pool.toBuffer())
+    .update(Buffer.from([specialization]))
+    .update(nonce.toArrayLike(Buffer, "le", 8))
+    .digest();
+}
+
+describe("liquidity-mining", () => {
+  const owner      = provider.wallet.publicKey;
+  const vaultState = pda([Buffer.from("vault-state"), owner.toBuffer()], vaultProgram.programId);
+  const authorizer = pda([Buffer.from("authorizer"), vaultState.toBuffer()], vaultProgram.programId);
+  const factoryAuthority = pda([Buffer.from("factory-authority"), vaultState.toBuffer()], factoryProgram.programId);
+
+  let pool: anchor.web3.PublicKey;
+  let farm: anchor.web3.PublicKey;
+
+  before(async () => {
+    try {
+      await vaultProgram.account.vaultState.fetch(vaultState);
+    } catch {
+      await vaultProgram.methods
+        .initialize(owner)
+        .accounts({ vaultState, payer: owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    try {
+      await vaultProgram.account.authorizer.fetch(authorizer);
+    } catch {
+      await vaultProgram.methods
+        .initializeAuthorizer()
+        .accounts({ vaultState, authorizer, owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    await vaultProgram.methods
+      .grantRole({ registerPool: {} }, factoryAuthority)
+      .accounts({ authorizer, admin: owner })
+      .rpc();
+    await vaultProgram.methods
+      .grantRole({ manageRewards: {} }, owner)
+      .accounts({ authorizer, admin: owner })
+      .rpc();
+
+    // a factory pool to farm
+    const tokens = [];
+    for (let i = 0; i < 2; i++) {
+      tokens.push(await createMint(provider.connection, provider.wallet.payer, owner, null, 6));
+    }
+    tokens.sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+    const weights = [E18.divn(2), E18.divn(2)];
+    const swapFee = E18.divn(1000);
+    pool = pda(
+      [Buffer.from("pool-state"), vaultState.toBuffer(), canonicalPoolHash(tokens, weights, swapFee)],
+      weightedProgram.programId
+    );
+    const { poolCount, registryTail } = await vaultProgram.account.vaultState.fetch(vaultState);
+    const poolRegistration = pda(
+      [Buffer.from("pool-registration"), vaultState.toBuffer(), derivePoolId(vaultState, pool, 2, poolCount)],
+      vaultProgram.programId
+    );
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(registryTail);
+    await factoryProgram.methods
+      .createWeightedPool(weights, swapFee)
+      .accounts({
+        creator: owner,
+        factoryAuthority,
+        vaultState,
+        authorizer,
+        poolRegistration,
+        registryPage: pda([Buffer.from("pool-registry"), vaultState.toBuffer(), pageIndex], vaultProgram.programId),
+        vaultAuthority: pda([Buffer.from("vault-authority"), vaultState.toBuffer()], vaultProgram.programId),
+        pool,
+        weightedPoolProgram: weightedProgram.programId,
+        vaultProgram: vaultProgram.programId,
+      })
+      .remainingAccounts(
+        tokens.flatMap((mint) => [
+          { pubkey: mint, isWritable: false, isSigner: false },
+          {
+            pubkey: pda([Buffer.from("pool-token"), poolRegistration.toBuffer(), mint.toBuffer()], vaultProgram.programId),
+            isWritable: true,
+            isSigner: false,
+          },
+        ])
+      )
+      .rpc();
+  });
+
+  it("creates a farm for a factory pool", async () => {
+    const poolRecord = pda([Buffer.from("pool-record"), pool.toBuffer()], factoryProgram.programId);
+    const { lpMint } = await factoryProgram.account.poolRecord.fetch(poolRecord);
+    farm = pda([Buffer.from("farm"), pool.toBuffer()], farmProgram.programId);
+
+    await farmProgram.methods
+      .createFarm()
+      .accounts({ creator: owner, poolRecord, lpMint })
+      .rpc();
+
+    const state = await farmProgram.account.farm.fetch(farm);
+    assert.ok(state.pool.equals(pool));
+    assert.equal(state.rewardCount, 0);
+  });
+
+  it("emits partner rewards funded by their distributors", async () => {
+    const partner = anchor.web3.Keypair.generate();
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(partner.publicKey, anchor.web3.LAMPORTS_PER_SOL)
+    );
+
+    for (const distributor of [owner, partner.publicKey]) {
+      const rewardMint = await createMint(provider.connection, provider.wallet.payer, owner, null, 6);
+      await farmProgram.methods
+        .addReward(distributor, new anchor.BN(7 * DAY))
+        .accounts({ farm, authorizer, manager: owner, rewardMint })
+        .rpc();
+
+      const funding = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        provider.wallet.payer,
+        rewardMint,
+        distributor
+      );
+      await mintTo(provider.connection, provider.wallet.payer, rewardMint, funding.address, owner, 7 * DAY * 10);
+      const index = (await farmProgram.account.farm.fetch(farm)).rewardCount - 1;
+      await farmProgram.methods
+        .notifyRewardAmount(index, new anchor.BN(7 * DAY * 10))
+        .accounts({
+          farm,
+          distributor,
+          distributorTokenAccount: funding.address,
+          rewardVault: pda([Buffer.from("farm-rewards"), farm.toBuffer(), rewardMint.toBuffer()], farmProgram.programId),
+        })
+        .signers(distributor.equals(owner) ? [] : [partner])
+        .rpc();
+    }
+
+    const state = await farmProgram.account.farm.fetch(farm);
+    assert.equal(state.rewardCount, 2);
+    assert.equal(state.rewards.length, MAX_REWARDS);
+    assert.deepEqual(state.rewards.slice(0, 2).map((r) => r.rewardRate.toNumber()), [10, 10]);
+  });
+
+  it("only lets a reward's distributor fund it", async () => {
+    const { rewards } = await farmProgram.account.farm.fetch(farm);
+    const funding = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      rewards[1].mint,
+      owner
+    );
+    try {
+      await farmProgram.methods
+        .notifyRewardAmount(1, new anchor.BN(1))
+        .accounts({ farm, distributor: owner, distributorTokenAccount: funding.address, rewardVault: rewards[1].rewardVault })
+        .rpc();
+      assert.fail("funded by a stranger");
+    } catch (e) {
+      assert.include(String(e), "Unauthorized");
+    }
+  });
+});
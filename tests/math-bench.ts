@@ -0,0 +1,83 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import * as fs from "fs";
+import * as path from "path";
+import { MathBench } from "../target/types/math_bench";
+
+/*
+ * Compute units per math operation, measured on the local validator.
+ * With tests/fixtures/math-cu-baseline.json present each operation must
+ * stay within 5 % of its baseline; run with UPDATE_CU_BASELINE=1 to
+ * (re)write the baseline after an intended change.
+ */
+const BASELINE = path.join(__dirname, "fixtures", "math-cu-baseline.json");
+const TOLERANCE = 1.05;
+
+const OPS = [
+  "mulDown",
+  "divDown",
+  "pow",
+  "powNearOne",
+  "powDown",
+  "powUp",
+  "powDownInteger",
+  "invariant2",
+  "invariant2EqualWeights",
+  "invariant8",
+  "outGivenIn",
+  "outGivenInEqualWeights",
+  "inGivenOut",
+  "bptOutGivenExactTokensIn",
+  "tokenInGivenExactBptOut",
+  "tokensOutGivenExactBptIn",
+  "tokenOutGivenExactBptIn",
+];
+
+describe("math-bench", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MathBench as Program<MathBench>;
+
+  /** Units spent between the two `sol_log_compute_units` markers. */
+  async function measure(op: string): Promise<number> {
+    const sig = await program.methods
+      .bench({ [op]: {} } as any)
+      .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_400_000 })])
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const remaining = (tx?.meta?.logMessages ?? [])
+      .map((l) => /Program consumption: (\d+) units remaining/.exec(l))
+      .filter((m): m is RegExpExecArray => m !== null)
+      .map((m) => Number(m[1]));
+    expect(remaining).to.have.length(2);
+    return remaining[0] - remaining[1];
+  }
+
+  it("stays within the compute-unit baseline", async () => {
+    const units: Record<string, number> = {};
+    for (const op of OPS) {
+      units[op] = await measure(op);
+    }
+    console.table(units);
+
+    if (process.env.UPDATE_CU_BASELINE) {
+      fs.mkdirSync(path.dirname(BASELINE), { recursive: true });
+      fs.writeFileSync(BASELINE, JSON.stringify(units, null, 2) + "\n");
+      return;
+    }
+    if (!fs.existsSync(BASELINE)) {
+      console.log("no compute-unit baseline; run with UPDATE_CU_BASELINE=1 to record one");
+      return;
+    }
+    const baseline: Record<string, number> = JSON.parse(fs.readFileSync(BASELINE, "utf8"));
+    for (const op of OPS) {
+      if (baseline[op] === undefined) continue;
+      expect(units[op], `${op} compute units`).to.be.at.most(Math.ceil(baseline[op] * TOLERANCE));
+    }
+  });
+});
@@ -0,0 +1,118 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  createMint,
+  getAccount,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+
+import { MerkleOrchard } from "../target/types/merkle_orchard";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.MerkleOrchard as Program<MerkleOrchard>;
+
+function pda(seeds: Buffer[]) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+}
+
+function u64(n: number): Buffer {
+  const b = Buffer.alloc(8);
+  b.writeBigUInt64LE(BigInt(n));
+  return b;
+}
+
+/** Mirrors `merkle_orchard::leaf`. */
+function leaf(claimer: anchor.web3.PublicKey, amount: number): Buffer {
+  return createHash("sha256").update(claimer.toBuffer()).update(u64(amount)).digest();
+}
+
+/** Parent of two nodes, hashed in ascending order like `verify_proof`. */
+function parent(a: Buffer, b: Buffer): Buffer {
+  const [x, y] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return createHash("sha256").update(x).update(y).digest();
+}
+
+describe("merkle-orchard", () => {
+  const funder = provider.wallet.publicKey;
+  const other  = anchor.web3.Keypair.generate().publicKey;
+  let tokenMint: anchor.web3.PublicKey;
+  let funderAccount: anchor.web3.PublicKey;
+  let tokenVault: anchor.web3.PublicKey;
+
+  const distribution = (id: number) =>
+    pda([Buffer.from("distribution"), tokenMint.toBuffer(), funder.toBuffer(), u64(id)]);
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, provider.wallet.payer, funder, null, 6);
+    funderAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, tokenMint, funder)
+    ).address;
+    await mintTo(provider.connection, provider.wallet.payer, tokenMint, funderAccount, funder, 10_000);
+    tokenVault = pda([Buffer.from("orchard-tokens"), tokenMint.toBuffer()]);
+  });
+
+  it("posts sequential distributions", async () => {
+    // epoch 0: the wallet earns 300, `other` 700; epoch 1: the wallet alone earns 500
+    const root0 = parent(leaf(funder, 300), leaf(other, 700));
+    const root1 = leaf(funder, 500);
+    for (const [id, root, amount] of [[0, root0, 1_000], [1, root1, 500]] as const) {
+      await program.methods
+        .createDistribution(new anchor.BN(id), [...root], new anchor.BN(amount))
+        .accounts({ funder, tokenMint, funderTokenAccount: funderAccount, distribution: distribution(id) })
+        .rpc();
+    }
+
+    try {
+      await program.methods
+        .createDistribution(new anchor.BN(5), [...root1], new anchor.BN(1))
+        .accounts({ funder, tokenMint, funderTokenAccount: funderAccount, distribution: distribution(5) })
+        .rpc();
+      assert.fail("skipped a distribution id");
+    } catch (e) {
+      assert.include(String(e), "InvalidDistributionId");
+    }
+  });
+
+  it("claims several distributions in one instruction, once", async () => {
+    await program.methods
+      .initializeClaimBitmap(tokenMint, funder, new anchor.BN(0))
+      .accounts({ claimer: funder })
+      .rpc();
+    const bitmap = pda([Buffer.from("claim-bitmap"), tokenMint.toBuffer(), funder.toBuffer(), funder.toBuffer(), u64(0)]);
+
+    const before = (await getAccount(provider.connection, funderAccount)).amount;
+    const accounts = (id: number) => [
+      { pubkey: distribution(id), isWritable: true, isSigner: false },
+      { pubkey: bitmap,           isWritable: true, isSigner: false },
+      { pubkey: tokenVault,       isWritable: true, isSigner: false },
+      { pubkey: funderAccount,    isWritable: true, isSigner: false },
+    ];
+    const claims = [
+      { distributionId: new anchor.BN(0), amount: new anchor.BN(300), proof: [[...leaf(other, 700)]] },
+      { distributionId: new anchor.BN(1), amount: new anchor.BN(500), proof: [] },
+    ];
+    await program.methods
+      .claimDistributions(claims)
+      .accounts({ claimer: funder })
+      .remainingAccounts([...accounts(0), ...accounts(1)])
+      .rpc();
+    const after = (await getAccount(provider.connection, funderAccount)).amount;
+    assert.equal(Number(after - before), 800);
+
+    try {
+      await program.methods
+        .claimDistributions(claims.slice(1))
+        .accounts({ claimer: funder })
+        .remainingAccounts(accounts(1))
+        .rpc();
+      assert.fail("claimed twice");
+    } catch (e) {
+      assert.include(String(e), "AlreadyClaimed");
+    }
+  });
+});
@@ -0,0 +1,18 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { Migration } from "../target/types/migration";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.Migration as Program<Migration>;
+
+describe("migration", () => {
+  it("exposes a single slippage‑bounded migrate instruction", async () => {
+    const ix = program.idl.instructions.find((i) => i.name === "migrate");
+    assert.ok(ix);
+    assert.deepEqual(ix.args.map((a) => a.name), ["bptIn", "minBptOut"]);
+  });
+});
@@ -0,0 +1,197 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import { createMint, getTokenMetadata, TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
+
+import { Vault }        from "../target/types/vault";
+import { WeightedPool } from "../target/types/weighted_pool";
+import { PoolFactory }  from "../target/types/pool_factory";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const vaultProgram    = anchor.workspace.Vault        as Program<Vault>;
+const weightedProgram = anchor.workspace.WeightedPool as Program<WeightedPool>;
+const factoryProgram  = anchor.workspace.PoolFactory  as Program<PoolFactory>;
+
+const E18 = new anchor.BN("1000000000000000000");
+
+function pda(seeds: Buffer[], programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+/**
+ * sha256("canonical-pool" ‖ tokens ‖ weights_le_u128 ‖ swap_fee_le_u64),
+ * mirroring `common::canonical_pool_hash` (tokens must be sorted).
+ */
+function canonicalPoolHash(
+  tokens: anchor.web3.PublicKey[],
+  weights: anchor.BN[],
+  swapFee: anchor.BN
+): Buffer {
+  const h = createHash("sha256").update(Buffer.from("canonical-pool"));
+  tokens.forEach((t) => h.update(t.toBuffer()));
+  weights.forEach((w) => h.update(w.toArrayLike(Buffer, "le", 16)));
+  return h.update(swapFee.toArrayLike(Buffer, "le", 8)).digest();
+}
+
+/** Mirrors `common::derive_pool_id`. */
+function derivePoolId(
+  vaultState: anchor.web3.PublicKey,
+  pool: anchor.web3.PublicKey,
+  specialization: number,
+  nonce: anchor.BN
+): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("pool-id"))
+    .update(vaultState.toBuffer())
+    .update(pool.toBuffer())
+    .update(Buffer.from([specialization]))
+    .update(nonce.toArrayLike(Buffer, "le", 8))
+    .digest();
+}
+
+describe("pool-factory", () => {
+  const owner      = provider.wallet.publicKey;
+  const vaultState = pda([Buffer.from("vault-state"), owner.toBuffer()], vaultProgram.programId);
+  const authorizer = pda([Buffer.from("authorizer"), vaultState.toBuffer()], vaultProgram.programId);
+  const vaultAuthority   = pda([Buffer.from("vault-authority"), vaultState.toBuffer()], vaultProgram.programId);
+  const factoryAuthority = pda([Buffer.from("factory-authority"), vaultState.toBuffer()], factoryProgram.programId);
+
+  const weights = [E18.muln(8).divn(10), E18.muln(2).divn(10)];
+  const otherWeights = [E18.divn(2), E18.divn(2)];
+  const swapFee = E18.divn(1000); // 0.1 %
+  let tokens: anchor.web3.PublicKey[];
+
+  async function createAccounts(poolWeights = weights) {
+    const configHash = canonicalPoolHash(tokens, poolWeights, swapFee);
+    const pool = pda(
+      [Buffer.from("pool-state"), vaultState.toBuffer(), configHash],
+      weightedProgram.programId
+    );
+    const { poolCount, registryTail } = await vaultProgram.account.vaultState.fetch(vaultState);
+    const poolId = derivePoolId(vaultState, pool, 2 /* TwoToken */, poolCount);
+    const poolRegistration = pda(
+      [Buffer.from("pool-registration"), vaultState.toBuffer(), poolId],
+      vaultProgram.programId
+    );
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(registryTail);
+    const registryPage = pda(
+      [Buffer.from("pool-registry"), vaultState.toBuffer(), pageIndex],
+      vaultProgram.programId
+    );
+    const custody = tokens.flatMap((mint) => [
+      { pubkey: mint, isWritable: false, isSigner: false },
+      {
+        pubkey: pda(
+          [Buffer.from("pool-token"), poolRegistration.toBuffer(), mint.toBuffer()],
+          vaultProgram.programId
+        ),
+        isWritable: true,
+        isSigner: false,
+      },
+    ]);
+    return {
+      accounts: {
+        creator: owner,
+        factoryAuthority,
+        vaultState,
+        authorizer,
+        poolRegistration,
+        registryPage,
+        vaultAuthority,
+        pool,
+        weightedPoolProgram: weightedProgram.programId,
+        vaultProgram: vaultProgram.programId,
+      },
+      custody,
+      pool,
+    };
+  }
+
+  before(async () => {
+    try {
+      await vaultProgram.account.vaultState.fetch(vaultState);
+    } catch {
+      await vaultProgram.methods
+        .initialize(owner)
+        .accounts({ vaultState, payer: owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    try {
+      await vaultProgram.account.authorizer.fetch(authorizer);
+    } catch {
+      await vaultProgram.methods
+        .initializeAuthorizer()
+        .accounts({ vaultState, authorizer, owner, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+    // the vault admin lets the factory register pools
+    await vaultProgram.methods
+      .grantRole({ registerPool: {} }, factoryAuthority)
+      .accounts({ authorizer, admin: owner })
+      .rpc();
+
+    const mints = [];
+    for (let i = 0; i < 2; i++) {
+      mints.push(await createMint(provider.connection, provider.wallet.payer, owner, null, 6));
+    }
+    tokens = mints.sort((a, b) => Buffer.compare(a.toBuffer(), b.toBuffer()));
+  });
+
+  it("creates a weighted pool at its canonical address", async () => {
+    const { accounts, custody, pool } = await createAccounts();
+    await factoryProgram.methods
+      .createWeightedPool(weights, swapFee, null)
+      .accounts(accounts)
+      .remainingAccounts(custody)
+      .rpc();
+
+    const state = await weightedProgram.account.pool.fetch(pool);
+    assert.equal(state.swapFee.toString(), swapFee.toString());
+
+    const [record] = await factoryProgram.account.poolRecord.all();
+    assert.ok(record.account.pool.equals(pool));
+    assert.ok(record.account.creator.equals(owner));
+    assert.ok(state.lpMint.equals(record.account.lpMint));
+
+    // the factory authority keeps nothing
+    assert.equal(await provider.connection.getBalance(factoryAuthority), 0);
+  });
+
+  it("issues BPT under Token-2022 with its metadata on the mint", async () => {
+    const { accounts, custody, pool } = await createAccounts(otherWeights);
+    await factoryProgram.methods
+      .createWeightedPool(otherWeights, swapFee, { name: "Symmetric 50A-50B", symbol: "S-50A-50B", uri: "" })
+      .accounts({ ...accounts, token2022Program: TOKEN_2022_PROGRAM_ID })
+      .remainingAccounts(custody)
+      .rpc();
+
+    const { lpMint } = await weightedProgram.account.pool.fetch(pool);
+    const mint = await provider.connection.getAccountInfo(lpMint);
+    assert.ok(mint.owner.equals(TOKEN_2022_PROGRAM_ID));
+    const metadata = await getTokenMetadata(provider.connection, lpMint);
+    assert.equal(metadata.name, "Symmetric 50A-50B");
+    assert.equal(metadata.symbol, "S-50A-50B");
+    // the pool's mint authority can update it; the factory keeps nothing
+    const lpMintAuthority = pda([Buffer.from("lp-mint-authority"), pool.toBuffer()], weightedProgram.programId);
+    assert.ok(metadata.updateAuthority.equals(lpMintAuthority));
+    assert.equal(await provider.connection.getBalance(factoryAuthority), 0);
+  });
+
+  it("refuses a second pool with the same configuration", async () => {
+    const { accounts, custody } = await createAccounts();
+    try {
+      await factoryProgram.methods
+        .createWeightedPool(weights, swapFee, null)
+        .accounts(accounts)
+        .remainingAccounts(custody)
+        .rpc();
+      assert.fail("duplicate pool was created");
+    } catch (e) {
+      assert.notInclude(String(e), "duplicate pool was created");
+    }
+  });
+});
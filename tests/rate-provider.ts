@@ -0,0 +1,22 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { RateProvider } from "../target/types/rate_provider";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.RateProvider as Program<RateProvider>;
+
+describe("rate-provider", () => {
+  it("serves get_rate from a cached, refreshable rate", async () => {
+    const names = program.idl.instructions.map((i) => i.name);
+    assert.includeMembers(names, ["createRateSource", "refreshRate", "pushRate", "setMaxAge", "getRate"]);
+
+    // the standard interface: one read-only rate account
+    const getRate = program.idl.instructions.find((i) => i.name === "getRate");
+    assert.deepEqual(getRate.accounts.map((a) => a.name), ["rateAccount"]);
+    assert.deepEqual(getRate.discriminator, [177, 72, 238, 202, 231, 17, 128, 243]);
+  });
+});
@@ -0,0 +1,48 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { Vault }   from "../target/types/vault";
+import { Relayer } from "../target/types/relayer";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const vaultProgram   = anchor.workspace.Vault   as Program<Vault>;
+const relayerProgram = anchor.workspace.Relayer as Program<Relayer>;
+
+function pda(seeds: Buffer[], programId: anchor.web3.PublicKey) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, programId)[0];
+}
+
+describe("relayer", () => {
+  const user       = provider.wallet.publicKey;
+  const vaultState = pda([Buffer.from("vault-state"), user.toBuffer()], vaultProgram.programId);
+  const relayerAuthority = pda([Buffer.from("relayer-authority")], relayerProgram.programId);
+  const relayerApproval  = pda(
+    [Buffer.from("relayer-approval"), vaultState.toBuffer(), user.toBuffer(), relayerAuthority.toBuffer()],
+    vaultProgram.programId
+  );
+
+  before(async () => {
+    try {
+      await vaultProgram.account.vaultState.fetch(vaultState);
+    } catch {
+      await vaultProgram.methods
+        .initialize(user)
+        .accounts({ vaultState, payer: user, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+    }
+  });
+
+  it("is approved once in the vault as the user's relayer", async () => {
+    await vaultProgram.methods
+      .setRelayerApproval(relayerAuthority, true)
+      .accounts({ vaultState, relayerApproval, user })
+      .rpc();
+
+    const approval = await vaultProgram.account.relayerApproval.fetch(relayerApproval);
+    assert.ok(approval.relayer.equals(relayerAuthority));
+    assert.ok(approval.approved);
+  });
+});
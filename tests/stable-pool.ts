@@ -0,0 +1,192 @@
+import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
+import { Program } from "@coral-xyz/anchor";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+} from "@solana/spl-token";
+
+import { Vault }        from "../target/types/vault";
+import { StablePool }   from "../target/types/stable_pool";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const vaultProgram    = anchor.workspace.Vault        as Program<Vault>;
+const stableProgram   = anchor.workspace.StablePool   as Program<StablePool>;
+
+const TOKEN_PROGRAM_ID = new anchor.web3.PublicKey(
+  "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+);
+
+/**
+ * Derive all the PDAs your Rust code expects:
+ *  - vaultState  (from the Vault program)
+ *  - poolState   (for stable‐pool)
+ *  - lpMintAuth  (the “lp‐mint‐authority” PDA)
+ */
+function derivePdas(owner: anchor.web3.PublicKey) {
+  const [vaultState] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault-state"), owner.toBuffer()],
+    vaultProgram.programId
+  );
+  const [poolState] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("pool-state"), vaultState.toBuffer()],
+    stableProgram.programId
+  );
+  const [lpMintAuth] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("lp-mint-authority"), poolState.toBuffer()],
+    stableProgram.programId
+  );
+  const [authorizer] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("authorizer"), vaultState.toBuffer()],
+    vaultProgram.programId
+  );
+  return { vaultState, poolState, lpMintAuth, authorizer };
+}
+
+/**
+ * PoolId = sha256("pool-id" ‖ vault ‖ pool ‖ specialization ‖ nonce_le_u64),
+ * mirroring `common::derive_pool_id`.
+ */
+function derivePoolId(
+  vaultState: anchor.web3.PublicKey,
+  pool: anchor.web3.PublicKey,
+  specialization: number,
+  nonce: anchor.BN
+): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("pool-id"))
+    .update(vaultState.toBuffer())
+    .update(pool.toBuffer())
+    .update(Buffer.from([specialization]))
+    .update(nonce.toArrayLike(Buffer, "le", 8))
+    .digest();
+}
+
+describe("stable-pool", () => {
+  it("initialises a three‑token stable pool", async () => {
+    const { vaultState, poolState, lpMintAuth, authorizer } =
+      derivePdas(provider.wallet.publicKey);
+
+    // 1. Make sure the Vault is already initialized
+    try {
+      await vaultProgram.account.vaultState.fetch(vaultState);
+    } catch {
+      await vaultProgram.methods
+        .initialize(provider.wallet.publicKey)
+        .accounts({
+          vaultState,
+          payer:         provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    // 1b. The payer needs the RegisterPool role in the vault's Authorizer
+    try {
+      await vaultProgram.account.authorizer.fetch(authorizer);
+    } catch {
+      await vaultProgram.methods
+        .initializeAuthorizer()
+        .accounts({
+          vaultState,
+          authorizer,
+          owner:         provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+    await vaultProgram.methods
+      .grantRole({ registerPool: {} }, provider.wallet.publicKey)
+      .accounts({ authorizer, admin: provider.wallet.publicKey })
+      .rpc();
+
+    // 2. Create the LP mint, with the PDA as its mint authority
+    const lpMintKp = anchor.web3.Keypair.generate();
+    await createMint(
+      provider.connection,
+      provider.wallet.payer,  // fee-payer
+      lpMintAuth,             // mint-authority = PDA
+      null,                   // freeze-authority (none)
+      6,                      // decimals
+      lpMintKp                // new mint keypair
+    );
+
+    // 3. User needs an ATA for the LP tokens
+    const userLpAta = await getOrCreateAssociatedTokenAccount(
+      provider.connection,
+      provider.wallet.payer,
+      lpMintKp.publicKey,
+      provider.wallet.publicKey
+    );
+
+    // 4. Three like‑priced token mints (decimals may differ), and the
+    //    registration PDA the Vault will create
+    const tokenMints: anchor.web3.PublicKey[] = [];
+    for (const decimals of [6, 6, 9]) {
+      tokenMints.push(
+        await createMint(
+          provider.connection,
+          provider.wallet.payer,
+          provider.wallet.publicKey,
+          null,
+          decimals
+        )
+      );
+    }
+    const { poolCount, registryTail } = await vaultProgram.account.vaultState.fetch(vaultState);
+    const poolId = derivePoolId(vaultState, poolState, 0 /* General */, poolCount);
+    const [poolRegistration] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool-registration"), vaultState.toBuffer(), poolId],
+      vaultProgram.programId
+    );
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(registryTail);
+    const [registryPage] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool-registry"), vaultState.toBuffer(), pageIndex],
+      vaultProgram.programId
+    );
+
+    // 5. Call our initialize_pool instruction
+    await stableProgram.methods
+    .initializePool(
+      new anchor.BN(200),         // amplification
+      new anchor.BN(0),           // swap_fee
+      false,                      // composable
+      []                          // rate_providers (none)
+    )
+    .accounts({
+      vaultState:    vaultState,                      // ← rename from “vault”
+      authorizer:    authorizer,
+      poolRegistration,
+      registryPage,
+      vaultProgram:  vaultProgram.programId,          // ← must pass the CPI‐target program
+      pool:          poolState,
+      lpMint:        lpMintKp.publicKey,
+      lpMintAuthority: lpMintAuth,
+      vaultAuthority: null,                           // composable pools only
+      poolBpt:       null,
+      payer:         provider.wallet.publicKey,
+      tokenProgram:  TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    })
+    .remainingAccounts(
+      tokenMints.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+    )
+    .rpc();
+
+
+    const pool = await stableProgram.account.stablePool.fetch(poolState);
+    if (pool.tokens.length !== 3 || pool.amp.toNumber() !== 200_000) {
+      throw new Error("unexpected stable pool state");
+    }
+
+    console.log("✅ stable-pool initialised");
+    console.log("   vault_state  :", vaultState.toBase58());
+    console.log("   pool_state   :", poolState.toBase58());
+    console.log("   lp_mint      :", lpMintKp.publicKey.toBase58());
+    console.log("   lp_mint_auth :", lpMintAuth.toBase58());
+    console.log("   user_lp_ata  :", userLpAta.address.toBase58());
+  });
+});
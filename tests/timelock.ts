@@ -0,0 +1,89 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { Timelock } from "../target/types/timelock";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.Timelock as Program<Timelock>;
+
+const HOUR = 60 * 60;
+const DAY = 24 * HOUR;
+
+function pda(seeds: Buffer[]) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+}
+
+function actionPda(timelock: anchor.web3.PublicKey, id: number) {
+  const index = Buffer.alloc(8);
+  index.writeBigUInt64LE(BigInt(id));
+  return pda([Buffer.from("timelock-action"), timelock.toBuffer(), index]);
+}
+
+describe("timelock", () => {
+  const proposer = provider.wallet.publicKey;
+  const timelock = pda([Buffer.from("timelock"), proposer.toBuffer()]);
+  const timelockAuthority = pda([Buffer.from("timelock-authority"), timelock.toBuffer()]);
+
+  before(async () => {
+    await program.methods.initializeTimelock(new anchor.BN(DAY)).accounts({ proposer }).rpc();
+  });
+
+  it("publishes queued actions and holds them for the delay", async () => {
+    // the timelock's own delay is itself a protected parameter
+    const ix = await program.methods
+      .setDelay(new anchor.BN(2 * DAY))
+      .accounts({ timelock, timelockAuthority })
+      .instruction();
+    const eta = new anchor.BN(Math.floor(Date.now() / 1000) + DAY + HOUR);
+    const action = actionPda(timelock, 0);
+
+    await program.methods
+      .queueAction(
+        ix.programId,
+        ix.keys.map((k) => ({ pubkey: k.pubkey, isSigner: k.isSigner, isWritable: k.isWritable })),
+        ix.data,
+        eta
+      )
+      .accounts({ timelock, proposer })
+      .rpc();
+
+    const queued = await program.account.queuedAction.fetch(action);
+    assert.ok(queued.programId.equals(program.programId));
+    assert.ok(queued.eta.eq(eta));
+    assert.deepEqual(queued.status, { queued: {} });
+
+    try {
+      await program.methods
+        .executeAction()
+        .accounts({ timelock, action })
+        .remainingAccounts([
+          { pubkey: program.programId, isSigner: false, isWritable: false },
+          { pubkey: timelock, isSigner: false, isWritable: true },
+          { pubkey: timelockAuthority, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+      assert.fail("executed before the delay");
+    } catch (e) {
+      assert.include(String(e), "ActionNotReady");
+    }
+
+    await program.methods.cancelAction().accounts({ timelock, action, proposer }).rpc();
+    assert.deepEqual((await program.account.queuedAction.fetch(action)).status, { cancelled: {} });
+    assert.equal((await program.account.timelock.fetch(timelock)).delay.toNumber(), DAY);
+  });
+
+  it("rejects actions that skip the delay", async () => {
+    try {
+      await program.methods
+        .queueAction(program.programId, [], Buffer.alloc(0), new anchor.BN(Math.floor(Date.now() / 1000)))
+        .accounts({ timelock, proposer })
+        .rpc();
+      assert.fail("queued inside the delay");
+    } catch (e) {
+      assert.include(String(e), "EtaTooSoon");
+    }
+  });
+});
@@ -0,0 +1,25 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+
+import { Twamm } from "../target/types/twamm";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.Twamm as Program<Twamm>;
+
+describe("twamm", () => {
+  it("streams long-term orders through a crank settled pro rata", async () => {
+    const place = program.idl.instructions.find((i) => i.name === "placeOrder");
+    assert.deepEqual(place.args.map((a) => a.name), ["id", "side", "amount", "end"]);
+
+    const execute = program.idl.instructions.find((i) => i.name === "execute");
+    assert.deepEqual(execute.args, []);
+    assert.ok(execute.accounts.find((a) => a.name === "cranker"));
+    assert.ok(execute.accounts.find((a) => a.name === "expiry"));
+
+    assert.ok(program.idl.instructions.find((i) => i.name === "withdrawProceeds"));
+    assert.ok(program.idl.instructions.find((i) => i.name === "cancelOrder"));
+  });
+});
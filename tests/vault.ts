@@ -31,4 +31,63 @@ describe("vault", () => {
 
     console.log("Vault initialized at", vaultStatePda.toBase58());
   });
+
+  it("initializes the authorizer and grants a role", async () => {
+    const [vaultStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault-state"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+    const [authorizerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("authorizer"), vaultStatePda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initializeAuthorizer()
+      .accounts({
+        vaultState:    vaultStatePda,
+        authorizer:    authorizerPda,
+        owner:         provider.wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .grantRole({ registerPool: {} }, provider.wallet.publicKey)
+      .accounts({ authorizer: authorizerPda, admin: provider.wallet.publicKey })
+      .rpc();
+
+    const authorizer = await program.account.authorizer.fetch(authorizerPda);
+    console.log("Authorizer grants:", authorizer.grants.length);
+  });
+
+  it("transfers ownership in two steps", async () => {
+    const [vaultStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault-state"), provider.wallet.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const [authorizerPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("authorizer"), vaultStatePda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .grantRole({ transferOwnership: {} }, provider.wallet.publicKey)
+      .accounts({ authorizer: authorizerPda, admin: provider.wallet.publicKey })
+      .rpc();
+
+    // hand the vault back to the same key: exercises both steps without
+    // losing control of it for the tests that follow
+    await program.methods
+      .transferOwnership(provider.wallet.publicKey)
+      .accounts({ vaultState: vaultStatePda, authorizer: authorizerPda, authority: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .acceptOwnership()
+      .accounts({ vaultState: vaultStatePda, pendingOwner: provider.wallet.publicKey })
+      .rpc();
+
+    const state = await program.account.vaultState.fetch(vaultStatePda);
+    console.log("Owner:", state.owner.toBase58(), "pending:", state.pendingOwner.toBase58());
+  });
 });
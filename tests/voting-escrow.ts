@@ -0,0 +1,71 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
+import {
+  createMint,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+} from "@solana/spl-token";
+
+import { VotingEscrow } from "../target/types/voting_escrow";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+
+const program = anchor.workspace.VotingEscrow as Program<VotingEscrow>;
+
+const WEEK = 7 * 24 * 60 * 60;
+const MAX_LOCK_TIME = 4 * 365 * 24 * 60 * 60;
+
+function pda(seeds: Buffer[]) {
+  return anchor.web3.PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+}
+
+describe("voting-escrow", () => {
+  const owner = provider.wallet.publicKey;
+  let tokenMint: anchor.web3.PublicKey;
+  let escrow: anchor.web3.PublicKey;
+  let ownerAccount: anchor.web3.PublicKey;
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, provider.wallet.payer, owner, null, 6);
+    escrow = pda([Buffer.from("voting-escrow"), tokenMint.toBuffer()]);
+    ownerAccount = (
+      await getOrCreateAssociatedTokenAccount(provider.connection, provider.wallet.payer, tokenMint, owner)
+    ).address;
+    await mintTo(provider.connection, provider.wallet.payer, tokenMint, ownerAccount, owner, 1_000_000_000);
+
+    await program.methods
+      .initializeEscrow()
+      .accounts({ payer: owner, tokenMint })
+      .rpc();
+  });
+
+  it("locks tokens for decaying voting power", async () => {
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .createLock(new anchor.BN(1_000_000_000), new anchor.BN(now + MAX_LOCK_TIME - WEEK))
+      .accounts({ escrow, owner, ownerTokenAccount: ownerAccount })
+      .rpc();
+
+    const lock = await program.account.lock.fetch(pda([Buffer.from("lock"), escrow.toBuffer(), owner.toBuffer()]));
+    assert.equal(lock.amount.toNumber(), 1_000_000_000);
+    assert.equal(lock.end.toNumber() % WEEK, 0);
+
+    const state = await program.account.escrow.fetch(escrow);
+    assert.equal(state.totalLocked.toNumber(), 1_000_000_000);
+    assert.ok(state.slope.gtn(0));
+  });
+
+  it("refuses to move an unlock time earlier", async () => {
+    try {
+      await program.methods
+        .increaseUnlockTime(new anchor.BN(Math.floor(Date.now() / 1000)))
+        .accounts({ escrow, owner, ownerTokenAccount: ownerAccount })
+        .rpc();
+      assert.fail("unlock time moved earlier");
+    } catch (e) {
+      assert.include(String(e), "InvalidUnlockTime");
+    }
+  });
+});
@@ -1,5 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
+import { createHash } from "crypto";
 import { Program } from "@coral-xyz/anchor";
+import { assert } from "chai";
 import {
   createMint,
   getOrCreateAssociatedTokenAccount,
@@ -37,12 +39,35 @@ function derivePdas(owner: anchor.web3.PublicKey) {
     [Buffer.from("lp-mint-authority"), poolState.toBuffer()],
     weightedProgram.programId
   );
-  return { vaultState, poolState, lpMintAuth };
+  const [authorizer] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("authorizer"), vaultState.toBuffer()],
+    vaultProgram.programId
+  );
+  return { vaultState, poolState, lpMintAuth, authorizer };
+}
+
+/**
+ * PoolId = sha256("pool-id" ‖ vault ‖ pool ‖ specialization ‖ nonce_le_u64),
+ * mirroring `common::derive_pool_id`.
+ */
+function derivePoolId(
+  vaultState: anchor.web3.PublicKey,
+  pool: anchor.web3.PublicKey,
+  specialization: number,
+  nonce: anchor.BN
+): Buffer {
+  return createHash("sha256")
+    .update(Buffer.from("pool-id"))
+    .update(vaultState.toBuffer())
+    .update(pool.toBuffer())
+    .update(Buffer.from([specialization]))
+    .update(nonce.toArrayLike(Buffer, "le", 8))
+    .digest();
 }
 
 describe("weighted-pool", () => {
   it("initialises the weighted pool", async () => {
-    const { vaultState, poolState, lpMintAuth } =
+    const { vaultState, poolState, lpMintAuth, authorizer } =
       derivePdas(provider.wallet.publicKey);
 
     // 1. Make sure the Vault is already initialized
@@ -59,6 +84,25 @@ describe("weighted-pool", () => {
         .rpc();
     }
 
+    // 1b. The payer needs the RegisterPool role in the vault's Authorizer
+    try {
+      await vaultProgram.account.authorizer.fetch(authorizer);
+    } catch {
+      await vaultProgram.methods
+        .initializeAuthorizer()
+        .accounts({
+          vaultState,
+          authorizer,
+          owner:         provider.wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+    await vaultProgram.methods
+      .grantRole({ registerPool: {} }, provider.wallet.publicKey)
+      .accounts({ authorizer, admin: provider.wallet.publicKey })
+      .rpc();
+
     // 2. Create the LP mint, with the PDA as its mint authority
     const lpMintKp = anchor.web3.Keypair.generate();
     await createMint(
@@ -78,14 +122,38 @@ describe("weighted-pool", () => {
       provider.wallet.publicKey
     );
 
-    // 4. Call our initialize_pool instruction
+    // 4. One token mint per weight, and the registration PDA the Vault will create
+    const tokenMint = await createMint(
+      provider.connection,
+      provider.wallet.payer,
+      provider.wallet.publicKey,
+      null,
+      6
+    );
+    const { poolCount, registryTail } = await vaultProgram.account.vaultState.fetch(vaultState);
+    const poolId = derivePoolId(vaultState, poolState, 1 /* MinimalSwapInfo */, poolCount);
+    const [poolRegistration] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool-registration"), vaultState.toBuffer(), poolId],
+      vaultProgram.programId
+    );
+    const pageIndex = Buffer.alloc(4);
+    pageIndex.writeUInt32LE(registryTail);
+    const [registryPage] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pool-registry"), vaultState.toBuffer(), pageIndex],
+      vaultProgram.programId
+    );
+
+    // 5. Call our initialize_pool instruction
     await weightedProgram.methods
     .initializePool(
       [new anchor.BN(1_000_000)], // weights
-      new anchor.BN(0)            // swap_fee
+      new anchor.BN("3000000000000000") // swap_fee (0.3 %)
     )
     .accounts({
       vaultState:    vaultState,                      // ← rename from “vault”
+      authorizer:    authorizer,
+      poolRegistration,
+      registryPage,
       vaultProgram:  vaultProgram.programId,          // ← must pass the CPI‐target program
       pool:          poolState,
       lpMint:        lpMintKp.publicKey,
@@ -96,7 +164,7 @@ describe("weighted-pool", () => {
     })
     .remainingAccounts([
       {
-        pubkey:     provider.wallet.publicKey,
+        pubkey:     tokenMint,
         isWritable: false,
         isSigner:   false,
       },
@@ -111,4 +179,34 @@ describe("weighted-pool", () => {
     console.log("   lp_mint_auth :", lpMintAuth.toBase58());
     console.log("   user_lp_ata  :", userLpAta.address.toBase58());
   });
+
+  it("only gives two-token pools an oracle", async () => {
+    const { poolState, authorizer } = derivePdas(provider.wallet.publicKey);
+    await vaultProgram.methods
+      .grantRole({ setSwapFee: {} }, provider.wallet.publicKey)
+      .accounts({ authorizer, admin: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      await weightedProgram.methods
+        .enableOracle()
+        .accounts({
+          pool:      poolState,
+          authorizer,
+          authority: provider.wallet.publicKey,
+          payer:     provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("enabled the oracle of a one-token pool");
+    } catch (e) {
+      assert.include(String(e), "OracleUnsupported");
+    }
+  });
+
+  it("names its LP token through the token metadata program", async () => {
+    const create = weightedProgram.idl.instructions.find((i) => i.name === "createLpMetadata");
+    assert.deepEqual(create.args.map((a) => a.name), ["name", "symbol", "uri"]);
+    assert.include(create.accounts.map((a) => a.name), "tokenMetadataProgram");
+    assert.ok(weightedProgram.idl.instructions.find((i) => i.name === "setLpMetadata"));
+  });
 });